@@ -92,4 +92,13 @@ contract GatewayMessengerFacet is GatewayActorModifiers {
     function propagateAll() external payable {
         LibGateway.propagateAllPostboxMessages();
     }
+
+    /**
+     * @dev Propagates the given cross-net messages from the postbox.
+     * Messages that are no longer in the postbox, e.g. because someone else propagated them first, are skipped.
+     * @param msgCids - the cids of the cross-net messages
+     */
+    function propagate(bytes32[] calldata msgCids) external payable {
+        LibGateway.propagatePostboxMessages(msgCids);
+    }
 }
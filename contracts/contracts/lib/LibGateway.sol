@@ -691,6 +691,28 @@ library LibGateway {
         }
     }
 
+    /**
+     * @dev Propagates the populated cross-net messages for the given `msgCids`,
+     * skipping the ones that are not in the postbox.
+     * @param msgCids - the cids of the cross-net messages
+     */
+    function propagatePostboxMessages(bytes32[] calldata msgCids) internal {
+        GatewayActorStorage storage s = LibGatewayActorStorage.appStorage();
+
+        uint256 length = msgCids.length;
+
+        for (uint256 i = 0; i < length; ) {
+            bytes32 msgCid = msgCids[i];
+            if (s.postboxKeys.contains(msgCid)) {
+                LibGateway.propagatePostboxMessage(msgCid);
+            }
+
+            unchecked {
+                ++i;
+            }
+        }
+    }
+
      /**
      * @dev Propagates the populated cross-net message for the given `msgCid`.
      * @param msgCid - the cid of the cross-net message
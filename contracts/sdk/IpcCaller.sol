@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.23;
+
+import {IpcEnvelope, ResultMsg, CallMsg, IpcMsgKind, OutcomeType} from "../contracts/structs/CrossNet.sol";
+import {IPCAddress} from "../contracts/structs/Subnet.sol";
+import {EMPTY_BYTES} from "../contracts/constants/Constants.sol";
+import {IGateway} from "../contracts/interfaces/IGateway.sol";
+import {CrossMsgHelper} from "../contracts/lib/CrossMsgHelper.sol";
+import {Ownable} from "@openzeppelin/contracts/access/Ownable.sol";
+import {Address} from "@openzeppelin/contracts/utils/Address.sol";
+import {IIpcHandler} from "./interfaces/IIpcHandler.sol";
+
+/// @title Sends cross-net calls on behalf of its owner.
+/// @notice The gateway only accepts general-purpose cross-net messages from contracts, so an account
+/// deploys this contract to call contracts in other subnets. Every call and its receipt are announced
+/// through events keyed by the tracing id of the call, which is also the id carried by the receipt.
+contract IpcCaller is IIpcHandler, Ownable {
+    using CrossMsgHelper for IpcEnvelope;
+
+    // The address of the gateway in the network.
+    address public immutable gatewayAddr;
+
+    /// @dev emitted when a call is committed by the gateway for propagation.
+    event CallSent(bytes32 indexed id, IpcEnvelope envelope);
+    /// @dev emitted when the receipt of a call arrives back.
+    event ReceiptReceived(bytes32 indexed id, OutcomeType outcome, bytes ret);
+
+    constructor(address gatewayAddr_) Ownable(msg.sender) {
+        gatewayAddr = gatewayAddr_;
+    }
+
+    /// @notice Calls a contract in another subnet, forwarding the value of the transaction.
+    /// @dev The target receives the envelope through `IIpcHandler.handleIpcMessage` rather than the raw
+    /// calldata, so it must implement `IIpcHandler`; otherwise the call fails and an error receipt comes back.
+    function sendCall(
+        IPCAddress calldata to,
+        CallMsg calldata callMsg
+    ) external payable onlyOwner returns (IpcEnvelope memory envelope) {
+        envelope = IGateway(gatewayAddr).sendContractXnetMessage{value: msg.value}(
+            IpcEnvelope({
+                kind: IpcMsgKind.Call,
+                from: to, // replaced by sendContractXnetMessage.
+                to: to,
+                localNonce: 0,
+                originalNonce: 0,
+                value: msg.value,
+                message: abi.encode(callMsg)
+            })
+        );
+        emit CallSent(envelope.toTracingId(), envelope);
+    }
+
+    /// @notice Entrypoint for receipts delivered by the gateway.
+    /// The value of failed calls is returned along with the receipt and stays in the contract.
+    function handleIpcMessage(IpcEnvelope calldata envelope) external payable returns (bytes memory) {
+        if (msg.sender != gatewayAddr) {
+            revert IIpcHandler.CallerIsNotGateway();
+        }
+        if (envelope.kind != IpcMsgKind.Result) {
+            revert IIpcHandler.UnsupportedMsgKind();
+        }
+        ResultMsg memory result = abi.decode(envelope.message, (ResultMsg));
+        emit ReceiptReceived(result.id, result.outcome, result.ret);
+        return EMPTY_BYTES;
+    }
+
+    /// @notice Withdraws the value returned by failed calls.
+    function withdraw(address payable to) external onlyOwner {
+        Address.sendValue(to, address(this).balance);
+    }
+}
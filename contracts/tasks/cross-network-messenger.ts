@@ -44,3 +44,20 @@ task('cross-network-send')
         const contract = contracts.contracts.CrossMessengerCaller
         await contract.invokeSendMessage(subnetId, args.recipient, amount, { value: Number(amount) })
     })
+
+// deploy the contract used by `ipc-cli crossmsg call` to send cross network calls
+// sample command: pnpm exec hardhat cross-network-caller-deploy --network calibrationnet <GATEWAY ADDRESS>
+task('cross-network-caller-deploy')
+    .addPositionalParam('gatewayAddr', 'the address of the gateway contract')
+    .setDescription('Deploy the contract that sends cross network calls on behalf of its owner')
+    .setAction(async (args: TaskArguments, hre: HardhatRuntimeEnvironment) => {
+        await hre.run('compile')
+
+        const [deployer] = await hre.getUnnamedAccounts()
+        console.log(`Deploying cross network caller contract with account: ${deployer}`)
+
+        await Deployments.deploy(hre, deployer, {
+            name: 'IpcCaller',
+            args: [args.gatewayAddr],
+        })
+    })
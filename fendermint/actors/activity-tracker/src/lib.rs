@@ -3,7 +3,7 @@
 
 use crate::state::ConsensusData;
 pub use crate::state::State;
-use crate::types::FullActivityRollup;
+use crate::types::{FullActivityRollup, RecordChallengeResultsParams, StorageStats};
 use fil_actors_runtime::builtin::singletons::SYSTEM_ACTOR_ADDR;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{actor_dispatch, ActorError, EAM_ACTOR_ID};
@@ -29,6 +29,8 @@ pub enum Method {
     RecordBlockCommitted = frc42_dispatch::method_hash!("RecordBlockCommitted"),
     CommitActivity = frc42_dispatch::method_hash!("CommitActivity"),
     PendingActivity = frc42_dispatch::method_hash!("PendingActivity"),
+    RecordChallengeResults = frc42_dispatch::method_hash!("RecordChallengeResults"),
+    GetStorageStats = frc42_dispatch::method_hash!("GetStorageStats"),
 }
 
 trait ActivityTracker {
@@ -43,6 +45,28 @@ trait ActivityTracker {
 
     /// Queries the activity that has been accumulated since the last commit, and is pending a flush.
     fn pending_activity(rt: &impl Runtime) -> Result<FullActivityRollup, ActorError>;
+
+    /// Hook for the consensus layer to report which validators answered a blob storage challenge,
+    /// and which ones missed it.
+    fn record_challenge_results(
+        rt: &impl Runtime,
+        params: RecordChallengeResultsParams,
+    ) -> Result<(), ActorError>;
+
+    /// Queries the cumulative storage challenge stats of a validator.
+    fn get_storage_stats(rt: &impl Runtime, validator: Address)
+        -> Result<StorageStats, ActorError>;
+}
+
+/// Rejects non-f410 addresses.
+fn validate_validator_address(validator: &Address) -> Result<(), ActorError> {
+    if !matches!(validator.payload(), Payload::Delegated(d) if d.namespace() == EAM_ACTOR_ID && d.subaddress().len() == 20)
+    {
+        return Err(
+            actor_error!(illegal_argument; "validator address must be a valid f410 address"),
+        );
+    }
+    Ok(())
 }
 
 impl ActivityTrackerActor {
@@ -57,13 +81,7 @@ impl ActivityTracker for ActivityTrackerActor {
     fn record_block_committed(rt: &impl Runtime, validator: Address) -> Result<(), ActorError> {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
 
-        // Reject non-f410 addresses.
-        if !matches!(validator.payload(), Payload::Delegated(d) if d.namespace() == EAM_ACTOR_ID && d.subaddress().len() == 20)
-        {
-            return Err(
-                actor_error!(illegal_argument; "validator address must be a valid f410 address"),
-            );
-        }
+        validate_validator_address(&validator)?;
 
         rt.transaction(|st: &mut State, rt| {
             let mut consensus =
@@ -99,6 +117,47 @@ impl ActivityTracker for ActivityTrackerActor {
 
         rt.state::<State>()?.pending_activity_rollup(rt)
     }
+
+    fn record_challenge_results(
+        rt: &impl Runtime,
+        params: RecordChallengeResultsParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        for validator in params.answered.iter().chain(params.missed.iter()) {
+            validate_validator_address(validator)?;
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut storage = st.load_storage(rt.store())?;
+
+            for validator in &params.answered {
+                let mut v = storage.get(validator)?.cloned().unwrap_or_default();
+                v.challenges_answered += 1;
+                storage.set(validator, v)?;
+            }
+            for validator in &params.missed {
+                let mut v = storage.get(validator)?.cloned().unwrap_or_default();
+                v.challenges_missed += 1;
+                storage.set(validator, v)?;
+            }
+
+            st.storage = Some(storage.flush()?);
+
+            Ok(())
+        })
+    }
+
+    fn get_storage_stats(
+        rt: &impl Runtime,
+        validator: Address,
+    ) -> Result<StorageStats, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let st = rt.state::<State>()?;
+        let storage = st.load_storage(rt.store())?;
+        Ok(storage.get(&validator)?.cloned().unwrap_or_default())
+    }
 }
 
 impl ActorCode for ActivityTrackerActor {
@@ -113,5 +172,7 @@ impl ActorCode for ActivityTrackerActor {
         RecordBlockCommitted => record_block_committed,
         CommitActivity => commit_activity,
         PendingActivity => pending_activity,
+        RecordChallengeResults => record_challenge_results,
+        GetStorageStats => get_storage_stats,
     }
 }
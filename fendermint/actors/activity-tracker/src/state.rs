@@ -1,7 +1,7 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use crate::types::{FullActivityRollup, ValidatorStats};
+use crate::types::{FullActivityRollup, StorageStats, ValidatorStats};
 use cid::Cid;
 use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::{ActorError, Map2, DEFAULT_HAMT_CONFIG};
@@ -14,19 +14,35 @@ use serde::{Deserialize, Serialize};
 pub struct State {
     pub tracking_since: ChainEpoch,
     pub consensus: Cid, // ConsensusData
+    /// Missing from state created before storage stats were tracked.
+    #[serde(default)]
+    pub storage: Option<Cid>, // StorageData
 }
 
 pub type ConsensusData<BS> = Map2<BS, Address, ValidatorStats>;
 
+/// Storage challenge stats are cumulative; they are not part of the activity rollup,
+/// and are not reset when activity is committed.
+pub type StorageData<BS> = Map2<BS, Address, StorageStats>;
+
 impl State {
     pub fn new<BS: Blockstore>(store: &BS) -> Result<State, ActorError> {
         let state = State {
             tracking_since: 0,
             consensus: ConsensusData::flush_empty(store, DEFAULT_HAMT_CONFIG)?,
+            storage: Some(StorageData::flush_empty(store, DEFAULT_HAMT_CONFIG)?),
         };
         Ok(state)
     }
 
+    /// Loads the storage stats, which are empty if they were never tracked.
+    pub fn load_storage<BS: Blockstore>(&self, store: BS) -> Result<StorageData<BS>, ActorError> {
+        match &self.storage {
+            Some(cid) => StorageData::load(store, cid, DEFAULT_HAMT_CONFIG, "storage"),
+            None => Ok(StorageData::empty(store, DEFAULT_HAMT_CONFIG, "storage")),
+        }
+    }
+
     /// Returns the pending activity rollup.
     pub fn pending_activity_rollup(
         &self,
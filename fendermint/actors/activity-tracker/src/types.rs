@@ -26,3 +26,19 @@ pub struct FullActivityRollup {
 pub struct ValidatorStats {
     pub blocks_committed: u64,
 }
+
+/// Storage reliability of a validator, measured by answers to blob storage challenges.
+#[derive(Deserialize_tuple, Serialize_tuple, Debug, Clone, PartialEq, Eq, Default)]
+pub struct StorageStats {
+    pub challenges_answered: u64,
+    pub challenges_missed: u64,
+}
+
+/// Params for recording the outcome of a blob storage challenge.
+#[derive(Deserialize_tuple, Serialize_tuple, Debug, Clone, PartialEq, Eq, Default)]
+pub struct RecordChallengeResultsParams {
+    /// Validators that answered the challenge with a valid proof.
+    pub answered: Vec<Address>,
+    /// Validators that did not answer the challenge before its deadline.
+    pub missed: Vec<Address>,
+}
@@ -3,11 +3,14 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::shared::{
-    CloseReadRequestParams, GetOpenReadRequestsParams, GetReadRequestStatusParams, Method,
-    OpenReadRequestParams, OpenReadRequestTuple, ReadRequestStatus, SetReadRequestPendingParams,
-    State, BLOB_READER_ACTOR_NAME,
+    ChargeReadRequestParams, CloseReadRequestOutcome, CloseReadRequestParams,
+    GetOpenReadRequestsParams, GetReadRequestStatusParams, Method, OpenReadRequestParams,
+    OpenReadRequestTuple, ReadRequestStatus, SetReadRequestPendingParams, State,
+    BLOB_READER_ACTOR_NAME,
 };
+use fendermint_actor_blobs_shared::params::ChargeReadParams;
 use fendermint_actor_blobs_shared::state::Hash;
+use fendermint_actor_blobs_shared::{charge_read, get_read_charge};
 use fendermint_actor_machine::events::emit_evm_event;
 use fil_actors_runtime::{
     actor_dispatch, actor_error,
@@ -15,7 +18,9 @@ use fil_actors_runtime::{
     ActorError, FIRST_EXPORTED_METHOD_NUMBER, SYSTEM_ACTOR_ADDR,
 };
 use fvm_ipld_encoding::ipld_block::IpldBlock;
-use fvm_shared::MethodNum;
+use fvm_shared::{MethodNum, METHOD_SEND};
+use log::warn;
+use num_traits::Zero;
 use recall_sol_facade::blob_reader::{
     read_request_closed, read_request_opened, read_request_pending,
 };
@@ -38,14 +43,33 @@ impl ReadReqActor {
     ) -> Result<Hash, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
 
+        // Any tokens sent with the request are held until it completes or fails
+        let caller = rt.message().caller();
+        let deposit = rt.message().value_received();
+
+        // Make sure the whole read can be paid for upfront
+        get_read_charge(
+            rt,
+            ChargeReadParams {
+                hash: params.hash,
+                owner: params.owner,
+                reader: caller,
+                size: params.len,
+            },
+        )?;
+
         let id = rt.transaction(|st: &mut State, _rt| {
             st.open_read_request(
                 rt.store(),
                 params.hash,
                 params.offset,
                 params.len,
+                params.chunk_len,
                 params.callback_addr,
                 params.callback_method,
+                caller,
+                deposit,
+                params.owner,
             )
         })?;
 
@@ -54,8 +78,8 @@ impl ReadReqActor {
             read_request_opened(
                 &id.0,
                 &params.hash.0,
-                params.offset as u64,
-                params.len as u64,
+                params.offset,
+                params.len,
                 params.callback_addr,
                 params.callback_method,
             ),
@@ -84,13 +108,63 @@ impl ReadReqActor {
         Ok(status)
     }
 
+    /// Charges for the pending chunk of a read request before it is delivered.
+    ///
+    /// The chunk is charged in credit for the length of the data actually read, which can be
+    /// shorter than the chunk if the range goes past the end of the blob.
+    /// Returns whether the chunk is paid for and can be delivered. If the payer runs out of
+    /// credit, the request fails without retries and nothing is delivered.
+    /// Retries of a chunk whose callback failed are not charged again.
+    fn charge_read_request(
+        rt: &impl Runtime,
+        params: ChargeReadRequestParams,
+    ) -> Result<bool, ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        let request =
+            rt.state::<State>()?
+                .get_pending_read_request(rt.store(), params.id, params.chunk)?;
+        let Some(reader) = request.caller.filter(|_| !request.charged) else {
+            return Ok(true);
+        };
+        let (_, len) = request.chunk_range(params.chunk);
+        let charge = charge_read(
+            rt,
+            ChargeReadParams {
+                hash: request.blob_hash,
+                owner: request.owner,
+                reader,
+                size: len.min(params.len) as u64,
+            },
+        );
+        if let Err(e) = &charge {
+            warn!("failed to charge read request {}: {}", params.id, e);
+            let outcome = rt.transaction(|st: &mut State, _| {
+                st.fail_read_request(rt.store(), params.id, params.chunk)
+            })?;
+            settle_read_request(rt, params.id, outcome)?;
+            return Ok(false);
+        }
+        rt.transaction(|st: &mut State, _| {
+            st.set_read_request_charged(rt.store(), params.id, params.chunk)
+        })?;
+        Ok(true)
+    }
+
+    /// Closes the pending chunk of a read request after its callback was called.
+    ///
+    /// The chunk must have been charged with [`ReadReqActor::charge_read_request`] before it
+    /// was delivered.
     fn close_read_request(
         rt: &impl Runtime,
         params: CloseReadRequestParams,
     ) -> Result<(), ActorError> {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
-        rt.transaction(|st: &mut State, _| st.close_read_request(rt.store(), params.0))?;
-        emit_evm_event(rt, read_request_closed(&params.0 .0))
+
+        let outcome = rt.transaction(|st: &mut State, _| {
+            st.close_read_request(rt.store(), params.id, params.chunk, params.delivered)
+        })?;
+        settle_read_request(rt, params.id, outcome)
     }
 
     fn set_read_request_pending(
@@ -118,6 +192,27 @@ impl ReadReqActor {
     }
 }
 
+/// Settles the deposit of a read request once it completes or fails.
+///
+/// Reads are paid for in credit as the chunks are delivered, so the deposit is returned
+/// to the caller either way.
+fn settle_read_request(
+    rt: &impl Runtime,
+    id: Hash,
+    outcome: CloseReadRequestOutcome,
+) -> Result<(), ActorError> {
+    let request = match outcome {
+        CloseReadRequestOutcome::Next | CloseReadRequestOutcome::Retry => return Ok(()),
+        CloseReadRequestOutcome::Completed(request) | CloseReadRequestOutcome::Failed(request) => {
+            request
+        }
+    };
+    if let Some(caller) = request.caller.filter(|_| !request.deposit.is_zero()) {
+        rt.send_simple(&caller, METHOD_SEND, None, request.deposit)?;
+    }
+    emit_evm_event(rt, read_request_closed(&id.0))
+}
+
 impl ActorCode for ReadReqActor {
     type Methods = Method;
 
@@ -130,6 +225,7 @@ impl ActorCode for ReadReqActor {
         OpenReadRequest => open_read_request,
         GetOpenReadRequests => get_open_read_requests,
         GetReadRequestStatus => get_read_request_status,
+        ChargeReadRequest => charge_read_request,
         CloseReadRequest => close_read_request,
         SetReadRequestPending => set_read_request_pending,
         _ => fallback,
@@ -140,13 +236,17 @@ impl ActorCode for ReadReqActor {
 mod tests {
     use super::*;
 
+    use crate::shared::ReadRequest;
+    use fendermint_actor_blobs_shared::params::ReadCharge;
+    use fendermint_actor_blobs_shared::state::Credit;
+    use fendermint_actor_blobs_shared::{Method as BlobsMethod, BLOBS_ACTOR_ADDR};
     use fendermint_actor_machine::events::to_actor_event;
     use fil_actors_evm_shared::address::EthAddress;
     use fil_actors_runtime::test_utils::{
         expect_empty, MockRuntime, ETHACCOUNT_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
     };
     use fvm_ipld_encoding::ipld_block::IpldBlock;
-    use fvm_shared::address::Address;
+    use fvm_shared::{address::Address, econ::TokenAmount, error::ExitCode, sys::SendFlags};
     use rand::RngCore;
 
     pub fn new_hash(size: usize) -> (Hash, u64) {
@@ -175,13 +275,59 @@ mod tests {
         rt
     }
 
+    fn charge_params(hash: Hash, reader: Address, size: u64) -> ChargeReadParams {
+        ChargeReadParams {
+            hash,
+            owner: None,
+            reader,
+            size,
+        }
+    }
+
+    fn read_charge(params: &ChargeReadParams) -> ReadCharge {
+        ReadCharge {
+            payer: Some(params.reader),
+            credit: Credit::from_whole(params.size),
+        }
+    }
+
+    fn expect_get_read_charge(rt: &MockRuntime, params: ChargeReadParams) {
+        rt.expect_send(
+            BLOBS_ACTOR_ADDR,
+            BlobsMethod::GetReadCharge as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&read_charge(&params)).unwrap(),
+            ExitCode::OK,
+            None,
+        );
+    }
+
+    fn expect_charge_read(rt: &MockRuntime, params: ChargeReadParams, exit_code: ExitCode) {
+        let ret = if exit_code.is_success() {
+            IpldBlock::serialize_cbor(&read_charge(&params)).unwrap()
+        } else {
+            None
+        };
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobsMethod::ChargeRead as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+            TokenAmount::zero(),
+            ret,
+            exit_code,
+        );
+    }
+
     fn expect_emitted_open_event(rt: &MockRuntime, params: &OpenReadRequestParams, id: &Hash) {
         let event = to_actor_event(
             read_request_opened(
                 &id.0,
                 &params.hash.0,
-                params.offset as u64,
-                params.len as u64,
+                params.offset,
+                params.len,
                 params.callback_addr,
                 params.callback_method,
             )
@@ -197,7 +343,7 @@ mod tests {
     }
 
     fn expect_emitted_closed_event(rt: &MockRuntime, params: &CloseReadRequestParams) {
-        let event = to_actor_event(read_request_closed(&params.0 .0).unwrap()).unwrap();
+        let event = to_actor_event(read_request_closed(&params.id.0).unwrap()).unwrap();
         rt.expect_emitted_event(event);
     }
 
@@ -218,8 +364,8 @@ mod tests {
 
         // Create a test blob hash and callback details
         let blob_hash = new_hash(1024).0;
-        let offset = 32u32;
-        let len = 1024u32;
+        let offset = 32u64;
+        let len = 1024u64;
         let callback_method = 42u64;
 
         // Test opening a read request
@@ -228,10 +374,13 @@ mod tests {
             hash: blob_hash,
             offset,
             len,
+            chunk_len: 0,
             callback_addr: f4_eth_addr,
             callback_method,
+            owner: None,
         };
         let expected_id = Hash::from(1);
+        expect_get_read_charge(&rt, charge_params(blob_hash, id_addr, len));
         expect_emitted_open_event(&rt, &open_params, &expected_id);
         let request_id = rt
             .call::<ReadReqActor>(
@@ -269,18 +418,28 @@ mod tests {
             )
             .unwrap()
             .unwrap()
-            .deserialize::<Vec<(Hash, Hash, u32, u32, Address, u64)>>()
+            .deserialize::<Vec<OpenReadRequestTuple>>()
             .unwrap();
 
         assert_eq!(result.len(), 1);
-        let (req_id, req_blob_hash, req_offset, req_len, req_callback_addr, req_callback_method) =
-            &result[0];
+        let (
+            req_id,
+            req_blob_hash,
+            req_chunk,
+            req_offset,
+            req_len,
+            req_callback_addr,
+            req_callback_method,
+            req_chunked,
+        ) = &result[0];
         assert_eq!(req_id, &request_id);
         assert_eq!(req_blob_hash, &blob_hash);
+        assert_eq!(req_chunk, &0);
         assert_eq!(req_offset, &offset);
-        assert_eq!(req_len, &len);
+        assert_eq!(req_len, &(len as u32));
         assert_eq!(req_callback_addr, &f4_eth_addr);
         assert_eq!(req_callback_method, &callback_method);
+        assert!(!req_chunked);
         rt.verify();
 
         // Test setting request to pending
@@ -311,10 +470,16 @@ mod tests {
         assert!(matches!(result, Some(ReadRequestStatus::Pending)));
         rt.verify();
 
-        // Test closing a request (requires system actor caller)
+        // Test charging and closing a request (requires system actor caller)
+        expect_charge_read(&rt, charge_params(blob_hash, id_addr, len), ExitCode::OK);
+        assert!(charge_chunk(&rt, request_id, 0, len as u32));
         rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
         rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
-        let close_params = CloseReadRequestParams(request_id);
+        let close_params = CloseReadRequestParams {
+            id: request_id,
+            chunk: 0,
+            delivered: true,
+        };
         expect_emitted_closed_event(&rt, &close_params);
         let result = rt.call::<ReadReqActor>(
             Method::CloseReadRequest as u64,
@@ -340,6 +505,267 @@ mod tests {
         rt.verify();
     }
 
+    fn get_open_read_requests(rt: &MockRuntime) -> Vec<OpenReadRequestTuple> {
+        rt.expect_validate_caller_any();
+        let result = rt
+            .call::<ReadReqActor>(
+                Method::GetOpenReadRequests as u64,
+                IpldBlock::serialize_cbor(&GetOpenReadRequestsParams(10)).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Vec<OpenReadRequestTuple>>()
+            .unwrap();
+        rt.verify();
+        result
+    }
+
+    fn set_pending(rt: &MockRuntime, id: Hash) {
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        let params = SetReadRequestPendingParams(id);
+        expect_emitted_pending_event(rt, &params);
+        rt.call::<ReadReqActor>(
+            Method::SetReadRequestPending as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+    }
+
+    fn charge_chunk(rt: &MockRuntime, id: Hash, chunk: u32, len: u32) -> bool {
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        let result = rt
+            .call::<ReadReqActor>(
+                Method::ChargeReadRequest as u64,
+                IpldBlock::serialize_cbor(&ChargeReadRequestParams { id, chunk, len }).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<bool>()
+            .unwrap();
+        rt.verify();
+        result
+    }
+
+    fn close_chunk(rt: &MockRuntime, id: Hash, chunk: u32, delivered: bool) {
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        rt.call::<ReadReqActor>(
+            Method::CloseReadRequest as u64,
+            IpldBlock::serialize_cbor(&CloseReadRequestParams {
+                id,
+                chunk,
+                delivered,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+    }
+
+    fn get_status(rt: &MockRuntime, id: Hash) -> Option<ReadRequestStatus> {
+        rt.expect_validate_caller_any();
+        let result = rt
+            .call::<ReadReqActor>(
+                Method::GetReadRequestStatus as u64,
+                IpldBlock::serialize_cbor(&GetReadRequestStatusParams(id)).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Option<ReadRequestStatus>>()
+            .unwrap();
+        rt.verify();
+        result
+    }
+
+    fn open_chunked_request(
+        rt: &MockRuntime,
+        caller: Address,
+        deposit: TokenAmount,
+        expected_id: Hash,
+    ) -> OpenReadRequestParams {
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, caller);
+        rt.set_origin(caller);
+        rt.set_received(deposit.clone());
+        rt.set_balance(rt.get_balance() + deposit);
+        rt.expect_validate_caller_any();
+        let params = OpenReadRequestParams {
+            hash: new_hash(1024).0,
+            offset: 100,
+            len: 2500,
+            chunk_len: 1024,
+            callback_addr: caller,
+            callback_method: 42,
+            owner: None,
+        };
+        expect_get_read_charge(rt, charge_params(params.hash, caller, params.len));
+        expect_emitted_open_event(rt, &params, &expected_id);
+        let id = rt
+            .call::<ReadReqActor>(
+                Method::OpenReadRequest as u64,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Hash>()
+            .unwrap();
+        assert_eq!(id, expected_id);
+        rt.set_received(TokenAmount::zero());
+        rt.verify();
+        params
+    }
+
+    #[test]
+    fn test_chunked_read_request() {
+        let rt = construct_and_verify();
+        let caller = Address::new_id(110);
+        let deposit = TokenAmount::from_atto(1000);
+        let id = Hash::from(1);
+        let params = open_chunked_request(&rt, caller, deposit.clone(), id);
+
+        // Chunks are delivered in sequence, covering the whole range
+        let expected = [(0, 100, 1024), (1, 1124, 1024), (2, 2148, 452)];
+        for (chunk, offset, len) in expected {
+            let requests = get_open_read_requests(&rt);
+            assert_eq!(
+                requests,
+                vec![(id, params.hash, chunk, offset, len, caller, 42, true)]
+            );
+            set_pending(&rt, id);
+            assert!(get_open_read_requests(&rt).is_empty());
+
+            // A chunk cannot be delivered before it is charged
+            rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+            rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+            let result = rt.call::<ReadReqActor>(
+                Method::CloseReadRequest as u64,
+                IpldBlock::serialize_cbor(&CloseReadRequestParams {
+                    id,
+                    chunk,
+                    delivered: true,
+                })
+                .unwrap(),
+            );
+            assert!(result.is_err());
+            rt.reset();
+
+            expect_charge_read(
+                &rt,
+                charge_params(params.hash, caller, len as u64),
+                ExitCode::OK,
+            );
+            assert!(charge_chunk(&rt, id, chunk, len));
+            if chunk == 2 {
+                // The deposit is returned once the last chunk is delivered
+                rt.expect_send_simple(
+                    caller,
+                    METHOD_SEND,
+                    None,
+                    deposit.clone(),
+                    None,
+                    ExitCode::OK,
+                );
+                expect_emitted_closed_event(
+                    &rt,
+                    &CloseReadRequestParams {
+                        id,
+                        chunk,
+                        delivered: true,
+                    },
+                );
+            }
+            close_chunk(&rt, id, chunk, true);
+        }
+        assert!(get_status(&rt, id).is_none());
+    }
+
+    #[test]
+    fn test_read_request_callback_retries() {
+        let rt = construct_and_verify();
+        let caller = Address::new_id(110);
+        let deposit = TokenAmount::from_atto(1000);
+        let id = Hash::from(1);
+        let params = open_chunked_request(&rt, caller, deposit.clone(), id);
+
+        // The first chunk is delivered, the second one keeps failing
+        set_pending(&rt, id);
+        expect_charge_read(&rt, charge_params(params.hash, caller, 1024), ExitCode::OK);
+        assert!(charge_chunk(&rt, id, 0, 1024));
+        close_chunk(&rt, id, 0, true);
+        for attempt in 0..MAX_CALLBACK_RETRIES {
+            set_pending(&rt, id);
+            // Retries are not charged again
+            if attempt == 0 {
+                expect_charge_read(&rt, charge_params(params.hash, caller, 1024), ExitCode::OK);
+            }
+            assert!(charge_chunk(&rt, id, 1, 1024));
+            close_chunk(&rt, id, 1, false);
+            assert_eq!(get_status(&rt, id), Some(ReadRequestStatus::Open));
+            assert_eq!(get_open_read_requests(&rt)[0].2, 1);
+        }
+
+        // Closing a chunk out of sequence is rejected
+        set_pending(&rt, id);
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        let result = rt.call::<ReadReqActor>(
+            Method::CloseReadRequest as u64,
+            IpldBlock::serialize_cbor(&CloseReadRequestParams {
+                id,
+                chunk: 2,
+                delivered: true,
+            })
+            .unwrap(),
+        );
+        assert!(result.is_err());
+        rt.reset();
+
+        // The request is removed and the caller is refunded when retries run out
+        rt.expect_send_simple(caller, METHOD_SEND, None, deposit, None, ExitCode::OK);
+        expect_emitted_closed_event(
+            &rt,
+            &CloseReadRequestParams {
+                id,
+                chunk: 1,
+                delivered: false,
+            },
+        );
+        close_chunk(&rt, id, 1, false);
+        assert_eq!(get_status(&rt, id), None);
+        assert!(get_open_read_requests(&rt).is_empty());
+    }
+
+    #[test]
+    fn test_read_request_charge_failure() {
+        let rt = construct_and_verify();
+        let caller = Address::new_id(110);
+        let deposit = TokenAmount::from_atto(1000);
+        let id = Hash::from(1);
+        let params = open_chunked_request(&rt, caller, deposit.clone(), id);
+
+        // The request is removed without retries and the caller is refunded when a chunk can't be paid
+        set_pending(&rt, id);
+        expect_charge_read(
+            &rt,
+            charge_params(params.hash, caller, 1024),
+            ExitCode::USR_INSUFFICIENT_FUNDS,
+        );
+        rt.expect_send_simple(caller, METHOD_SEND, None, deposit, None, ExitCode::OK);
+        expect_emitted_closed_event(
+            &rt,
+            &CloseReadRequestParams {
+                id,
+                chunk: 0,
+                delivered: false,
+            },
+        );
+        assert!(!charge_chunk(&rt, id, 0, 1024));
+        assert_eq!(get_status(&rt, id), None);
+        assert!(get_open_read_requests(&rt).is_empty());
+    }
+
     #[test]
     fn test_read_request_error_cases() {
         let rt = construct_and_verify();
@@ -357,7 +783,11 @@ mod tests {
         rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
         rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
         let non_existent_request_id = Hash([0u8; 32]);
-        let close_params = CloseReadRequestParams(non_existent_request_id);
+        let close_params = CloseReadRequestParams {
+            id: non_existent_request_id,
+            chunk: 0,
+            delivered: true,
+        };
         let result = rt.call::<ReadReqActor>(
             Method::CloseReadRequest as u64,
             IpldBlock::serialize_cbor(&close_params).unwrap(),
@@ -375,4 +805,26 @@ mod tests {
         assert!(result.is_err());
         rt.verify();
     }
+
+    #[test]
+    fn test_decode_legacy_read_request() {
+        let (hash, _) = new_hash(1024);
+        let callback_addr = Address::new_id(110);
+        let legacy = (
+            hash,
+            16u32,
+            2048u32,
+            callback_addr,
+            42u64,
+            ReadRequestStatus::Pending,
+        );
+        let bytes = fvm_ipld_encoding::to_vec(&legacy).unwrap();
+        let request: ReadRequest = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(request.callback_addr, callback_addr);
+        assert_eq!(request.status, ReadRequestStatus::Pending);
+        assert_eq!(request.caller, None);
+        assert!(!request.is_chunked());
+        assert_eq!(request.chunk_count(), 1);
+        assert_eq!(request.chunk_range(0), (16, 2048));
+    }
 }
@@ -6,15 +6,14 @@ use std::fmt;
 
 use fendermint_actor_blobs_shared::state::Hash;
 use fvm_ipld_encoding::tuple::*;
-use fvm_shared::{address::Address, ActorID, MethodNum, METHOD_CONSTRUCTOR};
+use fvm_shared::{address::Address, econ::TokenAmount, MethodNum, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
 
-pub use crate::state::State;
+pub use crate::state::{CloseReadRequestOutcome, State, MAX_CALLBACK_RETRIES};
+pub use fendermint_actor_blobs_shared::{BLOB_READER_ACTOR_ADDR, BLOB_READER_ACTOR_ID};
 
 pub const BLOB_READER_ACTOR_NAME: &str = "blob_reader";
-pub const BLOB_READER_ACTOR_ID: ActorID = 67;
-pub const BLOB_READER_ACTOR_ADDR: Address = Address::new_id(BLOB_READER_ACTOR_ID);
 
 /// The status of a read request.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
@@ -36,20 +35,80 @@ impl fmt::Display for ReadRequestStatus {
 }
 
 /// A request to read blob data.
+///
+/// Fields after `status` default when missing, so requests stored before they were added
+/// still decode. Such requests are delivered in a single chunk and are not charged.
+///
+/// Requests without a chunk length are delivered to the callback as `(id, data)`, like before
+/// reads were chunked. Chunked requests are delivered as `(id, chunk, data)`.
 #[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct ReadRequest {
     /// The hash of the blob to read data from.
     pub blob_hash: Hash,
     /// The offset to start reading from.
-    pub offset: u32,
+    pub offset: u64,
     /// The length of data to read.
-    pub len: u32,
+    pub len: u64,
     /// The address to call back when the read is complete.
     pub callback_addr: Address,
     /// The method to call back when the read is complete.
     pub callback_method: MethodNum,
     /// Status of the read request
     pub status: ReadRequestStatus,
+    /// The maximum length of data delivered in a single callback.
+    /// If zero, the data is delivered in a single, unchunked callback.
+    #[serde(default)]
+    pub chunk_len: u32,
+    /// Sequence number of the next chunk to deliver.
+    #[serde(default)]
+    pub next_chunk: u32,
+    /// Number of failed callback attempts for the next chunk.
+    #[serde(default)]
+    pub attempts: u32,
+    /// The address that opened the request.
+    /// Delivered chunks are charged to it, unless the blob owner's read policy says otherwise.
+    /// If `None`, the request was opened before reads were charged and is delivered for free.
+    #[serde(default)]
+    pub caller: Option<Address>,
+    /// Tokens sent with the request, returned to the caller once the request completes or fails.
+    #[serde(default)]
+    pub deposit: TokenAmount,
+    /// Optional owner of the blob whose read policy applies.
+    #[serde(default)]
+    pub owner: Option<Address>,
+    /// Whether the next chunk has been charged, so retries of its callback are not charged again.
+    #[serde(default)]
+    pub charged: bool,
+}
+
+impl ReadRequest {
+    /// Returns whether the data is delivered with the chunk sequence number.
+    pub fn is_chunked(&self) -> bool {
+        self.chunk_len != 0
+    }
+
+    /// Returns the number of chunks the data is delivered in.
+    pub fn chunk_count(&self) -> u64 {
+        self.len.div_ceil(self.effective_chunk_len()).max(1)
+    }
+
+    /// Returns the offset and length of a chunk.
+    pub fn chunk_range(&self, chunk: u32) -> (u64, u32) {
+        let chunk_len = self.effective_chunk_len();
+        let start = chunk as u64 * chunk_len;
+        let len = chunk_len.min(self.len.saturating_sub(start));
+        // A chunk is at most as long as the chunk length, or the whole unchunked request,
+        // both of which are limited to a `u32`.
+        (self.offset + start, len as u32)
+    }
+
+    fn effective_chunk_len(&self) -> u64 {
+        if self.chunk_len == 0 {
+            self.len.max(1)
+        } else {
+            self.chunk_len as u64
+        }
+    }
 }
 
 #[derive(FromPrimitive)]
@@ -58,6 +117,7 @@ pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
     GetReadRequestStatus = frc42_dispatch::method_hash!("GetReadRequestStatus"),
     CloseReadRequest = frc42_dispatch::method_hash!("CloseReadRequest"),
+    ChargeReadRequest = frc42_dispatch::method_hash!("ChargeReadRequest"),
     GetOpenReadRequests = frc42_dispatch::method_hash!("GetOpenReadRequests"),
     OpenReadRequest = frc42_dispatch::method_hash!("OpenReadRequest"),
     SetReadRequestPending = frc42_dispatch::method_hash!("SetReadRequestPending"),
@@ -69,19 +129,45 @@ pub struct OpenReadRequestParams {
     /// The hash of the blob to read.
     pub hash: Hash,
     /// The offset to start reading from.
-    pub offset: u32,
+    pub offset: u64,
     /// The length of the read request.
-    pub len: u32,
+    pub len: u64,
     /// The address to call back when the read is complete.
     pub callback_addr: Address,
     /// The method to call back when the read is complete.
     pub callback_method: MethodNum,
+    /// The maximum length of data delivered in a single callback.
+    /// If zero or missing, the data is delivered in a single callback as `(id, data)`,
+    /// otherwise each chunk is delivered as `(id, chunk, data)`.
+    #[serde(default)]
+    pub chunk_len: u32,
+    /// Optional owner of the blob whose read policy applies.
+    /// If not present, the caller pays for the read.
+    #[serde(default)]
+    pub owner: Option<Address>,
 }
 
-/// Params for closing a read request. The ID of the read request.
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(transparent)]
-pub struct CloseReadRequestParams(pub Hash);
+/// Params for charging the pending chunk of a read request before it is delivered.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ChargeReadRequestParams {
+    /// The ID of the read request.
+    pub id: Hash,
+    /// The sequence number of the chunk about to be delivered.
+    pub chunk: u32,
+    /// The length of the data read for the chunk.
+    pub len: u32,
+}
+
+/// Params for closing the pending chunk of a read request.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct CloseReadRequestParams {
+    /// The ID of the read request.
+    pub id: Hash,
+    /// The sequence number of the chunk that was delivered.
+    pub chunk: u32,
+    /// Whether the callback accepted the chunk.
+    pub delivered: bool,
+}
 
 /// Params for getting pending read requests.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -98,4 +184,6 @@ pub struct SetReadRequestPendingParams(pub Hash);
 #[serde(transparent)]
 pub struct GetReadRequestStatusParams(pub Hash);
 
-pub type OpenReadRequestTuple = (Hash, Hash, u32, u32, Address, u64);
+/// An open read request chunk as
+/// (id, blob hash, chunk, offset, len, callback address, callback method, chunked).
+pub type OpenReadRequestTuple = (Hash, Hash, u32, u64, u32, Address, u64, bool);
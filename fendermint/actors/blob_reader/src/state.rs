@@ -6,7 +6,7 @@ use fendermint_actor_blobs_shared::state::Hash;
 use fil_actors_runtime::ActorError;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::tuple::*;
-use fvm_shared::address::Address;
+use fvm_shared::{address::Address, econ::TokenAmount};
 use log::info;
 
 use crate::shared::{OpenReadRequestTuple, ReadRequest, ReadRequestStatus};
@@ -14,6 +14,23 @@ use recall_ipld::hamt;
 use recall_ipld::hamt::map::TrackedFlushResult;
 
 const MAX_READ_REQUEST_LEN: u32 = 1024 * 1024; // 1MB
+const MAX_READ_REQUEST_CHUNKS: u64 = 1024;
+/// Maximum number of times a failed callback is retried before the request fails.
+pub const MAX_CALLBACK_RETRIES: u32 = 3;
+
+/// The result of closing the pending chunk of a read request.
+#[derive(Debug, PartialEq)]
+pub enum CloseReadRequestOutcome {
+    /// The next chunk is ready to be read.
+    Next,
+    /// The chunk will be read and delivered again.
+    Retry,
+    /// All chunks were delivered and the request was removed.
+    Completed(ReadRequest),
+    /// The callback failed too many times, or the chunk couldn't be paid for,
+    /// and the request was removed.
+    Failed(ReadRequest),
+}
 
 /// The state represents all read requests.
 #[derive(Debug, Serialize_tuple, Deserialize_tuple)]
@@ -33,20 +50,37 @@ impl State {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn open_read_request<BS: Blockstore>(
         &mut self,
         store: &BS,
         blob_hash: Hash,
-        offset: u32,
-        len: u32,
+        offset: u64,
+        len: u64,
+        chunk_len: u32,
         callback_addr: Address,
         callback_method: u64,
+        caller: Address,
+        deposit: TokenAmount,
+        owner: Option<Address>,
     ) -> Result<Hash, ActorError> {
-        // Validate length is not greater than the maximum allowed
-        if len > MAX_READ_REQUEST_LEN {
+        // A zero chunk length means the data is delivered at once
+        let max_len = if chunk_len == 0 {
+            len.max(1)
+        } else {
+            chunk_len as u64
+        };
+        // Validate chunk length is not greater than the maximum allowed
+        if max_len > MAX_READ_REQUEST_LEN as u64 {
+            return Err(ActorError::illegal_argument(format!(
+                "read request chunk length {} exceeds maximum allowed {}",
+                max_len, MAX_READ_REQUEST_LEN
+            )));
+        }
+        if offset.checked_add(len).is_none() {
             return Err(ActorError::illegal_argument(format!(
-                "read request length {} exceeds maximum allowed {}",
-                len, MAX_READ_REQUEST_LEN
+                "read request range {}+{} overflows",
+                offset, len
             )));
         }
 
@@ -55,10 +89,24 @@ impl State {
             blob_hash,
             offset,
             len,
+            chunk_len,
             callback_addr,
             callback_method,
             status: ReadRequestStatus::Open,
+            next_chunk: 0,
+            attempts: 0,
+            caller: Some(caller),
+            deposit,
+            owner,
+            charged: false,
         };
+        if read_request.chunk_count() > MAX_READ_REQUEST_CHUNKS {
+            return Err(ActorError::illegal_argument(format!(
+                "read request of {} chunks exceeds maximum allowed {}",
+                read_request.chunk_count(),
+                MAX_READ_REQUEST_CHUNKS
+            )));
+        }
         info!("opening a read request onchain: {:?}", request_id);
         // will create a new request even if the request parameters are the same
         let mut read_requests = self.read_requests.hamt(store)?;
@@ -67,22 +115,102 @@ impl State {
         Ok(request_id)
     }
 
-    pub fn close_read_request<BS: Blockstore>(
+    /// Returns a read request if `chunk` is its pending chunk.
+    pub fn get_pending_read_request<BS: Blockstore>(
+        &self,
+        store: &BS,
+        request_id: Hash,
+        chunk: u32,
+    ) -> Result<ReadRequest, ActorError> {
+        let read_requests = self.read_requests.hamt(store)?;
+        get_pending_chunk(&read_requests, request_id, chunk)
+    }
+
+    /// Marks the pending chunk of a read request as charged.
+    pub fn set_read_request_charged<BS: Blockstore>(
         &mut self,
         store: &BS,
         request_id: Hash,
+        chunk: u32,
     ) -> Result<(), ActorError> {
-        if self.get_read_request_status(store, request_id)?.is_none() {
-            return Err(ActorError::not_found(
-                "cannot close read request, it does not exist".to_string(),
-            ));
-        }
+        let mut read_requests = self.read_requests.hamt(store)?;
+        let mut request = get_pending_chunk(&read_requests, request_id, chunk)?;
+        request.charged = true;
+        self.read_requests
+            .save_tracked(read_requests.set_and_flush_tracked(&request_id, request)?);
+        Ok(())
+    }
+
+    /// Closes the pending chunk of a read request.
+    ///
+    /// Only chunks that have been charged can be delivered.
+    /// If the callback accepted the chunk, the request moves on to the next chunk, or is removed
+    /// if it was the last one. Otherwise, the chunk is retried until the retries run out.
+    pub fn close_read_request<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        request_id: Hash,
+        chunk: u32,
+        delivered: bool,
+    ) -> Result<CloseReadRequestOutcome, ActorError> {
+        let mut read_requests = self.read_requests.hamt(store)?;
+        let mut request = get_pending_chunk(&read_requests, request_id, chunk)?;
+
+        let outcome = if delivered {
+            if !request.charged {
+                return Err(ActorError::illegal_state(format!(
+                    "read request {} chunk {} was not charged",
+                    request_id, chunk
+                )));
+            }
+            request.next_chunk += 1;
+            request.attempts = 0;
+            request.charged = false;
+            if request.next_chunk as u64 == request.chunk_count() {
+                // remove the completed request
+                self.read_requests
+                    .save_tracked(read_requests.delete_and_flush_tracked(&request_id)?.0);
+                return Ok(CloseReadRequestOutcome::Completed(request));
+            }
+            CloseReadRequestOutcome::Next
+        } else {
+            request.attempts += 1;
+            if request.attempts > MAX_CALLBACK_RETRIES {
+                // remove the failed request
+                self.read_requests
+                    .save_tracked(read_requests.delete_and_flush_tracked(&request_id)?.0);
+                return Ok(CloseReadRequestOutcome::Failed(request));
+            }
+            CloseReadRequestOutcome::Retry
+        };
+        request.status = ReadRequestStatus::Open;
+        self.read_requests
+            .save_tracked(read_requests.set_and_flush_tracked(&request_id, request)?);
+        Ok(outcome)
+    }
 
-        // remove the closed request
+    /// Removes a read request without retrying its pending chunk,
+    /// e.g., because the chunk could not be paid for.
+    pub fn fail_read_request<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        request_id: Hash,
+        chunk: u32,
+    ) -> Result<CloseReadRequestOutcome, ActorError> {
         let mut read_requests = self.read_requests.hamt(store)?;
+        let request = get_pending_chunk(&read_requests, request_id, chunk)?;
         self.read_requests
             .save_tracked(read_requests.delete_and_flush_tracked(&request_id)?.0);
-        Ok(())
+        Ok(CloseReadRequestOutcome::Failed(request))
+    }
+
+    pub fn get_read_request<BS: Blockstore>(
+        &self,
+        store: BS,
+        id: Hash,
+    ) -> Result<Option<ReadRequest>, ActorError> {
+        let read_requests = self.read_requests.hamt(store)?;
+        read_requests.get(&id)
     }
 
     pub fn get_open_read_requests<BS: Blockstore>(
@@ -95,13 +223,16 @@ impl State {
         let mut requests = Vec::new();
         read_requests.for_each(|id, request| {
             if matches!(request.status, ReadRequestStatus::Open) && (requests.len() as u32) < size {
+                let (offset, len) = request.chunk_range(request.next_chunk);
                 requests.push((
                     id,
                     request.blob_hash,
-                    request.offset,
-                    request.len,
+                    request.next_chunk,
+                    offset,
+                    len,
                     request.callback_addr,
                     request.callback_method,
+                    request.is_chunked(),
                 ))
             }
 
@@ -150,6 +281,32 @@ impl State {
     }
 }
 
+/// Returns a read request if `chunk` is its pending chunk.
+fn get_pending_chunk<BS: Blockstore>(
+    read_requests: &hamt::map::Hamt<BS, Hash, ReadRequest>,
+    request_id: Hash,
+    chunk: u32,
+) -> Result<ReadRequest, ActorError> {
+    let Some(request) = read_requests.get(&request_id)? else {
+        return Err(ActorError::not_found(
+            "cannot close read request, it does not exist".to_string(),
+        ));
+    };
+    if !matches!(request.status, ReadRequestStatus::Pending) {
+        return Err(ActorError::illegal_state(format!(
+            "read request {} is not in pending state",
+            request_id
+        )));
+    }
+    if chunk != request.next_chunk {
+        return Err(ActorError::illegal_argument(format!(
+            "read request {} is pending chunk {}, not {}",
+            request_id, request.next_chunk, chunk
+        )));
+    }
+    Ok(request)
+}
+
 #[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ReadRequests {
     pub root: hamt::Root<Hash, ReadRequest>,
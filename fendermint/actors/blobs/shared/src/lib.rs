@@ -13,6 +13,7 @@ use fvm_shared::econ::TokenAmount;
 use fvm_shared::sys::SendFlags;
 use fvm_shared::{ActorID, MethodNum, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
+use num_traits::Zero;
 
 use crate::state::{Account, Credit, CreditApproval, Subscription};
 
@@ -22,6 +23,20 @@ pub mod state;
 pub const BLOBS_ACTOR_ID: ActorID = 66;
 pub const BLOBS_ACTOR_ADDR: Address = Address::new_id(BLOBS_ACTOR_ID);
 
+/// The blob reader actor is the only actor allowed to charge for reads.
+pub const BLOB_READER_ACTOR_ID: ActorID = 67;
+pub const BLOB_READER_ACTOR_ADDR: Address = Address::new_id(BLOB_READER_ACTOR_ID);
+
+/// Epoch interval at which validators issue a new round of storage challenges.
+pub const BLOB_CHALLENGE_INTERVAL: ChainEpoch = 100;
+/// Number of epochs validators have to answer a storage challenge.
+pub const BLOB_CHALLENGE_WINDOW: ChainEpoch = 50;
+/// Maximum number of storage challenges issued per round.
+pub const BLOB_CHALLENGES_PER_ROUND: u32 = 4;
+/// Size of the byte range covered by a storage challenge.
+/// This matches the BLAKE3 chunk size so that answers can be proven against the blob hash.
+pub const BLOB_CHALLENGE_CHUNK_SIZE: u64 = 1024;
+
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
@@ -31,13 +46,21 @@ pub enum Method {
     BuyCredit = frc42_dispatch::method_hash!("BuyCredit"),
     ApproveCredit = frc42_dispatch::method_hash!("ApproveCredit"),
     RevokeCredit = frc42_dispatch::method_hash!("RevokeCredit"),
+    TransferCredit = frc42_dispatch::method_hash!("TransferCredit"),
+    SellCredit = frc42_dispatch::method_hash!("SellCredit"),
     SetAccountSponsor = frc42_dispatch::method_hash!("SetAccountSponsor"),
+    SetReadPolicy = frc42_dispatch::method_hash!("SetReadPolicy"),
+    SetGasSponsorPolicy = frc42_dispatch::method_hash!("SetGasSponsorPolicy"),
+    GetReadCharge = frc42_dispatch::method_hash!("GetReadCharge"),
     GetAccount = frc42_dispatch::method_hash!("GetAccount"),
     GetCreditApproval = frc42_dispatch::method_hash!("GetCreditApproval"),
     AddBlob = frc42_dispatch::method_hash!("AddBlob"),
     GetBlob = frc42_dispatch::method_hash!("GetBlob"),
     DeleteBlob = frc42_dispatch::method_hash!("DeleteBlob"),
     OverwriteBlob = frc42_dispatch::method_hash!("OverwriteBlob"),
+    SetBlobAutoRenew = frc42_dispatch::method_hash!("SetBlobAutoRenew"),
+    ApproveSubscriptionTransfer = frc42_dispatch::method_hash!("ApproveSubscriptionTransfer"),
+    TransferSubscriptions = frc42_dispatch::method_hash!("TransferSubscriptions"),
 
     // System methods
     GetGasAllowance = frc42_dispatch::method_hash!("GetGasAllowance"),
@@ -48,6 +71,11 @@ pub enum Method {
     SetBlobPending = frc42_dispatch::method_hash!("SetBlobPending"),
     FinalizeBlob = frc42_dispatch::method_hash!("FinalizeBlob"),
     DebitAccounts = frc42_dispatch::method_hash!("DebitAccounts"),
+    IssueChallenges = frc42_dispatch::method_hash!("IssueChallenges"),
+    GetOpenChallenges = frc42_dispatch::method_hash!("GetOpenChallenges"),
+    AnswerChallenge = frc42_dispatch::method_hash!("AnswerChallenge"),
+    CloseChallenge = frc42_dispatch::method_hash!("CloseChallenge"),
+    ChargeRead = frc42_dispatch::method_hash!("ChargeRead"),
 
     // Admin methods
     SetAccountStatus = frc42_dispatch::method_hash!("SetAccountStatus"),
@@ -237,3 +265,106 @@ pub fn overwrite_blob(
         rt.message().value_received(),
     ))?)
 }
+
+/// Set or unset the auto-renewal of a blob subscription.
+pub fn set_blob_auto_renew(
+    rt: &impl Runtime,
+    from: Address,
+    sub_id: state::SubscriptionId,
+    hash: state::Hash,
+    sponsor: Option<Address>,
+    ttl: Option<ChainEpoch>,
+) -> Result<(), ActorError> {
+    extract_send_result(rt.send_simple(
+        &BLOBS_ACTOR_ADDR,
+        Method::SetBlobAutoRenew as MethodNum,
+        IpldBlock::serialize_cbor(&params::SetBlobAutoRenewParams {
+            sponsor,
+            hash,
+            id: sub_id,
+            ttl,
+            from,
+        })?,
+        rt.message().value_received(),
+    ))?;
+    Ok(())
+}
+
+/// Approve or revoke the transfer of `from`'s blob subscriptions held by the calling machine.
+pub fn approve_subscription_transfer(
+    rt: &impl Runtime,
+    from: Address,
+    to: Option<Address>,
+) -> Result<(), ActorError> {
+    extract_send_result(rt.send_simple(
+        &BLOBS_ACTOR_ADDR,
+        Method::ApproveSubscriptionTransfer as MethodNum,
+        IpldBlock::serialize_cbor(&params::ApproveSubscriptionTransferParams { from, to })?,
+        TokenAmount::zero(),
+    ))?;
+    Ok(())
+}
+
+/// Transfer blob subscriptions held by the calling machine from `from` to `to`.
+/// `machine` is the robust address of the calling machine.
+/// Returns the number of subscriptions transferred.
+pub fn transfer_subscriptions(
+    rt: &impl Runtime,
+    machine: Address,
+    from: Address,
+    to: Address,
+    subscriptions: Vec<params::MachineSubscription>,
+) -> Result<u64, ActorError> {
+    deserialize_block(extract_send_result(rt.send_simple(
+        &BLOBS_ACTOR_ADDR,
+        Method::TransferSubscriptions as MethodNum,
+        IpldBlock::serialize_cbor(&params::TransferSubscriptionsParams {
+            from,
+            to,
+            machine,
+            subscriptions,
+        })?,
+        TokenAmount::zero(),
+    ))?)
+}
+
+pub fn get_stats(rt: &impl Runtime) -> Result<params::GetStatsReturn, ActorError> {
+    deserialize_block(extract_send_result(rt.send(
+        &BLOBS_ACTOR_ADDR,
+        Method::GetStats as MethodNum,
+        None,
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+    ))?)
+}
+
+/// Returns the charge for a read of blob data without charging it.
+/// Fails if the payer cannot afford the read.
+pub fn get_read_charge(
+    rt: &impl Runtime,
+    params: params::ChargeReadParams,
+) -> Result<params::ReadCharge, ActorError> {
+    deserialize_block(extract_send_result(rt.send(
+        &BLOBS_ACTOR_ADDR,
+        Method::GetReadCharge as MethodNum,
+        IpldBlock::serialize_cbor(&params)?,
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+    ))?)
+}
+
+/// Charges for a read of blob data.
+/// Only the blob reader actor can charge for reads.
+pub fn charge_read(
+    rt: &impl Runtime,
+    params: params::ChargeReadParams,
+) -> Result<params::ReadCharge, ActorError> {
+    deserialize_block(extract_send_result(rt.send_simple(
+        &BLOBS_ACTOR_ADDR,
+        Method::ChargeRead as MethodNum,
+        IpldBlock::serialize_cbor(&params)?,
+        TokenAmount::zero(),
+    ))?)
+}
@@ -2,15 +2,17 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{strict_bytes, tuple::*};
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 use crate::state::{
-    BlobStatus, Credit, Hash, PublicKey, SubscriptionId, TokenCreditRate, TtlStatus,
+    BlobStatus, Credit, GasSponsorPolicy, Hash, PublicKey, ReadPolicy, SubscriptionId,
+    TokenCreditRate, TtlStatus,
 };
 
 /// Params for buying credits.
@@ -65,6 +67,26 @@ pub struct RevokeCreditParams {
     pub for_caller: Option<Address>,
 }
 
+/// Params for transferring credit.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct TransferCreditParams {
+    /// Account address that is sending the credit.
+    pub from: Address,
+    /// Account address that is receiving the credit.
+    pub to: Address,
+    /// Amount of free credit to transfer.
+    pub amount: Credit,
+}
+
+/// Params for selling credit.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SellCreditParams {
+    /// Account address that is selling the credit.
+    pub from: Address,
+    /// Amount of free credit to sell.
+    pub amount: Credit,
+}
+
 /// Params for setting sponsor.
 #[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct SetSponsorParams {
@@ -75,6 +97,89 @@ pub struct SetSponsorParams {
     pub sponsor: Option<Address>,
 }
 
+/// Params for setting the read policy of an account.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SetReadPolicyParams {
+    /// Account address that is setting its read policy.
+    pub from: Address,
+    /// Who pays for reads of blobs owned by the account.
+    pub policy: ReadPolicy,
+}
+
+/// Params for setting the gas sponsor policy of an account.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SetGasSponsorPolicyParams {
+    /// Account address that is setting its gas sponsor policy.
+    pub from: Address,
+    /// The policy, or `None` to sponsor gas for all messages.
+    pub policy: Option<GasSponsorPolicy>,
+}
+
+/// Params for approving the transfer of blob subscriptions held by a machine.
+/// The calling machine is the one holding the subscriptions.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ApproveSubscriptionTransferParams {
+    /// Account address whose subscriptions will be transferred.
+    pub from: Address,
+    /// The new subscriber, or `None` to revoke a pending approval.
+    pub to: Option<Address>,
+}
+
+/// Params for transferring blob subscriptions held by a machine to a new subscriber.
+/// The calling machine is the one holding the subscriptions.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct TransferSubscriptionsParams {
+    /// Account address that approved the transfer of its subscriptions.
+    pub from: Address,
+    /// The new subscriber.
+    pub to: Address,
+    /// The robust address of the calling machine, from which subscription IDs are derived.
+    pub machine: Address,
+    /// The objects whose subscriptions are transferred.
+    /// There can be at most [`MAX_TRANSFER_SUBSCRIPTIONS`] of them.
+    pub subscriptions: Vec<MachineSubscription>,
+}
+
+/// The maximum number of subscriptions moved by a single transfer.
+pub const MAX_TRANSFER_SUBSCRIPTIONS: usize = 1000;
+
+/// A blob subscription held by a machine for one of its objects.
+///
+/// The subscription ID is derived with [`SubscriptionId::for_machine_object`].
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct MachineSubscription {
+    /// The blob hash.
+    pub hash: Hash,
+    /// The object key.
+    #[serde(with = "strict_bytes")]
+    pub key: Vec<u8>,
+    /// The object version, or `0` for objects that aren't versioned.
+    pub version: u64,
+}
+
+/// Params for charging a read of blob data.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ChargeReadParams {
+    /// The hash of the blob being read.
+    pub hash: Hash,
+    /// Optional owner of the blob whose read policy applies.
+    /// If not present, the reader pays.
+    pub owner: Option<Address>,
+    /// Account address that is reading the blob.
+    pub reader: Address,
+    /// Number of bytes read.
+    pub size: u64,
+}
+
+/// The charge for a read of blob data.
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct ReadCharge {
+    /// The account paying for the read, if any.
+    pub payer: Option<Address>,
+    /// The credit charged to the payer.
+    pub credit: Credit,
+}
+
 /// Params for setting account status.
 #[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct SetAccountStatusParams {
@@ -99,9 +204,15 @@ pub struct GetCreditApprovalParams {
 }
 
 /// Params for looking up credit allowance.
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(transparent)]
-pub struct GetGasAllowanceParams(pub Address);
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct GetGasAllowanceParams {
+    /// Account address that is sending the message.
+    pub from: Address,
+    /// The message target, used to apply sponsor policies.
+    pub to: Address,
+    /// The message method number, used to apply sponsor policies.
+    pub method_num: MethodNum,
+}
 
 /// Params for adding a blob.
 #[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
@@ -180,6 +291,20 @@ pub struct FinalizeBlobParams {
     pub status: BlobStatus,
 }
 
+/// Params for recording a validator's answer to a storage challenge.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct AnswerChallengeParams {
+    /// Challenge identifier.
+    pub id: u64,
+    /// The f410 address of the validator that answered the challenge.
+    pub validator: Address,
+}
+
+/// Params for closing a storage challenge.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CloseChallengeParams(pub u64);
+
 /// Params for deleting a blob.
 #[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct DeleteBlobParams {
@@ -195,6 +320,23 @@ pub struct DeleteBlobParams {
     pub from: Address,
 }
 
+/// Params for setting the auto-renewal of a blob subscription.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SetBlobAutoRenewParams {
+    /// Optional sponsor address.
+    /// Origin or caller must still have a delegation from sponsor.
+    pub sponsor: Option<Address>,
+    /// Blob blake3 hash.
+    pub hash: Hash,
+    /// Identifier used to differentiate blob additions for the same subscriber.
+    pub id: SubscriptionId,
+    /// Renewal time-to-live epochs.
+    /// If not specified, auto-renewal is turned off.
+    pub ttl: Option<ChainEpoch>,
+    /// Account address that initiated the update.
+    pub from: Address,
+}
+
 /// Params for overwriting a blob, i.e., deleting one and adding another.
 #[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct OverwriteBlobParams {
@@ -245,4 +387,7 @@ pub struct GetStatsReturn {
     pub num_resolving: u64,
     /// Total bytes of all currently resolving blobs.
     pub bytes_resolving: u64,
+    /// The current storage price multiplier in basis points driven by subnet utilisation.
+    /// See `StoragePricing` in the Recall config for how TTL and size adjust it further.
+    pub storage_multiplier_bps: u64,
 }
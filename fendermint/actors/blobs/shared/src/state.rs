@@ -12,6 +12,8 @@ use fvm_shared::address::Address;
 use fvm_shared::bigint::BigInt;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+use num_traits::Zero;
 use recall_ipld::hamt::MapKey;
 use serde::{Deserialize, Serialize};
 
@@ -73,6 +75,9 @@ impl Ord for TokenCreditRate {
 }
 
 /// The stored representation of a credit account.
+///
+/// Fields after `gas_allowance` default when missing, so accounts stored before they were
+/// added still decode.
 #[derive(Clone, Debug, Default, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct Account {
     /// Total size of all blobs managed by the account.
@@ -93,6 +98,25 @@ pub struct Account {
     pub max_ttl: ChainEpoch,
     /// The total token value an account has used to buy credits.
     pub gas_allowance: TokenAmount,
+    /// Who pays for reads of blobs owned by the account.
+    #[serde(default)]
+    pub read_policy: ReadPolicy,
+    /// Optional policy restricting which messages the account sponsors gas for.
+    #[serde(default)]
+    pub gas_sponsor_policy: Option<GasSponsorPolicy>,
+    /// Gas fees sponsored by the account in the current budget window.
+    #[serde(default)]
+    pub gas_sponsor_usage: GasSponsorUsage,
+    /// Approved transfers of the account's blob subscriptions held by machines,
+    /// keyed by machine, with the new subscriber as value.
+    #[serde(default)]
+    pub subscription_transfers: HashMap<String, Address>,
+    /// The token value paid by the account for credit that can still be sold back.
+    ///
+    /// It never exceeds the gas allowance, so credit whose gas allowance has been spent,
+    /// or credit received from other accounts, can't be sold for tokens.
+    #[serde(default)]
+    pub credit_backing: TokenAmount,
 }
 
 impl Account {
@@ -105,6 +129,94 @@ impl Account {
     }
 }
 
+/// Who pays for reading the blobs owned by an account.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ReadPolicy {
+    /// Reads are paid by the reader.
+    #[default]
+    Reader,
+    /// Reads are free.
+    Free,
+    /// Reads are paid by the owner, through a credit approval from the owner to the reader.
+    Owner,
+}
+
+impl fmt::Display for ReadPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadPolicy::Reader => write!(f, "reader"),
+            ReadPolicy::Free => write!(f, "free"),
+            ReadPolicy::Owner => write!(f, "owner"),
+        }
+    }
+}
+
+/// Restricts which messages a sponsor pays gas fees for, and how much.
+///
+/// The policy applies to all accounts the sponsor has approved,
+/// in addition to the gas fee limit of each approval.
+#[derive(Debug, Default, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct GasSponsorPolicy {
+    /// Target actors of covered messages. If empty, messages to any actor are covered.
+    pub targets: Vec<Address>,
+    /// Method numbers of covered messages. If empty, all methods are covered.
+    pub methods: Vec<MethodNum>,
+    /// Optional maximum gas fee covered per message.
+    pub message_limit: Option<TokenAmount>,
+    /// Optional maximum gas fee covered per budget window.
+    pub window_budget: Option<TokenAmount>,
+    /// Length of the budget window in epochs.
+    pub window_length: ChainEpoch,
+}
+
+impl GasSponsorPolicy {
+    /// Returns whether the policy covers a message to `to` calling `method_num`.
+    pub fn covers(&self, to: &Address, method_num: MethodNum) -> bool {
+        (self.targets.is_empty() || self.targets.contains(to))
+            && (self.methods.is_empty() || self.methods.contains(&method_num))
+    }
+
+    /// Returns the first epoch of the budget window containing `epoch`.
+    pub fn window_start(&self, epoch: ChainEpoch) -> ChainEpoch {
+        if self.window_length <= 0 {
+            return 0;
+        }
+        epoch - epoch.rem_euclid(self.window_length)
+    }
+
+    /// Returns the gas fee that can still be covered in the budget window containing `epoch`.
+    /// Returns `None` if the policy has no window budget.
+    pub fn window_remaining(
+        &self,
+        usage: &GasSponsorUsage,
+        epoch: ChainEpoch,
+    ) -> Option<TokenAmount> {
+        let budget = self.window_budget.as_ref()?;
+        let used = usage.used_in(self.window_start(epoch));
+        Some((budget - used).max(TokenAmount::zero()))
+    }
+}
+
+/// Gas fees covered by a sponsor policy in a budget window.
+#[derive(Debug, Default, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct GasSponsorUsage {
+    /// The first epoch of the budget window.
+    pub window_start: ChainEpoch,
+    /// Gas fees covered in the budget window.
+    pub used: TokenAmount,
+}
+
+impl GasSponsorUsage {
+    /// Returns the gas fees covered in the window starting at `window_start`.
+    pub fn used_in(&self, window_start: ChainEpoch) -> TokenAmount {
+        if self.window_start == window_start {
+            self.used.clone()
+        } else {
+            TokenAmount::zero()
+        }
+    }
+}
+
 /// A credit approval from one account to another.
 #[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct CreditApproval {
@@ -223,6 +335,30 @@ pub struct Blob {
     pub status: BlobStatus,
 }
 
+/// A storage challenge issued to validators for a byte range of a resolved blob.
+///
+/// Validators answer a challenge by proving, with a BLAKE3 inclusion proof against the
+/// blob hash, that they hold the bytes in the range.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct Challenge {
+    /// Challenge identifier.
+    pub id: u64,
+    /// Blake3 hash of the challenged blob.
+    pub hash: Hash,
+    /// The size of the challenged blob.
+    pub size: u64,
+    /// The offset of the challenged byte range.
+    /// This is always aligned to a BLAKE3 chunk boundary.
+    pub offset: u64,
+    /// The length of the challenged byte range.
+    pub length: u64,
+    /// The epoch after which the challenge can be closed.
+    pub deadline: ChainEpoch,
+    /// The validators whose answers have been verified and recorded on-chain.
+    #[serde(default)]
+    pub answered: Vec<Address>,
+}
+
 /// An object used to determine what [`Account`](s) are accountable for a blob, and for how long.
 /// Subscriptions allow us to distribute the cost of a blob across multiple accounts that
 /// have added the same blob.   
@@ -240,6 +376,47 @@ pub struct Subscription {
     pub delegate: Option<Address>,
     /// Whether the subscription failed due to an issue resolving the target blob.
     pub failed: bool,
+    /// Optional renewal time-to-live epochs.
+    /// If set, the subscription is renewed for this many epochs when it expires,
+    /// as long as the subscriber has enough credit.
+    /// Defaults to `None` for subscriptions stored before auto-renewal existed.
+    #[serde(default)]
+    pub auto_renew: Option<ChainEpoch>,
+    /// Storage surcharge paid upfront for the epochs from `surcharge_epoch` to expiry, which
+    /// the subscription covers for its group. It's never negative.
+    #[serde(default)]
+    pub surcharge: Credit,
+    /// Epoch from which `surcharge` applies.
+    #[serde(default)]
+    pub surcharge_epoch: ChainEpoch,
+}
+
+impl Subscription {
+    /// Returns the part of the surcharge that covers the epochs after `epoch`.
+    pub fn unused_surcharge(&self, epoch: ChainEpoch) -> Credit {
+        let period = self.expiry - self.surcharge_epoch;
+        if self.surcharge.is_zero() || period <= 0 || epoch >= self.expiry {
+            return Credit::zero();
+        }
+        let remaining = self.expiry - epoch.max(self.surcharge_epoch);
+        Credit::from_atto(self.surcharge.atto() * remaining / period)
+    }
+
+    /// Returns the part of the unused surcharge that a new `expiry` cuts off, split into the
+    /// part for the epochs after `group_expiry`, which the group no longer covers, and the part
+    /// for the epochs up to it, which other subscriptions in the group still cover.
+    pub fn cut_surcharge(
+        &self,
+        epoch: ChainEpoch,
+        expiry: ChainEpoch,
+        group_expiry: Option<ChainEpoch>,
+    ) -> (Credit, Credit) {
+        let cut_from = epoch.max(expiry);
+        let cut = self.unused_surcharge(cut_from);
+        let released = self.unused_surcharge(cut_from.max(group_expiry.unwrap_or_default()));
+        let covered = cut - &released;
+        (released, covered)
+    }
 }
 
 /// User-defined identifier used to differentiate blob subscriptions for the same subscriber.
@@ -263,6 +440,30 @@ impl SubscriptionId {
             inner: value.to_string(),
         })
     }
+
+    /// Returns the ID of the subscription a machine holds for an object key and version.
+    ///
+    /// The ID is derived from the machine's robust address, so a machine can only produce the
+    /// IDs of its own subscriptions. Version `0` is used for objects that aren't versioned.
+    pub fn for_machine_object(
+        machine: &Address,
+        key: &[u8],
+        version: u64,
+    ) -> Result<Self, ActorError> {
+        let id = if version == 0 {
+            let mut data = machine.payload_bytes();
+            data.extend(key);
+            blake3::hash(&data)
+        } else {
+            // Use a separate hash domain so version IDs can't collide with plain object keys
+            let mut hasher = blake3::Hasher::new_derive_key("recall bucket object version");
+            hasher.update(&machine.payload_bytes());
+            hasher.update(&version.to_be_bytes());
+            hasher.update(key);
+            hasher.finalize()
+        };
+        Self::new(&id.to_hex())
+    }
 }
 
 impl TryFrom<String> for SubscriptionId {
@@ -326,6 +527,25 @@ impl SubscriptionGroup {
         (max, new_max)
     }
 
+    /// Adds surcharge paid for the epochs from `epoch` to the subscription with the max expiry,
+    /// which covers them once the subscription that paid it no longer does.
+    pub fn carry_surcharge(&mut self, surcharge: &Credit, epoch: ChainEpoch) {
+        let Some((_, sub)) = self
+            .subscriptions
+            .iter_mut()
+            .filter(|(_, sub)| !sub.failed)
+            .max_by(|(a_id, a), (b_id, b)| a.expiry.cmp(&b.expiry).then(a_id.cmp(b_id)))
+        else {
+            return;
+        };
+        sub.surcharge_epoch = if sub.surcharge.is_zero() {
+            epoch
+        } else {
+            sub.surcharge_epoch.min(epoch)
+        };
+        sub.surcharge += surcharge;
+    }
+
     /// Returns whether the provided ID corresponds to a subscription that has the minimum
     /// added epoch and the next minimum added epoch in the group.
     pub fn is_min_added(
@@ -6,21 +6,24 @@ use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 use fendermint_actor_blobs_shared::params::{
-    AddBlobParams, ApproveCreditParams, BuyCreditParams, DeleteBlobParams, FinalizeBlobParams,
+    AddBlobParams, AnswerChallengeParams, ApproveCreditParams, ApproveSubscriptionTransferParams,
+    BuyCreditParams, ChargeReadParams, CloseChallengeParams, DeleteBlobParams, FinalizeBlobParams,
     GetAccountParams, GetAddedBlobsParams, GetBlobParams, GetBlobStatusParams,
     GetCreditApprovalParams, GetGasAllowanceParams, GetPendingBlobsParams, GetStatsReturn,
-    OverwriteBlobParams, RevokeCreditParams, SetAccountStatusParams, SetBlobPendingParams,
-    SetSponsorParams, TrimBlobExpiriesParams, UpdateGasAllowanceParams,
+    OverwriteBlobParams, ReadCharge, RevokeCreditParams, SellCreditParams, SetAccountStatusParams,
+    SetBlobAutoRenewParams, SetBlobPendingParams, SetGasSponsorPolicyParams, SetReadPolicyParams,
+    SetSponsorParams, TransferCreditParams, TransferSubscriptionsParams, TrimBlobExpiriesParams,
+    UpdateGasAllowanceParams, MAX_TRANSFER_SUBSCRIPTIONS,
 };
 use fendermint_actor_blobs_shared::state::{
-    Account, Blob, BlobStatus, Credit, CreditApproval, GasAllowance, Hash, PublicKey, Subscription,
-    SubscriptionId,
+    Account, Blob, BlobStatus, Challenge, Credit, CreditApproval, GasAllowance, Hash, PublicKey,
+    Subscription, SubscriptionId,
 };
-use fendermint_actor_blobs_shared::Method;
+use fendermint_actor_blobs_shared::{Method, BLOB_CHALLENGES_PER_ROUND, BLOB_READER_ACTOR_ADDR};
 use fendermint_actor_machine::events::emit_evm_event;
 use fendermint_actor_machine::util::{
-    require_addr_is_origin_or_caller, to_delegated_address, to_id_address,
-    to_id_and_delegated_address, token_to_biguint,
+    require_addr_is_origin_or_caller, require_caller_is_machine, to_delegated_address,
+    to_id_address, to_id_and_delegated_address, token_to_biguint,
 };
 use fendermint_actor_recall_config_shared::{get_config, require_caller_is_admin};
 use fil_actors_runtime::{
@@ -244,6 +247,81 @@ impl BlobsActor {
         Ok(())
     }
 
+    /// Transfer free credit from one account to another.
+    ///
+    /// The `from` address must be delegated (only delegated addresses can own credit).
+    /// The `from` address must be the message origin or caller.
+    /// The `to` address must be delegated (only delegated addresses can own credit).
+    fn transfer_credit(
+        rt: &impl Runtime,
+        params: TransferCreditParams,
+    ) -> Result<Account, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let (from_id_addr, _) = to_id_and_delegated_address(rt, params.from)?;
+        require_addr_is_origin_or_caller(rt, from_id_addr)?;
+
+        let to_id_addr = match to_id_and_delegated_address(rt, params.to) {
+            Ok((to_id_addr, _)) => to_id_addr,
+            Err(e) if e.exit_code() == ExitCode::USR_NOT_FOUND => {
+                // We send zero tokens to create the account in the FVM
+                extract_send_result(rt.send_simple(
+                    &params.to,
+                    METHOD_SEND,
+                    None,
+                    TokenAmount::zero(),
+                ))?;
+                to_id_and_delegated_address(rt, params.to)?.0
+            }
+            Err(e) => return Err(e),
+        };
+
+        let config = get_config(rt)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.transfer_credit(
+                &config,
+                rt.store(),
+                from_id_addr,
+                to_id_addr,
+                params.amount,
+                rt.curr_epoch(),
+            )
+        })
+    }
+
+    /// Sell free credit back for tokens.
+    ///
+    /// Credit is refunded at the current token credit rate, less the configured sell discount.
+    /// The refund is paid from this actor's balance.
+    /// The `from` address must be delegated (only delegated addresses can own credit).
+    /// The `from` address must be the message origin or caller.
+    fn sell_credit(rt: &impl Runtime, params: SellCreditParams) -> Result<Account, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let (from_id_addr, _) = to_id_and_delegated_address(rt, params.from)?;
+        require_addr_is_origin_or_caller(rt, from_id_addr)?;
+
+        let config = get_config(rt)?;
+
+        let (account, refund) = rt.transaction(|st: &mut State, rt| {
+            let (account, refund) =
+                st.sell_credit(&config, rt.store(), from_id_addr, params.amount)?;
+            let balance = rt.current_balance();
+            if balance < refund {
+                return Err(ActorError::insufficient_funds(format!(
+                    "insufficient balance to refund credit (available: {}; required: {})",
+                    balance, refund
+                )));
+            }
+            Ok((account, refund))
+        })?;
+
+        extract_send_result(rt.send_simple(&from_id_addr, METHOD_SEND, None, refund))?;
+
+        Ok(account)
+    }
+
     /// Sets or unsets a default credit and gas sponsor from one account to another.
     ///
     /// If `sponsor` does not exist, the default sponsor is unset.
@@ -277,6 +355,77 @@ impl BlobsActor {
         Ok(())
     }
 
+    /// Sets the policy restricting which messages an account sponsors gas for.
+    ///
+    /// Policy targets are resolved to ID addresses, so they can be matched against any
+    /// address type used as a message target.
+    fn set_gas_sponsor_policy(
+        rt: &impl Runtime,
+        params: SetGasSponsorPolicyParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let from = to_id_address(rt, params.from, true)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+        let policy = params
+            .policy
+            .map(|mut policy| {
+                policy.targets = policy
+                    .targets
+                    .into_iter()
+                    .map(|target| to_id_address(rt, target, false))
+                    .collect::<Result<_, _>>()?;
+                Ok::<_, ActorError>(policy)
+            })
+            .transpose()?;
+
+        let config = get_config(rt)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.set_gas_sponsor_policy(&config, rt.store(), from, policy, rt.curr_epoch())
+        })
+    }
+
+    /// Sets who pays for reads of blobs owned by an account.
+    fn set_read_policy(rt: &impl Runtime, params: SetReadPolicyParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let from = to_id_address(rt, params.from, true)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        let config = get_config(rt)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.set_read_policy(&config, rt.store(), from, params.policy, rt.curr_epoch())
+        })
+    }
+
+    /// Returns who would pay for a read of blob data, and how much, without charging it.
+    fn get_read_charge(
+        rt: &impl Runtime,
+        params: ChargeReadParams,
+    ) -> Result<ReadCharge, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let owner = params
+            .owner
+            .map(|owner| to_id_address(rt, owner, false))
+            .transpose()?;
+        let reader = to_id_address(rt, params.reader, false)?;
+
+        let config = get_config(rt)?;
+
+        rt.state::<State>()?.get_read_charge(
+            &config,
+            rt.store(),
+            params.hash,
+            owner,
+            reader,
+            params.size,
+            rt.curr_epoch(),
+        )
+    }
+
     /// Sets the account status for an address.
     fn set_account_status(
         rt: &impl Runtime,
@@ -361,7 +510,7 @@ impl BlobsActor {
     ) -> Result<GasAllowance, ActorError> {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
 
-        let from = match to_id_address(rt, params.0, false) {
+        let from = match to_id_address(rt, params.from, false) {
             Ok(from) => from,
             Err(e) => {
                 return if e.exit_code() == ExitCode::USR_FORBIDDEN {
@@ -373,9 +522,19 @@ impl BlobsActor {
             }
         };
 
-        let allowance =
-            rt.state::<State>()?
-                .get_gas_allowance(rt.store(), from, rt.curr_epoch())?;
+        // The target may not exist yet, in which case it can't match a sponsor policy target
+        let to = rt
+            .resolve_address(&params.to)
+            .map(Address::new_id)
+            .unwrap_or(params.to);
+
+        let allowance = rt.state::<State>()?.get_gas_allowance(
+            rt.store(),
+            from,
+            to,
+            params.method_num,
+            rt.curr_epoch(),
+        )?;
 
         Ok(allowance)
     }
@@ -387,10 +546,12 @@ impl BlobsActor {
     fn debit_accounts(rt: &impl Runtime) -> Result<(), ActorError> {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
 
+        let config = get_config(rt)?;
+
         let mut credit_debited = Credit::zero();
         let (deletes, num_accounts) = rt.transaction(|st: &mut State, rt| {
             let initial_credit_debited = st.credit_debited.clone();
-            let deletes = st.debit_accounts(rt.store(), rt.curr_epoch())?;
+            let deletes = st.debit_accounts(&config, rt.store(), rt.curr_epoch())?;
             credit_debited = &st.credit_debited - initial_credit_debited;
             let num_accounts = st.accounts.len();
             Ok((deletes, num_accounts))
@@ -576,6 +737,86 @@ impl BlobsActor {
         )
     }
 
+    /// Issues a new round of storage challenges for randomly selected resolved blobs.
+    ///
+    /// This is called by the system actor every `BLOB_CHALLENGE_INTERVAL` blocks.
+    /// Validators answer each challenge with a BLAKE3 inclusion proof for the challenged byte
+    /// range before its deadline.
+    ///
+    /// The selection is seeded from the current epoch and challenge counter because the runtime
+    /// does not yet expose beacon randomness. This makes challenges predictable a few blocks
+    /// ahead, which is acceptable as long as the answer window is short relative to the time
+    /// needed to fetch a blob that is not held locally.
+    fn issue_challenges(rt: &impl Runtime) -> Result<Vec<Challenge>, ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        let next_id = rt.state::<State>()?.challenges.next_id;
+        let entropy = [rt.curr_epoch().to_le_bytes(), next_id.to_le_bytes()].concat();
+        let digest = rt.hash_blake2b(&entropy);
+        let seed = u64::from_le_bytes(digest[..8].try_into().unwrap());
+
+        rt.transaction(|st: &mut State, rt| {
+            st.issue_challenges(rt.store(), rt.curr_epoch(), BLOB_CHALLENGES_PER_ROUND, seed)
+        })
+    }
+
+    /// Returns all open storage challenges.
+    fn get_open_challenges(rt: &impl Runtime) -> Result<Vec<Challenge>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        Ok(rt.state::<State>()?.get_open_challenges())
+    }
+
+    /// Records a validator's answer to an open storage challenge.
+    ///
+    /// The system actor only calls this after every validator has verified the answer's
+    /// inclusion proof and signature while processing the proposal.
+    fn answer_challenge(
+        rt: &impl Runtime,
+        params: AnswerChallengeParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+        rt.transaction(|st: &mut State, rt| {
+            st.answer_challenge(rt.curr_epoch(), params.id, params.validator)
+        })
+    }
+
+    /// Closes a storage challenge once its deadline has passed.
+    ///
+    /// Returns the closed challenge if its answers should be counted, i.e., if the
+    /// challenged blob is still stored.
+    fn close_challenge(
+        rt: &impl Runtime,
+        params: CloseChallengeParams,
+    ) -> Result<Option<Challenge>, ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+        rt.transaction(|st: &mut State, rt| st.close_challenge(rt.store(), params.0))
+    }
+
+    /// Charges for a read of blob data delivered by the blob reader actor.
+    fn charge_read(rt: &impl Runtime, params: ChargeReadParams) -> Result<ReadCharge, ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&BLOB_READER_ACTOR_ADDR))?;
+
+        let owner = params
+            .owner
+            .map(|owner| to_id_address(rt, owner, false))
+            .transpose()?;
+        let reader = to_id_address(rt, params.reader, false)?;
+
+        let config = get_config(rt)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.charge_read(
+                &config,
+                rt.store(),
+                params.hash,
+                owner,
+                reader,
+                params.size,
+                rt.curr_epoch(),
+            )
+        })
+    }
+
     /// Deletes a blob subscription.
     ///
     /// The `sponsor` will be the subscriber (the account responsible for payment), if it exists
@@ -626,6 +867,131 @@ impl BlobsActor {
         Ok(())
     }
 
+    /// Sets or unsets the auto-renewal time-to-live of a blob subscription.
+    ///
+    /// An auto-renewed subscription is extended by `ttl` epochs when it expires, as long as
+    /// the subscriber has enough credit. Passing `None` disables auto-renewal.
+    ///
+    /// The `sponsor` will be the subscriber (the account responsible for payment), if it exists
+    /// and there is an approval from `sponsor` to the message `origin` or `caller`.
+    fn set_blob_auto_renew(
+        rt: &impl Runtime,
+        params: SetBlobAutoRenewParams,
+    ) -> Result<Subscription, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let from_id_addr = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from_id_addr)?;
+        let subscriber_id_addr = if let Some(sponsor) = params.sponsor {
+            to_id_address(rt, sponsor, false)?
+        } else {
+            from_id_addr
+        };
+
+        let config = get_config(rt)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.set_blob_auto_renew(
+                &config,
+                rt.store(),
+                from_id_addr,
+                subscriber_id_addr,
+                params.hash,
+                params.id,
+                params.ttl,
+            )
+        })
+    }
+
+    /// Approves or revokes the transfer of blob subscriptions held by the calling machine.
+    ///
+    /// Machines call this when their owner proposes a new owner, so that the subscriptions
+    /// the machine holds on behalf of the owner can be moved once the new owner accepts.
+    /// Only machines can call this, and they are trusted to check that `from` is their owner.
+    fn approve_subscription_transfer(
+        rt: &impl Runtime,
+        params: ApproveSubscriptionTransferParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        require_caller_is_machine(rt)?;
+
+        let from = to_id_address(rt, params.from, false)?;
+        let to = params
+            .to
+            .map(|to| to_id_address(rt, to, false))
+            .transpose()?;
+        let machine = rt.message().caller();
+
+        let config = get_config(rt)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.approve_subscription_transfer(
+                &config,
+                rt.store(),
+                from,
+                machine,
+                to,
+                rt.curr_epoch(),
+            )
+        })
+    }
+
+    /// Transfers blob subscriptions held by the calling machine to a new subscriber.
+    ///
+    /// The previous subscriber must have approved the transfer to the new subscriber through
+    /// the same machine. The new subscriber commits credit for the remaining TTL of each
+    /// subscription, and the previous subscriber is refunded.
+    ///
+    /// Subscription IDs are derived from the machine address and object keys, so a machine
+    /// can only move its own subscriptions.
+    fn transfer_subscriptions(
+        rt: &impl Runtime,
+        params: TransferSubscriptionsParams,
+    ) -> Result<u64, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        require_caller_is_machine(rt)?;
+
+        let machine = rt.message().caller();
+        if to_id_address(rt, params.machine, false)? != machine {
+            return Err(ActorError::illegal_argument(format!(
+                "machine {} is not the caller {}",
+                params.machine, machine
+            )));
+        }
+        if params.subscriptions.len() > MAX_TRANSFER_SUBSCRIPTIONS {
+            return Err(ActorError::illegal_argument(format!(
+                "the maximum number of subscriptions per transfer is {}",
+                MAX_TRANSFER_SUBSCRIPTIONS
+            )));
+        }
+        let from = to_id_address(rt, params.from, false)?;
+        let to = to_id_address(rt, params.to, false)?;
+        let subscriptions = params
+            .subscriptions
+            .into_iter()
+            .map(|sub| {
+                let id =
+                    SubscriptionId::for_machine_object(&params.machine, &sub.key, sub.version)?;
+                Ok((sub.hash, id))
+            })
+            .collect::<Result<Vec<_>, ActorError>>()?;
+
+        let config = get_config(rt)?;
+
+        let moved = rt.transaction(|st: &mut State, rt| {
+            st.transfer_subscriptions(
+                &config,
+                rt.store(),
+                machine,
+                from,
+                to,
+                rt.curr_epoch(),
+                subscriptions,
+            )
+        })?;
+        Ok(moved as u64)
+    }
+
     /// Deletes a blob subscription and adds another in a sinlge call.
     ///
     /// This method is more efficient than two separate calls to `delete_blob` and `add_blob`,
@@ -829,13 +1195,21 @@ impl ActorCode for BlobsActor {
         BuyCredit => buy_credit,
         ApproveCredit => approve_credit,
         RevokeCredit => revoke_credit,
+        TransferCredit => transfer_credit,
+        SellCredit => sell_credit,
         SetAccountSponsor => set_account_sponsor,
+        SetReadPolicy => set_read_policy,
+        SetGasSponsorPolicy => set_gas_sponsor_policy,
+        GetReadCharge => get_read_charge,
         GetAccount => get_account,
         GetCreditApproval => get_credit_approval,
         AddBlob => add_blob,
         GetBlob => get_blob,
         DeleteBlob => delete_blob,
         OverwriteBlob => overwrite_blob,
+        SetBlobAutoRenew => set_blob_auto_renew,
+        ApproveSubscriptionTransfer => approve_subscription_transfer,
+        TransferSubscriptions => transfer_subscriptions,
 
         // System methods
         GetGasAllowance => get_gas_allowance,
@@ -846,6 +1220,11 @@ impl ActorCode for BlobsActor {
         SetBlobPending => set_blob_pending,
         FinalizeBlob => finalize_blob,
         DebitAccounts => debit_accounts,
+        IssueChallenges => issue_challenges,
+        GetOpenChallenges => get_open_challenges,
+        AnswerChallenge => answer_challenge,
+        CloseChallenge => close_challenge,
+        ChargeRead => charge_read,
 
         // Admin methods
         SetAccountStatus => set_account_status,
@@ -1314,6 +1693,83 @@ mod tests {
         rt.verify();
     }
 
+    #[test]
+    fn test_sell_credit() {
+        let rt = construct_and_verify();
+
+        let id_addr = Address::new_id(110);
+        let eth_addr = EthAddress(hex_literal::hex!(
+            "CAFEB0BA00000000000000000000000000000000"
+        ));
+        let f4_eth_addr = Address::new_delegated(10, &eth_addr.0).unwrap();
+        rt.set_delegated_address(id_addr.id().unwrap(), f4_eth_addr);
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, id_addr);
+        rt.set_origin(id_addr);
+
+        // Buy some credit
+        let tokens = TokenAmount::from_whole(1);
+        rt.set_received(tokens.clone());
+        rt.set_balance(tokens.clone());
+        rt.expect_validate_caller_any();
+        let fund_params = BuyCreditParams(f4_eth_addr);
+        expect_get_config(&rt);
+        let credits = tokens.clone() * &RecallConfig::default().token_credit_rate;
+        expect_emitted_purchase_event(&rt, &fund_params, credits.clone());
+        let result = rt.call::<BlobsActor>(
+            Method::BuyCredit as u64,
+            IpldBlock::serialize_cbor(&fund_params).unwrap(),
+        );
+        assert!(result.is_ok());
+        rt.verify();
+        rt.set_received(TokenAmount::zero());
+
+        // Selling is disabled by default
+        rt.expect_validate_caller_any();
+        expect_get_config(&rt);
+        let sell_params = SellCreditParams {
+            from: f4_eth_addr,
+            amount: credits.clone(),
+        };
+        let result = rt.call::<BlobsActor>(
+            Method::SellCredit as u64,
+            IpldBlock::serialize_cbor(&sell_params).unwrap(),
+        );
+        assert_eq!(result.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+        rt.verify();
+
+        // Sell all the credit at a 10% discount
+        let config = RecallConfig {
+            credit_sell_discount_bps: Some(1000),
+            ..Default::default()
+        };
+        let refund = TokenAmount::from_nano(900_000_000);
+        rt.expect_validate_caller_any();
+        rt.expect_send(
+            RECALL_CONFIG_ACTOR_ADDR,
+            fendermint_actor_recall_config_shared::Method::GetConfig as MethodNum,
+            None,
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&config).unwrap(),
+            ExitCode::OK,
+            None,
+        );
+        rt.expect_send_simple(id_addr, METHOD_SEND, None, refund, None, ExitCode::OK);
+        let result = rt
+            .call::<BlobsActor>(
+                Method::SellCredit as u64,
+                IpldBlock::serialize_cbor(&sell_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Account>()
+            .unwrap();
+        assert!(result.credit_free.is_zero());
+        assert!(result.gas_allowance.is_zero());
+        rt.verify();
+    }
+
     #[test]
     fn test_add_blob() {
         let rt = construct_and_verify();
@@ -1615,4 +2071,95 @@ mod tests {
         assert!(response.is_err());
         rt.verify();
     }
+
+    #[test]
+    fn test_charge_read() {
+        let rt = construct_and_verify();
+
+        let id_addr = Address::new_id(110);
+        let eth_addr = EthAddress(hex_literal::hex!(
+            "CAFEB0BA00000000000000000000000000000000"
+        ));
+        let f4_eth_addr = Address::new_delegated(10, &eth_addr.0).unwrap();
+        rt.set_delegated_address(id_addr.id().unwrap(), f4_eth_addr);
+
+        // Buy some credit
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, id_addr);
+        rt.set_origin(id_addr);
+        rt.set_received(TokenAmount::from_whole(1));
+        rt.expect_validate_caller_any();
+        let fund_params = BuyCreditParams(f4_eth_addr);
+        let credits = Credit::from_atto(TokenAmount::from_whole(1).atto().clone())
+            * &RecallConfig::default().token_credit_rate;
+        expect_get_config(&rt);
+        expect_emitted_purchase_event(&rt, &fund_params, credits);
+        let account = rt
+            .call::<BlobsActor>(
+                Method::BuyCredit as u64,
+                IpldBlock::serialize_cbor(&fund_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Account>()
+            .unwrap();
+        rt.verify();
+        rt.set_received(TokenAmount::zero());
+
+        // Readers cannot charge themselves
+        let (hash, size) = new_hash(1024);
+        let charge_params = ChargeReadParams {
+            hash,
+            owner: None,
+            reader: f4_eth_addr,
+            size,
+        };
+        rt.expect_validate_caller_addr(vec![BLOB_READER_ACTOR_ADDR]);
+        let result = rt.call::<BlobsActor>(
+            Method::ChargeRead as u64,
+            IpldBlock::serialize_cbor(&charge_params).unwrap(),
+        );
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+        rt.verify();
+
+        // The read charge can be checked by anyone
+        let expected = ReadCharge {
+            payer: Some(id_addr),
+            credit: Credit::from_whole(size),
+        };
+        rt.expect_validate_caller_any();
+        expect_get_config(&rt);
+        let charge = rt
+            .call::<BlobsActor>(
+                Method::GetReadCharge as u64,
+                IpldBlock::serialize_cbor(&charge_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<ReadCharge>()
+            .unwrap();
+        assert_eq!(charge, expected);
+        rt.verify();
+
+        // The blob reader charges the reader
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, BLOB_READER_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![BLOB_READER_ACTOR_ADDR]);
+        expect_get_config(&rt);
+        let charge = rt
+            .call::<BlobsActor>(
+                Method::ChargeRead as u64,
+                IpldBlock::serialize_cbor(&charge_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<ReadCharge>()
+            .unwrap();
+        assert_eq!(charge, expected);
+        rt.verify();
+
+        let state = rt.get_state::<State>();
+        let after = state.get_account(rt.store(), id_addr).unwrap().unwrap();
+        assert_eq!(after.credit_free, &account.credit_free - &expected.credit);
+        assert_eq!(state.credit_debited, expected.credit);
+    }
 }
@@ -5,12 +5,14 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 
-use fendermint_actor_blobs_shared::params::GetStatsReturn;
+use fendermint_actor_blobs_shared::params::{GetStatsReturn, ReadCharge};
 use fendermint_actor_blobs_shared::state::{
-    Account, Blob, BlobStatus, Credit, CreditApproval, GasAllowance, Hash, PublicKey, Subscription,
-    SubscriptionGroup, SubscriptionId, TokenCreditRate, TtlStatus,
+    Account, Blob, BlobStatus, Challenge, Credit, CreditApproval, GasAllowance, GasSponsorPolicy,
+    GasSponsorUsage, Hash, PublicKey, ReadPolicy, Subscription, SubscriptionGroup, SubscriptionId,
+    TokenCreditRate, TtlStatus,
 };
-use fendermint_actor_recall_config_shared::RecallConfig;
+use fendermint_actor_blobs_shared::{BLOB_CHALLENGE_CHUNK_SIZE, BLOB_CHALLENGE_WINDOW};
+use fendermint_actor_recall_config_shared::{RecallConfig, BASIS_POINTS};
 use fil_actors_runtime::ActorError;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::tuple::*;
@@ -19,19 +21,25 @@ use fvm_shared::address::Address;
 use fvm_shared::bigint::BigInt;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
 use log::{debug, warn};
 use num_traits::{ToPrimitive, Zero};
 use recall_ipld::hamt::{BytesKey, MapKey};
 
 mod accounts;
 mod blobs;
+mod challenges;
 mod expiries;
 
 use accounts::AccountsState;
 use blobs::{BlobsProgressCollection, BlobsState};
+use challenges::{ChallengeRng, ChallengesState, MAX_CHALLENGE_SKIP};
 use expiries::{ExpiriesState, ExpiryUpdate};
 
 /// The state represents all accounts and stored blobs.
+///
+/// Fields after `blobs` default when missing, so state stored before they were added
+/// still decodes.
 #[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct State {
     /// The total used storage capacity of the subnet.
@@ -52,6 +60,16 @@ pub struct State {
     pub accounts: AccountsState,
     /// HAMT containing all blobs keyed by blob hash.
     pub blobs: BlobsState,
+    /// Open storage challenges.
+    #[serde(default)]
+    pub challenges: ChallengesState,
+    /// The storage price multiplier in basis points driven by subnet utilisation.
+    #[serde(default = "default_storage_multiplier_bps")]
+    pub storage_multiplier_bps: u64,
+}
+
+fn default_storage_multiplier_bps() -> u64 {
+    BASIS_POINTS as u64
 }
 
 /// Key used to namespace subscriptions in the expiry index.
@@ -109,6 +127,16 @@ impl<'a> CreditDelegation<'a> {
     }
 }
 
+/// The time-to-live of a subscription being added.
+enum SubscriptionTtl {
+    /// A TTL requested by the subscriber, or the default TTL if not set.
+    /// It must be within the configured limits, and is charged the storage surcharge.
+    Requested(Option<ChainEpoch>),
+    /// The remaining TTL of a subscription moved from another subscriber, which already paid
+    /// the surcharge. It's only capped by the subscriber's max TTL.
+    Transferred(ChainEpoch),
+}
+
 impl State {
     pub fn new<BS: Blockstore>(store: &BS) -> anyhow::Result<Self, ActorError> {
         Ok(Self {
@@ -121,6 +149,8 @@ impl State {
             pending: BlobsProgressCollection::new(store, "pending blobs queue")?,
             accounts: AccountsState::new(store)?,
             blobs: BlobsState::new(store)?,
+            challenges: ChallengesState::default(),
+            storage_multiplier_bps: default_storage_multiplier_bps(),
         })
     }
 
@@ -139,6 +169,7 @@ impl State {
             bytes_added: self.added.bytes_size(),
             num_resolving: self.pending.len(),
             bytes_resolving: self.pending.bytes_size(),
+            storage_multiplier_bps: self.storage_multiplier_bps,
         }
     }
 
@@ -169,6 +200,7 @@ impl State {
         let mut account =
             accounts.get_or_create(&to, || Account::new(current_epoch, config.blob_default_ttl))?;
         account.credit_free += &credits;
+        account.credit_backing += &amount;
         account.gas_allowance += amount;
         // Save account
         self.accounts
@@ -207,8 +239,38 @@ impl State {
             let gas_required = -add_amount.clone();
             ensure_gas_limit(&addr, current_epoch, &gas_required, &delegation)?;
         }
+        // Track gas fees covered under the sponsor's policy
+        if sponsor.is_some() {
+            if let Some(policy) = &account.gas_sponsor_policy {
+                let window_start = policy.window_start(current_epoch);
+                let used = account.gas_sponsor_usage.used_in(window_start);
+                if add_amount.is_negative() {
+                    if let Some(remaining) =
+                        policy.window_remaining(&account.gas_sponsor_usage, current_epoch)
+                    {
+                        if remaining < -add_amount.clone() {
+                            return Err(ActorError::insufficient_funds(format!(
+                                "gas sponsor policy of {} has insufficient budget (available: {}; required: {})",
+                                addr,
+                                remaining,
+                                -add_amount.clone()
+                            )));
+                        }
+                    }
+                }
+                account.gas_sponsor_usage = GasSponsorUsage {
+                    window_start,
+                    used: (used - &add_amount).max(TokenAmount::zero()),
+                };
+            }
+        }
 
         account.gas_allowance += &add_amount.clone();
+        // Spent gas allowance no longer backs the account's credit
+        account.credit_backing = account
+            .credit_backing
+            .clone()
+            .min(account.gas_allowance.clone());
         // Update credit approval
         if let Some(delegation) = delegation {
             let origin = delegation.origin;
@@ -368,6 +430,105 @@ impl State {
         Ok(())
     }
 
+    /// Moves free credit from one account to another.
+    ///
+    /// The receiving account is created if it doesn't exist.
+    /// Returns the updated sender account.
+    pub fn transfer_credit<BS: Blockstore>(
+        &mut self,
+        config: &RecallConfig,
+        store: &BS,
+        from: Address,
+        to: Address,
+        amount: Credit,
+        current_epoch: ChainEpoch,
+    ) -> anyhow::Result<Account, ActorError> {
+        if !amount.is_positive() {
+            return Err(ActorError::illegal_argument(
+                "credit amount must be positive".into(),
+            ));
+        }
+        if from == to {
+            return Err(ActorError::illegal_argument(
+                "cannot transfer credit to the same account".into(),
+            ));
+        }
+        // Get the accounts
+        let mut accounts = self.accounts.hamt(store)?;
+        let mut from_account = accounts.get_or_err(&from)?;
+        ensure_enough_credits(&from, &from_account.credit_free, &amount)?;
+        let mut to_account =
+            accounts.get_or_create(&to, || Account::new(current_epoch, config.blob_default_ttl))?;
+        from_account.credit_free -= &amount;
+        to_account.credit_free += &amount;
+        // Save accounts
+        accounts.set(&from, from_account.clone())?;
+        accounts.set(&to, to_account)?;
+        self.accounts.save_tracked(accounts.flush_tracked()?);
+
+        debug!("transferred {} credits from {} to {}", amount, from, to);
+        Ok(from_account)
+    }
+
+    /// Sells free credit back to the subnet.
+    ///
+    /// The credit is converted to tokens at the current token credit rate, less the configured
+    /// sell discount. The value sold can't exceed the tokens the account paid for credit that
+    /// still back it, see [`Account::credit_backing`].
+    /// Tokens are not transferred here; the caller is responsible for paying out
+    /// the returned refund.
+    /// Returns the updated account and the token refund.
+    pub fn sell_credit<BS: Blockstore>(
+        &mut self,
+        config: &RecallConfig,
+        store: &BS,
+        from: Address,
+        amount: Credit,
+    ) -> anyhow::Result<(Account, TokenAmount), ActorError> {
+        let Some(discount_bps) = config.credit_sell_discount_bps else {
+            return Err(ActorError::forbidden("selling credit is disabled".into()));
+        };
+        if !amount.is_positive() {
+            return Err(ActorError::illegal_argument(
+                "credit amount must be positive".into(),
+            ));
+        }
+        let value: TokenAmount = &amount / &config.token_credit_rate;
+        let refund = TokenAmount::from_atto(
+            value.atto() * BASIS_POINTS.saturating_sub(discount_bps) / BASIS_POINTS,
+        );
+        if !refund.is_positive() {
+            return Err(ActorError::illegal_argument(format!(
+                "credit amount {} is too small to sell",
+                amount
+            )));
+        }
+        // Get the account
+        let mut accounts = self.accounts.hamt(store)?;
+        let mut account = accounts.get_or_err(&from)?;
+        ensure_enough_credits(&from, &account.credit_free, &amount)?;
+        if value > account.credit_backing {
+            return Err(ActorError::insufficient_funds(format!(
+                "account {} can only sell credit worth up to {} (required: {})",
+                from, account.credit_backing, value
+            )));
+        }
+        account.credit_free -= &amount;
+        // The account no longer holds the value it paid for this credit
+        account.credit_backing -= &value;
+        account.gas_allowance -= &value;
+        self.credit_sold -= &amount;
+        // Save account
+        self.accounts
+            .save_tracked(accounts.set_and_flush_tracked(&from, account.clone())?);
+
+        debug!(
+            "bought back {} credits from {} for {}",
+            amount, from, refund
+        );
+        Ok((account, refund))
+    }
+
     pub fn get_account<BS: Blockstore>(
         &self,
         store: &BS,
@@ -393,11 +554,16 @@ impl State {
     }
 
     /// Returns the gas allowance for the given address, including an amount from a default sponsor.
+    ///
+    /// The sponsored amount is limited by the sponsor's gas sponsor policy for a message
+    /// to `to` calling `method_num`, and is zero if the policy does not cover the message.
     /// An error returned from this method would be fatal, as it's called from the FVM executor.
     pub fn get_gas_allowance<BS: Blockstore>(
         &self,
         store: &BS,
         from: Address,
+        to: Address,
+        method_num: MethodNum,
         current_epoch: ChainEpoch,
     ) -> anyhow::Result<GasAllowance, ActorError> {
         // Get the account or return default allowance
@@ -427,12 +593,25 @@ impl State {
                     }
                     let gas_allowance = sponsor.gas_allowance.clone();
                     let used = approval.gas_fee_used.clone();
-                    let amount = approval
+                    let mut amount = approval
                         .gas_fee_limit
                         .clone()
                         .map_or(gas_allowance.clone(), |limit| {
                             (limit - used).min(gas_allowance)
                         });
+                    if let Some(policy) = &sponsor.gas_sponsor_policy {
+                        if !policy.covers(&to, method_num) {
+                            return None;
+                        }
+                        if let Some(limit) = &policy.message_limit {
+                            amount = amount.min(limit.clone());
+                        }
+                        if let Some(remaining) =
+                            policy.window_remaining(&sponsor.gas_sponsor_usage, current_epoch)
+                        {
+                            amount = amount.min(remaining);
+                        }
+                    }
                     Some(amount)
                 })
                 .unwrap_or(TokenAmount::zero());
@@ -466,6 +645,219 @@ impl State {
         Ok(())
     }
 
+    pub fn set_gas_sponsor_policy<BS: Blockstore>(
+        &mut self,
+        config: &RecallConfig,
+        store: &BS,
+        from: Address,
+        policy: Option<GasSponsorPolicy>,
+        current_epoch: ChainEpoch,
+    ) -> anyhow::Result<(), ActorError> {
+        if let Some(policy) = &policy {
+            if policy.window_budget.is_some() && policy.window_length <= 0 {
+                return Err(ActorError::illegal_argument(
+                    "gas sponsor budget window length must be positive".into(),
+                ));
+            }
+        }
+        // Get or create a new account
+        let mut accounts = self.accounts.hamt(store)?;
+        let mut account = accounts.get_or_create(&from, || {
+            Account::new(current_epoch, config.blob_default_ttl)
+        })?;
+        account.gas_sponsor_policy = policy;
+        // Fees covered under a previous policy don't count against a new budget
+        account.gas_sponsor_usage = GasSponsorUsage::default();
+        // Save account
+        self.accounts
+            .save_tracked(accounts.set_and_flush_tracked(&from, account)?);
+
+        debug!("set gas sponsor policy for {}", from);
+        Ok(())
+    }
+
+    pub fn set_read_policy<BS: Blockstore>(
+        &mut self,
+        config: &RecallConfig,
+        store: &BS,
+        from: Address,
+        policy: ReadPolicy,
+        current_epoch: ChainEpoch,
+    ) -> anyhow::Result<(), ActorError> {
+        // Get or create a new account
+        let mut accounts = self.accounts.hamt(store)?;
+        let mut account = accounts.get_or_create(&from, || {
+            Account::new(current_epoch, config.blob_default_ttl)
+        })?;
+        account.read_policy = policy;
+        // Save account
+        self.accounts
+            .save_tracked(accounts.set_and_flush_tracked(&from, account)?);
+
+        debug!("set read policy for {} to {}", from, policy);
+        Ok(())
+    }
+
+    /// Approves or revokes the transfer of `from`'s blob subscriptions held by `machine` to `to`.
+    ///
+    /// The approval lasts until it's revoked, so the machine can move the subscriptions with
+    /// several calls to [`State::transfer_subscriptions`].
+    pub fn approve_subscription_transfer<BS: Blockstore>(
+        &mut self,
+        config: &RecallConfig,
+        store: &BS,
+        from: Address,
+        machine: Address,
+        to: Option<Address>,
+        current_epoch: ChainEpoch,
+    ) -> anyhow::Result<(), ActorError> {
+        // Get or create a new account
+        let mut accounts = self.accounts.hamt(store)?;
+        let mut account = accounts.get_or_create(&from, || {
+            Account::new(current_epoch, config.blob_default_ttl)
+        })?;
+        match to {
+            Some(to) => account
+                .subscription_transfers
+                .insert(machine.to_string(), to),
+            None => account.subscription_transfers.remove(&machine.to_string()),
+        };
+        // Save account
+        self.accounts
+            .save_tracked(accounts.set_and_flush_tracked(&from, account)?);
+
+        debug!(
+            "set subscription transfer approval from {} for {} to {:?}",
+            from, machine, to
+        );
+        Ok(())
+    }
+
+    /// Returns who pays for reading `size` bytes of a blob, and how much.
+    ///
+    /// If `owner` is set, it must be a subscriber of the blob, and its read policy applies.
+    /// Otherwise, the reader pays.
+    /// Fails if the payer cannot afford the read.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_read_charge<BS: Blockstore>(
+        &self,
+        config: &RecallConfig,
+        store: &BS,
+        hash: Hash,
+        owner: Option<Address>,
+        reader: Address,
+        size: u64,
+        current_epoch: ChainEpoch,
+    ) -> anyhow::Result<ReadCharge, ActorError> {
+        let accounts = self.accounts.hamt(store)?;
+        let payer = match owner {
+            None => Some(reader),
+            Some(owner) => {
+                let blobs = self.blobs.hamt(store)?;
+                let blob = blobs
+                    .get(&hash)?
+                    .ok_or(ActorError::not_found(format!("blob {} not found", hash)))?;
+                if !blob.subscribers.contains_key(&owner.to_string()) {
+                    return Err(ActorError::forbidden(format!(
+                        "blob {} is not owned by {}",
+                        hash, owner
+                    )));
+                }
+                let owner_account = accounts.get_or_err(&owner)?;
+                match owner_account.read_policy {
+                    ReadPolicy::Free => None,
+                    ReadPolicy::Reader => Some(reader),
+                    ReadPolicy::Owner => {
+                        if owner != reader {
+                            // Look for an approval for reader from owner
+                            let mut approval = owner_account
+                                .approvals_to
+                                .get(&reader.to_string())
+                                .cloned()
+                                .ok_or(ActorError::forbidden(format!(
+                                    "approval from {} to {} not found",
+                                    owner, reader
+                                )))?;
+                            let credit = Credit::from_whole(self.get_read_cost(config, size));
+                            ensure_delegated_credit(
+                                &owner,
+                                current_epoch,
+                                &credit,
+                                &Some(CreditDelegation::new(reader, &mut approval)),
+                            )?;
+                        }
+                        Some(owner)
+                    }
+                }
+            }
+        };
+        let Some(payer) = payer else {
+            return Ok(ReadCharge {
+                payer: None,
+                credit: Credit::zero(),
+            });
+        };
+        let credit = Credit::from_whole(self.get_read_cost(config, size));
+        let account = accounts.get_or_err(&payer)?;
+        ensure_enough_credits(&payer, &account.credit_free, &credit)?;
+        Ok(ReadCharge {
+            payer: Some(payer),
+            credit,
+        })
+    }
+
+    /// Charges for reading `size` bytes of a blob.
+    /// See [`State::get_read_charge`] for who pays.
+    #[allow(clippy::too_many_arguments)]
+    pub fn charge_read<BS: Blockstore>(
+        &mut self,
+        config: &RecallConfig,
+        store: &BS,
+        hash: Hash,
+        owner: Option<Address>,
+        reader: Address,
+        size: u64,
+        current_epoch: ChainEpoch,
+    ) -> anyhow::Result<ReadCharge, ActorError> {
+        let charge =
+            self.get_read_charge(config, store, hash, owner, reader, size, current_epoch)?;
+        let Some(payer) = charge.payer else {
+            return Ok(charge);
+        };
+        let mut accounts = self.accounts.hamt(store)?;
+        let mut account = accounts.get_or_err(&payer)?;
+        account.credit_free -= &charge.credit;
+        // Update credit approval
+        if payer != reader {
+            let approval = account.approvals_to.get_mut(&reader.to_string()).ok_or(
+                ActorError::illegal_state(format!(
+                    "approval from {} to {} not found",
+                    payer, reader
+                )),
+            )?;
+            approval.credit_used += &charge.credit;
+            let mut reader_account = accounts.get_or_err(&reader)?;
+            let reader_approval = reader_account
+                .approvals_from
+                .get_mut(&payer.to_string())
+                .ok_or(ActorError::illegal_state(format!(
+                    "approval from {} to {} not found in 'to' account",
+                    payer, reader
+                )))?;
+            reader_approval.credit_used += &charge.credit;
+            accounts.set(&reader, reader_account)?;
+        }
+        accounts.set(&payer, account)?;
+        self.accounts.save_tracked(accounts.flush_tracked()?);
+        self.credit_debited += &charge.credit;
+
+        debug!(
+            "debited {} credits from {} for reading {} bytes of blob {}",
+            charge.credit, payer, size, hash
+        );
+        Ok(charge)
+    }
+
     pub fn set_account_status<BS: Blockstore>(
         &mut self,
         config: &RecallConfig,
@@ -500,14 +892,20 @@ impl State {
     #[allow(clippy::type_complexity)]
     pub fn debit_accounts<BS: Blockstore>(
         &mut self,
+        config: &RecallConfig,
         store: &BS,
         current_epoch: ChainEpoch,
     ) -> anyhow::Result<HashSet<Hash>, ActorError> {
-        // Delete expired subscriptions
+        // Renew or delete expired subscriptions
         let mut delete_from_disc = HashSet::new();
         let mut num_deleted = 0;
+        let mut num_renewed = 0;
         let expiries = self.expiries.clone();
         expiries.foreach_up_to_epoch(store, current_epoch, |_, subscriber, key| {
+            if self.renew_blob(config, store, subscriber, current_epoch, &key)? {
+                num_renewed += 1;
+                return Ok(());
+            }
             match self.delete_blob(
                 store,
                 subscriber,
@@ -531,6 +929,7 @@ impl State {
             }
             Ok(())
         })?;
+        debug!("renewed {} expired subscriptions", num_renewed);
         debug!("deleted {} expired subscriptions", num_deleted);
         debug!(
             "{} blobs marked for deletion from disc",
@@ -553,9 +952,144 @@ impl State {
             Ok(())
         })?;
         self.accounts.root = writer.flush()?;
+        // Adjust the storage price to the utilisation over the last interval
+        self.storage_multiplier_bps = config.storage_pricing.next_utilization_multiplier_bps(
+            self.storage_multiplier_bps,
+            self.capacity_used,
+            config.blob_capacity,
+        );
         Ok(delete_from_disc)
     }
 
+    /// Renews an expired subscription if it has auto-renewal set.
+    ///
+    /// Returns `false` if the subscription should be deleted instead, which is also the case
+    /// when the subscriber doesn't have enough credit for the renewal.
+    fn renew_blob<BS: Blockstore>(
+        &mut self,
+        config: &RecallConfig,
+        store: &BS,
+        subscriber: Address,
+        current_epoch: ChainEpoch,
+        key: &ExpiryKey,
+    ) -> anyhow::Result<bool, ActorError> {
+        let Some(blob) = self.blobs.hamt(store)?.get(&key.hash)? else {
+            return Ok(false);
+        };
+        let Some(sub) = blob
+            .subscribers
+            .get(&subscriber.to_string())
+            .and_then(|group| group.subscriptions.get(&key.id.to_string()))
+        else {
+            return Ok(false);
+        };
+        let Some(ttl) = sub.auto_renew else {
+            return Ok(false);
+        };
+        if sub.failed {
+            return Ok(false);
+        }
+        // A failed renewal may have touched the subnet totals before erroring
+        let totals = (
+            self.capacity_used,
+            self.credit_sold.clone(),
+            self.credit_committed.clone(),
+            self.credit_debited.clone(),
+        );
+        match self.add_blob(
+            config,
+            store,
+            subscriber,
+            subscriber,
+            current_epoch,
+            key.hash,
+            blob.metadata_hash,
+            key.id.clone(),
+            blob.size,
+            Some(ttl),
+            sub.source,
+            TokenAmount::zero(),
+        ) {
+            Ok(_) => {
+                debug!(
+                    "renewed subscription to blob {} for {} (key: {})",
+                    key.hash, subscriber, key.id
+                );
+                Ok(true)
+            }
+            Err(e) => {
+                (
+                    self.capacity_used,
+                    self.credit_sold,
+                    self.credit_committed,
+                    self.credit_debited,
+                ) = totals;
+                debug!(
+                    "failed to renew subscription to blob {} for {} (key: {}): {}",
+                    key.hash, subscriber, key.id, e
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    /// Sets or unsets the auto-renewal of a blob subscription.
+    ///
+    /// @param origin - The address that is submitting the transaction.
+    ///   It must be the subscriber or have a credit approval from the subscriber.
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_blob_auto_renew<BS: Blockstore>(
+        &mut self,
+        config: &RecallConfig,
+        store: &BS,
+        origin: Address,
+        subscriber: Address,
+        hash: Hash,
+        id: SubscriptionId,
+        ttl: Option<ChainEpoch>,
+    ) -> anyhow::Result<Subscription, ActorError> {
+        if let Some(ttl) = ttl {
+            if ttl < config.blob_min_ttl {
+                return Err(ActorError::illegal_argument(format!(
+                    "minimum blob TTL is {}",
+                    config.blob_min_ttl
+                )));
+            }
+        }
+        if origin != subscriber {
+            let accounts = self.accounts.hamt(store)?;
+            let account = accounts.get_or_err(&subscriber)?;
+            if !account.approvals_to.contains_key(&origin.to_string()) {
+                return Err(ActorError::forbidden(format!(
+                    "approval from {} to {} not found",
+                    subscriber, origin
+                )));
+            }
+        }
+        let mut blobs = self.blobs.hamt(store)?;
+        let mut blob = blobs
+            .get(&hash)?
+            .ok_or(ActorError::not_found(format!("blob {} not found", hash)))?;
+        let sub = blob
+            .subscribers
+            .get_mut(&subscriber.to_string())
+            .and_then(|group| group.subscriptions.get_mut(&id.to_string()))
+            .ok_or(ActorError::not_found(format!(
+                "subscription id {} not found",
+                id
+            )))?;
+        sub.auto_renew = ttl;
+        let sub = sub.clone();
+        self.blobs
+            .save_tracked(blobs.set_and_flush_tracked(&hash, blob)?);
+
+        debug!(
+            "set auto-renew for blob {} for {} (key: {}) to {:?}",
+            hash, subscriber, id, ttl
+        );
+        Ok(sub)
+    }
+
     /// Add a blob.
     ///
     /// @param origin - The address that is submitting the transaction to add this blob.
@@ -579,33 +1113,69 @@ impl State {
         source: PublicKey,
         tokens_received: TokenAmount,
     ) -> anyhow::Result<(Subscription, TokenAmount), ActorError> {
-        // Get or create a new account
-        let mut accounts = self.accounts.hamt(store)?;
-        let mut account = accounts.get_or_create(&subscriber, || {
-            Account::new(current_epoch, config.blob_default_ttl)
-        })?;
-        // Validate the TTL
-        let ttl = self.validate_ttl(config, ttl, &account)?;
-        // Get the credit delegation if needed
-        let delegation =
-            if origin != subscriber {
-                // Look for an approval for origin from subscriber
-                let approval = account.approvals_to.get_mut(&origin.to_string()).ok_or(
-                    ActorError::forbidden(format!(
-                        "approval from {} to {} not found",
-                        subscriber, origin
-                    )),
-                )?;
-                Some(CreditDelegation::new(origin, approval))
-            } else {
-                None
-            };
-        // Capacity updates and required credit depend on whether the subscriber is already
-        // subscribing to this blob
-        let expiry = i64::saturating_add(current_epoch, ttl);
+        self.add_subscription(
+            config,
+            store,
+            origin,
+            subscriber,
+            current_epoch,
+            hash,
+            metadata_hash,
+            id,
+            size,
+            SubscriptionTtl::Requested(ttl),
+            source,
+            tokens_received,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_subscription<BS: Blockstore>(
+        &mut self,
+        config: &RecallConfig,
+        store: &BS,
+        origin: Address,
+        subscriber: Address,
+        current_epoch: ChainEpoch,
+        hash: Hash,
+        metadata_hash: Hash,
+        id: SubscriptionId,
+        size: u64,
+        ttl: SubscriptionTtl,
+        source: PublicKey,
+        tokens_received: TokenAmount,
+    ) -> anyhow::Result<(Subscription, TokenAmount), ActorError> {
+        // Get or create a new account
+        let mut accounts = self.accounts.hamt(store)?;
+        let mut account = accounts.get_or_create(&subscriber, || {
+            Account::new(current_epoch, config.blob_default_ttl)
+        })?;
+        // Validate the TTL
+        let (ttl, surcharged) = match ttl {
+            SubscriptionTtl::Requested(ttl) => (self.validate_ttl(config, ttl, &account)?, true),
+            SubscriptionTtl::Transferred(ttl) => (ttl.min(account.max_ttl), false),
+        };
+        // Get the credit delegation if needed
+        let delegation =
+            if origin != subscriber {
+                // Look for an approval for origin from subscriber
+                let approval = account.approvals_to.get_mut(&origin.to_string()).ok_or(
+                    ActorError::forbidden(format!(
+                        "approval from {} to {} not found",
+                        subscriber, origin
+                    )),
+                )?;
+                Some(CreditDelegation::new(origin, approval))
+            } else {
+                None
+            };
+        // Capacity updates and required credit depend on whether the subscriber is already
+        // subscribing to this blob
+        let expiry = i64::saturating_add(current_epoch, ttl);
         let mut new_capacity: u64 = 0;
         let mut new_account_capacity: u64 = 0;
         let credit_required: Credit;
+        let surcharge: Credit;
         // Like cashback but for sending unspent tokens back
         let tokens_unspent: TokenAmount;
         // Get or create a new blob
@@ -641,17 +1211,37 @@ impl State {
                 credit_required = Credit::from_whole(
                     self.get_storage_cost(new_group_expiry - group_expiry, &size),
                 );
+                // The surcharge is charged for the epochs added to the group, from its
+                // current expiry
+                let charged = if surcharged {
+                    self.get_storage_surcharge(config, &credit_required, ttl, size)
+                } else {
+                    Credit::zero()
+                };
+                // A shorter expiry settles back the unused surcharge for the epochs the group
+                // no longer covers, and carries over the rest to the subscription covering them
+                let (settled, covered, covered_epoch) =
+                    match group.subscriptions.get(&id.to_string()) {
+                        Some(sub) => {
+                            let (settled, covered) =
+                                sub.cut_surcharge(current_epoch, expiry, Some(new_group_expiry));
+                            let covered_epoch = current_epoch.max(sub.surcharge_epoch).max(expiry);
+                            (settled, covered, covered_epoch)
+                        }
+                        None => (Credit::zero(), Credit::zero(), current_epoch),
+                    };
+                surcharge = &charged - &settled;
                 tokens_unspent = ensure_credit_or_buy(
                     &mut account.credit_free,
                     &mut self.credit_sold,
-                    &credit_required,
+                    &(&credit_required + &surcharge),
                     &config.token_credit_rate,
                     &tokens_received,
                     &subscriber,
                     current_epoch,
                     &delegation,
                 )?;
-                if let Some(sub) = group.subscriptions.get_mut(&id.to_string()) {
+                let sub = if let Some(sub) = group.subscriptions.get_mut(&id.to_string()) {
                     // Update expiry index
                     if expiry != sub.expiry {
                         self.expiries.update_index(
@@ -662,6 +1252,14 @@ impl State {
                             vec![ExpiryUpdate::Add(expiry), ExpiryUpdate::Remove(sub.expiry)],
                         )?;
                     }
+                    // Keep the unused surcharge for the epochs that are not cut
+                    let kept = sub.unused_surcharge(current_epoch) - &settled - &covered;
+                    sub.surcharge_epoch = if kept.is_zero() {
+                        group_expiry
+                    } else {
+                        current_epoch.max(sub.surcharge_epoch)
+                    };
+                    sub.surcharge = kept + &charged;
                     sub.expiry = expiry;
                     // Overwrite source allows subscriber to retry resolving
                     sub.source = source;
@@ -680,6 +1278,9 @@ impl State {
                         source,
                         delegate: delegation.as_ref().map(|d| d.origin),
                         failed: false,
+                        auto_renew: None,
+                        surcharge: charged,
+                        surcharge_epoch: group_expiry,
                     };
                     group
                         .subscriptions
@@ -697,17 +1298,26 @@ impl State {
                         vec![ExpiryUpdate::Add(expiry)],
                     )?;
                     sub
+                };
+                if covered.is_positive() {
+                    group.carry_surcharge(&covered, covered_epoch);
                 }
+                sub
             } else {
                 new_account_capacity = size;
                 // One or more accounts have already committed credit.
                 // However, we still need to reserve the full required credit from the new
                 // subscriber, as the existing account(s) may decide to change the expiry or cancel.
                 credit_required = Credit::from_whole(self.get_storage_cost(ttl, &size));
+                surcharge = if surcharged {
+                    self.get_storage_surcharge(config, &credit_required, ttl, size)
+                } else {
+                    Credit::zero()
+                };
                 tokens_unspent = ensure_credit_or_buy(
                     &mut account.credit_free,
                     &mut self.credit_sold,
-                    &credit_required,
+                    &(&credit_required + &surcharge),
                     &config.token_credit_rate,
                     &tokens_received,
                     &subscriber,
@@ -721,6 +1331,9 @@ impl State {
                     source,
                     delegate: delegation.as_ref().map(|d| d.origin),
                     failed: false,
+                    auto_renew: None,
+                    surcharge: surcharge.clone(),
+                    surcharge_epoch: current_epoch,
                 };
                 blob.subscribers.insert(
                     subscriber.to_string(),
@@ -763,10 +1376,15 @@ impl State {
             }
             new_capacity = size;
             credit_required = Credit::from_whole(self.get_storage_cost(ttl, &size));
+            surcharge = if surcharged {
+                self.get_storage_surcharge(config, &credit_required, ttl, size)
+            } else {
+                Credit::zero()
+            };
             tokens_unspent = ensure_credit_or_buy(
                 &mut account.credit_free,
                 &mut self.credit_sold,
-                &credit_required,
+                &(&credit_required + &surcharge),
                 &config.token_credit_rate,
                 &tokens_received,
                 &subscriber,
@@ -780,6 +1398,9 @@ impl State {
                 source,
                 delegate: delegation.as_ref().map(|d| d.origin),
                 failed: false,
+                auto_renew: None,
+                surcharge: surcharge.clone(),
+                surcharge_epoch: current_epoch,
             };
             let blob = Blob {
                 size: size.to_u64().unwrap(),
@@ -828,6 +1449,9 @@ impl State {
         self.credit_committed += &credit_required;
         account.credit_committed += &credit_required;
         account.credit_free -= &credit_required;
+        // The difference to the base price is settled right away
+        self.credit_debited += &surcharge;
+        account.credit_free -= &surcharge;
         // Update credit approval
         if let Some(delegation) = delegation {
             let origin = delegation.origin;
@@ -840,8 +1464,8 @@ impl State {
                     subscriber, origin
                 )))?;
 
-            delegation.approval.credit_used += &credit_required;
-            origin_approval.credit_used += &credit_required;
+            delegation.approval.credit_used += &credit_required + &surcharge;
+            origin_approval.credit_used += &credit_required + &surcharge;
             // Save delegation origin account
             accounts.set(&origin, origin_account)?;
         }
@@ -853,6 +1477,18 @@ impl State {
         self.blobs
             .save_tracked(blobs.set_and_flush_tracked(&hash, blob)?);
 
+        if surcharge.is_positive() {
+            debug!(
+                "debited {} surcharge credits from {}",
+                surcharge, subscriber
+            );
+        } else if surcharge.is_negative() {
+            debug!(
+                "returned {} surcharge credits to {}",
+                surcharge.atto().magnitude(),
+                subscriber
+            );
+        }
         if credit_required.is_positive() {
             debug!("committed {} credits from {}", credit_required, subscriber);
         } else {
@@ -869,6 +1505,30 @@ impl State {
         ttl * BigInt::from(*size)
     }
 
+    /// Returns the credit charged on top of the base storage cost.
+    /// Volume discounts only reduce it, so it's never negative.
+    fn get_storage_surcharge(
+        &self,
+        config: &RecallConfig,
+        base: &Credit,
+        ttl: ChainEpoch,
+        size: u64,
+    ) -> Credit {
+        if !base.is_positive() {
+            return Credit::zero();
+        }
+        let multiplier_bps =
+            config
+                .storage_pricing
+                .multiplier_bps(self.storage_multiplier_bps, ttl, size);
+        let surcharge_bps = multiplier_bps.saturating_sub(BASIS_POINTS as u64);
+        Credit::from_atto(base.atto() * surcharge_bps / BASIS_POINTS)
+    }
+
+    fn get_read_cost(&self, config: &RecallConfig, size: u64) -> BigInt {
+        BigInt::from(config.blob_read_cost) * BigInt::from(size)
+    }
+
     pub fn get_blob<BS: Blockstore>(
         &self,
         store: &BS,
@@ -969,6 +1629,129 @@ impl State {
         Ok(())
     }
 
+    /// Issues up to `count` storage challenges for randomly selected resolved blobs.
+    ///
+    /// Blobs are selected by walking the blobs HAMT from a persistent cursor, skipping a
+    /// pseudo-random number of entries between challenges, so that every round is cheap and
+    /// all blobs are eventually covered.
+    /// The challenged byte range is a pseudo-random BLAKE3 chunk of the selected blob.
+    pub fn issue_challenges<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        current_epoch: ChainEpoch,
+        count: u32,
+        seed: u64,
+    ) -> anyhow::Result<Vec<Challenge>, ActorError> {
+        let count = (count as usize).min(self.challenges.available());
+        let blobs = self.blobs.hamt(store)?;
+        if count == 0 || blobs.is_empty() {
+            return Ok(Vec::new());
+        }
+        // The blob under the cursor may have been deleted since the last round
+        if let Some(cursor) = self.challenges.cursor {
+            if !blobs.contains_key(&cursor)? {
+                self.challenges.cursor = None;
+            }
+        }
+
+        let mut rng = ChallengeRng::new(seed);
+        let mut issued = Vec::with_capacity(count);
+        for _ in 0..count {
+            let skip = rng.next_u64() % MAX_CHALLENGE_SKIP;
+            let starting_key = self
+                .challenges
+                .cursor
+                .map(|h| BytesKey::from(h.0.as_slice()));
+            let mut candidate = None;
+            let (_, next_key) = blobs.for_each_ranged(
+                starting_key.as_ref(),
+                Some(skip as usize + 1),
+                |hash, blob| -> Result<(), ActorError> {
+                    if matches!(blob.status, BlobStatus::Resolved) && blob.size > 0 {
+                        candidate = Some((hash, blob.size));
+                    }
+                    Ok(())
+                },
+            )?;
+            // Wrap around once the end of the HAMT is reached
+            self.challenges.cursor = next_key;
+
+            let Some((hash, size)) = candidate else {
+                continue;
+            };
+            if issued.iter().any(|c: &Challenge| c.hash == hash) {
+                continue;
+            }
+            let num_chunks = size.div_ceil(BLOB_CHALLENGE_CHUNK_SIZE);
+            let offset = (rng.next_u64() % num_chunks) * BLOB_CHALLENGE_CHUNK_SIZE;
+            let length = BLOB_CHALLENGE_CHUNK_SIZE.min(size - offset);
+            let challenge = self.challenges.open(
+                hash,
+                size,
+                offset,
+                length,
+                current_epoch + BLOB_CHALLENGE_WINDOW,
+            );
+            debug!(
+                "issued challenge {} for blob {} (offset: {}; length: {})",
+                challenge.id, hash, offset, length
+            );
+            issued.push(challenge);
+        }
+        Ok(issued)
+    }
+
+    /// Returns all open storage challenges.
+    pub fn get_open_challenges(&self) -> Vec<Challenge> {
+        self.challenges.open.clone()
+    }
+
+    /// Records a validator's answer to an open storage challenge.
+    ///
+    /// Answers are only accepted before the challenge deadline. Recording the same validator
+    /// twice is a no-op.
+    pub fn answer_challenge(
+        &mut self,
+        current_epoch: ChainEpoch,
+        id: u64,
+        validator: Address,
+    ) -> anyhow::Result<(), ActorError> {
+        let challenge = self
+            .challenges
+            .get_mut(id)
+            .ok_or(ActorError::not_found(format!("challenge {} not found", id)))?;
+        if current_epoch >= challenge.deadline {
+            return Err(ActorError::forbidden(format!(
+                "challenge {} deadline has passed",
+                id
+            )));
+        }
+        if !challenge.answered.contains(&validator) {
+            challenge.answered.push(validator);
+        }
+        Ok(())
+    }
+
+    /// Closes a storage challenge.
+    ///
+    /// Returns the closed challenge, with the validators that answered it, if the challenged
+    /// blob is still stored in the [`BlobStatus::Resolved`] state.
+    /// If it is not, e.g., because it expired or was deleted while the challenge was open,
+    /// validators cannot be expected to hold it, and answers should not be counted.
+    pub fn close_challenge<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        id: u64,
+    ) -> anyhow::Result<Option<Challenge>, ActorError> {
+        let challenge = self
+            .challenges
+            .close(id)
+            .ok_or(ActorError::not_found(format!("challenge {} not found", id)))?;
+        let blob = self.get_blob(store, challenge.hash)?;
+        let is_stored = blob.is_some_and(|b| matches!(b.status, BlobStatus::Resolved));
+        Ok(is_stored.then_some(challenge))
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn finalize_blob<BS: Blockstore>(
         &mut self,
@@ -1137,6 +1920,22 @@ impl State {
         current_epoch: ChainEpoch,
         hash: Hash,
         id: SubscriptionId,
+    ) -> anyhow::Result<(bool, u64), ActorError> {
+        self.delete_subscription(store, origin, subscriber, current_epoch, hash, id, true)
+    }
+
+    /// Deletes a blob subscription.
+    /// If `settle_surcharge` is true, the unused part of the surcharge is settled back.
+    #[allow(clippy::too_many_arguments)]
+    fn delete_subscription<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        origin: Address,
+        subscriber: Address,
+        current_epoch: ChainEpoch,
+        hash: Hash,
+        id: SubscriptionId,
+        settle_surcharge: bool,
     ) -> anyhow::Result<(bool, u64), ActorError> {
         // Get or create a new account
         let mut accounts = self.accounts.hamt(store)?;
@@ -1261,7 +2060,20 @@ impl State {
         // Account for reclaimed size and move committed credit to free credit
         // If blob failed, capacity and committed credits have already been returned
         let size = blob.size;
+        let mut covered = None;
         if !matches!(blob.status, BlobStatus::Failed) && !sub.failed {
+            // Settle the unused surcharge for the epochs the group no longer covers, and carry
+            // over the rest to the subscription covering them
+            let surcharge = if settle_surcharge {
+                let (settled, carried) =
+                    sub.cut_surcharge(current_epoch, current_epoch, new_group_expiry);
+                if carried.is_positive() {
+                    covered = Some((carried, current_epoch.max(sub.surcharge_epoch)));
+                }
+                settled
+            } else {
+                Credit::zero()
+            };
             // If there's no new group expiry, we can reclaim capacity.
             if new_group_expiry.is_none() {
                 account.capacity_used -= &size;
@@ -1275,15 +2087,17 @@ impl State {
             // considering other subscriptions may still be active.
             if let Some(group_expiry) = group_expiry {
                 if account.last_debit_epoch < group_expiry {
-                    let reclaim_credits = Credit::from_whole(self.get_storage_cost(
+                    let committed_credits = Credit::from_whole(self.get_storage_cost(
                         group_expiry
                             - new_group_expiry.map_or(account.last_debit_epoch, |e| {
                                 e.max(account.last_debit_epoch)
                             }),
                         &size,
                     ));
-                    self.credit_committed -= &reclaim_credits;
-                    account.credit_committed -= &reclaim_credits;
+                    self.credit_committed -= &committed_credits;
+                    account.credit_committed -= &committed_credits;
+                    self.credit_debited -= &surcharge;
+                    let reclaim_credits = committed_credits + &surcharge;
                     account.credit_free += &reclaim_credits;
                     // Update credit approval
                     if let Some(delegation) = delegation {
@@ -1324,6 +2138,9 @@ impl State {
             .remove_source(store, hash, (subscriber, id.clone(), sub.source), size)?;
         // Delete subscription
         group.subscriptions.remove(&id.to_string());
+        if let Some((carried, epoch)) = covered {
+            group.carry_surcharge(&carried, epoch);
+        }
         debug!(
             "deleted subscription to blob {} for {} (key: {})",
             hash, subscriber, id
@@ -1351,6 +2168,99 @@ impl State {
         Ok((delete_blob, size))
     }
 
+    /// Moves blob subscriptions held by `machine` from subscriber `from` to subscriber `to`.
+    ///
+    /// `from` must have approved the transfer to `to` for the machine, and the subscription IDs
+    /// must be derived from the machine address with [`SubscriptionId::for_machine_object`].
+    /// Each subscription is added for `to` with its remaining TTL and auto-renewal before it's
+    /// deleted for `from`, so the blob is never dropped in between. `to` commits credit for the
+    /// remaining TTL, capped by its max TTL, and `from` is refunded the unused commitment.
+    /// The storage surcharge was paid by `from` when the subscription was added, so it's not
+    /// charged again, nor settled back to `from`.
+    /// Subscriptions to blobs that failed to resolve, and expired subscriptions, are only deleted.
+    /// Subscriptions that no longer exist are skipped.
+    /// Returns the number of subscriptions moved.
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer_subscriptions<BS: Blockstore>(
+        &mut self,
+        config: &RecallConfig,
+        store: &BS,
+        machine: Address,
+        from: Address,
+        to: Address,
+        current_epoch: ChainEpoch,
+        subscriptions: Vec<(Hash, SubscriptionId)>,
+    ) -> anyhow::Result<usize, ActorError> {
+        let account = self.accounts.hamt(store)?.get_or_err(&from)?;
+        if account.subscription_transfers.get(&machine.to_string()) != Some(&to) {
+            return Err(ActorError::forbidden(format!(
+                "{} has not approved the transfer of subscriptions held by {} to {}",
+                from, machine, to
+            )));
+        }
+
+        let mut moved = 0;
+        for (hash, id) in subscriptions {
+            let Some(blob) = self.blobs.hamt(store)?.get(&hash)? else {
+                debug!("skipping transfer of missing blob {}", hash);
+                continue;
+            };
+            let Some(sub) = blob
+                .subscribers
+                .get(&from.to_string())
+                .and_then(|group| group.subscriptions.get(&id.to_string()))
+                .cloned()
+            else {
+                debug!(
+                    "skipping transfer of missing subscription to blob {} (key: {})",
+                    hash, id
+                );
+                continue;
+            };
+            if matches!(blob.status, BlobStatus::Added | BlobStatus::Pending) {
+                return Err(ActorError::forbidden(format!(
+                    "blob {} pending finalization; please wait",
+                    hash
+                )));
+            }
+            let ttl = sub.expiry - current_epoch;
+            if !matches!(blob.status, BlobStatus::Failed) && !sub.failed && ttl > 0 {
+                self.add_subscription(
+                    config,
+                    store,
+                    to,
+                    to,
+                    current_epoch,
+                    hash,
+                    blob.metadata_hash,
+                    id.clone(),
+                    blob.size,
+                    SubscriptionTtl::Transferred(ttl),
+                    sub.source,
+                    TokenAmount::zero(),
+                )?;
+                if sub.auto_renew.is_some() {
+                    self.set_blob_auto_renew(
+                        config,
+                        store,
+                        to,
+                        to,
+                        hash,
+                        id.clone(),
+                        sub.auto_renew,
+                    )?;
+                }
+            }
+            self.delete_subscription(store, from, from, current_epoch, hash, id.clone(), false)?;
+            moved += 1;
+            debug!(
+                "transferred subscription to blob {} from {} to {} (key: {})",
+                hash, from, to, id
+            );
+        }
+        Ok(moved)
+    }
+
     /// Return available capacity as a difference between `blob_capacity_total` and `capacity_used`.
     fn capacity_available(&self, blob_capacity_total: u64) -> u64 {
         // Prevent underflow. We only care if free capacity is > 0 anyway.
@@ -1614,7 +2524,9 @@ mod tests {
     use fendermint_actor_blobs_testing::{
         new_address, new_hash, new_metadata_hash, new_pk, new_subscription_id, setup_logs,
     };
+    use fendermint_actor_recall_config_shared::StoragePricing;
     use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_shared::error::ExitCode;
     use rand::seq::SliceRandom;
     use rand::Rng;
     use std::collections::BTreeMap;
@@ -1666,6 +2578,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decode_legacy_state() {
+        let store = MemoryBlockstore::default();
+        let state = State::new(&store).unwrap();
+        // State stored before storage challenges and pricing only had these fields
+        let legacy = (
+            state.capacity_used,
+            &state.credit_sold,
+            &state.credit_committed,
+            &state.credit_debited,
+            &state.expiries,
+            &state.added,
+            &state.pending,
+            &state.accounts,
+            &state.blobs,
+        );
+        let bytes = fvm_ipld_encoding::to_vec(&legacy).unwrap();
+        let decoded: State = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded.challenges.next_id, 0);
+        assert!(decoded.challenges.open.is_empty());
+        assert_eq!(decoded.storage_multiplier_bps, BASIS_POINTS as u64);
+
+        // Accounts stored before read policies and gas sponsorship only had these fields
+        let account = Account::default();
+        let legacy = (
+            account.capacity_used,
+            &account.credit_free,
+            &account.credit_committed,
+            account.credit_sponsor,
+            account.last_debit_epoch,
+            &account.approvals_to,
+            &account.approvals_from,
+            account.max_ttl,
+            &account.gas_allowance,
+        );
+        let bytes = fvm_ipld_encoding::to_vec(&legacy).unwrap();
+        let decoded: Account = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, account);
+    }
+
     #[test]
     fn test_buy_credit_success() {
         setup_logs();
@@ -1912,6 +2864,141 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_gas_sponsor_policy() {
+        setup_logs();
+        let config = RecallConfig::default();
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let sponsor = new_address();
+        let from = new_address();
+        let target = new_address();
+        let current_epoch = ChainEpoch::from(5);
+        state
+            .buy_credit(
+                &config,
+                &store,
+                sponsor,
+                TokenAmount::from_whole(1),
+                current_epoch,
+            )
+            .unwrap();
+        state
+            .approve_credit(
+                &config,
+                &store,
+                sponsor,
+                from,
+                current_epoch,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        state
+            .set_account_sponsor(&config, &store, from, Some(sponsor), current_epoch)
+            .unwrap();
+
+        // Without a policy, all messages are sponsored
+        let allowance = state
+            .get_gas_allowance(&store, from, new_address(), 2, current_epoch)
+            .unwrap();
+        assert_eq!(allowance.sponsored_amount, TokenAmount::from_whole(1));
+
+        // A budget window must have a length
+        let res = state.set_gas_sponsor_policy(
+            &config,
+            &store,
+            sponsor,
+            Some(GasSponsorPolicy {
+                window_budget: Some(TokenAmount::from_atto(150)),
+                ..Default::default()
+            }),
+            current_epoch,
+        );
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_ILLEGAL_ARGUMENT);
+
+        state
+            .set_gas_sponsor_policy(
+                &config,
+                &store,
+                sponsor,
+                Some(GasSponsorPolicy {
+                    targets: vec![target],
+                    methods: vec![2],
+                    message_limit: Some(TokenAmount::from_atto(100)),
+                    window_budget: Some(TokenAmount::from_atto(150)),
+                    window_length: 10,
+                }),
+                current_epoch,
+            )
+            .unwrap();
+        let sponsored = |state: &State, to: Address, method_num: MethodNum, epoch: ChainEpoch| {
+            state
+                .get_gas_allowance(&store, from, to, method_num, epoch)
+                .unwrap()
+                .sponsored_amount
+        };
+
+        // Messages to other targets or methods are not sponsored
+        assert!(sponsored(&state, new_address(), 2, current_epoch).is_zero());
+        assert!(sponsored(&state, target, 3, current_epoch).is_zero());
+        // Covered messages are capped per message
+        assert_eq!(
+            sponsored(&state, target, 2, current_epoch),
+            TokenAmount::from_atto(100)
+        );
+
+        // Sponsored fees count against the window budget
+        state
+            .update_gas_allowance(
+                &store,
+                from,
+                Some(sponsor),
+                TokenAmount::from_atto(-100),
+                current_epoch,
+            )
+            .unwrap();
+        assert_eq!(
+            sponsored(&state, target, 2, current_epoch),
+            TokenAmount::from_atto(50)
+        );
+        let res = state.update_gas_allowance(
+            &store,
+            from,
+            Some(sponsor),
+            TokenAmount::from_atto(-60),
+            current_epoch,
+        );
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().exit_code(),
+            ExitCode::USR_INSUFFICIENT_FUNDS
+        );
+
+        // Refunds are returned to the budget
+        state
+            .update_gas_allowance(
+                &store,
+                from,
+                Some(sponsor),
+                TokenAmount::from_atto(40),
+                current_epoch,
+            )
+            .unwrap();
+        assert_eq!(
+            sponsored(&state, target, 2, current_epoch),
+            TokenAmount::from_atto(90)
+        );
+
+        // The budget resets in the next window
+        assert_eq!(
+            sponsored(&state, target, 2, current_epoch + 5),
+            TokenAmount::from_atto(100)
+        );
+    }
+
     #[test]
     fn test_revoke_credit_success() {
         setup_logs();
@@ -1956,6 +3043,274 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_transfer_credit_success() {
+        setup_logs();
+        let config = RecallConfig::default();
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let from = new_address();
+        let to = new_address();
+        let current_epoch = 1;
+
+        let amount = TokenAmount::from_whole(10);
+        state
+            .buy_credit(&config, &store, from, amount.clone(), current_epoch)
+            .unwrap();
+        let credit_sold = state.credit_sold.clone();
+
+        let transfer = Credit::from_whole(1_000);
+        let res = state.transfer_credit(&config, &store, from, to, transfer.clone(), current_epoch);
+        assert!(res.is_ok());
+        let from_account = res.unwrap();
+        assert_eq!(from_account.credit_free, &credit_sold - &transfer);
+        // Gas allowance stays with the buyer
+        assert_eq!(from_account.gas_allowance, amount);
+        let to_account = state.get_account(&store, to).unwrap().unwrap();
+        assert_eq!(to_account.credit_free, transfer);
+        assert_eq!(to_account.gas_allowance, TokenAmount::zero());
+        assert_eq!(to_account.max_ttl, config.blob_default_ttl);
+        // Transfers don't change the amount of credit in circulation
+        assert_eq!(state.credit_sold, credit_sold);
+    }
+
+    #[test]
+    fn test_transfer_credit_invalid() {
+        setup_logs();
+        let config = RecallConfig::default();
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let from = new_address();
+        let to = new_address();
+        let current_epoch = 1;
+
+        // Sender account doesn't exist
+        let res = state.transfer_credit(
+            &config,
+            &store,
+            from,
+            to,
+            Credit::from_whole(1),
+            current_epoch,
+        );
+        assert!(res.is_err());
+        assert_eq!(
+            res.err().unwrap().msg(),
+            format!("{} not found in accounts", from)
+        );
+
+        state
+            .buy_credit(
+                &config,
+                &store,
+                from,
+                TokenAmount::from_atto(1),
+                current_epoch,
+            )
+            .unwrap();
+        let credit_free = state
+            .get_account(&store, from)
+            .unwrap()
+            .unwrap()
+            .credit_free;
+
+        // Zero amount
+        let res = state.transfer_credit(&config, &store, from, to, Credit::zero(), current_epoch);
+        assert!(res.is_err());
+        assert_eq!(res.err().unwrap().msg(), "credit amount must be positive");
+
+        // Same account
+        let res = state.transfer_credit(
+            &config,
+            &store,
+            from,
+            from,
+            Credit::from_atto(1),
+            current_epoch,
+        );
+        assert!(res.is_err());
+        assert_eq!(
+            res.err().unwrap().msg(),
+            "cannot transfer credit to the same account"
+        );
+
+        // More than the sender has
+        let required = &credit_free + &Credit::from_atto(1);
+        let res = state.transfer_credit(&config, &store, from, to, required.clone(), current_epoch);
+        assert!(res.is_err());
+        let err = res.err().unwrap();
+        assert_eq!(err.exit_code(), ExitCode::USR_INSUFFICIENT_FUNDS);
+        assert_eq!(
+            err.msg(),
+            format!(
+                "account {} has insufficient credit (available: {}; required: {})",
+                from, credit_free, required
+            )
+        );
+        assert!(state.get_account(&store, to).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_sell_credit_success() {
+        setup_logs();
+        let config = RecallConfig {
+            credit_sell_discount_bps: Some(500), // 5%
+            ..Default::default()
+        };
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let from = new_address();
+        let current_epoch = 1;
+
+        let amount = TokenAmount::from_whole(1);
+        state
+            .buy_credit(&config, &store, from, amount.clone(), current_epoch)
+            .unwrap();
+        let credit_sold = state.credit_sold.clone();
+
+        // Sell half the credit back
+        let sell = credit_sold.div_floor(2);
+        let res = state.sell_credit(&config, &store, from, sell.clone());
+        assert!(res.is_ok());
+        let (account, refund) = res.unwrap();
+        assert_eq!(refund, TokenAmount::from_nano(475_000_000));
+        assert_eq!(account.credit_free, &credit_sold - &sell);
+        assert_eq!(account.gas_allowance, TokenAmount::from_nano(500_000_000));
+        assert_eq!(state.credit_sold, &credit_sold - &sell);
+        let account_back = state.get_account(&store, from).unwrap().unwrap();
+        assert_eq!(account, account_back);
+    }
+
+    #[test]
+    fn test_sell_credit_invalid() {
+        setup_logs();
+        let config = RecallConfig::default();
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let from = new_address();
+        let current_epoch = 1;
+
+        state
+            .buy_credit(
+                &config,
+                &store,
+                from,
+                TokenAmount::from_whole(1),
+                current_epoch,
+            )
+            .unwrap();
+        let credit_free = state
+            .get_account(&store, from)
+            .unwrap()
+            .unwrap()
+            .credit_free;
+
+        // Selling is disabled by default
+        let res = state.sell_credit(&config, &store, from, credit_free.clone());
+        assert!(res.is_err());
+        assert_eq!(res.err().unwrap().msg(), "selling credit is disabled");
+
+        let config = RecallConfig {
+            credit_sell_discount_bps: Some(0),
+            ..Default::default()
+        };
+
+        // Amount worth less than one atto token
+        let res = state.sell_credit(&config, &store, from, Credit::from_atto(1));
+        assert!(res.is_err());
+        assert_eq!(
+            res.err().unwrap().msg(),
+            format!(
+                "credit amount {} is too small to sell",
+                Credit::from_atto(1)
+            )
+        );
+
+        // More than the account has
+        let required = &credit_free + &Credit::from_whole(1);
+        let res = state.sell_credit(&config, &store, from, required);
+        assert!(res.is_err());
+        assert_eq!(
+            res.err().unwrap().exit_code(),
+            ExitCode::USR_INSUFFICIENT_FUNDS
+        );
+
+        // Selling everything without a discount refunds the full purchase
+        let (account, refund) = state
+            .sell_credit(&config, &store, from, credit_free)
+            .unwrap();
+        assert_eq!(refund, TokenAmount::from_whole(1));
+        assert!(account.credit_free.is_zero());
+        assert!(account.gas_allowance.is_zero());
+        assert!(account.credit_backing.is_zero());
+        assert!(state.credit_sold.is_zero());
+    }
+
+    #[test]
+    fn test_sell_credit_backing() {
+        setup_logs();
+        let config = RecallConfig {
+            credit_sell_discount_bps: Some(0),
+            ..Default::default()
+        };
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let (buyer, receiver) = (new_address(), new_address());
+        let current_epoch = 1;
+
+        let amount = TokenAmount::from_whole(1);
+        let account = state
+            .buy_credit(&config, &store, buyer, amount.clone(), current_epoch)
+            .unwrap();
+        assert_eq!(account.credit_backing, amount);
+        let half = account.credit_free.div_floor(2);
+
+        // Transferred credit can't be sold by the receiver
+        state
+            .transfer_credit(
+                &config,
+                &store,
+                buyer,
+                receiver,
+                half.clone(),
+                current_epoch,
+            )
+            .unwrap();
+        let res = state.sell_credit(&config, &store, receiver, half.clone());
+        assert!(res.is_err());
+        assert_eq!(
+            res.err().unwrap().exit_code(),
+            ExitCode::USR_INSUFFICIENT_FUNDS
+        );
+
+        // Credit whose gas allowance was spent can't be sold either
+        state
+            .update_gas_allowance(
+                &store,
+                buyer,
+                None,
+                TokenAmount::from_nano(-750_000_000),
+                current_epoch,
+            )
+            .unwrap();
+        let account = state.get_account(&store, buyer).unwrap().unwrap();
+        assert_eq!(account.credit_backing, TokenAmount::from_nano(250_000_000));
+        let res = state.sell_credit(&config, &store, buyer, half.clone());
+        assert!(res.is_err());
+        assert_eq!(
+            res.err().unwrap().exit_code(),
+            ExitCode::USR_INSUFFICIENT_FUNDS
+        );
+
+        // What's still backed can be sold
+        let (account, refund) = state
+            .sell_credit(&config, &store, buyer, half.div_floor(2))
+            .unwrap();
+        assert_eq!(refund, TokenAmount::from_nano(250_000_000));
+        assert!(account.credit_backing.is_zero());
+        assert!(account.gas_allowance.is_zero());
+    }
+
     #[test]
     fn test_debit_accounts_delete_from_disc() {
         setup_logs();
@@ -2155,7 +3510,7 @@ mod tests {
 
         // Debit all accounts at an epoch between the two expiries (3601-3621)
         let debit_epoch = ChainEpoch::from(config.blob_min_ttl + 11);
-        let deletes_from_disc = state.debit_accounts(&store, debit_epoch).unwrap();
+        let deletes_from_disc = state.debit_accounts(config, &store, debit_epoch).unwrap();
         assert!(deletes_from_disc.is_empty());
 
         // Check the account balance
@@ -2175,7 +3530,7 @@ mod tests {
 
         // Debit all accounts at an epoch greater than group expiry (3621)
         let debit_epoch = ChainEpoch::from(config.blob_min_ttl + 31);
-        let deletes_from_disc = state.debit_accounts(&store, debit_epoch).unwrap();
+        let deletes_from_disc = state.debit_accounts(config, &store, debit_epoch).unwrap();
         assert!(!deletes_from_disc.is_empty()); // blob is marked for deletion
 
         // Check the account balance
@@ -2188,23 +3543,140 @@ mod tests {
         assert_eq!(account.credit_free, credit_amount); // not changed
         assert_eq!(account.capacity_used, 0);
 
-        // Check state
-        assert_eq!(state.credit_committed, Credit::from_whole(0)); // credit was released
-        assert_eq!(
-            state.credit_debited,
-            token_amount * &config.token_credit_rate - &account.credit_free
-        );
-        assert_eq!(state.capacity_used, 0); // capacity was released
+        // Check state
+        assert_eq!(state.credit_committed, Credit::from_whole(0)); // credit was released
+        assert_eq!(
+            state.credit_debited,
+            token_amount * &config.token_credit_rate - &account.credit_free
+        );
+        assert_eq!(state.capacity_used, 0); // capacity was released
+
+        // Check indexes
+        assert_eq!(state.expiries.len(store).unwrap(), 0);
+        assert_eq!(state.added.len(), 0);
+        assert_eq!(state.pending.len(), 0);
+
+        // Check approval
+        if using_approval {
+            check_approval_used(&state, store, origin, subscriber);
+        }
+    }
+
+    #[test]
+    fn test_debit_accounts_auto_renew() {
+        setup_logs();
+        let config = RecallConfig::default();
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let current_epoch = ChainEpoch::from(1);
+        let ttl = config.blob_min_ttl;
+
+        // With the default rate, one atto token buys one whole credit
+        let (hash1, size1) = new_hash(1024);
+        let (hash2, size2) = new_hash(1024);
+        let subscriber1 = new_address();
+        let subscriber2 = new_address();
+        let renewals = [
+            (subscriber1, hash1, size1, 2 * ttl as u64 * size1),
+            (subscriber2, hash2, size2, ttl as u64 * size2),
+        ];
+        for (subscriber, hash, size, amount) in renewals {
+            state
+                .buy_credit(
+                    &config,
+                    &store,
+                    subscriber,
+                    TokenAmount::from_atto(amount),
+                    current_epoch,
+                )
+                .unwrap();
+            let res = state.add_blob(
+                &config,
+                &store,
+                subscriber,
+                subscriber,
+                current_epoch,
+                hash,
+                new_metadata_hash(),
+                SubscriptionId::default(),
+                size,
+                Some(ttl),
+                new_pk(),
+                TokenAmount::zero(),
+            );
+            assert!(res.is_ok());
+        }
+
+        // TTL must be at least the minimum
+        let res = state.set_blob_auto_renew(
+            &config,
+            &store,
+            subscriber1,
+            subscriber1,
+            hash1,
+            SubscriptionId::default(),
+            Some(ttl - 1),
+        );
+        assert!(res.is_err());
+        assert_eq!(
+            res.err().unwrap().msg(),
+            format!("minimum blob TTL is {}", config.blob_min_ttl)
+        );
+
+        // Origin must have an approval from the subscriber
+        let res = state.set_blob_auto_renew(
+            &config,
+            &store,
+            subscriber2,
+            subscriber1,
+            hash1,
+            SubscriptionId::default(),
+            Some(ttl),
+        );
+        assert!(res.is_err());
+        assert_eq!(
+            res.err().unwrap().msg(),
+            format!("approval from {} to {} not found", subscriber1, subscriber2)
+        );
+
+        for (subscriber, hash, _, _) in renewals {
+            let sub = state
+                .set_blob_auto_renew(
+                    &config,
+                    &store,
+                    subscriber,
+                    subscriber,
+                    hash,
+                    SubscriptionId::default(),
+                    Some(ttl),
+                )
+                .unwrap();
+            assert_eq!(sub.auto_renew, Some(ttl));
+        }
+
+        // The first subscriber can afford a renewal, the second can't
+        let expiry_epoch = current_epoch + ttl;
+        let credit_sold = state.credit_sold.clone();
+        let res = state.debit_accounts(&config, &store, expiry_epoch);
+        assert!(res.is_ok());
+        assert_eq!(state.credit_sold, credit_sold);
 
-        // Check indexes
-        assert_eq!(state.expiries.len(store).unwrap(), 0);
-        assert_eq!(state.added.len(), 0);
-        assert_eq!(state.pending.len(), 0);
+        let blob = state.get_blob(&store, hash1).unwrap().unwrap();
+        let group = blob.subscribers.get(&subscriber1.to_string()).unwrap();
+        let sub = group
+            .subscriptions
+            .get(&SubscriptionId::default().to_string())
+            .unwrap();
+        assert_eq!(sub.expiry, expiry_epoch + ttl);
+        assert_eq!(sub.auto_renew, Some(ttl));
+        let account = state.get_account(&store, subscriber1).unwrap().unwrap();
+        assert_eq!(account.capacity_used, size1);
+        assert_eq!(account.credit_free, Credit::zero());
 
-        // Check approval
-        if using_approval {
-            check_approval_used(&state, store, origin, subscriber);
-        }
+        assert!(state.get_blob(&store, hash2).unwrap().is_none());
+        let account = state.get_account(&store, subscriber2).unwrap().unwrap();
+        assert_eq!(account.capacity_used, 0);
+        assert_eq!(state.capacity_used, size1);
     }
 
     #[test]
@@ -2775,7 +4247,7 @@ mod tests {
 
         // Debit all accounts
         let debit_epoch = ChainEpoch::from(41);
-        let deletes_from_disc = state.debit_accounts(&store, debit_epoch).unwrap();
+        let deletes_from_disc = state.debit_accounts(config, &store, debit_epoch).unwrap();
         assert!(deletes_from_disc.is_empty());
 
         // Check the account balance
@@ -2894,45 +4366,486 @@ mod tests {
     }
 
     #[test]
-    fn test_add_blob_with_overflowing_ttl() {
+    fn test_add_blob_with_overflowing_ttl() {
+        setup_logs();
+        let config = RecallConfig::default();
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let subscriber = new_address();
+        let current_epoch = ChainEpoch::from(1);
+        let amount = TokenAmount::from_whole(1000000);
+        state
+            .buy_credit(&config, &store, subscriber, amount.clone(), current_epoch)
+            .unwrap();
+
+        let res = state.set_account_status(
+            &config,
+            &store,
+            subscriber,
+            TtlStatus::Extended,
+            current_epoch,
+        );
+        assert!(res.is_ok());
+
+        let (hash, size) = new_hash(1024);
+        let res = state.add_blob(
+            &config,
+            &store,
+            subscriber,
+            subscriber,
+            current_epoch,
+            hash,
+            new_metadata_hash(),
+            SubscriptionId::default(),
+            size,
+            Some(ChainEpoch::MAX),
+            new_pk(),
+            TokenAmount::zero(),
+        );
+        assert!(res.is_ok());
+        let (sub, _) = res.unwrap();
+        assert_eq!(sub.expiry, ChainEpoch::MAX);
+    }
+
+    #[test]
+    fn test_transfer_subscriptions() {
+        setup_logs();
+        let config = RecallConfig::default();
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let from = new_address();
+        let to = new_address();
+        let machine = new_address();
+        let current_epoch = ChainEpoch::from(1);
+        for addr in [from, to] {
+            state
+                .buy_credit(
+                    &config,
+                    &store,
+                    addr,
+                    TokenAmount::from_whole(10),
+                    current_epoch,
+                )
+                .unwrap();
+        }
+
+        // Add a resolved blob
+        let (hash, size) = new_hash(1024);
+        let id = SubscriptionId::new("key").unwrap();
+        let source = new_pk();
+        let (sub, _) = state
+            .add_blob(
+                &config,
+                &store,
+                from,
+                from,
+                current_epoch,
+                hash,
+                new_metadata_hash(),
+                id.clone(),
+                size,
+                None,
+                source,
+                TokenAmount::zero(),
+            )
+            .unwrap();
+        state
+            .set_blob_pending(&store, from, hash, id.clone(), source)
+            .unwrap();
+        state
+            .finalize_blob(
+                &config,
+                &store,
+                from,
+                current_epoch,
+                hash,
+                id.clone(),
+                BlobStatus::Resolved,
+            )
+            .unwrap();
+        let subscriptions = vec![(hash, id.clone()), (new_hash(1024).0, id.clone())];
+
+        // The transfer must be approved for the machine
+        let current_epoch = ChainEpoch::from(21);
+        let res = state.transfer_subscriptions(
+            &config,
+            &store,
+            machine,
+            from,
+            to,
+            current_epoch,
+            subscriptions.clone(),
+        );
+        assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+        state
+            .approve_subscription_transfer(&config, &store, from, machine, Some(to), current_epoch)
+            .unwrap();
+        let res = state.transfer_subscriptions(
+            &config,
+            &store,
+            new_address(),
+            from,
+            to,
+            current_epoch,
+            subscriptions.clone(),
+        );
+        assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+
+        // Missing subscriptions are skipped
+        let moved = state
+            .transfer_subscriptions(
+                &config,
+                &store,
+                machine,
+                from,
+                to,
+                current_epoch,
+                subscriptions.clone(),
+            )
+            .unwrap();
+        assert_eq!(moved, 1);
+
+        // The blob is kept with the same expiry, and the previous subscriber is released
+        let blob = state.get_blob(&store, hash).unwrap().unwrap();
+        assert_eq!(blob.status, BlobStatus::Resolved);
+        assert!(!blob.subscribers.contains_key(&from.to_string()));
+        let group = blob.subscribers.get(&to.to_string()).unwrap();
+        assert_eq!(
+            group.subscriptions.get(&id.to_string()).unwrap().expiry,
+            sub.expiry
+        );
+        let from_account = state.get_account(&store, from).unwrap().unwrap();
+        assert_eq!(from_account.capacity_used, 0);
+        assert!(from_account.credit_committed.is_zero());
+        let to_account = state.get_account(&store, to).unwrap().unwrap();
+        assert_eq!(to_account.capacity_used, size);
+        assert_eq!(
+            to_account.credit_committed,
+            Credit::from_whole((sub.expiry - current_epoch) as u64 * size)
+        );
+
+        // The approval lasts until it's revoked
+        let moved = state
+            .transfer_subscriptions(
+                &config,
+                &store,
+                machine,
+                from,
+                to,
+                current_epoch,
+                subscriptions.clone(),
+            )
+            .unwrap();
+        assert_eq!(moved, 0);
+        state
+            .approve_subscription_transfer(&config, &store, from, machine, None, current_epoch)
+            .unwrap();
+        let res = state.transfer_subscriptions(
+            &config,
+            &store,
+            machine,
+            from,
+            to,
+            current_epoch,
+            subscriptions,
+        );
+        assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+    }
+
+    #[test]
+    fn test_transfer_subscriptions_remaining_ttl() {
+        setup_logs();
+        let config = RecallConfig {
+            blob_min_ttl: 100,
+            storage_pricing: StoragePricing {
+                ttl_bands: vec![(ChainEpoch::MAX, 15_000)],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let from = new_address();
+        let to = new_address();
+        let machine = new_address();
+        let current_epoch = ChainEpoch::from(1);
+        for addr in [from, to] {
+            state
+                .buy_credit(
+                    &config,
+                    &store,
+                    addr,
+                    TokenAmount::from_whole(10),
+                    current_epoch,
+                )
+                .unwrap();
+        }
+        let (hash, size) = new_hash(1024);
+        let id = SubscriptionId::new("key").unwrap();
+        let source = new_pk();
+        let (sub, _) = state
+            .add_blob(
+                &config,
+                &store,
+                from,
+                from,
+                current_epoch,
+                hash,
+                new_metadata_hash(),
+                id.clone(),
+                size,
+                Some(config.blob_min_ttl),
+                source,
+                TokenAmount::zero(),
+            )
+            .unwrap();
+        state
+            .set_blob_pending(&store, from, hash, id.clone(), source)
+            .unwrap();
+        state
+            .finalize_blob(
+                &config,
+                &store,
+                from,
+                current_epoch,
+                hash,
+                id.clone(),
+                BlobStatus::Resolved,
+            )
+            .unwrap();
+        state
+            .approve_subscription_transfer(&config, &store, from, machine, Some(to), current_epoch)
+            .unwrap();
+
+        // The remaining TTL is below the minimum, but it's moved as is without a surcharge
+        let current_epoch = sub.expiry - 10;
+        let credit_free = state.get_account(&store, to).unwrap().unwrap().credit_free;
+        state
+            .transfer_subscriptions(
+                &config,
+                &store,
+                machine,
+                from,
+                to,
+                current_epoch,
+                vec![(hash, id.clone())],
+            )
+            .unwrap();
+        let blob = state.get_blob(&store, hash).unwrap().unwrap();
+        let group = blob.subscribers.get(&to.to_string()).unwrap();
+        assert_eq!(
+            group.subscriptions.get(&id.to_string()).unwrap().expiry,
+            sub.expiry
+        );
+        let to_account = state.get_account(&store, to).unwrap().unwrap();
+        let committed = Credit::from_whole(10 * size);
+        assert_eq!(to_account.credit_committed, committed);
+        assert_eq!(to_account.credit_free, credit_free - committed);
+    }
+
+    #[test]
+    fn test_add_blob_storage_surcharge() {
+        setup_logs();
+        let config = RecallConfig {
+            storage_pricing: StoragePricing {
+                ttl_bands: vec![(ChainEpoch::MAX, 15_000)],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let subscriber = new_address();
+        let current_epoch = ChainEpoch::from(1);
+        let amount = TokenAmount::from_whole(1000000);
+        state
+            .buy_credit(&config, &store, subscriber, amount.clone(), current_epoch)
+            .unwrap();
+        let credit_free = state
+            .get_account(&store, subscriber)
+            .unwrap()
+            .unwrap()
+            .credit_free;
+
+        let (hash, size) = new_hash(1024);
+        state
+            .add_blob(
+                &config,
+                &store,
+                subscriber,
+                subscriber,
+                current_epoch,
+                hash,
+                new_metadata_hash(),
+                SubscriptionId::default(),
+                size,
+                None,
+                new_pk(),
+                TokenAmount::zero(),
+            )
+            .unwrap();
+
+        // The base cost is committed, and the surcharge is debited upfront
+        let base = Credit::from_whole(config.blob_default_ttl as u64 * size);
+        let surcharge = Credit::from_atto(base.atto() / 2);
+        let account = state.get_account(&store, subscriber).unwrap().unwrap();
+        assert_eq!(account.credit_committed, base);
+        assert_eq!(account.credit_free, &credit_free - &base - &surcharge);
+        assert_eq!(state.credit_committed, base);
+        assert_eq!(state.credit_debited, surcharge);
+        let sub = state
+            .get_blob(&store, hash)
+            .unwrap()
+            .unwrap()
+            .subscribers
+            .get(&subscriber.to_string())
+            .unwrap()
+            .subscriptions
+            .get(&SubscriptionId::default().to_string())
+            .unwrap()
+            .clone();
+        assert_eq!(sub.surcharge, surcharge);
+        assert_eq!(sub.surcharge_epoch, current_epoch);
+
+        // Deleting halfway through the TTL settles back half of the surcharge
+        let source = sub.source;
+        state
+            .set_blob_pending(&store, subscriber, hash, SubscriptionId::default(), source)
+            .unwrap();
+        state
+            .finalize_blob(
+                &config,
+                &store,
+                subscriber,
+                current_epoch,
+                hash,
+                SubscriptionId::default(),
+                BlobStatus::Resolved,
+            )
+            .unwrap();
+        let half_ttl = config.blob_default_ttl / 2;
+        state
+            .delete_blob(
+                &store,
+                subscriber,
+                subscriber,
+                current_epoch + half_ttl,
+                hash,
+                SubscriptionId::default(),
+            )
+            .unwrap();
+        let used = Credit::from_whole(half_ttl as u64 * size);
+        let used_surcharge = Credit::from_atto(surcharge.atto() / 2);
+        let account = state.get_account(&store, subscriber).unwrap().unwrap();
+        assert_eq!(account.credit_committed, Credit::zero());
+        assert_eq!(account.credit_free, &credit_free - &used - &used_surcharge);
+        assert_eq!(state.credit_debited, &used + &used_surcharge);
+    }
+
+    #[test]
+    fn test_delete_blob_storage_surcharge_shared_group() {
         setup_logs();
-        let config = RecallConfig::default();
-        let store = MemoryBlockstore::default();
-        let mut state = State::new(&store).unwrap();
-        let subscriber = new_address();
-        let current_epoch = ChainEpoch::from(1);
-        let amount = TokenAmount::from_whole(1000000);
-        state
-            .buy_credit(&config, &store, subscriber, amount.clone(), current_epoch)
-            .unwrap();
+        let config = RecallConfig {
+            storage_pricing: StoragePricing {
+                ttl_bands: vec![(ChainEpoch::MAX, 15_000)],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (hash, size) = new_hash(1024);
+        let source = new_pk();
+        let id1 = SubscriptionId::new("foo").unwrap();
+        let id2 = SubscriptionId::new("bar").unwrap();
+        for order in [[&id1, &id2], [&id2, &id1]] {
+            let store = MemoryBlockstore::default();
+            let mut state = State::new(&store).unwrap();
+            let subscriber = new_address();
+            let current_epoch = ChainEpoch::from(1);
+            let amount = TokenAmount::from_whole(1000000);
+            state
+                .buy_credit(&config, &store, subscriber, amount.clone(), current_epoch)
+                .unwrap();
+            let credit_free = state
+                .get_account(&store, subscriber)
+                .unwrap()
+                .unwrap()
+                .credit_free;
+
+            // The second subscription is only charged for the epochs it adds to the group
+            for (id, ttl) in [(&id1, 4000), (&id2, 8000)] {
+                state
+                    .add_blob(
+                        &config,
+                        &store,
+                        subscriber,
+                        subscriber,
+                        current_epoch,
+                        hash,
+                        new_metadata_hash(),
+                        id.clone(),
+                        size,
+                        Some(ttl),
+                        source,
+                        TokenAmount::zero(),
+                    )
+                    .unwrap();
+            }
+            let base = Credit::from_whole(8000 * size);
+            let surcharge = Credit::from_atto(base.atto() / 2);
+            let account = state.get_account(&store, subscriber).unwrap().unwrap();
+            assert_eq!(account.credit_committed, base);
+            assert_eq!(account.credit_free, &credit_free - &base - &surcharge);
+            state
+                .set_blob_pending(&store, subscriber, hash, id1.clone(), source)
+                .unwrap();
+            state
+                .finalize_blob(
+                    &config,
+                    &store,
+                    subscriber,
+                    current_epoch,
+                    hash,
+                    id1.clone(),
+                    BlobStatus::Resolved,
+                )
+                .unwrap();
 
-        let res = state.set_account_status(
-            &config,
-            &store,
-            subscriber,
-            TtlStatus::Extended,
-            current_epoch,
-        );
-        assert!(res.is_ok());
+            // Either order settles back the surcharge for exactly the epochs not used
+            for id in order {
+                state
+                    .delete_blob(
+                        &store,
+                        subscriber,
+                        subscriber,
+                        current_epoch + 1000,
+                        hash,
+                        id.clone(),
+                    )
+                    .unwrap();
+            }
+            let used = Credit::from_whole(1000 * size);
+            let used_surcharge = Credit::from_atto(used.atto() / 2);
+            let account = state.get_account(&store, subscriber).unwrap().unwrap();
+            assert_eq!(account.credit_committed, Credit::zero());
+            assert_eq!(account.credit_free, &credit_free - &used - &used_surcharge);
+            assert_eq!(state.credit_committed, Credit::zero());
+            assert_eq!(state.credit_debited, &used + &used_surcharge);
+        }
 
-        let (hash, size) = new_hash(1024);
-        let res = state.add_blob(
-            &config,
-            &store,
-            subscriber,
-            subscriber,
-            current_epoch,
-            hash,
-            new_metadata_hash(),
-            SubscriptionId::default(),
-            size,
-            Some(ChainEpoch::MAX),
-            new_pk(),
-            TokenAmount::zero(),
+        // Volume discounts don't price storage below the base rate
+        let config = RecallConfig {
+            storage_pricing: StoragePricing {
+                volume_discounts: vec![(0, 5000)],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let state = State::new(&MemoryBlockstore::default()).unwrap();
+        let base = Credit::from_whole(3600 * size);
+        assert_eq!(
+            state.get_storage_surcharge(&config, &base, 3600, size),
+            Credit::zero()
         );
-        assert!(res.is_ok());
-        let (sub, _) = res.unwrap();
-        assert_eq!(sub.expiry, ChainEpoch::MAX);
     }
 
     #[test]
@@ -2997,6 +4910,114 @@ mod tests {
         assert_eq!(state.pending.len(), 0);
     }
 
+    #[test]
+    fn test_issue_challenges() {
+        setup_logs();
+        let config = RecallConfig::default();
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let subscriber = new_address();
+        let current_epoch = ChainEpoch::from(1);
+        state
+            .buy_credit(
+                &config,
+                &store,
+                subscriber,
+                TokenAmount::from_whole(10),
+                current_epoch,
+            )
+            .unwrap();
+
+        // No blobs, no challenges
+        let challenges = state
+            .issue_challenges(&store, current_epoch, 4, 42)
+            .unwrap();
+        assert!(challenges.is_empty());
+
+        // Add some resolved blobs and one that is still pending
+        let source = new_pk();
+        let mut resolved = HashMap::new();
+        for i in 0..4 {
+            let (hash, size) = new_hash(3000 + i * 1000);
+            state
+                .add_blob(
+                    &config,
+                    &store,
+                    subscriber,
+                    subscriber,
+                    current_epoch,
+                    hash,
+                    new_metadata_hash(),
+                    SubscriptionId::default(),
+                    size,
+                    None,
+                    source,
+                    TokenAmount::zero(),
+                )
+                .unwrap();
+            state
+                .set_blob_pending(&store, subscriber, hash, SubscriptionId::default(), source)
+                .unwrap();
+            if i < 3 {
+                state
+                    .finalize_blob(
+                        &config,
+                        &store,
+                        subscriber,
+                        current_epoch,
+                        hash,
+                        SubscriptionId::default(),
+                        BlobStatus::Resolved,
+                    )
+                    .unwrap();
+                resolved.insert(hash, size);
+            }
+        }
+
+        // Only resolved blobs are challenged, each at most once per round
+        let challenge_epoch = ChainEpoch::from(20);
+        let challenges = state
+            .issue_challenges(&store, challenge_epoch, 4, 42)
+            .unwrap();
+        assert!(!challenges.is_empty());
+        let mut hashes = HashSet::new();
+        for (i, challenge) in challenges.iter().enumerate() {
+            assert_eq!(challenge.id, i as u64);
+            assert!(hashes.insert(challenge.hash));
+            let size = resolved.get(&challenge.hash).expect("blob is resolved");
+            assert_eq!(challenge.size, *size);
+            assert_eq!(challenge.offset % BLOB_CHALLENGE_CHUNK_SIZE, 0);
+            assert!(challenge.offset < *size);
+            assert_eq!(
+                challenge.length,
+                BLOB_CHALLENGE_CHUNK_SIZE.min(size - challenge.offset)
+            );
+            assert_eq!(challenge.deadline, challenge_epoch + BLOB_CHALLENGE_WINDOW);
+        }
+        assert_eq!(state.get_open_challenges(), challenges);
+
+        // Answer a challenge before its deadline, once per validator
+        let first = challenges[0].id;
+        let validator = new_address();
+        state
+            .answer_challenge(challenge_epoch, first, validator)
+            .unwrap();
+        state
+            .answer_challenge(challenge_epoch, first, validator)
+            .unwrap();
+        let res = state.answer_challenge(challenges[0].deadline, first, new_address());
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+
+        // Close a challenge
+        let closed = state.close_challenge(&store, first).unwrap().unwrap();
+        assert_eq!(closed.answered, vec![validator]);
+        assert_eq!(state.get_open_challenges().len(), challenges.len() - 1);
+        let res = state.close_challenge(&store, first);
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_NOT_FOUND);
+    }
+
     #[test]
     fn test_finalize_blob_failed() {
         setup_logs();
@@ -3135,7 +5156,7 @@ mod tests {
 
         // Debit accounts to trigger a refund when we fail below
         let debit_epoch = ChainEpoch::from(11);
-        let deletes_from_disc = state.debit_accounts(&store, debit_epoch).unwrap();
+        let deletes_from_disc = state.debit_accounts(&config, &store, debit_epoch).unwrap();
         assert!(deletes_from_disc.is_empty());
 
         // Check the account balance
@@ -4396,7 +6417,7 @@ mod tests {
 
             // Every debit interval epochs we debit all acounts
             if epoch % debit_interval == 0 {
-                let deletes_from_disc = state.debit_accounts(&store, epoch).unwrap();
+                let deletes_from_disc = state.debit_accounts(&config, &store, epoch).unwrap();
                 warn!(
                     "deleting {} blobs at epoch {}",
                     deletes_from_disc.len(),
@@ -4439,4 +6460,175 @@ mod tests {
         assert_eq!(stats.num_resolving, 0);
         assert_eq!(stats.bytes_resolving, 0);
     }
+
+    #[test]
+    fn test_charge_read() {
+        setup_logs();
+        let config = RecallConfig::default();
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store).unwrap();
+        let owner = new_address();
+        let reader = new_address();
+        let current_epoch = ChainEpoch::from(1);
+        for addr in [owner, reader] {
+            state
+                .buy_credit(
+                    &config,
+                    &store,
+                    addr,
+                    TokenAmount::from_whole(1),
+                    current_epoch,
+                )
+                .unwrap();
+        }
+        let (hash, size) = new_hash(1024);
+        state
+            .add_blob(
+                &config,
+                &store,
+                owner,
+                owner,
+                current_epoch,
+                hash,
+                new_metadata_hash(),
+                SubscriptionId::default(),
+                size,
+                None,
+                new_pk(),
+                TokenAmount::zero(),
+            )
+            .unwrap();
+        let credit_free = |state: &State, addr: Address| {
+            state
+                .get_account(&store, addr)
+                .unwrap()
+                .unwrap()
+                .credit_free
+        };
+        let read_cost = Credit::from_whole(100 * config.blob_read_cost);
+
+        // Without an owner, the reader pays
+        let reader_credit = credit_free(&state, reader);
+        let charge = state
+            .charge_read(&config, &store, hash, None, reader, 100, current_epoch)
+            .unwrap();
+        assert_eq!(charge.payer, Some(reader));
+        assert_eq!(charge.credit, read_cost);
+        assert_eq!(credit_free(&state, reader), &reader_credit - &read_cost);
+        assert_eq!(state.credit_debited, read_cost);
+
+        // The owner must subscribe to the blob
+        let res = state.get_read_charge(
+            &config,
+            &store,
+            hash,
+            Some(reader),
+            reader,
+            100,
+            current_epoch,
+        );
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+
+        // Free reads are not charged
+        state
+            .set_read_policy(&config, &store, owner, ReadPolicy::Free, current_epoch)
+            .unwrap();
+        let charge = state
+            .charge_read(
+                &config,
+                &store,
+                hash,
+                Some(owner),
+                reader,
+                100,
+                current_epoch,
+            )
+            .unwrap();
+        assert_eq!(charge.payer, None);
+        assert!(charge.credit.is_zero());
+        assert_eq!(credit_free(&state, reader), &reader_credit - &read_cost);
+
+        // The owner pays only with an approval
+        state
+            .set_read_policy(&config, &store, owner, ReadPolicy::Owner, current_epoch)
+            .unwrap();
+        let res = state.get_read_charge(
+            &config,
+            &store,
+            hash,
+            Some(owner),
+            reader,
+            100,
+            current_epoch,
+        );
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+        state
+            .approve_credit(
+                &config,
+                &store,
+                owner,
+                reader,
+                current_epoch,
+                Some(read_cost.clone()),
+                None,
+                None,
+            )
+            .unwrap();
+        let owner_credit = credit_free(&state, owner);
+        let charge = state
+            .charge_read(
+                &config,
+                &store,
+                hash,
+                Some(owner),
+                reader,
+                100,
+                current_epoch,
+            )
+            .unwrap();
+        assert_eq!(charge.payer, Some(owner));
+        assert_eq!(credit_free(&state, owner), &owner_credit - &read_cost);
+        check_approvals_match(
+            &state,
+            &store,
+            owner,
+            reader,
+            CreditApproval {
+                credit_limit: Some(read_cost.clone()),
+                gas_fee_limit: None,
+                expiry: None,
+                credit_used: read_cost.clone(),
+                gas_fee_used: TokenAmount::zero(),
+            },
+        );
+
+        // The approval limit is exhausted
+        let res = state.charge_read(&config, &store, hash, Some(owner), reader, 1, current_epoch);
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().exit_code(),
+            ExitCode::USR_INSUFFICIENT_FUNDS
+        );
+
+        // Readers cannot read beyond their credit
+        state
+            .set_read_policy(&config, &store, owner, ReadPolicy::Reader, current_epoch)
+            .unwrap();
+        let res = state.get_read_charge(
+            &config,
+            &store,
+            hash,
+            Some(owner),
+            reader,
+            u64::MAX,
+            current_epoch,
+        );
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().exit_code(),
+            ExitCode::USR_INSUFFICIENT_FUNDS
+        );
+    }
 }
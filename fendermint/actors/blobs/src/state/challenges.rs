@@ -0,0 +1,85 @@
+// Copyright 2025 Recall Contributors
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fendermint_actor_blobs_shared::state::{Challenge, Hash};
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::clock::ChainEpoch;
+
+/// Maximum number of storage challenges that can be open at the same time.
+pub const MAX_OPEN_CHALLENGES: usize = 64;
+
+/// Maximum number of blobs skipped between two challenged blobs.
+pub const MAX_CHALLENGE_SKIP: u64 = 64;
+
+#[derive(Debug, Default, Serialize_tuple, Deserialize_tuple)]
+pub struct ChallengesState {
+    /// Identifier of the next challenge.
+    pub next_id: u64,
+    /// Blob hash at which the next challenge starts walking the blobs HAMT.
+    /// If `None`, the walk starts from the beginning.
+    pub cursor: Option<Hash>,
+    /// Currently open challenges, ordered by identifier.
+    pub open: Vec<Challenge>,
+}
+
+impl ChallengesState {
+    /// Returns the number of challenges that can still be opened.
+    pub fn available(&self) -> usize {
+        MAX_OPEN_CHALLENGES.saturating_sub(self.open.len())
+    }
+
+    /// Opens a new challenge and returns it.
+    pub fn open(
+        &mut self,
+        hash: Hash,
+        size: u64,
+        offset: u64,
+        length: u64,
+        deadline: ChainEpoch,
+    ) -> Challenge {
+        let challenge = Challenge {
+            id: self.next_id,
+            hash,
+            size,
+            offset,
+            length,
+            deadline,
+            answered: Vec::new(),
+        };
+        self.next_id += 1;
+        self.open.push(challenge.clone());
+        challenge
+    }
+
+    /// Returns a mutable reference to an open challenge.
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Challenge> {
+        self.open.iter_mut().find(|c| c.id == id)
+    }
+
+    /// Removes and returns an open challenge.
+    pub fn close(&mut self, id: u64) -> Option<Challenge> {
+        let index = self.open.iter().position(|c| c.id == id)?;
+        Some(self.open.remove(index))
+    }
+}
+
+/// A small deterministic pseudo-random number generator (SplitMix64).
+///
+/// Challenges must be reproducible by every validator executing the block,
+/// so the generator is seeded from chain data rather than from local entropy.
+pub struct ChallengeRng(u64);
+
+impl ChallengeRng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
@@ -5,31 +5,47 @@
 use std::collections::HashMap;
 
 use fendermint_actor_blobs_shared::{
-    add_blob, delete_blob, get_blob, has_credit_approval, overwrite_blob,
-    state::{Blob, BlobStatus, SubscriptionId},
+    add_blob, approve_subscription_transfer, delete_blob, get_blob, get_stats, has_credit_approval,
+    overwrite_blob,
+    params::{MachineSubscription, MAX_TRANSFER_SUBSCRIPTIONS},
+    set_blob_auto_renew,
+    state::{Blob, BlobStatus, Subscription, SubscriptionId},
+    transfer_subscriptions,
 };
 use fendermint_actor_machine::{
     events::emit_evm_event,
     util::{require_addr_is_origin_or_caller, to_id_address},
-    MachineActor,
+    Kind, MachineActor, Metadata, GET_METADATA_METHOD,
 };
+use fendermint_actor_recall_config_shared::get_config;
 use fil_actors_runtime::{
-    actor_dispatch, actor_error,
+    actor_dispatch, actor_error, deserialize_block, extract_send_result,
     runtime::{ActorCode, Runtime},
     ActorError,
 };
+use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_hamt::BytesKey;
-use fvm_shared::address::Address;
+use fvm_shared::{
+    address::Address, clock::ChainEpoch, econ::TokenAmount, sys::SendFlags, MethodNum,
+};
+use num_traits::Zero;
 use recall_sol_facade::bucket::{object_added, object_deleted, object_metadata_updated};
 
 use crate::shared::{
-    AddParams, DeleteParams, GetParams, ListObjectsReturn, ListParams, Method, Object,
-    BUCKET_ACTOR_NAME,
+    AddParams, CopyParams, DeleteObjectsParams, DeleteObjectsReturn, DeleteParams,
+    DeletePrefixParams, DeleteVersionParams, EnableVersioningParams, GetParams, GetQuotaReturn,
+    GetVersionParams, ListObjectsReturn, ListParams, ListVersionsParams, Method, MoveParams,
+    MoveSubscriptionsParams, MoveSubscriptionsReturn, Object, SetLifecycleRulesParams,
+    SetQuotaParams, BUCKET_ACTOR_NAME,
+};
+use crate::state::{
+    LifecycleRule, ObjectState, ObjectVersion, ObjectVersions, State, StoragePrice,
+    SubscriptionTransfer,
 };
-use crate::state::{ObjectState, State};
 use crate::{
-    UpdateObjectMetadataParams, MAX_METADATA_ENTRIES, MAX_METADATA_KEY_SIZE,
-    MAX_METADATA_VALUE_SIZE,
+    UpdateObjectMetadataParams, MAX_DELETE_LIMIT, MAX_LIFECYCLE_RULES, MAX_METADATA_ENTRIES,
+    MAX_METADATA_KEY_SIZE, MAX_METADATA_VALUE_SIZE, MAX_MOVE_SUBSCRIPTIONS_LIMIT,
+    MAX_OBJECT_VERSIONS,
 };
 
 #[cfg(feature = "fil-actor")]
@@ -45,78 +61,16 @@ impl Actor {
     /// and the Blobs actor will enforce that the `from` address is either
     /// the `subscriber` or has a valid credit delegation from the `subscriber`.
     /// The `from` address must be the origin or the caller.
+    ///
+    /// If a lifecycle rule matches the key, its TTL is used instead of the one in `params`.
+    /// The bucket quota is checked before the blob is added.
     fn add_object(rt: &impl Runtime, params: AddParams) -> Result<Object, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
 
         let from = to_id_address(rt, params.from, false)?;
         require_addr_is_origin_or_caller(rt, from)?;
 
-        let state = rt.state::<State>()?;
-        let sub_id = get_blob_id(&state, &params.key)?;
-        let key = BytesKey(params.key.clone());
-
-        validate_metadata(&params.metadata)?;
-
-        let sub = if let Some(object) = state.get(rt.store(), &key)? {
-            // If we have existing blob
-            if params.overwrite {
-                // Overwrite if the flag is passed
-                overwrite_blob(
-                    rt,
-                    from,
-                    object.hash,
-                    sub_id,
-                    params.hash,
-                    Some(state.owner),
-                    params.source,
-                    params.recovery_hash,
-                    params.size,
-                    params.ttl,
-                )?
-            } else {
-                // Return an error if no overwrite flag gets passed
-                return Err(ActorError::illegal_state(
-                    "key exists; use overwrite".into(),
-                ));
-            }
-        } else {
-            // No object found, just a new blob
-            add_blob(
-                rt,
-                params.from,
-                sub_id,
-                params.hash,
-                Some(state.owner),
-                params.source,
-                params.recovery_hash,
-                params.size,
-                params.ttl,
-            )?
-        };
-
-        rt.transaction(|st: &mut State, rt| {
-            st.add(
-                rt.store(),
-                key,
-                params.hash,
-                params.size,
-                params.metadata.clone(),
-                params.overwrite,
-            )
-        })?;
-
-        emit_evm_event(
-            rt,
-            object_added(params.key, &params.hash.0, &params.metadata),
-        )?;
-
-        Ok(Object {
-            hash: params.hash,
-            recovery_hash: params.recovery_hash,
-            size: params.size,
-            expiry: sub.expiry,
-            metadata: params.metadata,
-        })
+        put_object(rt, from, params, 0)
     }
 
     /// Deletes an object from a bucket.
@@ -132,21 +86,123 @@ impl Actor {
         let from = to_id_address(rt, params.from, false)?;
         require_addr_is_origin_or_caller(rt, from)?;
 
+        remove_object(rt, from, params.key)
+    }
+
+    /// Copies an object to another key, which may be in another bucket with the same owner.
+    ///
+    /// The copy adds a new subscription to the source object's blob, so no data is transferred.
+    /// Credit for the subscription is charged by the Blobs actor like for any added object,
+    /// and the destination bucket's lifecycle rules apply.
+    /// The `from` address must be the origin or the caller.
+    fn copy_object(rt: &impl Runtime, params: CopyParams) -> Result<Object, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        copy_object_to(rt, from, params, false)
+    }
+
+    /// Moves an object to another key, which may be in another bucket with the same owner.
+    ///
+    /// This copies the object and then deletes the source object. Moves within the same
+    /// unversioned bucket are not checked against the quota, since the source object's bytes
+    /// are released by the move.
+    /// The `from` address must be the origin or the caller.
+    fn move_object(rt: &impl Runtime, params: MoveParams) -> Result<Object, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        let key = params.key.clone();
+        let object = copy_object_to(rt, from, params, true)?;
+        remove_object(rt, from, key)?;
+
+        Ok(object)
+    }
+
+    /// Deletes a page of objects under a key prefix.
+    ///
+    /// Each call visits at most `limit` keys to stay within gas limits, and returns the key
+    /// to continue from until all keys have been visited. Access control is the same as for
+    /// `delete_object`.
+    fn delete_prefix(
+        rt: &impl Runtime,
+        params: DeletePrefixParams,
+    ) -> Result<DeleteObjectsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        let limit = if params.limit == 0 {
+            MAX_DELETE_LIMIT
+        } else {
+            params.limit.min(MAX_DELETE_LIMIT)
+        };
+        let mut keys = Vec::new();
+        let start_key = params.start_key.map(BytesKey::from);
         let state = rt.state::<State>()?;
-        let sub_id = get_blob_id(&state, &params.key)?;
-        let key = BytesKey(params.key);
-        let object = state
-            .get(rt.store(), &key)?
-            .ok_or(ActorError::illegal_state("object not found".into()))?;
+        let (_, next_key) = state.list(
+            rt.store(),
+            params.prefix,
+            vec![],
+            start_key.as_ref(),
+            limit,
+            |key: Vec<u8>, _: ObjectState| -> anyhow::Result<(), ActorError> {
+                keys.push(key);
+                Ok(())
+            },
+        )?;
 
-        // Delete blob for object
-        delete_blob(rt, from, sub_id, object.hash, Some(state.owner))?;
+        let deleted = keys.len() as u64;
+        for key in keys {
+            remove_object(rt, from, key)?;
+        }
 
-        rt.transaction(|st: &mut State, rt| st.delete(rt.store(), &key))?;
+        Ok(DeleteObjectsReturn {
+            deleted,
+            next_key: next_key.map(|key| key.0),
+        })
+    }
 
-        emit_evm_event(rt, object_deleted(key.0, &object.hash.0))?;
+    /// Deletes a batch of objects.
+    ///
+    /// Keys that don't exist are skipped. Access control is the same as for `delete_object`.
+    fn delete_objects(
+        rt: &impl Runtime,
+        params: DeleteObjectsParams,
+    ) -> Result<DeleteObjectsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
 
-        Ok(())
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        if params.keys.len() as u64 > MAX_DELETE_LIMIT {
+            return Err(ActorError::illegal_argument(format!(
+                "the maximum number of keys allowed is {}",
+                MAX_DELETE_LIMIT
+            )));
+        }
+
+        let mut deleted = 0;
+        for key in params.keys {
+            let exists = rt
+                .state::<State>()?
+                .get(rt.store(), &BytesKey(key.clone()))?
+                .is_some();
+            if exists {
+                remove_object(rt, from, key)?;
+                deleted += 1;
+            }
+        }
+
+        Ok(DeleteObjectsReturn {
+            deleted,
+            next_key: None,
+        })
     }
 
     /// Returns an object.
@@ -154,12 +210,12 @@ impl Actor {
         rt.validate_immediate_caller_accept_any()?;
 
         let state = rt.state::<State>()?;
-        let owner = state.owner;
-        let sub_id = get_blob_id(&state, &params.0)?;
         let key = BytesKey(params.0);
+        let sub_id = get_current_blob_id(rt, &state, &key)?;
         if let Some(object_state) = state.get(rt.store(), &key)? {
             if let Some(blob) = get_blob(rt, object_state.hash)? {
-                let object = build_object(&blob, &object_state, sub_id, owner)?;
+                let subscriber = get_subscriber(&state, &blob, &sub_id);
+                let object = build_object(&blob, &object_state, sub_id, subscriber)?;
                 Ok(object)
             } else {
                 Ok(None)
@@ -254,13 +310,21 @@ impl Actor {
 
             st.add(
                 rt.store(),
-                key,
+                key.clone(),
                 object.hash,
                 object.size,
                 object.metadata.clone(),
                 true,
             )?;
 
+            // Keep the current version in sync
+            if let Some(mut versions) = st.get_versions(rt.store(), &key)? {
+                if let Some(current) = versions.versions.last_mut() {
+                    current.metadata = object.metadata.clone();
+                }
+                st.set_versions(rt.store(), key, versions)?;
+            }
+
             Ok(object.metadata)
         })?;
 
@@ -268,237 +332,2267 @@ impl Actor {
 
         Ok(())
     }
-}
 
-/// Returns a blob subscription ID specific to this machine and object key.
-fn get_blob_id(state: &State, key: &[u8]) -> anyhow::Result<SubscriptionId, ActorError> {
-    let mut data = state.address.get()?.payload_bytes();
-    data.extend(key);
-    let id = blake3::hash(&data).to_hex().to_string();
-    SubscriptionId::new(&id)
-}
+    /// Enables object versioning.
+    ///
+    /// Once enabled, adding an object retains the previous version and deleting an object
+    /// adds a delete marker. Each version keeps its own blob subscription, so retained
+    /// versions are billed to the bucket owner until they are deleted.
+    /// At most `max_versions` versions are retained per key; the oldest ones are deleted along
+    /// with their blob subscriptions as new versions are added. Enabling versioning again
+    /// changes the limit, which applies to each key the next time a version is added to it.
+    /// Versioning can not be disabled. Only the bucket owner can enable versioning.
+    fn enable_versioning(
+        rt: &impl Runtime,
+        params: EnableVersioningParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
 
-/// Build an object from its state and blob.
-fn build_object(
-    blob: &Blob,
-    object_state: &ObjectState,
-    sub_id: SubscriptionId,
-    subscriber: Address,
-) -> anyhow::Result<Option<Object>, ActorError> {
-    match blob.status {
-        BlobStatus::Resolved => {
-            let group = blob
-                .subscribers
-                .get(&subscriber.to_string())
-                .ok_or_else(|| {
-                    ActorError::illegal_state(format!(
-                        "owner {} is not subscribed to blob {}; this should not happen",
-                        subscriber, object_state.hash
-                    ))
-                })?;
-            let (expiry, _) = group.max_expiries(&sub_id, None);
-            if let Some(expiry) = expiry {
-                Ok(Some(Object {
-                    hash: object_state.hash,
-                    recovery_hash: blob.metadata_hash,
-                    size: blob.size,
-                    expiry,
-                    metadata: object_state.metadata.clone(),
-                }))
-            } else {
-                Err(ActorError::illegal_state(format!(
-                    "subscription group is empty for blob {}; this should not happen",
-                    object_state.hash,
-                )))
-            }
-        }
-        BlobStatus::Added | BlobStatus::Pending | BlobStatus::Failed => Ok(None),
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        let state = rt.state::<State>()?;
+        require_owner(rt, &state, from, "enable versioning")?;
+
+        let max_versions = if params.max_versions == 0 {
+            MAX_OBJECT_VERSIONS
+        } else {
+            params.max_versions.min(MAX_OBJECT_VERSIONS)
+        };
+        rt.transaction(|st: &mut State, _| {
+            st.versioning = true;
+            st.max_versions = Some(max_versions);
+            Ok(())
+        })
     }
-}
 
-fn validate_metadata(metadata: &HashMap<String, String>) -> anyhow::Result<(), ActorError> {
-    if metadata.len() as u32 > MAX_METADATA_ENTRIES {
-        return Err(ActorError::illegal_state(format!(
-            "the maximum metadata entries allowed is {}",
-            MAX_METADATA_ENTRIES
-        )));
+    /// Replaces the bucket lifecycle rules.
+    ///
+    /// The rule with the longest prefix matching an object key sets the object's TTL and
+    /// whether its blob subscription is auto-renewed. Rules only apply when an object is added;
+    /// changing them does not affect existing objects. Only the bucket owner can set rules.
+    fn set_lifecycle_rules(
+        rt: &impl Runtime,
+        params: SetLifecycleRulesParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        let state = rt.state::<State>()?;
+        require_owner(rt, &state, from, "set lifecycle rules")?;
+
+        validate_lifecycle_rules(&params.rules)?;
+
+        rt.transaction(|st: &mut State, _| {
+            st.lifecycle_rules = params.rules;
+            Ok(())
+        })
     }
 
-    for (key, value) in metadata {
-        if key.len() as u32 > MAX_METADATA_KEY_SIZE {
-            return Err(ActorError::illegal_state(format!(
-                "key must be less than or equal to {}",
-                MAX_METADATA_KEY_SIZE
-            )));
-        }
+    /// Returns the bucket lifecycle rules.
+    fn get_lifecycle_rules(rt: &impl Runtime) -> Result<Vec<LifecycleRule>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
 
-        if value.is_empty() || value.len() as u32 > MAX_METADATA_VALUE_SIZE {
-            return Err(ActorError::illegal_state(format!(
-                "value must non-empty and less than or equal to {}",
-                MAX_METADATA_VALUE_SIZE
-            )));
-        }
+        let state = rt.state::<State>()?;
+        Ok(state.lifecycle_rules)
     }
 
-    Ok(())
-}
+    /// Replaces the bucket quota.
+    ///
+    /// The quota is checked when objects are added, before their blobs are added.
+    /// Lowering it below the current usage does not remove any objects, but no objects
+    /// can be added until usage drops below it. Only the bucket owner can set the quota.
+    fn set_quota(rt: &impl Runtime, params: SetQuotaParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
 
-fn validate_metadata_optional(
-    metadata: &HashMap<String, Option<String>>,
-) -> anyhow::Result<(), ActorError> {
-    for (key, value) in metadata {
-        if key.len() as u32 > MAX_METADATA_KEY_SIZE {
-            return Err(ActorError::illegal_state(format!(
-                "key must be less than or equal to {}",
-                MAX_METADATA_KEY_SIZE
-            )));
-        }
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
 
-        if let Some(value) = value {
-            if value.is_empty() || value.len() as u32 > MAX_METADATA_VALUE_SIZE {
-                return Err(ActorError::illegal_state(format!(
-                    "value must non-empty and less than or equal to {}",
-                    MAX_METADATA_VALUE_SIZE
-                )));
-            }
+        let state = rt.state::<State>()?;
+        require_owner(rt, &state, from, "set the quota")?;
+
+        if params
+            .quota
+            .max_credit_per_epoch
+            .as_ref()
+            .is_some_and(|credit| credit.is_negative())
+        {
+            return Err(ActorError::illegal_argument(
+                "credit quota must not be negative".into(),
+            ));
         }
+
+        rt.transaction(|st: &mut State, rt| {
+            st.track_usage(rt.store())?;
+            st.quota = params.quota;
+            Ok(())
+        })
     }
 
-    Ok(())
-}
+    /// Returns the bucket quota and its usage.
+    fn get_quota(rt: &impl Runtime) -> Result<GetQuotaReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
 
-impl MachineActor for Actor {
-    type State = State;
-}
+        let state = rt.state::<State>()?;
+        let used_bytes = state.used_bytes(rt.store())?;
+        Ok(GetQuotaReturn {
+            quota: state.quota,
+            used_bytes,
+            credit_per_epoch: storage_price(rt)?.credit_per_epoch(used_bytes),
+        })
+    }
 
-impl ActorCode for Actor {
-    type Methods = Method;
+    /// Returns a specific version of an object.
+    ///
+    /// Delete markers are returned as is.
+    fn get_object_version(
+        rt: &impl Runtime,
+        params: GetVersionParams,
+    ) -> Result<Option<ObjectVersion>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
 
-    fn name() -> &'static str {
-        BUCKET_ACTOR_NAME
+        let state = rt.state::<State>()?;
+        let key = BytesKey(params.key);
+        let versions = load_versions(rt, &state, &key)?;
+        let Some(version) = versions.get(params.version).cloned() else {
+            return Ok(None);
+        };
+        if version.delete_marker {
+            return Ok(Some(version));
+        }
+        match get_blob(rt, version.hash)? {
+            Some(blob) if matches!(blob.status, BlobStatus::Resolved) => {
+                let sub_id = get_version_blob_id(&state, &key.0, version.version)?;
+                let expiry =
+                    find_subscription(&blob, &sub_id, get_subscriber(&state, &blob, &sub_id))
+                        .map(|sub| sub.expiry)
+                        .unwrap_or(version.expiry);
+                Ok(Some(ObjectVersion {
+                    recovery_hash: blob.metadata_hash,
+                    expiry,
+                    ..version
+                }))
+            }
+            _ => Ok(None),
+        }
     }
 
-    actor_dispatch! {
-        Constructor => constructor,
-        Init => init,
-        GetAddress => get_address,
-        GetMetadata => get_metadata,
-        AddObject => add_object,
-        DeleteObject => delete_object,
-        GetObject => get_object,
-        ListObjects => list_objects,
-        UpdateObjectMetadata => update_object_metadata,
-        _ => fallback,
+    /// Lists the versions of an object, from oldest to newest.
+    fn list_object_versions(
+        rt: &impl Runtime,
+        params: ListVersionsParams,
+    ) -> Result<Vec<ObjectVersion>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let state = rt.state::<State>()?;
+        let versions = load_versions(rt, &state, &BytesKey(params.0))?;
+        Ok(versions.versions)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Deletes a specific version of an object.
+    ///
+    /// Deleting the current version or a delete marker makes the next newest version current.
+    /// Access control will be enforced by the Blobs actor, except for delete markers,
+    /// which require a credit delegation from the bucket owner.
+    /// The `from` address must be the origin or the caller.
+    fn delete_object_version(
+        rt: &impl Runtime,
+        params: DeleteVersionParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
 
-    use fendermint_actor_blobs_shared::{
-        params::{
-            AddBlobParams, DeleteBlobParams, GetBlobParams, GetCreditApprovalParams,
-            OverwriteBlobParams,
-        },
-        state::{CreditApproval, Hash, Subscription, SubscriptionGroup},
-        Method as BlobMethod, BLOBS_ACTOR_ADDR,
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        let state = rt.state::<State>()?;
+        require_no_subscription_transfer(&state)?;
+        let key = BytesKey(params.key);
+        let mut versions = load_versions(rt, &state, &key)?;
+        let previous = versions.current().map(|v| v.version);
+        let removed = versions
+            .remove(params.version)
+            .ok_or(ActorError::not_found("object version not found".into()))?;
+
+        if removed.delete_marker {
+            require_credit_approval(rt, state.owner, from)?;
+        } else {
+            let sub_id = get_version_blob_id(&state, &key.0, removed.version)?;
+            delete_blob(rt, from, sub_id, removed.hash, Some(state.owner))?;
+        }
+
+        let current = versions.current().cloned();
+        rt.transaction(|st: &mut State, rt| {
+            st.update_usage(removed.size, 0);
+            match &current {
+                Some(current) => {
+                    st.add(
+                        rt.store(),
+                        key.clone(),
+                        current.hash,
+                        current.size,
+                        current.metadata.clone(),
+                        true,
+                    )?;
+                }
+                None => {
+                    if st.get(rt.store(), &key)?.is_some() {
+                        st.delete(rt.store(), &key)?;
+                    }
+                }
+            }
+            st.set_versions(rt.store(), key.clone(), versions)
+        })?;
+
+        if current.as_ref().map(|v| v.version) != previous {
+            match current {
+                Some(current) => {
+                    emit_evm_event(rt, object_added(key.0, &current.hash.0, &current.metadata))?
+                }
+                None => emit_evm_event(rt, object_deleted(key.0, &removed.hash.0))?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves a batch of blob subscriptions to the new owner after an ownership transfer.
+    ///
+    /// Anyone can call this, since it only finishes a transfer both owners agreed to.
+    fn move_subscriptions(
+        rt: &impl Runtime,
+        params: MoveSubscriptionsParams,
+    ) -> Result<MoveSubscriptionsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let limit = if params.limit == 0 {
+            MAX_MOVE_SUBSCRIPTIONS_LIMIT
+        } else {
+            params.limit.min(MAX_MOVE_SUBSCRIPTIONS_LIMIT)
+        };
+        move_subscription_batch(rt, limit as usize)
+    }
+}
+
+/// Adds an object to a bucket on behalf of `from`.
+///
+/// `releasing` is the number of bytes the caller releases right after, which the quota check
+/// counts as already released.
+fn put_object(
+    rt: &impl Runtime,
+    from: Address,
+    mut params: AddParams,
+    releasing: u64,
+) -> Result<Object, ActorError> {
+    let state = rt.state::<State>()?;
+    require_no_subscription_transfer(&state)?;
+    let sub_id = get_blob_id(&state, &params.key)?;
+    let key = BytesKey(params.key.clone());
+
+    validate_metadata(&params.metadata)?;
+
+    if let Some(rule) = state.get_lifecycle_rule(&params.key) {
+        params.ttl = Some(rule.ttl);
+    }
+
+    if state.versioning {
+        return add_object_version(rt, &state, from, params, releasing);
+    }
+
+    let (sub, released) = if let Some(object) = state.get(rt.store(), &key)? {
+        // If we have existing blob
+        if params.overwrite {
+            // Overwrite if the flag is passed
+            check_quota(rt, &state, object.size + releasing, params.size)?;
+            let sub = overwrite_blob(
+                rt,
+                from,
+                object.hash,
+                sub_id.clone(),
+                params.hash,
+                Some(state.owner),
+                params.source,
+                params.recovery_hash,
+                params.size,
+                params.ttl,
+            )?;
+            (sub, object.size)
+        } else {
+            // Return an error if no overwrite flag gets passed
+            return Err(ActorError::illegal_state(
+                "key exists; use overwrite".into(),
+            ));
+        }
+    } else {
+        // No object found, just a new blob
+        check_quota(rt, &state, releasing, params.size)?;
+        let sub = add_blob(
+            rt,
+            params.from,
+            sub_id.clone(),
+            params.hash,
+            Some(state.owner),
+            params.source,
+            params.recovery_hash,
+            params.size,
+            params.ttl,
+        )?;
+        (sub, 0)
     };
-    use fendermint_actor_blobs_testing::{new_hash, new_pk, setup_logs};
-    use fendermint_actor_machine::{events::to_actor_event, ConstructorParams, InitParams, Kind};
-    use fil_actors_evm_shared::address::EthAddress;
-    use fil_actors_runtime::test_utils::{
-        expect_empty, MockRuntime, ADM_ACTOR_CODE_ID, ETHACCOUNT_ACTOR_CODE_ID, INIT_ACTOR_CODE_ID,
+    apply_lifecycle_rule(rt, &state, from, &params, sub_id)?;
+
+    rt.transaction(|st: &mut State, rt| {
+        st.update_usage(released, params.size);
+        st.add(
+            rt.store(),
+            key,
+            params.hash,
+            params.size,
+            params.metadata.clone(),
+            params.overwrite,
+        )
+    })?;
+
+    emit_evm_event(
+        rt,
+        object_added(params.key, &params.hash.0, &params.metadata),
+    )?;
+
+    Ok(Object {
+        hash: params.hash,
+        recovery_hash: params.recovery_hash,
+        size: params.size,
+        expiry: sub.expiry,
+        metadata: params.metadata,
+    })
+}
+
+/// Deletes an object from a bucket on behalf of `from`.
+fn remove_object(rt: &impl Runtime, from: Address, key: Vec<u8>) -> Result<(), ActorError> {
+    let state = rt.state::<State>()?;
+    require_no_subscription_transfer(&state)?;
+    if state.versioning {
+        return delete_object_version_marker(rt, &state, from, key);
+    }
+
+    let sub_id = get_blob_id(&state, &key)?;
+    let key = BytesKey(key);
+    let object = state
+        .get(rt.store(), &key)?
+        .ok_or(ActorError::illegal_state("object not found".into()))?;
+
+    // Delete blob for object
+    delete_blob(rt, from, sub_id, object.hash, Some(state.owner))?;
+
+    rt.transaction(|st: &mut State, rt| {
+        st.update_usage(object.size, 0);
+        st.delete(rt.store(), &key)
+    })?;
+
+    emit_evm_event(rt, object_deleted(key.0, &object.hash.0))?;
+
+    Ok(())
+}
+
+/// Copies an object on behalf of `from` by subscribing the destination key to the same blob.
+///
+/// If `moving`, the source object is deleted right after, so a copy within the same unversioned
+/// bucket is not checked against the quota.
+fn copy_object_to(
+    rt: &impl Runtime,
+    from: Address,
+    params: CopyParams,
+    moving: bool,
+) -> Result<Object, ActorError> {
+    let state = rt.state::<State>()?;
+    let to_bucket = match params.to_bucket {
+        Some(bucket) => Some(to_id_address(rt, bucket, false)?),
+        None => None,
+    }
+    .filter(|bucket| *bucket != rt.message().receiver());
+    if to_bucket.is_none() && params.to_key == params.key {
+        return Err(ActorError::illegal_argument(
+            "source and destination keys are the same".into(),
+        ));
+    }
+
+    let key = BytesKey(params.key);
+    let object = state
+        .get(rt.store(), &key)?
+        .ok_or(ActorError::not_found("object not found".into()))?;
+    let sub_id = get_current_blob_id(rt, &state, &key)?;
+    let blob = get_blob(rt, object.hash)?
+        .filter(|blob| matches!(blob.status, BlobStatus::Resolved))
+        .ok_or(ActorError::illegal_state(
+            "object is not resolved; try again later".into(),
+        ))?;
+    // The copy is resolved from the same source as the original, though the blob is already
+    // resolved, so it won't be fetched again
+    let source = find_subscription(&blob, &sub_id, get_subscriber(&state, &blob, &sub_id))
+        .map(|sub| sub.source)
+        .ok_or_else(|| {
+            ActorError::illegal_state(format!(
+                "owner {} is not subscribed to blob {}; this should not happen",
+                state.owner, object.hash
+            ))
+        })?;
+    let add_params = AddParams {
+        source,
+        key: params.to_key,
+        hash: object.hash,
+        recovery_hash: blob.metadata_hash,
+        size: object.size,
+        ttl: params.ttl,
+        metadata: params.metadata.unwrap_or(object.metadata),
+        overwrite: params.overwrite,
+        from: params.from,
     };
-    use fil_actors_runtime::{ADM_ACTOR_ADDR, INIT_ACTOR_ADDR};
-    use fvm_ipld_encoding::ipld_block::IpldBlock;
-    use fvm_shared::{
-        clock::ChainEpoch, econ::TokenAmount, error::ExitCode, sys::SendFlags, MethodNum,
+
+    match to_bucket {
+        Some(bucket) => {
+            require_same_owner(rt, &state, bucket)?;
+            deserialize_block(extract_send_result(rt.send_simple(
+                &bucket,
+                Method::AddObject as MethodNum,
+                IpldBlock::serialize_cbor(&add_params)?,
+                TokenAmount::zero(),
+            ))?)
+        }
+        None => {
+            // Versioned buckets keep the source as a previous version, so nothing is released
+            let releasing = if moving && !state.versioning {
+                object.size
+            } else {
+                0
+            };
+            put_object(rt, from, add_params, releasing)
+        }
+    }
+}
+
+/// Returns an error if `bucket` is not a bucket with the same owner as this one.
+fn require_same_owner(rt: &impl Runtime, state: &State, bucket: Address) -> Result<(), ActorError> {
+    let metadata: Metadata = deserialize_block(extract_send_result(rt.send(
+        &bucket,
+        GET_METADATA_METHOD,
+        None,
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+    ))?)?;
+    if !matches!(metadata.kind, Kind::Bucket) {
+        return Err(ActorError::illegal_argument(format!(
+            "{} is not a bucket",
+            bucket
+        )));
+    }
+    let owner = to_id_address(rt, state.owner, false)?;
+    if to_id_address(rt, metadata.owner, false)? != owner {
+        return Err(actor_error!(
+            forbidden;
+            "Unauthorized: bucket {} is not owned by {}", bucket, owner));
+    }
+    Ok(())
+}
+
+/// Adds a new version of an object in a versioned bucket.
+///
+/// Previous versions keep their blob subscriptions, unless they are pruned to make room for
+/// the new version.
+fn add_object_version(
+    rt: &impl Runtime,
+    state: &State,
+    from: Address,
+    params: AddParams,
+    releasing: u64,
+) -> Result<Object, ActorError> {
+    let key = BytesKey(params.key.clone());
+    let mut versions = load_versions(rt, state, &key)?;
+    if versions.current().is_some() && !params.overwrite {
+        return Err(ActorError::illegal_state(
+            "key exists; use overwrite".into(),
+        ));
+    }
+
+    let pruned = versions.prune(max_versions(state) - 1);
+    let released = pruned.iter().map(|v| v.size).sum();
+    check_quota(rt, state, released + releasing, params.size)?;
+
+    let version = versions.next_version;
+    let sub_id = get_version_blob_id(state, &params.key, version)?;
+    let sub = add_blob(
+        rt,
+        from,
+        sub_id.clone(),
+        params.hash,
+        Some(state.owner),
+        params.source,
+        params.recovery_hash,
+        params.size,
+        params.ttl,
+    )?;
+    apply_lifecycle_rule(rt, state, from, &params, sub_id)?;
+    delete_pruned_versions(rt, state, from, &params.key, &pruned)?;
+    versions.push(ObjectVersion {
+        version,
+        hash: params.hash,
+        recovery_hash: params.recovery_hash,
+        size: params.size,
+        expiry: sub.expiry,
+        metadata: params.metadata.clone(),
+        delete_marker: false,
+    });
+
+    rt.transaction(|st: &mut State, rt| {
+        st.update_usage(released, params.size);
+        st.add(
+            rt.store(),
+            key.clone(),
+            params.hash,
+            params.size,
+            params.metadata.clone(),
+            true,
+        )?;
+        st.set_versions(rt.store(), key, versions)
+    })?;
+
+    emit_evm_event(
+        rt,
+        object_added(params.key, &params.hash.0, &params.metadata),
+    )?;
+
+    Ok(Object {
+        hash: params.hash,
+        recovery_hash: params.recovery_hash,
+        size: params.size,
+        expiry: sub.expiry,
+        metadata: params.metadata,
+    })
+}
+
+/// Deletes an object in a versioned bucket by adding a delete marker.
+///
+/// The deleted version is retained along with its blob subscription, unless it is pruned to
+/// make room for the marker.
+fn delete_object_version_marker(
+    rt: &impl Runtime,
+    state: &State,
+    from: Address,
+    key: Vec<u8>,
+) -> Result<(), ActorError> {
+    let key = BytesKey(key);
+    let mut versions = load_versions(rt, state, &key)?;
+    let current = versions
+        .current()
+        .cloned()
+        .ok_or(ActorError::illegal_state("object not found".into()))?;
+
+    require_credit_approval(rt, state.owner, from)?;
+
+    let pruned = versions.prune(max_versions(state) - 1);
+    let released = pruned.iter().map(|v| v.size).sum();
+    delete_pruned_versions(rt, state, from, &key.0, &pruned)?;
+
+    versions.push(ObjectVersion::delete_marker(versions.next_version));
+    rt.transaction(|st: &mut State, rt| {
+        st.update_usage(released, 0);
+        st.delete(rt.store(), &key)?;
+        st.set_versions(rt.store(), key.clone(), versions)
+    })?;
+
+    emit_evm_event(rt, object_deleted(key.0, &current.hash.0))?;
+
+    Ok(())
+}
+
+/// Returns the number of versions retained per object key.
+fn max_versions(state: &State) -> usize {
+    state.max_versions.unwrap_or(MAX_OBJECT_VERSIONS).max(1) as usize
+}
+
+/// Deletes the blob subscriptions of versions pruned from an object's history.
+fn delete_pruned_versions(
+    rt: &impl Runtime,
+    state: &State,
+    from: Address,
+    key: &[u8],
+    pruned: &[ObjectVersion],
+) -> Result<(), ActorError> {
+    for version in pruned.iter().filter(|v| !v.delete_marker) {
+        let sub_id = get_version_blob_id(state, key, version.version)?;
+        delete_blob(rt, from, sub_id, version.hash, Some(state.owner))?;
+    }
+    Ok(())
+}
+
+/// Returns the version history of an object key.
+///
+/// An object that was added before versioning was enabled is returned as version `0`.
+fn load_versions(
+    rt: &impl Runtime,
+    state: &State,
+    key: &BytesKey,
+) -> Result<ObjectVersions, ActorError> {
+    if let Some(versions) = state.get_versions(rt.store(), key)? {
+        return Ok(versions);
+    }
+    let mut versions = ObjectVersions::default();
+    if let Some(object) = state.get(rt.store(), key)? {
+        let sub_id = get_blob_id(state, &key.0)?;
+        let (recovery_hash, expiry) = match get_blob(rt, object.hash)? {
+            Some(blob) => (
+                blob.metadata_hash,
+                find_subscription(&blob, &sub_id, get_subscriber(state, &blob, &sub_id))
+                    .map(|sub| sub.expiry)
+                    .unwrap_or_default(),
+            ),
+            None => Default::default(),
+        };
+        versions.push(ObjectVersion {
+            version: 0,
+            hash: object.hash,
+            recovery_hash,
+            size: object.size,
+            expiry,
+            metadata: object.metadata,
+            delete_marker: false,
+        });
+    }
+    Ok(versions)
+}
+
+/// Turns on auto-renewal of an object's blob subscription if its lifecycle rule asks for it.
+fn apply_lifecycle_rule(
+    rt: &impl Runtime,
+    state: &State,
+    from: Address,
+    params: &AddParams,
+    sub_id: SubscriptionId,
+) -> Result<(), ActorError> {
+    match state.get_lifecycle_rule(&params.key) {
+        Some(rule) if rule.auto_renew => set_blob_auto_renew(
+            rt,
+            from,
+            sub_id,
+            params.hash,
+            Some(state.owner),
+            Some(rule.ttl),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Returns an error if `from` is not the bucket owner.
+fn require_owner(
+    rt: &impl Runtime,
+    state: &State,
+    from: Address,
+    action: &str,
+) -> Result<(), ActorError> {
+    let owner = to_id_address(rt, state.owner, false)?;
+    if from != owner {
+        return Err(actor_error!(
+            forbidden;
+            "Unauthorized: only the bucket owner {} can {}", owner, action));
+    }
+    Ok(())
+}
+
+/// Returns an error if `from` has no credit delegation from the bucket owner.
+fn require_credit_approval(
+    rt: &impl Runtime,
+    bucket_owner: Address,
+    from: Address,
+) -> Result<(), ActorError> {
+    if !has_credit_approval(rt, bucket_owner, from)? {
+        return Err(actor_error!(
+            forbidden;
+            format!("Unauthorized: missing delegation from bucket owner {} to {}", bucket_owner, from)));
+    }
+    Ok(())
+}
+
+/// Returns a blob subscription ID specific to this machine and object key.
+fn get_blob_id(state: &State, key: &[u8]) -> anyhow::Result<SubscriptionId, ActorError> {
+    get_version_blob_id(state, key, 0)
+}
+
+/// Returns a blob subscription ID specific to this machine, object key, and version.
+///
+/// Version `0` uses the same ID as unversioned objects.
+fn get_version_blob_id(
+    state: &State,
+    key: &[u8],
+    version: u64,
+) -> anyhow::Result<SubscriptionId, ActorError> {
+    SubscriptionId::for_machine_object(&state.address.get()?, key, version)
+}
+
+/// Moves the blob subscriptions of up to `limit` object keys to the new owner, while an
+/// ownership transfer is in progress.
+///
+/// Once all subscriptions are moved, the previous owner's approval is revoked and the transfer
+/// is cleared.
+fn move_subscription_batch(
+    rt: &impl Runtime,
+    limit: usize,
+) -> Result<MoveSubscriptionsReturn, ActorError> {
+    let state = rt.state::<State>()?;
+    let Some(mut transfer) = state.subscription_transfer.clone() else {
+        return Ok(MoveSubscriptionsReturn {
+            moved: 0,
+            done: true,
+        });
     };
-    use recall_sol_facade::machine::{machine_created, machine_initialized};
 
-    fn get_runtime() -> (MockRuntime, Address) {
-        let origin_id_addr = Address::new_id(110);
-        let rt = construct_and_verify(origin_id_addr);
-        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, origin_id_addr);
-        rt.set_origin(origin_id_addr);
-        (rt, origin_id_addr)
+    let mut subscriptions = Vec::new();
+    let mut done = false;
+    let mut remaining = limit;
+    while remaining > 0 && !done {
+        let start_key = transfer.next_key.take();
+        let (visited, next_key) = if !transfer.versions_done {
+            // Versioned objects are subscribed per version
+            let (visited, next_key) = state.for_each_versions_ranged(
+                rt.store(),
+                start_key.as_ref(),
+                remaining,
+                |key, versions| {
+                    for version in versions.versions.iter().filter(|v| !v.delete_marker) {
+                        subscriptions.push(MachineSubscription {
+                            hash: version.hash,
+                            key: key.to_vec(),
+                            version: version.version,
+                        });
+                    }
+                    Ok(())
+                },
+            )?;
+            transfer.versions_done = next_key.is_none();
+            (visited, next_key)
+        } else {
+            let (visited, next_key) = state.for_each_object_ranged(
+                rt.store(),
+                start_key.as_ref(),
+                remaining,
+                |key, object| {
+                    if state
+                        .get_versions(rt.store(), &BytesKey(key.to_vec()))?
+                        .is_none()
+                    {
+                        subscriptions.push(MachineSubscription {
+                            hash: object.hash,
+                            key: key.to_vec(),
+                            version: 0,
+                        });
+                    }
+                    Ok(())
+                },
+            )?;
+            done = next_key.is_none();
+            (visited, next_key)
+        };
+        transfer.next_key = next_key;
+        remaining = remaining.saturating_sub(visited);
+    }
+
+    let machine = state.address.get()?;
+    let mut moved = 0;
+    for batch in subscriptions.chunks(MAX_TRANSFER_SUBSCRIPTIONS) {
+        moved += transfer_subscriptions(rt, machine, transfer.from, transfer.to, batch.to_vec())?;
+    }
+
+    if done {
+        approve_subscription_transfer(rt, transfer.from, None)?;
+    }
+    rt.transaction(|st: &mut State, _| {
+        st.subscription_transfer = if done { None } else { Some(transfer) };
+        Ok(())
+    })?;
+
+    Ok(MoveSubscriptionsReturn { moved, done })
+}
+
+/// Checks the bucket quota for storing `added` bytes in place of `released` bytes.
+/// The storage price is only fetched if a credit quota is set.
+fn check_quota(
+    rt: &impl Runtime,
+    state: &State,
+    released: u64,
+    added: u64,
+) -> Result<(), ActorError> {
+    state.check_quota(rt.store(), released, added, || storage_price(rt))
+}
+
+/// Returns the current storage price from the Recall config and the subnet utilisation.
+fn storage_price(rt: &impl Runtime) -> Result<StoragePrice, ActorError> {
+    Ok(StoragePrice {
+        config: get_config(rt)?,
+        utilization_multiplier_bps: get_stats(rt)?.storage_multiplier_bps,
+    })
+}
+
+/// Returns an error if the blob subscriptions are being moved to a new owner.
+fn require_no_subscription_transfer(state: &State) -> Result<(), ActorError> {
+    if state.subscription_transfer.is_some() {
+        return Err(ActorError::illegal_state(
+            "objects are being moved to the new owner; call MoveSubscriptions to finish".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the subscriber holding a blob subscription of the bucket.
+///
+/// This is the owner, or the previous owner if the subscription hasn't been moved yet.
+fn get_subscriber(state: &State, blob: &Blob, sub_id: &SubscriptionId) -> Address {
+    match &state.subscription_transfer {
+        Some(transfer) if find_subscription(blob, sub_id, state.owner).is_none() => transfer.from,
+        _ => state.owner,
+    }
+}
+
+/// Returns the blob subscription ID of the current version of an object.
+fn get_current_blob_id(
+    rt: &impl Runtime,
+    state: &State,
+    key: &BytesKey,
+) -> anyhow::Result<SubscriptionId, ActorError> {
+    // Versioned objects are subscribed per version
+    match state
+        .get_versions(rt.store(), key)?
+        .and_then(|versions| versions.current().map(|v| v.version))
+    {
+        Some(version) => get_version_blob_id(state, &key.0, version),
+        None => get_blob_id(state, &key.0),
+    }
+}
+
+/// Returns a subscriber's subscription to a blob.
+fn find_subscription<'a>(
+    blob: &'a Blob,
+    sub_id: &SubscriptionId,
+    subscriber: Address,
+) -> Option<&'a Subscription> {
+    blob.subscribers
+        .get(&subscriber.to_string())
+        .and_then(|group| group.subscriptions.get(&sub_id.to_string()))
+}
+
+/// Build an object from its state and blob.
+fn build_object(
+    blob: &Blob,
+    object_state: &ObjectState,
+    sub_id: SubscriptionId,
+    subscriber: Address,
+) -> anyhow::Result<Option<Object>, ActorError> {
+    match blob.status {
+        BlobStatus::Resolved => {
+            let group = blob
+                .subscribers
+                .get(&subscriber.to_string())
+                .ok_or_else(|| {
+                    ActorError::illegal_state(format!(
+                        "owner {} is not subscribed to blob {}; this should not happen",
+                        subscriber, object_state.hash
+                    ))
+                })?;
+            let (expiry, _) = group.max_expiries(&sub_id, None);
+            if let Some(expiry) = expiry {
+                Ok(Some(Object {
+                    hash: object_state.hash,
+                    recovery_hash: blob.metadata_hash,
+                    size: blob.size,
+                    expiry,
+                    metadata: object_state.metadata.clone(),
+                }))
+            } else {
+                Err(ActorError::illegal_state(format!(
+                    "subscription group is empty for blob {}; this should not happen",
+                    object_state.hash,
+                )))
+            }
+        }
+        BlobStatus::Added | BlobStatus::Pending | BlobStatus::Failed => Ok(None),
+    }
+}
+
+fn validate_lifecycle_rules(rules: &[LifecycleRule]) -> anyhow::Result<(), ActorError> {
+    if rules.len() as u32 > MAX_LIFECYCLE_RULES {
+        return Err(ActorError::illegal_argument(format!(
+            "the maximum lifecycle rules allowed is {}",
+            MAX_LIFECYCLE_RULES
+        )));
+    }
+
+    for (i, rule) in rules.iter().enumerate() {
+        if rule.ttl <= 0 {
+            return Err(ActorError::illegal_argument(
+                "lifecycle rule TTL must be positive".into(),
+            ));
+        }
+        if rules[..i].iter().any(|r| r.prefix == rule.prefix) {
+            return Err(ActorError::illegal_argument(
+                "lifecycle rule prefixes must be unique".into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_metadata(metadata: &HashMap<String, String>) -> anyhow::Result<(), ActorError> {
+    if metadata.len() as u32 > MAX_METADATA_ENTRIES {
+        return Err(ActorError::illegal_state(format!(
+            "the maximum metadata entries allowed is {}",
+            MAX_METADATA_ENTRIES
+        )));
+    }
+
+    for (key, value) in metadata {
+        if key.len() as u32 > MAX_METADATA_KEY_SIZE {
+            return Err(ActorError::illegal_state(format!(
+                "key must be less than or equal to {}",
+                MAX_METADATA_KEY_SIZE
+            )));
+        }
+
+        if value.is_empty() || value.len() as u32 > MAX_METADATA_VALUE_SIZE {
+            return Err(ActorError::illegal_state(format!(
+                "value must non-empty and less than or equal to {}",
+                MAX_METADATA_VALUE_SIZE
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_metadata_optional(
+    metadata: &HashMap<String, Option<String>>,
+) -> anyhow::Result<(), ActorError> {
+    for (key, value) in metadata {
+        if key.len() as u32 > MAX_METADATA_KEY_SIZE {
+            return Err(ActorError::illegal_state(format!(
+                "key must be less than or equal to {}",
+                MAX_METADATA_KEY_SIZE
+            )));
+        }
+
+        if let Some(value) = value {
+            if value.is_empty() || value.len() as u32 > MAX_METADATA_VALUE_SIZE {
+                return Err(ActorError::illegal_state(format!(
+                    "value must non-empty and less than or equal to {}",
+                    MAX_METADATA_VALUE_SIZE
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl MachineActor for Actor {
+    type State = State;
+
+    /// Has the owner approve moving the bucket's blob subscriptions to the proposed owner.
+    fn ownership_proposed(
+        rt: &impl Runtime,
+        state: &State,
+        new_owner: Option<Address>,
+    ) -> Result<(), ActorError> {
+        require_no_subscription_transfer(state)?;
+        approve_subscription_transfer(rt, state.owner, new_owner)
+    }
+
+    /// Starts moving the bucket's blob subscriptions, and the credit committed to them, to the
+    /// new owner, and moves the first batch.
+    ///
+    /// The new owner commits credit for the remaining TTL of every object, including retained
+    /// versions, and the previous owner is refunded.
+    /// Buckets with more objects than fit in a batch can't be changed until the remaining
+    /// batches are moved with `move_subscriptions`.
+    fn ownership_accepted(
+        rt: &impl Runtime,
+        state: &State,
+        new_owner: Address,
+    ) -> Result<(), ActorError> {
+        let transfer = SubscriptionTransfer::new(state.owner, new_owner);
+        rt.transaction(|st: &mut State, _| {
+            st.subscription_transfer = Some(transfer);
+            Ok(())
+        })?;
+        move_subscription_batch(rt, MAX_MOVE_SUBSCRIPTIONS_LIMIT as usize)?;
+        Ok(())
+    }
+}
+
+impl ActorCode for Actor {
+    type Methods = Method;
+
+    fn name() -> &'static str {
+        BUCKET_ACTOR_NAME
+    }
+
+    actor_dispatch! {
+        Constructor => constructor,
+        Init => init,
+        GetAddress => get_address,
+        GetMetadata => get_metadata,
+        TransferOwnership => transfer_ownership,
+        AcceptOwnership => accept_ownership,
+        SetMetadata => set_metadata,
+        AddObject => add_object,
+        DeleteObject => delete_object,
+        GetObject => get_object,
+        ListObjects => list_objects,
+        UpdateObjectMetadata => update_object_metadata,
+        EnableVersioning => enable_versioning,
+        GetObjectVersion => get_object_version,
+        ListObjectVersions => list_object_versions,
+        DeleteObjectVersion => delete_object_version,
+        SetLifecycleRules => set_lifecycle_rules,
+        GetLifecycleRules => get_lifecycle_rules,
+        CopyObject => copy_object,
+        MoveObject => move_object,
+        DeletePrefix => delete_prefix,
+        DeleteObjects => delete_objects,
+        SetQuota => set_quota,
+        GetQuota => get_quota,
+        MoveSubscriptions => move_subscriptions,
+        _ => fallback,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Quota;
+    use fendermint_actor_blobs_shared::{
+        params::{
+            AddBlobParams, ApproveSubscriptionTransferParams, DeleteBlobParams, GetBlobParams,
+            GetCreditApprovalParams, GetStatsReturn, OverwriteBlobParams, SetBlobAutoRenewParams,
+            TransferSubscriptionsParams,
+        },
+        state::{CreditApproval, Hash, Subscription, SubscriptionGroup, TokenCreditRate},
+        Method as BlobMethod, BLOBS_ACTOR_ADDR,
+    };
+    use fendermint_actor_blobs_testing::{new_hash, new_pk, setup_logs};
+    use fendermint_actor_machine::{
+        events::{
+            metadata_updated, ownership_transfer_started, ownership_transferred, to_actor_event,
+        },
+        AcceptOwnershipParams, AdmTransferMachineParams, ConstructorParams, InitParams, Kind,
+        SetMetadataParams, TransferOwnershipParams, ADM_TRANSFER_MACHINE_METHOD,
+    };
+    use fendermint_actor_recall_config_shared::{
+        Method as RecallConfigMethod, RecallConfig, BASIS_POINTS, RECALL_CONFIG_ACTOR_ADDR,
+    };
+    use fil_actors_evm_shared::address::EthAddress;
+    use fil_actors_runtime::test_utils::{
+        expect_empty, MockRuntime, ADM_ACTOR_CODE_ID, ETHACCOUNT_ACTOR_CODE_ID, INIT_ACTOR_CODE_ID,
+    };
+    use fil_actors_runtime::{ADM_ACTOR_ADDR, INIT_ACTOR_ADDR};
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_shared::{
+        clock::ChainEpoch, econ::TokenAmount, error::ExitCode, sys::SendFlags, MethodNum,
+    };
+    use recall_sol_facade::machine::{machine_created, machine_initialized};
+
+    fn get_runtime() -> (MockRuntime, Address) {
+        let origin_id_addr = Address::new_id(110);
+        let rt = construct_and_verify(origin_id_addr);
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, origin_id_addr);
+        rt.set_origin(origin_id_addr);
+        (rt, origin_id_addr)
+    }
+
+    fn construct_and_verify(owner_id_addr: Address) -> MockRuntime {
+        let owner_eth_addr = EthAddress(hex_literal::hex!(
+            "CAFEB0BA00000000000000000000000000000000"
+        ));
+        let owner_delegated_addr = Address::new_delegated(10, &owner_eth_addr.0).unwrap();
+
+        let buck_addr = Address::new_id(111);
+        let rt = MockRuntime {
+            receiver: buck_addr,
+            ..Default::default()
+        };
+        rt.set_delegated_address(owner_id_addr.id().unwrap(), owner_delegated_addr);
+
+        rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
+        let metadata = HashMap::new();
+        let event = to_actor_event(
+            machine_created(Kind::Bucket as u8, owner_delegated_addr, &metadata).unwrap(),
+        )
+        .unwrap();
+        rt.expect_emitted_event(event);
+        let actor_construction = rt
+            .call::<Actor>(
+                Method::Constructor as u64,
+                IpldBlock::serialize_cbor(&ConstructorParams {
+                    owner: owner_id_addr,
+                    metadata,
+                })
+                .unwrap(),
+            )
+            .unwrap();
+        expect_empty(actor_construction);
+        rt.verify();
+
+        rt.set_caller(*ADM_ACTOR_CODE_ID, ADM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![ADM_ACTOR_ADDR]);
+        let event =
+            to_actor_event(machine_initialized(Kind::Bucket as u8, buck_addr).unwrap()).unwrap();
+        rt.expect_emitted_event(event);
+        let actor_init = rt
+            .call::<Actor>(
+                Method::Init as u64,
+                IpldBlock::serialize_cbor(&InitParams { address: buck_addr }).unwrap(),
+            )
+            .unwrap();
+        expect_empty(actor_init);
+        rt.verify();
+
+        rt.reset();
+        rt
+    }
+
+    fn expect_emitted_add_event(rt: &MockRuntime, params: &AddParams) {
+        let event = to_actor_event(
+            object_added(params.key.clone(), &params.hash.0, &params.metadata).unwrap(),
+        )
+        .unwrap();
+        rt.expect_emitted_event(event);
+    }
+
+    fn expect_emitted_delete_event(rt: &MockRuntime, params: &DeleteParams, hash: Hash) {
+        let event = to_actor_event(object_deleted(params.key.clone(), &hash.0).unwrap()).unwrap();
+        rt.expect_emitted_event(event);
+    }
+
+    #[test]
+    pub fn test_add_object() {
+        setup_logs();
+        let (rt, origin) = get_runtime();
+
+        // Add an object
+        let hash = new_hash(256);
+        let key = vec![0, 1, 2];
+        let add_params: AddParams = AddParams {
+            source: new_pk(),
+            key: key.clone(),
+            hash: hash.0,
+            recovery_hash: new_hash(256).0,
+            size: hash.1,
+            ttl: None,
+            metadata: HashMap::new(),
+            from: origin,
+            overwrite: false,
+        };
+        rt.expect_validate_caller_any();
+        let state = rt.state::<State>().unwrap();
+        let sub_id = get_blob_id(&state, &key).unwrap();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::AddBlob as MethodNum,
+            IpldBlock::serialize_cbor(&AddBlobParams {
+                sponsor: Some(origin),
+                source: add_params.source,
+                hash: add_params.hash,
+                metadata_hash: add_params.recovery_hash,
+                id: sub_id,
+                size: add_params.size,
+                ttl: add_params.ttl,
+                from: origin,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            ExitCode::OK,
+        );
+        expect_emitted_add_event(&rt, &add_params);
+        let result = rt
+            .call::<Actor>(
+                Method::AddObject as u64,
+                IpldBlock::serialize_cbor(&add_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Object>()
+            .unwrap();
+        assert_eq!(add_params.hash, result.hash);
+        assert_eq!(add_params.recovery_hash, result.recovery_hash);
+        assert_eq!(add_params.size, result.size);
+        assert_eq!(add_params.metadata, result.metadata);
+        rt.verify();
+    }
+
+    #[test]
+    pub fn test_add_overwrite() {
+        let (rt, origin) = get_runtime();
+
+        // Add an object
+        let hash = new_hash(256);
+        let key = vec![0, 1, 2];
+        let add_params: AddParams = AddParams {
+            source: new_pk(),
+            key: key.clone(),
+            hash: hash.0,
+            recovery_hash: new_hash(256).0,
+            size: hash.1,
+            ttl: None,
+            metadata: HashMap::new(),
+            from: origin,
+            overwrite: false,
+        };
+        rt.expect_validate_caller_any();
+        let state = rt.state::<State>().unwrap();
+        let sub_id = get_blob_id(&state, &key).unwrap();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::AddBlob as MethodNum,
+            IpldBlock::serialize_cbor(&AddBlobParams {
+                sponsor: Some(origin),
+                source: add_params.source,
+                hash: add_params.hash,
+                metadata_hash: add_params.recovery_hash,
+                id: sub_id.clone(),
+                size: add_params.size,
+                ttl: add_params.ttl,
+                from: origin,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            ExitCode::OK,
+        );
+        expect_emitted_add_event(&rt, &add_params);
+        let result = rt
+            .call::<Actor>(
+                Method::AddObject as u64,
+                IpldBlock::serialize_cbor(&add_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Object>()
+            .unwrap();
+        assert_eq!(add_params.hash, result.hash);
+        assert_eq!(add_params.metadata, result.metadata);
+        assert_eq!(add_params.recovery_hash, result.recovery_hash);
+        assert_eq!(add_params.size, result.size);
+        rt.verify();
+
+        // Overwrite object (old blob is deleted)
+        let hash = new_hash(256);
+        let add_params2 = AddParams {
+            source: add_params.source,
+            key: add_params.key,
+            hash: hash.0,
+            recovery_hash: new_hash(256).0,
+            size: hash.1,
+            ttl: None,
+            metadata: HashMap::new(),
+            from: origin,
+            overwrite: true,
+        };
+        rt.expect_validate_caller_any();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::OverwriteBlob as MethodNum,
+            IpldBlock::serialize_cbor(&OverwriteBlobParams {
+                old_hash: add_params.hash,
+                add: AddBlobParams {
+                    id: sub_id,
+                    hash: add_params2.hash,
+                    sponsor: Some(origin),
+                    source: add_params2.source,
+                    metadata_hash: add_params2.recovery_hash,
+                    size: add_params2.size,
+                    ttl: add_params2.ttl,
+                    from: origin,
+                },
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            ExitCode::OK,
+        );
+        expect_emitted_add_event(&rt, &add_params2);
+        let result = rt
+            .call::<Actor>(
+                Method::AddObject as u64,
+                IpldBlock::serialize_cbor(&add_params2).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Object>()
+            .unwrap();
+        assert_eq!(add_params2.hash, result.hash);
+        assert_eq!(add_params2.metadata, result.metadata);
+        assert_eq!(add_params2.recovery_hash, result.recovery_hash);
+        assert_eq!(add_params2.size, result.size);
+        rt.verify();
+    }
+
+    #[test]
+    pub fn test_add_overwrite_fail() {
+        let (rt, origin) = get_runtime();
+
+        // Add an object
+        let hash = new_hash(256);
+        let key = vec![0, 1, 2];
+        let add_params: AddParams = AddParams {
+            source: new_pk(),
+            key: key.clone(),
+            hash: hash.0,
+            size: hash.1,
+            recovery_hash: new_hash(256).0,
+            ttl: None,
+            metadata: HashMap::new(),
+            from: origin,
+            overwrite: false,
+        };
+        rt.expect_validate_caller_any();
+        let state = rt.state::<State>().unwrap();
+        let sub_id = get_blob_id(&state, &key).unwrap();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::AddBlob as MethodNum,
+            IpldBlock::serialize_cbor(&AddBlobParams {
+                sponsor: Some(origin),
+                source: add_params.source,
+                hash: add_params.hash,
+                metadata_hash: add_params.recovery_hash,
+                id: sub_id,
+                size: add_params.size,
+                ttl: add_params.ttl,
+                from: origin,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            ExitCode::OK,
+        );
+        expect_emitted_add_event(&rt, &add_params);
+        let result = rt
+            .call::<Actor>(
+                Method::AddObject as u64,
+                IpldBlock::serialize_cbor(&add_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Object>()
+            .unwrap();
+        let state = rt.state::<State>().unwrap();
+        assert_eq!(add_params.hash, result.hash);
+        assert_eq!(add_params.metadata, result.metadata);
+        assert_eq!(add_params.recovery_hash, result.recovery_hash);
+        assert_eq!(add_params.size, result.size);
+        rt.verify();
+
+        // Try to overwrite
+        let hash = new_hash(256);
+        let add_params2 = AddParams {
+            source: add_params.source,
+            key: add_params.key,
+            hash: hash.0,
+            size: hash.1,
+            recovery_hash: new_hash(256).0,
+            ttl: None,
+            metadata: HashMap::new(),
+            from: origin,
+            overwrite: false,
+        };
+        rt.expect_validate_caller_any();
+        let result = rt.call::<Actor>(
+            Method::AddObject as u64,
+            IpldBlock::serialize_cbor(&add_params2).unwrap(),
+        );
+        assert!(result.is_err_and(|e| { e.msg().eq("key exists; use overwrite") }));
+        let state2 = rt.state::<State>().unwrap();
+        assert_eq!(state2.root, state.root);
+        rt.verify();
+    }
+
+    #[test]
+    pub fn test_delete_object() {
+        let (rt, origin) = get_runtime();
+
+        // Add an object
+        let key = vec![0, 1, 2];
+        let hash = new_hash(256);
+        let add_params: AddParams = AddParams {
+            source: new_pk(),
+            key: key.clone(),
+            hash: hash.0,
+            size: hash.1,
+            recovery_hash: new_hash(256).0,
+            ttl: None,
+            metadata: HashMap::new(),
+            from: origin,
+            overwrite: false,
+        };
+        rt.expect_validate_caller_any();
+        let state = rt.state::<State>().unwrap();
+        let sub_id = get_blob_id(&state, &key).unwrap();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::AddBlob as MethodNum,
+            IpldBlock::serialize_cbor(&AddBlobParams {
+                sponsor: Some(origin),
+                source: add_params.source,
+                hash: add_params.hash,
+                id: sub_id.clone(),
+                size: add_params.size,
+                metadata_hash: add_params.recovery_hash,
+                ttl: add_params.ttl,
+                from: origin,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            ExitCode::OK,
+        );
+        expect_emitted_add_event(&rt, &add_params);
+        let result_add = rt
+            .call::<Actor>(
+                Method::AddObject as u64,
+                IpldBlock::serialize_cbor(&add_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Object>()
+            .unwrap();
+        assert_eq!(add_params.hash, result_add.hash);
+        assert_eq!(add_params.metadata, result_add.metadata);
+        assert_eq!(add_params.recovery_hash, result_add.recovery_hash);
+        assert_eq!(add_params.size, result_add.size);
+        rt.verify();
+
+        // Delete object
+        let delete_params = DeleteParams { key, from: origin };
+        rt.expect_validate_caller_any();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::DeleteBlob as MethodNum,
+            IpldBlock::serialize_cbor(&DeleteBlobParams {
+                sponsor: Some(origin),
+                hash: add_params.hash,
+                id: sub_id,
+                from: origin,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            None,
+            ExitCode::OK,
+        );
+        expect_emitted_delete_event(&rt, &delete_params, add_params.hash);
+        let result_delete = rt.call::<Actor>(
+            Method::DeleteObject as u64,
+            IpldBlock::serialize_cbor(&delete_params).unwrap(),
+        );
+        assert!(result_delete.is_ok());
+        rt.verify();
+    }
+
+    #[test]
+    pub fn test_get_object_none() {
+        let (rt, _) = get_runtime();
+
+        let get_params = GetParams(vec![0, 1, 2]);
+        rt.expect_validate_caller_any();
+        let result = rt
+            .call::<Actor>(
+                Method::GetObject as u64,
+                IpldBlock::serialize_cbor(&get_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Option<Object>>();
+        assert!(result.is_ok());
+        assert_eq!(result, Ok(None));
+        rt.verify();
+    }
+
+    #[test]
+    pub fn test_get_object() {
+        let (rt, origin) = get_runtime();
+
+        // Add an object
+        let key = vec![0, 1, 2];
+        let hash = new_hash(256);
+        let ttl = ChainEpoch::from(3600);
+        let add_params: AddParams = AddParams {
+            source: new_pk(),
+            key: key.clone(),
+            hash: hash.0,
+            size: hash.1,
+            recovery_hash: new_hash(256).0,
+            ttl: Some(ttl),
+            metadata: HashMap::new(),
+            from: origin,
+            overwrite: false,
+        };
+        rt.expect_validate_caller_any();
+        let state = rt.state::<State>().unwrap();
+        let sub_id = get_blob_id(&state, &key).unwrap();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::AddBlob as MethodNum,
+            IpldBlock::serialize_cbor(&AddBlobParams {
+                sponsor: Some(origin),
+                source: add_params.source,
+                hash: add_params.hash,
+                id: sub_id.clone(),
+                size: add_params.size,
+                metadata_hash: add_params.recovery_hash,
+                ttl: add_params.ttl,
+                from: origin,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            ExitCode::OK,
+        );
+        expect_emitted_add_event(&rt, &add_params);
+        rt.call::<Actor>(
+            Method::AddObject as u64,
+            IpldBlock::serialize_cbor(&add_params).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize::<Object>()
+        .unwrap();
+        rt.verify();
+
+        // Get the object
+        let blob = Blob {
+            size: add_params.size,
+            subscribers: HashMap::from([(
+                origin.to_string(),
+                SubscriptionGroup {
+                    subscriptions: HashMap::from([(
+                        sub_id.to_string(),
+                        Subscription {
+                            added: 0,
+                            expiry: ttl,
+                            source: add_params.source,
+                            delegate: Some(origin),
+                            failed: false,
+                            auto_renew: None,
+                            ..Default::default()
+                        },
+                    )]),
+                },
+            )]),
+            status: BlobStatus::Resolved,
+            metadata_hash: add_params.recovery_hash,
+        };
+        rt.expect_validate_caller_any();
+        rt.expect_send(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::GetBlob as MethodNum,
+            IpldBlock::serialize_cbor(&GetBlobParams(add_params.hash)).unwrap(),
+            TokenAmount::from_whole(0),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&Some(blob)).unwrap(),
+            ExitCode::OK,
+            None,
+        );
+        let get_params = GetParams(key);
+        let result = rt
+            .call::<Actor>(
+                Method::GetObject as u64,
+                IpldBlock::serialize_cbor(&get_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Option<Object>>();
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Object {
+                hash: hash.0,
+                recovery_hash: add_params.recovery_hash,
+                size: add_params.size,
+                expiry: ttl,
+                metadata: add_params.metadata,
+            })
+        );
+        rt.verify();
+    }
+
+    #[test]
+    pub fn test_update_object_metadata() {
+        let (rt, origin) = get_runtime();
+
+        // Add an object
+        let hash = new_hash(256);
+        let key = vec![0, 1, 2];
+        let add_params: AddParams = AddParams {
+            source: new_pk(),
+            key: key.clone(),
+            hash: hash.0,
+            size: hash.1,
+            recovery_hash: new_hash(256).0,
+            ttl: None,
+            metadata: HashMap::from([("foo".into(), "bar".into()), ("foo2".into(), "bar".into())]),
+            from: origin,
+            overwrite: false,
+        };
+        rt.expect_validate_caller_any();
+        let state = rt.state::<State>().unwrap();
+        let sub_id = get_blob_id(&state, &key).unwrap();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::AddBlob as MethodNum,
+            IpldBlock::serialize_cbor(&AddBlobParams {
+                sponsor: Some(origin),
+                source: add_params.source,
+                hash: add_params.hash,
+                metadata_hash: add_params.recovery_hash,
+                id: sub_id,
+                size: add_params.size,
+                ttl: add_params.ttl,
+                from: origin,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            ExitCode::OK,
+        );
+        expect_emitted_add_event(&rt, &add_params);
+        let result = rt
+            .call::<Actor>(
+                Method::AddObject as u64,
+                IpldBlock::serialize_cbor(&add_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Object>()
+            .unwrap();
+        let state = rt.state::<State>().unwrap();
+        assert_eq!(add_params.hash, result.hash);
+        assert_eq!(add_params.metadata, result.metadata);
+        assert_eq!(add_params.recovery_hash, result.recovery_hash);
+        assert_eq!(add_params.size, result.size);
+        rt.verify();
+
+        // Update metadata
+        let update_object_params = UpdateObjectMetadataParams {
+            from: origin,
+            key: add_params.key.clone(),
+            metadata: HashMap::from([
+                ("foo".into(), Some("zar".into())),
+                ("foo2".into(), None),
+                ("foo3".into(), Some("bar".into())),
+            ]),
+        };
+        rt.expect_validate_caller_any();
+        let event = to_actor_event(
+            object_metadata_updated(
+                add_params.key,
+                &HashMap::from([("foo".into(), "zar".into()), ("foo3".into(), "bar".into())]),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        rt.expect_emitted_event(event);
+        let result = rt.call::<Actor>(
+            Method::UpdateObjectMetadata as u64,
+            IpldBlock::serialize_cbor(&update_object_params).unwrap(),
+        );
+        assert!(result.is_ok());
+        rt.verify();
+
+        // Fail if "from" is neither origin nor caller
+        let alien_id_addr = Address::new_id(112);
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, alien_id_addr);
+        rt.set_origin(alien_id_addr);
+        rt.expect_validate_caller_any();
+        let result = rt.call::<Actor>(
+            Method::UpdateObjectMetadata as u64,
+            IpldBlock::serialize_cbor(&update_object_params).unwrap(),
+        );
+        assert!(result.is_err());
+        rt.verify();
+
+        // Fail if "from" is not the owner, and has no delegation.
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, alien_id_addr);
+        rt.set_origin(alien_id_addr);
+        rt.expect_validate_caller_any();
+        let alien_update = UpdateObjectMetadataParams {
+            from: alien_id_addr,
+            key: update_object_params.key,
+            metadata: update_object_params.metadata,
+        };
+        rt.expect_send(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::GetCreditApproval as MethodNum,
+            IpldBlock::serialize_cbor(&GetCreditApprovalParams {
+                from: origin,
+                to: alien_id_addr,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor::<Option<CreditApproval>>(&None).unwrap(),
+            ExitCode::OK,
+            None,
+        );
+        let result = rt.call::<Actor>(
+            Method::UpdateObjectMetadata as u64,
+            IpldBlock::serialize_cbor(&alien_update).unwrap(),
+        );
+        assert!(result.is_err());
+        rt.verify();
+
+        // Allowed if there is a delegation
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, alien_id_addr);
+        rt.set_origin(alien_id_addr);
+        rt.expect_validate_caller_any();
+        rt.expect_send(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::GetCreditApproval as MethodNum,
+            IpldBlock::serialize_cbor(&GetCreditApprovalParams {
+                from: origin,
+                to: alien_id_addr,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            None,
+            SendFlags::READ_ONLY,
+            // We do not care what is inside credit approval. We only care if it is present.
+            IpldBlock::serialize_cbor::<Option<CreditApproval>>(&Some(CreditApproval {
+                credit_limit: None,
+                gas_fee_limit: None,
+                expiry: None,
+                credit_used: TokenAmount::from_whole(0),
+                gas_fee_used: TokenAmount::from_whole(0),
+            }))
+            .unwrap(),
+            ExitCode::OK,
+            None,
+        );
+        let event = to_actor_event(
+            object_metadata_updated(
+                alien_update.key.clone(),
+                &HashMap::from([("foo".into(), "zar".into()), ("foo3".into(), "bar".into())]),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        rt.expect_emitted_event(event);
+        let result = rt.call::<Actor>(
+            Method::UpdateObjectMetadata as u64,
+            IpldBlock::serialize_cbor(&alien_update).unwrap(),
+        );
+        assert!(result.is_ok());
+        rt.verify();
+
+        // Get the object and check metadata
+        let sub_id = get_blob_id(&state, &key).unwrap();
+        let blob = Blob {
+            size: add_params.size,
+            subscribers: HashMap::from([(
+                origin.to_string(),
+                SubscriptionGroup {
+                    subscriptions: HashMap::from([(
+                        sub_id.to_string(),
+                        Subscription {
+                            added: 0,
+                            expiry: ChainEpoch::from(3600),
+                            source: add_params.source,
+                            delegate: Some(origin),
+                            failed: false,
+                            auto_renew: None,
+                            ..Default::default()
+                        },
+                    )]),
+                },
+            )]),
+            status: BlobStatus::Resolved,
+            metadata_hash: add_params.recovery_hash,
+        };
+        rt.expect_validate_caller_any();
+        rt.expect_send(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::GetBlob as MethodNum,
+            IpldBlock::serialize_cbor(&GetBlobParams(add_params.hash)).unwrap(),
+            TokenAmount::from_whole(0),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&Some(blob)).unwrap(),
+            ExitCode::OK,
+            None,
+        );
+        let get_params = GetParams(key);
+        let result = rt
+            .call::<Actor>(
+                Method::GetObject as u64,
+                IpldBlock::serialize_cbor(&get_params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Option<Object>>();
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            Some(Object {
+                hash: hash.0,
+                recovery_hash: add_params.recovery_hash,
+                size: add_params.size,
+                expiry: ChainEpoch::from(3600),
+                metadata: HashMap::from([
+                    ("foo".into(), "zar".into()),
+                    ("foo3".into(), "bar".into())
+                ]),
+            })
+        );
+        rt.verify();
+    }
+
+    fn expect_add_version(
+        rt: &MockRuntime,
+        origin: Address,
+        add_params: &AddParams,
+        version: u64,
+    ) -> SubscriptionId {
+        let state = rt.state::<State>().unwrap();
+        let sub_id = get_version_blob_id(&state, &add_params.key, version).unwrap();
+        rt.expect_validate_caller_any();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::AddBlob as MethodNum,
+            IpldBlock::serialize_cbor(&AddBlobParams {
+                sponsor: Some(origin),
+                source: add_params.source,
+                hash: add_params.hash,
+                metadata_hash: add_params.recovery_hash,
+                id: sub_id.clone(),
+                size: add_params.size,
+                ttl: add_params.ttl,
+                from: origin,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            ExitCode::OK,
+        );
+        expect_emitted_add_event(rt, add_params);
+        rt.call::<Actor>(
+            Method::AddObject as u64,
+            IpldBlock::serialize_cbor(add_params).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        sub_id
+    }
+
+    fn list_versions(rt: &MockRuntime, key: &[u8]) -> Vec<ObjectVersion> {
+        rt.expect_validate_caller_any();
+        let result = rt
+            .call::<Actor>(
+                Method::ListObjectVersions as u64,
+                IpldBlock::serialize_cbor(&ListVersionsParams(key.to_vec())).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Vec<ObjectVersion>>()
+            .unwrap();
+        rt.verify();
+        result
     }
 
-    fn construct_and_verify(owner_id_addr: Address) -> MockRuntime {
-        let owner_eth_addr = EthAddress(hex_literal::hex!(
-            "CAFEB0BA00000000000000000000000000000000"
-        ));
-        let owner_delegated_addr = Address::new_delegated(10, &owner_eth_addr.0).unwrap();
+    #[test]
+    pub fn test_enable_versioning() {
+        let (rt, origin) = get_runtime();
 
-        let buck_addr = Address::new_id(111);
-        let rt = MockRuntime {
-            receiver: buck_addr,
-            ..Default::default()
+        // Fail if "from" is not the owner
+        let alien_id_addr = Address::new_id(112);
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, alien_id_addr);
+        rt.set_origin(alien_id_addr);
+        rt.expect_validate_caller_any();
+        let result = rt.call::<Actor>(
+            Method::EnableVersioning as u64,
+            IpldBlock::serialize_cbor(&EnableVersioningParams {
+                from: alien_id_addr,
+                max_versions: 0,
+            })
+            .unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_FORBIDDEN));
+        assert!(!rt.state::<State>().unwrap().versioning);
+        rt.verify();
+
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, origin);
+        rt.set_origin(origin);
+        rt.expect_validate_caller_any();
+        rt.call::<Actor>(
+            Method::EnableVersioning as u64,
+            IpldBlock::serialize_cbor(&EnableVersioningParams {
+                from: origin,
+                max_versions: 0,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        let state = rt.state::<State>().unwrap();
+        assert!(state.versioning);
+        assert_eq!(state.max_versions, Some(MAX_OBJECT_VERSIONS));
+        rt.verify();
+
+        // Enabling it again changes the limit
+        rt.expect_validate_caller_any();
+        rt.call::<Actor>(
+            Method::EnableVersioning as u64,
+            IpldBlock::serialize_cbor(&EnableVersioningParams {
+                from: origin,
+                max_versions: 5,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(rt.state::<State>().unwrap().max_versions, Some(5));
+        rt.verify();
+    }
+
+    #[test]
+    pub fn test_transfer_ownership() {
+        let (rt, origin) = get_runtime();
+        let origin_delegated_addr = Address::new_delegated(
+            10,
+            &hex_literal::hex!("CAFEB0BA00000000000000000000000000000000"),
+        )
+        .unwrap();
+        let new_owner = Address::new_id(112);
+        let new_owner_delegated_addr = Address::new_delegated(
+            10,
+            &hex_literal::hex!("DEADBEEF00000000000000000000000000000000"),
+        )
+        .unwrap();
+        rt.set_delegated_address(new_owner.id().unwrap(), new_owner_delegated_addr);
+
+        rt.expect_validate_caller_any();
+        rt.call::<Actor>(
+            Method::EnableVersioning as u64,
+            IpldBlock::serialize_cbor(&EnableVersioningParams {
+                from: origin,
+                max_versions: 0,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        let hash = new_hash(256);
+        let add_params = AddParams {
+            source: new_pk(),
+            key: vec![0, 1, 2],
+            hash: hash.0,
+            recovery_hash: new_hash(256).0,
+            size: hash.1,
+            ttl: None,
+            metadata: HashMap::new(),
+            from: origin,
+            overwrite: false,
         };
-        rt.set_delegated_address(owner_id_addr.id().unwrap(), owner_delegated_addr);
+        expect_add_version(&rt, origin, &add_params, 1);
 
-        rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
-        rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
-        let metadata = HashMap::new();
+        // Fail if "from" is not the owner
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, new_owner);
+        rt.set_origin(new_owner);
+        rt.expect_validate_caller_any();
+        let result = rt.call::<Actor>(
+            Method::TransferOwnership as u64,
+            IpldBlock::serialize_cbor(&TransferOwnershipParams {
+                from: new_owner,
+                new_owner: Some(new_owner),
+            })
+            .unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_FORBIDDEN));
+        rt.verify();
+
+        // Propose the new owner, which approves moving the owner's subscriptions
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, origin);
+        rt.set_origin(origin);
+        rt.expect_validate_caller_any();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::ApproveSubscriptionTransfer as MethodNum,
+            IpldBlock::serialize_cbor(&ApproveSubscriptionTransferParams {
+                from: origin,
+                to: Some(new_owner),
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            None,
+            ExitCode::OK,
+        );
         let event = to_actor_event(
-            machine_created(Kind::Bucket as u8, owner_delegated_addr, &metadata).unwrap(),
+            ownership_transfer_started(origin_delegated_addr, Some(new_owner_delegated_addr))
+                .unwrap(),
         )
         .unwrap();
         rt.expect_emitted_event(event);
-        let actor_construction = rt
-            .call::<Actor>(
-                Method::Constructor as u64,
-                IpldBlock::serialize_cbor(&ConstructorParams {
-                    owner: owner_id_addr,
-                    metadata,
-                })
-                .unwrap(),
-            )
-            .unwrap();
-        expect_empty(actor_construction);
+        rt.call::<Actor>(
+            Method::TransferOwnership as u64,
+            IpldBlock::serialize_cbor(&TransferOwnershipParams {
+                from: origin,
+                new_owner: Some(new_owner),
+            })
+            .unwrap(),
+        )
+        .unwrap();
         rt.verify();
+        let state = rt.state::<State>().unwrap();
+        assert_eq!(state.owner, origin);
+        assert_eq!(state.pending_owner, Some(new_owner));
 
-        rt.set_caller(*ADM_ACTOR_CODE_ID, ADM_ACTOR_ADDR);
-        rt.expect_validate_caller_addr(vec![ADM_ACTOR_ADDR]);
-        let event =
-            to_actor_event(machine_initialized(Kind::Bucket as u8, buck_addr).unwrap()).unwrap();
-        rt.expect_emitted_event(event);
-        let actor_init = rt
-            .call::<Actor>(
-                Method::Init as u64,
-                IpldBlock::serialize_cbor(&InitParams { address: buck_addr }).unwrap(),
-            )
-            .unwrap();
-        expect_empty(actor_init);
+        // Only the proposed owner can accept
+        rt.expect_validate_caller_any();
+        let result = rt.call::<Actor>(
+            Method::AcceptOwnership as u64,
+            IpldBlock::serialize_cbor(&AcceptOwnershipParams { from: origin }).unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_FORBIDDEN));
         rt.verify();
 
-        rt.reset();
-        rt
-    }
-
-    fn expect_emitted_add_event(rt: &MockRuntime, params: &AddParams) {
+        // Accepting moves the subscriptions to the new owner, revokes the approval, and moves
+        // the bucket to the new owner in the ADM
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, new_owner);
+        rt.set_origin(new_owner);
+        rt.expect_validate_caller_any();
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::TransferSubscriptions as MethodNum,
+            IpldBlock::serialize_cbor(&TransferSubscriptionsParams {
+                from: origin,
+                to: new_owner,
+                machine: rt.receiver,
+                subscriptions: vec![MachineSubscription {
+                    hash: add_params.hash,
+                    key: add_params.key.clone(),
+                    version: 1,
+                }],
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            IpldBlock::serialize_cbor(&1u64).unwrap(),
+            ExitCode::OK,
+        );
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::ApproveSubscriptionTransfer as MethodNum,
+            IpldBlock::serialize_cbor(&ApproveSubscriptionTransferParams {
+                from: origin,
+                to: None,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            None,
+            ExitCode::OK,
+        );
+        // An ADM without the method doesn't block the transfer
+        rt.expect_send_simple(
+            ADM_ACTOR_ADDR,
+            ADM_TRANSFER_MACHINE_METHOD,
+            IpldBlock::serialize_cbor(&AdmTransferMachineParams {
+                previous_owner: origin,
+                new_owner,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            None,
+            ExitCode::USR_UNHANDLED_MESSAGE,
+        );
         let event = to_actor_event(
-            object_added(params.key.clone(), &params.hash.0, &params.metadata).unwrap(),
+            ownership_transferred(origin_delegated_addr, new_owner_delegated_addr).unwrap(),
         )
         .unwrap();
         rt.expect_emitted_event(event);
-    }
+        rt.call::<Actor>(
+            Method::AcceptOwnership as u64,
+            IpldBlock::serialize_cbor(&AcceptOwnershipParams { from: new_owner }).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        let state = rt.state::<State>().unwrap();
+        assert_eq!(state.owner, new_owner);
+        assert_eq!(state.pending_owner, None);
 
-    fn expect_emitted_delete_event(rt: &MockRuntime, params: &DeleteParams, hash: Hash) {
-        let event = to_actor_event(object_deleted(params.key.clone(), &hash.0).unwrap()).unwrap();
+        // The previous owner can no longer set metadata, but the new owner can
+        let metadata = HashMap::from([("name".to_string(), "bucket".to_string())]);
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, origin);
+        rt.set_origin(origin);
+        rt.expect_validate_caller_any();
+        let result = rt.call::<Actor>(
+            Method::SetMetadata as u64,
+            IpldBlock::serialize_cbor(&SetMetadataParams {
+                from: origin,
+                metadata: metadata.clone(),
+            })
+            .unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_FORBIDDEN));
+        rt.verify();
+
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, new_owner);
+        rt.set_origin(new_owner);
+        rt.expect_validate_caller_any();
+        let event =
+            to_actor_event(metadata_updated(Kind::Bucket as u8, &metadata).unwrap()).unwrap();
         rt.expect_emitted_event(event);
+        rt.call::<Actor>(
+            Method::SetMetadata as u64,
+            IpldBlock::serialize_cbor(&SetMetadataParams {
+                from: new_owner,
+                metadata: metadata.clone(),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        assert_eq!(rt.state::<State>().unwrap().metadata, metadata);
     }
 
     #[test]
-    pub fn test_add_object() {
-        setup_logs();
+    pub fn test_move_subscriptions() {
         let (rt, origin) = get_runtime();
+        let new_owner = Address::new_id(112);
+        for key in ["a", "b", "c"] {
+            let hash = new_hash(256);
+            let add_params = AddParams {
+                source: new_pk(),
+                key: key.as_bytes().to_vec(),
+                hash: hash.0,
+                recovery_hash: new_hash(256).0,
+                size: hash.1,
+                ttl: None,
+                metadata: HashMap::new(),
+                from: origin,
+                overwrite: false,
+            };
+            expect_add_version(&rt, origin, &add_params, 0);
+        }
+        let mut state = rt.state::<State>().unwrap();
+        state.subscription_transfer = Some(SubscriptionTransfer::new(origin, new_owner));
+        rt.replace_state(&state);
 
-        // Add an object
+        // Objects can't change until all subscriptions are moved
+        rt.expect_validate_caller_any();
         let hash = new_hash(256);
+        let result = rt.call::<Actor>(
+            Method::AddObject as u64,
+            IpldBlock::serialize_cbor(&AddParams {
+                source: new_pk(),
+                key: b"d".to_vec(),
+                hash: hash.0,
+                recovery_hash: new_hash(256).0,
+                size: hash.1,
+                ttl: None,
+                metadata: HashMap::new(),
+                from: origin,
+                overwrite: false,
+            })
+            .unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_ILLEGAL_STATE));
+        rt.verify();
+
+        // Keys are visited in HAMT order, so page through it the same way
+        let mut start_key = None;
+        let mut moved = 0;
+        loop {
+            let state = rt.state::<State>().unwrap();
+            let mut subscriptions = Vec::new();
+            let (_, next_key) = state
+                .for_each_object_ranged(rt.store(), start_key.as_ref(), 2, |key, object| {
+                    subscriptions.push(MachineSubscription {
+                        hash: object.hash,
+                        key: key.to_vec(),
+                        version: 0,
+                    });
+                    Ok(())
+                })
+                .unwrap();
+            rt.expect_validate_caller_any();
+            rt.expect_send_simple(
+                BLOBS_ACTOR_ADDR,
+                BlobMethod::TransferSubscriptions as MethodNum,
+                IpldBlock::serialize_cbor(&TransferSubscriptionsParams {
+                    from: origin,
+                    to: new_owner,
+                    machine: rt.receiver,
+                    subscriptions: subscriptions.clone(),
+                })
+                .unwrap(),
+                TokenAmount::from_whole(0),
+                IpldBlock::serialize_cbor(&(subscriptions.len() as u64)).unwrap(),
+                ExitCode::OK,
+            );
+            if next_key.is_none() {
+                rt.expect_send_simple(
+                    BLOBS_ACTOR_ADDR,
+                    BlobMethod::ApproveSubscriptionTransfer as MethodNum,
+                    IpldBlock::serialize_cbor(&ApproveSubscriptionTransferParams {
+                        from: origin,
+                        to: None,
+                    })
+                    .unwrap(),
+                    TokenAmount::from_whole(0),
+                    None,
+                    ExitCode::OK,
+                );
+            }
+            let result = rt
+                .call::<Actor>(
+                    Method::MoveSubscriptions as u64,
+                    IpldBlock::serialize_cbor(&MoveSubscriptionsParams { limit: 2 }).unwrap(),
+                )
+                .unwrap()
+                .unwrap()
+                .deserialize::<MoveSubscriptionsReturn>()
+                .unwrap();
+            rt.verify();
+            assert_eq!(result.moved, subscriptions.len() as u64);
+            assert_eq!(result.done, next_key.is_none());
+            moved += result.moved;
+            start_key = next_key;
+            if result.done {
+                break;
+            }
+        }
+        assert_eq!(moved, 3);
+        let state = rt.state::<State>().unwrap();
+        assert_eq!(state.subscription_transfer, None);
+    }
+
+    #[test]
+    pub fn test_object_versions() {
+        let (rt, origin) = get_runtime();
+
+        rt.expect_validate_caller_any();
+        rt.call::<Actor>(
+            Method::EnableVersioning as u64,
+            IpldBlock::serialize_cbor(&EnableVersioningParams {
+                from: origin,
+                max_versions: 0,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+
+        // Add two versions, each with its own subscription
         let key = vec![0, 1, 2];
-        let add_params: AddParams = AddParams {
+        let hash = new_hash(256);
+        let add_params = AddParams {
             source: new_pk(),
             key: key.clone(),
             hash: hash.0,
@@ -509,52 +2603,198 @@ mod tests {
             from: origin,
             overwrite: false,
         };
+        let sub_id1 = expect_add_version(&rt, origin, &add_params, 1);
+        let hash = new_hash(256);
+        let add_params2 = AddParams {
+            hash: hash.0,
+            recovery_hash: new_hash(256).0,
+            size: hash.1,
+            metadata: HashMap::from([("foo".into(), "bar".into())]),
+            overwrite: true,
+            ..add_params.clone()
+        };
+        let sub_id2 = expect_add_version(&rt, origin, &add_params2, 2);
+        assert_ne!(sub_id1, sub_id2);
+        assert_ne!(
+            sub_id1,
+            get_blob_id(&rt.state::<State>().unwrap(), &key).unwrap()
+        );
+
+        let versions = list_versions(&rt, &key);
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].version, 1);
+        assert_eq!(versions[0].hash, add_params.hash);
+        assert_eq!(versions[1].version, 2);
+        assert_eq!(versions[1].hash, add_params2.hash);
+        assert_eq!(versions[1].metadata, add_params2.metadata);
+
+        // Deleting the object adds a delete marker and keeps the blobs
+        let delete_params = DeleteParams {
+            key: key.clone(),
+            from: origin,
+        };
+        rt.expect_validate_caller_any();
+        expect_emitted_delete_event(&rt, &delete_params, add_params2.hash);
+        rt.call::<Actor>(
+            Method::DeleteObject as u64,
+            IpldBlock::serialize_cbor(&delete_params).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        let state = rt.state::<State>().unwrap();
+        assert_eq!(state.get(rt.store(), &BytesKey(key.clone())).unwrap(), None);
+        let versions = list_versions(&rt, &key);
+        assert_eq!(versions.len(), 3);
+        assert!(versions[2].delete_marker);
+
+        // Deleting the delete marker restores the previous version
         rt.expect_validate_caller_any();
+        expect_emitted_add_event(&rt, &add_params2);
+        rt.call::<Actor>(
+            Method::DeleteObjectVersion as u64,
+            IpldBlock::serialize_cbor(&DeleteVersionParams {
+                key: key.clone(),
+                version: 3,
+                from: origin,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
         let state = rt.state::<State>().unwrap();
-        let sub_id = get_blob_id(&state, &key).unwrap();
+        let object = state
+            .get(rt.store(), &BytesKey(key.clone()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(object.hash, add_params2.hash);
+
+        // Deleting an old version deletes its blob subscription only
+        rt.expect_validate_caller_any();
         rt.expect_send_simple(
             BLOBS_ACTOR_ADDR,
-            BlobMethod::AddBlob as MethodNum,
-            IpldBlock::serialize_cbor(&AddBlobParams {
+            BlobMethod::DeleteBlob as MethodNum,
+            IpldBlock::serialize_cbor(&DeleteBlobParams {
                 sponsor: Some(origin),
-                source: add_params.source,
                 hash: add_params.hash,
-                metadata_hash: add_params.recovery_hash,
-                id: sub_id,
-                size: add_params.size,
-                ttl: add_params.ttl,
+                id: sub_id1,
                 from: origin,
             })
             .unwrap(),
             TokenAmount::from_whole(0),
-            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            None,
             ExitCode::OK,
         );
-        expect_emitted_add_event(&rt, &add_params);
+        rt.call::<Actor>(
+            Method::DeleteObjectVersion as u64,
+            IpldBlock::serialize_cbor(&DeleteVersionParams {
+                key: key.clone(),
+                version: 1,
+                from: origin,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        let versions = list_versions(&rt, &key);
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, 2);
+
+        // Get a specific version
+        let blob = Blob {
+            size: add_params2.size,
+            subscribers: HashMap::from([(
+                origin.to_string(),
+                SubscriptionGroup {
+                    subscriptions: HashMap::from([(
+                        sub_id2.to_string(),
+                        Subscription {
+                            added: 0,
+                            expiry: 3600,
+                            source: add_params2.source,
+                            delegate: Some(origin),
+                            failed: false,
+                            auto_renew: None,
+                            ..Default::default()
+                        },
+                    )]),
+                },
+            )]),
+            status: BlobStatus::Resolved,
+            metadata_hash: add_params2.recovery_hash,
+        };
+        rt.expect_validate_caller_any();
+        rt.expect_send(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::GetBlob as MethodNum,
+            IpldBlock::serialize_cbor(&GetBlobParams(add_params2.hash)).unwrap(),
+            TokenAmount::from_whole(0),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&Some(blob)).unwrap(),
+            ExitCode::OK,
+            None,
+        );
         let result = rt
             .call::<Actor>(
-                Method::AddObject as u64,
-                IpldBlock::serialize_cbor(&add_params).unwrap(),
+                Method::GetObjectVersion as u64,
+                IpldBlock::serialize_cbor(&GetVersionParams {
+                    key: key.clone(),
+                    version: 2,
+                })
+                .unwrap(),
             )
             .unwrap()
             .unwrap()
-            .deserialize::<Object>()
+            .deserialize::<Option<ObjectVersion>>()
             .unwrap();
-        assert_eq!(add_params.hash, result.hash);
-        assert_eq!(add_params.recovery_hash, result.recovery_hash);
-        assert_eq!(add_params.size, result.size);
-        assert_eq!(add_params.metadata, result.metadata);
+        assert_eq!(
+            result,
+            Some(ObjectVersion {
+                version: 2,
+                hash: add_params2.hash,
+                recovery_hash: add_params2.recovery_hash,
+                size: add_params2.size,
+                expiry: 3600,
+                metadata: add_params2.metadata,
+                delete_marker: false,
+            })
+        );
+        rt.verify();
+
+        // Unknown versions are not found
+        rt.expect_validate_caller_any();
+        let result = rt.call::<Actor>(
+            Method::DeleteObjectVersion as u64,
+            IpldBlock::serialize_cbor(&DeleteVersionParams {
+                key,
+                version: 1,
+                from: origin,
+            })
+            .unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_NOT_FOUND));
         rt.verify();
     }
 
     #[test]
-    pub fn test_add_overwrite() {
+    pub fn test_max_object_versions() {
         let (rt, origin) = get_runtime();
 
-        // Add an object
-        let hash = new_hash(256);
+        rt.expect_validate_caller_any();
+        rt.call::<Actor>(
+            Method::EnableVersioning as u64,
+            IpldBlock::serialize_cbor(&EnableVersioningParams {
+                from: origin,
+                max_versions: 2,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+
         let key = vec![0, 1, 2];
-        let add_params: AddParams = AddParams {
+        let hash = new_hash(256);
+        let add_params = AddParams {
             source: new_pk(),
             key: key.clone(),
             hash: hash.0,
@@ -563,22 +2803,36 @@ mod tests {
             ttl: None,
             metadata: HashMap::new(),
             from: origin,
-            overwrite: false,
+            overwrite: true,
+        };
+        let sub_id1 = expect_add_version(&rt, origin, &add_params, 1);
+        let hash = new_hash(256);
+        let add_params2 = AddParams {
+            hash: hash.0,
+            size: hash.1,
+            ..add_params.clone()
+        };
+        expect_add_version(&rt, origin, &add_params2, 2);
+
+        // A third version prunes the oldest one along with its blob subscription
+        let hash = new_hash(256);
+        let add_params3 = AddParams {
+            hash: hash.0,
+            size: hash.1,
+            ..add_params.clone()
         };
         rt.expect_validate_caller_any();
-        let state = rt.state::<State>().unwrap();
-        let sub_id = get_blob_id(&state, &key).unwrap();
         rt.expect_send_simple(
             BLOBS_ACTOR_ADDR,
             BlobMethod::AddBlob as MethodNum,
             IpldBlock::serialize_cbor(&AddBlobParams {
                 sponsor: Some(origin),
-                source: add_params.source,
-                hash: add_params.hash,
-                metadata_hash: add_params.recovery_hash,
-                id: sub_id.clone(),
-                size: add_params.size,
-                ttl: add_params.ttl,
+                source: add_params3.source,
+                hash: add_params3.hash,
+                metadata_hash: add_params3.recovery_hash,
+                id: get_version_blob_id(&rt.state::<State>().unwrap(), &key, 3).unwrap(),
+                size: add_params3.size,
+                ttl: add_params3.ttl,
                 from: origin,
             })
             .unwrap(),
@@ -586,168 +2840,152 @@ mod tests {
             IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
             ExitCode::OK,
         );
-        expect_emitted_add_event(&rt, &add_params);
-        let result = rt
-            .call::<Actor>(
-                Method::AddObject as u64,
-                IpldBlock::serialize_cbor(&add_params).unwrap(),
-            )
-            .unwrap()
-            .unwrap()
-            .deserialize::<Object>()
-            .unwrap();
-        assert_eq!(add_params.hash, result.hash);
-        assert_eq!(add_params.metadata, result.metadata);
-        assert_eq!(add_params.recovery_hash, result.recovery_hash);
-        assert_eq!(add_params.size, result.size);
+        rt.expect_send_simple(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::DeleteBlob as MethodNum,
+            IpldBlock::serialize_cbor(&DeleteBlobParams {
+                sponsor: Some(origin),
+                hash: add_params.hash,
+                id: sub_id1,
+                from: origin,
+            })
+            .unwrap(),
+            TokenAmount::from_whole(0),
+            None,
+            ExitCode::OK,
+        );
+        expect_emitted_add_event(&rt, &add_params3);
+        rt.call::<Actor>(
+            Method::AddObject as u64,
+            IpldBlock::serialize_cbor(&add_params3).unwrap(),
+        )
+        .unwrap();
         rt.verify();
+        let versions = list_versions(&rt, &key);
+        assert_eq!(
+            versions.iter().map(|v| v.version).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(
+            rt.state::<State>().unwrap().used_bytes(rt.store()).unwrap(),
+            add_params2.size + add_params3.size
+        );
 
-        // Overwrite object (old blob is deleted)
-        let hash = new_hash(256);
-        let add_params2 = AddParams {
-            source: add_params.source,
-            key: add_params.key,
-            hash: hash.0,
-            recovery_hash: new_hash(256).0,
-            size: hash.1,
-            ttl: None,
-            metadata: HashMap::new(),
+        // Delete markers count towards the limit too
+        let delete_params = DeleteParams {
+            key: key.clone(),
             from: origin,
-            overwrite: true,
         };
         rt.expect_validate_caller_any();
         rt.expect_send_simple(
             BLOBS_ACTOR_ADDR,
-            BlobMethod::OverwriteBlob as MethodNum,
-            IpldBlock::serialize_cbor(&OverwriteBlobParams {
-                old_hash: add_params.hash,
-                add: AddBlobParams {
-                    id: sub_id,
-                    hash: add_params2.hash,
-                    sponsor: Some(origin),
-                    source: add_params2.source,
-                    metadata_hash: add_params2.recovery_hash,
-                    size: add_params2.size,
-                    ttl: add_params2.ttl,
-                    from: origin,
-                },
+            BlobMethod::DeleteBlob as MethodNum,
+            IpldBlock::serialize_cbor(&DeleteBlobParams {
+                sponsor: Some(origin),
+                hash: add_params2.hash,
+                id: get_version_blob_id(&rt.state::<State>().unwrap(), &key, 2).unwrap(),
+                from: origin,
             })
             .unwrap(),
             TokenAmount::from_whole(0),
-            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            None,
             ExitCode::OK,
         );
-        expect_emitted_add_event(&rt, &add_params2);
-        let result = rt
-            .call::<Actor>(
-                Method::AddObject as u64,
-                IpldBlock::serialize_cbor(&add_params2).unwrap(),
-            )
-            .unwrap()
-            .unwrap()
-            .deserialize::<Object>()
-            .unwrap();
-        assert_eq!(add_params2.hash, result.hash);
-        assert_eq!(add_params2.metadata, result.metadata);
-        assert_eq!(add_params2.recovery_hash, result.recovery_hash);
-        assert_eq!(add_params2.size, result.size);
+        expect_emitted_delete_event(&rt, &delete_params, add_params3.hash);
+        rt.call::<Actor>(
+            Method::DeleteObject as u64,
+            IpldBlock::serialize_cbor(&delete_params).unwrap(),
+        )
+        .unwrap();
         rt.verify();
+        let versions = list_versions(&rt, &key);
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].version, 3);
+        assert!(versions[1].delete_marker);
     }
 
     #[test]
-    pub fn test_add_overwrite_fail() {
+    pub fn test_lifecycle_rules() {
         let (rt, origin) = get_runtime();
 
-        // Add an object
-        let hash = new_hash(256);
-        let key = vec![0, 1, 2];
-        let add_params: AddParams = AddParams {
-            source: new_pk(),
-            key: key.clone(),
-            hash: hash.0,
-            size: hash.1,
-            recovery_hash: new_hash(256).0,
-            ttl: None,
-            metadata: HashMap::new(),
-            from: origin,
-            overwrite: false,
-        };
+        let rules = vec![
+            LifecycleRule {
+                prefix: b"tmp/".to_vec(),
+                ttl: 3600,
+                auto_renew: false,
+            },
+            LifecycleRule {
+                prefix: b"logs/".to_vec(),
+                ttl: 7200,
+                auto_renew: true,
+            },
+        ];
+
+        // Fail if "from" is not the owner
+        let alien_id_addr = Address::new_id(112);
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, alien_id_addr);
+        rt.set_origin(alien_id_addr);
         rt.expect_validate_caller_any();
-        let state = rt.state::<State>().unwrap();
-        let sub_id = get_blob_id(&state, &key).unwrap();
-        rt.expect_send_simple(
-            BLOBS_ACTOR_ADDR,
-            BlobMethod::AddBlob as MethodNum,
-            IpldBlock::serialize_cbor(&AddBlobParams {
-                sponsor: Some(origin),
-                source: add_params.source,
-                hash: add_params.hash,
-                metadata_hash: add_params.recovery_hash,
-                id: sub_id,
-                size: add_params.size,
-                ttl: add_params.ttl,
+        let result = rt.call::<Actor>(
+            Method::SetLifecycleRules as u64,
+            IpldBlock::serialize_cbor(&SetLifecycleRulesParams {
+                rules: rules.clone(),
+                from: alien_id_addr,
+            })
+            .unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_FORBIDDEN));
+        rt.verify();
+
+        // Fail if a rule has a non-positive TTL
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, origin);
+        rt.set_origin(origin);
+        rt.expect_validate_caller_any();
+        let mut invalid_rules = rules.clone();
+        invalid_rules[0].ttl = 0;
+        let result = rt.call::<Actor>(
+            Method::SetLifecycleRules as u64,
+            IpldBlock::serialize_cbor(&SetLifecycleRulesParams {
+                rules: invalid_rules,
+                from: origin,
+            })
+            .unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_ILLEGAL_ARGUMENT));
+        rt.verify();
+
+        rt.expect_validate_caller_any();
+        rt.call::<Actor>(
+            Method::SetLifecycleRules as u64,
+            IpldBlock::serialize_cbor(&SetLifecycleRulesParams {
+                rules: rules.clone(),
                 from: origin,
             })
             .unwrap(),
-            TokenAmount::from_whole(0),
-            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
-            ExitCode::OK,
-        );
-        expect_emitted_add_event(&rt, &add_params);
+        )
+        .unwrap();
+        rt.verify();
+
+        rt.expect_validate_caller_any();
         let result = rt
-            .call::<Actor>(
-                Method::AddObject as u64,
-                IpldBlock::serialize_cbor(&add_params).unwrap(),
-            )
+            .call::<Actor>(Method::GetLifecycleRules as u64, None)
             .unwrap()
             .unwrap()
-            .deserialize::<Object>()
+            .deserialize::<Vec<LifecycleRule>>()
             .unwrap();
-        let state = rt.state::<State>().unwrap();
-        assert_eq!(add_params.hash, result.hash);
-        assert_eq!(add_params.metadata, result.metadata);
-        assert_eq!(add_params.recovery_hash, result.recovery_hash);
-        assert_eq!(add_params.size, result.size);
-        rt.verify();
-
-        // Try to overwrite
-        let hash = new_hash(256);
-        let add_params2 = AddParams {
-            source: add_params.source,
-            key: add_params.key,
-            hash: hash.0,
-            size: hash.1,
-            recovery_hash: new_hash(256).0,
-            ttl: None,
-            metadata: HashMap::new(),
-            from: origin,
-            overwrite: false,
-        };
-        rt.expect_validate_caller_any();
-        let result = rt.call::<Actor>(
-            Method::AddObject as u64,
-            IpldBlock::serialize_cbor(&add_params2).unwrap(),
-        );
-        assert!(result.is_err_and(|e| { e.msg().eq("key exists; use overwrite") }));
-        let state2 = rt.state::<State>().unwrap();
-        assert_eq!(state2.root, state.root);
+        assert_eq!(result, rules);
         rt.verify();
-    }
-
-    #[test]
-    pub fn test_delete_object() {
-        let (rt, origin) = get_runtime();
 
-        // Add an object
-        let key = vec![0, 1, 2];
+        // The matching rule's TTL replaces the requested one and enables auto-renewal
         let hash = new_hash(256);
-        let add_params: AddParams = AddParams {
+        let key = b"logs/app.log".to_vec();
+        let add_params = AddParams {
             source: new_pk(),
             key: key.clone(),
             hash: hash.0,
-            size: hash.1,
             recovery_hash: new_hash(256).0,
-            ttl: None,
+            size: hash.1,
+            ttl: Some(100),
             metadata: HashMap::new(),
             from: origin,
             overwrite: false,
@@ -762,10 +3000,10 @@ mod tests {
                 sponsor: Some(origin),
                 source: add_params.source,
                 hash: add_params.hash,
+                metadata_hash: add_params.recovery_hash,
                 id: sub_id.clone(),
                 size: add_params.size,
-                metadata_hash: add_params.recovery_hash,
-                ttl: add_params.ttl,
+                ttl: Some(7200),
                 from: origin,
             })
             .unwrap(),
@@ -773,32 +3011,14 @@ mod tests {
             IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
             ExitCode::OK,
         );
-        expect_emitted_add_event(&rt, &add_params);
-        let result_add = rt
-            .call::<Actor>(
-                Method::AddObject as u64,
-                IpldBlock::serialize_cbor(&add_params).unwrap(),
-            )
-            .unwrap()
-            .unwrap()
-            .deserialize::<Object>()
-            .unwrap();
-        assert_eq!(add_params.hash, result_add.hash);
-        assert_eq!(add_params.metadata, result_add.metadata);
-        assert_eq!(add_params.recovery_hash, result_add.recovery_hash);
-        assert_eq!(add_params.size, result_add.size);
-        rt.verify();
-
-        // Delete object
-        let delete_params = DeleteParams { key, from: origin };
-        rt.expect_validate_caller_any();
         rt.expect_send_simple(
             BLOBS_ACTOR_ADDR,
-            BlobMethod::DeleteBlob as MethodNum,
-            IpldBlock::serialize_cbor(&DeleteBlobParams {
+            BlobMethod::SetBlobAutoRenew as MethodNum,
+            IpldBlock::serialize_cbor(&SetBlobAutoRenewParams {
                 sponsor: Some(origin),
                 hash: add_params.hash,
                 id: sub_id,
+                ttl: Some(7200),
                 from: origin,
             })
             .unwrap(),
@@ -806,50 +3026,30 @@ mod tests {
             None,
             ExitCode::OK,
         );
-        expect_emitted_delete_event(&rt, &delete_params, add_params.hash);
-        let result_delete = rt.call::<Actor>(
-            Method::DeleteObject as u64,
-            IpldBlock::serialize_cbor(&delete_params).unwrap(),
-        );
-        assert!(result_delete.is_ok());
-        rt.verify();
-    }
-
-    #[test]
-    pub fn test_get_object_none() {
-        let (rt, _) = get_runtime();
-
-        let get_params = GetParams(vec![0, 1, 2]);
-        rt.expect_validate_caller_any();
-        let result = rt
-            .call::<Actor>(
-                Method::GetObject as u64,
-                IpldBlock::serialize_cbor(&get_params).unwrap(),
-            )
-            .unwrap()
-            .unwrap()
-            .deserialize::<Option<Object>>();
-        assert!(result.is_ok());
-        assert_eq!(result, Ok(None));
+        expect_emitted_add_event(&rt, &add_params);
+        rt.call::<Actor>(
+            Method::AddObject as u64,
+            IpldBlock::serialize_cbor(&add_params).unwrap(),
+        )
+        .unwrap();
         rt.verify();
     }
 
     #[test]
-    pub fn test_get_object() {
+    pub fn test_copy_and_move_object() {
         let (rt, origin) = get_runtime();
 
         // Add an object
-        let key = vec![0, 1, 2];
+        let key = b"foo".to_vec();
         let hash = new_hash(256);
-        let ttl = ChainEpoch::from(3600);
-        let add_params: AddParams = AddParams {
+        let add_params = AddParams {
             source: new_pk(),
             key: key.clone(),
             hash: hash.0,
-            size: hash.1,
             recovery_hash: new_hash(256).0,
-            ttl: Some(ttl),
-            metadata: HashMap::new(),
+            size: hash.1,
+            ttl: None,
+            metadata: HashMap::from([("foo".into(), "bar".into())]),
             from: origin,
             overwrite: false,
         };
@@ -863,9 +3063,9 @@ mod tests {
                 sponsor: Some(origin),
                 source: add_params.source,
                 hash: add_params.hash,
+                metadata_hash: add_params.recovery_hash,
                 id: sub_id.clone(),
                 size: add_params.size,
-                metadata_hash: add_params.recovery_hash,
                 ttl: add_params.ttl,
                 from: origin,
             })
@@ -879,14 +3079,10 @@ mod tests {
             Method::AddObject as u64,
             IpldBlock::serialize_cbor(&add_params).unwrap(),
         )
-        .unwrap()
-        .unwrap()
-        .deserialize::<Object>()
         .unwrap();
         rt.verify();
 
-        // Get the object
-        let blob = Blob {
+        let resolved_blob = |sub_id: &SubscriptionId| Blob {
             size: add_params.size,
             subscribers: HashMap::from([(
                 origin.to_string(),
@@ -895,10 +3091,12 @@ mod tests {
                         sub_id.to_string(),
                         Subscription {
                             added: 0,
-                            expiry: ttl,
+                            expiry: 3600,
                             source: add_params.source,
-                            delegate: Some(origin),
+                            delegate: None,
                             failed: false,
+                            auto_renew: None,
+                            ..Default::default()
                         },
                     )]),
                 },
@@ -906,267 +3104,578 @@ mod tests {
             status: BlobStatus::Resolved,
             metadata_hash: add_params.recovery_hash,
         };
+        let expect_get_blob = |sub_id: &SubscriptionId| {
+            rt.expect_send(
+                BLOBS_ACTOR_ADDR,
+                BlobMethod::GetBlob as MethodNum,
+                IpldBlock::serialize_cbor(&GetBlobParams(add_params.hash)).unwrap(),
+                TokenAmount::from_whole(0),
+                None,
+                SendFlags::READ_ONLY,
+                IpldBlock::serialize_cbor(&Some(resolved_blob(sub_id))).unwrap(),
+                ExitCode::OK,
+                None,
+            );
+        };
+        let expect_add_copy = |to_key: &[u8]| {
+            let state = rt.state::<State>().unwrap();
+            let to_sub_id = get_blob_id(&state, to_key).unwrap();
+            rt.expect_send_simple(
+                BLOBS_ACTOR_ADDR,
+                BlobMethod::AddBlob as MethodNum,
+                IpldBlock::serialize_cbor(&AddBlobParams {
+                    sponsor: Some(origin),
+                    source: add_params.source,
+                    hash: add_params.hash,
+                    metadata_hash: add_params.recovery_hash,
+                    id: to_sub_id.clone(),
+                    size: add_params.size,
+                    ttl: None,
+                    from: origin,
+                })
+                .unwrap(),
+                TokenAmount::from_whole(0),
+                IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+                ExitCode::OK,
+            );
+            expect_emitted_add_event(
+                &rt,
+                &AddParams {
+                    key: to_key.to_vec(),
+                    ..add_params.clone()
+                },
+            );
+            to_sub_id
+        };
+
+        // Fail to copy an object onto itself
+        let copy_params = CopyParams {
+            key: key.clone(),
+            to_bucket: None,
+            to_key: key.clone(),
+            ttl: None,
+            metadata: None,
+            overwrite: false,
+            from: origin,
+        };
         rt.expect_validate_caller_any();
-        rt.expect_send(
+        let result = rt.call::<Actor>(
+            Method::CopyObject as u64,
+            IpldBlock::serialize_cbor(&copy_params).unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_ILLEGAL_ARGUMENT));
+        rt.verify();
+
+        // Copy the object to a new key
+        let copy_key = b"bar".to_vec();
+        rt.expect_validate_caller_any();
+        expect_get_blob(&sub_id);
+        let copy_sub_id = expect_add_copy(&copy_key);
+        let result = rt
+            .call::<Actor>(
+                Method::CopyObject as u64,
+                IpldBlock::serialize_cbor(&CopyParams {
+                    to_key: copy_key.clone(),
+                    ..copy_params.clone()
+                })
+                .unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<Object>()
+            .unwrap();
+        assert_eq!(result.hash, add_params.hash);
+        assert_eq!(result.recovery_hash, add_params.recovery_hash);
+        assert_eq!(result.metadata, add_params.metadata);
+        rt.verify();
+
+        // Fill the quota, so copies no longer fit
+        rt.expect_validate_caller_any();
+        rt.call::<Actor>(
+            Method::SetQuota as u64,
+            IpldBlock::serialize_cbor(&SetQuotaParams {
+                quota: Quota {
+                    max_bytes: Some(add_params.size * 2),
+                    max_credit_per_epoch: None,
+                },
+                from: origin,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        rt.expect_validate_caller_any();
+        expect_get_blob(&copy_sub_id);
+        let result = rt.call::<Actor>(
+            Method::CopyObject as u64,
+            IpldBlock::serialize_cbor(&CopyParams {
+                key: copy_key.clone(),
+                to_key: b"qux".to_vec(),
+                ..copy_params.clone()
+            })
+            .unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_FORBIDDEN));
+        rt.verify();
+
+        // Move the copy to another key, which doesn't count against the quota
+        let move_key = b"baz".to_vec();
+        rt.expect_validate_caller_any();
+        expect_get_blob(&copy_sub_id);
+        let move_sub_id = expect_add_copy(&move_key);
+        rt.expect_send_simple(
             BLOBS_ACTOR_ADDR,
-            BlobMethod::GetBlob as MethodNum,
-            IpldBlock::serialize_cbor(&GetBlobParams(add_params.hash)).unwrap(),
+            BlobMethod::DeleteBlob as MethodNum,
+            IpldBlock::serialize_cbor(&DeleteBlobParams {
+                sponsor: Some(origin),
+                hash: add_params.hash,
+                id: copy_sub_id,
+                from: origin,
+            })
+            .unwrap(),
             TokenAmount::from_whole(0),
             None,
-            SendFlags::READ_ONLY,
-            IpldBlock::serialize_cbor(&Some(blob)).unwrap(),
             ExitCode::OK,
-            None,
         );
-        let get_params = GetParams(key);
-        let result = rt
-            .call::<Actor>(
-                Method::GetObject as u64,
-                IpldBlock::serialize_cbor(&get_params).unwrap(),
-            )
+        expect_emitted_delete_event(
+            &rt,
+            &DeleteParams {
+                key: copy_key.clone(),
+                from: origin,
+            },
+            add_params.hash,
+        );
+        rt.call::<Actor>(
+            Method::MoveObject as u64,
+            IpldBlock::serialize_cbor(&MoveParams {
+                key: copy_key.clone(),
+                to_key: move_key.clone(),
+                ..copy_params.clone()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+
+        let state = rt.state::<State>().unwrap();
+        assert!(state.get(rt.store(), &BytesKey(key)).unwrap().is_some());
+        assert!(state
+            .get(rt.store(), &BytesKey(copy_key))
             .unwrap()
+            .is_none());
+        assert!(state
+            .get(rt.store(), &BytesKey(move_key.clone()))
             .unwrap()
-            .deserialize::<Option<Object>>();
-        assert!(result.is_ok());
-        assert_eq!(
-            result.unwrap(),
-            Some(Object {
-                hash: hash.0,
-                recovery_hash: add_params.recovery_hash,
-                size: add_params.size,
-                expiry: ttl,
-                metadata: add_params.metadata,
+            .is_some());
+
+        // Moving in a versioned bucket keeps the source as a previous version,
+        // so it counts against the quota
+        rt.expect_validate_caller_any();
+        rt.call::<Actor>(
+            Method::EnableVersioning as u64,
+            IpldBlock::serialize_cbor(&EnableVersioningParams {
+                from: origin,
+                max_versions: 0,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        rt.expect_validate_caller_any();
+        expect_get_blob(&move_sub_id);
+        let result = rt.call::<Actor>(
+            Method::MoveObject as u64,
+            IpldBlock::serialize_cbor(&MoveParams {
+                key: move_key,
+                to_key: b"qux".to_vec(),
+                ..copy_params
             })
+            .unwrap(),
         );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_FORBIDDEN));
         rt.verify();
     }
 
     #[test]
-    pub fn test_update_object_metadata() {
+    pub fn test_delete_prefix() {
+        let (rt, origin) = get_runtime();
+
+        // Unversioned objects use the version 0 subscription ID
+        let mut sub_ids = HashMap::new();
+        for key in ["logs/a", "logs/b", "logs/c", "img/a"] {
+            let hash = new_hash(256);
+            let add_params = AddParams {
+                source: new_pk(),
+                key: key.as_bytes().to_vec(),
+                hash: hash.0,
+                recovery_hash: new_hash(256).0,
+                size: hash.1,
+                ttl: None,
+                metadata: HashMap::new(),
+                from: origin,
+                overwrite: false,
+            };
+            let sub_id = expect_add_version(&rt, origin, &add_params, 0);
+            sub_ids.insert(add_params.key.clone(), (sub_id, add_params.hash));
+        }
+
+        let expect_delete = |key: &[u8]| {
+            let (sub_id, hash) = sub_ids.get(key).unwrap().clone();
+            rt.expect_send_simple(
+                BLOBS_ACTOR_ADDR,
+                BlobMethod::DeleteBlob as MethodNum,
+                IpldBlock::serialize_cbor(&DeleteBlobParams {
+                    sponsor: Some(origin),
+                    hash,
+                    id: sub_id,
+                    from: origin,
+                })
+                .unwrap(),
+                TokenAmount::from_whole(0),
+                None,
+                ExitCode::OK,
+            );
+            expect_emitted_delete_event(
+                &rt,
+                &DeleteParams {
+                    key: key.to_vec(),
+                    from: origin,
+                },
+                hash,
+            );
+        };
+
+        // Keys are visited in HAMT order, so page through it the same way
+        let mut start_key = None;
+        let mut deleted = 0;
+        loop {
+            let state = rt.state::<State>().unwrap();
+            let mut keys = Vec::new();
+            let (_, next_key) = state
+                .list(
+                    rt.store(),
+                    b"logs/".to_vec(),
+                    vec![],
+                    start_key.clone().map(BytesKey).as_ref(),
+                    2,
+                    |key, _| {
+                        keys.push(key);
+                        Ok(())
+                    },
+                )
+                .unwrap();
+            rt.expect_validate_caller_any();
+            for key in &keys {
+                expect_delete(key);
+            }
+            let result = rt
+                .call::<Actor>(
+                    Method::DeletePrefix as u64,
+                    IpldBlock::serialize_cbor(&DeletePrefixParams {
+                        prefix: b"logs/".to_vec(),
+                        start_key: start_key.clone(),
+                        limit: 2,
+                        from: origin,
+                    })
+                    .unwrap(),
+                )
+                .unwrap()
+                .unwrap()
+                .deserialize::<DeleteObjectsReturn>()
+                .unwrap();
+            rt.verify();
+            assert_eq!(result.deleted, keys.len() as u64);
+            assert_eq!(result.next_key, next_key.map(|key| key.0));
+            deleted += result.deleted;
+            start_key = result.next_key;
+            if start_key.is_none() {
+                break;
+            }
+        }
+        assert_eq!(deleted, 3);
+
+        let state = rt.state::<State>().unwrap();
+        let mut keys = Vec::new();
+        state
+            .list(rt.store(), vec![], vec![], None, 0, |key, _| {
+                keys.push(key);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(keys, vec![b"img/a".to_vec()]);
+    }
+
+    #[test]
+    pub fn test_delete_objects() {
         let (rt, origin) = get_runtime();
 
-        // Add an object
         let hash = new_hash(256);
-        let key = vec![0, 1, 2];
-        let add_params: AddParams = AddParams {
+        let add_params = AddParams {
             source: new_pk(),
-            key: key.clone(),
+            key: b"foo".to_vec(),
             hash: hash.0,
-            size: hash.1,
             recovery_hash: new_hash(256).0,
+            size: hash.1,
             ttl: None,
-            metadata: HashMap::from([("foo".into(), "bar".into()), ("foo2".into(), "bar".into())]),
+            metadata: HashMap::new(),
             from: origin,
             overwrite: false,
         };
+        let sub_id = expect_add_version(&rt, origin, &add_params, 0);
+
+        // Fail if there are too many keys
+        rt.expect_validate_caller_any();
+        let result = rt.call::<Actor>(
+            Method::DeleteObjects as u64,
+            IpldBlock::serialize_cbor(&DeleteObjectsParams {
+                keys: vec![vec![0]; MAX_DELETE_LIMIT as usize + 1],
+                from: origin,
+            })
+            .unwrap(),
+        );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_ILLEGAL_ARGUMENT));
+        rt.verify();
+
+        // Missing keys are skipped
         rt.expect_validate_caller_any();
-        let state = rt.state::<State>().unwrap();
-        let sub_id = get_blob_id(&state, &key).unwrap();
         rt.expect_send_simple(
             BLOBS_ACTOR_ADDR,
-            BlobMethod::AddBlob as MethodNum,
-            IpldBlock::serialize_cbor(&AddBlobParams {
+            BlobMethod::DeleteBlob as MethodNum,
+            IpldBlock::serialize_cbor(&DeleteBlobParams {
                 sponsor: Some(origin),
-                source: add_params.source,
                 hash: add_params.hash,
-                metadata_hash: add_params.recovery_hash,
                 id: sub_id,
-                size: add_params.size,
-                ttl: add_params.ttl,
                 from: origin,
             })
             .unwrap(),
             TokenAmount::from_whole(0),
-            IpldBlock::serialize_cbor(&Subscription::default()).unwrap(),
+            None,
             ExitCode::OK,
         );
-        expect_emitted_add_event(&rt, &add_params);
+        expect_emitted_delete_event(
+            &rt,
+            &DeleteParams {
+                key: add_params.key.clone(),
+                from: origin,
+            },
+            add_params.hash,
+        );
         let result = rt
             .call::<Actor>(
-                Method::AddObject as u64,
-                IpldBlock::serialize_cbor(&add_params).unwrap(),
+                Method::DeleteObjects as u64,
+                IpldBlock::serialize_cbor(&DeleteObjectsParams {
+                    keys: vec![b"bar".to_vec(), add_params.key.clone()],
+                    from: origin,
+                })
+                .unwrap(),
             )
             .unwrap()
             .unwrap()
-            .deserialize::<Object>()
+            .deserialize::<DeleteObjectsReturn>()
             .unwrap();
-        let state = rt.state::<State>().unwrap();
-        assert_eq!(add_params.hash, result.hash);
-        assert_eq!(add_params.metadata, result.metadata);
-        assert_eq!(add_params.recovery_hash, result.recovery_hash);
-        assert_eq!(add_params.size, result.size);
+        assert_eq!(
+            result,
+            DeleteObjectsReturn {
+                deleted: 1,
+                next_key: None,
+            }
+        );
         rt.verify();
+    }
 
-        // Update metadata
-        let update_object_params = UpdateObjectMetadataParams {
-            from: origin,
-            key: add_params.key.clone(),
-            metadata: HashMap::from([
-                ("foo".into(), Some("zar".into())),
-                ("foo2".into(), None),
-                ("foo3".into(), Some("bar".into())),
-            ]),
+    fn expect_storage_price(rt: &MockRuntime, utilization_multiplier_bps: u64) {
+        rt.expect_send(
+            RECALL_CONFIG_ACTOR_ADDR,
+            RecallConfigMethod::GetConfig as MethodNum,
+            None,
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&RecallConfig::default()).unwrap(),
+            ExitCode::OK,
+            None,
+        );
+        let stats = GetStatsReturn {
+            balance: TokenAmount::zero(),
+            capacity_free: 0,
+            capacity_used: 0,
+            credit_sold: TokenAmount::zero(),
+            credit_committed: TokenAmount::zero(),
+            credit_debited: TokenAmount::zero(),
+            token_credit_rate: TokenCreditRate::from(1),
+            num_accounts: 0,
+            num_blobs: 0,
+            num_added: 0,
+            bytes_added: 0,
+            num_resolving: 0,
+            bytes_resolving: 0,
+            storage_multiplier_bps: utilization_multiplier_bps,
         };
-        rt.expect_validate_caller_any();
-        let event = to_actor_event(
-            object_metadata_updated(
-                add_params.key,
-                &HashMap::from([("foo".into(), "zar".into()), ("foo3".into(), "bar".into())]),
-            )
-            .unwrap(),
-        )
-        .unwrap();
-        rt.expect_emitted_event(event);
-        let result = rt.call::<Actor>(
-            Method::UpdateObjectMetadata as u64,
-            IpldBlock::serialize_cbor(&update_object_params).unwrap(),
+        rt.expect_send(
+            BLOBS_ACTOR_ADDR,
+            BlobMethod::GetStats as MethodNum,
+            None,
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&stats).unwrap(),
+            ExitCode::OK,
+            None,
         );
-        assert!(result.is_ok());
+    }
+
+    fn get_quota(rt: &MockRuntime, utilization_multiplier_bps: u64) -> GetQuotaReturn {
+        rt.expect_validate_caller_any();
+        expect_storage_price(rt, utilization_multiplier_bps);
+        let result = rt
+            .call::<Actor>(Method::GetQuota as u64, None)
+            .unwrap()
+            .unwrap()
+            .deserialize::<GetQuotaReturn>()
+            .unwrap();
         rt.verify();
+        result
+    }
 
-        // Fail if "from" is neither origin nor caller
+    #[test]
+    pub fn test_quota() {
+        let (rt, origin) = get_runtime();
+
+        let hash = new_hash(256);
+        let quota = Quota {
+            max_bytes: Some(hash.1 + 10),
+            max_credit_per_epoch: None,
+        };
+
+        // Fail if "from" is not the owner
         let alien_id_addr = Address::new_id(112);
         rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, alien_id_addr);
         rt.set_origin(alien_id_addr);
         rt.expect_validate_caller_any();
         let result = rt.call::<Actor>(
-            Method::UpdateObjectMetadata as u64,
-            IpldBlock::serialize_cbor(&update_object_params).unwrap(),
+            Method::SetQuota as u64,
+            IpldBlock::serialize_cbor(&SetQuotaParams {
+                quota: quota.clone(),
+                from: alien_id_addr,
+            })
+            .unwrap(),
         );
-        assert!(result.is_err());
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_FORBIDDEN));
         rt.verify();
 
-        // Fail if "from" is not the owner, and has no delegation.
-        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, alien_id_addr);
-        rt.set_origin(alien_id_addr);
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, origin);
+        rt.set_origin(origin);
         rt.expect_validate_caller_any();
-        let alien_update = UpdateObjectMetadataParams {
-            from: alien_id_addr,
-            key: update_object_params.key,
-            metadata: update_object_params.metadata,
-        };
-        rt.expect_send(
-            BLOBS_ACTOR_ADDR,
-            BlobMethod::GetCreditApproval as MethodNum,
-            IpldBlock::serialize_cbor(&GetCreditApprovalParams {
+        rt.call::<Actor>(
+            Method::SetQuota as u64,
+            IpldBlock::serialize_cbor(&SetQuotaParams {
+                quota: quota.clone(),
                 from: origin,
-                to: alien_id_addr,
             })
             .unwrap(),
-            TokenAmount::from_whole(0),
-            None,
-            SendFlags::READ_ONLY,
-            IpldBlock::serialize_cbor::<Option<CreditApproval>>(&None).unwrap(),
-            ExitCode::OK,
-            None,
+        )
+        .unwrap();
+        rt.verify();
+
+        // Add an object within the quota
+        let add_params = AddParams {
+            source: new_pk(),
+            key: b"foo".to_vec(),
+            hash: hash.0,
+            recovery_hash: new_hash(256).0,
+            size: hash.1,
+            ttl: None,
+            metadata: HashMap::new(),
+            from: origin,
+            overwrite: false,
+        };
+        let sub_id = expect_add_version(&rt, origin, &add_params, 0);
+        let base = BASIS_POINTS as u64;
+        assert_eq!(
+            get_quota(&rt, base),
+            GetQuotaReturn {
+                quota: quota.clone(),
+                used_bytes: hash.1,
+                credit_per_epoch: TokenAmount::from_whole(hash.1),
+            }
+        );
+        // The credit follows the storage price
+        assert_eq!(
+            get_quota(&rt, 2 * base).credit_per_epoch,
+            TokenAmount::from_whole(2 * hash.1)
         );
+
+        // Fail to add an object exceeding the quota without calling the blobs actor
+        let hash2 = new_hash(256);
+        rt.expect_validate_caller_any();
         let result = rt.call::<Actor>(
-            Method::UpdateObjectMetadata as u64,
-            IpldBlock::serialize_cbor(&alien_update).unwrap(),
+            Method::AddObject as u64,
+            IpldBlock::serialize_cbor(&AddParams {
+                key: b"bar".to_vec(),
+                hash: hash2.0,
+                size: hash2.1,
+                ..add_params.clone()
+            })
+            .unwrap(),
         );
-        assert!(result.is_err());
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_FORBIDDEN));
         rt.verify();
 
-        // Allowed if there is a delegation
-        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, alien_id_addr);
-        rt.set_origin(alien_id_addr);
+        // Deleting the object releases its bytes
         rt.expect_validate_caller_any();
-        rt.expect_send(
+        rt.expect_send_simple(
             BLOBS_ACTOR_ADDR,
-            BlobMethod::GetCreditApproval as MethodNum,
-            IpldBlock::serialize_cbor(&GetCreditApprovalParams {
+            BlobMethod::DeleteBlob as MethodNum,
+            IpldBlock::serialize_cbor(&DeleteBlobParams {
+                sponsor: Some(origin),
+                hash: add_params.hash,
+                id: sub_id,
                 from: origin,
-                to: alien_id_addr,
             })
             .unwrap(),
             TokenAmount::from_whole(0),
             None,
-            SendFlags::READ_ONLY,
-            // We do not care what is inside credit approval. We only care if it is present.
-            IpldBlock::serialize_cbor::<Option<CreditApproval>>(&Some(CreditApproval {
-                credit_limit: None,
-                gas_fee_limit: None,
-                expiry: None,
-                credit_used: TokenAmount::from_whole(0),
-                gas_fee_used: TokenAmount::from_whole(0),
-            }))
-            .unwrap(),
             ExitCode::OK,
-            None,
         );
-        let event = to_actor_event(
-            object_metadata_updated(
-                alien_update.key.clone(),
-                &HashMap::from([("foo".into(), "zar".into()), ("foo3".into(), "bar".into())]),
-            )
-            .unwrap(),
+        let delete_params = DeleteParams {
+            key: add_params.key.clone(),
+            from: origin,
+        };
+        expect_emitted_delete_event(&rt, &delete_params, add_params.hash);
+        rt.call::<Actor>(
+            Method::DeleteObject as u64,
+            IpldBlock::serialize_cbor(&delete_params).unwrap(),
         )
         .unwrap();
-        rt.expect_emitted_event(event);
-        let result = rt.call::<Actor>(
-            Method::UpdateObjectMetadata as u64,
-            IpldBlock::serialize_cbor(&alien_update).unwrap(),
-        );
-        assert!(result.is_ok());
         rt.verify();
+        assert_eq!(get_quota(&rt, base).used_bytes, 0);
 
-        // Get the object and check metadata
-        let sub_id = get_blob_id(&state, &key).unwrap();
-        let blob = Blob {
-            size: add_params.size,
-            subscribers: HashMap::from([(
-                origin.to_string(),
-                SubscriptionGroup {
-                    subscriptions: HashMap::from([(
-                        sub_id.to_string(),
-                        Subscription {
-                            added: 0,
-                            expiry: ChainEpoch::from(3600),
-                            source: add_params.source,
-                            delegate: Some(origin),
-                            failed: false,
-                        },
-                    )]),
+        // A credit quota is checked against the storage price
+        rt.expect_validate_caller_any();
+        rt.call::<Actor>(
+            Method::SetQuota as u64,
+            IpldBlock::serialize_cbor(&SetQuotaParams {
+                quota: Quota {
+                    max_bytes: None,
+                    max_credit_per_epoch: Some(TokenAmount::from_whole(hash.1)),
                 },
-            )]),
-            status: BlobStatus::Resolved,
-            metadata_hash: add_params.recovery_hash,
-        };
+                from: origin,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
         rt.expect_validate_caller_any();
-        rt.expect_send(
-            BLOBS_ACTOR_ADDR,
-            BlobMethod::GetBlob as MethodNum,
-            IpldBlock::serialize_cbor(&GetBlobParams(add_params.hash)).unwrap(),
-            TokenAmount::from_whole(0),
-            None,
-            SendFlags::READ_ONLY,
-            IpldBlock::serialize_cbor(&Some(blob)).unwrap(),
-            ExitCode::OK,
-            None,
-        );
-        let get_params = GetParams(key);
-        let result = rt
-            .call::<Actor>(
-                Method::GetObject as u64,
-                IpldBlock::serialize_cbor(&get_params).unwrap(),
-            )
-            .unwrap()
-            .unwrap()
-            .deserialize::<Option<Object>>();
-        assert!(result.is_ok());
-        assert_eq!(
-            result.unwrap(),
-            Some(Object {
-                hash: hash.0,
-                recovery_hash: add_params.recovery_hash,
-                size: add_params.size,
-                expiry: ChainEpoch::from(3600),
-                metadata: HashMap::from([
-                    ("foo".into(), "zar".into()),
-                    ("foo3".into(), "bar".into())
-                ]),
+        expect_storage_price(&rt, 2 * base);
+        let result = rt.call::<Actor>(
+            Method::AddObject as u64,
+            IpldBlock::serialize_cbor(&AddParams {
+                key: b"baz".to_vec(),
+                ..add_params
             })
+            .unwrap(),
         );
+        assert!(result.is_err_and(|e| e.exit_code() == ExitCode::USR_FORBIDDEN));
         rt.verify();
     }
 }
@@ -4,9 +4,10 @@
 
 use std::collections::HashMap;
 
-use fendermint_actor_blobs_shared::state::{Hash, PublicKey};
+use fendermint_actor_blobs_shared::state::{Credit, Hash, PublicKey};
 use fendermint_actor_machine::{
-    GET_ADDRESS_METHOD, GET_METADATA_METHOD, INIT_METHOD, METHOD_CONSTRUCTOR,
+    ACCEPT_OWNERSHIP_METHOD, GET_ADDRESS_METHOD, GET_METADATA_METHOD, INIT_METHOD,
+    METHOD_CONSTRUCTOR, SET_METADATA_METHOD, TRANSFER_OWNERSHIP_METHOD,
 };
 use fvm_ipld_encoding::{strict_bytes, tuple::*};
 use fvm_shared::address::Address;
@@ -14,12 +15,18 @@ use fvm_shared::clock::ChainEpoch;
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
 
-pub use crate::state::{ObjectState, State};
+pub use crate::state::{
+    LifecycleRule, ObjectState, ObjectVersion, ObjectVersions, Quota, State, SubscriptionTransfer,
+};
 
 pub const BUCKET_ACTOR_NAME: &str = "bucket";
 pub const MAX_METADATA_ENTRIES: u32 = 20;
 pub const MAX_METADATA_KEY_SIZE: u32 = 32;
 pub const MAX_METADATA_VALUE_SIZE: u32 = 128;
+pub const MAX_LIFECYCLE_RULES: u32 = 20;
+pub const MAX_DELETE_LIMIT: u64 = 100;
+pub const MAX_MOVE_SUBSCRIPTIONS_LIMIT: u64 = 100;
+pub const MAX_OBJECT_VERSIONS: u32 = 100;
 
 #[derive(FromPrimitive)]
 #[repr(u64)]
@@ -28,11 +35,27 @@ pub enum Method {
     Init = INIT_METHOD,
     GetAddress = GET_ADDRESS_METHOD,
     GetMetadata = GET_METADATA_METHOD,
+    TransferOwnership = TRANSFER_OWNERSHIP_METHOD,
+    AcceptOwnership = ACCEPT_OWNERSHIP_METHOD,
+    SetMetadata = SET_METADATA_METHOD,
     AddObject = frc42_dispatch::method_hash!("AddObject"),
     DeleteObject = frc42_dispatch::method_hash!("DeleteObject"),
     GetObject = frc42_dispatch::method_hash!("GetObject"),
     ListObjects = frc42_dispatch::method_hash!("ListObjects"),
     UpdateObjectMetadata = frc42_dispatch::method_hash!("UpdateObjectMetadata"),
+    EnableVersioning = frc42_dispatch::method_hash!("EnableVersioning"),
+    GetObjectVersion = frc42_dispatch::method_hash!("GetObjectVersion"),
+    ListObjectVersions = frc42_dispatch::method_hash!("ListObjectVersions"),
+    DeleteObjectVersion = frc42_dispatch::method_hash!("DeleteObjectVersion"),
+    SetLifecycleRules = frc42_dispatch::method_hash!("SetLifecycleRules"),
+    GetLifecycleRules = frc42_dispatch::method_hash!("GetLifecycleRules"),
+    CopyObject = frc42_dispatch::method_hash!("CopyObject"),
+    MoveObject = frc42_dispatch::method_hash!("MoveObject"),
+    DeletePrefix = frc42_dispatch::method_hash!("DeletePrefix"),
+    DeleteObjects = frc42_dispatch::method_hash!("DeleteObjects"),
+    SetQuota = frc42_dispatch::method_hash!("SetQuota"),
+    GetQuota = frc42_dispatch::method_hash!("GetQuota"),
+    MoveSubscriptions = frc42_dispatch::method_hash!("MoveSubscriptions"),
 }
 
 /// Params for adding an object.
@@ -70,6 +93,65 @@ pub struct DeleteParams {
     pub from: Address,
 }
 
+/// Params for deleting objects by key prefix.
+#[derive(Clone, Debug, Default, Serialize_tuple, Deserialize_tuple)]
+pub struct DeletePrefixParams {
+    /// The prefix of the keys to delete. An empty prefix matches all keys.
+    #[serde(with = "strict_bytes")]
+    pub prefix: Vec<u8>,
+    /// The key to continue deleting from.
+    pub start_key: Option<Vec<u8>>,
+    /// The maximum number of keys to visit, including keys that don't match the prefix.
+    /// It can't exceed [`MAX_DELETE_LIMIT`]; `0` means the maximum.
+    pub limit: u64,
+    /// Account address that initiated the call
+    pub from: Address,
+}
+
+/// Params for deleting a batch of objects.
+#[derive(Clone, Debug, Default, Serialize_tuple, Deserialize_tuple)]
+pub struct DeleteObjectsParams {
+    /// Keys of the objects to delete. There can be at most [`MAX_DELETE_LIMIT`] keys.
+    pub keys: Vec<Vec<u8>>,
+    /// Account address that initiated the call
+    pub from: Address,
+}
+
+/// The result of a bulk delete.
+#[derive(Default, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct DeleteObjectsReturn {
+    /// The number of objects deleted.
+    pub deleted: u64,
+    /// Next key to continue deleting from when there are more keys to visit.
+    pub next_key: Option<Vec<u8>>,
+}
+
+/// Params for copying or moving an object.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct CopyParams {
+    /// Key of the source object.
+    #[serde(with = "strict_bytes")]
+    pub key: Vec<u8>,
+    /// Destination bucket, which must have the same owner.
+    /// If not specified, the object is copied within this bucket.
+    pub to_bucket: Option<Address>,
+    /// Destination object key.
+    #[serde(with = "strict_bytes")]
+    pub to_key: Vec<u8>,
+    /// Destination object time-to-live epochs.
+    /// If not specified, the current default TTL from the config actor is used.
+    pub ttl: Option<ChainEpoch>,
+    /// Destination object metadata. If not specified, the source object metadata is used.
+    pub metadata: Option<HashMap<String, String>>,
+    /// Whether to overwrite the destination key if it already exists.
+    pub overwrite: bool,
+    /// Account address that initiated the call
+    pub from: Address,
+}
+
+/// Params for moving an object.
+pub type MoveParams = CopyParams;
+
 /// Params for getting an object.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -129,3 +211,86 @@ pub struct UpdateObjectMetadataParams {
     /// Account address that initiated the call
     pub from: Address,
 }
+
+/// Params for enabling object versioning.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct EnableVersioningParams {
+    /// Account address that initiated the call
+    pub from: Address,
+    /// Maximum number of versions retained per object key, including delete markers.
+    /// Zero or values above [`MAX_OBJECT_VERSIONS`] use [`MAX_OBJECT_VERSIONS`].
+    pub max_versions: u32,
+}
+
+/// Params for getting a specific object version.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct GetVersionParams {
+    /// Object key.
+    #[serde(with = "strict_bytes")]
+    pub key: Vec<u8>,
+    /// Object version.
+    pub version: u64,
+}
+
+/// Params for listing the versions of an object.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ListVersionsParams(#[serde(with = "strict_bytes")] pub Vec<u8>);
+
+/// Params for deleting a specific object version.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct DeleteVersionParams {
+    /// Object key.
+    #[serde(with = "strict_bytes")]
+    pub key: Vec<u8>,
+    /// Object version.
+    pub version: u64,
+    /// Account address that initiated the call
+    pub from: Address,
+}
+
+/// Params for setting bucket lifecycle rules.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SetLifecycleRulesParams {
+    /// Rules replacing the current ones. An empty list removes all rules.
+    pub rules: Vec<LifecycleRule>,
+    /// Account address that initiated the call
+    pub from: Address,
+}
+
+/// Params for setting the bucket quota.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SetQuotaParams {
+    /// Quota replacing the current one. A quota without limits removes it.
+    pub quota: Quota,
+    /// Account address that initiated the call
+    pub from: Address,
+}
+
+/// The bucket quota and its usage.
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetQuotaReturn {
+    /// The current quota.
+    pub quota: Quota,
+    /// Bytes stored by the bucket's objects, including retained versions.
+    pub used_bytes: u64,
+    /// Credit consumed per epoch by the bucket's objects at the current storage price.
+    pub credit_per_epoch: Credit,
+}
+
+/// Params for moving a batch of blob subscriptions to the new owner after an ownership transfer.
+#[derive(Clone, Debug, Default, Serialize_tuple, Deserialize_tuple)]
+pub struct MoveSubscriptionsParams {
+    /// The maximum number of object keys to visit.
+    /// It can't exceed [`MAX_MOVE_SUBSCRIPTIONS_LIMIT`]; `0` means the maximum.
+    pub limit: u64,
+}
+
+/// The result of moving a batch of blob subscriptions.
+#[derive(Default, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct MoveSubscriptionsReturn {
+    /// The number of subscriptions moved.
+    pub moved: u64,
+    /// Whether all subscriptions have been moved.
+    pub done: bool,
+}
@@ -6,13 +6,15 @@ use std::collections::HashMap;
 use std::string::FromUtf8Error;
 
 use cid::Cid;
-use fendermint_actor_blobs_shared::state::Hash;
+use fendermint_actor_blobs_shared::state::{Credit, Hash};
 use fendermint_actor_machine::{Kind, MachineAddress, MachineState};
+use fendermint_actor_recall_config_shared::{RecallConfig, BASIS_POINTS};
 use fil_actors_runtime::ActorError;
 use fvm_ipld_blockstore::Blockstore;
-use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{strict_bytes, tuple::*};
 use fvm_ipld_hamt::{BytesKey, Config, Hamt};
 use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
 use serde::{Deserialize, Serialize};
 
 const MAX_LIST_LIMIT: usize = 1000;
@@ -32,6 +34,9 @@ fn utf8_error(e: FromUtf8Error) -> ActorError {
 }
 
 /// The state represents a bucket backed by a Hamt.
+///
+/// Fields after `metadata` default when missing, so buckets stored before they were added
+/// still decode.
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct State {
     /// The machine address set by the init actor.
@@ -42,6 +47,35 @@ pub struct State {
     pub root: Cid,
     /// User-defined metadata (e.g., bucket name, etc.).
     pub metadata: HashMap<String, String>,
+    /// The address proposed as the next owner, until it accepts.
+    #[serde(default)]
+    pub pending_owner: Option<Address>,
+    /// Whether object versions are retained.
+    #[serde(default)]
+    pub versioning: bool,
+    /// The root cid of the Hamt holding object version histories.
+    /// `None` until the first history is stored in buckets that predate versioning.
+    #[serde(default)]
+    pub versions: Option<Cid>,
+    /// Maximum number of versions retained per object key, including delete markers.
+    /// `None` in buckets that enabled versioning before the limit was added, which are bound
+    /// by the default limit.
+    #[serde(default)]
+    pub max_versions: Option<u32>,
+    /// Rules that set the time-to-live and auto-renewal of objects by key prefix.
+    #[serde(default)]
+    pub lifecycle_rules: Vec<LifecycleRule>,
+    /// Storage limits enforced when objects are added.
+    #[serde(default)]
+    pub quota: Quota,
+    /// Bytes stored by the bucket's objects, including retained versions.
+    /// `None` in buckets that predate usage tracking, until a quota is set.
+    /// Use [`State::used_bytes`] to read it.
+    #[serde(default)]
+    pub used_bytes: Option<u64>,
+    /// Progress of moving the blob subscriptions to a new owner, while it's in progress.
+    #[serde(default)]
+    pub subscription_transfer: Option<SubscriptionTransfer>,
 }
 
 impl MachineState for State {
@@ -59,11 +93,29 @@ impl MachineState for State {
                 )));
             }
         };
+        let versions = match Hamt::<_, ObjectVersions>::new_with_config(store, HAMT_CONFIG).flush()
+        {
+            Ok(cid) => cid,
+            Err(e) => {
+                return Err(ActorError::illegal_state(format!(
+                    "bucket actor failed to create empty versions Hamt: {}",
+                    e
+                )));
+            }
+        };
         Ok(Self {
             address: Default::default(),
             owner,
+            pending_owner: None,
             root,
             metadata,
+            versioning: false,
+            versions: Some(versions),
+            max_versions: None,
+            lifecycle_rules: Vec::new(),
+            quota: Quota::default(),
+            used_bytes: Some(0),
+            subscription_transfer: None,
         })
     }
 
@@ -83,9 +135,25 @@ impl MachineState for State {
         self.owner
     }
 
+    fn set_owner(&mut self, owner: Address) {
+        self.owner = owner;
+    }
+
+    fn pending_owner(&self) -> Option<Address> {
+        self.pending_owner
+    }
+
+    fn set_pending_owner(&mut self, owner: Option<Address>) {
+        self.pending_owner = owner;
+    }
+
     fn metadata(&self) -> HashMap<String, String> {
         self.metadata.clone()
     }
+
+    fn set_metadata(&mut self, metadata: HashMap<String, String>) {
+        self.metadata = metadata;
+    }
 }
 
 /// The stored representation of an object in the bucket.
@@ -99,6 +167,158 @@ pub struct ObjectState {
     pub metadata: HashMap<String, String>,
 }
 
+/// A rule that applies to objects added under a key prefix.
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct LifecycleRule {
+    /// The key prefix the rule applies to. An empty prefix matches all keys.
+    #[serde(with = "strict_bytes")]
+    pub prefix: Vec<u8>,
+    /// Object time-to-live epochs. Overrides the TTL passed when adding an object.
+    pub ttl: ChainEpoch,
+    /// Whether object blob subscriptions are renewed for `ttl` epochs when they expire.
+    pub auto_renew: bool,
+}
+
+/// Storage limits of a bucket. Limits that are not set are not enforced.
+///
+/// The limits are separate from the owner's account credit, which still pays for all objects.
+#[derive(Clone, Debug, Default, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct Quota {
+    /// Maximum bytes stored by the bucket's objects, including retained versions.
+    pub max_bytes: Option<u64>,
+    /// Maximum credit the bucket's objects can consume per epoch at the current storage price.
+    pub max_credit_per_epoch: Option<Credit>,
+}
+
+/// Progress of moving a bucket's blob subscriptions from the previous owner to the new owner.
+///
+/// Subscriptions of versioned objects are moved first, then those of the other objects.
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct SubscriptionTransfer {
+    /// The previous owner, which holds the subscriptions that haven't been moved yet.
+    pub from: Address,
+    /// The new owner.
+    pub to: Address,
+    /// Whether the subscriptions of all versioned objects have been moved.
+    pub versions_done: bool,
+    /// The key to continue moving from.
+    pub next_key: Option<BytesKey>,
+}
+
+impl SubscriptionTransfer {
+    pub fn new(from: Address, to: Address) -> Self {
+        Self {
+            from,
+            to,
+            versions_done: false,
+            next_key: None,
+        }
+    }
+}
+
+/// The storage price that the credit quota is checked against.
+pub struct StoragePrice {
+    /// The Recall network configuration, which holds the storage pricing.
+    pub config: RecallConfig,
+    /// The storage price multiplier in basis points driven by subnet utilisation.
+    pub utilization_multiplier_bps: u64,
+}
+
+impl StoragePrice {
+    /// Returns the credit consumed per epoch by storing `bytes` with the default TTL.
+    pub fn credit_per_epoch(&self, bytes: u64) -> Credit {
+        let multiplier_bps = self.config.storage_pricing.multiplier_bps(
+            self.utilization_multiplier_bps,
+            self.config.blob_default_ttl,
+            bytes,
+        );
+        Credit::from_atto(Credit::from_whole(bytes).atto() * multiplier_bps / BASIS_POINTS)
+    }
+}
+
+/// A single version of an object in a versioned bucket.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ObjectVersion {
+    /// The version number, increasing per key.
+    /// Version `0` is an object that was added before versioning was enabled.
+    pub version: u64,
+    /// The object blake3 hash. Unset for delete markers.
+    pub hash: Hash,
+    /// Blake3 hash of the metadata to use for object recovery.
+    pub recovery_hash: Hash,
+    /// The object size.
+    pub size: u64,
+    /// Expiry block of the version's blob subscription.
+    pub expiry: ChainEpoch,
+    /// User-defined object metadata.
+    pub metadata: HashMap<String, String>,
+    /// Whether this version marks the object as deleted.
+    pub delete_marker: bool,
+}
+
+impl ObjectVersion {
+    /// Returns a delete marker with the given version number.
+    pub fn delete_marker(version: u64) -> Self {
+        Self {
+            version,
+            hash: Hash::default(),
+            recovery_hash: Hash::default(),
+            size: 0,
+            expiry: 0,
+            metadata: HashMap::new(),
+            delete_marker: true,
+        }
+    }
+}
+
+/// The version history of an object key.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ObjectVersions {
+    /// The version number assigned to the next version.
+    pub next_version: u64,
+    /// Versions ordered from oldest to newest.
+    pub versions: Vec<ObjectVersion>,
+}
+
+impl Default for ObjectVersions {
+    fn default() -> Self {
+        Self {
+            next_version: 1,
+            versions: Vec::new(),
+        }
+    }
+}
+
+impl ObjectVersions {
+    /// Appends a version as the newest one.
+    pub fn push(&mut self, version: ObjectVersion) {
+        self.next_version = self.next_version.max(version.version + 1);
+        self.versions.push(version);
+    }
+
+    /// Removes a version by number.
+    pub fn remove(&mut self, version: u64) -> Option<ObjectVersion> {
+        let index = self.versions.iter().position(|v| v.version == version)?;
+        Some(self.versions.remove(index))
+    }
+
+    /// Removes the oldest versions until at most `max` are left, and returns them.
+    pub fn prune(&mut self, max: usize) -> Vec<ObjectVersion> {
+        let excess = self.versions.len().saturating_sub(max);
+        self.versions.drain(..excess).collect()
+    }
+
+    /// Returns a version by number.
+    pub fn get(&self, version: u64) -> Option<&ObjectVersion> {
+        self.versions.iter().find(|v| v.version == version)
+    }
+
+    /// Returns the newest version unless it is a delete marker.
+    pub fn current(&self) -> Option<&ObjectVersion> {
+        self.versions.last().filter(|v| !v.delete_marker)
+    }
+}
+
 /// A list of objects and their common prefixes.
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ObjectList {
@@ -162,6 +382,183 @@ impl State {
         Ok(object)
     }
 
+    /// Returns an error if storing `added` bytes in place of `released` bytes would exceed
+    /// the quota. Changes that don't increase usage are always allowed, so objects can still
+    /// be replaced or deleted after the quota is lowered below the current usage.
+    /// `price` is only called if a credit quota is set, and usage is only read if any quota is.
+    pub fn check_quota<BS: Blockstore, F>(
+        &self,
+        store: &BS,
+        released: u64,
+        added: u64,
+        price: F,
+    ) -> anyhow::Result<(), ActorError>
+    where
+        F: FnOnce() -> Result<StoragePrice, ActorError>,
+    {
+        if added <= released
+            || (self.quota.max_bytes.is_none() && self.quota.max_credit_per_epoch.is_none())
+        {
+            return Ok(());
+        }
+        let required = self
+            .used_bytes(store)?
+            .saturating_sub(released)
+            .saturating_add(added);
+        if let Some(max_bytes) = self.quota.max_bytes {
+            if required > max_bytes {
+                return Err(ActorError::forbidden(format!(
+                    "bucket byte quota exceeded (quota: {}; required: {})",
+                    max_bytes, required
+                )));
+            }
+        }
+        if let Some(max_credit) = &self.quota.max_credit_per_epoch {
+            let required = price()?.credit_per_epoch(required);
+            if &required > max_credit {
+                return Err(ActorError::forbidden(format!(
+                    "bucket credit quota exceeded (quota: {} per epoch; required: {} per epoch)",
+                    max_credit, required
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Records that `added` bytes are stored in place of `released` bytes.
+    ///
+    /// Buckets that predate usage tracking don't track it until [`State::track_usage`] is called.
+    pub fn update_usage(&mut self, released: u64, added: u64) {
+        if let Some(used) = self.used_bytes {
+            self.used_bytes = Some(used.saturating_sub(released).saturating_add(added));
+        }
+    }
+
+    /// Starts tracking usage in buckets that predate it, by counting the bytes of their objects.
+    ///
+    /// This visits every object, so it's only done once, when a quota is first set.
+    pub fn track_usage<BS: Blockstore>(&mut self, store: &BS) -> anyhow::Result<(), ActorError> {
+        if self.used_bytes.is_none() {
+            self.used_bytes = Some(self.used_bytes(store)?);
+        }
+        Ok(())
+    }
+
+    /// Returns the bytes stored by the bucket's objects, including retained versions.
+    ///
+    /// Buckets that predate usage tracking also predate versioning, so until they track usage,
+    /// it's the total size of their objects. Counting it visits every object, so writes only
+    /// read it in buckets that track usage.
+    pub fn used_bytes<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<u64, ActorError> {
+        if let Some(used) = self.used_bytes {
+            return Ok(used);
+        }
+        let mut used = 0u64;
+        self.for_each_object(store, |_, object| {
+            used = used.saturating_add(object.size);
+            Ok(())
+        })?;
+        Ok(used)
+    }
+
+    /// Returns the lifecycle rule with the longest prefix matching the key.
+    pub fn get_lifecycle_rule(&self, key: &[u8]) -> Option<&LifecycleRule> {
+        self.lifecycle_rules
+            .iter()
+            .filter(|rule| key.starts_with(&rule.prefix))
+            .max_by_key(|rule| rule.prefix.len())
+    }
+
+    pub fn get_versions<BS: Blockstore>(
+        &self,
+        store: &BS,
+        key: &BytesKey,
+    ) -> anyhow::Result<Option<ObjectVersions>, ActorError> {
+        let Some(root) = &self.versions else {
+            return Ok(None);
+        };
+        let hamt = Hamt::<_, ObjectVersions>::load_with_config(root, store, HAMT_CONFIG)
+            .map_err(state_error)?;
+        let versions = hamt.get(key).map(|v| v.cloned()).map_err(state_error)?;
+        Ok(versions)
+    }
+
+    /// Sets the version history of a key, removing it if there are no versions left.
+    pub fn set_versions<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        key: BytesKey,
+        versions: ObjectVersions,
+    ) -> anyhow::Result<Cid, ActorError> {
+        let mut hamt = match &self.versions {
+            Some(root) => Hamt::<_, ObjectVersions>::load_with_config(root, store, HAMT_CONFIG)
+                .map_err(state_error)?,
+            None => Hamt::new_with_config(store, HAMT_CONFIG),
+        };
+        if versions.versions.is_empty() {
+            hamt.delete(&key).map_err(state_error)?;
+        } else {
+            hamt.set(key, versions).map_err(state_error)?;
+        }
+        let root = hamt.flush().map_err(state_error)?;
+        self.versions = Some(root);
+        Ok(root)
+    }
+
+    /// Calls `f` with the key and state of every object.
+    pub fn for_each_object<BS: Blockstore, F>(
+        &self,
+        store: &BS,
+        mut f: F,
+    ) -> anyhow::Result<(), ActorError>
+    where
+        F: FnMut(&[u8], &ObjectState) -> anyhow::Result<(), ActorError>,
+    {
+        let hamt = Hamt::<_, ObjectState>::load_with_config(&self.root, store, HAMT_CONFIG)
+            .map_err(state_error)?;
+        hamt.for_each(|k, v| Ok(f(&k.0, v)?)).map_err(state_error)
+    }
+
+    /// Calls `f` with the key and state of up to `limit` objects, starting at `start_key`.
+    /// Returns the number of objects visited and the key to continue from.
+    pub fn for_each_object_ranged<BS: Blockstore, F>(
+        &self,
+        store: &BS,
+        start_key: Option<&BytesKey>,
+        limit: usize,
+        mut f: F,
+    ) -> anyhow::Result<(usize, Option<BytesKey>), ActorError>
+    where
+        F: FnMut(&[u8], &ObjectState) -> anyhow::Result<(), ActorError>,
+    {
+        let hamt = Hamt::<_, ObjectState>::load_with_config(&self.root, store, HAMT_CONFIG)
+            .map_err(state_error)?;
+        hamt.for_each_ranged(start_key, Some(limit), |k, v| Ok(f(&k.0, v)?))
+            .map_err(state_error)
+    }
+
+    /// Calls `f` with the key and version history of up to `limit` versioned objects,
+    /// starting at `start_key`.
+    /// Returns the number of objects visited and the key to continue from.
+    pub fn for_each_versions_ranged<BS: Blockstore, F>(
+        &self,
+        store: &BS,
+        start_key: Option<&BytesKey>,
+        limit: usize,
+        mut f: F,
+    ) -> anyhow::Result<(usize, Option<BytesKey>), ActorError>
+    where
+        F: FnMut(&[u8], &ObjectVersions) -> anyhow::Result<(), ActorError>,
+    {
+        let Some(root) = &self.versions else {
+            return Ok((0, None));
+        };
+        let hamt = Hamt::<_, ObjectVersions>::load_with_config(root, store, HAMT_CONFIG)
+            .map_err(state_error)?;
+        hamt.for_each_ranged(start_key, Some(limit), |k, v| Ok(f(&k.0, v)?))
+            .map_err(state_error)
+    }
+
     pub fn list<BS: Blockstore, F>(
         &self,
         store: &BS,
@@ -380,6 +777,162 @@ mod tests {
         assert_eq!(result.unwrap().unwrap(), object);
     }
 
+    #[test]
+    fn test_get_lifecycle_rule() {
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store, Address::new_id(100), HashMap::new()).unwrap();
+        assert_eq!(state.get_lifecycle_rule(b"logs/app.log"), None);
+
+        let rule = |prefix: &[u8], ttl| LifecycleRule {
+            prefix: prefix.to_vec(),
+            ttl,
+            auto_renew: false,
+        };
+        state.lifecycle_rules = vec![rule(b"logs/", 100), rule(b"", 200), rule(b"logs/app", 300)];
+        assert_eq!(state.get_lifecycle_rule(b"logs/app.log").unwrap().ttl, 300);
+        assert_eq!(state.get_lifecycle_rule(b"logs/db.log").unwrap().ttl, 100);
+        assert_eq!(state.get_lifecycle_rule(b"images/a.png").unwrap().ttl, 200);
+    }
+
+    #[test]
+    fn test_check_quota() {
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store, Address::new_id(100), HashMap::new()).unwrap();
+        let price = || {
+            Ok(StoragePrice {
+                config: RecallConfig::default(),
+                utilization_multiplier_bps: 2 * BASIS_POINTS as u64,
+            })
+        };
+        assert!(state.check_quota(&store, 0, u64::MAX, price).is_ok());
+
+        state.quota = Quota {
+            max_bytes: Some(100),
+            max_credit_per_epoch: None,
+        };
+        state.update_usage(0, 60);
+        assert!(state.check_quota(&store, 0, 40, price).is_ok());
+        assert!(state.check_quota(&store, 0, 41, price).is_err());
+        assert!(state.check_quota(&store, 20, 60, price).is_ok());
+
+        state.quota = Quota {
+            max_bytes: None,
+            max_credit_per_epoch: Some(Credit::from_whole(100)),
+        };
+        // Objects are priced at twice the base rate
+        assert!(state.check_quota(&store, 0, 1, price).is_err());
+        assert!(state.check_quota(&store, 10, 0, price).is_ok());
+        // Replacing with a smaller object is allowed even if the bucket is over quota
+        assert!(state.check_quota(&store, 30, 20, price).is_ok());
+
+        state.update_usage(60, 0);
+        assert_eq!(state.used_bytes(&store).unwrap(), 0);
+        assert!(state.check_quota(&store, 0, 50, price).is_ok());
+    }
+
+    #[test]
+    fn test_decode_legacy_state() {
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store, Address::new_id(100), HashMap::new()).unwrap();
+        let hash = new_hash(10).0;
+        state
+            .add(&store, BytesKey(vec![1]), hash, 10, HashMap::new(), false)
+            .unwrap();
+        state
+            .add(&store, BytesKey(vec![2]), hash, 20, HashMap::new(), false)
+            .unwrap();
+
+        // Buckets stored before versioning, quotas, and ownership transfers only had these fields
+        let legacy = (
+            state.address.clone(),
+            state.owner,
+            state.root,
+            state.metadata.clone(),
+        );
+        let bytes = fvm_ipld_encoding::to_vec(&legacy).unwrap();
+        let mut decoded: State = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded.pending_owner, None);
+        assert!(!decoded.versioning);
+        assert_eq!(
+            decoded.get_versions(&store, &BytesKey(vec![1])).unwrap(),
+            None
+        );
+        assert_eq!(decoded.used_bytes(&store).unwrap(), 30);
+
+        // Usage isn't tracked until a quota is set
+        decoded.update_usage(10, 0);
+        assert_eq!(decoded.used_bytes, None);
+        decoded.track_usage(&store).unwrap();
+        assert_eq!(decoded.used_bytes, Some(30));
+        decoded.update_usage(10, 0);
+        assert_eq!(decoded.used_bytes, Some(20));
+    }
+
+    #[test]
+    fn test_versions() {
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(&store, Address::new_id(100), HashMap::new()).unwrap();
+        let key = BytesKey(vec![1, 2, 3]);
+        assert_eq!(state.get_versions(&store, &key).unwrap(), None);
+
+        let object = object_one();
+        let mut versions = ObjectVersions::default();
+        versions.push(ObjectVersion {
+            version: versions.next_version,
+            hash: object.hash,
+            recovery_hash: new_hash(256).0,
+            size: object.size,
+            expiry: 100,
+            metadata: object.metadata,
+            delete_marker: false,
+        });
+        versions.push(ObjectVersion::delete_marker(versions.next_version));
+        assert_eq!(versions.next_version, 3);
+        assert_eq!(versions.current(), None);
+        state
+            .set_versions(&store, key.clone(), versions.clone())
+            .unwrap();
+        assert_eq!(
+            state.get_versions(&store, &key).unwrap(),
+            Some(versions.clone())
+        );
+
+        // Removing the delete marker restores the previous version
+        assert!(versions.remove(2).unwrap().delete_marker);
+        assert_eq!(versions.current().unwrap().version, 1);
+        assert_eq!(versions.remove(2), None);
+
+        // Removing the last version drops the history
+        versions.remove(1).unwrap();
+        state
+            .set_versions(&store, key.clone(), versions.clone())
+            .unwrap();
+        assert_eq!(state.get_versions(&store, &key).unwrap(), None);
+    }
+
+    #[test]
+    fn test_prune_versions() {
+        let mut versions = ObjectVersions::default();
+        for _ in 0..3 {
+            versions.push(ObjectVersion::delete_marker(versions.next_version));
+        }
+
+        assert!(versions.prune(3).is_empty());
+        let pruned = versions.prune(1);
+        assert_eq!(
+            pruned.iter().map(|v| v.version).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(versions.versions.len(), 1);
+        assert_eq!(versions.versions[0].version, 3);
+        assert_eq!(versions.next_version, 4);
+
+        // Pruning everything keeps the version numbers increasing
+        versions.prune(0);
+        assert!(versions.versions.is_empty());
+        assert_eq!(versions.next_version, 4);
+    }
+
     fn create_and_put_objects(
         state: &mut State,
         store: &MemoryBlockstore,
@@ -2,10 +2,14 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use fil_actors_runtime::{actor_error, runtime::Runtime, ActorError};
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use fil_actors_runtime::{actor_error, runtime::Runtime, ActorError, EAM_ACTOR_ID};
 use fvm_ipld_encoding::IPLD_RAW;
+use fvm_shared::address::{Address, Payload};
 use fvm_shared::event::{ActorEvent, Entry, Flags};
-use recall_sol_facade::primitives::IntoLogData;
+use recall_sol_facade::primitives::{keccak256, IntoLogData, LogData, B256};
 
 /// The event key prefix for the Ethereum log topics.
 const EVENT_TOPIC_KEY_PREFIX: &str = "t";
@@ -48,3 +52,176 @@ pub fn emit_evm_event<T: IntoLogData>(
     let actor_event = to_actor_event(event)?;
     rt.emit_event(&actor_event)
 }
+
+/// An EVM event emitted by every machine kind.
+///
+/// These events are not part of the Solidity facade, so their log data is built here.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MachineEvent {
+    topics: Vec<B256>,
+    data: Vec<u8>,
+}
+
+impl IntoLogData for MachineEvent {
+    fn to_log_data(&self) -> LogData {
+        LogData::new_unchecked(self.topics.clone(), self.data.clone().into())
+    }
+
+    fn into_log_data(self) -> LogData {
+        LogData::new_unchecked(self.topics, self.data.into())
+    }
+}
+
+/// Returns an `OwnershipTransferStarted(address indexed owner, address indexed newOwner)` event.
+/// A cancelled transfer has the zero address as the new owner.
+pub fn ownership_transfer_started(
+    owner: Address,
+    new_owner: Option<Address>,
+) -> anyhow::Result<MachineEvent> {
+    Ok(MachineEvent {
+        topics: vec![
+            keccak256("OwnershipTransferStarted(address,address)"),
+            address_topic(Some(owner))?,
+            address_topic(new_owner)?,
+        ],
+        data: Vec::new(),
+    })
+}
+
+/// Returns an `OwnershipTransferred(address indexed previousOwner, address indexed newOwner)` event.
+pub fn ownership_transferred(
+    previous_owner: Address,
+    new_owner: Address,
+) -> anyhow::Result<MachineEvent> {
+    Ok(MachineEvent {
+        topics: vec![
+            keccak256("OwnershipTransferred(address,address)"),
+            address_topic(Some(previous_owner))?,
+            address_topic(Some(new_owner))?,
+        ],
+        data: Vec::new(),
+    })
+}
+
+/// Returns a `MetadataUpdated(uint8 indexed kind, (string,string)[] metadata)` event.
+/// Metadata entries are sorted by key so that the log is deterministic.
+pub fn metadata_updated(
+    kind: u8,
+    metadata: &HashMap<String, String>,
+) -> anyhow::Result<MachineEvent> {
+    let mut entries: Vec<_> = metadata.iter().collect();
+    entries.sort();
+
+    // Encodes each (key, value) tuple, which are referenced by offsets from the array head
+    let mut heads = Vec::new();
+    let mut tails = Vec::new();
+    let mut offset = entries.len() * 32;
+    for (key, value) in entries {
+        let key = encode_string(key);
+        let mut tuple = Vec::new();
+        tuple.extend(abi_word(64));
+        tuple.extend(abi_word(64 + key.len()));
+        tuple.extend(key);
+        tuple.extend(encode_string(value));
+        heads.extend(abi_word(offset));
+        offset += tuple.len();
+        tails.extend(tuple);
+    }
+
+    let mut data = Vec::with_capacity(64 + heads.len() + tails.len());
+    data.extend(abi_word(32));
+    data.extend(abi_word(metadata.len()));
+    data.extend(heads);
+    data.extend(tails);
+
+    let mut kind_topic = B256::ZERO;
+    kind_topic.0[31] = kind;
+    Ok(MachineEvent {
+        topics: vec![
+            keccak256("MetadataUpdated(uint8,(string,string)[])"),
+            kind_topic,
+        ],
+        data,
+    })
+}
+
+/// Returns the indexed topic of an address, or the zero address if not set.
+///
+/// Delegated EAM addresses map to their Ethereum address, and ID addresses map to their
+/// masked Ethereum form.
+fn address_topic(address: Option<Address>) -> anyhow::Result<B256> {
+    let Some(address) = address else {
+        return Ok(B256::ZERO);
+    };
+    let mut eth_addr = [0u8; 20];
+    match address.payload() {
+        Payload::Delegated(delegated)
+            if delegated.namespace() == EAM_ACTOR_ID && delegated.subaddress().len() == 20 =>
+        {
+            eth_addr.copy_from_slice(delegated.subaddress());
+        }
+        Payload::ID(id) => {
+            eth_addr[0] = 0xff;
+            eth_addr[12..].copy_from_slice(&id.to_be_bytes());
+        }
+        _ => return Err(anyhow!("address {} has no ethereum equivalent", address)),
+    }
+    Ok(B256::left_padding_from(&eth_addr))
+}
+
+/// Returns a 32-byte ABI word holding `value`.
+fn abi_word(value: usize) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+/// Returns the ABI encoding of a string: its length followed by its bytes, right-padded.
+fn encode_string(value: &str) -> Vec<u8> {
+    let mut encoded = abi_word(value.len()).to_vec();
+    encoded.extend(value.as_bytes());
+    encoded.resize(32 + value.len().div_ceil(32) * 32, 0);
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ownership_events() {
+        let owner = Address::new_id(100);
+        let event = ownership_transfer_started(owner, None).unwrap();
+        let log = event.to_log_data();
+        assert_eq!(log.topics().len(), 3);
+        assert_eq!(log.topics()[2], B256::ZERO);
+        assert_eq!(log.topics()[1].0[12], 0xff);
+        assert_eq!(log.topics()[1].0[31], 100);
+
+        let new_owner = Address::new_delegated(EAM_ACTOR_ID, &[1u8; 20]).unwrap();
+        let event = ownership_transferred(owner, new_owner).unwrap();
+        assert_eq!(event.to_log_data().topics()[2].0[12..], [1u8; 20]);
+
+        let invalid = Address::new_secp256k1(&[3u8; 65]).unwrap();
+        assert!(ownership_transferred(owner, invalid).is_err());
+    }
+
+    #[test]
+    fn test_metadata_updated() {
+        let event = metadata_updated(1, &HashMap::new()).unwrap();
+        assert_eq!(event.data, [abi_word(32), abi_word(0)].concat());
+
+        let metadata = HashMap::from([
+            ("b".to_string(), "2".to_string()),
+            ("a".to_string(), "1".to_string()),
+        ]);
+        let event = metadata_updated(1, &metadata).unwrap();
+        assert_eq!(event.topics[1].0[31], 1);
+        // Offset and length, two tuple offsets, and two tuples of two offsets and two strings
+        assert_eq!(event.data.len(), 32 * (2 + 2 + 2 * (2 + 2 * 2)));
+        assert_eq!(event.data[64..96], abi_word(64));
+        assert_eq!(event.data[96..128], abi_word(64 + 6 * 32));
+        // The first tuple is the smallest key
+        assert_eq!(event.data[128 + 3 * 32], b'a');
+    }
+}
@@ -6,18 +6,23 @@ use std::collections::HashMap;
 
 pub use fil_actor_adm::Kind;
 use fil_actors_runtime::{
-    actor_error, runtime::Runtime, ActorError, ADM_ACTOR_ADDR, FIRST_EXPORTED_METHOD_NUMBER,
-    INIT_ACTOR_ADDR,
+    actor_error, extract_send_result, runtime::Runtime, ActorError, ADM_ACTOR_ADDR,
+    FIRST_EXPORTED_METHOD_NUMBER, INIT_ACTOR_ADDR,
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::{ipld_block::IpldBlock, tuple::*};
 pub use fvm_shared::METHOD_CONSTRUCTOR;
-use fvm_shared::{address::Address, MethodNum};
+use fvm_shared::{address::Address, bigint::Zero, econ::TokenAmount, MethodNum};
 use recall_sol_facade::machine::{machine_created, machine_initialized};
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::events::emit_evm_event;
-use crate::util::{to_delegated_address, to_id_address, to_id_and_delegated_address};
+use crate::events::{
+    emit_evm_event, metadata_updated, ownership_transfer_started, ownership_transferred,
+};
+use crate::util::{
+    require_addr_is_origin_or_caller, to_delegated_address, to_id_address,
+    to_id_and_delegated_address,
+};
 
 pub mod events;
 pub mod util;
@@ -38,14 +43,57 @@ pub struct InitParams {
     pub address: Address,
 }
 
+/// Params for proposing a new machine owner.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct TransferOwnershipParams {
+    /// Account address that initiated the transfer. Must be the current owner.
+    pub from: Address,
+    /// The proposed owner, or `None` to cancel a pending transfer.
+    pub new_owner: Option<Address>,
+}
+
+/// Params for accepting a pending ownership transfer.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct AcceptOwnershipParams {
+    /// Account address that accepts the transfer. Must be the proposed owner.
+    pub from: Address,
+}
+
+/// Params for moving the calling machine to a new owner in the ADM's index of machines by owner.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct AdmTransferMachineParams {
+    /// The previous owner.
+    pub previous_owner: Address,
+    /// The new owner.
+    pub new_owner: Address,
+}
+
+/// Params for replacing machine metadata.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SetMetadataParams {
+    /// Account address that initiated the update. Must be the owner.
+    pub from: Address,
+    /// User-defined metadata that replaces the existing metadata.
+    pub metadata: HashMap<String, String>,
+}
+
 /// Machine initialization method number.
 pub const INIT_METHOD: MethodNum = 2;
 /// Get machine address method number.
 pub const GET_ADDRESS_METHOD: MethodNum = frc42_dispatch::method_hash!("GetAddress");
 /// Get machine metadata method number.
 pub const GET_METADATA_METHOD: MethodNum = frc42_dispatch::method_hash!("GetMetadata");
+/// Propose or cancel a machine ownership transfer method number.
+pub const TRANSFER_OWNERSHIP_METHOD: MethodNum = frc42_dispatch::method_hash!("TransferOwnership");
+/// Accept a machine ownership transfer method number.
+pub const ACCEPT_OWNERSHIP_METHOD: MethodNum = frc42_dispatch::method_hash!("AcceptOwnership");
+/// Set machine metadata method number.
+pub const SET_METADATA_METHOD: MethodNum = frc42_dispatch::method_hash!("SetMetadata");
+/// ADM method that moves a machine to a new owner in the ADM's index of machines by owner.
+///
+/// Not every ADM version has it, so machines only call it on a best-effort basis.
+pub const ADM_TRANSFER_MACHINE_METHOD: MethodNum = frc42_dispatch::method_hash!("TransferMachine");
 
-// TODO: Add method for changing owner from ADM actor.
 pub trait MachineActor {
     type State: MachineState + Serialize + DeserializeOwned;
 
@@ -98,6 +146,138 @@ pub trait MachineActor {
         })
     }
 
+    /// Proposes a new machine owner, or cancels a pending proposal.
+    ///
+    /// Ownership only changes once the proposed owner accepts, so a machine can't be handed
+    /// to a mistyped address. Only the owner can propose a transfer.
+    fn transfer_ownership(
+        rt: &impl Runtime,
+        params: TransferOwnershipParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        let state = rt.state::<Self::State>()?;
+        require_machine_owner(rt, &state, from)?;
+
+        let new_owner = params
+            .new_owner
+            .map(|new_owner| to_id_address(rt, new_owner, false))
+            .transpose()?;
+        if new_owner == Some(from) {
+            return Err(actor_error!(illegal_argument; "new owner is already the owner"));
+        }
+
+        Self::ownership_proposed(rt, &state, new_owner)?;
+
+        rt.transaction(|st: &mut Self::State, _| {
+            st.set_pending_owner(new_owner);
+            Ok(())
+        })?;
+
+        let new_owner =
+            new_owner.map(|new_owner| to_delegated_address(rt, new_owner).unwrap_or(new_owner));
+        emit_evm_event(
+            rt,
+            ownership_transfer_started(to_delegated_address(rt, from).unwrap_or(from), new_owner),
+        )
+    }
+
+    /// Accepts a pending ownership transfer. Only the proposed owner can accept.
+    ///
+    /// The machine's own state is the source of truth for its owner. The ADM's index of
+    /// machines by owner is updated if the ADM supports it, but an ADM that doesn't won't
+    /// block the transfer.
+    fn accept_ownership(
+        rt: &impl Runtime,
+        params: AcceptOwnershipParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        let state = rt.state::<Self::State>()?;
+        if state.pending_owner() != Some(from) {
+            return Err(actor_error!(
+                forbidden;
+                "Unauthorized: {} is not the proposed owner", from));
+        }
+        let previous_owner = state.owner();
+
+        Self::ownership_accepted(rt, &state, from)?;
+
+        rt.transaction(|st: &mut Self::State, _| {
+            st.set_owner(from);
+            st.set_pending_owner(None);
+            Ok(())
+        })?;
+
+        // Keep the ADM listing the machine under its owner, if it can. Changes made by a failed
+        // call are reverted, so ignoring the failure leaves the ADM as it was.
+        let _ = extract_send_result(rt.send_simple(
+            &ADM_ACTOR_ADDR,
+            ADM_TRANSFER_MACHINE_METHOD,
+            IpldBlock::serialize_cbor(&AdmTransferMachineParams {
+                previous_owner,
+                new_owner: from,
+            })?,
+            TokenAmount::zero(),
+        ));
+
+        emit_evm_event(
+            rt,
+            ownership_transferred(
+                to_delegated_address(rt, previous_owner).unwrap_or(previous_owner),
+                to_delegated_address(rt, from).unwrap_or(from),
+            ),
+        )
+    }
+
+    /// Replaces the machine metadata. Only the owner can set metadata.
+    fn set_metadata(rt: &impl Runtime, params: SetMetadataParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let from = to_id_address(rt, params.from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        let state = rt.state::<Self::State>()?;
+        require_machine_owner(rt, &state, from)?;
+
+        let kind = rt.transaction(|st: &mut Self::State, _| {
+            st.set_metadata(params.metadata.clone());
+            Ok(st.kind())
+        })?;
+
+        emit_evm_event(rt, metadata_updated(kind as u8, &params.metadata))
+    }
+
+    /// Called when the owner proposes a new owner, or cancels a proposal if `new_owner` is not
+    /// set, before the proposal is saved.
+    ///
+    /// Machines that hold resources on behalf of their owner use this to let the owner
+    /// consent to moving them.
+    fn ownership_proposed(
+        _rt: &impl Runtime,
+        _state: &Self::State,
+        _new_owner: Option<Address>,
+    ) -> Result<(), ActorError> {
+        Ok(())
+    }
+
+    /// Called when the proposed owner accepts, before ownership changes.
+    ///
+    /// Machines that hold resources on behalf of their owner use this to move them.
+    fn ownership_accepted(
+        _rt: &impl Runtime,
+        _state: &Self::State,
+        _new_owner: Address,
+    ) -> Result<(), ActorError> {
+        Ok(())
+    }
+
     fn fallback(
         rt: &impl Runtime,
         method: MethodNum,
@@ -112,6 +292,21 @@ pub trait MachineActor {
     }
 }
 
+/// Returns an error if `from` is not the machine owner.
+fn require_machine_owner<S: MachineState>(
+    rt: &impl Runtime,
+    state: &S,
+    from: Address,
+) -> Result<(), ActorError> {
+    let owner = to_id_address(rt, state.owner(), false)?;
+    if from != owner {
+        return Err(actor_error!(
+            forbidden;
+            "Unauthorized: only the machine owner {} can do this", owner));
+    }
+    Ok(())
+}
+
 /// Machine metadata.
 #[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct Metadata {
@@ -136,7 +331,11 @@ pub trait MachineState {
     fn address(&self) -> MachineAddress;
     fn kind(&self) -> Kind;
     fn owner(&self) -> Address;
+    fn set_owner(&mut self, owner: Address);
+    fn pending_owner(&self) -> Option<Address>;
+    fn set_pending_owner(&mut self, owner: Option<Address>);
     fn metadata(&self) -> HashMap<String, String>;
+    fn set_metadata(&mut self, metadata: HashMap<String, String>);
 }
 
 /// Machine address wrapper.
@@ -23,6 +23,28 @@ pub fn require_addr_is_origin_or_caller(
     )))
 }
 
+/// Returns an error if the caller is not a machine.
+///
+/// Machines are the only actors with custom code that are created after genesis, and only the
+/// ADM can create them, so a caller whose code is not a builtin actor type is trusted to act on
+/// behalf of its owner.
+pub fn require_caller_is_machine(rt: &impl Runtime) -> Result<(), ActorError> {
+    let caller = rt.message().caller();
+    let caller_id = caller
+        .id()
+        .map_err(|e| ActorError::illegal_state(e.to_string()))?;
+    let code_cid = rt
+        .get_actor_code_cid(&caller_id)
+        .ok_or_else(|| ActorError::not_found(format!("actor {} code cid not found", caller)))?;
+    if rt.resolve_builtin_actor_type(&code_cid).is_some() {
+        return Err(ActorError::forbidden(format!(
+            "caller {} is not a machine",
+            caller
+        )));
+    }
+    Ok(())
+}
+
 /// Resolves ID address of an actor.
 /// If `require_delegated` is `true`, the address must be of type
 /// EVM (a Solidity contract), EthAccount (an Ethereum-style EOA), or Placeholder (a yet to be
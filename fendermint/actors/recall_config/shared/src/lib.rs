@@ -2,6 +2,8 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::cmp::Ordering;
+
 use fendermint_actor_blobs_shared::state::TokenCreditRate;
 use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::{deserialize_block, extract_send_result, ActorError};
@@ -19,7 +21,16 @@ use serde::{Deserialize, Serialize};
 pub const RECALL_CONFIG_ACTOR_ID: ActorID = 70;
 pub const RECALL_CONFIG_ACTOR_ADDR: Address = Address::new_id(RECALL_CONFIG_ACTOR_ID);
 
+/// The number of basis points in one whole.
+pub const BASIS_POINTS: u16 = 10_000;
+
+/// The minimum number of epochs between scheduling a config change and its activation.
+pub const MIN_CONFIG_CHANGE_DELAY: ChainEpoch = 60 * 60; // ~1 hour
+
 /// The updatable config.
+///
+/// Fields after `blob_default_ttl` default when missing, so configs stored before they were
+/// added still decode.
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
 pub struct RecallConfig {
     /// The total storage capacity of the subnet.
@@ -32,6 +43,16 @@ pub struct RecallConfig {
     pub blob_min_ttl: ChainEpoch,
     /// The default epoch duration a blob is stored.
     pub blob_default_ttl: ChainEpoch,
+    /// The discount in basis points applied when selling credit back for tokens.
+    /// If `None`, selling credit is disabled.
+    #[serde(default)]
+    pub credit_sell_discount_bps: Option<u16>,
+    /// The credit charged per byte delivered by a read request.
+    #[serde(default = "default_blob_read_cost")]
+    pub blob_read_cost: u64,
+    /// Pricing of storage above the base rate.
+    #[serde(default)]
+    pub storage_pricing: StoragePricing,
 }
 
 impl Default for RecallConfig {
@@ -45,24 +66,139 @@ impl Default for RecallConfig {
             blob_credit_debit_interval: ChainEpoch::from(60 * 10), // ~10 min
             blob_min_ttl: ChainEpoch::from(60 * 60),               // ~1 hour
             blob_default_ttl: ChainEpoch::from(60 * 60 * 24),      // ~1 day
+            credit_sell_discount_bps: None,
+            blob_read_cost: default_blob_read_cost(),
+            storage_pricing: StoragePricing::default(),
+        }
+    }
+}
+
+/// Reading a byte costs the same as storing it for one epoch.
+fn default_blob_read_cost() -> u64 {
+    1
+}
+
+/// Pricing of storage relative to the base rate of one credit per byte per epoch.
+///
+/// Committed credit is debited at the base rate, so the difference to the price is settled
+/// upfront, and the unused part is settled back if the subscription is deleted early.
+/// Utilisation and TTL bands never price below the base rate, and volume discounts only reduce
+/// the part of the price above it.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Default, PartialEq)]
+pub struct StoragePricing {
+    /// Subnet utilisation in basis points above which the utilisation multiplier rises,
+    /// and below which it falls. If zero, the price does not depend on utilisation.
+    pub target_utilization_bps: u16,
+    /// Bounds the change of the utilisation multiplier per debit interval, like the base fee
+    /// max change denominator in [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559).
+    pub multiplier_change_denominator: u64,
+    /// The maximum utilisation multiplier in basis points.
+    pub max_multiplier_bps: u64,
+    /// Multipliers in basis points by TTL, as (maximum TTL, multiplier) pairs sorted by TTL.
+    /// TTLs above the last band are priced at the base rate.
+    pub ttl_bands: Vec<(ChainEpoch, u64)>,
+    /// Discounts in basis points by blob size, as (minimum size, discount) pairs sorted by size.
+    pub volume_discounts: Vec<(u64, u16)>,
+}
+
+impl StoragePricing {
+    /// Returns the price multiplier in basis points for storing `size` bytes for `ttl` epochs
+    /// at the given utilisation multiplier.
+    pub fn multiplier_bps(
+        &self,
+        utilization_multiplier_bps: u64,
+        ttl: ChainEpoch,
+        size: u64,
+    ) -> u64 {
+        let bps = BASIS_POINTS as u128;
+        let band_bps = self
+            .ttl_bands
+            .iter()
+            .find(|(max_ttl, _)| ttl <= *max_ttl)
+            .map_or(bps, |(_, multiplier)| *multiplier as u128);
+        let discount_bps = self
+            .volume_discounts
+            .iter()
+            .rev()
+            .find(|(min_size, _)| size >= *min_size)
+            .map_or(0, |(_, discount)| (*discount).min(BASIS_POINTS) as u128);
+        let multiplier = (utilization_multiplier_bps as u128 * band_bps / bps).max(bps);
+        u64::try_from(multiplier * (bps - discount_bps) / bps).unwrap_or(u64::MAX)
+    }
+
+    /// Returns the utilisation multiplier for the next debit interval.
+    pub fn next_utilization_multiplier_bps(
+        &self,
+        multiplier_bps: u64,
+        capacity_used: u64,
+        capacity: u64,
+    ) -> u64 {
+        if self.target_utilization_bps == 0 || capacity == 0 {
+            return BASIS_POINTS as u64;
         }
+        let target = self.target_utilization_bps as u128;
+        let utilization = (capacity_used as u128 * BASIS_POINTS as u128 / capacity as u128)
+            .min(BASIS_POINTS as u128);
+        let multiplier = multiplier_bps as u128;
+        let denominator = self.multiplier_change_denominator.max(1) as u128;
+        let next = match utilization.cmp(&target) {
+            Ordering::Equal => multiplier,
+            Ordering::Less => {
+                let delta = multiplier * (target - utilization) / target / denominator;
+                multiplier.saturating_sub(delta)
+            }
+            Ordering::Greater => {
+                let delta = multiplier * (utilization - target) / target / denominator;
+                multiplier + delta.max(1)
+            }
+        };
+        let max = self.max_multiplier_bps.max(BASIS_POINTS as u64);
+        u64::try_from(next)
+            .unwrap_or(u64::MAX)
+            .clamp(BASIS_POINTS as u64, max)
     }
 }
 
+/// A config change that takes effect at a future epoch.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct ScheduledConfig {
+    /// The config to put in effect.
+    pub config: RecallConfig,
+    /// The epoch at which the config takes effect.
+    pub activation_epoch: ChainEpoch,
+}
+
+/// Params for proposing a new admin.
+/// The admin can be any account or actor, e.g., a multisig, and must accept to take over.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(transparent)]
-pub struct SetAdminParams(pub Address);
+pub struct ProposeAdminParams(pub Address);
 
+/// Params of the replaced `SetAdmin` method.
+pub type SetAdminParams = ProposeAdminParams;
+
+pub type ScheduleConfigParams = ScheduledConfig;
+
+/// Params of the replaced `SetConfig` method.
 pub type SetConfigParams = RecallConfig;
 
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
-    SetAdmin = frc42_dispatch::method_hash!("SetAdmin"),
+    ProposeAdmin = frc42_dispatch::method_hash!("ProposeAdmin"),
+    AcceptAdmin = frc42_dispatch::method_hash!("AcceptAdmin"),
     GetAdmin = frc42_dispatch::method_hash!("GetAdmin"),
-    SetConfig = frc42_dispatch::method_hash!("SetConfig"),
+    GetPendingAdmin = frc42_dispatch::method_hash!("GetPendingAdmin"),
+    ScheduleConfig = frc42_dispatch::method_hash!("ScheduleConfig"),
+    CancelConfig = frc42_dispatch::method_hash!("CancelConfig"),
+    ActivateConfig = frc42_dispatch::method_hash!("ActivateConfig"),
     GetConfig = frc42_dispatch::method_hash!("GetConfig"),
+    GetPendingConfig = frc42_dispatch::method_hash!("GetPendingConfig"),
+    // Replaced by `ProposeAdmin` and `ScheduleConfig`. Kept so that existing callers get
+    // an error pointing to the replacements instead of a generic one.
+    SetAdmin = frc42_dispatch::method_hash!("SetAdmin"),
+    SetConfig = frc42_dispatch::method_hash!("SetConfig"),
 }
 
 pub fn get_admin(rt: &impl Runtime) -> Result<Option<Address>, ActorError> {
@@ -98,3 +234,72 @@ pub fn get_config(rt: &impl Runtime) -> Result<RecallConfig, ActorError> {
         SendFlags::READ_ONLY,
     ))?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pricing() -> StoragePricing {
+        StoragePricing {
+            target_utilization_bps: 5000,
+            multiplier_change_denominator: 8,
+            max_multiplier_bps: 40_000,
+            ttl_bands: vec![(60 * 60, 20_000), (24 * 60 * 60, 15_000)],
+            volume_discounts: vec![(1024, 2000), (1024 * 1024, 5000)],
+        }
+    }
+
+    #[test]
+    fn test_multiplier_bps() {
+        let pricing = pricing();
+        let base = BASIS_POINTS as u64;
+        assert_eq!(StoragePricing::default().multiplier_bps(base, 60, 1), base);
+        // TTL bands
+        assert_eq!(pricing.multiplier_bps(base, 60, 1), 20_000);
+        assert_eq!(pricing.multiplier_bps(base, 60 * 60 + 1, 1), 15_000);
+        assert_eq!(pricing.multiplier_bps(base, 24 * 60 * 60 + 1, 1), base);
+        // Volume discounts
+        assert_eq!(pricing.multiplier_bps(base, 60, 1024), 16_000);
+        assert_eq!(pricing.multiplier_bps(base, 60, 1024 * 1024), base);
+        // Utilisation
+        assert_eq!(pricing.multiplier_bps(2 * base, 60, 1), 40_000);
+        // Volume discounts apply below the base rate
+        assert_eq!(
+            pricing.multiplier_bps(base, 24 * 60 * 60 + 1, 1024 * 1024),
+            5_000
+        );
+        // Bands below the base rate are floored before the discount
+        let cheap = StoragePricing {
+            ttl_bands: vec![(ChainEpoch::MAX, 5_000)],
+            ..pricing.clone()
+        };
+        assert_eq!(cheap.multiplier_bps(base, 60, 1), base);
+        assert_eq!(cheap.multiplier_bps(base, 60, 1024), 8_000);
+    }
+
+    #[test]
+    fn test_next_utilization_multiplier_bps() {
+        let pricing = pricing();
+        let base = BASIS_POINTS as u64;
+        assert_eq!(pricing.next_utilization_multiplier_bps(base, 50, 100), base);
+        // Full utilisation raises the multiplier by 1 / denominator
+        assert_eq!(
+            pricing.next_utilization_multiplier_bps(base, 100, 100),
+            11_250
+        );
+        // Low utilisation lowers it, but not below the base rate
+        assert_eq!(
+            pricing.next_utilization_multiplier_bps(20_000, 0, 100),
+            17_500
+        );
+        assert_eq!(pricing.next_utilization_multiplier_bps(base, 0, 100), base);
+        // The multiplier is capped
+        assert_eq!(
+            pricing.next_utilization_multiplier_bps(39_000, 100, 100),
+            40_000
+        );
+        // Without a target, utilisation has no effect
+        let flat = StoragePricing::default();
+        assert_eq!(flat.next_utilization_multiplier_bps(20_000, 100, 100), base);
+    }
+}
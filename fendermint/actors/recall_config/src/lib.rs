@@ -2,13 +2,12 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use fendermint_actor_blobs_shared::get_stats;
 use fendermint_actor_blobs_shared::state::TokenCreditRate;
-use fendermint_actor_machine::{
-    events::emit_evm_event,
-    util::{to_delegated_address, to_id_and_delegated_address},
-};
+use fendermint_actor_machine::{events::emit_evm_event, util::to_id_address};
 use fendermint_actor_recall_config_shared::{
-    Method, RecallConfig, SetAdminParams, SetConfigParams,
+    Method, ProposeAdminParams, RecallConfig, ScheduleConfigParams, ScheduledConfig,
+    SetAdminParams, SetConfigParams, StoragePricing, BASIS_POINTS, MIN_CONFIG_CHANGE_DELAY,
 };
 use fil_actors_runtime::{
     actor_dispatch, actor_error,
@@ -16,21 +15,58 @@ use fil_actors_runtime::{
     ActorError, SYSTEM_ACTOR_ADDR,
 };
 use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::DAG_CBOR;
+use fvm_shared::event::{ActorEvent, Entry, Flags};
 use fvm_shared::{address::Address, clock::ChainEpoch};
 use num_traits::Signed;
 use recall_sol_facade::config::{config_admin_set, config_set};
+use serde::Serialize;
 
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
 pub const ACTOR_NAME: &str = "recall_config";
 
+/// The actor state.
+///
+/// Fields after `config` default when missing, so state stored before they were added
+/// still decodes.
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
 pub struct State {
     /// The admin address that is allowed to update the config.
     pub admin: Option<Address>,
     /// The Recall network configuration.
     pub config: RecallConfig,
+    /// The address proposed as the next admin, until it accepts.
+    #[serde(default)]
+    pub pending_admin: Option<Address>,
+    /// A config change waiting for its activation epoch.
+    #[serde(default)]
+    pub pending_config: Option<ScheduledConfig>,
+}
+
+impl State {
+    /// Returns the config in effect at an epoch, including a scheduled change that is due.
+    pub fn config_at(&self, epoch: ChainEpoch) -> &RecallConfig {
+        match &self.pending_config {
+            Some(pending) if pending.activation_epoch <= epoch => &pending.config,
+            _ => &self.config,
+        }
+    }
+
+    /// Puts a scheduled change that is due into effect and returns it.
+    fn activate_due_config(&mut self, epoch: ChainEpoch) -> Option<ScheduledConfig> {
+        let due = self
+            .pending_config
+            .as_ref()
+            .is_some_and(|pending| pending.activation_epoch <= epoch);
+        if !due {
+            return None;
+        }
+        let pending = self.pending_config.take()?;
+        self.config = pending.config.clone();
+        Some(pending)
+    }
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
@@ -40,6 +76,9 @@ pub struct ConstructorParams {
     initial_blob_credit_debit_interval: ChainEpoch,
     initial_blob_min_ttl: ChainEpoch,
     initial_blob_default_ttl: ChainEpoch,
+    initial_credit_sell_discount_bps: Option<u16>,
+    initial_blob_read_cost: u64,
+    initial_storage_pricing: StoragePricing,
 }
 
 pub struct Actor {}
@@ -50,146 +89,402 @@ impl Actor {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
         let st = State {
             admin: None,
+            pending_admin: None,
             config: RecallConfig {
                 blob_capacity: params.initial_blob_capacity,
                 token_credit_rate: params.initial_token_credit_rate,
                 blob_credit_debit_interval: params.initial_blob_credit_debit_interval,
                 blob_min_ttl: params.initial_blob_min_ttl,
                 blob_default_ttl: params.initial_blob_default_ttl,
+                credit_sell_discount_bps: params.initial_credit_sell_discount_bps,
+                blob_read_cost: params.initial_blob_read_cost,
+                storage_pricing: params.initial_storage_pricing,
             },
+            pending_config: None,
         };
         rt.create(&st)
     }
 
-    fn set_admin(rt: &impl Runtime, params: SetAdminParams) -> Result<(), ActorError> {
+    /// Proposes a new admin, which takes over once it calls `AcceptAdmin`.
+    ///
+    /// The admin does not need a delegated address, so a multisig actor can govern the config.
+    fn propose_admin(rt: &impl Runtime, params: ProposeAdminParams) -> Result<(), ActorError> {
         Self::ensure_update_allowed(rt)?;
 
-        let (admin_id_addr, admin_delegated_addr) = to_id_and_delegated_address(rt, params.0)?;
+        let admin_id_addr = to_id_address(rt, params.0, false)?;
 
         rt.transaction(|st: &mut State, _rt| {
-            st.admin = Some(admin_id_addr);
+            st.pending_admin = Some(admin_id_addr);
             Ok(())
         })?;
 
-        emit_evm_event(rt, config_admin_set(admin_delegated_addr))?;
+        emit_governance_event(
+            rt,
+            "admin-proposed",
+            "admin",
+            &to_external_address(rt, admin_id_addr),
+        )
+    }
 
-        Ok(())
+    /// Makes the immediate caller the admin, if it was proposed.
+    fn accept_admin(rt: &impl Runtime) -> Result<(), ActorError> {
+        let pending_admin = rt
+            .state::<State>()?
+            .pending_admin
+            .ok_or_else(|| ActorError::illegal_state(String::from("no admin has been proposed")))?;
+        rt.validate_immediate_caller_is(std::iter::once(&pending_admin))?;
+
+        rt.transaction(|st: &mut State, _rt| {
+            st.admin = st.pending_admin.take();
+            Ok(())
+        })?;
+
+        emit_evm_event(rt, config_admin_set(to_external_address(rt, pending_admin)))
     }
 
     fn get_admin(rt: &impl Runtime) -> Result<Option<Address>, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
-        match rt.state::<State>().map(|s| s.admin)? {
-            Some(admin) => {
-                let admin = to_delegated_address(rt, admin)?;
-                Ok(Some(admin))
-            }
-            None => Ok(None),
-        }
+        let admin = rt.state::<State>()?.admin;
+        Ok(admin.map(|admin| to_external_address(rt, admin)))
     }
 
-    fn set_config(rt: &impl Runtime, params: SetConfigParams) -> Result<(), ActorError> {
+    fn get_pending_admin(rt: &impl Runtime) -> Result<Option<Address>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let pending_admin = rt.state::<State>()?.pending_admin;
+        Ok(pending_admin.map(|admin| to_external_address(rt, admin)))
+    }
+
+    /// Schedules a config change, replacing any change that is not yet due.
+    ///
+    /// The change cannot take effect sooner than [`MIN_CONFIG_CHANGE_DELAY`] epochs from now.
+    fn schedule_config(rt: &impl Runtime, params: ScheduleConfigParams) -> Result<(), ActorError> {
         let admin_exists = Self::ensure_update_allowed(rt)?;
 
-        if !params.token_credit_rate.rate().is_positive() {
-            return Err(actor_error!(
-                illegal_argument,
-                "token credit rate must be positive"
-            ));
-        }
-        if params.blob_capacity == 0 {
+        let earliest = rt.curr_epoch() + MIN_CONFIG_CHANGE_DELAY;
+        if params.activation_epoch < earliest {
             return Err(actor_error!(
                 illegal_argument,
-                "blob capacity must be positive"
-            ));
-        }
-        if params.blob_credit_debit_interval <= 0 {
-            return Err(actor_error!(
-                illegal_argument,
-                "credit debit interval must be positive"
-            ));
-        }
-        if params.blob_min_ttl <= 0 {
-            return Err(actor_error!(
-                illegal_argument,
-                "minimum TTL must be positive"
-            ));
-        }
-        if params.blob_default_ttl <= 0 {
-            return Err(actor_error!(
-                illegal_argument,
-                "default TTL must be positive"
-            ));
-        }
-        if params.blob_default_ttl < params.blob_min_ttl {
-            return Err(actor_error!(
-                illegal_argument,
-                "default TTL must be greater than or equal to minimum TTL"
+                "config change cannot activate before epoch {}",
+                earliest
             ));
         }
+        validate_config(rt, &params.config)?;
 
-        let (admin_id_addr, admin_delegated_addr) = if !admin_exists {
+        let admin_id_addr = if !admin_exists {
             // The first caller becomes admin
-            let addrs = to_id_and_delegated_address(rt, rt.message().caller())?;
-            (Some(addrs.0), Some(addrs.1))
+            Some(rt.message().caller())
         } else {
-            (None, None)
+            None
         };
 
-        rt.transaction(|st: &mut State, _rt| {
+        let activated = rt.transaction(|st: &mut State, rt| {
             if let Some(admin) = admin_id_addr {
                 st.admin = Some(admin);
             }
-            st.config = params.clone();
-            Ok(())
+            let activated = st.activate_due_config(rt.curr_epoch());
+            st.pending_config = Some(params.clone());
+            Ok(activated)
         })?;
 
-        if let Some(admin) = admin_delegated_addr {
-            emit_evm_event(rt, config_admin_set(admin))?;
+        if let Some(admin) = admin_id_addr {
+            emit_evm_event(rt, config_admin_set(to_external_address(rt, admin)))?;
         }
-        emit_evm_event(
+        if let Some(activated) = activated {
+            emit_config_set(rt, &activated.config)?;
+        }
+        emit_governance_event(
             rt,
-            config_set(
-                params.blob_capacity,
-                params
-                    .token_credit_rate
-                    .rate()
-                    .to_biguint()
-                    .unwrap_or_default(),
-                params.blob_credit_debit_interval as u64,
-                params.blob_min_ttl as u64,
-                params.blob_default_ttl as u64,
-            ),
-        )?;
+            "config-scheduled",
+            "activation_epoch",
+            &params.activation_epoch,
+        )
+    }
+
+    /// Rejects the replaced `SetAdmin` method, since an admin now has to accept the role.
+    fn set_admin(rt: &impl Runtime, _: SetAdminParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        Err(ActorError::unhandled_message(String::from(
+            "SetAdmin was replaced by ProposeAdmin, followed by AcceptAdmin from the new admin",
+        )))
+    }
+
+    /// Rejects the replaced `SetConfig` method, since config changes now take effect later.
+    fn set_config(rt: &impl Runtime, _: SetConfigParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        Err(ActorError::unhandled_message(String::from(
+            "SetConfig was replaced by ScheduleConfig, with an activation epoch at least \
+             MIN_CONFIG_CHANGE_DELAY epochs ahead",
+        )))
+    }
+
+    /// Cancels the scheduled config change, if it is not yet due.
+    fn cancel_config(rt: &impl Runtime) -> Result<(), ActorError> {
+        Self::ensure_admin(rt)?;
+
+        let cancelled = rt.transaction(|st: &mut State, rt| {
+            match &st.pending_config {
+                Some(pending) if pending.activation_epoch > rt.curr_epoch() => {}
+                Some(_) => {
+                    return Err(ActorError::illegal_state(String::from(
+                        "scheduled config change is already in effect",
+                    )))
+                }
+                None => {
+                    return Err(ActorError::not_found(String::from(
+                        "no config change is scheduled",
+                    )))
+                }
+            }
+            Ok(st.pending_config.take())
+        })?;
 
+        if let Some(cancelled) = cancelled {
+            emit_governance_event(
+                rt,
+                "config-cancelled",
+                "activation_epoch",
+                &cancelled.activation_epoch,
+            )?;
+        }
         Ok(())
     }
 
+    /// Records a scheduled config change that is due as the current config.
+    ///
+    /// Validators propose this in the first block at or after the activation epoch,
+    /// so the activation event is emitted when the change takes effect.
+    fn activate_config(rt: &impl Runtime) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        let activated = rt.transaction(|st: &mut State, rt| {
+            st.activate_due_config(rt.curr_epoch()).ok_or_else(|| {
+                ActorError::illegal_state(String::from("no scheduled config change is due"))
+            })
+        })?;
+
+        emit_config_set(rt, &activated.config)
+    }
+
     fn get_config(rt: &impl Runtime) -> Result<RecallConfig, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
-        rt.state::<State>().map(|s| s.config)
+        let st = rt.state::<State>()?;
+        Ok(st.config_at(rt.curr_epoch()).clone())
+    }
+
+    /// Returns the scheduled config change until it has been activated.
+    fn get_pending_config(rt: &impl Runtime) -> Result<Option<ScheduledConfig>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        Ok(rt.state::<State>()?.pending_config)
     }
 
     /// Ensures that immediate caller is allowed to update the config.
     /// Returns whether the admin exists.
     fn ensure_update_allowed(rt: &impl Runtime) -> Result<bool, ActorError> {
         let st = rt.state::<State>()?;
-        let admin_exists = if let Some(admin) = st.admin {
-            if let Some(admin_id) = rt.resolve_address(&admin) {
-                rt.validate_immediate_caller_is(std::iter::once(&Address::new_id(admin_id)))?
-            } else {
-                // This should not happen.
-                return Err(ActorError::forbidden(String::from(
-                    "failed to resolve config admin id",
-                )));
-            }
-            true
+        if st.admin.is_some() {
+            Self::ensure_admin(rt)?;
+            Ok(true)
         } else {
             // The first caller becomes the admin
             rt.validate_immediate_caller_accept_any()?;
-            false
+            Ok(false)
+        }
+    }
+
+    /// Ensures that immediate caller is the admin.
+    fn ensure_admin(rt: &impl Runtime) -> Result<(), ActorError> {
+        let st = rt.state::<State>()?;
+        let Some(admin) = st.admin else {
+            return Err(ActorError::forbidden(String::from(
+                "config admin is not set",
+            )));
         };
-        Ok(admin_exists)
+        if let Some(admin_id) = rt.resolve_address(&admin) {
+            rt.validate_immediate_caller_is(std::iter::once(&Address::new_id(admin_id)))
+        } else {
+            // This should not happen.
+            Err(ActorError::forbidden(String::from(
+                "failed to resolve config admin id",
+            )))
+        }
+    }
+}
+
+/// Checks that a config is internally consistent and fits the current state of the subnet.
+fn validate_config(rt: &impl Runtime, config: &RecallConfig) -> Result<(), ActorError> {
+    if !config.token_credit_rate.rate().is_positive() {
+        return Err(actor_error!(
+            illegal_argument,
+            "token credit rate must be positive"
+        ));
+    }
+    if config.blob_capacity == 0 {
+        return Err(actor_error!(
+            illegal_argument,
+            "blob capacity must be positive"
+        ));
+    }
+    if config.blob_credit_debit_interval <= 0 {
+        return Err(actor_error!(
+            illegal_argument,
+            "credit debit interval must be positive"
+        ));
+    }
+    if config.blob_min_ttl <= 0 {
+        return Err(actor_error!(
+            illegal_argument,
+            "minimum TTL must be positive"
+        ));
+    }
+    if config.blob_default_ttl <= 0 {
+        return Err(actor_error!(
+            illegal_argument,
+            "default TTL must be positive"
+        ));
+    }
+    if config.blob_default_ttl < config.blob_min_ttl {
+        return Err(actor_error!(
+            illegal_argument,
+            "default TTL must be greater than or equal to minimum TTL"
+        ));
+    }
+    if config
+        .credit_sell_discount_bps
+        .is_some_and(|bps| bps > BASIS_POINTS)
+    {
+        return Err(actor_error!(
+            illegal_argument,
+            "credit sell discount cannot exceed {} basis points",
+            BASIS_POINTS
+        ));
+    }
+    validate_storage_pricing(&config.storage_pricing)?;
+    let stats = get_stats(rt)?;
+    if config.blob_capacity < stats.capacity_used {
+        return Err(actor_error!(
+            illegal_argument,
+            "blob capacity cannot be less than the current usage of {} bytes",
+            stats.capacity_used
+        ));
+    }
+    Ok(())
+}
+
+fn validate_storage_pricing(pricing: &StoragePricing) -> Result<(), ActorError> {
+    if pricing.target_utilization_bps > BASIS_POINTS {
+        return Err(actor_error!(
+            illegal_argument,
+            "target utilization cannot exceed {} basis points",
+            BASIS_POINTS
+        ));
+    }
+    if pricing.target_utilization_bps > 0 {
+        if pricing.multiplier_change_denominator == 0 {
+            return Err(actor_error!(
+                illegal_argument,
+                "multiplier change denominator must be positive"
+            ));
+        }
+        if pricing.max_multiplier_bps < BASIS_POINTS as u64 {
+            return Err(actor_error!(
+                illegal_argument,
+                "maximum multiplier must be at least {} basis points",
+                BASIS_POINTS
+            ));
+        }
+    }
+    if pricing.ttl_bands.iter().any(|(max_ttl, _)| *max_ttl <= 0) {
+        return Err(actor_error!(
+            illegal_argument,
+            "TTL band maximums must be positive"
+        ));
+    }
+    if pricing.ttl_bands.windows(2).any(|w| w[0].0 >= w[1].0) {
+        return Err(actor_error!(
+            illegal_argument,
+            "TTL bands must be sorted by TTL"
+        ));
+    }
+    if pricing
+        .volume_discounts
+        .iter()
+        .any(|(_, discount)| *discount > BASIS_POINTS)
+    {
+        return Err(actor_error!(
+            illegal_argument,
+            "volume discounts cannot exceed {} basis points",
+            BASIS_POINTS
+        ));
     }
+    if pricing
+        .volume_discounts
+        .windows(2)
+        .any(|w| w[0].0 >= w[1].0)
+    {
+        return Err(actor_error!(
+            illegal_argument,
+            "volume discounts must be sorted by size"
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the delegated address of an actor if it has one, e.g., for EVM events,
+/// and its ID address otherwise.
+fn to_external_address(rt: &impl Runtime, id_addr: Address) -> Address {
+    id_addr
+        .id()
+        .ok()
+        .and_then(|id| rt.lookup_delegated_address(id))
+        .unwrap_or(id_addr)
+}
+
+fn emit_config_set(rt: &impl Runtime, config: &RecallConfig) -> Result<(), ActorError> {
+    emit_evm_event(
+        rt,
+        config_set(
+            config.blob_capacity,
+            config
+                .token_credit_rate
+                .rate()
+                .to_biguint()
+                .unwrap_or_default(),
+            config.blob_credit_debit_interval as u64,
+            config.blob_min_ttl as u64,
+            config.blob_default_ttl as u64,
+        ),
+    )
+}
+
+/// Emits a native actor event for governance actions that have no EVM event.
+fn emit_governance_event<T: Serialize>(
+    rt: &impl Runtime,
+    typ: &str,
+    key: &str,
+    value: &T,
+) -> Result<(), ActorError> {
+    rt.emit_event(&governance_event(typ, key, value)?)
+}
+
+/// Returns a native actor event with a type and a single value, both CBOR-encoded.
+pub fn governance_event<T: Serialize>(
+    typ: &str,
+    key: &str,
+    value: &T,
+) -> Result<ActorEvent, ActorError> {
+    let entry = |key: &str, value: Vec<u8>| Entry {
+        flags: Flags::FLAG_INDEXED_ALL,
+        key: key.to_owned(),
+        codec: DAG_CBOR,
+        value,
+    };
+    let encode =
+        |e: fvm_ipld_encoding::Error| actor_error!(serialization; "failed to encode event: {}", e);
+    let entries = vec![
+        entry("$type", fvm_ipld_encoding::to_vec(typ).map_err(encode)?),
+        entry(key, fvm_ipld_encoding::to_vec(value).map_err(encode)?),
+    ];
+    Ok(entries.into())
 }
 
 impl ActorCode for Actor {
@@ -201,10 +496,17 @@ impl ActorCode for Actor {
 
     actor_dispatch! {
         Constructor => constructor,
-        SetAdmin => set_admin,
+        ProposeAdmin => propose_admin,
+        AcceptAdmin => accept_admin,
         GetAdmin => get_admin,
-        SetConfig => set_config,
+        GetPendingAdmin => get_pending_admin,
+        ScheduleConfig => schedule_config,
+        CancelConfig => cancel_config,
+        ActivateConfig => activate_config,
         GetConfig => get_config,
+        GetPendingConfig => get_pending_config,
+        SetAdmin => set_admin,
+        SetConfig => set_config,
     }
 }
 
@@ -212,15 +514,22 @@ impl ActorCode for Actor {
 mod tests {
     use super::*;
 
+    use fendermint_actor_blobs_shared::params::GetStatsReturn;
+    use fendermint_actor_blobs_shared::{Method as BlobsMethod, BLOBS_ACTOR_ADDR};
     use fendermint_actor_machine::events::to_actor_event;
     use fendermint_actor_recall_config_shared::{RecallConfig, RECALL_CONFIG_ACTOR_ID};
     use fil_actors_evm_shared::address::EthAddress;
     use fil_actors_runtime::test_utils::{
-        expect_empty, MockRuntime, ETHACCOUNT_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
+        expect_empty, MockRuntime, ETHACCOUNT_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID,
+        SYSTEM_ACTOR_CODE_ID,
     };
     use fvm_ipld_encoding::ipld_block::IpldBlock;
     use fvm_shared::bigint::BigInt;
+    use fvm_shared::econ::TokenAmount;
     use fvm_shared::error::ExitCode;
+    use fvm_shared::sys::SendFlags;
+    use fvm_shared::MethodNum;
+    use num_traits::Zero;
 
     pub fn construct_and_verify(
         blob_capacity: u64,
@@ -248,6 +557,9 @@ mod tests {
                     ),
                     initial_blob_min_ttl,
                     initial_blob_default_ttl,
+                    initial_credit_sell_discount_bps: None,
+                    initial_blob_read_cost: 1,
+                    initial_storage_pricing: StoragePricing::default(),
                 })
                 .unwrap(),
             )
@@ -281,80 +593,146 @@ mod tests {
         assert!(admin.is_none());
     }
 
-    #[test]
-    fn test_set_admin() {
-        let rt = construct_and_verify(
-            1024,
-            TokenCreditRate::from(BigInt::from(5)),
-            3600,
-            3600,
-            3600,
-        );
+    fn new_eth_account(rt: &MockRuntime, id: u64, eth_addr: [u8; 20]) -> (Address, Address) {
+        let id_addr = Address::new_id(id);
+        let f4_eth_addr = Address::new_delegated(10, &EthAddress(eth_addr).0).unwrap();
+        rt.set_delegated_address(id, f4_eth_addr);
+        (id_addr, f4_eth_addr)
+    }
 
-        let id_addr = Address::new_id(110);
-        let eth_addr = EthAddress(hex_literal::hex!(
-            "CAFEB0BA00000000000000000000000000000000"
-        ));
-        let f4_eth_addr = Address::new_delegated(10, &eth_addr.0).unwrap();
-        rt.set_delegated_address(id_addr.id().unwrap(), f4_eth_addr);
+    fn valid_config() -> RecallConfig {
+        RecallConfig {
+            blob_capacity: 2048,
+            token_credit_rate: TokenCreditRate::from(BigInt::from(10)),
+            blob_credit_debit_interval: ChainEpoch::from(1800),
+            blob_min_ttl: ChainEpoch::from(2 * 60 * 60),
+            blob_default_ttl: ChainEpoch::from(24 * 60 * 60),
+            credit_sell_discount_bps: Some(500),
+            blob_read_cost: 2,
+            storage_pricing: StoragePricing {
+                target_utilization_bps: 5000,
+                multiplier_change_denominator: 8,
+                max_multiplier_bps: 40_000,
+                ttl_bands: vec![(60 * 60, 20_000), (24 * 60 * 60, 15_000)],
+                volume_discounts: vec![(1024 * 1024, 1000), (1024 * 1024 * 1024, 2500)],
+            },
+        }
+    }
 
-        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, id_addr);
-        rt.expect_validate_caller_any();
-        let event = to_actor_event(config_admin_set(f4_eth_addr).unwrap()).unwrap();
+    fn expect_get_stats(rt: &MockRuntime, capacity_used: u64) {
+        let stats = GetStatsReturn {
+            balance: TokenAmount::zero(),
+            capacity_free: 0,
+            capacity_used,
+            credit_sold: TokenAmount::zero(),
+            credit_committed: TokenAmount::zero(),
+            credit_debited: TokenAmount::zero(),
+            token_credit_rate: TokenCreditRate::from(BigInt::from(5)),
+            num_accounts: 0,
+            num_blobs: 0,
+            num_added: 0,
+            bytes_added: 0,
+            num_resolving: 0,
+            bytes_resolving: 0,
+            storage_multiplier_bps: BASIS_POINTS as u64,
+        };
+        rt.expect_send(
+            BLOBS_ACTOR_ADDR,
+            BlobsMethod::GetStats as MethodNum,
+            None,
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&stats).unwrap(),
+            ExitCode::OK,
+            None,
+        );
+    }
+
+    fn expect_emitted_config_set_event(rt: &MockRuntime, config: &RecallConfig) {
+        let event = to_actor_event(
+            config_set(
+                config.blob_capacity,
+                config
+                    .token_credit_rate
+                    .rate()
+                    .to_biguint()
+                    .unwrap_or_default(),
+                config.blob_credit_debit_interval as u64,
+                config.blob_min_ttl as u64,
+                config.blob_default_ttl as u64,
+            )
+            .unwrap(),
+        )
+        .unwrap();
         rt.expect_emitted_event(event);
-        let params = SetAdminParams(f4_eth_addr);
+    }
+
+    fn propose_admin(rt: &MockRuntime, caller: Address, admin: Address, admin_external: Address) {
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, caller);
+        rt.expect_emitted_event(
+            governance_event("admin-proposed", "admin", &admin_external).unwrap(),
+        );
         let result = rt.call::<Actor>(
-            Method::SetAdmin as u64,
-            IpldBlock::serialize_cbor(&params).unwrap(),
+            Method::ProposeAdmin as u64,
+            IpldBlock::serialize_cbor(&ProposeAdminParams(admin)).unwrap(),
         );
         assert!(result.is_ok());
         rt.verify();
+    }
 
+    fn accept_admin(rt: &MockRuntime, multisig: bool, admin: Address, admin_external: Address) {
+        if multisig {
+            rt.set_caller(*MULTISIG_ACTOR_CODE_ID, admin);
+        } else {
+            rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, admin);
+        }
+        rt.expect_validate_caller_addr(vec![admin]);
+        let event = to_actor_event(config_admin_set(admin_external).unwrap()).unwrap();
+        rt.expect_emitted_event(event);
+        let result = rt.call::<Actor>(Method::AcceptAdmin as u64, None);
+        assert!(result.is_ok());
+        rt.verify();
+    }
+
+    fn get_admin(rt: &MockRuntime, method: Method) -> Option<Address> {
         rt.expect_validate_caller_any();
         let admin = rt
-            .call::<Actor>(Method::GetAdmin as u64, None)
+            .call::<Actor>(method as u64, None)
             .unwrap()
             .unwrap()
             .deserialize::<Option<Address>>()
             .unwrap();
         rt.verify();
+        admin
+    }
 
-        assert_eq!(admin, Some(f4_eth_addr));
-
-        // Reset admin
-        let new_id_addr = Address::new_id(111);
-        let new_eth_addr = EthAddress(hex_literal::hex!(
-            "CAFEB0BA00000000000000000000000000000001"
-        ));
-        let new_f4_eth_addr = Address::new_delegated(10, &new_eth_addr.0).unwrap();
-        rt.set_delegated_address(new_id_addr.id().unwrap(), new_f4_eth_addr);
-
-        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, id_addr); // current admin
-        rt.expect_validate_caller_addr(vec![id_addr]);
-        let event = to_actor_event(config_admin_set(new_f4_eth_addr).unwrap()).unwrap();
-        rt.expect_emitted_event(event);
-        let params = SetAdminParams(new_f4_eth_addr);
-        let result = rt.call::<Actor>(
-            Method::SetAdmin as u64,
-            IpldBlock::serialize_cbor(&params).unwrap(),
-        );
-        assert!(result.is_ok());
+    fn get_config(rt: &MockRuntime) -> RecallConfig {
+        rt.expect_validate_caller_any();
+        let config = rt
+            .call::<Actor>(Method::GetConfig as u64, None)
+            .unwrap()
+            .unwrap()
+            .deserialize::<RecallConfig>()
+            .unwrap();
         rt.verify();
+        config
+    }
 
+    fn get_pending_config(rt: &MockRuntime) -> Option<ScheduledConfig> {
         rt.expect_validate_caller_any();
-        let admin = rt
-            .call::<Actor>(Method::GetAdmin as u64, None)
+        let pending = rt
+            .call::<Actor>(Method::GetPendingConfig as u64, None)
             .unwrap()
             .unwrap()
-            .deserialize::<Option<Address>>()
+            .deserialize::<Option<ScheduledConfig>>()
             .unwrap();
         rt.verify();
-
-        assert_eq!(admin, Some(new_f4_eth_addr));
+        pending
     }
 
     #[test]
-    fn test_set_admin_unauthorized() {
+    fn test_propose_and_accept_admin() {
         let rt = construct_and_verify(
             1024,
             TokenCreditRate::from(BigInt::from(5)),
@@ -362,50 +740,87 @@ mod tests {
             3600,
             3600,
         );
+        let (id_addr, f4_eth_addr) = new_eth_account(
+            &rt,
+            110,
+            hex_literal::hex!("CAFEB0BA00000000000000000000000000000000"),
+        );
+        let (new_id_addr, new_f4_eth_addr) = new_eth_account(
+            &rt,
+            111,
+            hex_literal::hex!("CAFEB0BA00000000000000000000000000000001"),
+        );
 
-        let id_addr = Address::new_id(110);
-        let eth_addr = EthAddress(hex_literal::hex!(
-            "CAFEB0BA00000000000000000000000000000000"
-        ));
-        let f4_eth_addr = Address::new_delegated(10, &eth_addr.0).unwrap();
-        rt.set_delegated_address(id_addr.id().unwrap(), f4_eth_addr);
-
-        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, id_addr);
+        // Anyone can propose the first admin
         rt.expect_validate_caller_any();
-        let event = to_actor_event(config_admin_set(f4_eth_addr).unwrap()).unwrap();
-        rt.expect_emitted_event(event);
-        let params = SetAdminParams(f4_eth_addr);
-        let result = rt.call::<Actor>(
-            Method::SetAdmin as u64,
-            IpldBlock::serialize_cbor(&params).unwrap(),
-        );
-        assert!(result.is_ok());
+        propose_admin(&rt, id_addr, f4_eth_addr, f4_eth_addr);
+        assert_eq!(get_admin(&rt, Method::GetAdmin), None);
+        assert_eq!(get_admin(&rt, Method::GetPendingAdmin), Some(f4_eth_addr));
+
+        // Only the proposed admin can accept
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, new_id_addr);
+        rt.expect_validate_caller_addr(vec![id_addr]);
+        let result = rt.call::<Actor>(Method::AcceptAdmin as u64, None);
+        assert_eq!(result.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
         rt.verify();
 
-        // Try to set again with a different caller
-        let unauthorized_id_addr = Address::new_id(111);
-        let unauthorized_eth_addr = EthAddress(hex_literal::hex!(
-            "CAFEB0BA00000000000000000000000000000001"
-        ));
-        let unauthorized_f4_eth_addr =
-            Address::new_delegated(10, &unauthorized_eth_addr.0).unwrap();
-        rt.set_delegated_address(unauthorized_id_addr.id().unwrap(), unauthorized_f4_eth_addr);
+        accept_admin(&rt, false, id_addr, f4_eth_addr);
+        assert_eq!(get_admin(&rt, Method::GetAdmin), Some(f4_eth_addr));
+        assert_eq!(get_admin(&rt, Method::GetPendingAdmin), None);
+
+        // The current admin stays in charge until the new one accepts
+        rt.expect_validate_caller_addr(vec![id_addr]);
+        propose_admin(&rt, id_addr, new_f4_eth_addr, new_f4_eth_addr);
+        assert_eq!(get_admin(&rt, Method::GetAdmin), Some(f4_eth_addr));
+        accept_admin(&rt, false, new_id_addr, new_f4_eth_addr);
+        assert_eq!(get_admin(&rt, Method::GetAdmin), Some(new_f4_eth_addr));
+    }
 
-        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, unauthorized_id_addr); // unauthorized caller
+    #[test]
+    fn test_propose_admin_unauthorized() {
+        let rt = construct_and_verify(
+            1024,
+            TokenCreditRate::from(BigInt::from(5)),
+            3600,
+            3600,
+            3600,
+        );
+        let (id_addr, f4_eth_addr) = new_eth_account(
+            &rt,
+            110,
+            hex_literal::hex!("CAFEB0BA00000000000000000000000000000000"),
+        );
+        let (unauthorized_id_addr, unauthorized_f4_eth_addr) = new_eth_account(
+            &rt,
+            111,
+            hex_literal::hex!("CAFEB0BA00000000000000000000000000000001"),
+        );
+        rt.expect_validate_caller_any();
+        propose_admin(&rt, id_addr, f4_eth_addr, f4_eth_addr);
+        accept_admin(&rt, false, id_addr, f4_eth_addr);
+
+        // Try to propose with a different caller
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, unauthorized_id_addr);
         rt.expect_validate_caller_addr(vec![id_addr]); // expect current admin
-        let params = SetAdminParams(unauthorized_f4_eth_addr);
+        let params = ProposeAdminParams(unauthorized_f4_eth_addr);
         let result = rt.call::<Actor>(
-            Method::SetAdmin as u64,
+            Method::ProposeAdmin as u64,
             IpldBlock::serialize_cbor(&params).unwrap(),
         );
         rt.verify();
 
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+
+        // Accepting without a proposal fails
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, unauthorized_id_addr);
+        let result = rt.call::<Actor>(Method::AcceptAdmin as u64, None);
+        assert_eq!(result.unwrap_err().exit_code(), ExitCode::USR_ILLEGAL_STATE);
+        rt.verify();
     }
 
     #[test]
-    fn test_set_config() {
+    fn test_multisig_admin() {
         let rt = construct_and_verify(
             1024,
             TokenCreditRate::from(BigInt::from(5)),
@@ -413,56 +828,108 @@ mod tests {
             3600,
             3600,
         );
+        let (id_addr, f4_eth_addr) = new_eth_account(
+            &rt,
+            110,
+            hex_literal::hex!("CAFEB0BA00000000000000000000000000000000"),
+        );
+        // A multisig has no delegated address
+        let multisig_addr = Address::new_id(120);
 
-        let id_addr = Address::new_id(110);
-        let eth_addr = EthAddress(hex_literal::hex!(
-            "CAFEB0BA00000000000000000000000000000000"
-        ));
-        let f4_eth_addr = Address::new_delegated(10, &eth_addr.0).unwrap();
-        rt.set_delegated_address(id_addr.id().unwrap(), f4_eth_addr);
-
-        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, id_addr);
         rt.expect_validate_caller_any();
-        let event = to_actor_event(config_admin_set(f4_eth_addr).unwrap()).unwrap();
-        rt.expect_emitted_event(event);
-        let params = RecallConfig {
-            blob_capacity: 2048,
-            token_credit_rate: TokenCreditRate::from(BigInt::from(10)),
-            blob_credit_debit_interval: ChainEpoch::from(1800),
-            blob_min_ttl: ChainEpoch::from(2 * 60 * 60),
-            blob_default_ttl: ChainEpoch::from(24 * 60 * 60),
+        propose_admin(&rt, id_addr, f4_eth_addr, f4_eth_addr);
+        accept_admin(&rt, false, id_addr, f4_eth_addr);
+        rt.expect_validate_caller_addr(vec![id_addr]);
+        propose_admin(&rt, id_addr, multisig_addr, multisig_addr);
+        accept_admin(&rt, true, multisig_addr, multisig_addr);
+        assert_eq!(get_admin(&rt, Method::GetAdmin), Some(multisig_addr));
+
+        // The multisig can schedule config changes
+        let params = ScheduledConfig {
+            config: valid_config(),
+            activation_epoch: MIN_CONFIG_CHANGE_DELAY,
         };
-        let event = to_actor_event(
-            config_set(
-                params.blob_capacity,
-                params
-                    .token_credit_rate
-                    .rate()
-                    .to_biguint()
-                    .unwrap_or_default(),
-                params.blob_credit_debit_interval as u64,
-                params.blob_min_ttl as u64,
-                params.blob_default_ttl as u64,
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, multisig_addr);
+        rt.expect_validate_caller_addr(vec![multisig_addr]);
+        expect_get_stats(&rt, 0);
+        rt.expect_emitted_event(
+            governance_event(
+                "config-scheduled",
+                "activation_epoch",
+                &params.activation_epoch,
             )
             .unwrap(),
-        )
-        .unwrap();
-        rt.expect_emitted_event(event);
+        );
         let result = rt.call::<Actor>(
-            Method::SetConfig as u64,
+            Method::ScheduleConfig as u64,
             IpldBlock::serialize_cbor(&params).unwrap(),
         );
         assert!(result.is_ok());
         rt.verify();
+    }
+
+    #[test]
+    fn test_schedule_config() {
+        let rt = construct_and_verify(
+            1024,
+            TokenCreditRate::from(BigInt::from(5)),
+            3600,
+            3600,
+            3600,
+        );
+        let (id_addr, f4_eth_addr) = new_eth_account(
+            &rt,
+            110,
+            hex_literal::hex!("CAFEB0BA00000000000000000000000000000000"),
+        );
 
+        // The first caller becomes admin
+        let activation_epoch = 10 + MIN_CONFIG_CHANGE_DELAY;
+        let params = ScheduledConfig {
+            config: valid_config(),
+            activation_epoch,
+        };
+        rt.set_epoch(10);
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, id_addr);
         rt.expect_validate_caller_any();
-        let recall_config = rt
-            .call::<Actor>(Method::GetConfig as u64, None)
-            .unwrap()
-            .unwrap()
-            .deserialize::<RecallConfig>()
-            .unwrap();
+        expect_get_stats(&rt, 1024);
+        let event = to_actor_event(config_admin_set(f4_eth_addr).unwrap()).unwrap();
+        rt.expect_emitted_event(event);
+        rt.expect_emitted_event(
+            governance_event("config-scheduled", "activation_epoch", &activation_epoch).unwrap(),
+        );
+        let result = rt.call::<Actor>(
+            Method::ScheduleConfig as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        assert!(result.is_ok());
         rt.verify();
+        assert_eq!(get_admin(&rt, Method::GetAdmin), Some(f4_eth_addr));
+
+        // The change is pending until its activation epoch
+        assert_eq!(get_config(&rt).blob_capacity, 1024);
+        let pending = get_pending_config(&rt).unwrap();
+        assert_eq!(pending.activation_epoch, activation_epoch);
+        assert_eq!(pending.config.blob_capacity, 2048);
+
+        // Only the system actor can activate changes
+        rt.set_epoch(activation_epoch);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        let result = rt.call::<Actor>(Method::ActivateConfig as u64, None);
+        assert_eq!(result.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+        rt.verify();
+
+        // Nothing can be activated early
+        rt.set_epoch(activation_epoch - 1);
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        let result = rt.call::<Actor>(Method::ActivateConfig as u64, None);
+        assert_eq!(result.unwrap_err().exit_code(), ExitCode::USR_ILLEGAL_STATE);
+        rt.verify();
+
+        // The change is in effect at its activation epoch
+        rt.set_epoch(activation_epoch);
+        let recall_config = get_config(&rt);
 
         assert_eq!(recall_config.blob_capacity, 2048);
         assert_eq!(
@@ -475,33 +942,142 @@ mod tests {
             recall_config.blob_default_ttl,
             ChainEpoch::from(24 * 60 * 60)
         );
+        assert_eq!(recall_config.credit_sell_discount_bps, Some(500));
+        assert_eq!(recall_config.blob_read_cost, 2);
+
+        // The change stays pending until the chain activates it
+        assert_eq!(
+            get_pending_config(&rt).unwrap().activation_epoch,
+            activation_epoch
+        );
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        expect_emitted_config_set_event(&rt, &params.config);
+        let result = rt.call::<Actor>(Method::ActivateConfig as u64, None);
+        assert!(result.is_ok());
+        rt.verify();
+        assert!(get_pending_config(&rt).is_none());
+        assert_eq!(rt.get_state::<State>().config.blob_capacity, 2048);
+    }
+
+    #[test]
+    fn test_set_admin_and_config_replaced() {
+        let rt = construct_and_verify(
+            1024,
+            TokenCreditRate::from(BigInt::from(5)),
+            3600,
+            3600,
+            3600,
+        );
+        let (id_addr, f4_eth_addr) = new_eth_account(
+            &rt,
+            110,
+            hex_literal::hex!("CAFEB0BA00000000000000000000000000000000"),
+        );
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, id_addr);
 
+        // The replaced methods fail instead of silently only proposing or scheduling
         rt.expect_validate_caller_any();
-        let admin = rt
-            .call::<Actor>(Method::GetAdmin as u64, None)
-            .unwrap()
-            .unwrap()
-            .deserialize::<Option<Address>>()
-            .unwrap();
+        let result = rt.call::<Actor>(
+            Method::SetAdmin as u64,
+            IpldBlock::serialize_cbor(&SetAdminParams(f4_eth_addr)).unwrap(),
+        );
+        let err = result.unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::USR_UNHANDLED_MESSAGE);
+        assert!(err.msg().contains("ProposeAdmin"));
+        rt.verify();
+
+        rt.expect_validate_caller_any();
+        let result = rt.call::<Actor>(
+            Method::SetConfig as u64,
+            IpldBlock::serialize_cbor(&valid_config()).unwrap(),
+        );
+        let err = result.unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::USR_UNHANDLED_MESSAGE);
+        assert!(err.msg().contains("ScheduleConfig"));
         rt.verify();
 
-        assert_eq!(admin, Some(f4_eth_addr));
+        assert_eq!(get_admin(&rt, Method::GetAdmin), None);
+        assert!(get_pending_config(&rt).is_none());
+        assert_eq!(get_config(&rt).blob_capacity, 1024);
     }
 
     #[test]
-    fn test_set_invalid_config() {
+    fn test_cancel_config() {
+        let rt = construct_and_verify(
+            1024,
+            TokenCreditRate::from(BigInt::from(5)),
+            3600,
+            3600,
+            3600,
+        );
+        let (id_addr, f4_eth_addr) = new_eth_account(
+            &rt,
+            110,
+            hex_literal::hex!("CAFEB0BA00000000000000000000000000000000"),
+        );
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, id_addr);
+
+        // Cancelling requires an admin
+        let result = rt.call::<Actor>(Method::CancelConfig as u64, None);
+        assert_eq!(result.unwrap_err().exit_code(), ExitCode::USR_FORBIDDEN);
+        rt.verify();
+
+        let params = ScheduledConfig {
+            config: valid_config(),
+            activation_epoch: MIN_CONFIG_CHANGE_DELAY,
+        };
+        rt.expect_validate_caller_any();
+        expect_get_stats(&rt, 0);
+        let event = to_actor_event(config_admin_set(f4_eth_addr).unwrap()).unwrap();
+        rt.expect_emitted_event(event);
+        rt.expect_emitted_event(
+            governance_event(
+                "config-scheduled",
+                "activation_epoch",
+                &params.activation_epoch,
+            )
+            .unwrap(),
+        );
+        rt.call::<Actor>(
+            Method::ScheduleConfig as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+
+        rt.expect_validate_caller_addr(vec![id_addr]);
+        rt.expect_emitted_event(
+            governance_event(
+                "config-cancelled",
+                "activation_epoch",
+                &params.activation_epoch,
+            )
+            .unwrap(),
+        );
+        let result = rt.call::<Actor>(Method::CancelConfig as u64, None);
+        assert!(result.is_ok());
+        rt.verify();
+        assert!(get_pending_config(&rt).is_none());
+
+        // The config never takes effect
+        rt.set_epoch(MIN_CONFIG_CHANGE_DELAY);
+        assert_eq!(get_config(&rt).blob_capacity, 1024);
+
+        // There is nothing left to cancel
+        rt.expect_validate_caller_addr(vec![id_addr]);
+        let result = rt.call::<Actor>(Method::CancelConfig as u64, None);
+        assert_eq!(result.unwrap_err().exit_code(), ExitCode::USR_NOT_FOUND);
+        rt.verify();
+    }
+
+    #[test]
+    fn test_schedule_invalid_config() {
         struct TestCase {
             name: &'static str,
             config: RecallConfig,
         }
 
-        let valid_config = RecallConfig {
-            blob_capacity: 2048,
-            token_credit_rate: TokenCreditRate::from(BigInt::from(10)),
-            blob_credit_debit_interval: ChainEpoch::from(1800),
-            blob_min_ttl: ChainEpoch::from(2 * 60 * 60),
-            blob_default_ttl: ChainEpoch::from(24 * 60 * 60),
-        };
+        let valid_config = valid_config();
 
         let test_cases = vec![
             // Token credit rate validation
@@ -579,6 +1155,65 @@ mod tests {
                     ..valid_config.clone()
                 },
             },
+            // Credit sell discount validation
+            TestCase {
+                name: "credit sell discount cannot exceed 100%",
+                config: RecallConfig {
+                    credit_sell_discount_bps: Some(10_001),
+                    ..valid_config.clone()
+                },
+            },
+            // Storage pricing validation
+            TestCase {
+                name: "target utilization cannot exceed 100%",
+                config: RecallConfig {
+                    storage_pricing: StoragePricing {
+                        target_utilization_bps: 10_001,
+                        ..valid_config.storage_pricing.clone()
+                    },
+                    ..valid_config.clone()
+                },
+            },
+            TestCase {
+                name: "multiplier change denominator cannot be zero",
+                config: RecallConfig {
+                    storage_pricing: StoragePricing {
+                        multiplier_change_denominator: 0,
+                        ..valid_config.storage_pricing.clone()
+                    },
+                    ..valid_config.clone()
+                },
+            },
+            TestCase {
+                name: "max multiplier cannot be below the base price",
+                config: RecallConfig {
+                    storage_pricing: StoragePricing {
+                        max_multiplier_bps: 9_999,
+                        ..valid_config.storage_pricing.clone()
+                    },
+                    ..valid_config.clone()
+                },
+            },
+            TestCase {
+                name: "ttl bands must be sorted",
+                config: RecallConfig {
+                    storage_pricing: StoragePricing {
+                        ttl_bands: vec![(2 * 60 * 60, 20_000), (60 * 60, 15_000)],
+                        ..valid_config.storage_pricing.clone()
+                    },
+                    ..valid_config.clone()
+                },
+            },
+            TestCase {
+                name: "volume discount cannot exceed 100%",
+                config: RecallConfig {
+                    storage_pricing: StoragePricing {
+                        volume_discounts: vec![(1024, 10_001)],
+                        ..valid_config.storage_pricing.clone()
+                    },
+                    ..valid_config.clone()
+                },
+            },
         ];
 
         let rt = construct_and_verify(
@@ -588,22 +1223,23 @@ mod tests {
             3600,
             3600,
         );
-
-        let id_addr = Address::new_id(110);
-        let eth_addr = EthAddress(hex_literal::hex!(
-            "CAFEB0BA00000000000000000000000000000000"
-        ));
-        let f4_eth_addr = Address::new_delegated(10, &eth_addr.0).unwrap();
-        rt.set_delegated_address(id_addr.id().unwrap(), f4_eth_addr);
-
+        let (id_addr, _) = new_eth_account(
+            &rt,
+            110,
+            hex_literal::hex!("CAFEB0BA00000000000000000000000000000000"),
+        );
         rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, id_addr);
 
         // Now test all invalid configurations
         for test_case in test_cases {
             rt.expect_validate_caller_any();
+            let params = ScheduledConfig {
+                config: test_case.config,
+                activation_epoch: MIN_CONFIG_CHANGE_DELAY,
+            };
             let result = rt.call::<Actor>(
-                Method::SetConfig as u64,
-                IpldBlock::serialize_cbor(&test_case.config).unwrap(),
+                Method::ScheduleConfig as u64,
+                IpldBlock::serialize_cbor(&params).unwrap(),
             );
             rt.verify();
             assert!(
@@ -612,6 +1248,39 @@ mod tests {
                 test_case.name
             );
         }
+
+        // Capacity cannot drop below what is already stored
+        rt.expect_validate_caller_any();
+        expect_get_stats(&rt, valid_config.blob_capacity + 1);
+        let params = ScheduledConfig {
+            config: valid_config.clone(),
+            activation_epoch: MIN_CONFIG_CHANGE_DELAY,
+        };
+        let result = rt.call::<Actor>(
+            Method::ScheduleConfig as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        assert_eq!(
+            result.unwrap_err().exit_code(),
+            ExitCode::USR_ILLEGAL_ARGUMENT
+        );
+
+        // Changes cannot activate before the minimum delay
+        rt.expect_validate_caller_any();
+        let params = ScheduledConfig {
+            config: valid_config,
+            activation_epoch: MIN_CONFIG_CHANGE_DELAY - 1,
+        };
+        let result = rt.call::<Actor>(
+            Method::ScheduleConfig as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        assert_eq!(
+            result.unwrap_err().exit_code(),
+            ExitCode::USR_ILLEGAL_ARGUMENT
+        );
     }
 
     #[test]
@@ -641,5 +1310,6 @@ mod tests {
         assert_eq!(recall_config.blob_credit_debit_interval, 3600);
         assert_eq!(recall_config.blob_min_ttl, 3600);
         assert_eq!(recall_config.blob_default_ttl, 3600);
+        assert_eq!(recall_config.credit_sell_discount_bps, None);
     }
 }
@@ -0,0 +1,301 @@
+// Copyright 2025 Recall Contributors
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Hashing and proof verification for the timehub Merkle mountain range (MMR).
+//!
+//! This crate has no dependency on the FVM, so that timestamp claims can be checked against a
+//! published timehub root outside the chain, e.g., by auditors or in light clients.
+//! Disable the default `std` feature to use it in `no_std` environments.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use cid::multihash::Multihash;
+use cid::Cid;
+
+mod proof;
+
+pub use proof::*;
+
+/// Multicodec code of DAG-CBOR, used for all timehub nodes.
+pub const DAG_CBOR: u64 = 0x71;
+/// Multihash code of Blake2b-256, used for all timehub nodes.
+pub const BLAKE2B_256: u64 = 0xb220;
+
+/// CBOR major types used by the timehub encodings.
+const MAJOR_UINT: u8 = 0;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_TAG: u8 = 6;
+/// CBOR tag for CIDs in DAG-CBOR.
+const TAG_CID: u64 = 42;
+
+/// An error verifying a timehub proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofError {
+    /// The leaf index is not covered by the leaf count.
+    IndexOutOfRange { index: u64, count: u64 },
+    /// The number of peaks doesn't match the leaf count.
+    PeakCountMismatch { expected: usize, actual: usize },
+    /// A sibling path has the wrong length for its position in the MMR.
+    PathLengthMismatch { expected: usize, actual: usize },
+    /// A sibling path doesn't lead to the expected peak.
+    PeakMismatch { index: usize },
+    /// The bagged peaks don't match the root.
+    RootMismatch,
+    /// The old leaf count is larger than the new leaf count.
+    InvalidRange { old_count: u64, new_count: u64 },
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfRange { index, count } => {
+                write!(f, "leaf index {} out of range ({} leaves)", index, count)
+            }
+            Self::PeakCountMismatch { expected, actual } => {
+                write!(f, "expected {} peaks, got {}", expected, actual)
+            }
+            Self::PathLengthMismatch { expected, actual } => {
+                write!(f, "expected {} siblings, got {}", expected, actual)
+            }
+            Self::PeakMismatch { index } => write!(f, "path does not lead to peak {}", index),
+            Self::RootMismatch => write!(f, "peaks do not match root"),
+            Self::InvalidRange {
+                old_count,
+                new_count,
+            } => write!(
+                f,
+                "old leaf count {} exceeds new leaf count {}",
+                old_count, new_count
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProofError {}
+
+/// Returns the DAG-CBOR CID of a block, as stored by the timehub actor.
+fn block_cid(data: &[u8]) -> Cid {
+    let digest = blake2b_simd::Params::new().hash_length(32).hash(data);
+    let mh = Multihash::wrap(BLAKE2B_256, digest.as_bytes())
+        .expect("a 32-byte digest always fits a multihash");
+    Cid::new_v1(DAG_CBOR, mh)
+}
+
+/// Writes a CBOR item header.
+fn write_header(buf: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    if value < 24 {
+        buf.push(major | value as u8);
+    } else if value <= u8::MAX as u64 {
+        buf.push(major | 24);
+        buf.push(value as u8);
+    } else if value <= u16::MAX as u64 {
+        buf.push(major | 25);
+        buf.extend_from_slice(&(value as u16).to_be_bytes());
+    } else if value <= u32::MAX as u64 {
+        buf.push(major | 26);
+        buf.extend_from_slice(&(value as u32).to_be_bytes());
+    } else {
+        buf.push(major | 27);
+        buf.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Writes a CID as a DAG-CBOR link.
+fn write_cid(buf: &mut Vec<u8>, cid: &Cid) {
+    let bytes = cid.to_bytes();
+    write_header(buf, MAJOR_TAG, TAG_CID);
+    // Links are prefixed with the identity multibase
+    write_header(buf, MAJOR_BYTES, bytes.len() as u64 + 1);
+    buf.push(0);
+    buf.extend_from_slice(&bytes);
+}
+
+/// Computes the hash of a pair of nodes.
+/// The hash is the CID of a block containing the DAG-CBOR array of the two CIDs.
+pub fn hash_pair(left: &Cid, right: &Cid) -> Cid {
+    let mut buf = Vec::with_capacity(100);
+    write_header(&mut buf, MAJOR_ARRAY, 2);
+    write_cid(&mut buf, left);
+    write_cid(&mut buf, right);
+    block_cid(&buf)
+}
+
+/// Computes the hash of a leaf witnessing `cid_bytes` at `timestamp`.
+///
+/// The leaf block is the DAG-CBOR tuple of the timestamp and the CID bytes, where the bytes are
+/// encoded as an array of integers.
+pub fn leaf_hash(timestamp: u64, cid_bytes: &[u8]) -> Cid {
+    let mut buf = Vec::with_capacity(16 + 2 * cid_bytes.len());
+    write_header(&mut buf, MAJOR_ARRAY, 2);
+    write_header(&mut buf, MAJOR_UINT, timestamp);
+    write_header(&mut buf, MAJOR_ARRAY, cid_bytes.len() as u64);
+    for b in cid_bytes {
+        write_header(&mut buf, MAJOR_UINT, *b as u64);
+    }
+    block_cid(&buf)
+}
+
+/// Combines the peaks, ordered from the highest to the lowest, into the root commitment.
+///
+/// The peaks are folded from the right, so the root is `H(p0, H(p1, ... H(pn-1, pn)))`.
+/// A single peak is the root itself, and an empty MMR has the default CID as root.
+pub fn bag_peaks(peaks: &[Cid]) -> Cid {
+    let Some((last, rest)) = peaks.split_last() else {
+        return Cid::default();
+    };
+    rest.iter()
+        .rev()
+        .fold(*last, |root, peak| hash_pair(peak, &root))
+}
+
+/// The position of a peak, i.e., of a perfect subtree, in an MMR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeakPosition {
+    /// Index of the peak, counting from the highest peak.
+    pub index: usize,
+    /// Index of the first leaf under the peak.
+    pub start: u64,
+    /// Height of the peak, where a leaf has height zero.
+    pub height: u32,
+}
+
+/// Returns the positions of all peaks in an MMR with `leaf_count` leaves, from the highest to
+/// the lowest.
+pub fn peak_positions(leaf_count: u64) -> impl Iterator<Item = PeakPosition> {
+    // Each one-bit of the leaf count is a peak
+    (0..u64::BITS)
+        .rev()
+        .filter(move |height| leaf_count & (1 << height) != 0)
+        .enumerate()
+        .map(move |(index, height)| PeakPosition {
+            index,
+            // The leaves of all higher peaks come first
+            start: leaf_count & u64::MAX.checked_shl(height + 1).unwrap_or(0),
+            height,
+        })
+}
+
+/// Returns the position of the peak containing `leaf_index` in an MMR with `leaf_count` leaves.
+pub fn peak_position(leaf_index: u64, leaf_count: u64) -> Option<PeakPosition> {
+    if leaf_index >= leaf_count {
+        return None;
+    }
+    peak_positions(leaf_count).find(|peak| leaf_index < peak.start + (1 << peak.height))
+}
+
+/// Hashes a node up its sibling path.
+///
+/// `offset` is the position of the node among the nodes of the same height under the peak.
+/// Siblings are ordered from the node up to the peak.
+fn fold_path(node: Cid, mut offset: u64, siblings: &[Cid]) -> Cid {
+    siblings.iter().fold(node, |node, sibling| {
+        let parent = if offset & 1 == 1 {
+            hash_pair(sibling, &node)
+        } else {
+            hash_pair(&node, sibling)
+        };
+        offset >>= 1;
+        parent
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use core::str::FromStr;
+
+    #[test]
+    fn test_leaf_hash() {
+        let cid = Cid::from_str("bafk2bzacecmnyfiwb52tkbwmm2dsd7ysi3nvuxl3lmspy7pl26wxj4zj7w4wi")
+            .unwrap();
+        // A single leaf is the root of the timehub
+        assert_eq!(
+            leaf_hash(1738787063, &cid.to_bytes()),
+            Cid::from_str("bafy2bzacebva5uaq4ayn6ax7zzywcqapf3w4q3oamez6sukidiqiz3m4c6osu")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hash_pair() {
+        let cid0 = Cid::from_str("bafk2bzacecmnyfiwb52tkbwmm2dsd7ysi3nvuxl3lmspy7pl26wxj4zj7w4wi")
+            .unwrap();
+        let cid1 =
+            Cid::from_str("baeabeidtz333ke5c4ultzeg6jkyzgdmvduytt2so3ahozm4zqstiuwq33e").unwrap();
+        let leaf0 = leaf_hash(1738787063, &cid0.to_bytes());
+        let leaf1 = leaf_hash(1738787064, &cid1.to_bytes());
+        assert_eq!(
+            hash_pair(&leaf0, &leaf1),
+            Cid::from_str("bafy2bzaceb6nrirwdm2ebk5ygl4nhwqjaegpbhavjg2obkshcgoogy4kbovds")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_bag_peaks() {
+        assert_eq!(bag_peaks(&[]), Cid::default());
+        let a = leaf_hash(1, &[1]);
+        let b = leaf_hash(2, &[2]);
+        let c = leaf_hash(3, &[3]);
+        assert_eq!(bag_peaks(&[a]), a);
+        assert_eq!(bag_peaks(&[a, b]), hash_pair(&a, &b));
+        assert_eq!(bag_peaks(&[a, b, c]), hash_pair(&a, &hash_pair(&b, &c)));
+    }
+
+    #[test]
+    fn test_peak_position() {
+        // 11 leaves: peaks of 8, 2 and 1 leaves
+        assert_eq!(
+            peak_position(0, 11),
+            Some(PeakPosition {
+                index: 0,
+                start: 0,
+                height: 3
+            })
+        );
+        assert_eq!(
+            peak_position(7, 11),
+            Some(PeakPosition {
+                index: 0,
+                start: 0,
+                height: 3
+            })
+        );
+        assert_eq!(
+            peak_position(9, 11),
+            Some(PeakPosition {
+                index: 1,
+                start: 8,
+                height: 1
+            })
+        );
+        assert_eq!(
+            peak_position(10, 11),
+            Some(PeakPosition {
+                index: 2,
+                start: 10,
+                height: 0
+            })
+        );
+        assert_eq!(peak_position(11, 11), None);
+        assert_eq!(peak_position(0, 0), None);
+    }
+
+    #[test]
+    fn test_peak_positions() {
+        assert_eq!(peak_positions(0).count(), 0);
+        let peaks: Vec<(u64, u32)> = peak_positions(11).map(|p| (p.start, p.height)).collect();
+        assert_eq!(peaks, vec![(0, 3), (8, 1), (10, 0)]);
+        let peak = peak_positions(u64::MAX).next().unwrap();
+        assert_eq!((peak.start, peak.height), (0, 63));
+    }
+}
@@ -0,0 +1,311 @@
+// Copyright 2025 Recall Contributors
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use alloc::vec::Vec;
+
+use cid::Cid;
+
+use crate::{bag_peaks, fold_path, peak_position, peak_positions, ProofError};
+
+/// A proof that a leaf sits at a given index of a timehub MMR.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_tuple::Serialize_tuple, serde_tuple::Deserialize_tuple)
+)]
+pub struct InclusionProof {
+    /// Index of the leaf.
+    pub leaf_index: u64,
+    /// Number of leaves in the MMR the proof was made for.
+    pub leaf_count: u64,
+    /// Siblings on the path from the leaf up to its peak.
+    pub siblings: Vec<Cid>,
+    /// All peaks of the MMR, ordered from the highest to the lowest.
+    /// Bagging the peaks yields the root.
+    pub peaks: Vec<Cid>,
+}
+
+/// Verifies that `leaf` is included in the MMR with the given root.
+///
+/// The leaf hash of a timestamp claim can be computed with [`crate::leaf_hash`].
+pub fn verify_inclusion(root: &Cid, leaf: &Cid, proof: &InclusionProof) -> Result<(), ProofError> {
+    let position =
+        peak_position(proof.leaf_index, proof.leaf_count).ok_or(ProofError::IndexOutOfRange {
+            index: proof.leaf_index,
+            count: proof.leaf_count,
+        })?;
+    check_peaks(&proof.peaks, proof.leaf_count)?;
+    check_path(&proof.siblings, position.height)?;
+
+    let peak = fold_path(*leaf, proof.leaf_index - position.start, &proof.siblings);
+    if peak != proof.peaks[position.index] {
+        return Err(ProofError::PeakMismatch {
+            index: position.index,
+        });
+    }
+    if bag_peaks(&proof.peaks) != *root {
+        return Err(ProofError::RootMismatch);
+    }
+    Ok(())
+}
+
+/// A proof that a timehub MMR with `new_count` leaves is an append-only extension of the MMR
+/// with `old_count` leaves.
+///
+/// Every peak of the old MMR is a node of the new MMR, so the proof consists of the sibling
+/// paths from each old peak up to the new peak containing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_tuple::Serialize_tuple, serde_tuple::Deserialize_tuple)
+)]
+pub struct ConsistencyProof {
+    /// Number of leaves in the old MMR.
+    pub old_count: u64,
+    /// Number of leaves in the new MMR.
+    pub new_count: u64,
+    /// Peaks of the old MMR, ordered from the highest to the lowest.
+    pub old_peaks: Vec<Cid>,
+    /// For each old peak, the siblings on the path up to the new peak containing it.
+    pub paths: Vec<Vec<Cid>>,
+    /// Peaks of the new MMR, ordered from the highest to the lowest.
+    pub new_peaks: Vec<Cid>,
+}
+
+/// Verifies that the MMR with root `new_root` only appended leaves to the MMR with root
+/// `old_root`.
+pub fn verify_consistency(
+    old_root: &Cid,
+    new_root: &Cid,
+    proof: &ConsistencyProof,
+) -> Result<(), ProofError> {
+    if proof.old_count > proof.new_count {
+        return Err(ProofError::InvalidRange {
+            old_count: proof.old_count,
+            new_count: proof.new_count,
+        });
+    }
+    check_peaks(&proof.old_peaks, proof.old_count)?;
+    check_peaks(&proof.new_peaks, proof.new_count)?;
+    if proof.paths.len() != proof.old_peaks.len() {
+        return Err(ProofError::PeakCountMismatch {
+            expected: proof.old_peaks.len(),
+            actual: proof.paths.len(),
+        });
+    }
+
+    for ((old, old_peak), path) in peak_positions(proof.old_count)
+        .zip(&proof.old_peaks)
+        .zip(&proof.paths)
+    {
+        // The old peak's first leaf is also in the new MMR
+        let new = peak_position(old.start, proof.new_count).ok_or(ProofError::IndexOutOfRange {
+            index: old.start,
+            count: proof.new_count,
+        })?;
+        check_path(path, new.height - old.height)?;
+        let peak = fold_path(*old_peak, (old.start - new.start) >> old.height, path);
+        if peak != proof.new_peaks[new.index] {
+            return Err(ProofError::PeakMismatch { index: new.index });
+        }
+    }
+
+    if bag_peaks(&proof.old_peaks) != *old_root || bag_peaks(&proof.new_peaks) != *new_root {
+        return Err(ProofError::RootMismatch);
+    }
+    Ok(())
+}
+
+/// Checks that there is one peak for every one-bit of the leaf count.
+fn check_peaks(peaks: &[Cid], leaf_count: u64) -> Result<(), ProofError> {
+    let expected = leaf_count.count_ones() as usize;
+    if peaks.len() != expected {
+        return Err(ProofError::PeakCountMismatch {
+            expected,
+            actual: peaks.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Checks that a sibling path covers all levels it has to climb.
+fn check_path(siblings: &[Cid], levels: u32) -> Result<(), ProofError> {
+    let expected = levels as usize;
+    if siblings.len() != expected {
+        return Err(ProofError::PathLengthMismatch {
+            expected,
+            actual: siblings.len(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hash_pair, leaf_hash};
+    use alloc::vec;
+
+    /// Builds the leaves and peaks of an MMR with `count` leaves.
+    fn build(count: u64) -> (Vec<Cid>, Vec<Cid>) {
+        let leaves: Vec<Cid> = (0..count).map(|i| leaf_hash(i, &[i as u8])).collect();
+        let mut peaks: Vec<(Cid, u32)> = Vec::new();
+        for leaf in &leaves {
+            let mut node = (*leaf, 0);
+            while let Some((top, height)) = peaks.last().copied() {
+                if height != node.1 {
+                    break;
+                }
+                peaks.pop();
+                node = (hash_pair(&top, &node.0), height + 1);
+            }
+            peaks.push(node);
+        }
+        (leaves, peaks.into_iter().map(|(cid, _)| cid).collect())
+    }
+
+    #[test]
+    fn test_verify_inclusion() {
+        // 3 leaves: a peak over leaves 0 and 1, and a peak for leaf 2
+        let (leaves, peaks) = build(3);
+        let root = bag_peaks(&peaks);
+        let proof = InclusionProof {
+            leaf_index: 1,
+            leaf_count: 3,
+            siblings: vec![leaves[0]],
+            peaks: peaks.clone(),
+        };
+        assert_eq!(verify_inclusion(&root, &leaves[1], &proof), Ok(()));
+
+        let proof = InclusionProof {
+            leaf_index: 2,
+            leaf_count: 3,
+            siblings: vec![],
+            peaks,
+        };
+        assert_eq!(verify_inclusion(&root, &leaves[2], &proof), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_consistency() {
+        // 3 leaves are extended to 6: the old peak over leaves 0 and 1 is under the new peak
+        // over leaves 0 to 3, and the old peak for leaf 2 is under the same new peak.
+        let (_, old_peaks) = build(3);
+        let (leaves, new_peaks) = build(6);
+        let proof = ConsistencyProof {
+            old_count: 3,
+            new_count: 6,
+            old_peaks: old_peaks.clone(),
+            paths: vec![
+                vec![hash_pair(&leaves[2], &leaves[3])],
+                vec![leaves[3], old_peaks[0]],
+            ],
+            new_peaks: new_peaks.clone(),
+        };
+        let old_root = bag_peaks(&old_peaks);
+        let new_root = bag_peaks(&new_peaks);
+        assert_eq!(verify_consistency(&old_root, &new_root, &proof), Ok(()));
+
+        // A rewritten history is detected
+        let (_, other_peaks) = build(4);
+        assert_eq!(
+            verify_consistency(&bag_peaks(&other_peaks[..1]), &new_root, &proof),
+            Err(ProofError::RootMismatch)
+        );
+        let mut invalid = proof.clone();
+        invalid.old_peaks[1] = leaves[1];
+        assert_eq!(
+            verify_consistency(&old_root, &new_root, &invalid),
+            Err(ProofError::PeakMismatch { index: 0 })
+        );
+
+        // The same MMR is consistent with itself
+        let proof = ConsistencyProof {
+            old_count: 6,
+            new_count: 6,
+            old_peaks: new_peaks.clone(),
+            paths: vec![vec![], vec![]],
+            new_peaks: new_peaks.clone(),
+        };
+        assert_eq!(verify_consistency(&new_root, &new_root, &proof), Ok(()));
+
+        // Anything is consistent with an empty MMR
+        let proof = ConsistencyProof {
+            old_count: 0,
+            new_count: 6,
+            old_peaks: vec![],
+            paths: vec![],
+            new_peaks,
+        };
+        assert_eq!(
+            verify_consistency(&Cid::default(), &new_root, &proof),
+            Ok(())
+        );
+
+        // Logs can't shrink
+        let mut invalid = proof;
+        invalid.old_count = 7;
+        assert_eq!(
+            verify_consistency(&Cid::default(), &new_root, &invalid),
+            Err(ProofError::InvalidRange {
+                old_count: 7,
+                new_count: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_inclusion_rejects_invalid_proofs() {
+        let (leaves, peaks) = build(3);
+        let root = bag_peaks(&peaks);
+        let proof = InclusionProof {
+            leaf_index: 1,
+            leaf_count: 3,
+            siblings: vec![leaves[0]],
+            peaks: peaks.clone(),
+        };
+
+        // Wrong leaf
+        assert_eq!(
+            verify_inclusion(&root, &leaves[0], &proof),
+            Err(ProofError::PeakMismatch { index: 0 })
+        );
+        // Wrong root
+        assert_eq!(
+            verify_inclusion(&leaves[0], &leaves[1], &proof),
+            Err(ProofError::RootMismatch)
+        );
+        // Wrong index
+        let mut invalid = proof.clone();
+        invalid.leaf_index = 0;
+        assert_eq!(
+            verify_inclusion(&root, &leaves[1], &invalid),
+            Err(ProofError::PeakMismatch { index: 0 })
+        );
+        invalid.leaf_index = 3;
+        assert_eq!(
+            verify_inclusion(&root, &leaves[1], &invalid),
+            Err(ProofError::IndexOutOfRange { index: 3, count: 3 })
+        );
+        // Missing sibling
+        let mut invalid = proof.clone();
+        invalid.siblings.clear();
+        assert_eq!(
+            verify_inclusion(&root, &leaves[1], &invalid),
+            Err(ProofError::PathLengthMismatch {
+                expected: 1,
+                actual: 0
+            })
+        );
+        // Missing peak
+        let mut invalid = proof;
+        invalid.peaks.pop();
+        assert_eq!(
+            verify_inclusion(&root, &leaves[1], &invalid),
+            Err(ProofError::PeakCountMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+}
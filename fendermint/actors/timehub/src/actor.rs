@@ -14,10 +14,15 @@ use fil_actors_runtime::{
     runtime::{ActorCode, Runtime},
     ActorError,
 };
+use fvm_shared::address::Address;
 use recall_sol_facade::timehub::event_pushed;
 use tracing::debug;
 
-use crate::{Leaf, Method, PushParams, PushReturn, State, TIMEHUB_ACTOR_NAME};
+use crate::{
+    ConsistencyProof, GetConsistencyProofParams, InclusionProof, Leaf, Method, PushBatchParams,
+    PushBatchReturn, PushParams, PushReturn, State, MAX_PAYLOAD_SIZE, MAX_PUSH_BATCH_SIZE,
+    TIMEHUB_ACTOR_NAME,
+};
 
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(TimehubActor);
@@ -31,21 +36,7 @@ type RawLeaf = (u64, Vec<u8>);
 impl TimehubActor {
     fn push(rt: &impl Runtime, params: PushParams) -> Result<PushReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
-
-        // Check access control.
-        // Either the caller needs to be the Timehub owner, or the owner needs to have given a
-        // credit approval to the caller.
-        let state = rt.state::<State>()?;
-        let owner = state.owner;
-        let from = to_id_address(rt, params.from, false)?;
-        require_addr_is_origin_or_caller(rt, from)?;
-
-        let actor_address = state.address.get()?;
-        if !has_credit_approval(rt, owner, from)? {
-            return Err(actor_error!(
-                forbidden;
-                format!("Unauthorized: missing credit approval from Timehub owner {} to {} for Timehub {}", owner, from, actor_address)));
-        }
+        Self::require_push_access(rt, params.from)?;
 
         // Decode the raw bytes as a Cid and report any errors.
         // However, we pass opaque bytes to the store as it tries to validate and resolve any CID
@@ -64,6 +55,72 @@ impl TimehubActor {
         Ok(ret)
     }
 
+    fn push_batch(
+        rt: &impl Runtime,
+        params: PushBatchParams,
+    ) -> Result<PushBatchReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        Self::require_push_access(rt, params.from)?;
+
+        if params.leaves.is_empty() {
+            return Err(actor_error!(illegal_argument; "batch must contain at least one CID"));
+        }
+        if params.leaves.len() > MAX_PUSH_BATCH_SIZE {
+            return Err(actor_error!(
+                illegal_argument;
+                "batch of {} CIDs exceeds the maximum of {}", params.leaves.len(), MAX_PUSH_BATCH_SIZE
+            ));
+        }
+
+        // All leaves in a batch share the same timestamp
+        let timestamp = rt.tipset_timestamp();
+        let mut cids = Vec::with_capacity(params.leaves.len());
+        let mut data = Vec::with_capacity(params.leaves.len());
+        for leaf in params.leaves {
+            let cid = Cid::try_from(leaf.cid_bytes.as_slice()).map_err(|_err| {
+                actor_error!(illegal_argument;
+                    "data must be valid CID bytes")
+            })?;
+            if let Some(payload) = &leaf.payload {
+                if payload.len() > MAX_PAYLOAD_SIZE {
+                    return Err(actor_error!(
+                        illegal_argument;
+                        "payload of {} bytes exceeds the maximum of {}", payload.len(), MAX_PAYLOAD_SIZE
+                    ));
+                }
+            }
+            cids.push(cid);
+            let raw: RawLeaf = (timestamp, leaf.cid_bytes);
+            data.push((raw, leaf.payload));
+        }
+
+        let ret = rt.transaction(|st: &mut State, rt| st.push_batch(rt.store(), data))?;
+
+        for (index, cid) in (ret.first_index..).zip(cids) {
+            emit_evm_event(rt, event_pushed(index, timestamp, cid.to_bytes()))?;
+        }
+
+        Ok(ret)
+    }
+
+    /// Checks that `from` may push to the timehub.
+    /// Either the caller needs to be the Timehub owner, or the owner needs to have given a
+    /// credit approval to the caller.
+    fn require_push_access(rt: &impl Runtime, from: Address) -> Result<(), ActorError> {
+        let state = rt.state::<State>()?;
+        let owner = state.owner;
+        let from = to_id_address(rt, from, false)?;
+        require_addr_is_origin_or_caller(rt, from)?;
+
+        let actor_address = state.address.get()?;
+        if !has_credit_approval(rt, owner, from)? {
+            return Err(actor_error!(
+                forbidden;
+                format!("Unauthorized: missing credit approval from Timehub owner {} to {} for Timehub {}", owner, from, actor_address)));
+        }
+        Ok(())
+    }
+
     fn get_leaf_at(rt: &impl Runtime, index: u64) -> Result<Option<Leaf>, ActorError> {
         debug!(index, "get_leaf_at");
         rt.validate_immediate_caller_accept_any()?;
@@ -81,6 +138,14 @@ impl TimehubActor {
         .transpose()
     }
 
+    /// Returns the payload pushed alongside the leaf at `index`, if any.
+    /// Payloads are served separately from leaves because they are not covered by proofs.
+    fn get_payload(rt: &impl Runtime, index: u64) -> Result<Option<Vec<u8>>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        st.get_payload(rt.store(), index)
+    }
+
     fn get_root(rt: &impl Runtime) -> Result<Cid, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
         let st: State = rt.state()?;
@@ -98,6 +163,24 @@ impl TimehubActor {
         let st: State = rt.state()?;
         Ok(st.leaf_count)
     }
+
+    fn get_inclusion_proof(
+        rt: &impl Runtime,
+        index: u64,
+    ) -> Result<Option<InclusionProof>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        st.get_inclusion_proof(rt.store(), index)
+    }
+
+    fn get_consistency_proof(
+        rt: &impl Runtime,
+        params: GetConsistencyProofParams,
+    ) -> Result<ConsistencyProof, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        st.get_consistency_proof(rt.store(), params.old_count, params.new_count)
+    }
 }
 
 impl MachineActor for TimehubActor {
@@ -116,11 +199,18 @@ impl ActorCode for TimehubActor {
         Init => init,
         GetAddress => get_address,
         GetMetadata => get_metadata,
+        TransferOwnership => transfer_ownership,
+        AcceptOwnership => accept_ownership,
+        SetMetadata => set_metadata,
         Push => push,
+        PushBatch => push_batch,
         Get => get_leaf_at,
         Root => get_root,
         Peaks => get_peaks,
         Count => get_count,
+        GetInclusionProof => get_inclusion_proof,
+        GetConsistencyProof => get_consistency_proof,
+        GetPayload => get_payload,
         _ => fallback,
     }
 }
@@ -128,6 +218,7 @@ impl ActorCode for TimehubActor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::PushLeaf;
 
     use std::collections::HashMap;
     use std::str::FromStr;
@@ -137,6 +228,7 @@ mod tests {
         BLOBS_ACTOR_ADDR,
     };
     use fendermint_actor_machine::{events::to_actor_event, ConstructorParams, InitParams, Kind};
+    use fendermint_actor_timehub_shared::{leaf_hash, verify_inclusion};
     use fil_actors_evm_shared::address::EthAddress;
     use fil_actors_runtime::{
         runtime::MessageInfo,
@@ -239,6 +331,30 @@ mod tests {
         .unwrap()
     }
 
+    fn get_payload(rt: &MockRuntime, index: u64) -> Option<Vec<u8>> {
+        rt.expect_validate_caller_any();
+        rt.call::<TimehubActor>(
+            Method::GetPayload as u64,
+            IpldBlock::serialize_cbor(&index).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize::<Option<Vec<u8>>>()
+        .unwrap()
+    }
+
+    fn get_inclusion_proof(rt: &MockRuntime, index: u64) -> Option<InclusionProof> {
+        rt.expect_validate_caller_any();
+        rt.call::<TimehubActor>(
+            Method::GetInclusionProof as u64,
+            IpldBlock::serialize_cbor(&index).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize::<Option<InclusionProof>>()
+        .unwrap()
+    }
+
     fn push_cid(rt: &mut MockRuntime, cid: Cid, timestamp: u64, expected_index: u64) -> PushReturn {
         rt.expect_validate_caller_any();
         rt.tipset_timestamp = timestamp;
@@ -335,6 +451,98 @@ mod tests {
         let count = get_count(&rt);
         assert_eq!(count, 2);
 
+        // Prove both timestamps against the root
+        for (index, cid, timestamp) in [(0, cid0, t0), (1, cid1, t1)] {
+            let proof = get_inclusion_proof(&rt, index).unwrap();
+            let leaf = leaf_hash(timestamp, &cid.to_bytes());
+            assert_eq!(verify_inclusion(&root, &leaf, &proof), Ok(()));
+        }
+        assert!(get_inclusion_proof(&rt, 2).is_none());
+
+        rt.verify();
+    }
+
+    #[test]
+    pub fn test_push_batch() {
+        let owner = Address::new_id(110);
+        let actor_address = Address::new_id(111);
+
+        let mut rt = construct_runtime(actor_address, owner);
+        rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, owner);
+        rt.set_origin(owner);
+
+        let cid0 = Cid::from_str("bafk2bzacecmnyfiwb52tkbwmm2dsd7ysi3nvuxl3lmspy7pl26wxj4zj7w4wi")
+            .unwrap();
+        let cid1 =
+            Cid::from_str("baeabeidtz333ke5c4ultzeg6jkyzgdmvduytt2so3ahozm4zqstiuwq33e").unwrap();
+        let t0 = 1738787063;
+        rt.tipset_timestamp = t0;
+
+        let params = PushBatchParams {
+            leaves: vec![
+                PushLeaf {
+                    cid_bytes: cid0.to_bytes(),
+                    payload: None,
+                },
+                PushLeaf {
+                    cid_bytes: cid1.to_bytes(),
+                    payload: Some(b"meta".to_vec()),
+                },
+            ],
+            from: owner,
+        };
+        rt.expect_validate_caller_any();
+        for (index, cid) in [(0, cid0), (1, cid1)] {
+            let event = to_actor_event(event_pushed(index, t0, cid.to_bytes()).unwrap()).unwrap();
+            rt.expect_emitted_event(event);
+        }
+        let ret = rt
+            .call::<TimehubActor>(
+                Method::PushBatch as u64,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize::<PushBatchReturn>()
+            .unwrap();
+        rt.verify();
+        assert_eq!(ret.first_index, 0);
+        assert_eq!(ret.last_index, 1);
+        assert_eq!(ret.root, get_root(&rt));
+        assert_eq!(get_count(&rt), 2);
+
+        let leaf0 = get_leaf(&rt, 0);
+        assert_eq!(leaf0.witnessed, cid0);
+        assert_eq!(leaf0.timestamp, t0);
+        assert_eq!(get_payload(&rt, 0), None);
+        let leaf1 = get_leaf(&rt, 1);
+        assert_eq!(leaf1.witnessed, cid1);
+        assert_eq!(leaf1.timestamp, t0);
+        assert_eq!(get_payload(&rt, 1), Some(b"meta".to_vec()));
+        assert_eq!(get_payload(&rt, 2), None);
+
+        // Payloads are not part of the leaf hash
+        let proof = get_inclusion_proof(&rt, 1).unwrap();
+        let leaf = leaf_hash(t0, &cid1.to_bytes());
+        assert_eq!(verify_inclusion(&ret.root, &leaf, &proof), Ok(()));
+
+        // Oversized payloads are rejected
+        let params = PushBatchParams {
+            leaves: vec![PushLeaf {
+                cid_bytes: cid0.to_bytes(),
+                payload: Some(vec![0; MAX_PAYLOAD_SIZE + 1]),
+            }],
+            from: owner,
+        };
+        rt.expect_validate_caller_any();
+        let err = rt
+            .call::<TimehubActor>(
+                Method::PushBatch as u64,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            )
+            .unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::USR_ILLEGAL_ARGUMENT);
+        assert_eq!(get_count(&rt), 2);
         rt.verify();
     }
 
@@ -4,23 +4,32 @@
 
 use std::collections::HashMap;
 
-use cid::multihash::{Code, MultihashDigest};
+use cid::multihash::Code;
 use cid::Cid;
 use fendermint_actor_machine::{
-    Kind, MachineAddress, MachineState, GET_ADDRESS_METHOD, GET_METADATA_METHOD, INIT_METHOD,
-    METHOD_CONSTRUCTOR,
+    Kind, MachineAddress, MachineState, ACCEPT_OWNERSHIP_METHOD, GET_ADDRESS_METHOD,
+    GET_METADATA_METHOD, INIT_METHOD, METHOD_CONSTRUCTOR, SET_METADATA_METHOD,
+    TRANSFER_OWNERSHIP_METHOD,
 };
 use fil_actors_runtime::ActorError;
 use fvm_ipld_amt::Amt;
 use fvm_ipld_blockstore::Blockstore;
-use fvm_ipld_encoding::{strict_bytes, to_vec, tuple::*, CborStore, DAG_CBOR};
+use fvm_ipld_encoding::{strict_bytes, tuple::*, CborStore, RawBytes};
 use fvm_shared::address::Address;
 use num_derive::FromPrimitive;
 use serde::{de::DeserializeOwned, Serialize};
 
+use fendermint_actor_timehub_shared::peak_position;
+pub use fendermint_actor_timehub_shared::{ConsistencyProof, InclusionProof};
+
 pub const TIMEHUB_ACTOR_NAME: &str = "timehub";
 const BIT_WIDTH: u32 = 3;
 
+/// Maximum number of leaves that can be pushed in a single batch.
+pub const MAX_PUSH_BATCH_SIZE: usize = 1000;
+/// Maximum size of the payload stored alongside a leaf.
+pub const MAX_PAYLOAD_SIZE: usize = 1024;
+
 fn state_error(e: fvm_ipld_amt::Error) -> ActorError {
     ActorError::illegal_state(e.to_string())
 }
@@ -36,11 +45,18 @@ pub enum Method {
     Init = INIT_METHOD,
     GetAddress = GET_ADDRESS_METHOD,
     GetMetadata = GET_METADATA_METHOD,
+    TransferOwnership = TRANSFER_OWNERSHIP_METHOD,
+    AcceptOwnership = ACCEPT_OWNERSHIP_METHOD,
+    SetMetadata = SET_METADATA_METHOD,
     Push = frc42_dispatch::method_hash!("Push"),
+    PushBatch = frc42_dispatch::method_hash!("PushBatch"),
     Get = frc42_dispatch::method_hash!("Get"),
     Root = frc42_dispatch::method_hash!("Root"),
     Peaks = frc42_dispatch::method_hash!("Peaks"),
     Count = frc42_dispatch::method_hash!("Count"),
+    GetInclusionProof = frc42_dispatch::method_hash!("GetInclusionProof"),
+    GetConsistencyProof = frc42_dispatch::method_hash!("GetConsistencyProof"),
+    GetPayload = frc42_dispatch::method_hash!("GetPayload"),
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple)]
@@ -60,6 +76,42 @@ pub struct PushReturn {
     pub index: u64,
 }
 
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct PushLeaf {
+    /// Bytes of a CID to add.
+    #[serde(with = "strict_bytes")]
+    pub cid_bytes: Vec<u8>,
+    /// Optional small payload stored alongside the leaf, readable with `GetPayload`.
+    /// The payload is not part of the leaf hash, so it is not covered by proofs.
+    pub payload: Option<Vec<u8>>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct PushBatchParams {
+    /// Leaves to add, in order.
+    pub leaves: Vec<PushLeaf>,
+    /// Account address that initiated the call.
+    pub from: Address,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct PushBatchReturn {
+    /// The new root of the timehub MMR after the batch was pushed into it.
+    pub root: Cid,
+    /// The index of the first object in the batch.
+    pub first_index: u64,
+    /// The index of the last object in the batch.
+    pub last_index: u64,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetConsistencyProofParams {
+    /// Number of leaves in the old version of the timehub.
+    pub old_count: u64,
+    /// Number of leaves in the new version of the timehub.
+    pub new_count: u64,
+}
+
 #[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct Leaf {
     /// Timestamp of the witness in seconds since the UNIX epoch
@@ -73,13 +125,8 @@ pub struct Leaf {
 /// We do not include the index of the element(s) because incoming data should already be "nonced".
 fn hash_pair(left: Option<&Cid>, right: Option<&Cid>) -> anyhow::Result<Cid, ActorError> {
     if let (Some(left), Some(right)) = (left, right) {
-        // Encode the CIDs into a binary format
-        let data = to_vec(&[left, right])?;
-        // Compute the CID for the block
-        let mh_code = Code::Blake2b256;
-        let mh = mh_code.digest(&data);
-        let cid = Cid::new_v1(DAG_CBOR, mh);
-        Ok(cid)
+        // Proof verifiers outside the chain hash with the same function
+        Ok(fendermint_actor_timehub_shared::hash_pair(left, right))
     } else {
         Err(ActorError::illegal_argument(
             "hash_pair requires two CIDs".into(),
@@ -107,13 +154,14 @@ fn hash_and_put_pair<BS: Blockstore>(
     }
 }
 
-/// Return the new peaks of the timehub after adding `new_leaf`.
+/// Add `obj` as a new leaf to the peaks of the timehub.
+/// The peaks are not flushed, so several leaves can be added before flushing once.
 fn push<BS: Blockstore, S: DeserializeOwned + Serialize>(
     store: &BS,
     leaf_count: u64,
     peaks: &mut Amt<Cid, &BS>,
     obj: S,
-) -> anyhow::Result<Cid, ActorError> {
+) -> anyhow::Result<(), ActorError> {
     // Create new leaf
     let leaf = store
         .put_cbor(&obj, Code::Blake2b256)
@@ -138,32 +186,84 @@ fn push<BS: Blockstore, S: DeserializeOwned + Serialize>(
             .map_err(state_error)?;
         new_peaks -= 1;
     }
-    peaks.flush().map_err(state_error)
+    Ok(())
 }
 
 /// Collect the peaks and combine to compute the root commitment.
 fn bag_peaks<BS: Blockstore>(peaks: &Amt<Cid, &BS>) -> anyhow::Result<Cid, ActorError> {
-    let peaks_count = peaks.count();
-    // Handle special cases where we have no peaks or only one peak
-    if peaks_count == 0 {
-        return Ok(Cid::default());
-    }
-    // If there is only one leaf element, we simply "promote" that to the root peak
-    if peaks_count == 1 {
-        return Ok(peaks.get(0).map_err(state_error)?.unwrap().to_owned());
-    }
-    // Walk backward through the peaks, combining them pairwise
-    let mut root = hash_pair(
-        peaks.get(peaks_count - 2).map_err(state_error)?,
-        peaks.get(peaks_count - 1).map_err(state_error)?,
-    )?;
-    for i in 2..peaks_count {
-        root = hash_pair(
-            peaks.get(peaks_count - 1 - i).map_err(state_error)?,
-            Some(&root),
-        )?;
+    Ok(fendermint_actor_timehub_shared::bag_peaks(&collect_peaks(
+        peaks,
+    )?))
+}
+
+/// Collect the peaks, ordered from the highest to the lowest.
+fn collect_peaks<BS: Blockstore>(peaks: &Amt<Cid, &BS>) -> anyhow::Result<Vec<Cid>, ActorError> {
+    let mut cids = Vec::with_capacity(peaks.count() as usize);
+    peaks
+        .for_each(|_, cid| {
+            cids.push(cid.to_owned());
+            Ok(())
+        })
+        .map_err(state_error)?;
+    Ok(cids)
+}
+
+/// Returns a node `levels` below a peak, along with the siblings on the path from the node up to
+/// the peak.
+///
+/// `offset` is the position of the node among the nodes of the same height under the peak.
+/// The siblings are ordered from the node up to the peak.
+fn descend<BS: Blockstore>(
+    store: &BS,
+    peak: &Cid,
+    offset: u64,
+    levels: u32,
+) -> anyhow::Result<(Cid, Vec<Cid>), ActorError> {
+    let mut siblings = Vec::with_capacity(levels as usize);
+    let mut cid = *peak;
+    // Walk down from the peak, the bits of the offset select the child at each level
+    for level in (0..levels).rev() {
+        let pair = store
+            .get_cbor::<[Cid; 2]>(&cid)
+            .map_err(store_error)?
+            .ok_or_else(|| {
+                ActorError::illegal_state(format!("failed to get eigentree node for cid {}", cid))
+            })?;
+        let bit = ((offset >> level) & 1) as usize;
+        siblings.push(pair[1 - bit]);
+        cid = pair[bit];
     }
-    Ok(root)
+    siblings.reverse();
+    Ok((cid, siblings))
+}
+
+/// Returns the peaks an MMR had when it had `leaf_count` leaves.
+///
+/// Every peak of an earlier MMR is a node of the current MMR, found under the current peak
+/// containing its first leaf.
+fn peaks_at<BS: Blockstore>(
+    store: &BS,
+    current_peaks: &[Cid],
+    current_count: u64,
+    leaf_count: u64,
+) -> anyhow::Result<Vec<Cid>, ActorError> {
+    fendermint_actor_timehub_shared::peak_positions(leaf_count)
+        .map(|old| {
+            let Some(current) = peak_position(old.start, current_count) else {
+                return Err(ActorError::illegal_argument(format!(
+                    "leaf count {} exceeds current leaf count {}",
+                    leaf_count, current_count
+                )));
+            };
+            let (node, _) = descend(
+                store,
+                &current_peaks[current.index],
+                (old.start - current.start) >> old.height,
+                current.height - old.height,
+            )?;
+            Ok(node)
+        })
+        .collect()
 }
 
 /// Given the size of the MMR and an index into the MMR, returns a tuple where the first element
@@ -247,6 +347,9 @@ fn get_at<BS: Blockstore, S: DeserializeOwned + Serialize>(
 }
 
 /// The state represents an MMR with peaks stored in an AMT
+///
+/// Fields after `metadata` default when missing, so state stored before they were added
+/// still decodes.
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct State {
     /// The machine address set by the init actor.
@@ -259,6 +362,13 @@ pub struct State {
     pub leaf_count: u64,
     /// User-defined metadata.
     pub metadata: HashMap<String, String>,
+    /// The address proposed as the next owner, until it accepts.
+    #[serde(default)]
+    pub pending_owner: Option<Address>,
+    /// Root of the AMT that is storing leaf payloads by leaf index.
+    /// Created with the first payload.
+    #[serde(default)]
+    pub payloads: Option<Cid>,
 }
 
 impl MachineState for State {
@@ -279,8 +389,10 @@ impl MachineState for State {
         Ok(Self {
             address: Default::default(),
             owner,
+            pending_owner: None,
             peaks,
             leaf_count: 0,
+            payloads: None,
             metadata,
         })
     }
@@ -301,9 +413,25 @@ impl MachineState for State {
         self.owner
     }
 
+    fn set_owner(&mut self, owner: Address) {
+        self.owner = owner;
+    }
+
+    fn pending_owner(&self) -> Option<Address> {
+        self.pending_owner
+    }
+
+    fn set_pending_owner(&mut self, owner: Option<Address>) {
+        self.pending_owner = owner;
+    }
+
     fn metadata(&self) -> HashMap<String, String> {
         self.metadata.clone()
     }
+
+    fn set_metadata(&mut self, metadata: HashMap<String, String>) {
+        self.metadata = metadata;
+    }
 }
 
 impl State {
@@ -321,7 +449,8 @@ impl State {
         obj: S,
     ) -> anyhow::Result<PushReturn, ActorError> {
         let mut amt = Amt::<Cid, &BS>::load(&self.peaks, store).map_err(state_error)?;
-        self.peaks = push(store, self.leaf_count, &mut amt, obj)?;
+        push(store, self.leaf_count, &mut amt, obj)?;
+        self.peaks = amt.flush().map_err(state_error)?;
         self.leaf_count += 1;
 
         let root = bag_peaks(&amt)?;
@@ -331,6 +460,57 @@ impl State {
         })
     }
 
+    /// Pushes several objects, each with an optional payload, flushing the peaks only once.
+    pub fn push_batch<BS: Blockstore, S: DeserializeOwned + Serialize>(
+        &mut self,
+        store: &BS,
+        objs: Vec<(S, Option<Vec<u8>>)>,
+    ) -> anyhow::Result<PushBatchReturn, ActorError> {
+        if objs.is_empty() {
+            return Err(ActorError::illegal_argument(
+                "batch must contain at least one object".into(),
+            ));
+        }
+        let mut amt = Amt::<Cid, &BS>::load(&self.peaks, store).map_err(state_error)?;
+        let mut payloads = match &self.payloads {
+            Some(root) => Amt::<RawBytes, &BS>::load(root, store).map_err(state_error)?,
+            None => Amt::new_with_bit_width(store, BIT_WIDTH),
+        };
+        let first_index = self.leaf_count;
+        for (obj, payload) in objs {
+            push(store, self.leaf_count, &mut amt, obj)?;
+            if let Some(payload) = payload {
+                payloads
+                    .set(self.leaf_count, RawBytes::new(payload))
+                    .map_err(state_error)?;
+            }
+            self.leaf_count += 1;
+        }
+        self.peaks = amt.flush().map_err(state_error)?;
+        self.payloads = Some(payloads.flush().map_err(state_error)?);
+
+        let root = bag_peaks(&amt)?;
+        Ok(PushBatchReturn {
+            root,
+            first_index,
+            last_index: self.leaf_count - 1,
+        })
+    }
+
+    /// Returns the payload pushed alongside the leaf at `index`, if any.
+    pub fn get_payload<BS: Blockstore>(
+        &self,
+        store: &BS,
+        index: u64,
+    ) -> anyhow::Result<Option<Vec<u8>>, ActorError> {
+        let Some(root) = &self.payloads else {
+            return Ok(None);
+        };
+        let payloads = Amt::<RawBytes, &BS>::load(root, store).map_err(state_error)?;
+        let payload = payloads.get(index).map_err(state_error)?;
+        Ok(payload.map(|p| p.to_vec()))
+    }
+
     pub fn get_root<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<Cid, ActorError> {
         let amt = Amt::<Cid, &BS>::load(&self.peaks, store).map_err(state_error)?;
         bag_peaks(&amt)
@@ -338,13 +518,79 @@ impl State {
 
     pub fn get_peaks<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<Vec<Cid>, ActorError> {
         let amt = Amt::<Cid, &BS>::load(&self.peaks, store).map_err(state_error)?;
-        let mut peaks = Vec::new();
-        amt.for_each(|_, cid| {
-            peaks.push(cid.to_owned());
-            Ok(())
+        collect_peaks(&amt)
+    }
+
+    /// Returns a proof that the leaf at `index` is included under the current root.
+    /// Returns `None` if the index doesn't point to a leaf.
+    pub fn get_inclusion_proof<BS: Blockstore>(
+        &self,
+        store: &BS,
+        index: u64,
+    ) -> anyhow::Result<Option<InclusionProof>, ActorError> {
+        let Some(position) = peak_position(index, self.leaf_count) else {
+            return Ok(None);
+        };
+        let peaks = self.get_peaks(store)?;
+        let (_, siblings) = descend(
+            store,
+            &peaks[position.index],
+            index - position.start,
+            position.height,
+        )?;
+        Ok(Some(InclusionProof {
+            leaf_index: index,
+            leaf_count: self.leaf_count,
+            siblings,
+            peaks,
+        }))
+    }
+
+    /// Returns a proof that the MMR with `new_count` leaves extends the MMR with `old_count`
+    /// leaves, where both are earlier or current versions of this MMR.
+    pub fn get_consistency_proof<BS: Blockstore>(
+        &self,
+        store: &BS,
+        old_count: u64,
+        new_count: u64,
+    ) -> anyhow::Result<ConsistencyProof, ActorError> {
+        if old_count > new_count {
+            return Err(ActorError::illegal_argument(format!(
+                "old count {} exceeds new count {}",
+                old_count, new_count
+            )));
+        }
+        if new_count > self.leaf_count {
+            return Err(ActorError::illegal_argument(format!(
+                "new count {} exceeds leaf count {}",
+                new_count, self.leaf_count
+            )));
+        }
+        let peaks = self.get_peaks(store)?;
+        let old_peaks = peaks_at(store, &peaks, self.leaf_count, old_count)?;
+        let new_peaks = peaks_at(store, &peaks, self.leaf_count, new_count)?;
+        // Walk from each new peak down to the old peaks under it
+        let paths = fendermint_actor_timehub_shared::peak_positions(old_count)
+            .map(|old| {
+                let new = peak_position(old.start, new_count).ok_or_else(|| {
+                    ActorError::illegal_state(format!("no peak for leaf {}", old.start))
+                })?;
+                let (_, siblings) = descend(
+                    store,
+                    &new_peaks[new.index],
+                    (old.start - new.start) >> old.height,
+                    new.height - old.height,
+                )?;
+                Ok(siblings)
+            })
+            .collect::<Result<Vec<_>, ActorError>>()?;
+        Ok(ConsistencyProof {
+            old_count,
+            new_count,
+            old_peaks,
+            paths,
+            new_peaks,
         })
-        .map_err(state_error)?;
-        Ok(peaks)
     }
 
     pub fn get_leaf_at<BS: Blockstore, S: DeserializeOwned + Serialize>(
@@ -527,4 +773,113 @@ mod tests {
         }
         assert_eq!(state.peak_count(), 5);
     }
+
+    #[test]
+    fn test_get_inclusion_proof() {
+        use fendermint_actor_timehub_shared::{leaf_hash, verify_inclusion};
+
+        let store = fvm_ipld_blockstore::MemoryBlockstore::default();
+        let mut state = State::new(&store, Address::new_id(100), HashMap::new()).unwrap();
+        assert!(state.get_inclusion_proof(&store, 0).unwrap().is_none());
+
+        for i in 0..11u64 {
+            let root = state.push(&store, (i, vec![i as u8])).unwrap().root;
+
+            // Every leaf pushed so far is provable under the new root
+            for j in 0..=i {
+                let proof = state.get_inclusion_proof(&store, j).unwrap().unwrap();
+                assert_eq!(proof.leaf_count, i + 1);
+                let leaf = leaf_hash(j, &[j as u8]);
+                assert_eq!(verify_inclusion(&root, &leaf, &proof), Ok(()));
+            }
+            assert!(state.get_inclusion_proof(&store, i + 1).unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_push_batch() {
+        let store = fvm_ipld_blockstore::MemoryBlockstore::default();
+        let mut single = State::new(&store, Address::new_id(100), HashMap::new()).unwrap();
+        let mut batched = State::new(&store, Address::new_id(100), HashMap::new()).unwrap();
+
+        let mut expected = single.get_root(&store).unwrap();
+        for i in 0..7u64 {
+            expected = single.push(&store, vec![i]).unwrap().root;
+        }
+
+        let ret = batched
+            .push_batch(
+                &store,
+                vec![(vec![0u64], None), (vec![1], Some(vec![1, 1]))],
+            )
+            .unwrap();
+        assert_eq!(ret.first_index, 0);
+        assert_eq!(ret.last_index, 1);
+        let ret = batched
+            .push_batch(
+                &store,
+                (2..7u64).map(|i| (vec![i], Some(vec![i as u8]))).collect(),
+            )
+            .unwrap();
+        assert_eq!(ret.first_index, 2);
+        assert_eq!(ret.last_index, 6);
+
+        // Batching doesn't change the MMR
+        assert_eq!(ret.root, expected);
+        assert_eq!(batched.get_root(&store).unwrap(), expected);
+        assert_eq!(batched.leaf_count(), 7);
+        for i in 0..7u64 {
+            let leaf: Vec<u64> = batched.get_leaf_at(&store, i).unwrap().unwrap();
+            assert_eq!(leaf, vec![i]);
+        }
+
+        assert_eq!(batched.get_payload(&store, 0).unwrap(), None);
+        assert_eq!(batched.get_payload(&store, 1).unwrap(), Some(vec![1, 1]));
+        assert_eq!(batched.get_payload(&store, 6).unwrap(), Some(vec![6]));
+        assert_eq!(batched.get_payload(&store, 7).unwrap(), None);
+
+        assert!(batched
+            .push_batch::<_, Vec<u64>>(&store, Vec::new())
+            .is_err());
+        assert_eq!(batched.leaf_count(), 7);
+    }
+
+    #[test]
+    fn test_get_consistency_proof() {
+        use fendermint_actor_timehub_shared::verify_consistency;
+
+        let store = fvm_ipld_blockstore::MemoryBlockstore::default();
+        let mut state = State::new(&store, Address::new_id(100), HashMap::new()).unwrap();
+        let mut roots = vec![state.get_root(&store).unwrap()];
+        for i in 0..13u64 {
+            roots.push(state.push(&store, vec![i]).unwrap().root);
+        }
+
+        // Any earlier version is consistent with any later version
+        for new_count in 0..=13 {
+            for old_count in 0..=new_count {
+                let proof = state
+                    .get_consistency_proof(&store, old_count, new_count)
+                    .unwrap();
+                assert_eq!(
+                    verify_consistency(
+                        &roots[old_count as usize],
+                        &roots[new_count as usize],
+                        &proof
+                    ),
+                    Ok(()),
+                    "old count {} new count {}",
+                    old_count,
+                    new_count
+                );
+            }
+        }
+
+        // A root of a different history is rejected
+        let proof = state.get_consistency_proof(&store, 5, 13).unwrap();
+        assert!(verify_consistency(&roots[6], &roots[13], &proof).is_err());
+
+        assert!(state.get_consistency_proof(&store, 6, 5).is_err());
+        assert!(state.get_consistency_proof(&store, 5, 14).is_err());
+    }
 }
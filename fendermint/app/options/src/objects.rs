@@ -26,4 +26,29 @@ pub enum ObjectsCommands {
         #[arg(long, short, default_value = "127.0.0.1:4919", env = "IROH_RPC_ADDR")]
         iroh_addr: String,
     },
+    /// Compare the blobs stored in the local iroh node with the blobs actor state,
+    /// and remove the tags of blobs that no longer have any subscribers.
+    Reconcile {
+        /// The URL of the Tendermint node's RPC endpoint.
+        #[arg(
+            long,
+            short,
+            default_value = "http://127.0.0.1:26657",
+            env = "TENDERMINT_RPC_URL"
+        )]
+        tendermint_url: Url,
+
+        #[arg(long, short, default_value = "127.0.0.1:4919", env = "IROH_RPC_ADDR")]
+        iroh_addr: String,
+
+        /// Only report the differences, without removing any tags.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Also remove the temporary tags of multipart uploads and repairs.
+        /// Only use this while the objects service is stopped, since it aborts uploads
+        /// and repairs in progress.
+        #[arg(long)]
+        remove_temp_tags: bool,
+    },
 }
@@ -185,6 +185,13 @@ pub struct TopDownSettings {
     pub exponential_retry_limit: usize,
     /// The parent rpc http endpoint
     pub parent_http_endpoint: Url,
+    /// Further parent rpc http endpoints, queried along with `parent_http_endpoint`
+    /// to accept only what a quorum of them agree on, with the same timeout and auth token.
+    #[serde(default)]
+    pub parent_http_endpoints: Vec<Url>,
+    /// The number of parent endpoints that have to agree on a block hash or top-down messages.
+    /// Defaults to a majority of the endpoints.
+    pub parent_quorum: Option<usize>,
     /// Timeout for calls to the parent Ethereum API.
     #[serde_as(as = "Option<DurationSeconds<u64>>")]
     pub parent_http_timeout: Option<Duration>,
@@ -351,7 +358,8 @@ impl Settings {
                     .with_list_parse_key("eth.cors.allowed_methods")
                     .with_list_parse_key("eth.cors.allowed_headers")
                     .with_list_parse_key("eth.tracing.file.domain_filter")
-                    .with_list_parse_key("eth.tracing.file.events_filter"),
+                    .with_list_parse_key("eth.tracing.file.events_filter")
+                    .with_list_parse_key("ipc.topdown.parent_http_endpoints"),
             ))
             // Set the home directory based on what was passed to the CLI,
             // so everything in the config can be relative to it.
@@ -5,7 +5,9 @@
 use crate::{MetricsSettings, SocketAddress};
 use ipc_observability::config::TracingSettings;
 use serde::Deserialize;
-use serde_with::serde_as;
+use serde_with::{serde_as, DurationSeconds};
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Object API facade settings.
 #[serde_as]
@@ -15,4 +17,76 @@ pub struct ObjectsSettings {
     pub listen: SocketAddress,
     pub tracing: TracingSettings,
     pub metrics: MetricsSettings,
+    pub multipart: MultipartSettings,
+    pub signer: SignerSettings,
+    pub s3: S3Settings,
+    pub repair: RepairSettings,
+}
+
+/// Settings for multipart (chunked) uploads.
+#[serde_as]
+#[derive(Debug, Deserialize, Clone)]
+pub struct MultipartSettings {
+    /// Maximum size of a single part, in bytes.
+    pub max_part_size: u64,
+    /// Maximum number of parts in a single upload.
+    pub max_parts: u32,
+    /// How long an upload session can stay idle before it is garbage-collected.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub session_ttl: Duration,
+    /// How often to look for abandoned upload sessions.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub gc_interval: Duration,
+}
+
+/// Settings for signing bucket transactions on behalf of API clients.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SignerSettings {
+    /// Secp256k1 private key used to sign bucket transactions.
+    ///
+    /// Bucket owners have to approve the corresponding address to use their credit.
+    /// Leave empty to disable the endpoints that write to buckets.
+    pub secret_key: Option<PathBuf>,
+    /// Gas limit for bucket transactions.
+    pub gas_limit: u64,
+    /// Bearer token required to copy and move objects through the objects API,
+    /// which sign transactions with the key above.
+    ///
+    /// Leave empty to disable copying and moving objects.
+    pub token: Option<String>,
+}
+
+/// Settings for the S3-compatible API.
+///
+/// Writes are signed with the key in [`SignerSettings`], and clients have to sign their write
+/// requests with the access key below. Without either of them the API is read-only.
+#[derive(Debug, Deserialize, Clone)]
+pub struct S3Settings {
+    /// Enable the S3-compatible API.
+    pub enabled: bool,
+    /// Address where the S3-compatible API is served.
+    pub listen: SocketAddress,
+    /// Access key ID clients sign write requests with, using AWS Signature Version 4.
+    pub access_key_id: Option<String>,
+    /// Secret access key clients sign write requests with.
+    pub secret_access_key: Option<String>,
+}
+
+/// Settings for the repair of damaged blobs from their entanglement parities.
+#[serde_as]
+#[derive(Debug, Deserialize, Clone)]
+pub struct RepairSettings {
+    /// Enable the daemon which periodically checks and repairs blobs.
+    ///
+    /// Objects can also be repaired on demand through the objects API, with the token below.
+    pub enabled: bool,
+    /// How often to scan the buckets for damaged blobs.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub interval: Duration,
+    /// Addresses of the buckets whose objects this node should hold.
+    pub buckets: Vec<String>,
+    /// Bearer token required to repair objects on demand through the objects API.
+    ///
+    /// Leave empty to disable on-demand repairs.
+    pub token: Option<String>,
 }
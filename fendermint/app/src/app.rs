@@ -38,7 +38,9 @@ use fendermint_vm_interpreter::signed::InvalidSignature;
 use fendermint_vm_interpreter::{
     CheckInterpreter, ExecInterpreter, ProposalInterpreter, QueryInterpreter,
 };
-use fendermint_vm_message::query::FvmQueryHeight;
+use fendermint_vm_message::query::{
+    CheckpointAggregate, FvmQueryHeight, CHECKPOINT_AGGREGATE_QUERY_PATH,
+};
 use fendermint_vm_snapshot::{SnapshotClient, SnapshotError};
 use fvm::engine::MultiEngine;
 use fvm_ipld_blockstore::Blockstore;
@@ -418,6 +420,20 @@ where
         }
     }
 
+    /// Look up the aggregate signature of the checkpoint at the height in the query data.
+    fn query_checkpoint_aggregate(&self, data: &[u8]) -> AbciResult<response::Query> {
+        let height: BlockHeight = match fvm_ipld_encoding::from_slice(data) {
+            Ok(height) => height,
+            Err(e) => return Ok(invalid_query(AppError::InvalidEncoding, e.to_string())),
+        };
+        let pool = &self.chain_env.checkpoint_signatures;
+        let aggregate = pool.aggregate(height).map(|aggregate| CheckpointAggregate {
+            aggregate,
+            signer_public_keys: pool.signer_public_keys(height),
+        });
+        Ok(to_checkpoint_aggregate_query(aggregate)?)
+    }
+
     /// Replaces the current validators cache with a new one.
     async fn refresh_validators_cache(&self) -> Result<()> {
         // TODO: This should be read only state, but we can't use the read-only view here
@@ -567,6 +583,10 @@ where
     /// Query the application for data at the current or past height.
     #[instrument(skip(self))]
     async fn query(&self, request: request::Query) -> AbciResult<response::Query> {
+        if request.path == CHECKPOINT_AGGREGATE_QUERY_PATH {
+            return self.query_checkpoint_aggregate(&request.data);
+        }
+
         let db = self.state_store_clone();
         let height = FvmQueryHeight::from(request.height.value());
         let (state_params, block_height) = self.state_params_at_height(height)?;
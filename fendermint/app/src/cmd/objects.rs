@@ -2,7 +2,10 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
 use std::{convert::Infallible, net::ToSocketAddrs, num::ParseIntError};
 
@@ -11,13 +14,15 @@ use anyhow::Context;
 use bytes::Buf;
 use entangler::{ChunkRange, Config, Entangler};
 use entangler_storage::iroh::IrohStorage as EntanglerIrohStorage;
-use fendermint_actor_bucket::{GetParams, Object};
+use fendermint_actor_bucket::{CopyParams, GetParams, Method as BucketMethod, Object};
 use fendermint_app_settings::objects::ObjectsSettings;
 use fendermint_rpc::{client::FendermintClient, message::GasParams, QueryClient};
 use fendermint_vm_message::query::FvmQueryHeight;
 use futures_util::{StreamExt, TryStreamExt};
+use fvm_ipld_encoding::RawBytes;
 use fvm_shared::{
     address::{Address, Error as NetworkError, Network},
+    clock::ChainEpoch,
     econ::TokenAmount,
 };
 use ipc_api::ethers_address_to_fil_address;
@@ -30,6 +35,7 @@ use lazy_static::lazy_static;
 use num_traits::Zero;
 use prometheus::{register_histogram, register_int_counter, Histogram, IntCounter};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use tracing::info;
 use warp::{
@@ -43,6 +49,17 @@ use warp::{
 use crate::cmd;
 use crate::options::objects::{ObjectsArgs, ObjectsCommands};
 
+mod multipart;
+mod reconcile;
+mod repair;
+mod s3;
+mod signer;
+mod sigv4;
+
+use multipart::{PartLimits, UploadSessions};
+use repair::RepairDaemon;
+use signer::Signer;
+
 /// The alpha parameter for alpha entanglement determines the number of parity blobs to generate
 /// for the original blob.
 const ENTANGLER_ALPHA: u8 = 3;
@@ -92,6 +109,93 @@ cmd! {
                 .and(with_max_size(settings.max_object_size))
                 .and_then(handle_object_upload);
 
+                // Multipart upload routes
+                let sessions = UploadSessions::default();
+                sessions.spawn_gc(
+                    iroh_client.clone(),
+                    settings.multipart.session_ttl,
+                    settings.multipart.gc_interval,
+                );
+
+                let uploads_create = warp::path!("v1" / "uploads")
+                .and(warp::post())
+                .and(with_sessions(sessions.clone()))
+                .and_then(multipart::handle_upload_create);
+
+                let uploads_part = warp::path!("v1" / "uploads" / String / u32)
+                .and(warp::put())
+                .and(with_sessions(sessions.clone()))
+                .and(with_iroh(iroh_client.clone()))
+                .and(with_multipart_limits(&settings))
+                .and(warp::body::stream())
+                .and_then(multipart::handle_upload_part);
+
+                let uploads_list = warp::path!("v1" / "uploads" / String)
+                .and(warp::get())
+                .and(with_sessions(sessions.clone()))
+                .and_then(multipart::handle_upload_list);
+
+                let uploads_complete = warp::path!("v1" / "uploads" / String / "complete")
+                .and(warp::post())
+                .and(with_sessions(sessions.clone()))
+                .and(with_iroh(iroh_client.clone()))
+                .and(with_max_size(settings.max_object_size))
+                .and(warp::body::json())
+                .and_then(multipart::handle_upload_complete);
+
+                let uploads_abort = warp::path!("v1" / "uploads" / String)
+                .and(warp::delete())
+                .and(with_sessions(sessions.clone()))
+                .and(with_iroh(iroh_client.clone()))
+                .and_then(multipart::handle_upload_abort);
+
+                // Object copy and move routes
+                let signer = match &settings.signer.secret_key {
+                    Some(secret_key) => {
+                        let signer = Signer::new(client.clone(), secret_key, settings.signer.gas_limit).await?;
+                        info!(address = signer.address.to_string(), "bucket transaction signing enabled");
+                        Some(signer)
+                    }
+                    None => None,
+                };
+
+                let objects_copy = warp::path!("v1" / "objects" / String / "copy")
+                .and(warp::post())
+                .and(warp::header::optional::<String>("Authorization"))
+                .and(with_token(settings.signer.token.clone()))
+                .and(with_signer(signer.clone()))
+                .and(warp::body::json())
+                .and_then(handle_object_copy);
+
+                let objects_move = warp::path!("v1" / "objects" / String / "move")
+                .and(warp::post())
+                .and(warp::header::optional::<String>("Authorization"))
+                .and(with_token(settings.signer.token.clone()))
+                .and(with_signer(signer.clone()))
+                .and(warp::body::json())
+                .and_then(handle_object_move);
+
+                // Object repair route and daemon
+                let objects_repair = warp::path!("v1" / "objects" / String / ..)
+                .and(warp::path::tail())
+                .and(warp::post())
+                .and(warp::header::optional::<String>("Authorization"))
+                .and(with_token(settings.repair.token.clone()))
+                .and(with_client(client.clone()))
+                .and(with_iroh(iroh_client.clone()))
+                .and_then(repair::handle_object_repair);
+
+                if settings.repair.enabled {
+                    info!(buckets = settings.repair.buckets.len(), "blob repair daemon enabled");
+                    RepairDaemon::new(
+                        client.clone(),
+                        iroh_client.clone(),
+                        &settings.repair.buckets,
+                        settings.repair.interval,
+                    )?
+                    .spawn();
+                }
+
                 let objects_download = warp::path!("v1" / "objects" / String / ..)
                 .and(warp::path::tail())
                 .and(
@@ -103,15 +207,57 @@ cmd! {
                 .and(with_iroh(iroh_client.clone()))
                 .and_then(handle_object_download);
 
+                // Routes that sign transactions with the node's key are not exposed to browsers
+                // on other origins.
+                let signing_routes = objects_copy.or(objects_move);
                 let router = health
                     .or(node_addr)
                     .or(objects_upload)
+                    .or(objects_repair)
                     .or(objects_download)
+                    .or(uploads_create)
+                    .or(uploads_part)
+                    .or(uploads_list)
+                    .or(uploads_complete)
+                    .or(uploads_abort)
                     .with(warp::cors().allow_any_origin()
-                        .allow_headers(vec!["Content-Type"])
-                        .allow_methods(vec!["POST", "DEL", "GET", "HEAD"]))
+                        .allow_headers(vec!["Content-Type", "Authorization"])
+                        .allow_methods(vec!["POST", "PUT", "DEL", "DELETE", "GET", "HEAD"]))
+                    .or(signing_routes)
                     .recover(handle_rejection);
 
+                if settings.s3.enabled {
+                    let credentials = match (&settings.s3.access_key_id, &settings.s3.secret_access_key) {
+                        (Some(access_key_id), Some(secret_access_key)) => Some(sigv4::Credentials {
+                            access_key_id: access_key_id.clone(),
+                            secret_access_key: secret_access_key.clone(),
+                        }),
+                        _ => None,
+                    };
+                    let ctx = s3::S3Context::new(
+                        client.clone(),
+                        iroh_client.clone(),
+                        sessions.clone(),
+                        PartLimits {
+                            max_part_size: settings.multipart.max_part_size,
+                            max_parts: settings.multipart.max_parts,
+                            max_object_size: settings.max_object_size,
+                        },
+                        settings.max_object_size,
+                        signer,
+                        credentials,
+                    )
+                    .await?;
+                    let s3_addr = settings
+                        .s3
+                        .listen
+                        .to_socket_addrs()?
+                        .next()
+                        .ok_or(anyhow!("failed to convert S3 listen address to a socket address"))?;
+                    info!(listen_addr = s3_addr.to_string(), "serving S3-compatible API");
+                    tokio::spawn(warp::serve(s3::routes(ctx)).run(s3_addr));
+                }
+
                 if let Some(listen_addr) = settings.listen.to_socket_addrs()?.next() {
                     warp::serve(router).run(listen_addr).await;
                     Ok(())
@@ -119,6 +265,25 @@ cmd! {
                     Err(anyhow!("failed to convert to a socket address"))
                 }
             },
+            ObjectsCommands::Reconcile {
+                tendermint_url,
+                iroh_addr,
+                dry_run,
+                remove_temp_tags,
+            } => {
+                let client = FendermintClient::new_http(tendermint_url, None)?;
+
+                let iroh_addr = iroh_addr
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or(anyhow!("failed to convert iroh_addr to a socket address"))?;
+                let iroh_client = iroh::client::Iroh::connect_addr(iroh_addr).await?;
+
+                let report =
+                    reconcile::reconcile(client, &iroh_client, dry_run, remove_temp_tags).await?;
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                Ok(())
+            },
         }
     }
 }
@@ -135,10 +300,39 @@ fn with_iroh(
     warp::any().map(move || client.clone())
 }
 
+fn with_signer(
+    signer: Option<Signer>,
+) -> impl Filter<Extract = (Option<Signer>,), Error = Infallible> + Clone {
+    warp::any().map(move || signer.clone())
+}
+
+fn with_token(
+    token: Option<String>,
+) -> impl Filter<Extract = (Option<String>,), Error = Infallible> + Clone {
+    warp::any().map(move || token.clone())
+}
+
 fn with_max_size(max_size: u64) -> impl Filter<Extract = (u64,), Error = Infallible> + Clone {
     warp::any().map(move || max_size)
 }
 
+fn with_sessions(
+    sessions: UploadSessions,
+) -> impl Filter<Extract = (UploadSessions,), Error = Infallible> + Clone {
+    warp::any().map(move || sessions.clone())
+}
+
+fn with_multipart_limits(
+    settings: &ObjectsSettings,
+) -> impl Filter<Extract = (PartLimits,), Error = Infallible> + Clone {
+    let limits = PartLimits {
+        max_part_size: settings.multipart.max_part_size,
+        max_parts: settings.multipart.max_parts,
+        max_object_size: settings.max_object_size,
+    };
+    warp::any().map(move || limits)
+}
+
 #[derive(Serialize, Deserialize)]
 struct HeightQuery {
     pub height: Option<u64>,
@@ -270,6 +464,21 @@ lazy_static! {
         "Time spent downloading an object in seconds"
     )
     .unwrap();
+    static ref COUNTER_BLOBS_REPAIRED: IntCounter = register_int_counter!(
+        "objects_blobs_repaired_total",
+        "Number of blobs rebuilt from their parities"
+    )
+    .unwrap();
+    static ref COUNTER_BYTES_REPAIRED: IntCounter = register_int_counter!(
+        "objects_bytes_repaired_total",
+        "Number of bytes rebuilt from parities"
+    )
+    .unwrap();
+    static ref COUNTER_REPAIRS_FAILED: IntCounter = register_int_counter!(
+        "objects_repairs_failed_total",
+        "Number of failed blob repairs"
+    )
+    .unwrap();
 }
 
 async fn handle_health() -> Result<impl Reply, Rejection> {
@@ -380,38 +589,7 @@ async fn handle_object_upload(
                     })
             });
 
-            let mut progress = iroh
-                .blobs()
-                .add_stream(stream, SetTagOption::Auto)
-                .await
-                .map_err(|e| {
-                    Rejection::from(BadRequest {
-                        message: format!("failed to store blob: {}", e),
-                    })
-                })?;
-
-            let uploaded_hash = loop {
-                let Some(event) = progress.next().await else {
-                    return Err(Rejection::from(BadRequest {
-                        message: "Unexpected end while ingesting data".to_string(),
-                    }));
-                };
-                match event.map_err(|e| {
-                    Rejection::from(BadRequest {
-                        message: format!("failed to make progress: {}", e),
-                    })
-                })? {
-                    AddProgress::AllDone { hash, .. } => {
-                        break hash;
-                    }
-                    AddProgress::Abort(err) => {
-                        return Err(Rejection::from(BadRequest {
-                            message: format!("upload aborted: {}", err),
-                        }));
-                    }
-                    _ => continue,
-                }
-            };
+            let uploaded_hash = store_stream(&iroh, stream, SetTagOption::Auto).await?;
             info!("stored uploaded blob {} (size: {})", uploaded_hash, size);
             COUNTER_BYTES_UPLOADED.inc_by(size);
 
@@ -452,6 +630,79 @@ async fn handle_object_upload(
     Ok(warp::reply::json(&response))
 }
 
+/// Adapt a request body into a byte stream for iroh, which fails once more than `max_size`
+/// bytes have arrived.
+///
+/// Returns the stream along with a counter of the bytes received so far.
+fn limited_body_stream<S, B>(
+    body: S,
+    max_size: u64,
+) -> (
+    impl futures_util::Stream<Item = std::io::Result<bytes::Bytes>> + Send + 'static,
+    Arc<AtomicU64>,
+)
+where
+    S: futures_util::Stream<Item = Result<B, warp::Error>> + Send + 'static,
+    B: Buf + Send,
+{
+    let received = Arc::new(AtomicU64::new(0));
+    let counter = received.clone();
+    let stream = body.map(move |result| {
+        let mut buf = result.map_err(|e| {
+            std::io::Error::new(std::io::ErrorKind::Other, format!("Warp error: {}", e))
+        })?;
+        let bytes = buf.copy_to_bytes(buf.remaining());
+        let len = bytes.len() as u64;
+        if counter.fetch_add(len, Ordering::Relaxed) + len > max_size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("size exceeds maximum of {}", max_size),
+            ));
+        }
+        Ok(bytes)
+    });
+    (stream, received)
+}
+
+/// Ingest a stream of bytes into iroh and wait for it to complete.
+async fn store_stream<S>(
+    iroh: &iroh::client::Iroh,
+    stream: S,
+    tag: SetTagOption,
+) -> Result<Hash, Rejection>
+where
+    S: futures_util::Stream<Item = std::io::Result<bytes::Bytes>> + Send + Unpin + 'static,
+{
+    let mut progress = iroh.blobs().add_stream(stream, tag).await.map_err(|e| {
+        Rejection::from(BadRequest {
+            message: format!("failed to store blob: {}", e),
+        })
+    })?;
+
+    loop {
+        let Some(event) = progress.next().await else {
+            return Err(Rejection::from(BadRequest {
+                message: "Unexpected end while ingesting data".to_string(),
+            }));
+        };
+        match event.map_err(|e| {
+            Rejection::from(BadRequest {
+                message: format!("failed to make progress: {}", e),
+            })
+        })? {
+            AddProgress::AllDone { hash, .. } => {
+                return Ok(hash);
+            }
+            AddProgress::Abort(err) => {
+                return Err(Rejection::from(BadRequest {
+                    message: format!("upload aborted: {}", err),
+                }));
+            }
+            _ => continue,
+        }
+    }
+}
+
 fn new_entangler(
     iroh: iroh::client::Iroh,
 ) -> Result<Entangler<EntanglerIrohStorage>, entangler::Error> {
@@ -529,156 +780,292 @@ async fn handle_object_download<F: QueryClient + Send + Sync>(
         })?;
 
     match maybe_object {
-        Some(object) => {
-            let hash = Hash::from_bytes(object.hash.0);
-            let status = iroh.blobs().status(hash).await.map_err(|e| {
+        Some(object) => object_response(&object, &method, range, iroh, start_time).await,
+        None => Err(Rejection::from(NotFound)),
+    }
+}
+
+#[derive(Deserialize)]
+struct CopyObjectRequest {
+    /// Key of the source object.
+    key: String,
+    /// Destination bucket address. Defaults to the source bucket.
+    to_bucket: Option<String>,
+    /// Destination object key.
+    to_key: String,
+    /// Destination object time-to-live epochs.
+    ttl: Option<ChainEpoch>,
+    /// Destination object metadata. Defaults to the source object metadata.
+    metadata: Option<HashMap<String, String>>,
+    #[serde(default)]
+    overwrite: bool,
+}
+
+#[derive(Serialize)]
+struct CopyObjectResponse {
+    hash: String,
+    metadata_hash: String,
+    size: u64,
+    expiry: ChainEpoch,
+    metadata: HashMap<String, String>,
+}
+
+async fn handle_object_copy(
+    address: String,
+    authorization: Option<String>,
+    token: Option<String>,
+    signer: Option<Signer>,
+    request: CopyObjectRequest,
+) -> Result<impl Reply, Rejection> {
+    if !is_authorized(token.as_deref(), authorization.as_deref()) {
+        return Err(Rejection::from(Forbidden));
+    }
+    copy_object(address, signer, request, BucketMethod::CopyObject).await
+}
+
+async fn handle_object_move(
+    address: String,
+    authorization: Option<String>,
+    token: Option<String>,
+    signer: Option<Signer>,
+    request: CopyObjectRequest,
+) -> Result<impl Reply, Rejection> {
+    if !is_authorized(token.as_deref(), authorization.as_deref()) {
+        return Err(Rejection::from(Forbidden));
+    }
+    copy_object(address, signer, request, BucketMethod::MoveObject).await
+}
+
+/// Checks the bearer token of a request against the configured one.
+///
+/// Without a configured token, the requests it guards are disabled.
+fn is_authorized(token: Option<&str>, authorization: Option<&str>) -> bool {
+    let (Some(token), Some(given)) = (token, authorization.and_then(|a| a.strip_prefix("Bearer ")))
+    else {
+        return false;
+    };
+    // Compare digests so the comparison time doesn't depend on the matching prefix.
+    Sha256::digest(token.as_bytes()) == Sha256::digest(given.trim().as_bytes())
+}
+
+/// Copy or move an object with a transaction signed by the configured key.
+///
+/// The destination subscribes to the source object's blob, so no data is transferred.
+async fn copy_object(
+    address: String,
+    signer: Option<Signer>,
+    request: CopyObjectRequest,
+    method: BucketMethod,
+) -> Result<impl Reply, Rejection> {
+    let signer = signer.ok_or_else(|| {
+        Rejection::from(BadRequest {
+            message: "object copy and move are disabled; no signer key is configured".into(),
+        })
+    })?;
+    let address = parse_address(&address).map_err(|e| {
+        Rejection::from(BadRequest {
+            message: format!("invalid address {}: {}", address, e),
+        })
+    })?;
+    let to_bucket = request
+        .to_bucket
+        .map(|to_bucket| {
+            parse_address(&to_bucket).map_err(|e| {
                 Rejection::from(BadRequest {
-                    message: format!("failed to read object: {} {}", hash, e),
+                    message: format!("invalid address {}: {}", to_bucket, e),
                 })
-            })?;
-            let BlobStatus::Complete { size } = status else {
-                return Err(Rejection::from(BadRequest {
-                    message: format!("object {} is not available", hash),
-                }));
-            };
-            // Sanity check size
-            if size.is_zero() {
-                return Err(Rejection::from(BadRequest {
-                    message: format!("object {} has zero size", hash),
-                }));
-            }
+            })
+        })
+        .transpose()?;
+    let params = CopyParams {
+        key: request.key.into_bytes(),
+        to_bucket,
+        to_key: request.to_key.into_bytes(),
+        ttl: request.ttl,
+        metadata: request.metadata,
+        overwrite: request.overwrite,
+        from: signer.address,
+    };
+    let params = RawBytes::serialize(params).map_err(|e| {
+        Rejection::from(BadRequest {
+            message: format!("failed to encode params: {}", e),
+        })
+    })?;
+    let object = signer
+        .transaction(address, method as u64, params)
+        .await
+        .and_then(|ret| Ok(ret.deserialize::<Object>()?))
+        .map_err(|e| {
+            Rejection::from(BadRequest {
+                message: format!("failed to copy object: {}", e),
+            })
+        })?;
+
+    Ok(warp::reply::json(&CopyObjectResponse {
+        hash: object.hash.to_string(),
+        metadata_hash: object.recovery_hash.to_string(),
+        size: object.size,
+        expiry: object.expiry,
+        metadata: object.metadata,
+    }))
+}
 
-            let ent = new_entangler(iroh).map_err(|e| {
+/// Build a response which streams the object's content from iroh.
+///
+/// For `HEAD` requests, only the headers are returned.
+async fn object_response(
+    object: &Object,
+    method: &str,
+    range: Option<String>,
+    iroh: iroh::client::Iroh,
+    start_time: Instant,
+) -> Result<warp::reply::Response, Rejection> {
+    let hash = Hash::from_bytes(object.hash.0);
+    let status = iroh.blobs().status(hash).await.map_err(|e| {
+        Rejection::from(BadRequest {
+            message: format!("failed to read object: {} {}", hash, e),
+        })
+    })?;
+    let BlobStatus::Complete { size } = status else {
+        return Err(Rejection::from(BadRequest {
+            message: format!("object {} is not available", hash),
+        }));
+    };
+    // Sanity check size
+    if size.is_zero() {
+        return Err(Rejection::from(BadRequest {
+            message: format!("object {} has zero size", hash),
+        }));
+    }
+
+    let ent = new_entangler(iroh).map_err(|e| {
+        Rejection::from(BadRequest {
+            message: format!("failed to create entangler: {}", e),
+        })
+    })?;
+    let recovery_hash = Hash::from_bytes(object.recovery_hash.0);
+
+    let object_range = match range {
+        Some(range) => {
+            let (first_byte, last_byte) = get_range_params(range, size).map_err(|e| {
                 Rejection::from(BadRequest {
-                    message: format!("failed to create entangler: {}", e),
+                    message: e.to_string(),
                 })
             })?;
-            let recovery_hash = Hash::from_bytes(object.recovery_hash.0);
-
-            let object_range = match range {
-                Some(range) => {
-                    let (first_byte, last_byte) = get_range_params(range, size).map_err(|e| {
-                        Rejection::from(BadRequest {
-                            message: e.to_string(),
-                        })
-                    })?;
-                    let len = (last_byte - first_byte) + 1;
-
-                    let first_chunk = first_byte / CHUNK_SIZE;
-                    let last_chunk = last_byte / CHUNK_SIZE;
-
-                    let bytes_stream = ent
-                        .download_range(
-                            &hash.to_string(),
-                            ChunkRange::Between(first_chunk, last_chunk),
-                            Some(recovery_hash.to_string()),
-                        )
-                        .await
-                        .map_err(|e| {
-                            Rejection::from(BadRequest {
-                                message: format!("failed to download object: {} {}", hash, e),
-                            })
-                        })?;
-
-                    let offset = (first_byte % CHUNK_SIZE) as usize;
-                    let end_offset = (last_byte % CHUNK_SIZE + 1) as usize;
-
-                    let bytes_stream = bytes_stream.enumerate().map(move |(i, chunk)| {
-                        let chunk = chunk?;
-                        let result = if first_chunk == last_chunk {
-                            // Single chunk case - slice with both offsets
-                            chunk.slice(offset..end_offset)
-                        } else if i == 0 {
-                            // First of multiple chunks
-                            chunk.slice(offset..)
-                        } else if i == (last_chunk - first_chunk) as usize {
-                            // Last of multiple chunks
-                            chunk.slice(..end_offset)
-                        } else {
-                            // Middle chunks
-                            chunk
-                        };
-                        Ok::<_, anyhow::Error>(result)
-                    });
-
-                    let body = Body::wrap_stream(bytes_stream);
-                    ObjectRange {
-                        start: first_byte,
-                        end: last_byte,
-                        len,
-                        size,
-                        body,
-                    }
-                }
-                None => {
-                    let bytes_stream = ent
-                        .download(&hash.to_string(), Some(&recovery_hash.to_string()))
-                        .await
-                        .map_err(|e| {
-                            Rejection::from(BadRequest {
-                                message: format!("failed to download object: {} {}", hash, e),
-                            })
-                        })?;
-                    let body = Body::wrap_stream(bytes_stream.map_err(|e| anyhow::anyhow!(e)));
-                    ObjectRange {
-                        start: 0,
-                        end: size - 1,
-                        len: size,
-                        size,
-                        body,
-                    }
-                }
-            };
+            let len = (last_byte - first_byte) + 1;
 
-            // If it is a HEAD request, we don't need to send the body,
-            // but we still need to send the Content-Length header
-            if method == "HEAD" {
-                let mut response = warp::reply::Response::new(Body::empty());
-                let mut header_map = HeaderMap::new();
-                header_map.insert("Content-Length", HeaderValue::from(object_range.len));
-                let headers = response.headers_mut();
-                headers.extend(header_map);
-                return Ok(response);
-            }
+            let first_chunk = first_byte / CHUNK_SIZE;
+            let last_chunk = last_byte / CHUNK_SIZE;
 
-            let mut response = warp::reply::Response::new(object_range.body);
-            let mut header_map = HeaderMap::new();
-            if object_range.len < object_range.size {
-                *response.status_mut() = StatusCode::PARTIAL_CONTENT;
-                header_map.insert(
-                    "Content-Range",
-                    HeaderValue::from_str(&format!(
-                        "bytes {}-{}/{}",
-                        object_range.start, object_range.end, object_range.size
-                    ))
-                    .unwrap(),
-                );
-            } else {
-                header_map.insert("Accept-Ranges", HeaderValue::from_str("bytes").unwrap());
-            }
-            header_map.insert("Content-Length", HeaderValue::from(object_range.len));
-            header_map.insert(
-                "Content-Type",
-                HeaderValue::from_str(
-                    object
-                        .metadata
-                        .get("content-type")
-                        .unwrap_or(&"application/octet-stream".to_string()),
+            let bytes_stream = ent
+                .download_range(
+                    &hash.to_string(),
+                    ChunkRange::Between(first_chunk, last_chunk),
+                    Some(recovery_hash.to_string()),
                 )
-                .unwrap(),
-            );
-
-            let headers = response.headers_mut();
-            headers.extend(header_map);
+                .await
+                .map_err(|e| {
+                    Rejection::from(BadRequest {
+                        message: format!("failed to download object: {} {}", hash, e),
+                    })
+                })?;
 
-            COUNTER_BLOBS_DOWNLOADED.inc();
-            COUNTER_BYTES_DOWNLOADED.inc_by(object_range.len);
-            HISTOGRAM_DOWNLOAD_TIME.observe(start_time.elapsed().as_secs_f64());
+            let offset = (first_byte % CHUNK_SIZE) as usize;
+            let end_offset = (last_byte % CHUNK_SIZE + 1) as usize;
+
+            let bytes_stream = bytes_stream.enumerate().map(move |(i, chunk)| {
+                let chunk = chunk?;
+                let result = if first_chunk == last_chunk {
+                    // Single chunk case - slice with both offsets
+                    chunk.slice(offset..end_offset)
+                } else if i == 0 {
+                    // First of multiple chunks
+                    chunk.slice(offset..)
+                } else if i == (last_chunk - first_chunk) as usize {
+                    // Last of multiple chunks
+                    chunk.slice(..end_offset)
+                } else {
+                    // Middle chunks
+                    chunk
+                };
+                Ok::<_, anyhow::Error>(result)
+            });
 
-            Ok(response)
+            let body = Body::wrap_stream(bytes_stream);
+            ObjectRange {
+                start: first_byte,
+                end: last_byte,
+                len,
+                size,
+                body,
+            }
         }
-        None => Err(Rejection::from(NotFound)),
+        None => {
+            let bytes_stream = ent
+                .download(&hash.to_string(), Some(&recovery_hash.to_string()))
+                .await
+                .map_err(|e| {
+                    Rejection::from(BadRequest {
+                        message: format!("failed to download object: {} {}", hash, e),
+                    })
+                })?;
+            let body = Body::wrap_stream(bytes_stream.map_err(|e| anyhow::anyhow!(e)));
+            ObjectRange {
+                start: 0,
+                end: size - 1,
+                len: size,
+                size,
+                body,
+            }
+        }
+    };
+
+    // If it is a HEAD request, we don't need to send the body,
+    // but we still need to send the Content-Length header
+    if method == "HEAD" {
+        let mut response = warp::reply::Response::new(Body::empty());
+        let mut header_map = HeaderMap::new();
+        header_map.insert("Content-Length", HeaderValue::from(object_range.len));
+        let headers = response.headers_mut();
+        headers.extend(header_map);
+        return Ok(response);
+    }
+
+    let mut response = warp::reply::Response::new(object_range.body);
+    let mut header_map = HeaderMap::new();
+    if object_range.len < object_range.size {
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+        header_map.insert(
+            "Content-Range",
+            HeaderValue::from_str(&format!(
+                "bytes {}-{}/{}",
+                object_range.start, object_range.end, object_range.size
+            ))
+            .unwrap(),
+        );
+    } else {
+        header_map.insert("Accept-Ranges", HeaderValue::from_str("bytes").unwrap());
     }
+    header_map.insert("Content-Length", HeaderValue::from(object_range.len));
+    header_map.insert(
+        "Content-Type",
+        HeaderValue::from_str(
+            object
+                .metadata
+                .get("content-type")
+                .unwrap_or(&"application/octet-stream".to_string()),
+        )
+        .unwrap(),
+    );
+
+    let headers = response.headers_mut();
+    headers.extend(header_map);
+
+    COUNTER_BLOBS_DOWNLOADED.inc();
+    COUNTER_BYTES_DOWNLOADED.inc_by(object_range.len);
+    HISTOGRAM_DOWNLOAD_TIME.observe(start_time.elapsed().as_secs_f64());
+
+    Ok(response)
 }
 
 /// Parse an f/eth-address from string.
@@ -710,6 +1097,11 @@ struct NotFound;
 
 impl warp::reject::Reject for NotFound {}
 
+#[derive(Debug)]
+struct Forbidden;
+
+impl warp::reject::Reject for Forbidden {}
+
 #[derive(Clone, Debug, Serialize)]
 struct ErrorMessage {
     code: u16,
@@ -719,6 +1111,8 @@ struct ErrorMessage {
 async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
     let (code, message) = if err.is_not_found() || err.find::<NotFound>().is_some() {
         (StatusCode::NOT_FOUND, "Not Found".to_string())
+    } else if err.find::<Forbidden>().is_some() {
+        (StatusCode::FORBIDDEN, "Forbidden".to_string())
     } else if let Some(e) = err.find::<BadRequest>() {
         let err = e.to_owned();
         (StatusCode::BAD_REQUEST, err.message)
@@ -1054,6 +1448,15 @@ mod tests {
         assert_eq!(response.headers().get("Content-Length").unwrap(), "11");
     }
 
+    #[test]
+    fn test_is_authorized() {
+        assert!(is_authorized(Some("secret"), Some("Bearer secret")));
+        assert!(!is_authorized(Some("secret"), Some("Bearer other")));
+        assert!(!is_authorized(Some("secret"), Some("secret")));
+        assert!(!is_authorized(Some("secret"), None));
+        assert!(!is_authorized(None, Some("Bearer secret")));
+    }
+
     #[test]
     fn test_get_range_params() {
         // bad formats
@@ -0,0 +1,737 @@
+// Copyright 2025 Recall Contributors
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! S3-style multipart uploads.
+//!
+//! A client initiates an upload session, uploads numbered parts which can be retried
+//! individually, then completes the session. On completion the parts are joined into a
+//! single iroh blob, which is entangled just like a regular upload.
+//!
+//! Parts are kept in iroh under a temporary tag until the session is completed or aborted.
+//! Sessions which have been idle for longer than the configured TTL are garbage-collected.
+//! Sessions only live in memory, so part tags left behind by an earlier run are removed on startup.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::Buf;
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use iroh::blobs::{util::SetTagOption, Hash, Tag};
+use iroh_manager::tags::UPLOAD_PART_TAG_PREFIX;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+use warp::{Rejection, Reply};
+
+use super::{
+    limited_body_stream, new_entangler, store_stream, BadRequest, NotFound, UploadResponse,
+    COUNTER_BLOBS_UPLOADED, COUNTER_BYTES_UPLOADED, HISTOGRAM_UPLOAD_TIME,
+};
+
+/// Limits applied to individual parts of a multipart upload.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct PartLimits {
+    pub max_part_size: u64,
+    pub max_parts: u32,
+    /// The parts of an upload may not add up to more than the maximum object size.
+    pub max_object_size: u64,
+}
+
+/// A part which has been stored in iroh.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(super) struct UploadedPart {
+    pub part_number: u32,
+    pub hash: String,
+    pub size: u64,
+}
+
+/// An upload session which has not been completed or aborted yet.
+struct UploadSession {
+    /// Parts uploaded so far, ordered by part number.
+    parts: BTreeMap<u32, (Hash, u64)>,
+    /// Object metadata given when the upload was created.
+    metadata: HashMap<String, String>,
+    /// Last time a part was uploaded or the session was otherwise used.
+    last_active: Instant,
+}
+
+impl UploadSession {
+    fn new() -> Self {
+        Self {
+            parts: BTreeMap::new(),
+            metadata: HashMap::new(),
+            last_active: Instant::now(),
+        }
+    }
+
+    /// Total size of the uploaded parts, except the one with the given part number.
+    fn size_without(&self, part_number: u32) -> u64 {
+        self.parts
+            .iter()
+            .filter(|(n, _)| **n != part_number)
+            .map(|(_, (_, size))| size)
+            .sum()
+    }
+
+    fn uploaded_parts(&self) -> Vec<UploadedPart> {
+        self.parts
+            .iter()
+            .map(|(part_number, (hash, size))| UploadedPart {
+                part_number: *part_number,
+                hash: hash.to_string(),
+                size: *size,
+            })
+            .collect()
+    }
+}
+
+/// In-memory registry of open upload sessions.
+#[derive(Clone, Default)]
+pub(super) struct UploadSessions {
+    inner: Arc<Mutex<HashMap<String, UploadSession>>>,
+}
+
+impl UploadSessions {
+    /// Open a new session and return its ID.
+    pub async fn create(&self) -> String {
+        let upload_id = new_upload_id();
+        self.inner
+            .lock()
+            .await
+            .insert(upload_id.clone(), UploadSession::new());
+        upload_id
+    }
+
+    /// Remove a session, returning the parts it was holding on to.
+    async fn remove(&self, upload_id: &str) -> Option<Vec<(u32, Hash)>> {
+        self.inner.lock().await.remove(upload_id).map(|session| {
+            session
+                .parts
+                .into_iter()
+                .map(|(part_number, (hash, _))| (part_number, hash))
+                .collect()
+        })
+    }
+
+    /// Remove all sessions that have been idle for longer than `ttl`.
+    async fn remove_expired(&self, ttl: Duration) -> Vec<(String, Vec<(u32, Hash)>)> {
+        let mut sessions = self.inner.lock().await;
+        let expired: Vec<String> = sessions
+            .iter()
+            .filter(|(_, session)| session.last_active.elapsed() > ttl)
+            .map(|(upload_id, _)| upload_id.clone())
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|upload_id| {
+                sessions.remove(&upload_id).map(|session| {
+                    let parts = session
+                        .parts
+                        .into_iter()
+                        .map(|(part_number, (hash, _))| (part_number, hash))
+                        .collect();
+                    (upload_id, parts)
+                })
+            })
+            .collect()
+    }
+
+    /// Delete the part tags of uploads without a session, such as those left behind by an
+    /// earlier run, returning how many were deleted.
+    ///
+    /// Parts of live sessions are kept, so this is safe to call while uploads are served.
+    async fn delete_stale_part_tags(&self, iroh: &iroh::client::Iroh) -> anyhow::Result<usize> {
+        let mut tags = Vec::new();
+        let mut list = iroh.tags().list().await?;
+        while let Some(tag) = list.next().await {
+            tags.push(tag?.name);
+        }
+        let stale: Vec<Tag> = {
+            let sessions = self.inner.lock().await;
+            tags.into_iter()
+                .filter(|tag| {
+                    part_tag_upload_id(tag)
+                        .is_some_and(|upload_id| !sessions.contains_key(upload_id))
+                })
+                .collect()
+        };
+        let deleted = stale.len();
+        for tag in stale {
+            iroh.tags().delete(tag).await?;
+        }
+        Ok(deleted)
+    }
+
+    /// Drop the parts left behind by an earlier run, then periodically drop
+    /// abandoned sessions along with their parts.
+    pub fn spawn_gc(&self, iroh: iroh::client::Iroh, ttl: Duration, interval: Duration) {
+        let sessions = self.clone();
+        tokio::spawn(async move {
+            match sessions.delete_stale_part_tags(&iroh).await {
+                Ok(0) => {}
+                Ok(deleted) => info!(deleted, "dropped parts of uploads from an earlier run"),
+                Err(e) => warn!(error = e.to_string(), "dropping stale upload parts failed"),
+            }
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                for (upload_id, parts) in sessions.remove_expired(ttl).await {
+                    info!(%upload_id, parts = parts.len(), "dropping abandoned upload");
+                    delete_part_tags(&iroh, &upload_id, parts).await;
+                }
+            }
+        });
+    }
+}
+
+/// Generate a hard to guess upload ID from the OS random number generator.
+fn new_upload_id() -> String {
+    let mut id = [0u8; 16];
+    OsRng.fill_bytes(&mut id);
+    hex::encode(id)
+}
+
+/// The tag under which a part is kept until its session ends.
+fn part_tag(upload_id: &str, part_number: u32) -> Tag {
+    Tag(format!("{UPLOAD_PART_TAG_PREFIX}{upload_id}-{part_number}").into())
+}
+
+/// Returns the upload ID of a part tag.
+fn part_tag_upload_id(tag: &Tag) -> Option<&str> {
+    let (upload_id, _) = std::str::from_utf8(&tag.0)
+        .ok()?
+        .strip_prefix(UPLOAD_PART_TAG_PREFIX)?
+        .rsplit_once('-')?;
+    Some(upload_id)
+}
+
+async fn delete_part_tags(iroh: &iroh::client::Iroh, upload_id: &str, parts: Vec<(u32, Hash)>) {
+    for (part_number, hash) in parts {
+        let tag = part_tag(upload_id, part_number);
+        if let Err(e) = iroh.tags().delete(tag.clone()).await {
+            warn!(tag = ?tag, hash = ?hash, error = e.to_string(), "deleting part tag failed");
+        }
+    }
+}
+
+fn unknown_upload(upload_id: &str) -> Rejection {
+    debug!(%upload_id, "unknown upload");
+    Rejection::from(NotFound)
+}
+
+/// A part as listed by the client when completing an upload.
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct CompletedPart {
+    pub part_number: u32,
+    pub hash: String,
+}
+
+/// The joined blob of a completed upload.
+pub(super) struct CompletedUpload {
+    pub hash: Hash,
+    pub metadata_hash: String,
+    pub size: u64,
+}
+
+impl UploadSessions {
+    /// Store a part of an open upload in iroh, replacing any previous attempt.
+    pub async fn put_part<S, B>(
+        &self,
+        iroh: &iroh::client::Iroh,
+        upload_id: &str,
+        part_number: u32,
+        limits: PartLimits,
+        body: S,
+    ) -> Result<UploadedPart, Rejection>
+    where
+        S: Stream<Item = Result<B, warp::Error>> + Send + 'static,
+        B: Buf + Send,
+    {
+        if part_number == 0 || part_number > limits.max_parts {
+            return Err(Rejection::from(BadRequest {
+                message: format!("part number must be between 1 and {}", limits.max_parts),
+            }));
+        }
+        let uploaded = match self.inner.lock().await.get(upload_id) {
+            Some(session) => session.size_without(part_number),
+            None => return Err(unknown_upload(upload_id)),
+        };
+        let max_size = limits
+            .max_part_size
+            .min(limits.max_object_size.saturating_sub(uploaded));
+
+        let (stream, received) = limited_body_stream(body, max_size);
+
+        let tag = part_tag(upload_id, part_number);
+        let hash = store_stream(iroh, Box::pin(stream), SetTagOption::Named(tag.clone())).await?;
+        let size = received.load(Ordering::Relaxed);
+
+        let mut guard = self.inner.lock().await;
+        let Some(session) = guard.get_mut(upload_id) else {
+            // The session was completed, aborted or collected while we were uploading.
+            drop(guard);
+            iroh.tags().delete(tag).await.ok();
+            return Err(unknown_upload(upload_id));
+        };
+        // Other parts may have been stored concurrently. A previous attempt of this part
+        // was stored under the same tag, so it's gone along with the rejected one.
+        if session.size_without(part_number) + size > limits.max_object_size {
+            session.parts.remove(&part_number);
+            drop(guard);
+            iroh.tags().delete(tag).await.ok();
+            return Err(Rejection::from(BadRequest {
+                message: format!("upload size exceeds maximum of {}", limits.max_object_size),
+            }));
+        }
+        session.parts.insert(part_number, (hash, size));
+        session.last_active = Instant::now();
+        drop(guard);
+
+        debug!(%upload_id, part_number, hash = ?hash, size, "stored upload part");
+        COUNTER_BYTES_UPLOADED.inc_by(size);
+
+        Ok(UploadedPart {
+            part_number,
+            hash: hash.to_string(),
+            size,
+        })
+    }
+
+    /// Attach object metadata to an open upload.
+    pub async fn set_metadata(
+        &self,
+        upload_id: &str,
+        metadata: HashMap<String, String>,
+    ) -> Result<(), Rejection> {
+        let mut guard = self.inner.lock().await;
+        let session = guard
+            .get_mut(upload_id)
+            .ok_or_else(|| unknown_upload(upload_id))?;
+        session.metadata = metadata;
+        Ok(())
+    }
+
+    /// Get the object metadata attached to an open upload.
+    pub async fn metadata(&self, upload_id: &str) -> Result<HashMap<String, String>, Rejection> {
+        let guard = self.inner.lock().await;
+        let session = guard
+            .get(upload_id)
+            .ok_or_else(|| unknown_upload(upload_id))?;
+        Ok(session.metadata.clone())
+    }
+
+    /// List the parts of an open upload.
+    pub async fn list_parts(&self, upload_id: &str) -> Result<Vec<UploadedPart>, Rejection> {
+        let guard = self.inner.lock().await;
+        let session = guard
+            .get(upload_id)
+            .ok_or_else(|| unknown_upload(upload_id))?;
+        Ok(session.uploaded_parts())
+    }
+
+    /// Join the given parts into a single entangled blob and close the upload.
+    pub async fn complete(
+        &self,
+        iroh: &iroh::client::Iroh,
+        upload_id: &str,
+        max_size: u64,
+        parts: &[CompletedPart],
+    ) -> Result<CompletedUpload, Rejection> {
+        let start_time = Instant::now();
+        if parts.is_empty() {
+            return Err(Rejection::from(BadRequest {
+                message: "must provide at least one part".to_string(),
+            }));
+        }
+
+        // Validate the client's view of the upload against what we have stored.
+        let (hashes, size) = {
+            let mut guard = self.inner.lock().await;
+            let session = guard
+                .get_mut(upload_id)
+                .ok_or_else(|| unknown_upload(upload_id))?;
+            session.last_active = Instant::now();
+
+            let mut hashes = Vec::with_capacity(parts.len());
+            let mut size = 0u64;
+            let mut last_part_number = 0;
+            for part in parts.iter() {
+                if part.part_number <= last_part_number {
+                    return Err(Rejection::from(BadRequest {
+                        message: "parts must be in ascending order".to_string(),
+                    }));
+                }
+                last_part_number = part.part_number;
+
+                let Some((hash, part_size)) = session.parts.get(&part.part_number) else {
+                    return Err(Rejection::from(BadRequest {
+                        message: format!("part {} has not been uploaded", part.part_number),
+                    }));
+                };
+                if hash.to_string() != part.hash {
+                    return Err(Rejection::from(BadRequest {
+                        message: format!(
+                            "part {} hash mismatch (expected {}, got {})",
+                            part.part_number, hash, part.hash
+                        ),
+                    }));
+                }
+                hashes.push(*hash);
+                size += part_size;
+            }
+            (hashes, size)
+        };
+        if size > max_size {
+            return Err(Rejection::from(BadRequest {
+                message: format!("blob size exceeds maximum of {}", max_size),
+            }));
+        }
+
+        // Stream the parts back out of iroh, one after the other, into a single blob.
+        let reader = iroh.clone();
+        let joined = stream::iter(hashes)
+            .then(move |hash| {
+                let iroh = reader.clone();
+                async move {
+                    iroh.blobs().read(hash).await.map_err(|e| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::Other,
+                            format!("failed to read part {}: {}", hash, e),
+                        )
+                    })
+                }
+            })
+            .try_flatten();
+        let hash = store_stream(iroh, Box::pin(joined), SetTagOption::Auto).await?;
+
+        let ent = new_entangler(iroh.clone()).map_err(|e| {
+            Rejection::from(BadRequest {
+                message: format!("failed to create entangler: {}", e),
+            })
+        })?;
+        let metadata_hash = ent.entangle_uploaded(hash.to_string()).await.map_err(|e| {
+            Rejection::from(BadRequest {
+                message: format!("failed to entangle uploaded data: {}", e),
+            })
+        })?;
+
+        // The parts are no longer needed once the joined blob is stored.
+        if let Some(parts) = self.remove(upload_id).await {
+            delete_part_tags(iroh, upload_id, parts).await;
+        }
+
+        info!(%upload_id, hash = ?hash, size, "completed multipart upload");
+        COUNTER_BLOBS_UPLOADED.inc();
+        HISTOGRAM_UPLOAD_TIME.observe(start_time.elapsed().as_secs_f64());
+
+        Ok(CompletedUpload {
+            hash,
+            metadata_hash,
+            size,
+        })
+    }
+
+    /// Close an upload without joining its parts.
+    pub async fn abort(&self, iroh: &iroh::client::Iroh, upload_id: &str) -> Result<(), Rejection> {
+        let parts = self
+            .remove(upload_id)
+            .await
+            .ok_or_else(|| unknown_upload(upload_id))?;
+        delete_part_tags(iroh, upload_id, parts).await;
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct CreateUploadResponse {
+    upload_id: String,
+}
+
+pub(super) async fn handle_upload_create(
+    sessions: UploadSessions,
+) -> Result<impl Reply, Rejection> {
+    let upload_id = sessions.create().await;
+    Ok(warp::reply::json(&CreateUploadResponse { upload_id }))
+}
+
+pub(super) async fn handle_upload_part<S, B>(
+    upload_id: String,
+    part_number: u32,
+    sessions: UploadSessions,
+    iroh: iroh::client::Iroh,
+    limits: PartLimits,
+    body: S,
+) -> Result<impl Reply, Rejection>
+where
+    S: Stream<Item = Result<B, warp::Error>> + Send + 'static,
+    B: Buf + Send,
+{
+    let part = sessions
+        .put_part(&iroh, &upload_id, part_number, limits, body)
+        .await?;
+    Ok(warp::reply::json(&part))
+}
+
+pub(super) async fn handle_upload_list(
+    upload_id: String,
+    sessions: UploadSessions,
+) -> Result<impl Reply, Rejection> {
+    let parts = sessions.list_parts(&upload_id).await?;
+    Ok(warp::reply::json(&parts))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct CompleteUploadParams {
+    /// The parts to join, in ascending part number order.
+    pub parts: Vec<CompletedPart>,
+}
+
+#[derive(Serialize)]
+struct CompleteUploadResponse {
+    #[serde(flatten)]
+    upload: UploadResponse,
+    size: u64,
+}
+
+pub(super) async fn handle_upload_complete(
+    upload_id: String,
+    sessions: UploadSessions,
+    iroh: iroh::client::Iroh,
+    max_size: u64,
+    params: CompleteUploadParams,
+) -> Result<impl Reply, Rejection> {
+    let completed = sessions
+        .complete(&iroh, &upload_id, max_size, &params.parts)
+        .await?;
+    Ok(warp::reply::json(&CompleteUploadResponse {
+        upload: UploadResponse {
+            hash: completed.hash.to_string(),
+            metadata_hash: completed.metadata_hash,
+        },
+        size: completed.size,
+    }))
+}
+
+pub(super) async fn handle_upload_abort(
+    upload_id: String,
+    sessions: UploadSessions,
+    iroh: iroh::client::Iroh,
+) -> Result<impl Reply, Rejection> {
+    sessions.abort(&iroh, &upload_id).await?;
+    Ok(warp::reply::reply())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use iroh::client::blobs::BlobStatus;
+    use warp::http::StatusCode;
+
+    const LIMITS: PartLimits = PartLimits {
+        max_part_size: 1024,
+        max_parts: 10,
+        max_object_size: 2048,
+    };
+
+    fn body(
+        data: &'static [u8],
+    ) -> impl Stream<Item = Result<Bytes, warp::Error>> + Send + 'static {
+        stream::iter(vec![Ok(Bytes::from_static(data))])
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload() {
+        let iroh = iroh::node::Node::memory().spawn().await.unwrap();
+        let sessions = UploadSessions::default();
+        let upload_id = sessions.create().await;
+
+        let mut parts = Vec::new();
+        for (part_number, data) in [(1, &b"hello "[..]), (2, &b"world"[..])] {
+            let reply = handle_upload_part(
+                upload_id.clone(),
+                part_number,
+                sessions.clone(),
+                iroh.client().clone(),
+                LIMITS,
+                body(data),
+            )
+            .await
+            .unwrap();
+            let response = reply.into_response();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = warp::hyper::body::to_bytes(response.into_body())
+                .await
+                .unwrap();
+            let part: UploadedPart = serde_json::from_slice(&body).unwrap();
+            assert_eq!(part.size, data.len() as u64);
+            parts.push(CompletedPart {
+                part_number: part.part_number,
+                hash: part.hash,
+            });
+        }
+
+        let reply = handle_upload_complete(
+            upload_id.clone(),
+            sessions.clone(),
+            iroh.client().clone(),
+            1000,
+            CompleteUploadParams { parts },
+        )
+        .await
+        .unwrap();
+        assert_eq!(reply.into_response().status(), StatusCode::OK);
+
+        let hash = Hash::new(b"hello world");
+        let status = iroh.blobs().status(hash).await.unwrap();
+        assert!(matches!(status, BlobStatus::Complete { size: 11 }));
+
+        // The session is gone after completion.
+        assert!(sessions.remove(&upload_id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_rejects_bad_parts() {
+        let iroh = iroh::node::Node::memory().spawn().await.unwrap();
+        let sessions = UploadSessions::default();
+        let upload_id = sessions.create().await;
+
+        // Part numbers are 1-based and bounded.
+        for part_number in [0, LIMITS.max_parts + 1] {
+            let res = handle_upload_part(
+                upload_id.clone(),
+                part_number,
+                sessions.clone(),
+                iroh.client().clone(),
+                LIMITS,
+                body(b"data"),
+            )
+            .await;
+            assert!(res.is_err());
+        }
+
+        // Unknown session.
+        let res = handle_upload_part(
+            "unknown".to_string(),
+            1,
+            sessions.clone(),
+            iroh.client().clone(),
+            LIMITS,
+            body(b"data"),
+        )
+        .await;
+        assert!(res.is_err());
+
+        // Completing with a part that was never uploaded.
+        let res = handle_upload_complete(
+            upload_id.clone(),
+            sessions.clone(),
+            iroh.client().clone(),
+            1000,
+            CompleteUploadParams {
+                parts: vec![CompletedPart {
+                    part_number: 1,
+                    hash: Hash::new(b"data").to_string(),
+                }],
+            },
+        )
+        .await;
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_max_object_size() {
+        let iroh = iroh::node::Node::memory().spawn().await.unwrap();
+        let sessions = UploadSessions::default();
+        let upload_id = sessions.create().await;
+
+        let data: &'static [u8] = &[1; 1024];
+        for part_number in [1, 2] {
+            sessions
+                .put_part(iroh.client(), &upload_id, part_number, LIMITS, body(data))
+                .await
+                .unwrap();
+        }
+        // The parts would add up to more than the maximum object size.
+        let res = sessions
+            .put_part(iroh.client(), &upload_id, 3, LIMITS, body(b"data"))
+            .await;
+        assert!(res.is_err());
+        // Replacing a part doesn't count its previous attempt.
+        sessions
+            .put_part(iroh.client(), &upload_id, 2, LIMITS, body(b"data"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_delete_stale_part_tags() {
+        let iroh = iroh::node::Node::memory().spawn().await.unwrap();
+        let sessions = UploadSessions::default();
+        let upload_id = sessions.create().await;
+        sessions
+            .put_part(iroh.client(), &upload_id, 1, LIMITS, body(b"data"))
+            .await
+            .unwrap();
+
+        // Parts of live sessions are kept.
+        assert_eq!(
+            sessions
+                .delete_stale_part_tags(iroh.client())
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            part_tag_upload_id(&part_tag(&upload_id, 1)),
+            Some(upload_id.as_str())
+        );
+
+        // A restarted service doesn't know about the session anymore.
+        let restarted = UploadSessions::default();
+        assert_eq!(
+            restarted
+                .delete_stale_part_tags(iroh.client())
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            restarted
+                .delete_stale_part_tags(iroh.client())
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_new_upload_id() {
+        let id = new_upload_id();
+        assert_eq!(id.len(), 32);
+        assert_ne!(id, new_upload_id());
+    }
+
+    #[tokio::test]
+    async fn test_remove_expired_sessions() {
+        let sessions = UploadSessions::default();
+        let old = sessions.create().await;
+        sessions
+            .inner
+            .lock()
+            .await
+            .get_mut(&old)
+            .unwrap()
+            .last_active -= Duration::from_secs(120);
+        let new = sessions.create().await;
+
+        let expired = sessions.remove_expired(Duration::from_secs(60)).await;
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, old);
+        assert!(sessions.inner.lock().await.contains_key(&new));
+    }
+}
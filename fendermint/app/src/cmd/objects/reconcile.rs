@@ -0,0 +1,150 @@
+// Copyright 2025 Recall Contributors
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Reconciliation of the local iroh store with the blobs actor state.
+//!
+//! Blobs are kept in iroh under a tag that is removed when the blobs actor drops the last
+//! subscription. If a removal never went through, e.g., because the node was down, the blob
+//! lingers on disc. Conversely, a tag may be lost while the blob is still subscribed.
+//! Reconciliation finds both cases, and removes the tags of blobs without subscribers.
+//!
+//! The objects service also keeps blobs under temporary tags while they are uploaded in parts
+//! or repaired. These are left behind if the service stops midway, so they are reported too.
+
+use std::collections::BTreeSet;
+
+use fendermint_actor_blobs_shared::params::GetBlobParams;
+use fendermint_actor_blobs_shared::state::{BlobStatus, Hash as BlobHash};
+use fendermint_rpc::{message::GasParams, QueryClient};
+use fendermint_vm_message::query::FvmQueryHeight;
+use futures_util::StreamExt;
+use fvm_shared::econ::TokenAmount;
+use iroh::blobs::Hash;
+use iroh::blobs::Tag;
+use iroh::client::blobs::BlobStatus as IrohBlobStatus;
+use iroh_manager::tags::{
+    delete_blob_tags, parse_repaired_tag, parse_stored_tag, REPAIRING_TAG_PREFIX,
+    UPLOAD_PART_TAG_PREFIX,
+};
+use serde::Serialize;
+use tracing::{debug, info};
+
+/// How a stored blob compares to the blobs actor state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TagState {
+    /// The blob is subscribed and held locally.
+    InSync,
+    /// The blob has no subscribers, so its tag should be removed.
+    Orphaned,
+    /// The blob is resolved and subscribed, but not complete in the local store.
+    Missing,
+}
+
+/// The outcome of a reconciliation.
+#[derive(Debug, Default, Serialize)]
+pub(super) struct ReconcileReport {
+    /// Number of blobs stored or repaired under a tag that were checked.
+    pub checked: usize,
+    /// Blobs that are stored locally without any subscribers.
+    pub orphaned: Vec<String>,
+    /// Number of orphaned blob tags that were removed.
+    pub removed: usize,
+    /// Subscribed blobs whose local copy is incomplete.
+    pub missing: Vec<String>,
+    /// Temporary tags of multipart uploads and repairs.
+    pub temporary: Vec<String>,
+    /// Number of temporary tags that were removed.
+    pub removed_temporary: usize,
+}
+
+/// Compares the blobs stored under tags in the local iroh node with the blobs actor state.
+///
+/// Unless `dry_run` is set, the tags of blobs without subscribers are removed,
+/// which lets iroh garbage collect the blobs. Temporary tags are only removed if
+/// `remove_temporary` is set as well, since they may belong to uploads and repairs that
+/// are still in progress.
+pub(super) async fn reconcile<F: QueryClient + Send + Sync>(
+    mut client: F,
+    iroh: &iroh::client::Iroh,
+    dry_run: bool,
+    remove_temporary: bool,
+) -> anyhow::Result<ReconcileReport> {
+    let mut hashes = BTreeSet::new();
+    let mut temporary = Vec::new();
+    let mut tags = iroh.tags().list().await?;
+    while let Some(tag) = tags.next().await {
+        let tag = tag?.name;
+        if let Some(hash) = parse_stored_tag(&tag).or_else(|| parse_repaired_tag(&tag)) {
+            hashes.insert(hash);
+        } else if is_temporary_tag(&tag) {
+            temporary.push(tag);
+        }
+    }
+
+    let mut report = ReconcileReport::default();
+    for hash in hashes {
+        report.checked += 1;
+        match tag_state(&mut client, iroh, hash).await? {
+            TagState::InSync => debug!(%hash, "blob in sync"),
+            TagState::Orphaned => {
+                info!(%hash, dry_run, "blob has no subscribers");
+                report.orphaned.push(hash.to_string());
+                if !dry_run {
+                    delete_blob_tags(iroh, &hash).await?;
+                    report.removed += 1;
+                }
+            }
+            TagState::Missing => {
+                info!(%hash, "subscribed blob is missing from the local store");
+                report.missing.push(hash.to_string());
+            }
+        }
+    }
+
+    for tag in temporary {
+        info!(%tag, dry_run, "temporary tag");
+        report.temporary.push(tag.to_string());
+        if !dry_run && remove_temporary {
+            iroh.tags().delete(tag).await?;
+            report.removed_temporary += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// Whether `tag` is a temporary tag of a multipart upload or a repair.
+fn is_temporary_tag(tag: &Tag) -> bool {
+    [UPLOAD_PART_TAG_PREFIX, REPAIRING_TAG_PREFIX]
+        .iter()
+        .any(|prefix| tag.0.starts_with(prefix.as_bytes()))
+}
+
+async fn tag_state<F: QueryClient + Send + Sync>(
+    client: &mut F,
+    iroh: &iroh::client::Iroh,
+    hash: Hash,
+) -> anyhow::Result<TagState> {
+    let gas_params = GasParams {
+        gas_limit: Default::default(),
+        gas_fee_cap: Default::default(),
+        gas_premium: Default::default(),
+    };
+    let blob = client
+        .blob_get_call(
+            GetBlobParams(BlobHash(*hash.as_bytes())),
+            TokenAmount::default(),
+            gas_params,
+            FvmQueryHeight::Committed,
+        )
+        .await?;
+    let Some(blob) = blob.filter(|blob| !blob.subscribers.is_empty()) else {
+        return Ok(TagState::Orphaned);
+    };
+    if blob.status != BlobStatus::Resolved {
+        return Ok(TagState::InSync);
+    }
+    match iroh.blobs().status(hash).await? {
+        IrohBlobStatus::Complete { .. } => Ok(TagState::InSync),
+        _ => Ok(TagState::Missing),
+    }
+}
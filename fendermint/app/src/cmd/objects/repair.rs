@@ -0,0 +1,348 @@
+// Copyright 2025 Recall Contributors
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Repair of entangled blobs.
+//!
+//! Uploaded blobs are entangled with alpha-entanglement parity blobs, which are stored alongside
+//! the original in the local iroh node. When chunks of a blob go missing or get corrupted, the
+//! blob can be rebuilt from the surviving chunks and the parities, and re-added to iroh.
+//!
+//! Repairs are triggered on demand through the objects API, or periodically by a daemon which
+//! scans the objects of the buckets this node is configured to hold.
+
+use std::io;
+use std::time::Duration;
+
+use fendermint_actor_bucket::{GetParams, ListParams, Object};
+use fendermint_rpc::{client::FendermintClient, message::GasParams, QueryClient};
+use fendermint_vm_message::query::FvmQueryHeight;
+use futures_util::{StreamExt, TryStreamExt};
+use fvm_shared::{address::Address, econ::TokenAmount};
+use iroh::blobs::{util::SetTagOption, BlobFormat, Hash, Tag};
+use iroh::client::blobs::{BlobStatus, DownloadMode, DownloadOptions};
+use iroh_manager::tags::{repaired_tag, REPAIRING_TAG_PREFIX};
+use serde::Serialize;
+use tracing::{debug, info, warn};
+use warp::{path::Tail, Rejection, Reply};
+
+use super::{
+    is_authorized, new_entangler, os_get, parse_address, BadRequest, Forbidden, NotFound,
+    COUNTER_BLOBS_REPAIRED, COUNTER_BYTES_REPAIRED, COUNTER_REPAIRS_FAILED,
+};
+
+/// Number of objects fetched per bucket list query while scanning.
+const SCAN_PAGE_SIZE: u64 = 100;
+
+/// The health of a blob in the local iroh store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BlobHealth {
+    /// The blob is complete and its content matches its hash.
+    Healthy,
+    /// The blob is not in the store, or only partially.
+    Missing,
+    /// The blob is complete, but its content doesn't match its hash.
+    Corrupt,
+}
+
+/// The result of a blob repair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum RepairOutcome {
+    /// Nothing had to be repaired.
+    Healthy,
+    /// The blob was rebuilt from its parities.
+    Repaired,
+}
+
+#[derive(Serialize)]
+struct RepairResponse {
+    hash: String,
+    status: RepairOutcome,
+}
+
+/// The tag under which a blob is rebuilt until it has been verified.
+fn repairing_tag(hash: &Hash) -> Tag {
+    Tag(format!("{REPAIRING_TAG_PREFIX}{hash}").into())
+}
+
+/// Returns the object key from a repair request path, i.e., `{key}/repair`.
+fn repair_key(tail: &str) -> Option<&str> {
+    tail.strip_suffix("/repair").filter(|key| !key.is_empty())
+}
+
+/// Checks whether a blob is complete and intact in the local iroh store.
+async fn check_blob(iroh: &iroh::client::Iroh, hash: Hash) -> anyhow::Result<BlobHealth> {
+    let BlobStatus::Complete { .. } = iroh.blobs().status(hash).await? else {
+        return Ok(BlobHealth::Missing);
+    };
+    let mut reader = match iroh.blobs().read(hash).await {
+        Ok(reader) => reader,
+        Err(_) => return Ok(BlobHealth::Corrupt),
+    };
+    let mut hasher = blake3::Hasher::new();
+    while let Some(chunk) = reader.next().await {
+        match chunk {
+            Ok(chunk) => {
+                hasher.update(&chunk);
+            }
+            Err(_) => return Ok(BlobHealth::Corrupt),
+        }
+    }
+    if hasher.finalize().as_bytes() == hash.as_bytes() {
+        Ok(BlobHealth::Healthy)
+    } else {
+        Ok(BlobHealth::Corrupt)
+    }
+}
+
+/// Checks a blob in the local iroh store and rebuilds it from its parities if needed.
+///
+/// The rebuilt blob is streamed into the store under a temporary tag, which iroh hashes
+/// as it goes. Only once the result matches the expected hash is it tagged as repaired,
+/// so a failed repair never replaces an earlier repaired copy with wrong data.
+pub(super) async fn repair_blob(
+    iroh: &iroh::client::Iroh,
+    hash: Hash,
+    recovery_hash: Hash,
+) -> anyhow::Result<RepairOutcome> {
+    let health = check_blob(iroh, hash).await?;
+    if health == BlobHealth::Healthy {
+        return Ok(RepairOutcome::Healthy);
+    }
+    info!(%hash, ?health, "repairing blob");
+
+    // The entangler only rebuilds chunks it fails to read, so a corrupt copy has to go first
+    if health == BlobHealth::Corrupt {
+        iroh.blobs().delete_blob(hash).await?;
+    }
+
+    let ent = new_entangler(iroh.clone())?;
+    let chunks = ent
+        .download(&hash.to_string(), Some(&recovery_hash.to_string()))
+        .await?
+        .map_err(|e| io::Error::other(e.to_string()));
+
+    let temp_tag = repairing_tag(&hash);
+    let outcome = iroh
+        .blobs()
+        .add_stream(Box::pin(chunks), SetTagOption::Named(temp_tag.clone()))
+        .await?
+        .finish()
+        .await;
+    let result = match outcome {
+        Ok(outcome) if outcome.hash == hash => tag_repaired(iroh, hash).await.map(|_| outcome.size),
+        Ok(outcome) => Err(anyhow::anyhow!(
+            "rebuilt blob was stored under {} instead of {}",
+            outcome.hash,
+            hash
+        )),
+        Err(e) => Err(e),
+    };
+    // A mismatching rebuild is only held by this tag, so it gets collected along with it.
+    if let Err(e) = iroh.tags().delete(temp_tag.clone()).await {
+        warn!(tag = ?temp_tag, error = e.to_string(), "deleting repair tag failed");
+    }
+    let size = result?;
+
+    info!(%hash, size, "repaired blob");
+    COUNTER_BLOBS_REPAIRED.inc();
+    COUNTER_BYTES_REPAIRED.inc_by(size);
+    Ok(RepairOutcome::Repaired)
+}
+
+/// Keeps a verified blob under its repaired tag.
+///
+/// The blob is complete in the local store, so fetching it from our own node only sets the tag.
+async fn tag_repaired(iroh: &iroh::client::Iroh, hash: Hash) -> anyhow::Result<()> {
+    let node_addr = iroh.net().node_addr().await?;
+    iroh.blobs()
+        .download_with_opts(
+            hash,
+            DownloadOptions {
+                format: BlobFormat::Raw,
+                nodes: vec![node_addr],
+                tag: SetTagOption::Named(repaired_tag(&hash)),
+                mode: DownloadMode::Direct,
+            },
+        )
+        .await?
+        .finish()
+        .await?;
+    Ok(())
+}
+
+/// Repair the blob of a single object.
+///
+/// Requires the token from the repair settings as a bearer token.
+pub(super) async fn handle_object_repair(
+    address: String,
+    tail: Tail,
+    authorization: Option<String>,
+    token: Option<String>,
+    client: FendermintClient,
+    iroh: iroh::client::Iroh,
+) -> Result<impl Reply, Rejection> {
+    let key = repair_key(tail.as_str()).ok_or_else(|| Rejection::from(NotFound))?;
+    if !is_authorized(token.as_deref(), authorization.as_deref()) {
+        return Err(Rejection::from(Forbidden));
+    }
+    let address = parse_address(&address).map_err(|e| {
+        Rejection::from(BadRequest {
+            message: format!("invalid address {}: {}", address, e),
+        })
+    })?;
+    let object = os_get(
+        client,
+        address,
+        GetParams(key.into()),
+        FvmQueryHeight::Committed.into(),
+    )
+    .await
+    .map_err(|e| {
+        Rejection::from(BadRequest {
+            message: format!("bucket get error: {}", e),
+        })
+    })?
+    .ok_or_else(|| Rejection::from(NotFound))?;
+
+    let hash = Hash::from_bytes(object.hash.0);
+    let recovery_hash = Hash::from_bytes(object.recovery_hash.0);
+    let status = repair_blob(&iroh, hash, recovery_hash).await.map_err(|e| {
+        COUNTER_REPAIRS_FAILED.inc();
+        Rejection::from(BadRequest {
+            message: format!("failed to repair object: {} {}", hash, e),
+        })
+    })?;
+
+    Ok(warp::reply::json(&RepairResponse {
+        hash: hash.to_string(),
+        status,
+    }))
+}
+
+/// Periodically checks the objects of a set of buckets and repairs their blobs.
+pub(super) struct RepairDaemon {
+    client: FendermintClient,
+    iroh: iroh::client::Iroh,
+    buckets: Vec<Address>,
+    interval: Duration,
+}
+
+impl RepairDaemon {
+    pub fn new(
+        client: FendermintClient,
+        iroh: iroh::client::Iroh,
+        buckets: &[String],
+        interval: Duration,
+    ) -> anyhow::Result<Self> {
+        let buckets = buckets
+            .iter()
+            .map(|bucket| parse_address(bucket))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            client,
+            iroh,
+            buckets,
+            interval,
+        })
+    }
+
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                for bucket in &self.buckets {
+                    if let Err(e) = self.scan_bucket(*bucket).await {
+                        warn!(%bucket, error = e.to_string(), "failed to scan bucket for repairs");
+                    }
+                }
+            }
+        });
+    }
+
+    /// Walks all objects in a bucket and repairs the ones whose blobs are damaged.
+    async fn scan_bucket(&self, bucket: Address) -> anyhow::Result<()> {
+        let mut start_key = None;
+        let (mut checked, mut repaired) = (0u64, 0u64);
+        loop {
+            let list = self
+                .client
+                .clone()
+                .os_list_call(
+                    bucket,
+                    ListParams {
+                        start_key,
+                        limit: SCAN_PAGE_SIZE,
+                        ..Default::default()
+                    },
+                    TokenAmount::default(),
+                    GasParams {
+                        gas_limit: Default::default(),
+                        gas_fee_cap: Default::default(),
+                        gas_premium: Default::default(),
+                    },
+                    FvmQueryHeight::Committed,
+                )
+                .await?;
+            for (key, _) in list.objects {
+                // The listing doesn't include the recovery hash, so we fetch the full object
+                let Some(object) = os_get(
+                    self.client.clone(),
+                    bucket,
+                    GetParams(key.clone()),
+                    FvmQueryHeight::Committed.into(),
+                )
+                .await?
+                else {
+                    // Deleted since it was listed
+                    continue;
+                };
+                checked += 1;
+                match self.repair_object(&object).await {
+                    Ok(RepairOutcome::Healthy) => {}
+                    Ok(RepairOutcome::Repaired) => repaired += 1,
+                    Err(e) => {
+                        COUNTER_REPAIRS_FAILED.inc();
+                        warn!(
+                            %bucket,
+                            key = String::from_utf8_lossy(&key).to_string(),
+                            error = e.to_string(),
+                            "failed to repair object"
+                        );
+                    }
+                }
+            }
+            match list.next_key {
+                Some(next_key) => start_key = Some(next_key),
+                None => break,
+            }
+        }
+        debug!(%bucket, checked, repaired, "scanned bucket for repairs");
+        Ok(())
+    }
+
+    async fn repair_object(&self, object: &Object) -> anyhow::Result<RepairOutcome> {
+        repair_blob(
+            &self.iroh,
+            Hash::from_bytes(object.hash.0),
+            Hash::from_bytes(object.recovery_hash.0),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repair_key() {
+        assert_eq!(repair_key("foo/repair"), Some("foo"));
+        assert_eq!(repair_key("foo/bar/repair"), Some("foo/bar"));
+        assert_eq!(repair_key("repair/repair"), Some("repair"));
+        assert_eq!(repair_key("/repair"), None);
+        assert_eq!(repair_key("repair"), None);
+        assert_eq!(repair_key("foo"), None);
+    }
+}
@@ -0,0 +1,1007 @@
+// Copyright 2025 Recall Contributors
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! S3-compatible API over bucket machines.
+//!
+//! Buckets are addressed path-style by their machine address, e.g. `GET /{address}/{key}`.
+//! Reads are served from iroh using the bucket actor's view of the object. Writes are signed
+//! by the configured key, which the bucket owner has to approve to use their credit.
+//!
+//! Supported operations are `GetObject`, `HeadObject`, `PutObject`, `CopyObject`,
+//! `DeleteObject`, `ListObjectsV2`, `HeadBucket` and the multipart upload operations.
+//! Object user metadata is mapped to `x-amz-meta-*` headers.
+//!
+//! Requests that write to buckets or upload data must be signed with AWS Signature Version 4
+//! using the configured access key. Without an access key the API is read-only.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::atomic::Ordering;
+use std::time::{Instant, SystemTime};
+
+use bytes::{Buf, Bytes};
+use fendermint_actor_blobs_shared::state::{Hash as BlobHash, PublicKey};
+use fendermint_actor_bucket::{
+    AddParams, CopyParams, DeleteParams, GetParams, ListObjectsReturn, ListParams,
+    Method as BucketMethod, Object,
+};
+use fendermint_rpc::{client::FendermintClient, message::GasParams};
+use fendermint_vm_message::query::FvmQueryHeight;
+use futures_util::Stream;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::{address::Address, econ::TokenAmount};
+use iroh::blobs::{util::SetTagOption, Hash};
+use tracing::{debug, info};
+use warp::{
+    http::{HeaderMap, HeaderValue, Method, StatusCode},
+    path::{FullPath, Tail},
+    Filter, Rejection, Reply,
+};
+
+use super::multipart::{CompletedPart, PartLimits, UploadSessions};
+use super::signer::Signer;
+use super::sigv4::{self, Credentials};
+use super::{
+    limited_body_stream, new_entangler, object_response, os_get, parse_address, store_stream,
+    BadRequest, NotFound, COUNTER_BLOBS_UPLOADED, COUNTER_BYTES_UPLOADED, HISTOGRAM_UPLOAD_TIME,
+};
+
+const S3_NAMESPACE: &str = "http://s3.amazonaws.com/doc/2006-03-01/";
+const METADATA_HEADER_PREFIX: &str = "x-amz-meta-";
+const CONTENT_TYPE_METADATA_KEY: &str = "content-type";
+/// Objects carry no modification time, so listings report a fixed one.
+const LAST_MODIFIED: &str = "1970-01-01T00:00:00.000Z";
+/// Maximum size of the `CompleteMultipartUpload` request body.
+const MAX_COMPLETE_BODY_SIZE: u64 = 1024 * 1024;
+
+/// An error reported to S3 clients in the S3 XML format.
+#[derive(Clone, Debug)]
+struct S3Error {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl warp::reject::Reject for S3Error {}
+
+impl S3Error {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Rejection {
+        Rejection::from(Self {
+            status,
+            code,
+            message: message.into(),
+        })
+    }
+
+    fn no_such_key() -> Rejection {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            "NoSuchKey",
+            "The specified key does not exist.",
+        )
+    }
+
+    fn invalid_argument(message: impl Into<String>) -> Rejection {
+        Self::new(StatusCode::BAD_REQUEST, "InvalidArgument", message)
+    }
+
+    fn internal(message: impl Into<String>) -> Rejection {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "InternalError", message)
+    }
+}
+
+/// Everything the S3 handlers need.
+#[derive(Clone)]
+pub(super) struct S3Context {
+    client: FendermintClient,
+    iroh: iroh::client::Iroh,
+    sessions: UploadSessions,
+    limits: PartLimits,
+    max_object_size: u64,
+    /// The iroh node validators fetch uploaded blobs from.
+    source: PublicKey,
+    signer: Option<Signer>,
+    credentials: Option<Credentials>,
+}
+
+impl S3Context {
+    pub async fn new(
+        client: FendermintClient,
+        iroh: iroh::client::Iroh,
+        sessions: UploadSessions,
+        limits: PartLimits,
+        max_object_size: u64,
+        signer: Option<Signer>,
+        credentials: Option<Credentials>,
+    ) -> anyhow::Result<Self> {
+        let node_id = iroh.net().node_id().await?;
+        if signer.is_none() || credentials.is_none() {
+            info!("S3 API is read-only");
+        }
+        Ok(Self {
+            client,
+            iroh,
+            sessions,
+            limits,
+            max_object_size,
+            source: PublicKey(*node_id.as_bytes()),
+            signer,
+            credentials,
+        })
+    }
+
+    /// Checks the signature of a request that writes to buckets or uploads data.
+    fn authorize(
+        &self,
+        method: &Method,
+        path: &FullPath,
+        query: &str,
+        headers: &HeaderMap,
+    ) -> Result<(), Rejection> {
+        let Some(credentials) = &self.credentials else {
+            return Err(S3Error::new(
+                StatusCode::FORBIDDEN,
+                "AccessDenied",
+                "the S3 API is read-only",
+            ));
+        };
+        sigv4::verify(
+            credentials,
+            method.as_str(),
+            path.as_str(),
+            query,
+            headers,
+            SystemTime::now(),
+        )
+        .map_err(|e| {
+            debug!(error = e.to_string(), "rejected S3 request signature");
+            S3Error::new(
+                StatusCode::FORBIDDEN,
+                "SignatureDoesNotMatch",
+                format!("request signature is invalid: {}", e),
+            )
+        })
+    }
+
+    fn signer(&self) -> Result<&Signer, Rejection> {
+        self.signer.as_ref().ok_or_else(|| {
+            S3Error::new(
+                StatusCode::FORBIDDEN,
+                "AccessDenied",
+                "the S3 API is read-only",
+            )
+        })
+    }
+
+    async fn get_object(&self, bucket: Address, key: Vec<u8>) -> Result<Object, Rejection> {
+        os_get(
+            self.client.clone(),
+            bucket,
+            GetParams(key),
+            FvmQueryHeight::Committed.into(),
+        )
+        .await
+        .map_err(|e| S3Error::internal(format!("bucket get error: {}", e)))?
+        .ok_or_else(S3Error::no_such_key)
+    }
+
+    async fn list_objects(
+        &self,
+        bucket: Address,
+        params: ListParams,
+    ) -> Result<ListObjectsReturn, Rejection> {
+        let mut client = self.client.clone();
+        client
+            .os_list_call(
+                bucket,
+                params,
+                TokenAmount::default(),
+                GasParams {
+                    gas_limit: Default::default(),
+                    gas_fee_cap: Default::default(),
+                    gas_premium: Default::default(),
+                },
+                FvmQueryHeight::Committed,
+            )
+            .await
+            .map_err(|e| S3Error::internal(format!("bucket list error: {}", e)))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn add_object(
+        &self,
+        bucket: Address,
+        key: Vec<u8>,
+        hash: Hash,
+        recovery_hash: BlobHash,
+        size: u64,
+        metadata: HashMap<String, String>,
+    ) -> Result<(), Rejection> {
+        let signer = self.signer()?;
+        let params = AddParams {
+            source: self.source,
+            key,
+            hash: BlobHash(*hash.as_bytes()),
+            recovery_hash,
+            size,
+            ttl: None,
+            metadata,
+            overwrite: true,
+            from: signer.address,
+        };
+        let params = RawBytes::serialize(params)
+            .map_err(|e| S3Error::internal(format!("failed to encode params: {}", e)))?;
+        signer
+            .transaction(bucket, BucketMethod::AddObject as u64, params)
+            .await
+            .map_err(|e| S3Error::internal(format!("failed to add object: {}", e)))?;
+        Ok(())
+    }
+
+    /// Copy an object on chain. The copy subscribes to the same blob, so no data is transferred.
+    async fn copy_object(
+        &self,
+        bucket: Address,
+        key: Vec<u8>,
+        to_bucket: Address,
+        to_key: Vec<u8>,
+        metadata: Option<HashMap<String, String>>,
+    ) -> Result<Object, Rejection> {
+        let signer = self.signer()?;
+        let params = CopyParams {
+            key,
+            to_bucket: Some(to_bucket),
+            to_key,
+            ttl: None,
+            metadata,
+            overwrite: true,
+            from: signer.address,
+        };
+        let params = RawBytes::serialize(params)
+            .map_err(|e| S3Error::internal(format!("failed to encode params: {}", e)))?;
+        signer
+            .transaction(bucket, BucketMethod::CopyObject as u64, params)
+            .await
+            .and_then(|ret| Ok(ret.deserialize::<Object>()?))
+            .map_err(|e| S3Error::internal(format!("failed to copy object: {}", e)))
+    }
+
+    async fn delete_object(&self, bucket: Address, key: Vec<u8>) -> Result<(), Rejection> {
+        let signer = self.signer()?;
+        let params = DeleteParams {
+            key,
+            from: signer.address,
+        };
+        let params = RawBytes::serialize(params)
+            .map_err(|e| S3Error::internal(format!("failed to encode params: {}", e)))?;
+        signer
+            .transaction(bucket, BucketMethod::DeleteObject as u64, params)
+            .await
+            .map_err(|e| S3Error::internal(format!("failed to delete object: {}", e)))?;
+        Ok(())
+    }
+
+    /// Entangle a blob which has been stored in iroh and return its recovery hash.
+    async fn entangle(&self, hash: Hash) -> Result<BlobHash, Rejection> {
+        let ent = new_entangler(self.iroh.clone())
+            .map_err(|e| S3Error::internal(format!("failed to create entangler: {}", e)))?;
+        let metadata_hash = ent
+            .entangle_uploaded(hash.to_string())
+            .await
+            .map_err(|e| S3Error::internal(format!("failed to entangle uploaded data: {}", e)))?;
+        parse_blob_hash(&metadata_hash)
+    }
+}
+
+fn parse_blob_hash(hash: &str) -> Result<BlobHash, Rejection> {
+    BlobHash::try_from(hash).map_err(|e| S3Error::internal(format!("invalid hash {}: {}", hash, e)))
+}
+
+fn with_context(ctx: S3Context) -> impl Filter<Extract = (S3Context,), Error = Infallible> + Clone {
+    warp::any().map(move || ctx.clone())
+}
+
+/// Rejects requests that are not signed with the configured access key.
+fn with_auth(ctx: S3Context) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let query = warp::query::raw().or(warp::any().map(String::new)).unify();
+    warp::method()
+        .and(warp::path::full())
+        .and(query)
+        .and(warp::header::headers_cloned())
+        .and(with_context(ctx))
+        .and_then(
+            |method: Method, path: FullPath, query: String, headers: HeaderMap, ctx: S3Context| async move {
+                ctx.authorize(&method, &path, &query, &headers)
+            },
+        )
+        .untuple_one()
+}
+
+/// All routes of the S3-compatible API.
+pub(super) fn routes(
+    ctx: S3Context,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    let bucket_list = warp::path::param::<String>()
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_context(ctx.clone()))
+        .and_then(handle_list_objects);
+
+    let bucket_head = warp::path::param::<String>()
+        .and(warp::path::end())
+        .and(warp::head())
+        .and(with_context(ctx.clone()))
+        .and_then(handle_head_bucket);
+
+    let object_get = warp::path::param::<String>()
+        .and(warp::path::tail())
+        .and(
+            warp::get()
+                .map(|| "GET".to_string())
+                .or(warp::head().map(|| "HEAD".to_string()))
+                .unify(),
+        )
+        .and(warp::header::optional::<String>("Range"))
+        .and(with_context(ctx.clone()))
+        .and_then(handle_get_object);
+
+    let object_put = warp::path::param::<String>()
+        .and(warp::path::tail())
+        .and(warp::put())
+        .and(with_auth(ctx.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::header::headers_cloned())
+        .and(with_context(ctx.clone()))
+        .and(warp::body::stream())
+        .and_then(handle_put_object);
+
+    let object_post = warp::path::param::<String>()
+        .and(warp::path::tail())
+        .and(warp::post())
+        .and(with_auth(ctx.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::header::headers_cloned())
+        .and(with_context(ctx.clone()))
+        .and(warp::body::content_length_limit(MAX_COMPLETE_BODY_SIZE))
+        .and(warp::body::bytes())
+        .and_then(handle_post_object);
+
+    let object_delete = warp::path::param::<String>()
+        .and(warp::path::tail())
+        .and(warp::delete())
+        .and(with_auth(ctx.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_context(ctx))
+        .and_then(handle_delete_object);
+
+    bucket_list
+        .or(bucket_head)
+        .or(object_get)
+        .or(object_put)
+        .or(object_post)
+        .or(object_delete)
+        .recover(handle_s3_rejection)
+}
+
+/// Parse the bucket machine address and the object key from the request path.
+fn parse_path(bucket: &str, tail: &Tail) -> Result<(Address, Vec<u8>), Rejection> {
+    let address = parse_bucket(bucket)?;
+    let key = percent_decode(tail.as_str())?;
+    if key.is_empty() {
+        return Err(S3Error::invalid_argument("missing object key"));
+    }
+    Ok((address, key))
+}
+
+fn parse_bucket(bucket: &str) -> Result<Address, Rejection> {
+    parse_address(bucket).map_err(|e| {
+        S3Error::new(
+            StatusCode::NOT_FOUND,
+            "NoSuchBucket",
+            format!("invalid bucket address {}: {}", bucket, e),
+        )
+    })
+}
+
+/// Decode a percent-encoded path segment or query value into raw bytes.
+fn percent_decode(s: &str) -> Result<Vec<u8>, Rejection> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| S3Error::invalid_argument("invalid percent-encoding"))?;
+            decoded.push(hex);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(decoded)
+}
+
+/// Collect `x-amz-meta-*` headers and the content type into object metadata.
+fn metadata_from_headers(headers: &HeaderMap) -> HashMap<String, String> {
+    let mut metadata = HashMap::new();
+    for (name, value) in headers.iter() {
+        if let Some(key) = name.as_str().strip_prefix(METADATA_HEADER_PREFIX) {
+            if let Ok(value) = value.to_str() {
+                metadata.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+    if let Some(value) = headers
+        .get("Content-Type")
+        .and_then(|value| value.to_str().ok())
+    {
+        metadata.insert(CONTENT_TYPE_METADATA_KEY.to_string(), value.to_string());
+    }
+    metadata
+}
+
+/// Add the ETag and the object metadata as `x-amz-meta-*` headers to a response.
+fn add_object_headers(headers: &mut HeaderMap, object: &Object) {
+    let hash = Hash::from_bytes(object.hash.0);
+    if let Ok(etag) = HeaderValue::from_str(&etag(&hash)) {
+        headers.insert("ETag", etag);
+    }
+    headers.insert("Last-Modified", HeaderValue::from_static(LAST_MODIFIED));
+    for (key, value) in object.metadata.iter() {
+        if key == CONTENT_TYPE_METADATA_KEY {
+            continue;
+        }
+        if let Ok(value) = HeaderValue::from_str(value) {
+            if let Ok(name) = warp::http::header::HeaderName::from_bytes(
+                format!("{}{}", METADATA_HEADER_PREFIX, key).as_bytes(),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+    }
+}
+
+fn etag(hash: &Hash) -> String {
+    format!("\"{}\"", hash)
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn xml_reply(status: StatusCode, body: String) -> warp::reply::Response {
+    let body = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}", body);
+    let reply = warp::reply::with_header(body, "Content-Type", "application/xml");
+    warp::reply::with_status(reply, status).into_response()
+}
+
+async fn handle_head_bucket(bucket: String, ctx: S3Context) -> Result<impl Reply, Rejection> {
+    let address = parse_bucket(&bucket)?;
+    ctx.list_objects(
+        address,
+        ListParams {
+            limit: 1,
+            ..Default::default()
+        },
+    )
+    .await?;
+    Ok(warp::reply::reply())
+}
+
+async fn handle_list_objects(
+    bucket: String,
+    query: HashMap<String, String>,
+    ctx: S3Context,
+) -> Result<impl Reply, Rejection> {
+    let address = parse_bucket(&bucket)?;
+    let prefix = query.get("prefix").cloned().unwrap_or_default();
+    let delimiter = query.get("delimiter").cloned().unwrap_or_default();
+    let max_keys = match query.get("max-keys") {
+        Some(max_keys) => max_keys
+            .parse::<u64>()
+            .map_err(|_| S3Error::invalid_argument("invalid max-keys"))?,
+        None => 1000,
+    };
+    let start_key = match query.get("continuation-token") {
+        Some(token) => Some(
+            hex::decode(token)
+                .map_err(|_| S3Error::invalid_argument("invalid continuation-token"))?,
+        ),
+        None => query
+            .get("start-after")
+            .map(|start_after| start_after.as_bytes().to_vec()),
+    };
+
+    let list = ctx
+        .list_objects(
+            address,
+            ListParams {
+                prefix: prefix.as_bytes().to_vec(),
+                delimiter: delimiter.as_bytes().to_vec(),
+                start_key,
+                limit: max_keys,
+            },
+        )
+        .await?;
+
+    let body = list_objects_xml(
+        &bucket,
+        &prefix,
+        &delimiter,
+        max_keys,
+        query.get("continuation-token"),
+        &list,
+    );
+    Ok(xml_reply(StatusCode::OK, body))
+}
+
+fn list_objects_xml(
+    bucket: &str,
+    prefix: &str,
+    delimiter: &str,
+    max_keys: u64,
+    continuation_token: Option<&String>,
+    list: &ListObjectsReturn,
+) -> String {
+    let mut xml = format!("<ListBucketResult xmlns=\"{}\">", S3_NAMESPACE);
+    xml.push_str(&format!("<Name>{}</Name>", xml_escape(bucket)));
+    xml.push_str(&format!("<Prefix>{}</Prefix>", xml_escape(prefix)));
+    if !delimiter.is_empty() {
+        xml.push_str(&format!("<Delimiter>{}</Delimiter>", xml_escape(delimiter)));
+    }
+    xml.push_str(&format!("<MaxKeys>{}</MaxKeys>", max_keys));
+    xml.push_str(&format!(
+        "<KeyCount>{}</KeyCount>",
+        list.objects.len() + list.common_prefixes.len()
+    ));
+    if let Some(token) = continuation_token {
+        xml.push_str(&format!(
+            "<ContinuationToken>{}</ContinuationToken>",
+            xml_escape(token)
+        ));
+    }
+    match &list.next_key {
+        Some(next_key) => {
+            xml.push_str("<IsTruncated>true</IsTruncated>");
+            xml.push_str(&format!(
+                "<NextContinuationToken>{}</NextContinuationToken>",
+                hex::encode(next_key)
+            ));
+        }
+        None => xml.push_str("<IsTruncated>false</IsTruncated>"),
+    }
+    for (key, object) in list.objects.iter() {
+        let hash = Hash::from_bytes(object.hash.0);
+        xml.push_str("<Contents>");
+        xml.push_str(&format!(
+            "<Key>{}</Key>",
+            xml_escape(&String::from_utf8_lossy(key))
+        ));
+        xml.push_str(&format!("<LastModified>{}</LastModified>", LAST_MODIFIED));
+        xml.push_str(&format!("<ETag>{}</ETag>", xml_escape(&etag(&hash))));
+        xml.push_str(&format!("<Size>{}</Size>", object.size));
+        xml.push_str("<StorageClass>STANDARD</StorageClass>");
+        xml.push_str("</Contents>");
+    }
+    for common_prefix in list.common_prefixes.iter() {
+        xml.push_str(&format!(
+            "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+            xml_escape(&String::from_utf8_lossy(common_prefix))
+        ));
+    }
+    xml.push_str("</ListBucketResult>");
+    xml
+}
+
+async fn handle_get_object(
+    bucket: String,
+    tail: Tail,
+    method: String,
+    range: Option<String>,
+    ctx: S3Context,
+) -> Result<impl Reply, Rejection> {
+    let start_time = Instant::now();
+    let (address, key) = parse_path(&bucket, &tail)?;
+    let object = ctx.get_object(address, key).await?;
+    let mut response =
+        object_response(&object, &method, range, ctx.iroh.clone(), start_time).await?;
+    add_object_headers(response.headers_mut(), &object);
+    Ok(response)
+}
+
+async fn handle_put_object<S, B>(
+    bucket: String,
+    tail: Tail,
+    query: HashMap<String, String>,
+    headers: HeaderMap,
+    ctx: S3Context,
+    body: S,
+) -> Result<warp::reply::Response, Rejection>
+where
+    S: Stream<Item = Result<B, warp::Error>> + Send + 'static,
+    B: Buf + Send,
+{
+    let (address, key) = parse_path(&bucket, &tail)?;
+
+    // UploadPart
+    if let (Some(upload_id), Some(part_number)) = (query.get("uploadId"), query.get("partNumber")) {
+        let part_number = part_number
+            .parse::<u32>()
+            .map_err(|_| S3Error::invalid_argument("invalid partNumber"))?;
+        let part = ctx
+            .sessions
+            .put_part(&ctx.iroh, upload_id, part_number, ctx.limits, body)
+            .await?;
+        let mut response = warp::reply::reply().into_response();
+        if let Ok(etag) = HeaderValue::from_str(&format!("\"{}\"", part.hash)) {
+            response.headers_mut().insert("ETag", etag);
+        }
+        return Ok(response);
+    }
+
+    // CopyObject
+    if let Some(copy_source) = headers.get("x-amz-copy-source") {
+        let copy_source = copy_source
+            .to_str()
+            .map_err(|_| S3Error::invalid_argument("invalid x-amz-copy-source"))?;
+        let (source_bucket, source_key) = parse_copy_source(copy_source)?;
+        let replace = headers
+            .get("x-amz-metadata-directive")
+            .map(|directive| directive.as_bytes().eq_ignore_ascii_case(b"REPLACE"))
+            .unwrap_or_default();
+        let metadata = replace.then(|| metadata_from_headers(&headers));
+        let object = ctx
+            .copy_object(source_bucket, source_key, address, key, metadata)
+            .await?;
+        let hash = Hash::from_bytes(object.hash.0);
+        let body = format!(
+            "<CopyObjectResult><LastModified>{}</LastModified><ETag>{}</ETag></CopyObjectResult>",
+            LAST_MODIFIED,
+            xml_escape(&etag(&hash))
+        );
+        return Ok(xml_reply(StatusCode::OK, body));
+    }
+
+    // PutObject
+    let start_time = Instant::now();
+    ctx.signer()?;
+    let (stream, received) = limited_body_stream(body, ctx.max_object_size);
+    let hash = store_stream(&ctx.iroh, Box::pin(stream), SetTagOption::Auto).await?;
+    let size = received.load(Ordering::Relaxed);
+    let recovery_hash = ctx.entangle(hash).await?;
+    ctx.add_object(
+        address,
+        key,
+        hash,
+        recovery_hash,
+        size,
+        metadata_from_headers(&headers),
+    )
+    .await?;
+
+    COUNTER_BYTES_UPLOADED.inc_by(size);
+    COUNTER_BLOBS_UPLOADED.inc();
+    HISTOGRAM_UPLOAD_TIME.observe(start_time.elapsed().as_secs_f64());
+
+    let mut response = warp::reply::reply().into_response();
+    if let Ok(etag) = HeaderValue::from_str(&etag(&hash)) {
+        response.headers_mut().insert("ETag", etag);
+    }
+    Ok(response)
+}
+
+/// Parse `x-amz-copy-source`, which looks like `/{bucket}/{key}` with an optional `?versionId=`.
+fn parse_copy_source(copy_source: &str) -> Result<(Address, Vec<u8>), Rejection> {
+    let copy_source = copy_source.trim_start_matches('/');
+    let copy_source = copy_source
+        .split_once('?')
+        .map(|(path, _)| path)
+        .unwrap_or(copy_source);
+    let (bucket, key) = copy_source
+        .split_once('/')
+        .ok_or_else(|| S3Error::invalid_argument("invalid x-amz-copy-source"))?;
+    let key = percent_decode(key)?;
+    if key.is_empty() {
+        return Err(S3Error::invalid_argument("invalid x-amz-copy-source"));
+    }
+    Ok((parse_bucket(bucket)?, key))
+}
+
+async fn handle_post_object(
+    bucket: String,
+    tail: Tail,
+    query: HashMap<String, String>,
+    headers: HeaderMap,
+    ctx: S3Context,
+    body: Bytes,
+) -> Result<warp::reply::Response, Rejection> {
+    let (address, key) = parse_path(&bucket, &tail)?;
+    let key_str = String::from_utf8_lossy(&key).to_string();
+
+    // CreateMultipartUpload
+    if query.contains_key("uploads") {
+        ctx.signer()?;
+        let upload_id = ctx.sessions.create().await;
+        // Remember the metadata given on creation until the upload is completed.
+        ctx.sessions
+            .set_metadata(&upload_id, metadata_from_headers(&headers))
+            .await?;
+        let body = format!(
+            "<InitiateMultipartUploadResult xmlns=\"{}\"><Bucket>{}</Bucket><Key>{}</Key><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+            S3_NAMESPACE,
+            xml_escape(&bucket),
+            xml_escape(&key_str),
+            upload_id
+        );
+        return Ok(xml_reply(StatusCode::OK, body));
+    }
+
+    // CompleteMultipartUpload
+    if let Some(upload_id) = query.get("uploadId") {
+        let body = std::str::from_utf8(body.chunk())
+            .map_err(|_| S3Error::new(StatusCode::BAD_REQUEST, "MalformedXML", "invalid body"))?;
+        let parts = parse_complete_multipart_upload(body)?;
+        let metadata = ctx.sessions.metadata(upload_id).await?;
+        let completed = ctx
+            .sessions
+            .complete(&ctx.iroh, upload_id, ctx.max_object_size, &parts)
+            .await?;
+        let recovery_hash = parse_blob_hash(&completed.metadata_hash)?;
+        ctx.add_object(
+            address,
+            key,
+            completed.hash,
+            recovery_hash,
+            completed.size,
+            metadata,
+        )
+        .await?;
+        let body = format!(
+            "<CompleteMultipartUploadResult xmlns=\"{}\"><Bucket>{}</Bucket><Key>{}</Key><ETag>{}</ETag></CompleteMultipartUploadResult>",
+            S3_NAMESPACE,
+            xml_escape(&bucket),
+            xml_escape(&key_str),
+            xml_escape(&etag(&completed.hash))
+        );
+        return Ok(xml_reply(StatusCode::OK, body));
+    }
+
+    Err(S3Error::new(
+        StatusCode::NOT_IMPLEMENTED,
+        "NotImplemented",
+        "unsupported operation",
+    ))
+}
+
+/// Parse the part list of a `CompleteMultipartUpload` request.
+fn parse_complete_multipart_upload(body: &str) -> Result<Vec<CompletedPart>, Rejection> {
+    let malformed = || {
+        S3Error::new(
+            StatusCode::BAD_REQUEST,
+            "MalformedXML",
+            "malformed part list",
+        )
+    };
+    let mut parts = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("<Part>") {
+        let end = rest[start..].find("</Part>").ok_or_else(malformed)? + start;
+        let part = &rest[start..end];
+        let part_number = xml_element(part, "PartNumber")
+            .and_then(|n| n.trim().parse::<u32>().ok())
+            .ok_or_else(malformed)?;
+        let etag = xml_element(part, "ETag").ok_or_else(malformed)?;
+        let hash = etag
+            .replace("&quot;", "")
+            .replace("&#34;", "")
+            .trim()
+            .trim_matches('"')
+            .to_string();
+        parts.push(CompletedPart { part_number, hash });
+        rest = &rest[end..];
+    }
+    Ok(parts)
+}
+
+/// Extract the text of the first `<name>` element.
+fn xml_element<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)? + start;
+    Some(&xml[start..end])
+}
+
+async fn handle_delete_object(
+    bucket: String,
+    tail: Tail,
+    query: HashMap<String, String>,
+    ctx: S3Context,
+) -> Result<impl Reply, Rejection> {
+    let (address, key) = parse_path(&bucket, &tail)?;
+
+    // AbortMultipartUpload
+    if let Some(upload_id) = query.get("uploadId") {
+        ctx.sessions.abort(&ctx.iroh, upload_id).await?;
+        return Ok(warp::reply::with_status(
+            warp::reply::reply(),
+            StatusCode::NO_CONTENT,
+        ));
+    }
+
+    // DeleteObject
+    ctx.delete_object(address, key).await?;
+    Ok(warp::reply::with_status(
+        warp::reply::reply(),
+        StatusCode::NO_CONTENT,
+    ))
+}
+
+async fn handle_s3_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
+    let (status, code, message) = if let Some(e) = err.find::<S3Error>() {
+        (e.status, e.code, e.message.clone())
+    } else if err.is_not_found() {
+        (
+            StatusCode::NOT_FOUND,
+            "NoSuchKey",
+            "The specified key does not exist.".to_string(),
+        )
+    } else if err.find::<NotFound>().is_some() {
+        (
+            StatusCode::NOT_FOUND,
+            "NoSuchUpload",
+            "The specified upload does not exist.".to_string(),
+        )
+    } else if let Some(e) = err.find::<BadRequest>() {
+        (StatusCode::BAD_REQUEST, "InvalidRequest", e.message.clone())
+    } else if err.find::<warp::reject::PayloadTooLarge>().is_some() {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "EntityTooLarge",
+            "Payload too large".to_string(),
+        )
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+        (
+            StatusCode::METHOD_NOT_ALLOWED,
+            "MethodNotAllowed",
+            "The specified method is not allowed.".to_string(),
+        )
+    } else {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "InternalError",
+            format!("{:?}", err),
+        )
+    };
+
+    let body = format!(
+        "<Error><Code>{}</Code><Message>{}</Message></Error>",
+        code,
+        xml_escape(&message)
+    );
+    Ok(xml_reply(status, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fendermint_actor_bucket::ObjectState;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("foo/bar").unwrap(), b"foo/bar".to_vec());
+        assert_eq!(
+            percent_decode("foo%20bar%2Fbaz").unwrap(),
+            b"foo bar/baz".to_vec()
+        );
+        assert!(percent_decode("foo%2").is_err());
+        assert!(percent_decode("foo%zz").is_err());
+    }
+
+    #[test]
+    fn test_metadata_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-amz-meta-foo", HeaderValue::from_static("bar"));
+        headers.insert("Content-Type", HeaderValue::from_static("text/plain"));
+        headers.insert("x-amz-acl", HeaderValue::from_static("private"));
+        let metadata = metadata_from_headers(&headers);
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata.get("foo").unwrap(), "bar");
+        assert_eq!(metadata.get("content-type").unwrap(), "text/plain");
+    }
+
+    #[test]
+    fn test_add_object_headers() {
+        let mut metadata = HashMap::new();
+        metadata.insert("foo".to_string(), "bar".to_string());
+        metadata.insert("content-type".to_string(), "text/plain".to_string());
+        let object = Object {
+            hash: BlobHash(*Hash::new(b"hello world").as_bytes()),
+            recovery_hash: BlobHash::default(),
+            size: 11,
+            expiry: 100,
+            metadata,
+        };
+        let mut headers = HeaderMap::new();
+        add_object_headers(&mut headers, &object);
+        assert_eq!(headers.get("x-amz-meta-foo").unwrap(), "bar");
+        assert!(headers.get("x-amz-meta-content-type").is_none());
+        assert_eq!(
+            headers.get("ETag").unwrap().to_str().unwrap(),
+            etag(&Hash::new(b"hello world"))
+        );
+    }
+
+    #[test]
+    fn test_parse_copy_source() {
+        let bucket = "t2mnd5jkuvmsaf457ympnf3monalh3vothdd5njoy";
+        let (address, key) = parse_copy_source(&format!("/{}/foo%2Fbar", bucket)).unwrap();
+        assert_eq!(address, parse_address(bucket).unwrap());
+        assert_eq!(key, b"foo/bar".to_vec());
+
+        let (_, key) = parse_copy_source(&format!("{}/foo?versionId=1", bucket)).unwrap();
+        assert_eq!(key, b"foo".to_vec());
+
+        assert!(parse_copy_source(bucket).is_err());
+        assert!(parse_copy_source(&format!("{}/", bucket)).is_err());
+    }
+
+    #[test]
+    fn test_parse_complete_multipart_upload() {
+        let body = r#"<CompleteMultipartUpload xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+            <Part><ETag>"abc"</ETag><PartNumber>1</PartNumber></Part>
+            <Part><PartNumber>2</PartNumber><ETag>&quot;def&quot;</ETag></Part>
+        </CompleteMultipartUpload>"#;
+        let parts = parse_complete_multipart_upload(body).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].part_number, 1);
+        assert_eq!(parts[0].hash, "abc");
+        assert_eq!(parts[1].part_number, 2);
+        assert_eq!(parts[1].hash, "def");
+
+        assert!(parse_complete_multipart_upload("<Part><ETag>x</ETag></Part>").is_err());
+    }
+
+    #[test]
+    fn test_list_objects_xml() {
+        let hash = Hash::new(b"hello world");
+        let list = ListObjectsReturn {
+            objects: vec![(
+                b"foo/a&b".to_vec(),
+                ObjectState {
+                    hash: BlobHash(*hash.as_bytes()),
+                    size: 11,
+                    metadata: HashMap::new(),
+                },
+            )],
+            common_prefixes: vec![b"foo/bar/".to_vec()],
+            next_key: Some(b"foo/c".to_vec()),
+        };
+        let xml = list_objects_xml("bucket", "foo/", "/", 2, None, &list);
+        assert!(xml.contains("<Key>foo/a&amp;b</Key>"));
+        assert!(xml.contains("<Size>11</Size>"));
+        assert!(xml.contains("<KeyCount>2</KeyCount>"));
+        assert!(xml.contains("<CommonPrefixes><Prefix>foo/bar/</Prefix></CommonPrefixes>"));
+        assert!(xml.contains("<IsTruncated>true</IsTruncated>"));
+        assert!(xml.contains(&format!(
+            "<NextContinuationToken>{}</NextContinuationToken>",
+            hex::encode(b"foo/c")
+        )));
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2025 Recall Contributors
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use fendermint_rpc::{
+    client::{BoundFendermintClient, FendermintClient},
+    message::{GasParams, SignedMessageFactory},
+    tx::{BoundClient, TxClient, TxCommit},
+    QueryClient,
+};
+use fendermint_vm_actor_interface::eam::EthAddress;
+use fendermint_vm_message::query::FvmQueryHeight;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::{address::Address, chainid::ChainID, econ::TokenAmount, MethodNum};
+use tokio::sync::Mutex;
+
+/// Signs bucket transactions on behalf of API clients.
+#[derive(Clone)]
+pub(super) struct Signer {
+    pub address: Address,
+    gas_limit: u64,
+    client: Arc<Mutex<BoundFendermintClient>>,
+}
+
+impl Signer {
+    pub async fn new(
+        client: FendermintClient,
+        secret_key: &Path,
+        gas_limit: u64,
+    ) -> anyhow::Result<Self> {
+        let sk = SignedMessageFactory::read_secret_key(secret_key)?;
+        let address = Address::from(EthAddress::from(sk.public_key()));
+        let sequence = sequence(&client, &address).await?;
+        let chain_id = client
+            .state_params(FvmQueryHeight::default())
+            .await
+            .context("failed to get state params")?
+            .value
+            .chain_id;
+        let mf = SignedMessageFactory::new(sk, address, sequence, ChainID::from(chain_id));
+        Ok(Self {
+            address,
+            gas_limit,
+            client: Arc::new(Mutex::new(client.bind(mf))),
+        })
+    }
+
+    /// Send a transaction and wait for it to be delivered.
+    ///
+    /// Transactions are sent one at a time to keep the sequence in order.
+    pub async fn transaction(
+        &self,
+        to: Address,
+        method_num: MethodNum,
+        params: RawBytes,
+    ) -> anyhow::Result<RawBytes> {
+        let mut client = self.client.lock().await;
+        let base_fee = client
+            .state_params(FvmQueryHeight::default())
+            .await
+            .context("failed to get state params")?
+            .value
+            .base_fee;
+        let gas_params = GasParams {
+            gas_limit: self.gas_limit,
+            gas_fee_cap: base_fee,
+            gas_premium: TokenAmount::default(),
+        };
+        let res = TxClient::<TxCommit>::transaction(
+            &mut *client,
+            to,
+            method_num,
+            params,
+            TokenAmount::default(),
+            gas_params,
+        )
+        .await?;
+
+        if res.response.check_tx.code.is_err() {
+            // The sequence was not consumed, so we have to resync it.
+            let sequence = sequence(&*client, &self.address).await?;
+            client.message_factory_mut().set_sequence(sequence);
+            return Err(anyhow!("check failed: {}", res.response.check_tx.info));
+        }
+        if res.response.deliver_tx.code.is_err() {
+            return Err(anyhow!("{}", res.response.deliver_tx.info));
+        }
+        Ok(res.return_data.unwrap_or_default())
+    }
+}
+
+/// Get the next sequence number (nonce) of an account.
+async fn sequence(client: &impl QueryClient, address: &Address) -> anyhow::Result<u64> {
+    let state = client
+        .actor_state(address, FvmQueryHeight::default())
+        .await
+        .context("failed to get actor state")?;
+    Ok(state
+        .value
+        .map(|(_, state)| state.sequence)
+        .unwrap_or_default())
+}
@@ -4,12 +4,12 @@
 use std::sync::Arc;
 
 use anyhow::{anyhow, bail, Context};
-use async_stm::atomically_or_err;
+use async_stm::{atomically, atomically_or_err};
 use fendermint_abci::ApplicationService;
 use fendermint_app::ipc::{AppParentFinalityQuery, AppVote};
 use fendermint_app::{App, AppConfig, AppStore, BitswapBlockstore};
 use fendermint_app_settings::AccountKind;
-use fendermint_crypto::SecretKey;
+use fendermint_crypto::{PublicKey, SecretKey};
 use fendermint_rocksdb::{blockstore::NamespaceBlockstore, namespaces, RocksDb, RocksDbConfig};
 use fendermint_vm_actor_interface::eam::EthAddress;
 use fendermint_vm_interpreter::chain::ChainEnv;
@@ -17,25 +17,34 @@ use fendermint_vm_interpreter::fvm::observe::register_metrics as register_interp
 use fendermint_vm_interpreter::fvm::upgrades::UpgradeScheduler;
 use fendermint_vm_interpreter::{
     bytes::{BytesMessageInterpreter, ProposalPrepareMode},
-    chain::{BlobPool, ChainMessageInterpreter, CheckpointPool, ReadRequestPool},
-    fvm::{Broadcaster, FvmMessageInterpreter, ValidatorContext},
+    chain::{BlobPool, ChainMessageInterpreter, ChallengePool, CheckpointPool, ReadRequestPool},
+    fvm::{
+        aggregation::CheckpointSignaturePool, Broadcaster, FvmMessageInterpreter, ValidatorContext,
+    },
     signed::SignedMessageInterpreter,
 };
+use fendermint_vm_iroh_resolver::challenge::{
+    verify_answer, verify_answer_signature, ChallengeAnswers, ChallengeResolver,
+};
 use fendermint_vm_iroh_resolver::iroh::IrohResolver;
 use fendermint_vm_resolver::ipld::IpldResolver;
 use fendermint_vm_snapshot::{SnapshotManager, SnapshotParams};
 use fendermint_vm_topdown::observe::register_metrics as register_topdown_metrics;
 use fendermint_vm_topdown::proxy::{IPCProviderProxy, IPCProviderProxyWithLatency};
+use fendermint_vm_topdown::quorum::QuorumParentProxy;
 use fendermint_vm_topdown::sync::launch_polling_syncer;
 use fendermint_vm_topdown::voting::{publish_vote_loop, Error as VoteError, VoteTally};
 use fendermint_vm_topdown::{
-    CachedFinalityProvider, IPCBlobFinality, IPCParentFinality, IPCReadRequestClosed, Toggle,
+    CachedFinalityProvider, IPCBlobFinality, IPCCheckpointSignature, IPCParentFinality,
+    IPCReadRequestClosed, Toggle,
 };
 use fvm_shared::address::{current_network, Address, Network};
-use ipc_ipld_resolver::{Event as ResolverEvent, VoteRecord};
+use ipc_api::subnet_id::SubnetID;
+use ipc_ipld_resolver::{Event as ResolverEvent, ValidatorKey, VoteRecord};
 use ipc_observability::{emit, observe::register_metrics as register_default_metrics};
 use ipc_provider::config::subnet::{EVMSubnet, SubnetConfig};
 use ipc_provider::IpcProvider;
+use tendermint_rpc::Url;
 use tokio::sync::broadcast::error::RecvError;
 use tower::ServiceBuilder;
 use tracing::{debug, error, info, warn};
@@ -53,6 +62,11 @@ cmd! {
     // this env var must be set for the blobs_syscall to work. the CLI has a default and accepts
     // an override via the env variable, but it doesn't require it's set, so we ensure it here
     std::env::set_var("IROH_RPC_ADDR", self.iroh_addr.clone());
+    // failed deletions of iroh tags are queued here, so they are retried across restarts
+    std::env::set_var(
+        iroh_manager::tags::ENV_TAG_QUEUE_DIR,
+        settings.data_dir().join("iroh-tag-queue"),
+    );
     run(settings, self.iroh_addr.clone()).await
   }
 }
@@ -124,22 +138,28 @@ async fn run(settings: Settings, iroh_addr: String) -> anyhow::Result<()> {
         libp2p::identity::Keypair::from(kp)
     });
 
-    let validator_ctx = validator.map(|(sk, addr)| {
-        // For now we are using the validator key for submitting transactions.
-        // This allows us to identify transactions coming from empowered validators, to give priority to protocol related transactions.
-        let broadcaster = Broadcaster::new(
-            tendermint_client.clone(),
-            addr,
-            sk.clone(),
-            settings.fvm.gas_fee_cap.clone(),
-            settings.fvm.gas_premium.clone(),
-            settings.fvm.gas_overestimation_rate,
-        )
-        .with_max_retries(settings.broadcast.max_retries)
-        .with_retry_delay(settings.broadcast.retry_delay);
+    let (checkpoint_signatures, own_checkpoint_signatures) = CheckpointSignaturePool::new();
+
+    let validator_ctx = validator
+        .map(|(sk, addr)| {
+            // For now we are using the validator key for submitting transactions.
+            // This allows us to identify transactions coming from empowered validators, to give priority to protocol related transactions.
+            let broadcaster = Broadcaster::new(
+                tendermint_client.clone(),
+                addr,
+                sk.clone(),
+                settings.fvm.gas_fee_cap.clone(),
+                settings.fvm.gas_premium.clone(),
+                settings.fvm.gas_overestimation_rate,
+            )
+            .with_max_retries(settings.broadcast.max_retries)
+            .with_retry_delay(settings.broadcast.retry_delay);
 
-        ValidatorContext::new(sk, addr, broadcaster)
-    });
+            ValidatorContext::new(sk, addr, broadcaster)
+                .with_checkpoint_aggregation(checkpoint_signatures.clone())
+        })
+        .transpose()
+        .context("failed to set up checkpoint signature aggregation")?;
 
     let testing_settings = match settings.testing.as_ref() {
         Some(_) if current_network() == Network::Mainnet => {
@@ -177,6 +197,8 @@ async fn run(settings: Settings, iroh_addr: String) -> anyhow::Result<()> {
     let checkpoint_pool = CheckpointPool::new();
     let blob_pool = BlobPool::new();
     let read_request_pool = ReadRequestPool::new();
+    let challenge_pool = ChallengePool::new();
+    let challenge_answers = ChallengeAnswers::new();
     let parent_finality_votes = VoteTally::empty();
 
     let topdown_enabled = settings.topdown_enabled();
@@ -242,6 +264,25 @@ async fn run(settings: Settings, iroh_addr: String) -> anyhow::Result<()> {
             info!("parent finality vote gossip disabled");
         }
 
+        if let Some(key) = validator_keypair.clone() {
+            info!("starting the checkpoint signature gossip loop...");
+            let client = client.clone();
+            let own_subnet_id = own_subnet_id.clone();
+            let checkpoint_signatures = checkpoint_signatures.clone();
+            let republish_interval = settings.ipc.vote_timeout;
+            tokio::spawn(async move {
+                publish_checkpoint_signatures(
+                    own_checkpoint_signatures,
+                    checkpoint_signatures,
+                    republish_interval,
+                    key,
+                    own_subnet_id,
+                    client,
+                )
+                .await
+            });
+        }
+
         if let Some(key) = validator_keypair {
             // Blob resolver
             let iroh_resolver = IrohResolver::new(
@@ -264,24 +305,48 @@ async fn run(settings: Settings, iroh_addr: String) -> anyhow::Result<()> {
                 read_request_pool.queue(),
                 settings.resolver.retry_delay,
                 parent_finality_votes.clone(),
-                key,
-                own_subnet_id,
+                key.clone(),
+                own_subnet_id.clone(),
                 |hash, _| AppVote::ReadRequestClosed(IPCReadRequestClosed::new(hash)),
                 read_request_pool.results(),
             );
 
             info!("starting the read request resolver...");
             tokio::spawn(async move { read_request_resolver.run().await });
+
+            // Storage challenge resolver
+            let challenge_resolver = ChallengeResolver::new(
+                client.clone(),
+                challenge_pool.queue(),
+                settings.resolver.retry_delay,
+                challenge_answers.clone(),
+                key,
+                own_subnet_id,
+                AppVote::ChallengeAnswer,
+            );
+
+            info!("starting the challenge resolver...");
+            tokio::spawn(async move { challenge_resolver.run().await });
         } else {
             info!("iroh Resolver disabled.");
             info!("read request resolver disabled.");
+            info!("challenge resolver disabled.");
         }
 
         info!("subscribing to gossip...");
         let rx = service.subscribe();
         let parent_finality_votes = parent_finality_votes.clone();
+        let challenge_answers = challenge_answers.clone();
+        let checkpoint_signatures = checkpoint_signatures.clone();
         tokio::spawn(async move {
-            dispatch_resolver_events(rx, parent_finality_votes, topdown_enabled).await;
+            dispatch_resolver_events(
+                rx,
+                parent_finality_votes,
+                challenge_answers,
+                checkpoint_signatures,
+                topdown_enabled,
+            )
+            .await;
         });
 
         info!("starting the IPLD Resolver Service...");
@@ -314,10 +379,7 @@ async fn run(settings: Settings, iroh_addr: String) -> anyhow::Result<()> {
             config = config.with_max_cache_blocks(v);
         }
 
-        let ipc_provider = {
-            let p = make_ipc_provider_proxy(&settings)?;
-            Arc::new(IPCProviderProxyWithLatency::new(p))
-        };
+        let ipc_provider = Arc::new(make_parent_proxy(&settings)?);
 
         let finality_provider =
             CachedFinalityProvider::uninitialized(config.clone(), ipc_provider.clone()).await?;
@@ -373,8 +435,11 @@ async fn run(settings: Settings, iroh_addr: String) -> anyhow::Result<()> {
             blob_concurrency: settings.blob_concurrency,
             read_request_pool,
             read_request_concurrency: settings.read_request_concurrency,
+            challenge_pool,
+            challenge_answers,
             blob_metrics_interval: settings.blob_metrics_interval,
             blob_queue_gas_limit: settings.blob_queue_gas_limit,
+            checkpoint_signatures,
         },
         snapshots,
     )?;
@@ -487,7 +552,34 @@ async fn make_resolver_service(
     Ok(service)
 }
 
-fn make_ipc_provider_proxy(settings: &Settings) -> anyhow::Result<IPCProviderProxy> {
+/// Query every configured parent endpoint, accepting what a quorum of them agree on.
+fn make_parent_proxy(
+    settings: &Settings,
+) -> anyhow::Result<QuorumParentProxy<IPCProviderProxyWithLatency>> {
+    let topdown_config = settings.ipc.topdown_config()?;
+    let endpoints = std::iter::once(&topdown_config.parent_http_endpoint)
+        .chain(topdown_config.parent_http_endpoints.iter())
+        .map(|url| {
+            let p = make_ipc_provider_proxy(settings, url)?;
+            Ok((url.to_string(), IPCProviderProxyWithLatency::new(p)))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let quorum = topdown_config.parent_quorum.unwrap_or_else(|| {
+        QuorumParentProxy::<IPCProviderProxyWithLatency>::majority(endpoints.len())
+    });
+    info!(
+        endpoints = endpoints.len(),
+        quorum, "init parent proxy with quorum reads"
+    );
+
+    QuorumParentProxy::new(endpoints, quorum)
+}
+
+fn make_ipc_provider_proxy(
+    settings: &Settings,
+    endpoint: &Url,
+) -> anyhow::Result<IPCProviderProxy> {
     let topdown_config = settings.ipc.topdown_config()?;
     let subnet = ipc_provider::config::Subnet {
         id: settings
@@ -496,7 +588,7 @@ fn make_ipc_provider_proxy(settings: &Settings) -> anyhow::Result<IPCProviderPro
             .parent()
             .ok_or_else(|| anyhow!("subnet has no parent"))?,
         config: SubnetConfig::Fevm(EVMSubnet {
-            provider_http: topdown_config.parent_http_endpoint.to_string().parse()?,
+            provider_http: endpoint.to_string().parse()?,
             provider_timeout: topdown_config.parent_http_timeout,
             auth_token: topdown_config.parent_http_auth_token.as_ref().cloned(),
             registry_addr: topdown_config.parent_registry,
@@ -575,9 +667,71 @@ fn to_address(sk: &SecretKey, kind: &AccountKind) -> anyhow::Result<Address> {
     }
 }
 
+/// Gossip our own signatures of bottom-up checkpoints to be aggregated by the other validators.
+///
+/// Signatures of checkpoints without an aggregate are re-published after `republish_interval`,
+/// so validators who missed them can still reach a quorum.
+async fn publish_checkpoint_signatures(
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<IPCCheckpointSignature>,
+    checkpoint_signatures: CheckpointSignaturePool,
+    republish_interval: std::time::Duration,
+    key: libp2p::identity::Keypair,
+    subnet_id: SubnetID,
+    client: ipc_ipld_resolver::Client<AppVote>,
+) {
+    let mut republish = tokio::time::interval(republish_interval);
+    republish.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The first tick completes immediately.
+    republish.tick().await;
+
+    loop {
+        let sigs = tokio::select! {
+            sig = rx.recv() => match sig {
+                Some(sig) => vec![sig],
+                None => return,
+            },
+            _ = republish.tick() => checkpoint_signatures.pending_own_signatures(),
+        };
+        for sig in sigs {
+            publish_checkpoint_signature(&key, &subnet_id, &client, sig);
+        }
+    }
+}
+
+fn publish_checkpoint_signature(
+    key: &libp2p::identity::Keypair,
+    subnet_id: &SubnetID,
+    client: &ipc_ipld_resolver::Client<AppVote>,
+    sig: IPCCheckpointSignature,
+) {
+    let height = sig.height;
+    match VoteRecord::signed(key, subnet_id.clone(), AppVote::CheckpointSignature(sig)) {
+        Ok(vote) => {
+            if let Err(e) = client.publish_vote(vote) {
+                error!(
+                    height,
+                    error = e.to_string(),
+                    "failed to publish checkpoint signature"
+                );
+            } else {
+                debug!(height, "published checkpoint signature");
+            }
+        }
+        Err(e) => {
+            error!(
+                height,
+                error = e.to_string(),
+                "failed to sign checkpoint signature vote"
+            );
+        }
+    }
+}
+
 async fn dispatch_resolver_events(
     mut rx: tokio::sync::broadcast::Receiver<ResolverEvent<AppVote>>,
     parent_finality_votes: VoteTally,
+    challenge_answers: ChallengeAnswers,
+    checkpoint_signatures: CheckpointSignaturePool,
     topdown_enabled: bool,
 ) {
     loop {
@@ -585,7 +739,14 @@ async fn dispatch_resolver_events(
             Ok(event) => match event {
                 ResolverEvent::ReceivedPreemptive(_, _) => {}
                 ResolverEvent::ReceivedVote(vote) => {
-                    dispatch_vote(*vote, &parent_finality_votes, topdown_enabled).await;
+                    dispatch_vote(
+                        *vote,
+                        &parent_finality_votes,
+                        &challenge_answers,
+                        &checkpoint_signatures,
+                        topdown_enabled,
+                    )
+                    .await;
                 }
             },
             Err(RecvError::Lagged(n)) => {
@@ -602,6 +763,8 @@ async fn dispatch_resolver_events(
 async fn dispatch_vote(
     vote: VoteRecord<AppVote>,
     parent_finality_votes: &VoteTally,
+    challenge_answers: &ChallengeAnswers,
+    checkpoint_signatures: &CheckpointSignaturePool,
     topdown_enabled: bool,
 ) {
     match vote.content {
@@ -710,5 +873,66 @@ async fn dispatch_vote(
                 }
             }
         }
+        AppVote::ChallengeAnswer(a) => {
+            debug!(id = a.id, hash = ?a.hash, "received vote for challenge answer");
+            // Only keep answers that carry a valid proof, signed by the validator that sent them,
+            // so that they pass the checks of the other validators when we propose them.
+            if !verify_answer(&a) {
+                warn!(id = a.id, hash = ?a.hash, "ignoring challenge answer with invalid proof");
+                return;
+            }
+            let public_key = libp2p::identity::PublicKey::from(vote.public_key.clone());
+            if !verify_answer_signature(&public_key, &a) {
+                warn!(id = a.id, hash = ?a.hash, "ignoring challenge answer with invalid signature");
+                return;
+            }
+            let validator = vote.public_key;
+            match atomically(|| {
+                if !parent_finality_votes.has_power(&validator)? {
+                    return Ok(None);
+                }
+                challenge_answers
+                    .add(validator.clone(), a.clone())
+                    .map(Some)
+            })
+            .await
+            {
+                Some(true) => debug!("challenge answer handled"),
+                Some(false) => {}
+                // Maybe arrived too early or too late, or spam
+                None => debug!("ignoring challenge answer from unpowered validator"),
+            }
+        }
+        AppVote::CheckpointSignature(s) => {
+            debug!(height = s.height, "received checkpoint signature");
+            let validator = match to_validator_public_key(&vote.public_key) {
+                Ok(validator) => validator,
+                Err(e) => {
+                    debug!(
+                        error = e.to_string(),
+                        "failed to handle checkpoint signature"
+                    );
+                    return;
+                }
+            };
+            match checkpoint_signatures.add_signature(&validator, s) {
+                Ok(true) => debug!("checkpoint signature handled"),
+                Ok(false) => {}
+                // Expected from validators of past or future power tables, or spam.
+                Err(e) => debug!(
+                    error = e.to_string(),
+                    "failed to handle checkpoint signature"
+                ),
+            }
+        }
     }
 }
+
+/// Convert the key which signed a vote to the public key of the validator.
+fn to_validator_public_key(key: &ValidatorKey) -> anyhow::Result<PublicKey> {
+    let key = libp2p::identity::PublicKey::from(key.clone())
+        .try_into_secp256k1()
+        .context("validator key is not secp256k1")?;
+    PublicKey::parse_slice(&key.to_bytes(), None)
+        .map_err(|e| anyhow!("invalid validator public key: {e:?}"))
+}
@@ -10,7 +10,10 @@ use fendermint_vm_interpreter::fvm::state::ipc::GatewayCaller;
 use fendermint_vm_interpreter::fvm::state::{FvmExecState, FvmStateParams};
 use fendermint_vm_interpreter::fvm::store::ReadOnlyBlockstore;
 use fendermint_vm_topdown::sync::ParentFinalityStateQuery;
-use fendermint_vm_topdown::{IPCBlobFinality, IPCParentFinality, IPCReadRequestClosed};
+use fendermint_vm_topdown::{
+    IPCBlobFinality, IPCChallengeAnswer, IPCCheckpointSignature, IPCParentFinality,
+    IPCReadRequestClosed,
+};
 use fvm_ipld_blockstore::Blockstore;
 use std::sync::Arc;
 
@@ -25,6 +28,10 @@ pub enum AppVote {
     BlobFinality(IPCBlobFinality),
     /// The validator considers a certain read request completed.
     ReadRequestClosed(IPCReadRequestClosed),
+    /// The validator proves that it holds a challenged chunk of a blob.
+    ChallengeAnswer(IPCChallengeAnswer),
+    /// The validator's partial BLS signature over a bottom-up checkpoint.
+    CheckpointSignature(IPCCheckpointSignature),
 }
 
 /// Queries the LATEST COMMITTED parent finality from the storage
@@ -8,6 +8,7 @@ use fendermint_vm_interpreter::fvm::{
     state::{BlockHash, FvmStateParams},
     FvmApplyRet, FvmCheckRet, FvmQueryRet,
 };
+use fendermint_vm_message::query::CheckpointAggregate;
 use fendermint_vm_message::signed::DomainHash;
 use fendermint_vm_snapshot::{SnapshotItem, SnapshotManifest};
 use fvm_shared::{address::Address, error::ExitCode, event::StampedEvent, ActorID};
@@ -308,6 +309,25 @@ pub fn to_query(ret: FvmQueryRet, block_height: BlockHeight) -> anyhow::Result<r
     Ok(res)
 }
 
+/// Respond with the aggregate signature of a checkpoint, if the validators have signed it.
+pub fn to_checkpoint_aggregate_query(
+    aggregate: Option<CheckpointAggregate>,
+) -> anyhow::Result<response::Query> {
+    let (exit_code, value) = match aggregate {
+        None => (ExitCode::USR_NOT_FOUND, Vec::new()),
+        Some(aggregate) => (ExitCode::OK, ipld_encode!(aggregate)),
+    };
+
+    let res = response::Query {
+        code: to_code(exit_code),
+        info: to_error_msg(exit_code).to_owned(),
+        value: value.into(),
+        ..Default::default()
+    };
+
+    Ok(res)
+}
+
 /// Project Genesis validators to Tendermint.
 /// TODO: the import is quite strange, `Validator` and `Power` are imported from `genesis` crate,
 /// TODO: which should be from a `type` or `validator` crate.
@@ -0,0 +1,169 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! BLS signatures over BLS12-381, with public keys in G1 and signatures in G2,
+//! which can be aggregated into a single signature signed by many validators.
+//!
+//! Aggregation is only safe against rogue key attacks if every public key comes
+//! with a proof of possession, which must be checked before the key is trusted.
+
+use anyhow::anyhow;
+use blst::min_pk;
+use blst::BLST_ERROR;
+
+use crate::SecretKey;
+
+/// Size of a compressed BLS public key.
+pub const BLS_PUBLIC_KEY_SIZE: usize = 48;
+/// Size of a compressed BLS signature.
+pub const BLS_SIGNATURE_SIZE: usize = 96;
+
+/// Domain separation tag for signing messages.
+const SIG_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// Domain separation tag for proofs of possession.
+const POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// Key info to derive BLS keys from validator keys.
+const KEY_INFO: &[u8] = b"fendermint/checkpoint-bls";
+
+/// A BLS secret key.
+pub struct BlsSecretKey(min_pk::SecretKey);
+
+impl BlsSecretKey {
+    /// Derive a BLS key from the validator's secp256k1 key,
+    /// so that validators don't have to manage any extra key material.
+    pub fn derive(sk: &SecretKey) -> anyhow::Result<Self> {
+        let ikm = sk.serialize();
+        min_pk::SecretKey::key_gen(ikm.as_ref(), KEY_INFO)
+            .map(Self)
+            .map_err(|e| anyhow!("failed to derive BLS key: {e:?}"))
+    }
+
+    /// The compressed public key.
+    pub fn public_key(&self) -> [u8; BLS_PUBLIC_KEY_SIZE] {
+        self.0.sk_to_pk().to_bytes()
+    }
+
+    /// Sign a message.
+    pub fn sign(&self, msg: &[u8]) -> [u8; BLS_SIGNATURE_SIZE] {
+        self.0.sign(msg, SIG_DST, &[]).to_bytes()
+    }
+
+    /// Sign our own public key to prove that we hold the secret key.
+    pub fn prove_possession(&self) -> [u8; BLS_SIGNATURE_SIZE] {
+        self.0.sign(&self.public_key(), POP_DST, &[]).to_bytes()
+    }
+}
+
+/// Check a signature of a single signer.
+pub fn verify(public_key: &[u8], msg: &[u8], signature: &[u8]) -> bool {
+    verify_with_dst(public_key, msg, signature, SIG_DST)
+}
+
+/// Check that the proof of possession belongs to the public key.
+pub fn verify_possession(public_key: &[u8], proof: &[u8]) -> bool {
+    verify_with_dst(public_key, public_key, proof, POP_DST)
+}
+
+/// Aggregate signatures over the same message into a single signature.
+pub fn aggregate(signatures: &[&[u8]]) -> anyhow::Result<[u8; BLS_SIGNATURE_SIZE]> {
+    let signatures = signatures
+        .iter()
+        .map(|s| parse_signature(s))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let signatures = signatures.iter().collect::<Vec<_>>();
+    min_pk::AggregateSignature::aggregate(&signatures, false)
+        .map(|agg| agg.to_signature().to_bytes())
+        .map_err(|e| anyhow!("failed to aggregate signatures: {e:?}"))
+}
+
+/// Check an aggregate signature over the same message signed by all the public keys,
+/// which must have had their proof of possession checked.
+pub fn verify_aggregate(public_keys: &[&[u8]], msg: &[u8], signature: &[u8]) -> bool {
+    let Ok(public_keys) = public_keys
+        .iter()
+        .map(|pk| parse_public_key(pk))
+        .collect::<anyhow::Result<Vec<_>>>()
+    else {
+        return false;
+    };
+    let Ok(signature) = parse_signature(signature) else {
+        return false;
+    };
+    let public_keys = public_keys.iter().collect::<Vec<_>>();
+    signature.fast_aggregate_verify(false, msg, SIG_DST, &public_keys) == BLST_ERROR::BLST_SUCCESS
+}
+
+fn verify_with_dst(public_key: &[u8], msg: &[u8], signature: &[u8], dst: &[u8]) -> bool {
+    match (parse_public_key(public_key), parse_signature(signature)) {
+        (Ok(pk), Ok(sig)) => {
+            sig.verify(false, msg, dst, &[], &pk, false) == BLST_ERROR::BLST_SUCCESS
+        }
+        _ => false,
+    }
+}
+
+/// Parse a public key, checking that it's a valid, non-infinity point in the subgroup.
+fn parse_public_key(bz: &[u8]) -> anyhow::Result<min_pk::PublicKey> {
+    min_pk::PublicKey::key_validate(bz).map_err(|e| anyhow!("invalid BLS public key: {e:?}"))
+}
+
+/// Parse a signature, checking that it's a valid point in the subgroup.
+fn parse_signature(bz: &[u8]) -> anyhow::Result<min_pk::Signature> {
+    min_pk::Signature::sig_validate(bz, true).map_err(|e| anyhow!("invalid BLS signature: {e:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn keys(n: usize) -> Vec<BlsSecretKey> {
+        let mut rng = StdRng::seed_from_u64(42);
+        (0..n)
+            .map(|_| BlsSecretKey::derive(&SecretKey::random(&mut rng)).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_derive_is_deterministic() {
+        let sk = SecretKey::random(&mut StdRng::seed_from_u64(1));
+        let a = BlsSecretKey::derive(&sk).unwrap();
+        let b = BlsSecretKey::derive(&sk).unwrap();
+        assert_eq!(a.public_key(), b.public_key());
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let keys = keys(2);
+        let sig = keys[0].sign(b"checkpoint");
+        assert!(verify(&keys[0].public_key(), b"checkpoint", &sig));
+        assert!(!verify(&keys[1].public_key(), b"checkpoint", &sig));
+        assert!(!verify(&keys[0].public_key(), b"other", &sig));
+
+        let pop = keys[0].prove_possession();
+        assert!(verify_possession(&keys[0].public_key(), &pop));
+        assert!(!verify_possession(&keys[1].public_key(), &pop));
+        // A proof of possession is not a valid signature over the public key.
+        assert!(!verify(&keys[0].public_key(), &keys[0].public_key(), &pop));
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let keys = keys(4);
+        let msg = b"checkpoint";
+        let sigs = keys.iter().map(|k| k.sign(msg)).collect::<Vec<_>>();
+        let pks = keys.iter().map(|k| k.public_key()).collect::<Vec<_>>();
+
+        let agg = aggregate(&sigs[..3].iter().map(|s| &s[..]).collect::<Vec<_>>()).unwrap();
+        let signers = pks[..3].iter().map(|pk| &pk[..]).collect::<Vec<_>>();
+        assert!(verify_aggregate(&signers, msg, &agg));
+
+        // Claiming a different set of signers fails.
+        let others = pks[1..].iter().map(|pk| &pk[..]).collect::<Vec<_>>();
+        assert!(!verify_aggregate(&others, msg, &agg));
+        assert!(!verify_aggregate(&signers, b"other", &agg));
+        assert!(aggregate(&[&[0u8; 3]]).is_err());
+    }
+}
@@ -7,6 +7,8 @@ use base64::{alphabet, Engine};
 use rand::Rng;
 use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
+pub mod bls;
+
 pub use libsecp256k1::{PublicKey, RecoveryId, Signature};
 
 /// A [`GeneralPurpose`] engine using the [`alphabet::STANDARD`] base64 alphabet
@@ -0,0 +1,49 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! IPC specific methods, which are not part of the Ethereum API.
+
+use anyhow::Context;
+use ethers_core::types as et;
+use fendermint_vm_message::query::{CheckpointAggregate, CHECKPOINT_AGGREGATE_QUERY_PATH};
+use fvm_shared::error::ExitCode;
+use jsonrpc_v2::Params;
+use tendermint_rpc::Client;
+
+use crate::error::error;
+use crate::{JsonRpcData, JsonRpcResult};
+
+/// Returns the aggregate BLS signature of the bottom-up checkpoint at a height,
+/// once the node has collected signatures from a quorum of validators.
+pub async fn get_checkpoint_aggregate<C>(
+    data: JsonRpcData<C>,
+    Params((height,)): Params<(et::U64,)>,
+) -> JsonRpcResult<Option<CheckpointAggregate>>
+where
+    C: Client + Sync + Send,
+{
+    let query = fvm_ipld_encoding::to_vec(&height.as_u64()).context("failed to encode height")?;
+
+    let res = data
+        .tm()
+        .abci_query(
+            Some(CHECKPOINT_AGGREGATE_QUERY_PATH.to_owned()),
+            query,
+            None,
+            false,
+        )
+        .await
+        .context("failed to query checkpoint aggregate")?;
+
+    if res.code.value() == ExitCode::USR_NOT_FOUND.value() {
+        return Ok(None);
+    }
+    if res.code.is_err() {
+        return error(ExitCode::new(res.code.value()), res.info);
+    }
+
+    let aggregate = fvm_ipld_encoding::from_slice(&res.value)
+        .context("failed to decode checkpoint aggregate")?;
+
+    Ok(Some(aggregate))
+}
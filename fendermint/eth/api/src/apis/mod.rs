@@ -11,6 +11,7 @@ use prometheus::{register_histogram_vec, HistogramVec};
 use std::marker::PhantomData;
 
 mod eth;
+mod ipc;
 mod net;
 mod web3;
 
@@ -132,11 +133,13 @@ pub fn register_methods(server: ServerBuilder<MapRouter>) -> ServerBuilder<MapRo
         sha3
     });
 
-    with_methods!(server, net, {
+    let server = with_methods!(server, net, {
         version,
         listening,
         peerCount
-    })
+    });
+
+    with_methods!(server, ipc, { getCheckpointAggregate })
 }
 
 /// Indicate whether a method requires a WebSocket connection.
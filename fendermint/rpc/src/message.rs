@@ -6,9 +6,13 @@ use std::path::Path;
 use anyhow::Context;
 use base64::Engine;
 use bytes::Bytes;
-use fendermint_actor_bucket::{GetParams, Method::GetObject};
+use fendermint_actor_blobs_shared::{params::GetBlobParams, Method::GetBlob};
+use fendermint_actor_bucket::{
+    GetParams, ListParams,
+    Method::{GetObject, ListObjects},
+};
 use fendermint_crypto::SecretKey;
-use fendermint_vm_actor_interface::{eam, evm};
+use fendermint_vm_actor_interface::{blobs::BLOBS_ACTOR_ADDR, eam, evm};
 use fendermint_vm_message::{chain::ChainMessage, signed::SignedMessage};
 use fvm_ipld_encoding::{BytesSer, RawBytes};
 use fvm_shared::{
@@ -113,6 +117,29 @@ impl MessageFactory {
         Ok(self.transaction(address, GetObject as u64, params, value, gas_params))
     }
 
+    /// List objects in a bucket. This will not create a transaction.
+    pub fn os_list(
+        &mut self,
+        address: Address,
+        params: ListParams,
+        value: TokenAmount,
+        gas_params: GasParams,
+    ) -> anyhow::Result<Message> {
+        let params = RawBytes::serialize(params)?;
+        Ok(self.transaction(address, ListObjects as u64, params, value, gas_params))
+    }
+
+    /// Get a blob from the blobs actor. This will not create a transaction.
+    pub fn blob_get(
+        &mut self,
+        params: GetBlobParams,
+        value: TokenAmount,
+        gas_params: GasParams,
+    ) -> anyhow::Result<Message> {
+        let params = RawBytes::serialize(params)?;
+        Ok(self.transaction(BLOBS_ACTOR_ADDR, GetBlob as u64, params, value, gas_params))
+    }
+
     pub fn fevm_call(
         &mut self,
         contract: Address,
@@ -175,6 +202,11 @@ impl SignedMessageFactory {
         self.inner.address()
     }
 
+    /// Set the sequence to an arbitrary value, e.g. to resync after a failed broadcast.
+    pub fn set_sequence(&mut self, sequence: u64) {
+        self.inner.set_sequence(sequence);
+    }
+
     /// Transfer tokens to another account.
     pub fn transfer(
         &mut self,
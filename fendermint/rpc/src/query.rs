@@ -3,7 +3,8 @@
 
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
-use fendermint_actor_bucket::{GetParams, Object};
+use fendermint_actor_blobs_shared::{params::GetBlobParams, state::Blob};
+use fendermint_actor_bucket::{GetParams, ListObjectsReturn, ListParams, Object};
 use fendermint_vm_actor_interface::system::SYSTEM_ACTOR_ADDR;
 use fvm_ipld_encoding::serde::Serialize;
 use fvm_shared::econ::TokenAmount;
@@ -23,8 +24,8 @@ use fendermint_vm_message::query::{
 };
 
 use crate::message::{GasParams, MessageFactory};
-use crate::response::decode_os_get;
 use crate::response::encode_data;
+use crate::response::{decode_blob_get, decode_os_get, decode_os_list};
 
 #[derive(Serialize, Debug, Clone)]
 /// The parsed value from a query, along with the height at which the query was performed.
@@ -155,6 +156,48 @@ pub trait QueryClient: Sync {
         Ok(return_data)
     }
 
+    /// List objects in a bucket without including a transaction on the blockchain.
+    async fn os_list_call(
+        &mut self,
+        address: Address,
+        params: ListParams,
+        value: TokenAmount,
+        gas_params: GasParams,
+        height: FvmQueryHeight,
+    ) -> anyhow::Result<ListObjectsReturn> {
+        let msg = MessageFactory::new(SYSTEM_ACTOR_ADDR, 0)
+            .os_list(address, params, value, gas_params)?;
+
+        let response = self.call(msg, height).await?;
+        if response.value.code.is_err() {
+            return Err(anyhow!("{}", response.value.info));
+        }
+        let return_data = decode_os_list(&response.value)
+            .context("error decoding data from deliver_tx in call")?;
+
+        Ok(return_data)
+    }
+
+    /// Get a blob from the blobs actor without including a transaction on the blockchain.
+    async fn blob_get_call(
+        &mut self,
+        params: GetBlobParams,
+        value: TokenAmount,
+        gas_params: GasParams,
+        height: FvmQueryHeight,
+    ) -> anyhow::Result<Option<Blob>> {
+        let msg = MessageFactory::new(SYSTEM_ACTOR_ADDR, 0).blob_get(params, value, gas_params)?;
+
+        let response = self.call(msg, height).await?;
+        if response.value.code.is_err() {
+            return Err(anyhow!("{}", response.value.info));
+        }
+        let return_data = decode_blob_get(&response.value)
+            .context("error decoding data from deliver_tx in call")?;
+
+        Ok(return_data)
+    }
+
     /// Run an ABCI query.
     async fn perform(&self, query: FvmQuery, height: FvmQueryHeight) -> anyhow::Result<AbciQuery>;
 }
@@ -3,7 +3,8 @@
 use anyhow::{anyhow, Context};
 use base64::Engine;
 use bytes::Bytes;
-use fendermint_actor_bucket::Object;
+use fendermint_actor_blobs_shared::state::Blob;
+use fendermint_actor_bucket::{ListObjectsReturn, Object};
 use fendermint_vm_actor_interface::eam;
 use fvm_ipld_encoding::{BytesDe, RawBytes};
 use tendermint::abci::response::DeliverTx;
@@ -66,3 +67,17 @@ pub fn decode_os_get(deliver_tx: &DeliverTx) -> anyhow::Result<Option<Object>> {
     fvm_ipld_encoding::from_slice::<Option<Object>>(&data)
         .map_err(|e| anyhow!("error parsing as Option<Object>: {e}"))
 }
+
+/// Parse what Tendermint returns in the `data` field of [`DeliverTx`] as a [`ListObjectsReturn`].
+pub fn decode_os_list(deliver_tx: &DeliverTx) -> anyhow::Result<ListObjectsReturn> {
+    let data = decode_data(&deliver_tx.data)?;
+    fvm_ipld_encoding::from_slice::<ListObjectsReturn>(&data)
+        .map_err(|e| anyhow!("error parsing as ListObjectsReturn: {e}"))
+}
+
+/// Parse what Tendermint returns in the `data` field of [`DeliverTx`] as a [`Blob`].
+pub fn decode_blob_get(deliver_tx: &DeliverTx) -> anyhow::Result<Option<Blob>> {
+    let data = decode_data(&deliver_tx.data)?;
+    fvm_ipld_encoding::from_slice::<Option<Blob>>(&data)
+        .map_err(|e| anyhow!("error parsing as Option<Blob>: {e}"))
+}
@@ -21,6 +21,7 @@ pub enum Method {
     CreateExternal = 1214262202,
     UpdateDeployers = 1768606754,
     ListMetadata = 2283215593,
+    TransferMachine = 3295273063,
 }
 
 /// The kinds of machines available.
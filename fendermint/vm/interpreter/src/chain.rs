@@ -9,29 +9,45 @@ use crate::{
     fvm::state::ipc::GatewayCaller,
     fvm::state::FvmExecState,
     fvm::store::ReadOnlyBlockstore,
-    fvm::{topdown, EndBlockOutput, FvmApplyRet, FvmMessage},
+    fvm::{aggregation::CheckpointSignaturePool, topdown, EndBlockOutput, FvmApplyRet, FvmMessage},
     signed::{SignedMessageApplyRes, SignedMessageCheckRes, SyntheticMessage, VerifiableMessage},
     CheckInterpreter, ExecInterpreter, ProposalInterpreter, QueryInterpreter,
 };
 use anyhow::{anyhow, bail, Context};
 use async_stm::atomically;
 use async_trait::async_trait;
+use fendermint_actor_activity_tracker::{
+    types::RecordChallengeResultsParams, Method::RecordChallengeResults,
+};
 use fendermint_actor_blob_reader::{
-    CloseReadRequestParams, GetOpenReadRequestsParams, GetReadRequestStatusParams,
-    Method::{CloseReadRequest, GetOpenReadRequests, GetReadRequestStatus, SetReadRequestPending},
+    ChargeReadRequestParams, CloseReadRequestParams, GetOpenReadRequestsParams,
+    GetReadRequestStatusParams,
+    Method::{
+        ChargeReadRequest, CloseReadRequest, GetOpenReadRequests, GetReadRequestStatus,
+        SetReadRequestPending,
+    },
     ReadRequestStatus, SetReadRequestPendingParams, BLOB_READER_ACTOR_ADDR,
 };
 use fendermint_actor_blobs_shared::{
     params::{
-        FinalizeBlobParams, GetAddedBlobsParams, GetBlobStatusParams, GetStatsReturn,
-        SetBlobPendingParams,
+        AnswerChallengeParams, CloseChallengeParams, FinalizeBlobParams, GetAddedBlobsParams,
+        GetBlobStatusParams, GetStatsReturn, SetBlobPendingParams,
+    },
+    state::{BlobStatus, Challenge, SubscriptionId},
+    Method::{
+        AnswerChallenge, CloseChallenge, DebitAccounts, FinalizeBlob, GetAddedBlobs, GetBlobStatus,
+        GetOpenChallenges, GetStats, IssueChallenges, SetBlobPending,
     },
-    state::{BlobStatus, SubscriptionId},
-    Method::{DebitAccounts, FinalizeBlob, GetAddedBlobs, GetBlobStatus, GetStats, SetBlobPending},
+    BLOB_CHALLENGE_INTERVAL,
 };
+use fendermint_actor_recall_config_shared::Method::ActivateConfig;
 use fendermint_tracing::emit;
-use fendermint_vm_actor_interface::{blob_reader, blobs, ipc, system};
+use fendermint_vm_actor_interface::eam::EthAddress;
+use fendermint_vm_actor_interface::{activity, blob_reader, blobs, ipc, recall_config, system};
 use fendermint_vm_event::ParentFinalityMissingQuorum;
+use fendermint_vm_iroh_resolver::challenge::{
+    challenge_key, verify_answer, verify_answer_signature, ChallengeAnswers,
+};
 use fendermint_vm_iroh_resolver::observe::{
     BlobsFinalityAddedBlobs, BlobsFinalityAddedBytes, BlobsFinalityPendingBlobs,
     BlobsFinalityPendingBytes,
@@ -43,15 +59,18 @@ use fendermint_vm_iroh_resolver::pool::{
 use fendermint_vm_message::{
     chain::ChainMessage,
     ipc::{
-        BottomUpCheckpoint, CertifiedMessage, ClosedReadRequest, FinalizedBlob, IpcMessage,
-        ParentFinality, PendingBlob, PendingReadRequest, SignedRelayedMessage,
+        AnsweredChallenge, BottomUpCheckpoint, CertifiedMessage, ClosedChallenge,
+        ClosedReadRequest, FinalizedBlob, IpcMessage, ParentFinality, PendingBlob,
+        PendingReadRequest, SignedRelayedMessage,
     },
 };
 use fendermint_vm_resolver::pool::{ResolveKey, ResolvePool};
 use fendermint_vm_topdown::proxy::IPCProviderProxyWithLatency;
+use fendermint_vm_topdown::quorum::QuorumParentProxy;
 use fendermint_vm_topdown::voting::{ValidatorKey, VoteTally};
 use fendermint_vm_topdown::{
-    CachedFinalityProvider, IPCParentFinality, ParentFinalityProvider, ParentViewProvider, Toggle,
+    CachedFinalityProvider, IPCChallengeAnswer, IPCParentFinality, ParentFinalityProvider,
+    ParentViewProvider, Toggle,
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::RawBytes;
@@ -68,12 +87,14 @@ use tokio_util::bytes;
 
 /// A resolution pool for bottom-up and top-down checkpoints.
 pub type CheckpointPool = ResolvePool<CheckpointPoolItem>;
-pub type TopDownFinalityProvider = Arc<Toggle<CachedFinalityProvider<IPCProviderProxyWithLatency>>>;
+pub type TopDownFinalityProvider =
+    Arc<Toggle<CachedFinalityProvider<QuorumParentProxy<IPCProviderProxyWithLatency>>>>;
 pub type BlobPool = IrohResolvePool<BlobPoolItem>;
 pub type ReadRequestPool = IrohResolvePool<ReadRequestPoolItem>;
+pub type ChallengePool = IrohResolvePool<ChallengePoolItem>;
 
 type AddedBlobItem = (Hash, HashSet<(Address, SubscriptionId, PublicKey)>);
-type OpenReadRequestItem = (Hash, Hash, u32, u32, Address, MethodNum);
+type OpenReadRequestItem = (Hash, Hash, u32, u64, u32, Address, MethodNum, bool);
 
 /// These are the extra state items that the chain interpreter needs,
 /// a sort of "environment" supporting IPC.
@@ -92,10 +113,16 @@ pub struct ChainEnv {
     pub read_request_pool: ReadRequestPool,
     /// Number of pending read requests to process in parallel.
     pub read_request_concurrency: u32,
+    /// Storage challenge pool.
+    pub challenge_pool: ChallengePool,
+    /// Verified storage challenge answers waiting to be proposed.
+    pub challenge_answers: ChallengeAnswers,
     /// Interval in blocks at which to emit blob metrics
     pub blob_metrics_interval: ChainEpoch,
     /// Gas limit used by the system actor to manage blob queues.
     pub blob_queue_gas_limit: u64,
+    /// BLS signatures of bottom-up checkpoints gossiped by the validators.
+    pub checkpoint_signatures: CheckpointSignaturePool,
 }
 
 #[derive(Clone, Hash, PartialEq, Eq)]
@@ -144,19 +171,43 @@ impl From<&BlobPoolItem> for IrohTaskType {
 pub struct ReadRequestPoolItem {
     /// The unique id of the read request.
     id: Hash,
+    /// The sequence number of the chunk being read.
+    chunk: u32,
     /// The hash of the blob that the read request is for.
     blob_hash: Hash,
-    /// The offset of the read request.
-    offset: u32,
-    /// The length of the read request.
+    /// The offset of the chunk.
+    offset: u64,
+    /// The length of the chunk.
     len: u32,
     /// The address and method to callback when the read request is closed.
     callback: (Address, MethodNum),
+    /// Whether the chunk sequence number is delivered to the callback.
+    chunked: bool,
+}
+
+impl ReadRequestPoolItem {
+    /// The key under which the chunk is resolved.
+    /// Each chunk of a request is resolved and voted on separately.
+    fn key(&self) -> Hash {
+        let mut data = self.id.as_bytes().to_vec();
+        data.extend_from_slice(&self.chunk.to_be_bytes());
+        Hash::new(data)
+    }
+
+    /// The hash validators vote on for a response to the chunk.
+    fn vote_hash(&self, response: &[u8]) -> Hash {
+        // Extend the key with response data to use as the vote hash.
+        // This ensures that all validators are voting
+        // on the same response from IROH.
+        let mut data = self.key().as_bytes().to_vec();
+        data.extend_from_slice(response);
+        Hash::new(data)
+    }
 }
 
 impl From<&ReadRequestPoolItem> for IrohResolveKey {
     fn from(value: &ReadRequestPoolItem) -> Self {
-        Self { hash: value.id }
+        Self { hash: value.key() }
     }
 }
 
@@ -170,6 +221,49 @@ impl From<&ReadRequestPoolItem> for IrohTaskType {
     }
 }
 
+/// A storage challenge pool item. This is the task that needs to be answered by the challenge resolver.
+#[derive(Clone, Hash, PartialEq, Eq)]
+pub struct ChallengePoolItem {
+    /// The unique id of the challenge.
+    id: u64,
+    /// The hash of the challenged blob.
+    blob_hash: Hash,
+    /// The size of the challenged blob.
+    size: u64,
+    /// The offset of the challenged chunk.
+    offset: u64,
+}
+
+impl From<&Challenge> for ChallengePoolItem {
+    fn from(value: &Challenge) -> Self {
+        Self {
+            id: value.id,
+            blob_hash: Hash::from_bytes(value.hash.0),
+            size: value.size,
+            offset: value.offset,
+        }
+    }
+}
+
+impl From<&ChallengePoolItem> for IrohResolveKey {
+    fn from(value: &ChallengePoolItem) -> Self {
+        Self {
+            hash: challenge_key(value.id, &value.blob_hash, value.size, value.offset),
+        }
+    }
+}
+
+impl From<&ChallengePoolItem> for IrohTaskType {
+    fn from(value: &ChallengePoolItem) -> Self {
+        Self::AnswerChallenge {
+            id: value.id,
+            blob_hash: value.blob_hash,
+            size: value.size,
+            offset: value.offset,
+        }
+    }
+}
+
 /// A user sent a transaction which they are not allowed to do.
 pub struct IllegalMessage;
 
@@ -293,6 +387,19 @@ where
             msgs.push(ChainMessage::Ipc(IpcMessage::DebitCreditAccounts));
         }
 
+        // Maybe issue new storage challenges
+        if current_height > 0 && current_height % BLOB_CHALLENGE_INTERVAL == 0 {
+            msgs.push(ChainMessage::Ipc(IpcMessage::IssueBlobChallenges));
+        }
+
+        // Maybe activate a scheduled config change
+        if state
+            .recall_config_tracker()
+            .is_config_activation_due(current_height)
+        {
+            msgs.push(ChainMessage::Ipc(IpcMessage::ActivateRecallConfig));
+        }
+
         // Get added blobs from the blob actor
         state.state_tree_mut().begin_transaction();
         let added_blobs = with_state_transaction(&mut state, |state| {
@@ -380,14 +487,18 @@ where
         })?;
 
         // Create IPC messages to add read requests to the pool
-        for (id, blob_hash, offset, len, callback_addr, callback_method) in open_requests {
+        for (id, blob_hash, chunk, offset, len, callback_addr, callback_method, chunked) in
+            open_requests
+        {
             msgs.push(ChainMessage::Ipc(IpcMessage::ReadRequestPending(
                 PendingReadRequest {
                     id,
+                    chunk,
                     blob_hash,
                     offset,
                     len,
                     callback: (callback_addr, callback_method),
+                    chunked,
                 },
             )));
         }
@@ -408,10 +519,13 @@ where
             // We start a blockstore transaction that can be reverted
             state.state_tree_mut().begin_transaction();
             for item in locally_finalized_read_requests.iter() {
-                // Check if the read request is closed, i.e., not open or pending.
+                // Check if the read request chunk is closed, i.e., the request is not pending.
                 // If a request is not found in actor state but exists in the pool,
                 // it is considered closed.
-                if get_read_request_status(&mut state, item.id)?.is_none() {
+                if !matches!(
+                    get_read_request_status(&mut state, item.id)?,
+                    Some(ReadRequestStatus::Pending)
+                ) {
                     tracing::debug!(request_id = ?item.id, "read request already fulfilled on chain; removing from pool");
                     atomically(|| chain_env.read_request_pool.remove_task(item)).await;
                     continue;
@@ -422,12 +536,7 @@ where
                 // Remove the result from the pool
                 atomically(|| chain_env.read_request_pool.remove_result(item)).await;
 
-                // Extend request id with response data to use as the vote hash.
-                // This ensures that all validators are voting
-                // on the same response from IROH.
-                let mut request_id = item.id.as_bytes().to_vec();
-                request_id.extend(read_response.clone());
-                let vote_hash = Hash::new(request_id);
+                let vote_hash = item.vote_hash(&read_response);
                 let (is_globally_finalized, _) = atomically(|| {
                     chain_env
                         .parent_finality_votes
@@ -439,10 +548,12 @@ where
                     read_requests.push(ChainMessage::Ipc(IpcMessage::ReadRequestClosed(
                         ClosedReadRequest {
                             id: item.id,
+                            chunk: item.chunk,
                             blob_hash: item.blob_hash,
                             offset: item.offset,
                             len: item.len,
                             callback: item.callback,
+                            chunked: item.chunked,
                             response: read_response,
                         },
                     )));
@@ -460,6 +571,50 @@ where
         let pending_read_requests = atomically(|| chain_env.read_request_pool.count()).await;
         tracing::info!(size = pending_read_requests, "read request pool status");
 
+        // Propose the verified answers to open storage challenges that are not yet on-chain,
+        // and close the challenges whose deadline has passed.
+        let open_challenges = with_state_transaction(&mut state, get_open_challenges)?;
+        let validators = with_state_transaction(&mut state, validator_addresses)?;
+        for challenge in open_challenges.iter() {
+            let item = ChallengePoolItem::from(challenge);
+            if challenge.deadline <= current_height {
+                tracing::debug!(
+                    id = challenge.id,
+                    answered = challenge.answered.len(),
+                    "challenge deadline passed; adding tx to chain"
+                );
+                msgs.push(ChainMessage::Ipc(IpcMessage::BlobChallengeClosed(
+                    ClosedChallenge {
+                        id: item.id,
+                        hash: item.blob_hash,
+                        size: item.size,
+                        offset: item.offset,
+                    },
+                )));
+                continue;
+            }
+
+            let answers = atomically(|| chain_env.challenge_answers.get(challenge.id)).await;
+            for (validator, answer) in answers {
+                let answered = AnsweredChallenge {
+                    id: answer.id,
+                    hash: answer.hash,
+                    size: answer.size,
+                    offset: answer.offset,
+                    chunk: answer.chunk,
+                    siblings: answer.siblings,
+                    public_key: libp2p::identity::PublicKey::from(validator).encode_protobuf(),
+                    signature: answer.signature,
+                };
+                // Only propose answers the other validators are going to accept
+                if verify_challenge_answer(challenge, &answered, &validators).is_some() {
+                    msgs.push(ChainMessage::Ipc(IpcMessage::BlobChallengeAnswered(
+                        answered,
+                    )));
+                }
+            }
+        }
+
         Ok(msgs)
     }
 
@@ -607,13 +762,16 @@ where
                         return Ok(false);
                     }
 
-                    let read_response = read_request.response.clone();
-                    // Extend request id with response data to use as the vote hash.
-                    // This ensures that all validators are voting
-                    // on the same response from IROH.
-                    let mut request_id = read_request.id.as_bytes().to_vec();
-                    request_id.extend(read_response.clone());
-                    let vote_hash = Hash::new(request_id);
+                    let item = ReadRequestPoolItem {
+                        id: read_request.id,
+                        chunk: read_request.chunk,
+                        blob_hash: read_request.blob_hash,
+                        offset: read_request.offset,
+                        len: read_request.len,
+                        callback: read_request.callback,
+                        chunked: read_request.chunked,
+                    };
+                    let vote_hash = item.vote_hash(&read_request.response);
                     let (is_globally_finalized, _) = atomically(|| {
                         chain_env
                             .parent_finality_votes
@@ -626,13 +784,6 @@ where
                     }
 
                     // Remove from pool if locally resolved
-                    let item = ReadRequestPoolItem {
-                        id: read_request.id,
-                        blob_hash: read_request.blob_hash,
-                        offset: read_request.offset,
-                        len: read_request.len,
-                        callback: read_request.callback,
-                    };
                     let is_locally_finalized =
                         atomically(|| match chain_env.read_request_pool.get_status(&item)? {
                             None => Ok(false),
@@ -648,6 +799,77 @@ where
                         tracing::debug!(request_id = ?read_request.id, "read request is not locally finalized");
                     }
                 }
+                ChainMessage::Ipc(IpcMessage::IssueBlobChallenges) => {
+                    // Ensure that this is a valid height to issue challenges
+                    let current_height = state.block_height();
+                    if !(current_height > 0 && current_height % BLOB_CHALLENGE_INTERVAL == 0) {
+                        tracing::debug!(
+                            height = ?current_height,
+                            "invalid height for blob challenges; rejecting proposal"
+                        );
+                        return Ok(false);
+                    }
+                }
+                ChainMessage::Ipc(IpcMessage::ActivateRecallConfig) => {
+                    // Ensure that a scheduled config change is due
+                    let current_height = state.block_height();
+                    if !state
+                        .recall_config_tracker()
+                        .is_config_activation_due(current_height)
+                    {
+                        tracing::debug!(
+                            height = ?current_height,
+                            "no recall config change is due; rejecting proposal"
+                        );
+                        return Ok(false);
+                    }
+                }
+                ChainMessage::Ipc(IpcMessage::BlobChallengeAnswered(answered)) => {
+                    // Ensure that the challenge is still open, and that the answer carries a valid
+                    // proof, signed by a validator in the power table that has not answered yet.
+                    // These checks only depend on the ledger and the message itself, so every
+                    // validator reaches the same verdict, regardless of the gossip it received.
+                    let current_height = state.block_height();
+                    let open_challenges = with_state_transaction(&mut state, get_open_challenges)?;
+                    let validators = with_state_transaction(&mut state, validator_addresses)?;
+                    let is_valid = open_challenges
+                        .iter()
+                        .find(|c| c.id == answered.id && current_height < c.deadline)
+                        .is_some_and(|c| {
+                            verify_challenge_answer(c, &answered, &validators).is_some()
+                        });
+                    if !is_valid {
+                        tracing::debug!(
+                            id = answered.id,
+                            "invalid challenge answer; rejecting proposal"
+                        );
+                        return Ok(false);
+                    }
+                }
+                ChainMessage::Ipc(IpcMessage::BlobChallengeClosed(closed)) => {
+                    // Ensure that the challenge is open, its deadline has passed, and it matches
+                    // the one on chain. The answers are already recorded on chain.
+                    let current_height = state.block_height();
+                    let open_challenges = with_state_transaction(&mut state, get_open_challenges)?;
+                    let Some(item) = open_challenges
+                        .iter()
+                        .find(|c| c.id == closed.id && c.deadline <= current_height)
+                        .map(ChallengePoolItem::from)
+                    else {
+                        tracing::debug!(
+                            id = closed.id,
+                            "challenge cannot be closed; rejecting proposal"
+                        );
+                        return Ok(false);
+                    };
+                    if item.blob_hash != closed.hash
+                        || item.size != closed.size
+                        || item.offset != closed.offset
+                    {
+                        tracing::debug!(id = closed.id, "challenge mismatch; rejecting proposal");
+                        return Ok(false);
+                    }
+                }
                 _ => {}
             };
         }
@@ -974,10 +1196,12 @@ where
                     atomically(|| {
                         env.read_request_pool.add(ReadRequestPoolItem {
                             id: read_request.id,
+                            chunk: read_request.chunk,
                             blob_hash: read_request.blob_hash,
                             offset: read_request.offset,
                             len: read_request.len,
                             callback: read_request.callback,
+                            chunked: read_request.chunked,
                         })
                     })
                     .await;
@@ -985,21 +1209,196 @@ where
                     Ok(((env, state), ChainMessageApplyRet::Ipc(ret)))
                 }
                 IpcMessage::ReadRequestClosed(read_request) => {
+                    // Charge for the chunk before it is delivered, so that data is never handed
+                    // out unpaid. If the payer cannot pay, the blob_reader actor fails the
+                    // request and the callback is not called.
+                    let (paid, ret) = charge_read_request(&mut state, &read_request)?;
+                    if !paid {
+                        tracing::debug!(
+                            hash = ?read_request.id,
+                            chunk = read_request.chunk,
+                            "read request chunk could not be charged"
+                        );
+                        return Ok(((env, state), ChainMessageApplyRet::Ipc(ret)));
+                    }
+
                     // Send the data to the callback address.
                     // If this fails (e.g., the callback address is not reachable),
-                    // we will still close the request.
+                    // the blob_reader actor retries the chunk a bounded number of times.
                     //
                     // We MUST use a non-prevliged actor (BLOB_READER_ACTOR_ADDR) to call the callback.
                     // This is to prevent malicious user from accessing unauthorized APIs.
-                    read_request_callback(&mut state, &read_request)?;
-                    // Set the status of the request to fulfill.
-                    let ret = close_read_request(&mut state, read_request.id)?;
+                    let delivered = read_request_callback(&mut state, &read_request)?;
+                    // Move the request on to the next chunk, or retry this one.
+                    let ret = close_read_request(
+                        &mut state,
+                        read_request.id,
+                        read_request.chunk,
+                        delivered,
+                    )?;
 
                     tracing::debug!(
                         hash = ?read_request.id,
-                        "read request is closed"
+                        chunk = read_request.chunk,
+                        delivered,
+                        "read request chunk is closed"
                     );
 
+                    Ok(((env, state), ChainMessageApplyRet::Ipc(ret)))
+                }
+                IpcMessage::IssueBlobChallenges => {
+                    let from = system::SYSTEM_ACTOR_ADDR;
+                    let to = blobs::BLOBS_ACTOR_ADDR;
+                    let method_num = IssueChallenges as u64;
+                    let gas_limit = env.blob_queue_gas_limit;
+                    let msg =
+                        create_implicit_message(to, method_num, Default::default(), gas_limit);
+                    let (apply_ret, emitters) = state.execute_implicit(msg)?;
+
+                    // Add the new challenges to the pool, so we can answer them
+                    if apply_ret.msg_receipt.exit_code.is_success() {
+                        let challenges = fvm_ipld_encoding::from_slice::<Vec<Challenge>>(
+                            &apply_ret.msg_receipt.return_data,
+                        )
+                        .map_err(|e| anyhow!("error parsing challenges: {e}"))?;
+                        for challenge in challenges.iter() {
+                            atomically(|| {
+                                env.challenge_pool.add(ChallengePoolItem::from(challenge))
+                            })
+                            .await;
+                        }
+                        tracing::debug!(
+                            size = challenges.len(),
+                            "chain interpreter has issued blob challenges"
+                        );
+                    }
+
+                    let ret = FvmApplyRet {
+                        apply_ret,
+                        from,
+                        to,
+                        method_num,
+                        gas_limit,
+                        emitters,
+                    };
+
+                    Ok(((env, state), ChainMessageApplyRet::Ipc(ret)))
+                }
+                IpcMessage::ActivateRecallConfig => {
+                    let from = system::SYSTEM_ACTOR_ADDR;
+                    let to = recall_config::RECALL_CONFIG_ACTOR_ADDR;
+                    let method_num = ActivateConfig as u64;
+                    let gas_limit = fvm_shared::BLOCK_GAS_LIMIT;
+                    let msg =
+                        create_implicit_message(to, method_num, Default::default(), gas_limit);
+                    let (apply_ret, emitters) = state.execute_implicit(msg)?;
+                    tracing::debug!(
+                        exit_code = apply_ret.msg_receipt.exit_code.value(),
+                        "chain interpreter activated recall config"
+                    );
+
+                    let ret = FvmApplyRet {
+                        apply_ret,
+                        from,
+                        to,
+                        method_num,
+                        gas_limit,
+                        emitters,
+                    };
+
+                    Ok(((env, state), ChainMessageApplyRet::Ipc(ret)))
+                }
+                IpcMessage::BlobChallengeAnswered(answered) => {
+                    let from = system::SYSTEM_ACTOR_ADDR;
+                    let to = blobs::BLOBS_ACTOR_ADDR;
+                    let method_num = AnswerChallenge as u64;
+                    let gas_limit = env.blob_queue_gas_limit;
+                    // The answer was verified when processing the proposal.
+                    let public_key =
+                        libp2p::identity::PublicKey::try_decode_protobuf(&answered.public_key)
+                            .context("invalid challenge answer public key")?;
+                    let validator = validator_address(&ValidatorKey::from(public_key))?;
+                    let params = RawBytes::serialize(AnswerChallengeParams {
+                        id: answered.id,
+                        validator,
+                    })?;
+                    let msg = create_implicit_message(to, method_num, params, gas_limit);
+                    let (apply_ret, emitters) = state.execute_implicit(msg)?;
+
+                    tracing::debug!(
+                        id = answered.id,
+                        validator = validator.to_string(),
+                        "chain interpreter has recorded challenge answer"
+                    );
+
+                    let ret = FvmApplyRet {
+                        apply_ret,
+                        from,
+                        to,
+                        method_num,
+                        gas_limit,
+                        emitters,
+                    };
+
+                    Ok(((env, state), ChainMessageApplyRet::Ipc(ret)))
+                }
+                IpcMessage::BlobChallengeClosed(closed) => {
+                    let from = system::SYSTEM_ACTOR_ADDR;
+                    let to = blobs::BLOBS_ACTOR_ADDR;
+                    let method_num = CloseChallenge as u64;
+                    let gas_limit = env.blob_queue_gas_limit;
+                    let params = RawBytes::serialize(CloseChallengeParams(closed.id))?;
+                    let msg = create_implicit_message(to, method_num, params, gas_limit);
+                    let (apply_ret, emitters) = state.execute_implicit(msg)?;
+
+                    // Only record the results if the blob was still stored when the
+                    // challenge closed. Otherwise, there was nothing left to prove.
+                    let challenge = if apply_ret.msg_receipt.exit_code.is_success() {
+                        fvm_ipld_encoding::from_slice::<Option<Challenge>>(
+                            &apply_ret.msg_receipt.return_data,
+                        )
+                        .map_err(|e| anyhow!("error parsing challenge result: {e}"))?
+                    } else {
+                        None
+                    };
+                    if let Some(challenge) = challenge {
+                        // Validators in the current power table that have no answer on chain
+                        // missed the challenge.
+                        let validators = validator_addresses(&mut state)?;
+                        let (answered, missed): (Vec<_>, Vec<_>) = validators
+                            .into_iter()
+                            .partition(|v| challenge.answered.contains(v));
+                        record_challenge_results(&mut state, closed.id, answered, missed)?;
+                    }
+
+                    tracing::debug!(
+                        id = closed.id,
+                        "chain interpreter has closed blob challenge"
+                    );
+
+                    // Once the challenge is closed, we can clean up the answers and the pool
+                    let item = ChallengePoolItem {
+                        id: closed.id,
+                        blob_hash: closed.hash,
+                        size: closed.size,
+                        offset: closed.offset,
+                    };
+                    atomically(|| {
+                        env.challenge_answers.remove(closed.id)?;
+                        env.challenge_pool.remove_task(&item)?;
+                        env.challenge_pool.remove_result(&item)
+                    })
+                    .await;
+
+                    let ret = FvmApplyRet {
+                        apply_ret,
+                        from,
+                        to,
+                        method_num,
+                        gas_limit,
+                        emitters,
+                    };
+
                     Ok(((env, state), ChainMessageApplyRet::Ipc(ret)))
                 }
             },
@@ -1032,9 +1431,11 @@ where
             .await;
         }
 
+        let current_block = state.block_height();
+        env.checkpoint_signatures.set_height(current_block as u64);
+
         // Get pending blobs count and bytes count to emit metrics
         // Only emit metrics every 10 blocks
-        let current_block = state.block_height();
         if current_block > 0
             && env.blob_metrics_interval > 0
             && current_block % env.blob_metrics_interval == 0
@@ -1095,7 +1496,11 @@ where
                     | IpcMessage::BlobPending(_)
                     | IpcMessage::BlobFinalized(_)
                     | IpcMessage::ReadRequestClosed(_)
-                    | IpcMessage::ReadRequestPending(_) => {
+                    | IpcMessage::ReadRequestPending(_)
+                    | IpcMessage::IssueBlobChallenges
+                    | IpcMessage::ActivateRecallConfig
+                    | IpcMessage::BlobChallengeAnswered(_)
+                    | IpcMessage::BlobChallengeClosed(_) => {
                         // Users cannot send these messages, only validators can propose them in blocks.
                         Ok((state, Err(IllegalMessage)))
                     }
@@ -1316,23 +1721,32 @@ where
         .map_err(|e| anyhow!("error parsing read requests: {e}"))
 }
 
+/// Delivers a chunk of read request data to the callback as `(id, chunk, data)`,
+/// or as `(id, data)` if the request is not chunked.
+/// Returns whether the callback accepted the chunk.
 fn read_request_callback<DB>(
     state: &mut FvmExecState<DB>,
     read_request: &ClosedReadRequest,
-) -> anyhow::Result<()>
+) -> anyhow::Result<bool>
 where
     DB: Blockstore + Clone + 'static + Send + Sync,
 {
     let ClosedReadRequest {
         id,
+        chunk,
         blob_hash: _,
         offset: _,
         len: _,
         callback: (to, method_num),
+        chunked,
         response,
     } = read_request.clone();
 
-    let params = RawBytes::serialize((id, response))?;
+    let params = if chunked {
+        RawBytes::serialize((id, chunk, response))?
+    } else {
+        RawBytes::serialize((id, response))?
+    };
     let msg = Message {
         version: Default::default(),
         from: BLOB_READER_ACTOR_ADDR,
@@ -1349,26 +1763,75 @@ where
     match result {
         Ok((apply_ret, _)) => {
             tracing::debug!(
-                "Callback delivered for id: {:?}, exit code: {:?}",
+                "Callback delivered for id: {:?}, chunk: {}, exit code: {:?}",
                 id,
+                chunk,
                 apply_ret.msg_receipt.exit_code
             );
+            Ok(apply_ret.msg_receipt.exit_code.is_success())
         }
         Err(e) => {
             tracing::error!("failed to execute read request callback: {}", e);
+            Ok(false)
         }
     }
+}
 
-    Ok(())
+/// Charges for a chunk of read request data before it is delivered.
+/// Returns whether the chunk is paid for and can be delivered.
+fn charge_read_request<DB>(
+    state: &mut FvmExecState<DB>,
+    read_request: &ClosedReadRequest,
+) -> anyhow::Result<(bool, FvmApplyRet)>
+where
+    DB: Blockstore + Clone + 'static + Send + Sync,
+{
+    let len =
+        u32::try_from(read_request.response.len()).context("read request response is too large")?;
+    let params = RawBytes::serialize(ChargeReadRequestParams {
+        id: fendermint_actor_blobs_shared::state::Hash(*read_request.id.as_bytes()),
+        chunk: read_request.chunk,
+        len,
+    })?;
+    let gas_limit = fvm_shared::BLOCK_GAS_LIMIT;
+    let msg = create_implicit_message(
+        blob_reader::BLOB_READER_ACTOR_ADDR,
+        ChargeReadRequest as u64,
+        params,
+        gas_limit,
+    );
+
+    let (apply_ret, emitters) = state.execute_implicit(msg)?;
+    let paid = apply_ret.msg_receipt.exit_code.is_success()
+        && fvm_ipld_encoding::from_slice::<bool>(&apply_ret.msg_receipt.return_data)
+            .map_err(|e| anyhow!("error parsing read request charge: {e}"))?;
+    Ok((
+        paid,
+        FvmApplyRet {
+            apply_ret,
+            from: system::SYSTEM_ACTOR_ADDR,
+            to: blob_reader::BLOB_READER_ACTOR_ADDR,
+            method_num: ChargeReadRequest as u64,
+            gas_limit,
+            emitters,
+        },
+    ))
 }
 
-fn close_read_request<DB>(state: &mut FvmExecState<DB>, id: Hash) -> anyhow::Result<FvmApplyRet>
+fn close_read_request<DB>(
+    state: &mut FvmExecState<DB>,
+    id: Hash,
+    chunk: u32,
+    delivered: bool,
+) -> anyhow::Result<FvmApplyRet>
 where
     DB: Blockstore + Clone + 'static + Send + Sync,
 {
-    let params = RawBytes::serialize(CloseReadRequestParams(
-        fendermint_actor_blobs_shared::state::Hash(*id.as_bytes()),
-    ))?;
+    let params = RawBytes::serialize(CloseReadRequestParams {
+        id: fendermint_actor_blobs_shared::state::Hash(*id.as_bytes()),
+        chunk,
+        delivered,
+    })?;
     let gas_limit = fvm_shared::BLOCK_GAS_LIMIT;
     let msg = create_implicit_message(
         blob_reader::BLOB_READER_ACTOR_ADDR,
@@ -1439,6 +1902,111 @@ where
     })
 }
 
+/// Returns the storage challenges that are currently open.
+fn get_open_challenges<DB>(state: &mut FvmExecState<DB>) -> anyhow::Result<Vec<Challenge>>
+where
+    DB: Blockstore + Clone + 'static + Send + Sync,
+{
+    let msg = create_implicit_message(
+        blobs::BLOBS_ACTOR_ADDR,
+        GetOpenChallenges as u64,
+        Default::default(),
+        fvm_shared::BLOCK_GAS_LIMIT,
+    );
+    let (apply_ret, _) = state.execute_implicit(msg)?;
+    if let Some(err) = apply_ret.failure_info {
+        bail!("failed to apply get open challenges message: {}", err);
+    }
+
+    let data: bytes::Bytes = apply_ret.msg_receipt.return_data.to_vec().into();
+    fvm_ipld_encoding::from_slice::<Vec<Challenge>>(&data)
+        .map_err(|e| anyhow!("error parsing open challenges: {e}"))
+}
+
+/// Records which validators answered a closed storage challenge in the activity tracker.
+fn record_challenge_results<DB>(
+    state: &mut FvmExecState<DB>,
+    id: u64,
+    answered: Vec<Address>,
+    missed: Vec<Address>,
+) -> anyhow::Result<()>
+where
+    DB: Blockstore + Clone + 'static + Send + Sync,
+{
+    let params = RawBytes::serialize(RecordChallengeResultsParams { answered, missed })?;
+    let msg = create_implicit_message(
+        activity::ACTIVITY_TRACKER_ACTOR_ADDR,
+        RecordChallengeResults as u64,
+        params,
+        fvm_shared::BLOCK_GAS_LIMIT,
+    );
+    let (apply_ret, _) = state.execute_implicit(msg)?;
+    if let Some(err) = apply_ret.failure_info {
+        tracing::error!(id, "failed to record challenge results: {}", err);
+    }
+    Ok(())
+}
+
+/// Verifies an answer against an open storage challenge and the validators in the power table.
+///
+/// Returns the address of the validator that answered, if the answer is valid and the
+/// validator has not answered the challenge yet.
+fn verify_challenge_answer(
+    challenge: &Challenge,
+    answered: &AnsweredChallenge,
+    validators: &[Address],
+) -> Option<Address> {
+    let item = ChallengePoolItem::from(challenge);
+    if item.blob_hash != answered.hash
+        || item.size != answered.size
+        || item.offset != answered.offset
+    {
+        return None;
+    }
+    let public_key = libp2p::identity::PublicKey::try_decode_protobuf(&answered.public_key).ok()?;
+    let answer = IPCChallengeAnswer {
+        id: answered.id,
+        hash: answered.hash,
+        size: answered.size,
+        offset: answered.offset,
+        chunk: answered.chunk.clone(),
+        siblings: answered.siblings.clone(),
+        signature: answered.signature.clone(),
+    };
+    if !verify_answer(&answer) || !verify_answer_signature(&public_key, &answer) {
+        return None;
+    }
+    let validator = validator_address(&ValidatorKey::from(public_key)).ok()?;
+    (validators.contains(&validator) && !challenge.answered.contains(&validator))
+        .then_some(validator)
+}
+
+/// Returns the f410 addresses of the validators with power in the on-chain power table,
+/// in the order of the power table.
+fn validator_addresses<DB>(state: &mut FvmExecState<DB>) -> anyhow::Result<Vec<Address>>
+where
+    DB: Blockstore + Clone + 'static + Send + Sync,
+{
+    let (_, power_table) = GatewayCaller::default()
+        .current_power_table(state)
+        .context("failed to get current power table")?;
+    Ok(power_table
+        .into_iter()
+        .filter(|v| v.power.0 > 0)
+        .map(|v| EthAddress::from(v.public_key.0).into())
+        .collect())
+}
+
+/// Returns the f410 address of a validator, which is how the activity tracker identifies it.
+fn validator_address(validator: &ValidatorKey) -> anyhow::Result<Address> {
+    let public_key = libp2p::identity::PublicKey::from(validator.clone())
+        .try_into_secp256k1()
+        .context("validator key is not secp256k1")?;
+    let public_key = fendermint_crypto::PublicKey::parse_slice(&public_key.to_bytes(), None)
+        .map_err(|e| anyhow!("invalid validator public key: {e:?}"))?;
+    Ok(EthAddress::from(public_key).into())
+}
+
 /// Creates a standard implicit message with default values
 fn create_implicit_message(
     to: Address,
@@ -0,0 +1,537 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Aggregation of BLS checkpoint signatures gossiped between validators.
+//!
+//! Next to adding their signature of a bottom-up checkpoint to the ledger, validators
+//! gossip a partial BLS signature over the checkpoint hash, which every validator
+//! collects into an aggregate as soon as more than two thirds of the power has signed.
+//!
+//! The aggregate is an off-chain certificate only: the BLS keys are bound to the
+//! validators by the signed gossip alone, and the parent doesn't verify aggregates,
+//! so checkpoints are still submitted with the signatures from the ledger.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail};
+use fendermint_crypto::bls::{self, BlsSecretKey};
+use fendermint_crypto::PublicKey;
+use fendermint_vm_actor_interface::eam::EthAddress;
+use fendermint_vm_genesis::{Power, Validator};
+use fendermint_vm_topdown::{BlockHeight, IPCCheckpointSignature};
+use ipc_api::checkpoint::AggregateSignature;
+use tokio::sync::mpsc;
+
+/// Number of checkpoints to keep signatures for.
+const MAX_ROUNDS: usize = 32;
+/// Maximum number of signatures to buffer for checkpoints we haven't signed yet.
+const MAX_EARLY_SIGNATURES: usize = 1024;
+/// Maximum number of signatures to buffer for a single checkpoint we haven't signed yet.
+const MAX_EARLY_SIGNATURES_PER_HEIGHT: usize = 256;
+/// Maximum number of checkpoints we haven't signed yet to buffer signatures of a validator for.
+const MAX_EARLY_SIGNATURES_PER_VALIDATOR: usize = 4;
+/// How many blocks ahead of the last executed block we accept signatures for, and how many
+/// blocks behind it we keep them while waiting to sign the checkpoint ourselves.
+const MAX_EARLY_DISTANCE: BlockHeight = 100;
+
+/// A verified partial signature.
+struct Partial {
+    public_key: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+/// The partial signatures collected for the checkpoint at a height.
+struct Round {
+    checkpoint_hash: Vec<u8>,
+    /// The validators eligible to sign, ordered by address, which is the order of the signer bitmap.
+    validators: Vec<Validator<Power>>,
+    /// Partial signatures by the index of the validator.
+    partials: BTreeMap<usize, Partial>,
+    /// The aggregate of all partials, once they represent a quorum.
+    aggregate: Option<AggregateSignature>,
+    /// Our own signature, re-published until the round has an aggregate.
+    own: Option<IPCCheckpointSignature>,
+}
+
+impl Round {
+    fn new(checkpoint_hash: Vec<u8>, mut validators: Vec<Validator<Power>>) -> Self {
+        validators.sort_by_key(|v| EthAddress::from(v.public_key.0).0);
+        Self {
+            checkpoint_hash,
+            validators,
+            partials: Default::default(),
+            aggregate: None,
+            own: None,
+        }
+    }
+
+    /// Verify and add a partial signature, returning whether it was new.
+    fn add(&mut self, validator: &PublicKey, sig: &IPCCheckpointSignature) -> anyhow::Result<bool> {
+        if sig.checkpoint_hash != self.checkpoint_hash {
+            bail!("signature is for a different checkpoint");
+        }
+        let Some(index) = self
+            .validators
+            .iter()
+            .position(|v| v.public_key.0 == *validator)
+        else {
+            bail!("signer is not a validator of the checkpoint");
+        };
+        if self.partials.contains_key(&index) {
+            return Ok(false);
+        }
+        if !bls::verify_possession(&sig.public_key, &sig.proof_of_possession) {
+            bail!("invalid BLS proof of possession");
+        }
+        if !bls::verify(&sig.public_key, &sig.checkpoint_hash, &sig.signature) {
+            bail!("invalid BLS signature");
+        }
+        self.partials.insert(
+            index,
+            Partial {
+                public_key: sig.public_key.clone(),
+                signature: sig.signature.clone(),
+            },
+        );
+        self.try_aggregate()?;
+        Ok(true)
+    }
+
+    /// Aggregate all partials if they are signed by a quorum.
+    ///
+    /// The aggregate is recomputed with every new partial, so it includes all known signers.
+    fn try_aggregate(&mut self) -> anyhow::Result<()> {
+        let total: u128 = self.validators.iter().map(|v| v.power.0 as u128).sum();
+        let signed: u128 = self
+            .partials
+            .keys()
+            .map(|i| self.validators[*i].power.0 as u128)
+            .sum();
+
+        // Same as a CometBFT commit, more than two thirds of the power has to sign.
+        if signed * 3 <= total * 2 {
+            return Ok(());
+        }
+
+        let signatures = self
+            .partials
+            .values()
+            .map(|p| p.signature.as_slice())
+            .collect::<Vec<_>>();
+
+        let signature = bls::aggregate(&signatures)?;
+
+        self.aggregate = Some(AggregateSignature {
+            signature: signature.to_vec(),
+            signer_bitmap: AggregateSignature::bitmap(
+                self.validators.len(),
+                self.partials.keys().copied(),
+            ),
+        });
+        Ok(())
+    }
+
+    /// The BLS public keys of the signers, in the order of the signer bitmap.
+    fn signer_public_keys(&self) -> Vec<Vec<u8>> {
+        self.partials
+            .values()
+            .map(|p| p.public_key.clone())
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    /// The last executed block height.
+    height: BlockHeight,
+    rounds: BTreeMap<BlockHeight, Round>,
+    /// Signatures that arrived before we signed the checkpoint ourselves,
+    /// e.g. because we are still executing the block in which it was created.
+    early: BTreeMap<BlockHeight, Vec<(PublicKey, IPCCheckpointSignature)>>,
+}
+
+impl Inner {
+    /// Checkpoints below the retained rounds are considered done.
+    fn is_pruned(&self, height: BlockHeight) -> bool {
+        self.rounds.len() >= MAX_ROUNDS
+            && self
+                .rounds
+                .first_key_value()
+                .is_some_and(|(h, _)| height < *h)
+    }
+
+    fn open_round(
+        &mut self,
+        height: BlockHeight,
+        checkpoint_hash: Vec<u8>,
+        validators: Vec<Validator<Power>>,
+    ) -> &mut Round {
+        let round = self
+            .rounds
+            .entry(height)
+            .or_insert_with(|| Round::new(checkpoint_hash, validators));
+
+        for (validator, sig) in self.early.remove(&height).unwrap_or_default() {
+            if let Err(e) = round.add(&validator, &sig) {
+                tracing::debug!(
+                    height,
+                    error = e.to_string(),
+                    "dropping early checkpoint signature"
+                );
+            }
+        }
+
+        while self.rounds.len() > MAX_ROUNDS {
+            self.rounds.pop_first();
+        }
+        if let Some((lowest, _)) = self.rounds.first_key_value() {
+            let lowest = *lowest;
+            self.early.retain(|h, _| *h > lowest);
+        }
+
+        self.rounds.get_mut(&height).expect("round was just opened")
+    }
+
+    fn set_height(&mut self, height: BlockHeight) {
+        self.height = height;
+        // Checkpoints we haven't signed by now are not going to be signed by us,
+        // e.g. because we are not in their power table.
+        self.early
+            .retain(|h, _| h.saturating_add(MAX_EARLY_DISTANCE) >= height);
+    }
+
+    /// Buffer a signature of a checkpoint we haven't signed yet, returning whether it was new.
+    ///
+    /// The signature can't be verified without the power table, so the buffer is bounded
+    /// per checkpoint and per validator to keep anyone from filling it up.
+    fn add_early(
+        &mut self,
+        validator: &PublicKey,
+        sig: IPCCheckpointSignature,
+    ) -> anyhow::Result<bool> {
+        if sig.height > self.height.saturating_add(MAX_EARLY_DISTANCE) {
+            bail!("signature is too far ahead of the last executed block");
+        }
+        if sig.height.saturating_add(MAX_EARLY_DISTANCE) < self.height {
+            return Ok(false);
+        }
+        if let Some(sigs) = self.early.get(&sig.height) {
+            if sigs.iter().any(|(v, _)| v == validator) {
+                return Ok(false);
+            }
+            if sigs.len() >= MAX_EARLY_SIGNATURES_PER_HEIGHT {
+                bail!("too many early signatures for the checkpoint");
+            }
+        }
+        let mut buffered = 0;
+        let mut by_validator = 0;
+        for sigs in self.early.values() {
+            buffered += sigs.len();
+            by_validator += sigs.iter().filter(|(v, _)| v == validator).count();
+        }
+        if by_validator >= MAX_EARLY_SIGNATURES_PER_VALIDATOR {
+            bail!("too many early signatures from the validator");
+        }
+        if buffered >= MAX_EARLY_SIGNATURES {
+            bail!("too many early signatures");
+        }
+        self.early
+            .entry(sig.height)
+            .or_default()
+            .push((*validator, sig));
+        Ok(true)
+    }
+}
+
+/// Collects the partial BLS signatures of the validators over bottom-up checkpoints.
+#[derive(Clone)]
+pub struct CheckpointSignaturePool {
+    inner: Arc<Mutex<Inner>>,
+    outbox: mpsc::UnboundedSender<IPCCheckpointSignature>,
+}
+
+impl CheckpointSignaturePool {
+    /// Create an empty pool, along with the receiver of our own signatures, which should be gossiped.
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<IPCCheckpointSignature>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let pool = Self {
+            inner: Default::default(),
+            outbox: tx,
+        };
+        (pool, rx)
+    }
+
+    /// Sign the checkpoint with our BLS key, add the signature to the pool and queue it for gossiping.
+    pub fn sign(
+        &self,
+        height: BlockHeight,
+        checkpoint_hash: Vec<u8>,
+        validators: Vec<Validator<Power>>,
+        validator: &PublicKey,
+        key: &BlsSecretKey,
+    ) -> anyhow::Result<()> {
+        let sig = IPCCheckpointSignature {
+            height,
+            signature: key.sign(&checkpoint_hash).to_vec(),
+            checkpoint_hash,
+            public_key: key.public_key().to_vec(),
+            proof_of_possession: key.prove_possession().to_vec(),
+        };
+        {
+            let mut inner = self.inner.lock().unwrap();
+            let round = inner.open_round(height, sig.checkpoint_hash.clone(), validators);
+            round.add(validator, &sig)?;
+            round.own = Some(sig.clone());
+        }
+        self.outbox
+            .send(sig)
+            .map_err(|_| anyhow!("checkpoint signature gossip has stopped"))
+    }
+
+    /// Record the last executed block height, which bounds the heights of the
+    /// signatures we buffer before signing the checkpoints ourselves.
+    pub fn set_height(&self, height: BlockHeight) {
+        self.inner.lock().unwrap().set_height(height);
+    }
+
+    /// Our own signatures of the checkpoints which don't have an aggregate yet.
+    ///
+    /// Gossip is best effort, so these should be re-published in case validators
+    /// which missed them, or joined the network later, need them for a quorum.
+    pub fn pending_own_signatures(&self) -> Vec<IPCCheckpointSignature> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .rounds
+            .values()
+            .filter(|r| r.aggregate.is_none())
+            .filter_map(|r| r.own.clone())
+            .collect()
+    }
+
+    /// Check whether we have signed the checkpoint at a height already.
+    pub fn has_signed(&self, height: BlockHeight) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner.rounds.contains_key(&height) || inner.is_pruned(height)
+    }
+
+    /// Add a partial signature gossiped by a validator, returning whether it was new.
+    ///
+    /// Signatures of checkpoints we haven't signed yet are buffered until we do,
+    /// because that's when we know the power table to check them against.
+    pub fn add_signature(
+        &self,
+        validator: &PublicKey,
+        sig: IPCCheckpointSignature,
+    ) -> anyhow::Result<bool> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.is_pruned(sig.height) {
+            return Ok(false);
+        }
+        match inner.rounds.get_mut(&sig.height) {
+            Some(round) => round.add(validator, &sig),
+            None => inner.add_early(validator, sig),
+        }
+    }
+
+    /// The aggregate signature of the checkpoint at a height, once a quorum has signed it.
+    pub fn aggregate(&self, height: BlockHeight) -> Option<AggregateSignature> {
+        let inner = self.inner.lock().unwrap();
+        inner.rounds.get(&height).and_then(|r| r.aggregate.clone())
+    }
+
+    /// The BLS public keys of the validators who have signed the checkpoint at a height.
+    pub fn signer_public_keys(&self, height: BlockHeight) -> Vec<Vec<u8>> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .rounds
+            .get(&height)
+            .map(|r| r.signer_public_keys())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fendermint_crypto::SecretKey;
+    use fendermint_vm_genesis::ValidatorKey;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    struct Signer {
+        public_key: PublicKey,
+        bls: BlsSecretKey,
+    }
+
+    fn setup(seed: u64, powers: &[u64]) -> (Vec<Signer>, Vec<Validator<Power>>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut signers = Vec::new();
+        let mut validators = Vec::new();
+        for power in powers {
+            let sk = SecretKey::random(&mut rng);
+            validators.push(Validator {
+                public_key: ValidatorKey(sk.public_key()),
+                power: Power(*power),
+            });
+            signers.push(Signer {
+                public_key: sk.public_key(),
+                bls: BlsSecretKey::derive(&sk).unwrap(),
+            });
+        }
+        (signers, validators)
+    }
+
+    fn partial(signer: &Signer, height: BlockHeight, hash: &[u8]) -> IPCCheckpointSignature {
+        IPCCheckpointSignature {
+            height,
+            checkpoint_hash: hash.to_vec(),
+            public_key: signer.bls.public_key().to_vec(),
+            proof_of_possession: signer.bls.prove_possession().to_vec(),
+            signature: signer.bls.sign(hash).to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_on_quorum() {
+        let (signers, validators) = setup(1, &[10, 10, 10, 10]);
+        let (pool, mut rx) = CheckpointSignaturePool::new();
+        let hash = vec![1u8; 32];
+
+        // A signature arriving before we signed is buffered.
+        assert!(pool
+            .add_signature(&signers[1].public_key, partial(&signers[1], 100, &hash))
+            .unwrap());
+        assert!(!pool.has_signed(100));
+
+        pool.sign(
+            100,
+            hash.clone(),
+            validators.clone(),
+            &signers[0].public_key,
+            &signers[0].bls,
+        )
+        .unwrap();
+        assert!(pool.has_signed(100));
+        assert_eq!(rx.try_recv().unwrap().height, 100);
+
+        // 2 out of 4 is not a quorum.
+        assert!(pool.aggregate(100).is_none());
+
+        // A signature over a different hash is rejected.
+        assert!(pool
+            .add_signature(
+                &signers[2].public_key,
+                partial(&signers[2], 100, &[2u8; 32])
+            )
+            .is_err());
+
+        // A signature claiming someone else's BLS key is rejected.
+        let mut forged = partial(&signers[3], 100, &hash);
+        forged.public_key = signers[2].bls.public_key().to_vec();
+        assert!(pool.add_signature(&signers[3].public_key, forged).is_err());
+
+        assert!(pool
+            .add_signature(&signers[2].public_key, partial(&signers[2], 100, &hash))
+            .unwrap());
+        // Duplicates are ignored.
+        assert!(!pool
+            .add_signature(&signers[2].public_key, partial(&signers[2], 100, &hash))
+            .unwrap());
+
+        let aggregate = pool.aggregate(100).expect("quorum reached");
+        assert_eq!(aggregate.signers().count(), 3);
+
+        let public_keys = pool.signer_public_keys(100);
+        let public_keys = public_keys
+            .iter()
+            .map(|pk| pk.as_slice())
+            .collect::<Vec<_>>();
+        assert!(bls::verify_aggregate(
+            &public_keys,
+            &hash,
+            &aggregate.signature
+        ));
+
+        // The bitmap follows the validators ordered by address.
+        let mut ordered = validators.clone();
+        ordered.sort_by_key(|v| EthAddress::from(v.public_key.0).0);
+        let missing = ordered
+            .iter()
+            .position(|v| v.public_key.0 == signers[3].public_key)
+            .unwrap();
+        assert!(!aggregate.is_signer(missing));
+    }
+
+    #[test]
+    fn test_early_signatures_bounded() {
+        let (signers, validators) = setup(1, &[10, 10, 10]);
+        let (pool, mut rx) = CheckpointSignaturePool::new();
+        let hash = vec![1u8; 32];
+        pool.set_height(10);
+
+        // Too far ahead of the last executed block.
+        assert!(pool
+            .add_signature(
+                &signers[1].public_key,
+                partial(&signers[1], 10 + MAX_EARLY_DISTANCE + 1, &hash)
+            )
+            .is_err());
+
+        // A validator can only have a few heights buffered.
+        for h in 0..MAX_EARLY_SIGNATURES_PER_VALIDATOR as u64 {
+            assert!(pool
+                .add_signature(&signers[1].public_key, partial(&signers[1], 20 + h, &hash))
+                .unwrap());
+        }
+        assert!(pool
+            .add_signature(&signers[1].public_key, partial(&signers[1], 50, &hash))
+            .is_err());
+
+        // Buffered signatures are added when we sign, and ours is pending until there is a quorum.
+        pool.sign(
+            20,
+            hash.clone(),
+            validators.clone(),
+            &signers[0].public_key,
+            &signers[0].bls,
+        )
+        .unwrap();
+        assert_eq!(rx.try_recv().unwrap().height, 20);
+        assert_eq!(pool.pending_own_signatures().len(), 1);
+
+        assert!(pool
+            .add_signature(&signers[2].public_key, partial(&signers[2], 20, &hash))
+            .unwrap());
+        assert!(pool.aggregate(20).is_some());
+        assert!(pool.pending_own_signatures().is_empty());
+
+        // Stale buffered signatures are dropped, which frees up the validator's slots.
+        pool.set_height(23 + MAX_EARLY_DISTANCE + 1);
+        assert!(pool
+            .add_signature(&signers[1].public_key, partial(&signers[1], 130, &hash))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_non_validator_rejected() {
+        let (signers, validators) = setup(1, &[10, 10, 10]);
+        let (outsider, _) = setup(2, &[1]);
+        let (pool, _rx) = CheckpointSignaturePool::new();
+        let hash = vec![1u8; 32];
+
+        pool.sign(
+            1,
+            hash.clone(),
+            validators,
+            &signers[0].public_key,
+            &signers[0].bls,
+        )
+        .unwrap();
+
+        assert!(pool
+            .add_signature(&outsider[0].public_key, partial(&outsider[0], 1, &hash))
+            .is_err());
+    }
+}
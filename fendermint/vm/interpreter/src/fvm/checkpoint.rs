@@ -17,7 +17,7 @@ use ethers::abi::Tokenizable;
 use fendermint_crypto::PublicKey;
 use fendermint_crypto::SecretKey;
 use fendermint_vm_actor_interface::eam::EthAddress;
-use fendermint_vm_actor_interface::ipc::BottomUpCheckpoint;
+use fendermint_vm_actor_interface::ipc::{AbiHash, BottomUpCheckpoint};
 use fendermint_vm_genesis::{Power, Validator, ValidatorKey};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::{address::Address, chainid::ChainID};
@@ -228,6 +228,7 @@ where
 
     for cp in incomplete_checkpoints {
         let height = Height::try_from(cp.block_height.as_u64())?;
+
         // Getting the power table from CometBFT where the history is available.
         let power_table = bft_power_table(client, height)
             .await
@@ -268,6 +269,8 @@ where
                 },
             };
 
+            let checkpoint_hash = checkpoint.abi_hash().to_vec();
+
             // We mustn't do these in parallel because of how nonces are fetched.
             broadcast_signature(
                 &validator_ctx.broadcaster,
@@ -281,6 +284,22 @@ where
             .await
             .context("failed to broadcast checkpoint signature")?;
 
+            // The aggregate is only an off-chain certificate next to the ledger signatures,
+            // so failing to gossip our share must not hold up the checkpoint.
+            if let Some((key, pool)) = &validator_ctx.aggregation {
+                if !pool.has_signed(height.value()) {
+                    if let Err(e) = pool.sign(
+                        height.value(),
+                        checkpoint_hash,
+                        power_table.0,
+                        &validator_ctx.public_key,
+                        key,
+                    ) {
+                        tracing::warn!(?height, error = %e, "failed to sign checkpoint for aggregation");
+                    }
+                }
+            }
+
             emit(CheckpointSigned {
                 role: CheckpointSignedRole::Own,
                 height: height.value(),
@@ -16,6 +16,7 @@ pub mod upgrades;
 pub mod bundle;
 
 pub mod activity;
+pub mod aggregation;
 pub(crate) mod gas;
 pub(crate) mod recall_config;
 pub(crate) mod topdown;
@@ -23,13 +24,16 @@ pub(crate) mod topdown;
 pub use check::FvmCheckRet;
 pub use checkpoint::PowerUpdates;
 pub use exec::{EndBlockOutput, FvmApplyRet};
+use fendermint_crypto::bls::BlsSecretKey;
 use fendermint_crypto::{PublicKey, SecretKey};
 pub use fendermint_vm_message::query::FvmQuery;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::address::Address;
 pub use query::FvmQueryRet;
+use std::sync::Arc;
 use tendermint_rpc::Client;
 
+use self::aggregation::CheckpointSignaturePool;
 pub use self::broadcast::Broadcaster;
 use self::{state::ipc::GatewayCaller, upgrades::UpgradeScheduler};
 
@@ -48,6 +52,9 @@ pub struct ValidatorContext<C> {
     /// Used to broadcast transactions. It might use a different secret key for
     /// signing transactions than the validator's block producing key.
     broadcaster: Broadcaster<C>,
+    /// The BLS key derived from the secret key and the pool collecting the gossiped
+    /// checkpoint signatures into an aggregate, in addition to the ones in the ledger.
+    aggregation: Option<(Arc<BlsSecretKey>, CheckpointSignaturePool)>,
 }
 
 impl<C> ValidatorContext<C> {
@@ -59,8 +66,19 @@ impl<C> ValidatorContext<C> {
             public_key,
             addr,
             broadcaster,
+            aggregation: None,
         }
     }
+
+    /// Sign bottom-up checkpoints with BLS and gossip the signatures through the pool.
+    pub fn with_checkpoint_aggregation(
+        mut self,
+        pool: CheckpointSignaturePool,
+    ) -> anyhow::Result<Self> {
+        let key = BlsSecretKey::derive(&self.secret_key)?;
+        self.aggregation = Some((Arc::new(key), pool));
+        Ok(self)
+    }
 }
 
 /// Interpreter working on already verified unsigned messages.
@@ -5,8 +5,8 @@
 use crate::fvm::FvmMessage;
 use anyhow::{bail, Context};
 use fendermint_actor_blobs_shared::state::TokenCreditRate;
-use fendermint_actor_recall_config_shared::Method::GetConfig;
-use fendermint_actor_recall_config_shared::RecallConfig;
+use fendermint_actor_recall_config_shared::Method::{GetConfig, GetPendingConfig};
+use fendermint_actor_recall_config_shared::{RecallConfig, ScheduledConfig};
 use fendermint_vm_actor_interface::recall_config::RECALL_CONFIG_ACTOR_ADDR;
 use fendermint_vm_actor_interface::system;
 use fvm::executor::{ApplyKind, ApplyRet, Executor};
@@ -27,6 +27,8 @@ pub struct RecallConfigTracker {
     pub blob_min_ttl: ChainEpoch,
     /// The default epoch duration a blob is stored.
     pub blob_default_ttl: ChainEpoch,
+    /// The activation epoch of a scheduled config change that has not been activated yet.
+    pub pending_config_activation: Option<ChainEpoch>,
 }
 
 impl RecallConfigTracker {
@@ -37,6 +39,7 @@ impl RecallConfigTracker {
             blob_credit_debit_interval: Zero::zero(),
             blob_min_ttl: Zero::zero(),
             blob_default_ttl: Zero::zero(),
+            pending_config_activation: None,
         };
 
         let reading = Self::read_recall_config(executor)?;
@@ -47,31 +50,65 @@ impl RecallConfigTracker {
         ret.blob_min_ttl = reading.blob_min_ttl;
         ret.blob_default_ttl = reading.blob_default_ttl;
 
+        ret.pending_config_activation =
+            Self::read_pending_recall_config(executor)?.map(|pending| pending.activation_epoch);
+
         Ok(ret)
     }
 
+    /// Returns whether a scheduled config change is due at `height` and needs to be activated.
+    pub fn is_config_activation_due(&self, height: ChainEpoch) -> bool {
+        self.pending_config_activation
+            .is_some_and(|activation_epoch| activation_epoch <= height)
+    }
+
     pub fn read_recall_config<E: Executor>(executor: &mut E) -> anyhow::Result<RecallConfig> {
-        let msg = FvmMessage {
+        let apply_ret =
+            Self::apply_implicit_message(executor, Self::query_message(GetConfig as u64))?;
+
+        if let Some(err) = apply_ret.failure_info {
+            bail!("failed to acquire recall config: {}", err);
+        }
+
+        fvm_ipld_encoding::from_slice::<RecallConfig>(&apply_ret.msg_receipt.return_data)
+            .context("failed to parse recall config")
+    }
+
+    fn read_pending_recall_config<E: Executor>(
+        executor: &mut E,
+    ) -> anyhow::Result<Option<ScheduledConfig>> {
+        let apply_ret =
+            Self::apply_implicit_message(executor, Self::query_message(GetPendingConfig as u64))?;
+
+        if let Some(err) = apply_ret.failure_info {
+            bail!("failed to acquire pending recall config: {}", err);
+        }
+        // Failures without a backtrace don't set the failure info.
+        let exit_code = apply_ret.msg_receipt.exit_code;
+        if !exit_code.is_success() {
+            bail!(
+                "failed to acquire pending recall config: exit code {}",
+                exit_code
+            );
+        }
+
+        fvm_ipld_encoding::from_slice::<Option<ScheduledConfig>>(&apply_ret.msg_receipt.return_data)
+            .context("failed to parse pending recall config")
+    }
+
+    fn query_message(method_num: u64) -> FvmMessage {
+        FvmMessage {
             from: system::SYSTEM_ACTOR_ADDR,
             to: RECALL_CONFIG_ACTOR_ADDR,
             sequence: 0, // irrelevant for implicit executions.
             gas_limit: i64::MAX as u64,
-            method_num: GetConfig as u64,
+            method_num,
             params: fvm_ipld_encoding::RawBytes::default(),
             value: Default::default(),
             version: Default::default(),
             gas_fee_cap: Default::default(),
             gas_premium: Default::default(),
-        };
-
-        let apply_ret = Self::apply_implicit_message(executor, msg)?;
-
-        if let Some(err) = apply_ret.failure_info {
-            bail!("failed to acquire recall config: {}", err);
         }
-
-        fvm_ipld_encoding::from_slice::<RecallConfig>(&apply_ret.msg_receipt.return_data)
-            .context("failed to parse recall config")
     }
 
     fn apply_implicit_message<E: Executor>(
@@ -429,7 +429,9 @@ impl GenesisBuilder {
         // Initialize the recall config actor.
         let recall_config_state = fendermint_actor_recall_config::State {
             admin: None,
+            pending_admin: None,
             config: fendermint_actor_recall_config_shared::RecallConfig::default(),
+            pending_config: None,
         };
         state
             .create_custom_actor(
@@ -0,0 +1,337 @@
+// Copyright 2025 Recall Contributors
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::time::Duration;
+
+use async_stm::{atomically, queues::TQueueLike, Stm, TVar};
+use fendermint_vm_topdown::IPCChallengeAnswer;
+use ipc_api::subnet_id::SubnetID;
+use ipc_ipld_resolver::{Client, ResolverIrohReadRequest, ValidatorKey, VoteRecord};
+use iroh::blobs::Hash;
+use libp2p::identity::{Keypair, PublicKey};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::iroh::reenqueue;
+use crate::pool::{ResolveQueue, ResolveTask, TaskType};
+use crate::proof::{self, ChunkProof};
+
+/// Returns the key identifying a storage challenge, which validators sign to claim an answer.
+///
+/// The key commits to all the challenge parameters, so an answer proven against
+/// different parameters than the ones issued on-chain is never counted.
+pub fn challenge_key(id: u64, hash: &Hash, size: u64, offset: u64) -> Hash {
+    let mut data = b"challenge".to_vec();
+    data.extend_from_slice(&id.to_be_bytes());
+    data.extend_from_slice(hash.as_bytes());
+    data.extend_from_slice(&size.to_be_bytes());
+    data.extend_from_slice(&offset.to_be_bytes());
+    Hash::new(data)
+}
+
+/// Verifies the inclusion proof in a storage challenge answer.
+pub fn verify_answer(answer: &IPCChallengeAnswer) -> bool {
+    let index = answer.offset / proof::CHUNK_LEN;
+    if answer.offset % proof::CHUNK_LEN != 0 {
+        return false;
+    }
+    let proof = ChunkProof {
+        chunk: answer.chunk.clone(),
+        siblings: answer.siblings.clone(),
+    };
+    proof::verify(&answer.hash, answer.size, index, &proof)
+}
+
+/// Signs a storage challenge answer with the validator key.
+pub fn sign_answer(key: &Keypair, answer: &mut IPCChallengeAnswer) -> anyhow::Result<()> {
+    let payload = challenge_key(answer.id, &answer.hash, answer.size, answer.offset);
+    answer.signature = key.sign(payload.as_bytes())?;
+    Ok(())
+}
+
+/// Verifies that a storage challenge answer was signed by the given validator.
+pub fn verify_answer_signature(public_key: &PublicKey, answer: &IPCChallengeAnswer) -> bool {
+    let payload = challenge_key(answer.id, &answer.hash, answer.size, answer.offset);
+    public_key.verify(payload.as_bytes(), &answer.signature)
+}
+
+/// Verified answers to open storage challenges, keyed by challenge ID and validator,
+/// waiting to be proposed for inclusion in a block.
+#[derive(Clone)]
+pub struct ChallengeAnswers {
+    answers: TVar<im::HashMap<u64, im::HashMap<ValidatorKey, IPCChallengeAnswer>>>,
+}
+
+impl Default for ChallengeAnswers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChallengeAnswers {
+    pub fn new() -> Self {
+        Self {
+            answers: TVar::new(Default::default()),
+        }
+    }
+
+    /// Adds a verified answer. Returns whether the validator had not answered the challenge yet.
+    pub fn add(&self, validator: ValidatorKey, answer: IPCChallengeAnswer) -> Stm<bool> {
+        let mut answers = self.answers.read_clone()?;
+        let entry = answers.entry(answer.id).or_default();
+        if entry.contains_key(&validator) {
+            return Ok(false);
+        }
+        entry.insert(validator, answer);
+        self.answers.write(answers)?;
+        Ok(true)
+    }
+
+    /// Returns the answers received for a challenge.
+    pub fn get(&self, id: u64) -> Stm<Vec<(ValidatorKey, IPCChallengeAnswer)>> {
+        let answers = self.answers.read()?;
+        Ok(answers
+            .get(&id)
+            .map(|answers| answers.clone().into_iter().collect())
+            .unwrap_or_default())
+    }
+
+    /// Removes the answers to a challenge once it is closed.
+    pub fn remove(&self, id: u64) -> Stm<()> {
+        self.answers.update(|answers| answers.without(&id))
+    }
+}
+
+/// The challenge resolver takes storage challenges from the [ResolvePool](crate::pool::ResolvePool),
+/// proves that the challenged chunk is held by the local iroh node, and gossips the proof.
+pub struct ChallengeResolver<V> {
+    client: Client<V>,
+    queue: ResolveQueue,
+    retry_delay: Duration,
+    answers: ChallengeAnswers,
+    key: Keypair,
+    subnet_id: SubnetID,
+    to_vote: fn(IPCChallengeAnswer) -> V,
+}
+
+impl<V> ChallengeResolver<V>
+where
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    pub fn new(
+        client: Client<V>,
+        queue: ResolveQueue,
+        retry_delay: Duration,
+        answers: ChallengeAnswers,
+        key: Keypair,
+        subnet_id: SubnetID,
+        to_vote: fn(IPCChallengeAnswer) -> V,
+    ) -> Self {
+        Self {
+            client,
+            queue,
+            retry_delay,
+            answers,
+            key,
+            subnet_id,
+            to_vote,
+        }
+    }
+
+    /// Start taking challenges from the resolver pool and answering them.
+    pub async fn run(self) {
+        loop {
+            let task = atomically(|| self.queue.read()).await;
+
+            let client = self.client.clone();
+            let queue = self.queue.clone();
+            let answers = self.answers.clone();
+            let key = self.key.clone();
+            let subnet_id = self.subnet_id.clone();
+            let retry_delay = self.retry_delay;
+            let to_vote = self.to_vote;
+            tokio::spawn(async move {
+                answer_challenge(
+                    task,
+                    client,
+                    queue,
+                    retry_delay,
+                    answers,
+                    key,
+                    subnet_id,
+                    to_vote,
+                )
+                .await
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn answer_challenge<V>(
+    task: ResolveTask,
+    client: Client<V>,
+    queue: ResolveQueue,
+    retry_delay: Duration,
+    answers: ChallengeAnswers,
+    key: Keypair,
+    subnet_id: SubnetID,
+    to_vote: fn(IPCChallengeAnswer) -> V,
+) where
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    let TaskType::AnswerChallenge {
+        id,
+        blob_hash,
+        size,
+        offset,
+    } = task.task_type()
+    else {
+        tracing::error!(hash = ?task.hash(), "unexpected task type for challenge resolver");
+        return;
+    };
+    // Iroh serves the challenged chunk with the parent nodes from the blob's outboard,
+    // so we don't need to read the rest of the blob.
+    let index = offset / proof::CHUNK_LEN;
+    match client.read_chunk_proof(blob_hash, index).await {
+        Err(e) => {
+            tracing::error!(
+                error = e.to_string(),
+                "failed to submit iroh challenge task"
+            );
+        }
+        Ok(Ok(range)) => {
+            let answer =
+                match proof::prove_range(size, index, &range.parents, range.offset, &range.data) {
+                    Ok(proof) => IPCChallengeAnswer {
+                        id,
+                        hash: blob_hash,
+                        size,
+                        offset,
+                        chunk: proof.chunk,
+                        siblings: proof.siblings,
+                        signature: Vec::new(),
+                    },
+                    Err(e) => {
+                        tracing::error!(id, error = e.to_string(), "failed to prove challenge");
+                        return;
+                    }
+                };
+            // Don't gossip a proof others would reject, e.g., if the local copy is corrupted.
+            if !verify_answer(&answer) {
+                tracing::error!(id, hash = ?blob_hash, "local blob does not match challenge");
+                return;
+            }
+            atomically(|| task.set_resolved()).await;
+            add_own_answer(answer, client, answers, key, subnet_id, to_vote).await;
+        }
+        Ok(Err(e)) => {
+            tracing::error!(
+                id,
+                hash = ?blob_hash,
+                error = e.to_string(),
+                "failed to read challenged chunk"
+            );
+            reenqueue(task, queue, retry_delay).await;
+        }
+    }
+}
+
+async fn add_own_answer<V>(
+    mut answer: IPCChallengeAnswer,
+    client: Client<V>,
+    answers: ChallengeAnswers,
+    key: Keypair,
+    subnet_id: SubnetID,
+    to_vote: fn(IPCChallengeAnswer) -> V,
+) where
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    let id = answer.id;
+    if let Err(e) = sign_answer(&key, &mut answer) {
+        tracing::error!(id, error = e.to_string(), "failed to sign challenge answer");
+        return;
+    }
+    // Keep our own answer around, so we can propose it if we are the next proposer
+    let validator_key = ValidatorKey::from(key.public());
+    let is_new = atomically(|| answers.add(validator_key.clone(), answer.clone())).await;
+    if !is_new {
+        return;
+    }
+
+    match VoteRecord::signed(&key, subnet_id, to_vote(answer)) {
+        Ok(vote) => {
+            // Send our own answer to peers
+            if let Err(e) = client.publish_vote(vote) {
+                tracing::error!(error = e.to_string(), "failed to publish vote");
+            } else {
+                tracing::debug!(id, "published challenge answer");
+            }
+        }
+        Err(e) => {
+            tracing::error!(error = e.to_string(), "failed to sign vote");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn test_verify_answer() {
+        let mut data = vec![0u8; 3000];
+        rand::thread_rng().fill_bytes(&mut data);
+        let hash = Hash::new(&data);
+        let proof = proof::prove(&data, 1).unwrap();
+        let mut answer = IPCChallengeAnswer {
+            id: 7,
+            hash,
+            size: data.len() as u64,
+            offset: proof::CHUNK_LEN,
+            chunk: proof.chunk,
+            siblings: proof.siblings,
+            signature: Vec::new(),
+        };
+        assert!(verify_answer(&answer));
+
+        // Unaligned offsets are rejected
+        answer.offset += 1;
+        assert!(!verify_answer(&answer));
+    }
+
+    #[test]
+    fn test_sign_answer() {
+        let key = Keypair::generate_secp256k1();
+        let mut answer = IPCChallengeAnswer {
+            id: 7,
+            hash: Hash::new(b"blob"),
+            size: 2048,
+            offset: 1024,
+            chunk: vec![1; 1024],
+            siblings: vec![[2; 32]],
+            signature: Vec::new(),
+        };
+        sign_answer(&key, &mut answer).unwrap();
+        assert!(verify_answer_signature(&key.public(), &answer));
+
+        // The signature is bound to the validator and the challenge
+        let other = Keypair::generate_secp256k1();
+        assert!(!verify_answer_signature(&other.public(), &answer));
+        answer.id = 8;
+        assert!(!verify_answer_signature(&key.public(), &answer));
+    }
+
+    #[test]
+    fn test_challenge_key() {
+        let hash = Hash::new(b"blob");
+        let key = challenge_key(1, &hash, 2048, 1024);
+        assert_eq!(key, challenge_key(1, &hash, 2048, 1024));
+        assert_ne!(key, challenge_key(2, &hash, 2048, 1024));
+        assert_ne!(key, challenge_key(1, &hash, 4096, 1024));
+        assert_ne!(key, challenge_key(1, &hash, 2048, 0));
+        assert_ne!(key, hash);
+    }
+}
@@ -217,6 +217,12 @@ fn start_resolve<V>(
                     }
                 };
             }
+            TaskType::AnswerChallenge { .. } => {
+                tracing::error!(
+                    hash = ?task.hash(),
+                    "storage challenges are answered by the challenge resolver"
+                );
+            }
         };
     });
 }
@@ -270,7 +276,11 @@ where
     }
 }
 
-async fn reenqueue(task: ResolveTask, queue: ResolveQueue, retry_delay: Duration) -> bool {
+pub(crate) async fn reenqueue(
+    task: ResolveTask,
+    queue: ResolveQueue,
+    retry_delay: Duration,
+) -> bool {
     if atomically(|| task.add_attempt()).await {
         tracing::error!(
             hash = ?task.hash(),
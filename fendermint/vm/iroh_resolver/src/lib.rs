@@ -2,6 +2,8 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub mod challenge;
 pub mod iroh;
 pub mod observe;
 pub mod pool;
+pub mod proof;
@@ -89,9 +89,15 @@ pub enum TaskType {
     },
     CloseReadRequest {
         blob_hash: Hash,
-        offset: u32,
+        offset: u64,
         len: u32,
     },
+    AnswerChallenge {
+        id: u64,
+        blob_hash: Hash,
+        size: u64,
+        offset: u64,
+    },
 }
 
 impl ResolveTask {
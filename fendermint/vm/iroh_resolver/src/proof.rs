@@ -0,0 +1,300 @@
+// Copyright 2025 Recall Contributors
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! BLAKE3 inclusion proofs for single chunks of a blob.
+//!
+//! A BLAKE3 hash is the root of a binary tree over 1 KiB chunks, where the left subtree of
+//! every parent node covers the largest power of two number of chunks that leaves at least one
+//! chunk for the right subtree. A chunk can therefore be proven against the blob hash with the
+//! chunk bytes and the chaining values of the sibling subtrees on the path from the root.
+//!
+//! Iroh keeps the parent nodes of the tree above groups of chunks in the bao outboard of a blob,
+//! and serves them for a range of chunks, so a proof only needs the group of the challenged chunk.
+
+use anyhow::bail;
+use blake3::guts::{parent_cv, ChunkState};
+use iroh::blobs::Hash;
+
+/// The size of a BLAKE3 chunk.
+pub const CHUNK_LEN: u64 = blake3::guts::CHUNK_LEN as u64;
+
+/// An inclusion proof for a single chunk of a blob.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkProof {
+    /// The bytes of the chunk.
+    pub chunk: Vec<u8>,
+    /// The chaining values of the sibling subtrees, ordered from the root down to the chunk.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// Returns the number of BLAKE3 chunks in a blob of the given size.
+/// An empty blob consists of a single empty chunk.
+pub fn num_chunks(size: u64) -> u64 {
+    size.div_ceil(CHUNK_LEN).max(1)
+}
+
+/// Creates an inclusion proof for the chunk at `index` of `data`.
+pub fn prove(data: &[u8], index: u64) -> anyhow::Result<ChunkProof> {
+    prove_range(data.len() as u64, index, &[], 0, data)
+}
+
+/// Creates an inclusion proof for the chunk at `index` of a blob with the given size from a
+/// range of the blob, i.e., the chaining value pairs of the parent nodes on the path from the
+/// root down to the leaf that contains the chunk, and the leaf data starting at `leaf_offset`.
+///
+/// Siblings of subtrees covered by the leaf are computed from its data, all others are taken
+/// from the parent nodes.
+pub fn prove_range(
+    size: u64,
+    index: u64,
+    parents: &[([u8; 32], [u8; 32])],
+    leaf_offset: u64,
+    leaf: &[u8],
+) -> anyhow::Result<ChunkProof> {
+    let chunks = num_chunks(size);
+    if index >= chunks {
+        bail!("chunk index {} out of range ({} chunks)", index, chunks);
+    }
+    if leaf_offset % CHUNK_LEN != 0 {
+        bail!("leaf offset {} is not chunk aligned", leaf_offset);
+    }
+    let leaf_start = leaf_offset / CHUNK_LEN;
+    let leaf_end = leaf_start + num_chunks(leaf.len() as u64);
+    if index < leaf_start || index >= leaf_end {
+        bail!(
+            "chunk index {} is not in leaf (chunks {}..{})",
+            index,
+            leaf_start,
+            leaf_end
+        );
+    }
+
+    let mut parents = parents.iter();
+    let mut siblings = Vec::new();
+    let (mut start, mut count) = (0, chunks);
+    while count > 1 {
+        let left = left_len(count);
+        let is_left = index < start + left;
+        let sibling = if start >= leaf_start && start + count <= leaf_end {
+            if is_left {
+                subtree_cv(leaf, leaf_start, start + left, count - left)
+            } else {
+                subtree_cv(leaf, leaf_start, start, left)
+            }
+        } else {
+            let Some((left_cv, right_cv)) = parents.next() else {
+                bail!("missing parent nodes for chunk index {}", index);
+            };
+            if is_left {
+                blake3::Hash::from(*right_cv)
+            } else {
+                blake3::Hash::from(*left_cv)
+            }
+        };
+        siblings.push(*sibling.as_bytes());
+        if is_left {
+            count = left;
+        } else {
+            start += left;
+            count -= left;
+        }
+    }
+    if parents.next().is_some() {
+        bail!("unexpected parent nodes for chunk index {}", index);
+    }
+    Ok(ChunkProof {
+        chunk: chunk_bytes(leaf, leaf_start, index).to_vec(),
+        siblings,
+    })
+}
+
+/// Verifies an inclusion proof for the chunk at `index` of a blob with the given hash and size.
+pub fn verify(hash: &Hash, size: u64, index: u64, proof: &ChunkProof) -> bool {
+    let chunks = num_chunks(size);
+    if index >= chunks {
+        return false;
+    }
+    let expected_len = CHUNK_LEN.min(size - index * CHUNK_LEN);
+    if proof.chunk.len() as u64 != expected_len {
+        return false;
+    }
+
+    // Walk down the tree to find which side of each parent the chunk is on
+    let mut is_left = Vec::with_capacity(proof.siblings.len());
+    let (mut start, mut count) = (0, chunks);
+    while count > 1 {
+        let left = left_len(count);
+        if index < start + left {
+            is_left.push(true);
+            count = left;
+        } else {
+            is_left.push(false);
+            start += left;
+            count -= left;
+        }
+    }
+    if is_left.len() != proof.siblings.len() {
+        return false;
+    }
+
+    // Walk back up, combining the chunk with its siblings
+    let mut cv = ChunkState::new(index)
+        .update(&proof.chunk)
+        .finalize(is_left.is_empty());
+    for (level, (left, sibling)) in is_left.iter().zip(&proof.siblings).enumerate().rev() {
+        let sibling = blake3::Hash::from(*sibling);
+        let is_root = level == 0;
+        cv = if *left {
+            parent_cv(&cv, &sibling, is_root)
+        } else {
+            parent_cv(&sibling, &cv, is_root)
+        };
+    }
+    cv.as_bytes() == hash.as_bytes()
+}
+
+/// Returns the number of chunks in the left subtree of a tree with `count` chunks.
+fn left_len(count: u64) -> u64 {
+    debug_assert!(count > 1);
+    1 << (63 - (count - 1).leading_zeros())
+}
+
+/// Returns the bytes of the chunk at `index`, where `data` starts at chunk `base`.
+fn chunk_bytes(data: &[u8], base: u64, index: u64) -> &[u8] {
+    let start = (((index - base) * CHUNK_LEN) as usize).min(data.len());
+    let end = (start + CHUNK_LEN as usize).min(data.len());
+    &data[start..end]
+}
+
+/// Computes the (non-root) chaining value of the subtree covering `count` chunks from `start`,
+/// where `data` starts at chunk `base`.
+fn subtree_cv(data: &[u8], base: u64, start: u64, count: u64) -> blake3::Hash {
+    if count == 1 {
+        return ChunkState::new(start)
+            .update(chunk_bytes(data, base, start))
+            .finalize(false);
+    }
+    let left = left_len(count);
+    parent_cv(
+        &subtree_cv(data, base, start, left),
+        &subtree_cv(data, base, start + left, count - left),
+        false,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    fn random_data(size: usize) -> Vec<u8> {
+        let mut data = vec![0u8; size];
+        rand::thread_rng().fill_bytes(&mut data);
+        data
+    }
+
+    #[test]
+    fn test_left_len() {
+        assert_eq!(left_len(2), 1);
+        assert_eq!(left_len(3), 2);
+        assert_eq!(left_len(4), 2);
+        assert_eq!(left_len(5), 4);
+        assert_eq!(left_len(8), 4);
+        assert_eq!(left_len(9), 8);
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        for size in [0, 1, 1024, 1025, 2048, 3000, 4096, 5000, 8 * 1024 + 7] {
+            let data = random_data(size);
+            let hash = Hash::new(&data);
+            for index in 0..num_chunks(size as u64) {
+                let proof = prove(&data, index).unwrap();
+                assert!(
+                    verify(&hash, size as u64, index, &proof),
+                    "size {} index {}",
+                    size,
+                    index
+                );
+            }
+        }
+    }
+
+    /// Returns the parent nodes and the leaf a bao tree with groups of 16 chunks
+    /// would serve for the chunk at `index`.
+    fn range(data: &[u8], index: u64) -> (Vec<([u8; 32], [u8; 32])>, u64, &[u8]) {
+        const GROUP: u64 = 16;
+        let mut parents = Vec::new();
+        let (mut start, mut count) = (0, num_chunks(data.len() as u64));
+        while count > GROUP {
+            let left = left_len(count);
+            parents.push((
+                *subtree_cv(data, 0, start, left).as_bytes(),
+                *subtree_cv(data, 0, start + left, count - left).as_bytes(),
+            ));
+            if index < start + left {
+                count = left;
+            } else {
+                start += left;
+                count -= left;
+            }
+        }
+        let offset = start * CHUNK_LEN;
+        let end = ((start + count) * CHUNK_LEN).min(data.len() as u64);
+        (parents, offset, &data[offset as usize..end as usize])
+    }
+
+    #[test]
+    fn test_prove_range() {
+        for size in [1000, 16 * 1024, 16 * 1024 + 1, 50 * 1024 + 3, 100 * 1024] {
+            let data = random_data(size);
+            let hash = Hash::new(&data);
+            for index in 0..num_chunks(size as u64) {
+                let (parents, offset, leaf) = range(&data, index);
+                let proof = prove_range(size as u64, index, &parents, offset, leaf).unwrap();
+                assert_eq!(proof, prove(&data, index).unwrap());
+                assert!(verify(&hash, size as u64, index, &proof));
+            }
+        }
+
+        // The chunk must be in the leaf, and all parent nodes must be used
+        let data = random_data(50 * 1024);
+        let (mut parents, offset, leaf) = range(&data, 20);
+        assert!(prove_range(data.len() as u64, 0, &parents, offset, leaf).is_err());
+        parents.push(parents[0]);
+        assert!(prove_range(data.len() as u64, 20, &parents, offset, leaf).is_err());
+        parents.clear();
+        assert!(prove_range(data.len() as u64, 20, &parents, offset, leaf).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_invalid_proofs() {
+        let size = 5000;
+        let data = random_data(size);
+        let hash = Hash::new(&data);
+        let proof = prove(&data, 2).unwrap();
+
+        // Wrong index
+        assert!(!verify(&hash, size as u64, 1, &proof));
+        assert!(!verify(&hash, size as u64, 5, &proof));
+        // Wrong size, which changes the path to the chunk
+        assert!(!verify(&hash, size as u64 * 2, 2, &proof));
+        // Wrong hash
+        assert!(!verify(&Hash::new(b"other"), size as u64, 2, &proof));
+        // Tampered chunk
+        let mut tampered = proof.clone();
+        tampered.chunk[0] ^= 1;
+        assert!(!verify(&hash, size as u64, 2, &tampered));
+        // Tampered sibling
+        let mut tampered = proof.clone();
+        tampered.siblings[0][0] ^= 1;
+        assert!(!verify(&hash, size as u64, 2, &tampered));
+        // Missing sibling
+        let mut tampered = proof;
+        tampered.siblings.pop();
+        assert!(!verify(&hash, size as u64, 2, &tampered));
+        // Out of range
+        assert!(prove(&data, 5).is_err());
+    }
+}
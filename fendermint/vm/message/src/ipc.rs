@@ -46,6 +46,18 @@ pub enum IpcMessage {
 
     /// Proposed by validators when a read request has been closed.
     ReadRequestClosed(ClosedReadRequest),
+
+    /// Proposed by validators at the blob challenge interval.
+    IssueBlobChallenges,
+
+    /// Proposed by validators with a signed answer to an open storage challenge.
+    BlobChallengeAnswered(AnsweredChallenge),
+
+    /// Proposed by validators when the deadline of a storage challenge has passed.
+    BlobChallengeClosed(ClosedChallenge),
+
+    /// Proposed by validators once a scheduled Recall config change is due.
+    ActivateRecallConfig,
 }
 
 /// A message relayed by a user on the current subnet.
@@ -154,14 +166,18 @@ pub struct PendingBlob {
 pub struct ClosedReadRequest {
     /// The request ID.
     pub id: Hash,
+    /// The sequence number of the chunk being read.
+    pub chunk: u32,
     /// The hash of the blob to read from.
     pub blob_hash: Hash,
     /// The offset in the blob to read from.
-    pub offset: u32,
-    /// The length of the read request.
+    pub offset: u64,
+    /// The length of the chunk.
     pub len: u32,
     /// The address and method to callback when the read request is closed.
     pub callback: (Address, MethodNum),
+    /// Whether the chunk sequence number is delivered to the callback.
+    pub chunked: bool,
     /// The data read from the blob.
     pub response: Vec<u8>,
 }
@@ -171,14 +187,57 @@ pub struct ClosedReadRequest {
 pub struct PendingReadRequest {
     /// The request ID.
     pub id: Hash,
+    /// The sequence number of the chunk being read.
+    pub chunk: u32,
     /// The hash of the blob to read from.
     pub blob_hash: Hash,
     /// The offset in the blob to read from.
-    pub offset: u32,
-    /// The length of the read request.
+    pub offset: u64,
+    /// The length of the chunk.
     pub len: u32,
     /// The address and method to callback when the read request is closed.
     pub callback: (Address, MethodNum),
+    /// Whether the chunk sequence number is delivered to the callback.
+    pub chunked: bool,
+}
+
+/// A validator's answer to an open storage challenge.
+///
+/// The answer is signed by the validator, so every node can attribute it deterministically
+/// when processing the proposal, regardless of which answers it received over gossip.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct AnsweredChallenge {
+    /// The challenge ID.
+    pub id: u64,
+    /// The hash of the challenged blob.
+    pub hash: Hash,
+    /// The size of the challenged blob.
+    pub size: u64,
+    /// The offset of the challenged chunk.
+    pub offset: u64,
+    /// The bytes of the challenged chunk.
+    pub chunk: Vec<u8>,
+    /// The chaining values of the sibling subtrees on the path from the root to the chunk.
+    pub siblings: Vec<[u8; 32]>,
+    /// The protobuf encoded public key of the validator that answered the challenge.
+    pub public_key: Vec<u8>,
+    /// The validator's signature over the challenge parameters.
+    pub signature: Vec<u8>,
+}
+
+/// A storage challenge whose deadline has passed.
+///
+/// The validators that answered it are read from the ledger when the challenge is closed.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ClosedChallenge {
+    /// The challenge ID.
+    pub id: u64,
+    /// The hash of the challenged blob.
+    pub hash: Hash,
+    /// The size of the challenged blob.
+    pub size: u64,
+    /// The offset of the challenged chunk.
+    pub offset: u64,
 }
 
 #[cfg(feature = "arb")]
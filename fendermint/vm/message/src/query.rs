@@ -11,6 +11,8 @@ use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
 use fendermint_vm_encoding::IsHumanReadable;
+use ipc_api::checkpoint::AggregateSignature;
+use ipc_api::HumanReadable;
 
 /// Height at which to run a query.
 #[derive(Debug, Clone, PartialEq, Eq, Copy, Default)]
@@ -79,6 +81,23 @@ pub enum FvmQuery {
     BuiltinActors,
 }
 
+/// ABCI query path for the aggregate BLS signature of a bottom-up checkpoint.
+///
+/// The signatures are aggregated by the validators outside the ledger, so this is not
+/// an [`FvmQuery`]. The query data is the IPLD encoded height of the checkpoint.
+pub const CHECKPOINT_AGGREGATE_QUERY_PATH: &str = "/checkpoint/aggregate";
+
+/// The aggregate BLS signature of a bottom-up checkpoint.
+#[serde_as]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct CheckpointAggregate {
+    /// The aggregate signature and the bitmap of the validators who signed.
+    pub aggregate: AggregateSignature,
+    /// The BLS public keys of the signers, in the order of the signer bitmap.
+    #[serde_as(as = "Vec<HumanReadable>")]
+    pub signer_public_keys: Vec<Vec<u8>>,
+}
+
 /// State of all actor implementations.
 ///
 /// This is a copy of `fvm::state_tree::ActorState` so that this crate
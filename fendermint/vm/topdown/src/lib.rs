@@ -8,6 +8,7 @@ pub mod sync;
 
 pub mod convert;
 pub mod proxy;
+pub mod quorum;
 mod toggle;
 pub mod voting;
 
@@ -179,6 +180,68 @@ impl Display for IPCReadRequestClosed {
     }
 }
 
+/// A validator's answer to a blob storage challenge.
+///
+/// The answer carries a BLAKE3 inclusion proof for the challenged chunk, so that every
+/// validator receiving it can verify it against the blob hash before counting it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IPCChallengeAnswer {
+    /// The challenge ID.
+    pub id: u64,
+    /// The hash of the challenged blob.
+    pub hash: Hash,
+    /// The size of the challenged blob.
+    pub size: u64,
+    /// The offset of the challenged chunk.
+    pub offset: u64,
+    /// The bytes of the challenged chunk.
+    pub chunk: Vec<u8>,
+    /// The chaining values of the sibling subtrees on the path from the root to the chunk.
+    pub siblings: Vec<[u8; 32]>,
+    /// The validator's signature over the challenge parameters, which lets the answer be
+    /// included in a block and attributed to the validator by every node.
+    pub signature: Vec<u8>,
+}
+
+impl Display for IPCChallengeAnswer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IPCChallengeAnswer(id: {}, hash: {}, offset: {})",
+            self.id, self.hash, self.offset
+        )
+    }
+}
+
+/// A validator's partial BLS signature over a bottom-up checkpoint.
+///
+/// The public key is bound to the validator by the signed vote record carrying it,
+/// and the proof of possession guards the aggregate against rogue keys.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IPCCheckpointSignature {
+    /// The height of the checkpoint.
+    pub height: BlockHeight,
+    /// The ABI hash of the checkpoint.
+    pub checkpoint_hash: Vec<u8>,
+    /// The BLS public key of the validator.
+    pub public_key: Vec<u8>,
+    /// Proof that the validator holds the BLS secret key.
+    pub proof_of_possession: Vec<u8>,
+    /// The BLS signature over the checkpoint hash.
+    pub signature: Vec<u8>,
+}
+
+impl Display for IPCCheckpointSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IPCCheckpointSignature(height: {}, checkpoint_hash: {})",
+            self.height,
+            hex::encode(&self.checkpoint_hash)
+        )
+    }
+}
+
 #[async_trait]
 pub trait ParentViewProvider {
     /// Obtain the genesis epoch of the current subnet in the parent
@@ -0,0 +1,446 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Quorum reads from several parent endpoints, so that a single dishonest or lagging
+//! RPC provider can neither stall nor mislead the top-down finality.
+
+use crate::proxy::ParentQueryProxy;
+use crate::{is_null_round_error, BlockHeight, NULL_ROUND_ERR_MSG};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::future::join_all;
+use ipc_api::cross::IpcEnvelope;
+use ipc_api::staking::StakingChangeRequest;
+use ipc_provider::manager::{GetBlockHashResult, TopDownQueryPayload};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// The weight of the latest call in the moving averages of an endpoint score.
+const SCORE_SMOOTHING: f64 = 0.2;
+/// How many seconds of latency a failed or dissenting answer costs an endpoint in its ranking.
+const FAILURE_PENALTY_SECS: f64 = 10.0;
+
+type QueryFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// How well an endpoint has been answering, used to pick the endpoints to query first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointScore {
+    /// Moving average of the latency of the calls, in seconds.
+    pub latency: f64,
+    /// Moving average of the share of calls that failed or disagreed with the quorum.
+    pub failures: f64,
+}
+
+impl EndpointScore {
+    /// The rank of the endpoint, lower is better.
+    pub fn rank(&self) -> f64 {
+        self.latency + FAILURE_PENALTY_SECS * self.failures
+    }
+
+    fn record_call(&mut self, latency: f64, failed: bool) {
+        self.latency += SCORE_SMOOTHING * (latency - self.latency);
+        self.record_outcome(failed);
+    }
+
+    fn record_outcome(&mut self, failed: bool) {
+        let outcome = if failed { 1.0 } else { 0.0 };
+        self.failures += SCORE_SMOOTHING * (outcome - self.failures);
+    }
+}
+
+struct Endpoint<P> {
+    name: String,
+    proxy: P,
+    score: Mutex<EndpointScore>,
+}
+
+/// Queries the parent through several endpoints and accepts a block hash, top-down messages or
+/// validator changes only when `quorum` of them agree. Endpoints are queried in the order of
+/// their score, and more are only queried if the best ones don't agree.
+pub struct QuorumParentProxy<P> {
+    endpoints: Vec<Endpoint<P>>,
+    quorum: usize,
+}
+
+impl<P: ParentQueryProxy + Send + Sync> QuorumParentProxy<P> {
+    /// Creates a proxy over named endpoints, requiring `quorum` of them to agree.
+    pub fn new(endpoints: Vec<(String, P)>, quorum: usize) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(anyhow!("at least one parent endpoint is required"));
+        }
+        if quorum == 0 || quorum > endpoints.len() {
+            return Err(anyhow!(
+                "parent quorum must be between 1 and {}, got {quorum}",
+                endpoints.len()
+            ));
+        }
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(name, proxy)| Endpoint {
+                name,
+                proxy,
+                score: Mutex::new(EndpointScore::default()),
+            })
+            .collect();
+        Ok(Self { endpoints, quorum })
+    }
+
+    /// The smallest number of endpoints out of `n` that makes a majority.
+    pub fn majority(n: usize) -> usize {
+        n / 2 + 1
+    }
+
+    /// The current score of each endpoint.
+    pub fn scores(&self) -> Vec<(String, EndpointScore)> {
+        self.endpoints
+            .iter()
+            .map(|e| (e.name.clone(), e.score.lock().unwrap().clone()))
+            .collect()
+    }
+
+    /// The indices of the endpoints, the best ranked first.
+    fn ranked(&self) -> Vec<usize> {
+        let ranks = self
+            .endpoints
+            .iter()
+            .map(|e| e.score.lock().unwrap().rank())
+            .collect::<Vec<_>>();
+        let mut indices = (0..self.endpoints.len()).collect::<Vec<_>>();
+        indices.sort_by(|a, b| ranks[*a].total_cmp(&ranks[*b]));
+        indices
+    }
+
+    /// Queries a single endpoint and records how it went. A null round is a valid answer,
+    /// returned as `None`.
+    async fn call<T, F>(&self, index: usize, query: &F) -> Result<Option<T>>
+    where
+        F: for<'p> Fn(&'p P) -> QueryFuture<'p, T>,
+    {
+        let endpoint = &self.endpoints[index];
+        let start = Instant::now();
+        let result = match query(&endpoint.proxy).await {
+            Ok(v) => Ok(Some(v)),
+            Err(e) if is_null_round_error(&e) => Ok(None),
+            Err(e) => Err(e),
+        };
+        endpoint
+            .score
+            .lock()
+            .unwrap()
+            .record_call(start.elapsed().as_secs_f64(), result.is_err());
+        result
+    }
+
+    /// Returns the answer that `quorum` endpoints agree on, querying the best ranked
+    /// endpoints first and only as many others as needed to reach the quorum.
+    async fn agree<T, F>(&self, method: &str, query: F) -> Result<T>
+    where
+        T: PartialEq,
+        F: for<'p> Fn(&'p P) -> QueryFuture<'p, T>,
+    {
+        let ranked = self.ranked();
+        let mut answers: Vec<(Option<T>, Vec<usize>)> = Vec::new();
+        let mut errors = Vec::new();
+        let mut next = 0;
+
+        loop {
+            if let Some(pos) = answers.iter().position(|(_, v)| v.len() >= self.quorum) {
+                let (answer, _) = answers.swap_remove(pos);
+                for index in answers.into_iter().flat_map(|(_, v)| v) {
+                    let endpoint = &self.endpoints[index];
+                    tracing::warn!(
+                        endpoint = %endpoint.name,
+                        method,
+                        "parent endpoint disagrees with the quorum"
+                    );
+                    endpoint.score.lock().unwrap().record_outcome(true);
+                }
+                return answer.ok_or_else(|| anyhow!(NULL_ROUND_ERR_MSG));
+            }
+
+            let best = answers.iter().map(|(_, v)| v.len()).max().unwrap_or(0);
+            let remaining = ranked.len() - next;
+            if best + remaining < self.quorum {
+                break;
+            }
+
+            let batch = &ranked[next..next + self.quorum - best];
+            next += batch.len();
+
+            let results = join_all(batch.iter().map(|i| self.call(*i, &query))).await;
+            for (index, result) in batch.iter().zip(results) {
+                match result {
+                    Ok(answer) => match answers.iter_mut().find(|(a, _)| *a == answer) {
+                        Some((_, agreeing)) => agreeing.push(*index),
+                        None => answers.push((answer, vec![*index])),
+                    },
+                    Err(e) => errors.push(format!("{}: {e}", self.endpoints[*index].name)),
+                }
+            }
+        }
+
+        Err(anyhow!(
+            "no {} of {} parent endpoints agree on {method}, got {} different answers, errors: {errors:?}",
+            self.quorum,
+            self.endpoints.len(),
+            answers.len()
+        ))
+    }
+}
+
+#[async_trait]
+impl<P: ParentQueryProxy + Send + Sync> ParentQueryProxy for QuorumParentProxy<P> {
+    /// The highest height that at least `quorum` endpoints have reached, so that neither
+    /// lagging endpoints can hold back, nor a dishonest one run ahead of, the others.
+    async fn get_chain_head_height(&self) -> Result<BlockHeight> {
+        fn query<P: ParentQueryProxy>(p: &P) -> QueryFuture<'_, BlockHeight> {
+            p.get_chain_head_height()
+        }
+        let results = join_all((0..self.endpoints.len()).map(|i| self.call(i, &query::<P>))).await;
+
+        let mut heights = Vec::new();
+        let mut errors = Vec::new();
+        for (endpoint, result) in self.endpoints.iter().zip(results) {
+            match result {
+                Ok(Some(height)) => heights.push(height),
+                Ok(None) => {}
+                Err(e) => errors.push(format!("{}: {e}", endpoint.name)),
+            }
+        }
+        if heights.len() < self.quorum {
+            return Err(anyhow!(
+                "only {} of {} parent endpoints returned the chain head, {} required, errors: {errors:?}",
+                heights.len(),
+                self.endpoints.len(),
+                self.quorum
+            ));
+        }
+
+        heights.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(heights[self.quorum - 1])
+    }
+
+    async fn get_genesis_epoch(&self) -> Result<BlockHeight> {
+        self.agree("get_genesis_epoch", |p| p.get_genesis_epoch())
+            .await
+    }
+
+    async fn get_block_hash(&self, height: BlockHeight) -> Result<GetBlockHashResult> {
+        self.agree("get_block_hash", |p| p.get_block_hash(height))
+            .await
+    }
+
+    async fn get_top_down_msgs(
+        &self,
+        height: BlockHeight,
+    ) -> Result<TopDownQueryPayload<Vec<IpcEnvelope>>> {
+        self.agree("get_top_down_msgs", |p| p.get_top_down_msgs(height))
+            .await
+    }
+
+    async fn get_validator_changes(
+        &self,
+        height: BlockHeight,
+    ) -> Result<TopDownQueryPayload<Vec<StakingChangeRequest>>> {
+        self.agree("get_validator_changes", |p| p.get_validator_changes(height))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QuorumParentProxy;
+    use crate::proxy::ParentQueryProxy;
+    use crate::{is_null_round_error, BlockHeight, NULL_ROUND_ERR_MSG};
+    use anyhow::anyhow;
+    use async_trait::async_trait;
+    use ipc_api::cross::IpcEnvelope;
+    use ipc_api::staking::StakingChangeRequest;
+    use ipc_provider::manager::{GetBlockHashResult, TopDownQueryPayload};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A parent endpoint that answers every height with the same block hash.
+    struct TestEndpoint {
+        head: BlockHeight,
+        /// `None` answers every height as a null round.
+        hash: Option<Vec<u8>>,
+        fail: bool,
+        calls: AtomicUsize,
+    }
+
+    impl TestEndpoint {
+        fn new(head: BlockHeight, hash: Option<u8>) -> Self {
+            Self {
+                head,
+                hash: hash.map(|h| vec![h; 32]),
+                fail: false,
+                calls: AtomicUsize::new(0),
+            }
+        }
+
+        fn failing() -> Self {
+            Self {
+                fail: true,
+                ..Self::new(0, None)
+            }
+        }
+
+        fn block_hash(&self) -> anyhow::Result<Vec<u8>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                return Err(anyhow!("connection refused"));
+            }
+            self.hash.clone().ok_or_else(|| anyhow!(NULL_ROUND_ERR_MSG))
+        }
+    }
+
+    #[async_trait]
+    impl ParentQueryProxy for TestEndpoint {
+        async fn get_chain_head_height(&self) -> anyhow::Result<BlockHeight> {
+            if self.fail {
+                return Err(anyhow!("connection refused"));
+            }
+            Ok(self.head)
+        }
+
+        async fn get_genesis_epoch(&self) -> anyhow::Result<BlockHeight> {
+            Ok(0)
+        }
+
+        async fn get_block_hash(&self, _height: BlockHeight) -> anyhow::Result<GetBlockHashResult> {
+            Ok(GetBlockHashResult {
+                parent_block_hash: vec![],
+                block_hash: self.block_hash()?,
+            })
+        }
+
+        async fn get_top_down_msgs(
+            &self,
+            _height: BlockHeight,
+        ) -> anyhow::Result<TopDownQueryPayload<Vec<IpcEnvelope>>> {
+            Ok(TopDownQueryPayload {
+                value: vec![],
+                block_hash: self.block_hash()?,
+            })
+        }
+
+        async fn get_validator_changes(
+            &self,
+            _height: BlockHeight,
+        ) -> anyhow::Result<TopDownQueryPayload<Vec<StakingChangeRequest>>> {
+            Ok(TopDownQueryPayload {
+                value: vec![],
+                block_hash: self.block_hash()?,
+            })
+        }
+    }
+
+    fn proxy(endpoints: Vec<TestEndpoint>, quorum: usize) -> QuorumParentProxy<TestEndpoint> {
+        let endpoints = endpoints
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| (format!("endpoint-{i}"), e))
+            .collect();
+        QuorumParentProxy::new(endpoints, quorum).unwrap()
+    }
+
+    #[test]
+    fn test_invalid_quorum() {
+        let endpoints = || vec![("a".to_string(), TestEndpoint::new(0, None))];
+        assert!(QuorumParentProxy::new(endpoints(), 0).is_err());
+        assert!(QuorumParentProxy::new(endpoints(), 2).is_err());
+        assert!(QuorumParentProxy::<TestEndpoint>::new(vec![], 1).is_err());
+        assert_eq!(QuorumParentProxy::<TestEndpoint>::majority(3), 2);
+        assert_eq!(QuorumParentProxy::<TestEndpoint>::majority(4), 3);
+    }
+
+    #[tokio::test]
+    async fn test_dishonest_endpoint_outvoted() {
+        let proxy = proxy(
+            vec![
+                TestEndpoint::new(10, Some(1)),
+                TestEndpoint::new(10, Some(2)),
+                TestEndpoint::new(10, Some(1)),
+            ],
+            2,
+        );
+
+        let res = proxy.get_block_hash(5).await.unwrap();
+        assert_eq!(res.block_hash, vec![1; 32]);
+
+        let scores = proxy.scores();
+        assert_eq!(scores[0].1.failures, 0.0);
+        assert!(scores[1].1.failures > 0.0);
+        assert_eq!(scores[2].1.failures, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_no_quorum() {
+        let proxy = proxy(
+            vec![
+                TestEndpoint::new(10, Some(1)),
+                TestEndpoint::new(10, Some(2)),
+                TestEndpoint::failing(),
+            ],
+            2,
+        );
+
+        let err = proxy.get_top_down_msgs(5).await.unwrap_err();
+        assert!(!is_null_round_error(&err));
+    }
+
+    #[tokio::test]
+    async fn test_null_round_agreement() {
+        let proxy = proxy(
+            vec![
+                TestEndpoint::new(10, None),
+                TestEndpoint::new(10, Some(1)),
+                TestEndpoint::new(10, None),
+            ],
+            2,
+        );
+
+        let err = proxy.get_block_hash(5).await.unwrap_err();
+        assert!(is_null_round_error(&err));
+    }
+
+    #[tokio::test]
+    async fn test_failing_endpoint_ranked_last() {
+        let proxy = proxy(
+            vec![
+                TestEndpoint::failing(),
+                TestEndpoint::new(10, Some(1)),
+                TestEndpoint::new(10, Some(1)),
+            ],
+            2,
+        );
+
+        // The failing endpoint is queried until it has a worse score than the others.
+        proxy.get_validator_changes(5).await.unwrap();
+        let calls = proxy.endpoints[0].proxy.calls.load(Ordering::SeqCst);
+
+        proxy.get_validator_changes(6).await.unwrap();
+        assert_eq!(proxy.endpoints[0].proxy.calls.load(Ordering::SeqCst), calls);
+    }
+
+    #[tokio::test]
+    async fn test_chain_head_reached_by_quorum() {
+        let proxy = proxy(
+            vec![
+                TestEndpoint::new(12, None),
+                TestEndpoint::new(1000, None),
+                TestEndpoint::new(10, None),
+                TestEndpoint::failing(),
+            ],
+            2,
+        );
+        assert_eq!(proxy.get_chain_head_height().await.unwrap(), 12);
+
+        let proxy = self::proxy(
+            vec![TestEndpoint::new(12, None), TestEndpoint::failing()],
+            2,
+        );
+        assert!(proxy.get_chain_head_height().await.is_err());
+    }
+}
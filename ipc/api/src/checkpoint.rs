@@ -65,6 +65,45 @@ pub struct BottomUpCheckpointBundle {
     pub signatories: Vec<Address>,
 }
 
+/// A BLS signature aggregated from the partial signatures of the validators over the checkpoint hash.
+#[serde_as]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct AggregateSignature {
+    /// The aggregated BLS signature.
+    #[serde_as(as = "HumanReadable")]
+    pub signature: Signature,
+    /// Bit `i` (least significant bit first) is set if the `i`-th validator
+    /// in the power table, ordered by address, has signed.
+    #[serde_as(as = "HumanReadable")]
+    pub signer_bitmap: Vec<u8>,
+}
+
+impl AggregateSignature {
+    /// Create a bitmap for `size` validators with the bits of `signers` set.
+    pub fn bitmap(size: usize, signers: impl IntoIterator<Item = usize>) -> Vec<u8> {
+        let mut bitmap = vec![0u8; size.div_ceil(8)];
+        for i in signers {
+            if i < size {
+                bitmap[i / 8] |= 1 << (i % 8);
+            }
+        }
+        bitmap
+    }
+
+    /// Check whether the `i`-th validator has signed.
+    pub fn is_signer(&self, i: usize) -> bool {
+        self.signer_bitmap
+            .get(i / 8)
+            .map(|b| b & (1 << (i % 8)) != 0)
+            .unwrap_or_default()
+    }
+
+    /// Indices of the validators that have signed.
+    pub fn signers(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.signer_bitmap.len() * 8).filter(|i| self.is_signer(*i))
+    }
+}
+
 /// The collection of items for the bottom up checkpoint submission
 #[serde_as]
 #[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
@@ -165,7 +204,7 @@ where
 #[cfg(test)]
 mod tests {
     use crate::address::IPCAddress;
-    use crate::checkpoint::Signature;
+    use crate::checkpoint::{AggregateSignature, Signature};
     use crate::subnet_id::SubnetID;
     use crate::HumanReadable;
     use fvm_shared::address::Address;
@@ -173,6 +212,22 @@ mod tests {
     use serde_with::serde_as;
     use std::str::FromStr;
 
+    #[test]
+    fn test_aggregate_signature_bitmap() {
+        let aggregate = AggregateSignature {
+            signature: vec![1; 96],
+            signer_bitmap: AggregateSignature::bitmap(10, [0, 3, 9, 12]),
+        };
+        assert_eq!(aggregate.signer_bitmap, vec![0b0000_1001, 0b0000_0010]);
+        assert_eq!(aggregate.signers().collect::<Vec<_>>(), vec![0, 3, 9]);
+        assert!(!aggregate.is_signer(1));
+        assert!(!aggregate.is_signer(100));
+
+        let json = serde_json::to_string(&aggregate).unwrap();
+        let back: AggregateSignature = serde_json::from_str(&json).unwrap();
+        assert_eq!(aggregate, back);
+    }
+
     #[test]
     fn test_serialization_vec_vec_u8() {
         #[serde_as]
@@ -6,8 +6,11 @@ use crate::address::IPCAddress;
 use crate::subnet_id::SubnetID;
 use crate::HumanReadable;
 use anyhow::anyhow;
+use ethers::abi::{self, ParamType, Token, Tokenizable};
+use ethers::contract::EthError;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
+use ipc_actors_abis::gateway_messenger_facet;
 use serde::{Deserialize, Serialize};
 use serde_tuple::{Deserialize_tuple, Serialize_tuple};
 use serde_with::serde_as;
@@ -91,6 +94,43 @@ impl IpcEnvelope {
         })
     }
 
+    /// Creates a `Call` to a contract in another subnet. The nonces are set
+    /// when the message is committed.
+    pub fn new_call_msg(
+        from: IPCAddress,
+        to: IPCAddress,
+        value: TokenAmount,
+        call: &CallMsg,
+    ) -> Self {
+        Self {
+            kind: IpcMsgKind::Call,
+            from,
+            to,
+            value,
+            local_nonce: 0,
+            original_nonce: 0,
+            message: call.encode(),
+        }
+    }
+
+    /// The id of the message across all the networks it travels through,
+    /// which is also the id that its receipt refers to.
+    ///
+    /// Unlike the hash of the envelope, it doesn't depend on the local nonce,
+    /// which changes in every network the message is propagated through.
+    pub fn tracing_id(&self) -> anyhow::Result<[u8; 32]> {
+        let envelope = gateway_messenger_facet::IpcEnvelope::try_from(self.clone())?;
+        let bz = abi::encode(&[
+            Token::Uint(envelope.kind.into()),
+            envelope.to.into_token(),
+            envelope.from.into_token(),
+            Token::Uint(envelope.value),
+            Token::Bytes(envelope.message.to_vec()),
+            Token::Uint(envelope.original_nonce.into()),
+        ]);
+        Ok(ethers::utils::keccak256(bz))
+    }
+
     pub fn ipc_type(&self) -> anyhow::Result<IPCMsgType> {
         let sto = self.to.subnet()?;
         let sfrom = self.from.subnet()?;
@@ -121,8 +161,8 @@ pub enum IpcMsgKind {
     Transfer,
     /// general-purpose cross-net transaction that call smart contracts.
     Call,
-    /// receipt from the execution of cross-net messages
-    /// (currently limited to `Transfer` messages)
+    /// receipt from the execution of cross-net messages, which carries a [`ResultMsg`].
+    /// Receipts are sent back for every kind of message except receipts themselves.
     Receipt,
 }
 
@@ -139,6 +179,190 @@ impl TryFrom<u8> for IpcMsgKind {
     }
 }
 
+/// The message of a `Call`, ABI encoded in the envelope.
+#[serde_as]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct CallMsg {
+    /// Target method, the 4 byte function selector for EVM contracts.
+    #[serde_as(as = "HumanReadable")]
+    pub method: Vec<u8>,
+    /// Arguments of the method being called.
+    #[serde_as(as = "HumanReadable")]
+    pub params: Vec<u8>,
+}
+
+impl CallMsg {
+    /// A call that only carries value, the equivalent of `METHOD_SEND` in the contracts.
+    pub fn send() -> Self {
+        Self {
+            method: vec![0; 4],
+            params: vec![],
+        }
+    }
+
+    /// Splits EVM calldata into the function selector and the ABI encoded arguments.
+    pub fn from_calldata(calldata: &[u8]) -> anyhow::Result<Self> {
+        if calldata.len() < 4 {
+            return Err(anyhow!(
+                "calldata must start with a 4 byte function selector"
+            ));
+        }
+        let (method, params) = calldata.split_at(4);
+        Ok(Self {
+            method: method.to_vec(),
+            params: params.to_vec(),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        abi::encode(&[Token::Tuple(vec![
+            Token::Bytes(self.method.clone()),
+            Token::Bytes(self.params.clone()),
+        ])])
+    }
+
+    pub fn decode(bz: &[u8]) -> anyhow::Result<Self> {
+        let param = ParamType::Tuple(vec![ParamType::Bytes, ParamType::Bytes]);
+        match abi::decode(&[param], bz)?.pop().and_then(Token::into_tuple) {
+            Some(tokens) => match <[Token; 2]>::try_from(tokens) {
+                Ok([Token::Bytes(method), Token::Bytes(params)]) => Ok(Self { method, params }),
+                _ => Err(anyhow!("invalid call message")),
+            },
+            None => Err(anyhow!("invalid call message")),
+        }
+    }
+}
+
+/// Whether a cross-net message was executed successfully,
+/// or the kind of error that prevented it.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize, strum::Display)]
+#[repr(u8)]
+pub enum OutcomeType {
+    /// The execution succeeded, the return data is up to the contract called.
+    Ok,
+    /// The message was rejected by IPC, e.g. because of an invalid nonce or destination.
+    SystemErr,
+    /// The contract called reverted, the return data is the revert reason.
+    ActorErr,
+}
+
+impl TryFrom<u8> for OutcomeType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => OutcomeType::Ok,
+            1 => OutcomeType::SystemErr,
+            2 => OutcomeType::ActorErr,
+            _ => return Err(anyhow!("invalid outcome type")),
+        })
+    }
+}
+
+/// The message of a `Receipt`, ABI encoded in the envelope.
+#[serde_as]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct ResultMsg {
+    /// The tracing id of the message this is the receipt of.
+    #[serde_as(as = "serde_with::hex::Hex")]
+    pub id: [u8; 32],
+    pub outcome: OutcomeType,
+    /// The return data of the call, or the reason of the failure.
+    #[serde_as(as = "HumanReadable")]
+    pub ret: Vec<u8>,
+}
+
+/// Selector of the standard `Error(string)` revert.
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of the standard `Panic(uint256)` revert.
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+/// The reasons for `InvalidXnetMessage` errors, in the order of the Solidity enum.
+const INVALID_XNET_MESSAGE_REASONS: [&str; 8] = [
+    "Sender",
+    "DstSubnet",
+    "Nonce",
+    "Value",
+    "Kind",
+    "ReflexiveSend",
+    "NoRoute",
+    "IncompatibleSupplySource",
+];
+
+impl ResultMsg {
+    pub fn encode(&self) -> Vec<u8> {
+        abi::encode(&[Token::Tuple(vec![
+            Token::FixedBytes(self.id.to_vec()),
+            Token::Uint((self.outcome as u8).into()),
+            Token::Bytes(self.ret.clone()),
+        ])])
+    }
+
+    pub fn decode(bz: &[u8]) -> anyhow::Result<Self> {
+        let param = ParamType::Tuple(vec![
+            ParamType::FixedBytes(32),
+            ParamType::Uint(8),
+            ParamType::Bytes,
+        ]);
+        let tokens = abi::decode(&[param], bz)?
+            .pop()
+            .and_then(Token::into_tuple)
+            .and_then(|tokens| <[Token; 3]>::try_from(tokens).ok());
+        match tokens {
+            Some([Token::FixedBytes(id), Token::Uint(outcome), Token::Bytes(ret)]) => Ok(Self {
+                id: id
+                    .try_into()
+                    .map_err(|_| anyhow!("invalid result message id"))?,
+                outcome: OutcomeType::try_from(outcome.low_u32() as u8)?,
+                ret,
+            }),
+            _ => Err(anyhow!("invalid result message")),
+        }
+    }
+
+    /// Decodes the result carried by a receipt.
+    pub fn from_envelope(envelope: &IpcEnvelope) -> anyhow::Result<Self> {
+        if envelope.kind != IpcMsgKind::Receipt {
+            return Err(anyhow!("not a receipt: {}", envelope.kind));
+        }
+        Self::decode(&envelope.message)
+    }
+
+    /// A readable reason of the failure, or `None` if the message was executed successfully.
+    pub fn revert_reason(&self) -> Option<String> {
+        if self.outcome == OutcomeType::Ok {
+            return None;
+        }
+        let ret = self.ret.as_slice();
+        if ret.is_empty() {
+            return Some("execution reverted".to_string());
+        }
+        if let Some(err) = gateway_messenger_facet::InvalidXnetMessage::decode_with_selector(ret) {
+            let reason = INVALID_XNET_MESSAGE_REASONS
+                .get(err.reason as usize)
+                .copied()
+                .unwrap_or("Unknown");
+            return Some(format!("invalid cross-net message: {reason}"));
+        }
+        if let Some(data) = ret.strip_prefix(&ERROR_STRING_SELECTOR) {
+            if let Some(Token::String(reason)) = abi::decode(&[ParamType::String], data)
+                .ok()
+                .and_then(|mut tokens| tokens.pop())
+            {
+                return Some(reason);
+            }
+        }
+        if let Some(data) = ret.strip_prefix(&PANIC_SELECTOR) {
+            if let Some(Token::Uint(code)) = abi::decode(&[ParamType::Uint(256)], data)
+                .ok()
+                .and_then(|mut tokens| tokens.pop())
+            {
+                return Some(format!("panic: {code:#x}"));
+            }
+        }
+        Some(format!("0x{}", ethers::utils::hex::encode(ret)))
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum IPCMsgType {
     BottomUp,
@@ -202,6 +426,7 @@ impl IpcEnvelope {
 #[cfg(test)]
 mod tests {
     use crate::cross::*;
+    use ethers::abi::AbiEncode;
     use std::str::FromStr;
 
     #[test]
@@ -214,6 +439,69 @@ mod tests {
         bottom_up("/r123/f01/f02", "/r123/f01/f02/f03", false);
     }
 
+    #[test]
+    fn test_call_and_result_msg_encoding() {
+        let call = CallMsg::from_calldata(&[1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(call.method, vec![1, 2, 3, 4]);
+        assert_eq!(CallMsg::decode(&call.encode()).unwrap(), call);
+        assert!(CallMsg::from_calldata(&[1, 2, 3]).is_err());
+
+        let result = ResultMsg {
+            id: [7; 32],
+            outcome: OutcomeType::ActorErr,
+            ret: abi::encode(&[Token::String("not allowed".into())]),
+        };
+        assert_eq!(ResultMsg::decode(&result.encode()).unwrap(), result);
+        assert!(ResultMsg::decode(&call.encode()).is_err());
+    }
+
+    #[test]
+    fn test_revert_reason() {
+        let result = |outcome, ret: Vec<u8>| ResultMsg {
+            id: [0; 32],
+            outcome,
+            ret,
+        };
+        let error_string = [
+            ERROR_STRING_SELECTOR.to_vec(),
+            abi::encode(&[Token::String("not allowed".into())]),
+        ]
+        .concat();
+        assert_eq!(
+            result(OutcomeType::Ok, error_string.clone()).revert_reason(),
+            None
+        );
+        assert_eq!(
+            result(OutcomeType::ActorErr, error_string).revert_reason(),
+            Some("not allowed".to_string())
+        );
+
+        let invalid = gateway_messenger_facet::InvalidXnetMessage { reason: 2 }.encode();
+        assert_eq!(
+            result(OutcomeType::SystemErr, invalid).revert_reason(),
+            Some("invalid cross-net message: Nonce".to_string())
+        );
+        assert_eq!(
+            result(OutcomeType::ActorErr, vec![0xab]).revert_reason(),
+            Some("0xab".to_string())
+        );
+    }
+
+    #[test]
+    fn test_tracing_id_ignores_local_nonce() {
+        let addr = |id: u8| Address::new_delegated(10, &[id; 20]).unwrap();
+        let from = IPCAddress::new(&SubnetID::new(123, vec![addr(1)]), &addr(2)).unwrap();
+        let to = IPCAddress::new(&SubnetID::new(123, vec![]), &addr(3)).unwrap();
+        let call = CallMsg::from_calldata(&[1, 2, 3, 4]).unwrap();
+        let mut envelope = IpcEnvelope::new_call_msg(from, to, TokenAmount::from_atto(1), &call);
+        let id = envelope.tracing_id().unwrap();
+
+        envelope.local_nonce = 5;
+        assert_eq!(envelope.tracing_id().unwrap(), id);
+        envelope.original_nonce = 5;
+        assert_ne!(envelope.tracing_id().unwrap(), id);
+    }
+
     fn bottom_up(a: &str, b: &str, res: bool) {
         assert_eq!(
             is_bottomup(
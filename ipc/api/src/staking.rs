@@ -13,7 +13,7 @@ use std::fmt::{Display, Formatter};
 
 pub type ConfigurationNumber = u64;
 
-#[derive(Clone, Debug, num_enum::TryFromPrimitive, Deserialize, Serialize)]
+#[derive(PartialEq, Eq, Clone, Debug, num_enum::TryFromPrimitive, Deserialize, Serialize)]
 #[non_exhaustive]
 #[repr(u8)]
 pub enum StakingOperation {
@@ -23,14 +23,14 @@ pub enum StakingOperation {
     SetFederatedPower = 3,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub struct StakingChangeRequest {
     pub configuration_number: ConfigurationNumber,
     pub change: StakingChange,
 }
 
 /// The change request to validator staking
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub struct StakingChange {
     pub op: StakingOperation,
     pub payload: Vec<u8>,
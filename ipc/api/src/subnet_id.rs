@@ -193,6 +193,25 @@ impl SubnetID {
         }
         None
     }
+
+    /// The subnets that a cross-net message from the current subnet goes through
+    /// to reach `to`: bottom-up to their common parent, then top-down to `to`.
+    /// The current subnet is not included, `to` is the last hop.
+    pub fn route_to(&self, to: &SubnetID) -> Option<Vec<SubnetID>> {
+        if self == to {
+            return None;
+        }
+        let (common, _) = self.common_parent(to)?;
+
+        let mut route = Vec::new();
+        for i in (common..self.children_as_ref().len()).rev() {
+            route.push(SubnetID::new(self.root_id(), self.children()[..i].to_vec()));
+        }
+        for i in common + 1..=to.children_as_ref().len() {
+            route.push(SubnetID::new(self.root_id(), to.children()[..i].to_vec()));
+        }
+        Some(route)
+    }
 }
 
 impl fmt::Display for SubnetID {
@@ -387,6 +406,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_route_to() {
+        route_to("/r123/f01", "/r123/f01/f02", Some(vec!["/r123/f01/f02"]));
+        route_to("/r123/f01/f02", "/r123/f01", Some(vec!["/r123/f01"]));
+        route_to(
+            "/r123/f01/f02",
+            "/r123/f01/f03",
+            Some(vec!["/r123/f01", "/r123/f01/f03"]),
+        );
+        route_to(
+            "/r123/f01/f02/f03",
+            "/r123/f04",
+            Some(vec!["/r123/f01/f02", "/r123/f01", "/r123", "/r123/f04"]),
+        );
+        route_to(
+            "/r123",
+            "/r123/f01/f02",
+            Some(vec!["/r123/f01", "/r123/f01/f02"]),
+        );
+        route_to("/r123/f01", "/r123/f01", None);
+        route_to("/r122/f01", "/r123/f01", None);
+    }
+
     fn common_parent(a: &str, b: &str, res: &str, index: usize) {
         let id = SubnetID::from_str(a).unwrap();
         assert_eq!(
@@ -404,4 +446,14 @@ mod tests {
         let id = SubnetID::from_str(a).unwrap();
         assert_eq!(id.up(&SubnetID::from_str(b).unwrap()), res);
     }
+
+    fn route_to(a: &str, b: &str, res: Option<Vec<&str>>) {
+        let id = SubnetID::from_str(a).unwrap();
+        let res = res.map(|hops| {
+            hops.into_iter()
+                .map(|s| SubnetID::from_str(s).unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(id.route_to(&SubnetID::from_str(b).unwrap()), res);
+    }
 }
@@ -0,0 +1,187 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cross-net call cli command handlers.
+
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::address::IPCAddress;
+use ipc_api::cross::{CallMsg, ResultMsg};
+use ipc_api::subnet_id::SubnetID;
+
+use crate::{
+    f64_to_token_amount, get_ipc_provider, require_fil_addr_from_str, CommandLineHandler,
+    GlobalArguments,
+};
+
+/// The command to call a contract in another subnet.
+pub(crate) struct Call;
+
+#[async_trait]
+impl CommandLineHandler for Call {
+    type Arguments = CallArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("cross-net call with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let caller = require_fil_addr_from_str(&arguments.caller)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+        let to = IPCAddress::new(
+            &SubnetID::from_str(&arguments.to_subnet)?,
+            &require_fil_addr_from_str(&arguments.to)?,
+        )?;
+        let calldata = hex::decode(arguments.calldata.trim_start_matches("0x"))?;
+        let call = CallMsg::from_calldata(&calldata)?;
+
+        let (envelope, epoch) = provider
+            .send_cross_call(
+                &subnet,
+                caller,
+                from,
+                to,
+                call,
+                f64_to_token_amount(arguments.value)?,
+            )
+            .await?;
+        let id = envelope.tracing_id()?;
+        println!(
+            "call committed in epoch: {epoch}, id: 0x{}, nonce: {}",
+            hex::encode(id),
+            envelope.original_nonce
+        );
+
+        if arguments.wait {
+            let result = provider
+                .wait_for_cross_call_receipt(
+                    &subnet,
+                    caller,
+                    id,
+                    epoch,
+                    Duration::from_secs(arguments.timeout),
+                )
+                .await?;
+            print_receipt(&result);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    about = "Call a contract in another subnet through a deployed IpcCaller contract",
+    long_about = "Call a contract in another subnet through a deployed IpcCaller contract.\n\n\
+    The gateway of the destination subnet does not call the target with the raw calldata: \
+    it delivers the whole envelope to `handleIpcMessage`, so the target must implement \
+    `IIpcHandler` (e.g. by extending `IpcExchange`) and decode the calldata from the \
+    envelope itself. Calls to contracts that do not implement it revert and come back \
+    as a failed receipt."
+)]
+pub(crate) struct CallArgs {
+    #[arg(
+        long,
+        help = "The address that sends the call, which must own the caller contract"
+    )]
+    pub from: Option<String>,
+    #[arg(long, help = "The subnet to send the call from")]
+    pub subnet: String,
+    #[arg(long, help = "The IpcCaller contract that relays the call")]
+    pub caller: String,
+    #[arg(long, help = "The subnet of the contract to call")]
+    pub to_subnet: String,
+    #[arg(
+        long,
+        help = "The address of the contract to call, which must implement IIpcHandler"
+    )]
+    pub to: String,
+    #[arg(
+        long,
+        help = "The hex encoded calldata, starting with the function selector, handed to the target's handleIpcMessage"
+    )]
+    pub calldata: String,
+    #[arg(
+        long,
+        default_value = "0",
+        help = "The value to send with the call, in whole FIL"
+    )]
+    pub value: f64,
+    #[arg(long, help = "Wait for the receipt of the call")]
+    pub wait: bool,
+    #[arg(
+        long,
+        default_value = "600",
+        help = "How long to wait for the receipt, in seconds"
+    )]
+    pub timeout: u64,
+}
+
+/// The command to wait for the receipt of a cross-net call.
+pub(crate) struct CallReceipt;
+
+#[async_trait]
+impl CommandLineHandler for CallReceipt {
+    type Arguments = CallReceiptArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("cross-net call receipt with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let caller = require_fil_addr_from_str(&arguments.caller)?;
+        let id: [u8; 32] = hex::decode(arguments.id.trim_start_matches("0x"))?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("call id must be 32 bytes"))?;
+
+        let result = provider
+            .wait_for_cross_call_receipt(
+                &subnet,
+                caller,
+                id,
+                arguments.from_epoch,
+                Duration::from_secs(arguments.timeout),
+            )
+            .await?;
+        print_receipt(&result);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "call-receipt",
+    about = "Wait for the receipt of a cross-net call sent with `crossmsg call`"
+)]
+pub(crate) struct CallReceiptArgs {
+    #[arg(long, help = "The subnet the call was sent from")]
+    pub subnet: String,
+    #[arg(long, help = "The IpcCaller contract that relayed the call")]
+    pub caller: String,
+    #[arg(long, help = "The hex encoded id of the call")]
+    pub id: String,
+    #[arg(long, help = "Look for the receipt starting from this epoch")]
+    pub from_epoch: ChainEpoch,
+    #[arg(
+        long,
+        default_value = "600",
+        help = "How long to wait for the receipt, in seconds"
+    )]
+    pub timeout: u64,
+}
+
+fn print_receipt(result: &ResultMsg) {
+    println!("outcome: {}", result.outcome);
+    match result.revert_reason() {
+        None => println!("return data: 0x{}", hex::encode(&result.ret)),
+        Some(reason) => println!("revert reason: {reason}"),
+    }
+}
@@ -10,6 +10,7 @@ use ipc_api::subnet_id::SubnetID;
 use num_traits::Num;
 use std::{fmt::Debug, str::FromStr};
 
+use super::print_cross_msg_id;
 use crate::{
     f64_to_token_amount, get_ipc_provider, require_fil_addr_from_str, CommandLineHandler,
     GlobalArguments,
@@ -40,18 +41,17 @@ impl CommandLineHandler for Fund {
             None => None,
         };
 
-        println!(
-            "fund performed in epoch: {:?}",
-            provider
-                .fund(
-                    subnet,
-                    gateway_addr,
-                    from,
-                    to,
-                    f64_to_token_amount(arguments.amount)?,
-                )
-                .await?,
-        );
+        let (epoch, id) = provider
+            .fund(
+                subnet,
+                gateway_addr,
+                from,
+                to,
+                f64_to_token_amount(arguments.amount)?,
+            )
+            .await?;
+        println!("fund performed in epoch: {epoch:?}");
+        print_cross_msg_id(&id);
 
         Ok(())
     }
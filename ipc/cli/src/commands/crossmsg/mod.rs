@@ -1,7 +1,10 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
+use self::call::{Call, CallArgs, CallReceipt, CallReceiptArgs};
 use self::fund::{FundWithToken, FundWithTokenArgs, PreFund, PreFundArgs};
 use self::release::{PreRelease, PreReleaseArgs};
+use self::send::{SendArgs, SendCrossNet};
+use self::status::{Status, StatusArgs};
 use self::topdown_cross::{
     LatestParentFinality, LatestParentFinalityArgs, ListTopdownMsgs, ListTopdownMsgsArgs,
 };
@@ -15,9 +18,12 @@ use release::ReleaseArgs;
 
 use clap::{Args, Subcommand};
 
+mod call;
 pub mod fund;
 pub mod propagate;
 pub mod release;
+mod send;
+mod status;
 mod topdown_cross;
 
 #[derive(Debug, Args)]
@@ -37,6 +43,10 @@ impl CrossMsgsCommandsArgs {
             Commands::Release(args) => Release::handle(global, args).await,
             Commands::PreRelease(args) => PreRelease::handle(global, args).await,
             Commands::Propagate(args) => Propagate::handle(global, args).await,
+            Commands::Send(args) => SendCrossNet::handle(global, args).await,
+            Commands::Call(args) => Call::handle(global, args).await,
+            Commands::CallReceipt(args) => CallReceipt::handle(global, args).await,
+            Commands::Status(args) => Status::handle(global, args).await,
             Commands::ListTopdownMsgs(args) => ListTopdownMsgs::handle(global, args).await,
             Commands::ParentFinality(args) => LatestParentFinality::handle(global, args).await,
         }
@@ -51,6 +61,15 @@ pub(crate) enum Commands {
     Release(ReleaseArgs),
     PreRelease(PreReleaseArgs),
     Propagate(PropagateArgs),
+    Send(SendArgs),
+    Call(CallArgs),
+    CallReceipt(CallReceiptArgs),
+    Status(StatusArgs),
     ListTopdownMsgs(ListTopdownMsgsArgs),
     ParentFinality(LatestParentFinalityArgs),
 }
+
+/// Prints the id of a committed cross-net message, which can be followed with `crossmsg status`.
+fn print_cross_msg_id(id: &[u8; 32]) {
+    println!("cross-net message id: 0x{}", hex::encode(id));
+}
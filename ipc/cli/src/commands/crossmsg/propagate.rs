@@ -4,31 +4,55 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use std::fmt::Debug;
+use ipc_api::subnet_id::SubnetID;
+use std::{fmt::Debug, str::FromStr};
 
-use crate::{CommandLineHandler, GlobalArguments};
+use crate::{get_ipc_provider, require_fil_addr_from_str, CommandLineHandler, GlobalArguments};
 
-/// The command to propagate a message in the postbox.
+/// The command to propagate the messages in the postbox.
 pub(crate) struct Propagate;
 
 #[async_trait]
 impl CommandLineHandler for Propagate {
     type Arguments = PropagateArgs;
 
-    async fn handle(_global: &GlobalArguments, _arguments: &Self::Arguments) -> anyhow::Result<()> {
-        todo!()
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("propagate operation with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+
+        let msgs = provider.postbox_msgs(&subnet).await?;
+        if msgs.is_empty() {
+            println!("no messages to propagate in postbox");
+            return Ok(());
+        }
+        for (_, msg) in &msgs {
+            println!(
+                "propagating cross-net message id: 0x{}",
+                hex::encode(msg.tracing_id()?)
+            );
+        }
+
+        let cids = msgs.into_iter().map(|(cid, _)| cid).collect::<Vec<_>>();
+        let epoch = provider.propagate(&subnet, from, &cids).await?;
+        println!("propagate performed in epoch: {epoch:?}");
+
+        Ok(())
     }
 }
 
 #[derive(Debug, Args)]
-#[command(about = "Propagate operation in the gateway actor")]
+#[command(
+    about = "Propagate the messages in the postbox of the gateway to the next subnet in their route"
+)]
 pub(crate) struct PropagateArgs {
-    #[arg(long, help = "The JSON RPC server url for ipc agent")]
-    pub ipc_agent_url: Option<String>,
     #[arg(long, help = "The address that pays for the propagation gas")]
     pub from: Option<String>,
-    #[arg(long, help = "The subnet of the message to propagate")]
+    #[arg(long, help = "The subnet whose postbox to propagate")]
     pub subnet: String,
-    #[arg(help = "The message cid to propagate")]
-    pub postbox_msg_key: String,
 }
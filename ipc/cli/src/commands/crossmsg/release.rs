@@ -7,6 +7,7 @@ use clap::Args;
 use ipc_api::subnet_id::SubnetID;
 use std::{fmt::Debug, str::FromStr};
 
+use super::print_cross_msg_id;
 use crate::{
     f64_to_token_amount, get_ipc_provider, require_fil_addr_from_str, CommandLineHandler,
     GlobalArguments,
@@ -37,18 +38,17 @@ impl CommandLineHandler for Release {
             None => None,
         };
 
-        println!(
-            "release performed in epoch: {:?}",
-            provider
-                .release(
-                    subnet,
-                    gateway_addr,
-                    from,
-                    to,
-                    f64_to_token_amount(arguments.amount)?,
-                )
-                .await?,
-        );
+        let (epoch, id) = provider
+            .release(
+                subnet,
+                gateway_addr,
+                from,
+                to,
+                f64_to_token_amount(arguments.amount)?,
+            )
+            .await?;
+        println!("release performed in epoch: {epoch:?}");
+        print_cross_msg_id(&id);
 
         Ok(())
     }
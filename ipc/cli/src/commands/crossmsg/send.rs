@@ -0,0 +1,95 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cross-net send cli command handler.
+
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_api::cross::CallMsg;
+use ipc_api::subnet_id::SubnetID;
+
+use super::print_cross_msg_id;
+use crate::{
+    f64_to_token_amount, get_ipc_provider, require_fil_addr_from_str, CommandLineHandler,
+    GlobalArguments,
+};
+
+/// The command to send a message between any two subnets.
+pub(crate) struct SendCrossNet;
+
+#[async_trait]
+impl CommandLineHandler for SendCrossNet {
+    type Arguments = SendArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("cross-net send with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let source = SubnetID::from_str(&arguments.source)?;
+        let destination = SubnetID::from_str(&arguments.destination)?;
+        let caller = match &arguments.caller {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+        let call = match &arguments.calldata {
+            Some(calldata) => Some(CallMsg::from_calldata(&hex::decode(
+                calldata.trim_start_matches("0x"),
+            )?)?),
+            None => None,
+        };
+
+        if let Some(route) = source.route_to(&destination) {
+            let route = route.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            println!("route: {source} -> {}", route.join(" -> "));
+        }
+
+        let (epoch, id) = provider
+            .send_cross_net(
+                &source,
+                &destination,
+                caller,
+                from,
+                require_fil_addr_from_str(&arguments.to)?,
+                call,
+                f64_to_token_amount(arguments.value)?,
+            )
+            .await?;
+        println!("message committed in epoch: {epoch:?}");
+        print_cross_msg_id(&id);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    about = "Send value or a call between any two subnets, routed through their common parent"
+)]
+pub(crate) struct SendArgs {
+    #[arg(long, help = "The address that sends the message")]
+    pub from: Option<String>,
+    #[arg(long, help = "The subnet to send the message from")]
+    pub source: String,
+    #[arg(long, help = "The subnet to send the message to")]
+    pub destination: String,
+    #[arg(
+        long,
+        help = "The IpcCaller contract that relays the message, required unless the subnets are parent and child"
+    )]
+    pub caller: Option<String>,
+    #[arg(long, help = "The address to send the message to")]
+    pub to: String,
+    #[arg(
+        long,
+        help = "The hex encoded calldata to call `to` with, starting with the function selector"
+    )]
+    pub calldata: Option<String>,
+    #[arg(long, default_value = "0", help = "The value to send, in whole FIL")]
+    pub value: f64,
+}
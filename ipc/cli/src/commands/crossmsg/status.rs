@@ -0,0 +1,94 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cross-net message status cli command handler.
+
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::subnet_id::SubnetID;
+
+use crate::commands::get_ipc_provider;
+use crate::{CommandLineHandler, GlobalArguments};
+
+/// The command to show the lifecycle of a cross-net message.
+pub(crate) struct Status;
+
+#[async_trait]
+impl CommandLineHandler for Status {
+    type Arguments = StatusArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("cross-net message status with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let id: [u8; 32] = hex::decode(arguments.id.trim_start_matches("0x"))?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("message id must be 32 bytes"))?;
+
+        let status = provider
+            .cross_msg_status(&subnet, id, arguments.from_epoch)
+            .await?;
+
+        println!(
+            "id: 0x{}, direction: {}",
+            hex::encode(status.id),
+            if status.top_down {
+                "top-down"
+            } else {
+                "bottom-up"
+            }
+        );
+        if let Some(msg) = &status.envelope {
+            println!(
+                "kind: {}, from: {}, to: {}, value: {}, nonce: {}",
+                msg.kind,
+                msg.from.to_string()?,
+                msg.to.to_string()?,
+                msg.value,
+                msg.local_nonce
+            );
+        }
+        for event in &status.events {
+            let epoch = event
+                .epoch
+                .map(|e| e.to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            let timestamp = event
+                .timestamp
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            println!(
+                "{:<12} subnet: {}, epoch: {epoch}, timestamp: {timestamp}",
+                event.stage.to_string(),
+                event.subnet
+            );
+        }
+        if let Some(result) = &status.result {
+            match result.revert_reason() {
+                None => println!("outcome: {}", result.outcome),
+                Some(reason) => println!("outcome: {}, reason: {reason}", result.outcome),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Show the lifecycle of a cross-net message between a subnet and its parent")]
+pub(crate) struct StatusArgs {
+    #[arg(long, help = "The child subnet the message goes to or comes from")]
+    pub subnet: String,
+    #[arg(
+        long,
+        default_value = "0",
+        help = "Look for the message from this epoch in the subnet it was sent from"
+    )]
+    pub from_epoch: ChainEpoch,
+    #[arg(help = "The hex encoded id of the message")]
+    pub id: String,
+}
@@ -36,7 +36,8 @@ impl CommandLineHandler for ListTopdownMsgs {
             );
             for msg in result.value {
                 println!(
-                    "from: {}, to: {}, message: {}, nonce: {} ",
+                    "id: 0x{}, from: {}, to: {}, message: {}, nonce: {} ",
+                    hex::encode(msg.tracing_id()?),
                     msg.from.to_string()?,
                     msg.to.to_string()?,
                     hex::encode(msg.message),
@@ -10,6 +10,8 @@ use futures_util::future::try_join_all;
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
 use ipc_api::checkpoint::{BottomUpCheckpointBundle, QuorumReachedEvent};
+use ipc_api::cross::IpcEnvelope;
+use ipc_api::subnet_id::SubnetID;
 use ipc_observability::{emit, serde::HexEncodableBlockHash};
 use ipc_wallet::{EthKeyAddress, PersistentKeyStore};
 use std::cmp::max;
@@ -18,6 +20,9 @@ use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use tokio::sync::Semaphore;
 
+/// The maximum number of postbox messages propagated in a single transaction.
+const MAX_PROPAGATED_MSGS: usize = 16;
+
 /// Tracks the config required for bottom up checkpoint submissions
 /// parent/child subnet and checkpoint period.
 pub struct CheckpointConfig {
@@ -125,6 +130,11 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
             if let Err(e) = self.submit_next_epoch(submitter).await {
                 tracing::error!("cannot submit checkpoint for submitter: {submitter} due to {e}");
             }
+            if let Err(e) = self.propagate_postbox(submitter).await {
+                tracing::error!(
+                    "cannot propagate postbox messages for submitter: {submitter} due to {e}"
+                );
+            }
             tokio::time::sleep(submission_interval).await;
         }
     }
@@ -235,6 +245,40 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
         Ok(())
     }
 
+    /// Moves the cross-net messages in the postbox of the parent that are routed through the child
+    /// forward on their route. The gateways leave messages that are not for them in their postbox
+    /// once applied, either from a checkpoint or from the parent finality.
+    ///
+    /// Every postbox is shared by the relayers of all the children of its subnet, so each relayer
+    /// only propagates the messages it is responsible for: the ones for the child's subtree, and
+    /// the ones from the child's subtree going further up. The messages in the postbox of the
+    /// child are left to the relayers of the grandchildren.
+    async fn propagate_postbox(&self, submitter: Address) -> Result<()> {
+        let parent = &self.metadata.parent.id;
+        let child = &self.metadata.child.id;
+        let cids = self
+            .parent_handler
+            .postbox_msgs()
+            .await?
+            .into_iter()
+            .filter(|(_, msg)| is_routed_through(parent, child, msg))
+            .map(|(cid, _)| cid)
+            .collect::<Vec<_>>();
+
+        for batch in cids.chunks(MAX_PROPAGATED_MSGS) {
+            tracing::debug!("propagating {} postbox messages in {parent}", batch.len());
+            let epoch = self
+                .parent_handler
+                .propagate_postbox(&submitter, batch)
+                .await?;
+            tracing::info!(
+                "propagated {} postbox messages in {parent} at height {epoch}",
+                batch.len()
+            );
+        }
+        Ok(())
+    }
+
     async fn submit_checkpoint(
         parent_handler: Arc<T>,
         submitter: Address,
@@ -264,3 +308,60 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
         Ok(())
     }
 }
+
+/// Whether the next hop of a message in the postbox of `parent` is down to `child`,
+/// or up to the parent of `parent`, coming from `child`.
+fn is_routed_through(parent: &SubnetID, child: &SubnetID, msg: &IpcEnvelope) -> bool {
+    let (Ok(from), Ok(to)) = (msg.from.subnet(), msg.to.subnet()) else {
+        return false;
+    };
+    let Some(next) = parent
+        .route_to(&to)
+        .and_then(|route| route.into_iter().next())
+    else {
+        return false;
+    };
+    if &next == child {
+        return true;
+    }
+    Some(next) == parent.parent() && from.down(parent).as_ref() == Some(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::address::IPCAddress;
+    use ipc_api::cross::CallMsg;
+
+    use super::*;
+
+    fn msg(from: &str, to: &str) -> IpcEnvelope {
+        let addr = Address::new_id(100);
+        IpcEnvelope::new_call_msg(
+            IPCAddress::new(&SubnetID::from_str(from).unwrap(), &addr).unwrap(),
+            IPCAddress::new(&SubnetID::from_str(to).unwrap(), &addr).unwrap(),
+            TokenAmount::default(),
+            &CallMsg::send(),
+        )
+    }
+
+    #[test]
+    fn test_is_routed_through() {
+        let parent = SubnetID::from_str("/r123/f01").unwrap();
+        let child = SubnetID::from_str("/r123/f01/f02").unwrap();
+        let routed = |from, to| is_routed_through(&parent, &child, &msg(from, to));
+
+        // Down to the child's subtree
+        assert!(routed("/r123", "/r123/f01/f02"));
+        assert!(routed("/r123/f01/f03", "/r123/f01/f02/f04"));
+        // Up from the child's subtree
+        assert!(routed("/r123/f01/f02", "/r123/f05"));
+        assert!(routed("/r123/f01/f02/f04", "/r123"));
+        // Up from a sibling, or down to a sibling
+        assert!(!routed("/r123/f01/f03", "/r123/f05"));
+        assert!(!routed("/r123/f01/f02", "/r123/f01/f03"));
+    }
+}
@@ -0,0 +1,401 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Tracking of cross-net messages between a parent and a child subnet.
+//!
+//! Messages are identified by their tracing id, which is derived from the source, destination,
+//! value, payload and original nonce of the envelope. Unlike the hash of the envelope, it doesn't
+//! change with the local nonce assigned by each gateway the message goes through, and it's also
+//! the id that the receipt of the message refers to.
+
+use std::future::Future;
+
+use anyhow::anyhow;
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::cross::{IpcEnvelope, IpcMsgKind, OutcomeType, ResultMsg};
+use ipc_api::subnet_id::SubnetID;
+
+use crate::manager::SubnetManager;
+
+/// A stage in the lifecycle of a cross-net message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum CrossMsgStage {
+    /// The message is pending in the gateway of the subnet it was sent from.
+    Committed,
+    /// A bottom-up message is included in a checkpoint of the child subnet.
+    Checkpointed,
+    /// The destination can execute the message: the parent finality including a top-down
+    /// message is committed in the child, or the checkpoint of a bottom-up message is signed.
+    Resolved,
+    /// The message is applied in the destination subnet.
+    Executed,
+    /// The receipt of the message reports that its execution failed.
+    Failed,
+}
+
+/// A stage reached by a cross-net message, with the subnet and block it was reached in, if known.
+#[derive(Clone, Debug)]
+pub struct CrossMsgEvent {
+    pub stage: CrossMsgStage,
+    pub subnet: SubnetID,
+    pub epoch: Option<ChainEpoch>,
+    /// Timestamp of the block, in seconds since the epoch.
+    pub timestamp: Option<u64>,
+}
+
+/// The stages a cross-net message went through so far.
+#[derive(Clone, Debug)]
+pub struct CrossMsgStatus {
+    pub id: [u8; 32],
+    /// Whether the message goes from the parent to the child.
+    pub top_down: bool,
+    /// The committed envelope, once known.
+    pub envelope: Option<IpcEnvelope>,
+    pub events: Vec<CrossMsgEvent>,
+    /// The receipt of the message, once it's committed.
+    pub result: Option<ResultMsg>,
+}
+
+impl CrossMsgStatus {
+    /// The latest stage reached by the message.
+    pub fn stage(&self) -> Option<CrossMsgStage> {
+        self.events.last().map(|e| e.stage)
+    }
+}
+
+/// Follows the cross-net message with the given tracing id between `subnet` and its parent,
+/// looking for it from `from_epoch` onwards in the subnet it was sent from.
+pub async fn cross_msg_status(
+    parent: &dyn SubnetManager,
+    child: &dyn SubnetManager,
+    subnet: &SubnetID,
+    id: [u8; 32],
+    from_epoch: ChainEpoch,
+) -> anyhow::Result<CrossMsgStatus> {
+    let parent_subnet = subnet
+        .parent()
+        .ok_or_else(|| anyhow!("subnet {subnet} has no parent"))?;
+
+    if let Some((envelope, epoch)) = parent.find_top_down_msg(subnet, id, from_epoch).await? {
+        let mut status = CrossMsgStatus {
+            id,
+            top_down: true,
+            envelope: Some(envelope.clone()),
+            events: vec![
+                event(
+                    parent,
+                    CrossMsgStage::Committed,
+                    &parent_subnet,
+                    Some(epoch),
+                )
+                .await,
+            ],
+            result: None,
+        };
+        track_top_down(parent, child, subnet, &envelope, epoch, &mut status).await?;
+        return Ok(status);
+    }
+
+    if let Some(epoch) = child.find_bottom_up_msg(id, from_epoch).await? {
+        let mut status = CrossMsgStatus {
+            id,
+            top_down: false,
+            envelope: None,
+            events: vec![event(child, CrossMsgStage::Committed, subnet, Some(epoch)).await],
+            result: None,
+        };
+        track_bottom_up(parent, child, subnet, &parent_subnet, epoch, &mut status).await?;
+        return Ok(status);
+    }
+
+    Err(anyhow!(
+        "cross-net message 0x{} not found in {subnet} or its parent",
+        hex::encode(id)
+    ))
+}
+
+/// A top-down message is executed in the child once the parent finality covering the epoch it
+/// was committed in is committed; failures are reported in a receipt sent in the next checkpoint.
+async fn track_top_down(
+    parent: &dyn SubnetManager,
+    child: &dyn SubnetManager,
+    subnet: &SubnetID,
+    envelope: &IpcEnvelope,
+    epoch: ChainEpoch,
+    status: &mut CrossMsgStatus,
+) -> anyhow::Result<()> {
+    if child.latest_parent_finality().await? < epoch {
+        return Ok(());
+    }
+    status
+        .events
+        .push(event(child, CrossMsgStage::Resolved, subnet, None).await);
+
+    let head = child.chain_head_height().await?;
+    if child.applied_top_down_nonce_at(head).await? <= envelope.local_nonce {
+        return Ok(());
+    }
+    let executed = first_applied_epoch(0, head, envelope.local_nonce, |h| {
+        child.applied_top_down_nonce_at(h)
+    })
+    .await;
+    let executed = match executed {
+        Ok(executed) => executed,
+        Err(e) => {
+            // Historical state may not be available, we only know that it was executed.
+            tracing::warn!(error = e.to_string(), "cannot find the execution epoch");
+            status
+                .events
+                .push(event(child, CrossMsgStage::Executed, subnet, None).await);
+            return Ok(());
+        }
+    };
+    status
+        .events
+        .push(event(child, CrossMsgStage::Executed, subnet, Some(executed)).await);
+
+    let id = status.id;
+    let receipt = find_checkpoint(parent, child, subnet, executed, |msgs| {
+        find_receipt(msgs, id)
+    })
+    .await?;
+    if let Some((height, result)) = receipt {
+        record_receipt(child, subnet, height, result, status).await;
+    }
+    Ok(())
+}
+
+/// A bottom-up message is executed in the parent when the checkpoint it is included in is
+/// submitted; failures are reported in a receipt sent back to the child right away.
+async fn track_bottom_up(
+    parent: &dyn SubnetManager,
+    child: &dyn SubnetManager,
+    subnet: &SubnetID,
+    parent_subnet: &SubnetID,
+    epoch: ChainEpoch,
+    status: &mut CrossMsgStatus,
+) -> anyhow::Result<()> {
+    let id = status.id;
+    let checkpointed = find_checkpoint(parent, child, subnet, epoch, |msgs| {
+        msgs.iter()
+            .find(|msg| msg.tracing_id().ok() == Some(id))
+            .cloned()
+    })
+    .await?;
+    let Some((height, envelope)) = checkpointed else {
+        return Ok(());
+    };
+    status.envelope = Some(envelope.clone());
+    status
+        .events
+        .push(event(child, CrossMsgStage::Checkpointed, subnet, Some(height)).await);
+
+    let submitted = parent.last_bottom_up_checkpoint_height(subnet).await? >= height;
+    let signed = submitted || child.checkpoint_quorum_reached(height).await?;
+    if !signed {
+        return Ok(());
+    }
+    status
+        .events
+        .push(event(child, CrossMsgStage::Resolved, subnet, None).await);
+    if !submitted {
+        return Ok(());
+    }
+
+    let head = parent.chain_head_height().await?;
+    let executed = first_applied_epoch(0, head, envelope.local_nonce, |h| {
+        parent.applied_bottom_up_nonce_at(subnet, h)
+    })
+    .await;
+    let executed = match executed {
+        Ok(executed) => executed,
+        Err(e) => {
+            tracing::warn!(error = e.to_string(), "cannot find the execution epoch");
+            status
+                .events
+                .push(event(parent, CrossMsgStage::Executed, parent_subnet, None).await);
+            return Ok(());
+        }
+    };
+    status.events.push(
+        event(
+            parent,
+            CrossMsgStage::Executed,
+            parent_subnet,
+            Some(executed),
+        )
+        .await,
+    );
+
+    let receipts = parent.get_top_down_msgs(subnet, executed).await?.value;
+    if let Some(result) = find_receipt(&receipts, status.id) {
+        record_receipt(parent, parent_subnet, executed, result, status).await;
+    }
+    Ok(())
+}
+
+/// Finds the checkpoint of the child that includes a bottom-up message queued at `epoch`,
+/// returning its height and whatever `find` looks up among the checkpointed messages.
+///
+/// Messages are batched until the end of the checkpoint period, unless the batch fills up
+/// before that, in which case it's cut early and checkpointed at the height of the cut.
+async fn find_checkpoint<T>(
+    parent: &dyn SubnetManager,
+    child: &dyn SubnetManager,
+    subnet: &SubnetID,
+    epoch: ChainEpoch,
+    find: impl Fn(&[IpcEnvelope]) -> Option<T>,
+) -> anyhow::Result<Option<(ChainEpoch, T)>> {
+    let end = next_checkpoint_height(epoch, parent.checkpoint_period(subnet).await?);
+    let mut heights = child.bottom_up_batch_cuts(epoch, end).await?;
+    heights.push(end);
+    for height in heights {
+        let Some(bundle) = child.checkpoint_bundle_at(height).await? else {
+            continue;
+        };
+        if let Some(found) = find(&bundle.checkpoint.msgs) {
+            return Ok(Some((height, found)));
+        }
+    }
+    Ok(None)
+}
+
+/// Records the receipt of the message, and whether it reports a failure.
+async fn record_receipt(
+    manager: &dyn SubnetManager,
+    subnet: &SubnetID,
+    epoch: ChainEpoch,
+    result: ResultMsg,
+    status: &mut CrossMsgStatus,
+) {
+    if result.outcome != OutcomeType::Ok {
+        status
+            .events
+            .push(event(manager, CrossMsgStage::Failed, subnet, Some(epoch)).await);
+    }
+    status.result = Some(result);
+}
+
+/// Finds the receipt of the message with the given tracing id.
+fn find_receipt(msgs: &[IpcEnvelope], id: [u8; 32]) -> Option<ResultMsg> {
+    msgs.iter()
+        .filter(|msg| msg.kind == IpcMsgKind::Receipt)
+        .filter_map(|msg| ResultMsg::from_envelope(msg).ok())
+        .find(|result| result.id == id)
+}
+
+/// The height at the end of the checkpoint period of bottom-up messages queued at `epoch`.
+fn next_checkpoint_height(epoch: ChainEpoch, period: ChainEpoch) -> ChainEpoch {
+    (epoch / period + 1) * period
+}
+
+/// Finds the first epoch in `[lo, hi]` at which the message with `nonce` was applied, given that
+/// it was applied at `hi`, by a binary search over the applied nonce of the gateway.
+async fn first_applied_epoch<F, Fut>(
+    mut lo: ChainEpoch,
+    mut hi: ChainEpoch,
+    nonce: u64,
+    applied_nonce_at: F,
+) -> anyhow::Result<ChainEpoch>
+where
+    F: Fn(ChainEpoch) -> Fut,
+    Fut: Future<Output = anyhow::Result<u64>>,
+{
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if applied_nonce_at(mid).await? > nonce {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    Ok(hi)
+}
+
+async fn event(
+    manager: &dyn SubnetManager,
+    stage: CrossMsgStage,
+    subnet: &SubnetID,
+    epoch: Option<ChainEpoch>,
+) -> CrossMsgEvent {
+    let timestamp = match epoch {
+        Some(epoch) => match manager.block_timestamp(epoch).await {
+            Ok(timestamp) => Some(timestamp),
+            Err(e) => {
+                tracing::warn!(epoch, error = e.to_string(), "cannot get block timestamp");
+                None
+            }
+        },
+        None => None,
+    };
+    CrossMsgEvent {
+        stage,
+        subnet: subnet.clone(),
+        epoch,
+        timestamp,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::address::IPCAddress;
+    use ipc_api::cross::CallMsg;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_first_applied_epoch() {
+        // The message with nonce 3 is applied at epoch 42.
+        let applied_nonce_at =
+            |h: ChainEpoch| async move { Ok::<_, anyhow::Error>(if h < 42 { 3 } else { 4 }) };
+        assert_eq!(
+            first_applied_epoch(0, 100, 3, applied_nonce_at)
+                .await
+                .unwrap(),
+            42
+        );
+        assert_eq!(
+            first_applied_epoch(42, 42, 3, applied_nonce_at)
+                .await
+                .unwrap(),
+            42
+        );
+        assert_eq!(next_checkpoint_height(42, 10), 50);
+        assert_eq!(next_checkpoint_height(50, 10), 60);
+    }
+
+    #[test]
+    fn test_find_receipt() {
+        let addr = |id: u8| Address::new_delegated(10, &[id; 20]).unwrap();
+        let parent = SubnetID::new(123, vec![]);
+        let child = SubnetID::new(123, vec![addr(1)]);
+        let receipt = |id: [u8; 32], outcome| {
+            let result = ResultMsg {
+                id,
+                outcome,
+                ret: vec![],
+            };
+            IpcEnvelope {
+                kind: IpcMsgKind::Receipt,
+                message: result.encode(),
+                ..IpcEnvelope::new_call_msg(
+                    IPCAddress::new(&child, &addr(2)).unwrap(),
+                    IPCAddress::new(&parent, &addr(3)).unwrap(),
+                    TokenAmount::default(),
+                    &CallMsg::from_calldata(&[0; 4]).unwrap(),
+                )
+            }
+        };
+        let msgs = vec![
+            receipt([1; 32], OutcomeType::Ok),
+            receipt([2; 32], OutcomeType::ActorErr),
+        ];
+        assert_eq!(
+            find_receipt(&msgs, [2; 32]).map(|r| r.outcome),
+            Some(OutcomeType::ActorErr)
+        );
+        assert!(find_receipt(&msgs, [3; 32]).is_none());
+    }
+}
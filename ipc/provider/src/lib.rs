@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MIT
 //! Ipc agent sdk, contains the json rpc client to interact with the IPC agent rpc server.
 
+use crate::crossmsg::CrossMsgStatus;
 use crate::manager::{GetBlockHashResult, TopDownQueryPayload};
 use anyhow::anyhow;
 use base64::Engine;
@@ -9,13 +10,14 @@ use config::Config;
 use fvm_shared::{
     address::Address, clock::ChainEpoch, crypto::signature::SignatureType, econ::TokenAmount,
 };
+use ipc_api::address::IPCAddress;
 use ipc_api::checkpoint::consensus::ValidatorData;
 use ipc_api::checkpoint::{BottomUpCheckpointBundle, QuorumReachedEvent};
 use ipc_api::evm::payload_to_evm_address;
 use ipc_api::staking::{StakingChangeRequest, ValidatorInfo};
 use ipc_api::subnet::{Asset, PermissionMode};
 use ipc_api::{
-    cross::IpcEnvelope,
+    cross::{CallMsg, IpcEnvelope, ResultMsg},
     subnet::{ConsensusType, ConstructParams},
     subnet_id::SubnetID,
 };
@@ -31,11 +33,13 @@ use std::{
     path::{Path, PathBuf},
     str::FromStr,
     sync::{Arc, RwLock},
+    time::{Duration, Instant},
 };
 use zeroize::Zeroize;
 
 pub mod checkpoint;
 pub mod config;
+pub mod crossmsg;
 pub mod jsonrpc;
 pub mod lotus;
 pub mod manager;
@@ -43,6 +47,8 @@ pub mod observe;
 
 const DEFAULT_REPO_PATH: &str = ".ipc";
 const DEFAULT_CONFIG_NAME: &str = "config.toml";
+/// How often to check whether the receipt of a cross-net call has arrived.
+const CROSS_CALL_RECEIPT_POLLING_INTERVAL: Duration = Duration::from_secs(5);
 
 /// The subnet manager connection that holds the subnet config and the manager instance.
 pub struct Connection {
@@ -432,7 +438,7 @@ impl IpcProvider {
     }
 
     /// Funds an account in a child subnet, if `to` is `None`, the self account
-    /// is funded.
+    /// is funded. Returns the epoch of the fund and the tracing id of its message.
     pub async fn fund(
         &mut self,
         subnet: SubnetID,
@@ -440,7 +446,7 @@ impl IpcProvider {
         from: Option<Address>,
         to: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
+    ) -> anyhow::Result<(ChainEpoch, [u8; 32])> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
@@ -500,7 +506,7 @@ impl IpcProvider {
     }
 
     /// Release to an account in a child subnet, if `to` is `None`, the self account
-    /// is funded.
+    /// is funded. Returns the epoch of the release and the tracing id of its message.
     pub async fn release(
         &mut self,
         subnet: SubnetID,
@@ -508,7 +514,7 @@ impl IpcProvider {
         from: Option<Address>,
         to: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
+    ) -> anyhow::Result<(ChainEpoch, [u8; 32])> {
         let conn = match self.connection(&subnet) {
             None => return Err(anyhow!("target subnet not found: {subnet}")),
             Some(conn) => conn,
@@ -527,6 +533,166 @@ impl IpcProvider {
             .await
     }
 
+    /// Sends a `Call` from `subnet` to a contract in another subnet through the `caller` contract,
+    /// which must be owned by the sender. Returns the committed envelope, whose tracing id
+    /// identifies the receipt, and the epoch that the call is committed in.
+    ///
+    /// The destination gateway hands the whole envelope to `handleIpcMessage` of the target
+    /// rather than calling it with the calldata, so `to` must implement `IIpcHandler` and decode
+    /// the [`CallMsg`] itself. Calls to contracts that do not implement it fail and return
+    /// an error receipt.
+    pub async fn send_cross_call(
+        &mut self,
+        subnet: &SubnetID,
+        caller: Address,
+        from: Option<Address>,
+        to: IPCAddress,
+        call: CallMsg,
+        value: TokenAmount,
+    ) -> anyhow::Result<(IpcEnvelope, ChainEpoch)> {
+        let conn = self.get_connection(subnet)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+
+        conn.manager()
+            .send_cross_call(caller, sender, to, call, value)
+            .await
+    }
+
+    /// Sends value, and optionally a call, to `to` in `destination`, which can be any subnet
+    /// sharing a root with `source`. Value between a subnet and its parent is sent with a fund or
+    /// a release. Anything else is routed bottom-up to the common parent and then top-down, and has
+    /// to be sent through the `caller` contract, which must be owned by the sender. The relayers of
+    /// the subnets in the route propagate the message at each hop. Returns the epoch that the
+    /// message is committed in `source`, and its tracing id.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_cross_net(
+        &mut self,
+        source: &SubnetID,
+        destination: &SubnetID,
+        caller: Option<Address>,
+        from: Option<Address>,
+        to: Address,
+        call: Option<CallMsg>,
+        value: TokenAmount,
+    ) -> anyhow::Result<(ChainEpoch, [u8; 32])> {
+        let route = source
+            .route_to(destination)
+            .ok_or_else(|| anyhow!("no route from {source} to {destination}"))?;
+        log::debug!("cross-net route from {source}: {route:?}");
+
+        if call.is_none() && route.len() == 1 {
+            if destination.parent().as_ref() == Some(source) {
+                return self
+                    .fund(destination.clone(), None, from, Some(to), value)
+                    .await;
+            }
+            if source.parent().as_ref() == Some(destination) {
+                return self
+                    .release(source.clone(), None, from, Some(to), value)
+                    .await;
+            }
+        }
+
+        let caller = caller.ok_or_else(|| {
+            anyhow!(
+                "messages from {source} to {destination} must be sent through a caller contract"
+            )
+        })?;
+        let to = IPCAddress::new(destination, &to)?;
+        let (envelope, epoch) = self
+            .send_cross_call(
+                source,
+                caller,
+                from,
+                to,
+                call.unwrap_or_else(CallMsg::send),
+                value,
+            )
+            .await?;
+        Ok((epoch, envelope.tracing_id()?))
+    }
+
+    /// Propagates the cross-net messages with the given cids from the postbox of `subnet`
+    /// to the next subnet in their route.
+    pub async fn propagate(
+        &mut self,
+        subnet: &SubnetID,
+        from: Option<Address>,
+        cids: &[[u8; 32]],
+    ) -> anyhow::Result<ChainEpoch> {
+        let conn = self.get_connection(subnet)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+
+        conn.manager().propagate_postbox(&sender, cids).await
+    }
+
+    /// Returns the cross-net messages waiting in the postbox of `subnet`, along with their cids.
+    pub async fn postbox_msgs(
+        &self,
+        subnet: &SubnetID,
+    ) -> anyhow::Result<Vec<([u8; 32], IpcEnvelope)>> {
+        let conn = self.get_connection(subnet)?;
+
+        conn.manager().postbox_msgs().await
+    }
+
+    /// Waits until the receipt of the call with the given tracing id arrives at the `caller`
+    /// contract in `subnet`, looking from `from_epoch` onwards.
+    pub async fn wait_for_cross_call_receipt(
+        &self,
+        subnet: &SubnetID,
+        caller: Address,
+        id: [u8; 32],
+        from_epoch: ChainEpoch,
+        timeout: Duration,
+    ) -> anyhow::Result<ResultMsg> {
+        let conn = self.get_connection(subnet)?;
+
+        let started = Instant::now();
+        loop {
+            if let Some(result) = conn
+                .manager()
+                .cross_call_receipt(caller, id, from_epoch)
+                .await?
+            {
+                return Ok(result);
+            }
+            if started.elapsed() >= timeout {
+                return Err(anyhow!(
+                    "no receipt for cross-net call 0x{} after {timeout:?}",
+                    hex::encode(id)
+                ));
+            }
+            tokio::time::sleep(CROSS_CALL_RECEIPT_POLLING_INTERVAL).await;
+        }
+    }
+
+    /// Follows a cross-net message between `subnet` and its parent by its tracing id,
+    /// looking for it from `from_epoch` onwards in the subnet it was sent from.
+    pub async fn cross_msg_status(
+        &self,
+        subnet: &SubnetID,
+        id: [u8; 32],
+        from_epoch: ChainEpoch,
+    ) -> anyhow::Result<CrossMsgStatus> {
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+        let parent_conn = self.get_connection(&parent)?;
+        let child_conn = self.get_connection(subnet)?;
+
+        crossmsg::cross_msg_status(
+            parent_conn.manager(),
+            child_conn.manager(),
+            subnet,
+            id,
+            from_epoch,
+        )
+        .await
+    }
+
     /// Send value between two addresses in a subnet
     pub async fn send_value(
         &mut self,
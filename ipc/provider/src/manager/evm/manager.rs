@@ -8,10 +8,10 @@ use std::time::Duration;
 
 use ethers_contract::{ContractError, EthLogDecode, LogMeta};
 use ipc_actors_abis::{
-    checkpointing_facet, gateway_getter_facet, gateway_manager_facet, lib_gateway, lib_quorum,
-    lib_staking_change_log, register_subnet_facet, subnet_actor_activity_facet,
-    subnet_actor_checkpointing_facet, subnet_actor_getter_facet, subnet_actor_manager_facet,
-    subnet_actor_reward_facet,
+    checkpointing_facet, gateway_getter_facet, gateway_manager_facet, gateway_messenger_facet,
+    lib_gateway, lib_quorum, lib_staking_change_log, register_subnet_facet,
+    subnet_actor_activity_facet, subnet_actor_checkpointing_facet, subnet_actor_getter_facet,
+    subnet_actor_manager_facet, subnet_actor_reward_facet,
 };
 use ipc_api::evm::{fil_to_eth_amount, payload_to_evm_address, subnet_id_to_evm_addresses};
 use ipc_api::validator::from_contract_validators;
@@ -46,11 +46,12 @@ use ethers::middleware::Middleware;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::{address::Address, econ::TokenAmount};
 use ipc_actors_abis::subnet_actor_activity_facet::ValidatorClaim;
+use ipc_api::address::IPCAddress;
 use ipc_api::checkpoint::{
     consensus::ValidatorData, BottomUpCheckpoint, BottomUpCheckpointBundle, QuorumReachedEvent,
     Signature, VALIDATOR_REWARD_FIELDS,
 };
-use ipc_api::cross::IpcEnvelope;
+use ipc_api::cross::{CallMsg, IpcEnvelope, OutcomeType, ResultMsg};
 use ipc_api::merkle::MerkleGen;
 use ipc_api::staking::{StakingChangeRequest, ValidatorInfo, ValidatorStakingInfo};
 use ipc_api::subnet::ConstructParams;
@@ -102,6 +103,22 @@ abigen!(
     ]"#,
 );
 
+// Accounts can't send general-purpose cross-net messages, they send calls through the
+// `IpcCaller` contract from the contracts SDK instead.
+abigen!(
+    IpcCaller,
+    r#"[
+        struct SubnetID { uint64 root; address[] route; }
+        struct FvmAddress { uint8 addrType; bytes payload; }
+        struct IPCAddress { SubnetID subnetId; FvmAddress rawAddress; }
+        struct IpcEnvelope { uint8 kind; uint64 localNonce; uint64 originalNonce; uint256 value; IPCAddress to; IPCAddress from; bytes message; }
+        struct CallMsg { bytes method; bytes params; }
+        function sendCall(IPCAddress to, CallMsg callMsg) external payable returns (IpcEnvelope envelope)
+        event CallSent(bytes32 indexed id, IpcEnvelope envelope)
+        event ReceiptReceived(bytes32 indexed id, uint8 outcome, bytes ret)
+    ]"#,
+);
+
 #[async_trait]
 impl TopDownFinalityQuery for EthSubnetManager {
     async fn genesis_epoch(&self, subnet_id: &SubnetID) -> Result<ChainEpoch> {
@@ -546,7 +563,7 @@ impl SubnetManager for EthSubnetManager {
         from: Address,
         to: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch> {
+    ) -> Result<(ChainEpoch, [u8; 32])> {
         self.ensure_same_gateway(&gateway_addr)?;
 
         let value = amount
@@ -574,7 +591,8 @@ impl SubnetManager for EthSubnetManager {
 
         let pending_tx = txn.send().await?;
         let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
-        block_number_from_receipt(receipt)
+        let id = cross_msg_id_from_receipt(receipt.as_ref(), self.ipc_contract_info.gateway_addr)?;
+        block_number_from_receipt(receipt).map(|epoch| (epoch, id))
     }
 
     /// Approves the `from` address to use up to `amount` tokens from `token_address`.
@@ -649,7 +667,7 @@ impl SubnetManager for EthSubnetManager {
         from: Address,
         to: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch> {
+    ) -> Result<(ChainEpoch, [u8; 32])> {
         self.ensure_same_gateway(&gateway_addr)?;
 
         let value = amount
@@ -670,7 +688,206 @@ impl SubnetManager for EthSubnetManager {
 
         let pending_tx = txn.send().await?;
         let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
-        block_number_from_receipt(receipt)
+        let id = cross_msg_id_from_receipt(receipt.as_ref(), self.ipc_contract_info.gateway_addr)?;
+        block_number_from_receipt(receipt).map(|epoch| (epoch, id))
+    }
+
+    async fn send_cross_call(
+        &self,
+        caller: Address,
+        from: Address,
+        to: IPCAddress,
+        call: CallMsg,
+        value: TokenAmount,
+    ) -> Result<(IpcEnvelope, ChainEpoch)> {
+        let caller = payload_to_evm_address(caller.payload())?;
+        tracing::info!(
+            "cross-net call to: {to:?} through caller contract: {caller:} with value: {value:}"
+        );
+
+        let to = gateway_messenger_facet::Ipcaddress::try_from(to)?;
+        let call = ethers::abi::Token::Tuple(vec![
+            ethers::abi::Token::Bytes(call.method),
+            ethers::abi::Token::Bytes(call.params),
+        ]);
+
+        let signer = Arc::new(self.get_signer_with_fee_estimator(&from)?);
+        let contract = IpcCaller::new(caller, signer.clone());
+        let mut txn = contract.send_call(
+            Tokenizable::from_token(to.into_token())?,
+            Tokenizable::from_token(call)?,
+        );
+        txn.tx.set_value(fil_to_eth_amount(&value)?);
+        let txn = extend_call_with_pending_block(txn).await?;
+
+        let pending_tx = txn.send().await?;
+        let receipt = pending_tx
+            .retries(TRANSACTION_RECEIPT_RETRIES)
+            .await?
+            .ok_or_else(|| {
+                anyhow!("txn sent to network, but receipt cannot be obtained, please check scanner")
+            })?;
+
+        let event = receipt
+            .logs
+            .iter()
+            .filter(|log| log.address == caller)
+            .find_map(|log| ethers::contract::parse_log::<CallSentFilter>(log.clone()).ok())
+            .ok_or_else(|| anyhow!("no call sent by the caller contract"))?;
+        let envelope =
+            gateway_messenger_facet::IpcEnvelope::from_token(event.envelope.into_token())?;
+        let envelope = IpcEnvelope::try_from(envelope)?;
+
+        block_number_from_receipt(Some(receipt)).map(|epoch| (envelope, epoch))
+    }
+
+    async fn cross_call_receipt(
+        &self,
+        caller: Address,
+        id: [u8; 32],
+        from_epoch: ChainEpoch,
+    ) -> Result<Option<ResultMsg>> {
+        let caller = payload_to_evm_address(caller.payload())?;
+        let contract = IpcCaller::new(caller, Arc::new(self.ipc_contract_info.provider.clone()));
+
+        let ev = contract
+            .event::<ReceiptReceivedFilter>()
+            .from_block(from_epoch as u64)
+            .topic1(H256::from(id))
+            .address(ValueOrArray::Value(caller));
+
+        match query_with_meta(ev, contract.client())
+            .await?
+            .into_iter()
+            .next()
+        {
+            Some((event, _)) => Ok(Some(ResultMsg {
+                id,
+                outcome: OutcomeType::try_from(event.outcome)?,
+                ret: event.ret.to_vec(),
+            })),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_top_down_msg(
+        &self,
+        subnet: &SubnetID,
+        id: [u8; 32],
+        from_epoch: ChainEpoch,
+    ) -> Result<Option<(IpcEnvelope, ChainEpoch)>> {
+        let gateway_contract = gateway_manager_facet::GatewayManagerFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        let ev = gateway_contract
+            .event::<lib_gateway::NewTopDownMessageFilter>()
+            .from_block(from_epoch as u64)
+            .topic1(contract_address_from_subnet(subnet)?)
+            .topic2(H256::from(id))
+            .address(ValueOrArray::Value(gateway_contract.address()));
+
+        match query_with_meta(ev, gateway_contract.client())
+            .await?
+            .into_iter()
+            .next()
+        {
+            Some((event, meta)) => Ok(Some((
+                IpcEnvelope::try_from(event.message)?,
+                meta.block_number.as_u64() as ChainEpoch,
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_bottom_up_msg(
+        &self,
+        id: [u8; 32],
+        from_epoch: ChainEpoch,
+    ) -> Result<Option<ChainEpoch>> {
+        let gateway_contract = gateway_manager_facet::GatewayManagerFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        let ev = gateway_contract
+            .event::<lib_gateway::QueuedBottomUpMessageFilter>()
+            .from_block(from_epoch as u64)
+            .topic1(H256::from(id))
+            .address(ValueOrArray::Value(gateway_contract.address()));
+
+        Ok(query_with_meta(ev, gateway_contract.client())
+            .await?
+            .into_iter()
+            .next()
+            .map(|(_, meta)| meta.block_number.as_u64() as ChainEpoch))
+    }
+
+    async fn bottom_up_batch_cuts(
+        &self,
+        from_epoch: ChainEpoch,
+        to_epoch: ChainEpoch,
+    ) -> Result<Vec<ChainEpoch>> {
+        let gateway_contract = gateway_manager_facet::GatewayManagerFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        let ev = gateway_contract
+            .event::<lib_gateway::NewBottomUpMsgBatchFilter>()
+            .from_block(from_epoch as u64)
+            .to_block(to_epoch as u64)
+            .address(ValueOrArray::Value(gateway_contract.address()));
+
+        Ok(query_with_meta(ev, gateway_contract.client())
+            .await?
+            .into_iter()
+            .map(|(event, _)| event.epoch.as_u64() as ChainEpoch)
+            .collect())
+    }
+
+    async fn applied_top_down_nonce_at(&self, height: ChainEpoch) -> Result<u64> {
+        let gateway_contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+        let nonce = gateway_contract
+            .applied_top_down_nonce()
+            .block(height as u64)
+            .call()
+            .await?;
+        Ok(nonce)
+    }
+
+    async fn applied_bottom_up_nonce_at(
+        &self,
+        subnet: &SubnetID,
+        height: ChainEpoch,
+    ) -> Result<u64> {
+        let gateway_contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+        let (exists, nonce) = gateway_contract
+            .get_applied_bottom_up_nonce(gateway_getter_facet::SubnetID::try_from(subnet)?)
+            .block(height as u64)
+            .call()
+            .await?;
+        if !exists {
+            return Err(anyhow!("subnet {subnet} does not exist"));
+        }
+        Ok(nonce)
+    }
+
+    async fn block_timestamp(&self, height: ChainEpoch) -> Result<u64> {
+        let block = self
+            .ipc_contract_info
+            .provider
+            .get_block(height as u64)
+            .await?
+            .ok_or_else(|| anyhow!("height {height} not found"))?;
+        Ok(block.timestamp.as_u64())
     }
 
     /// Send value between two addresses in a subnet
@@ -1257,6 +1474,18 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
         }))
     }
 
+    async fn checkpoint_quorum_reached(&self, height: ChainEpoch) -> Result<bool> {
+        let contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+        let info = contract
+            .get_checkpoint_info(U256::from(height))
+            .call()
+            .await?;
+        Ok(info.reached)
+    }
+
     async fn quorum_reached_events(&self, height: ChainEpoch) -> Result<Vec<QuorumReachedEvent>> {
         let contract = checkpointing_facet::CheckpointingFacet::new(
             self.ipc_contract_info.gateway_addr,
@@ -1290,6 +1519,38 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
             .as_u64();
         Ok(epoch as ChainEpoch)
     }
+
+    async fn postbox_msgs(&self) -> Result<Vec<([u8; 32], IpcEnvelope)>> {
+        let contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+        let mut msgs = vec![];
+        for cid in contract.postbox_msgs().call().await? {
+            let envelope = contract.postbox(cid).call().await?;
+            msgs.push((cid, IpcEnvelope::try_from(envelope)?));
+        }
+        Ok(msgs)
+    }
+
+    async fn propagate_postbox(&self, from: &Address, cids: &[[u8; 32]]) -> Result<ChainEpoch> {
+        tracing::info!(
+            "propagate {} postbox messages in evm gateway contract: {:}",
+            cids.len(),
+            self.ipc_contract_info.gateway_addr
+        );
+
+        let signer = Arc::new(self.get_signer_with_fee_estimator(from)?);
+        let contract = gateway_messenger_facet::GatewayMessengerFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            signer.clone(),
+        );
+        let txn = extend_call_with_pending_block(contract.propagate(cids.to_vec())).await?;
+
+        let pending_tx = txn.send().await?;
+        let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
+        block_number_from_receipt(receipt)
+    }
 }
 
 lazy_static!(
@@ -1496,6 +1757,30 @@ where
 }
 
 /// Get the block number from the transaction receipt
+/// Returns the tracing id of the cross-net message that a transaction committed in the gateway.
+fn cross_msg_id_from_receipt(
+    receipt: Option<&ethers::types::TransactionReceipt>,
+    gateway_addr: ethers::types::Address,
+) -> Result<[u8; 32]> {
+    let receipt = receipt.ok_or_else(|| {
+        anyhow!("txn sent to network, but receipt cannot be obtained, please check scanner")
+    })?;
+    receipt
+        .logs
+        .iter()
+        .filter(|log| log.address == gateway_addr)
+        .find_map(|log| {
+            match ethers::contract::parse_log::<lib_gateway::LibGatewayEvents>(log.clone()) {
+                Ok(lib_gateway::LibGatewayEvents::NewTopDownMessageFilter(event)) => Some(event.id),
+                Ok(lib_gateway::LibGatewayEvents::QueuedBottomUpMessageFilter(event)) => {
+                    Some(event.id)
+                }
+                _ => None,
+            }
+        })
+        .ok_or_else(|| anyhow!("no cross-net message committed by the txn"))
+}
+
 fn block_number_from_receipt(
     receipt: Option<ethers::types::TransactionReceipt>,
 ) -> Result<ChainEpoch> {
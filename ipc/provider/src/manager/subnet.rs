@@ -6,11 +6,12 @@ use async_trait::async_trait;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::{address::Address, econ::TokenAmount};
 use ipc_actors_abis::subnet_actor_activity_facet::ValidatorClaim;
+use ipc_api::address::IPCAddress;
 use ipc_api::checkpoint::{
     consensus::ValidatorData, BottomUpCheckpoint, BottomUpCheckpointBundle, QuorumReachedEvent,
     Signature,
 };
-use ipc_api::cross::IpcEnvelope;
+use ipc_api::cross::{CallMsg, IpcEnvelope, ResultMsg};
 use ipc_api::staking::{StakingChangeRequest, ValidatorInfo};
 use ipc_api::subnet::{Asset, ConstructParams, PermissionMode};
 use ipc_api::subnet_id::SubnetID;
@@ -76,7 +77,8 @@ pub trait SubnetManager:
     async fn claim_collateral(&self, subnet: SubnetID, from: Address) -> Result<()>;
 
     /// Fund injects new funds from an account of the parent chain to a subnet.
-    /// Returns the epoch that the fund is executed in the parent.
+    /// Returns the epoch that the fund is executed in the parent, and the tracing id
+    /// of the top-down message it commits.
     async fn fund(
         &self,
         subnet: SubnetID,
@@ -84,7 +86,7 @@ pub trait SubnetManager:
         from: Address,
         to: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch>;
+    ) -> Result<(ChainEpoch, [u8; 32])>;
 
     /// Sends funds to a specified subnet receiver using ERC20 tokens.
     /// This function locks the amount of ERC20 tokens into custody and then mints the supply in the specified subnet.
@@ -131,14 +133,77 @@ pub trait SubnetManager:
     ) -> Result<ChainEpoch>;
 
     /// Release creates a new check message to release funds in parent chain
-    /// Returns the epoch that the released is executed in the child.
+    /// Returns the epoch that the released is executed in the child, and the tracing id
+    /// of the bottom-up message it queues.
     async fn release(
         &self,
         gateway_addr: Address,
         from: Address,
         to: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch>;
+    ) -> Result<(ChainEpoch, [u8; 32])>;
+
+    /// Sends a `Call` to a contract in another subnet through the `caller` contract owned by `from`.
+    /// The gateway only accepts calls from contracts, so accounts have to relay them through
+    /// a deployed `IpcCaller` contract, which also gets the receipt back. The target `to` gets
+    /// the envelope through `IIpcHandler.handleIpcMessage`, so it must implement that interface.
+    /// Returns the committed envelope and the epoch that the call is committed in.
+    async fn send_cross_call(
+        &self,
+        caller: Address,
+        from: Address,
+        to: IPCAddress,
+        call: CallMsg,
+        value: TokenAmount,
+    ) -> Result<(IpcEnvelope, ChainEpoch)>;
+
+    /// Looks up the receipt of the call with the given tracing id,
+    /// delivered to the `caller` contract from `from_epoch` onwards.
+    async fn cross_call_receipt(
+        &self,
+        caller: Address,
+        id: [u8; 32],
+        from_epoch: ChainEpoch,
+    ) -> Result<Option<ResultMsg>>;
+
+    /// Looks up a top-down message for `subnet` committed in this subnet by its tracing id,
+    /// from `from_epoch` onwards. Returns the envelope and the epoch it was committed in.
+    async fn find_top_down_msg(
+        &self,
+        subnet: &SubnetID,
+        id: [u8; 32],
+        from_epoch: ChainEpoch,
+    ) -> Result<Option<(IpcEnvelope, ChainEpoch)>>;
+
+    /// Looks up the epoch that a bottom-up message was queued in by its tracing id,
+    /// from `from_epoch` onwards.
+    async fn find_bottom_up_msg(
+        &self,
+        id: [u8; 32],
+        from_epoch: ChainEpoch,
+    ) -> Result<Option<ChainEpoch>>;
+
+    /// Returns the heights in `[from_epoch, to_epoch]` at which a full batch of bottom-up
+    /// messages was cut before the end of its checkpoint period.
+    async fn bottom_up_batch_cuts(
+        &self,
+        from_epoch: ChainEpoch,
+        to_epoch: ChainEpoch,
+    ) -> Result<Vec<ChainEpoch>>;
+
+    /// The nonce of the next top-down message to be applied in this subnet, as of `height`.
+    async fn applied_top_down_nonce_at(&self, height: ChainEpoch) -> Result<u64>;
+
+    /// The nonce of the next bottom-up message from the child `subnet` to be applied
+    /// in this subnet, as of `height`.
+    async fn applied_bottom_up_nonce_at(
+        &self,
+        subnet: &SubnetID,
+        height: ChainEpoch,
+    ) -> Result<u64>;
+
+    /// The timestamp of the block at `height`, in seconds since the epoch.
+    async fn block_timestamp(&self, height: ChainEpoch) -> Result<u64>;
 
     /// Send value between two addresses in a subnet
     async fn send_value(&self, from: Address, to: Address, amount: TokenAmount) -> Result<()>;
@@ -209,13 +274,13 @@ pub struct SubnetGenesisInfo {
 
 /// The generic payload that returns the block hash of the data returning block with the actual
 /// data payload.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct TopDownQueryPayload<T> {
     pub value: T,
     pub block_hash: Vec<u8>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, PartialEq, Eq)]
 pub struct GetBlockHashResult {
     pub parent_block_hash: Vec<u8>,
     pub block_hash: Vec<u8>,
@@ -269,10 +334,19 @@ pub trait BottomUpCheckpointRelayer: Send + Sync {
         &self,
         height: ChainEpoch,
     ) -> Result<Option<BottomUpCheckpointBundle>>;
+    /// Whether the validators reached a quorum of signatures on the checkpoint at a specific height.
+    async fn checkpoint_quorum_reached(&self, height: ChainEpoch) -> Result<bool>;
     /// Queries the signature quorum reached events at target height.
     async fn quorum_reached_events(&self, height: ChainEpoch) -> Result<Vec<QuorumReachedEvent>>;
     /// Get the current epoch in the current subnet
     async fn current_epoch(&self) -> Result<ChainEpoch>;
+    /// The cross-net messages waiting in the postbox of the gateway to be propagated to the next
+    /// subnet in their route, along with the cids they are kept under.
+    async fn postbox_msgs(&self) -> Result<Vec<([u8; 32], IpcEnvelope)>>;
+    /// Propagates the messages with the given cids from the postbox of the gateway to the next
+    /// subnet in their route, skipping those that are no longer in the postbox.
+    /// Returns the epoch that the propagation is executed in.
+    async fn propagate_postbox(&self, from: &Address, cids: &[[u8; 32]]) -> Result<ChainEpoch>;
 }
 
 /// The validator reward related functions, such as check reward and claim reward for mining blocks
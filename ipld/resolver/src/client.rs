@@ -10,7 +10,7 @@ use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::oneshot;
 
 use crate::{
-    service::{Request, ResolveReadRequestResult, ResolveResult},
+    service::{Request, ResolveChunkProofResult, ResolveReadRequestResult, ResolveResult},
     vote_record::SignedVoteRecord,
 };
 
@@ -145,9 +145,17 @@ pub trait ResolverIrohReadRequest {
     async fn close_read_request(
         &self,
         hash: Hash,
-        offset: u32,
+        offset: u64,
         len: u32,
     ) -> anyhow::Result<ResolveReadRequestResult>;
+
+    /// Send a hash and chunk index for getting a range proof of the chunk from iroh,
+    /// await its completion, then return the result, to be inspected by the caller.
+    async fn read_chunk_proof(
+        &self,
+        hash: Hash,
+        chunk: u64,
+    ) -> anyhow::Result<ResolveChunkProofResult>;
 }
 
 #[async_trait]
@@ -158,7 +166,7 @@ where
     async fn close_read_request(
         &self,
         hash: Hash,
-        offset: u32,
+        offset: u64,
         len: u32,
     ) -> anyhow::Result<ResolveReadRequestResult> {
         let (tx, rx) = oneshot::channel();
@@ -167,4 +175,16 @@ where
         let res = rx.await?;
         Ok(res)
     }
+
+    async fn read_chunk_proof(
+        &self,
+        hash: Hash,
+        chunk: u64,
+    ) -> anyhow::Result<ResolveChunkProofResult> {
+        let (tx, rx) = oneshot::channel();
+        let req = Request::ResolveIrohChunkProof(hash, chunk, tx);
+        self.send_request(req)?;
+        let res = rx.await?;
+        Ok(res)
+    }
 }
@@ -21,6 +21,6 @@ pub mod missing_blocks;
 
 pub use behaviour::{ContentConfig, DiscoveryConfig, MembershipConfig, NetworkConfig};
 pub use client::{Client, Resolver, ResolverIroh, ResolverIrohReadRequest};
-pub use service::{Config, ConnectionConfig, Event, NoKnownPeers, Service};
+pub use service::{ChunkRangeProof, Config, ConnectionConfig, Event, NoKnownPeers, Service};
 pub use timestamp::Timestamp;
 pub use vote_record::{ValidatorKey, VoteRecord};
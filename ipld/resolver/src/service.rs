@@ -11,14 +11,19 @@ use crate::behaviour::{
 use crate::client::Client;
 use crate::observe;
 use crate::vote_record::{SignedVoteRecord, VoteRecord};
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
+use bao_tree::io::BaoContentItem;
+use bao_tree::{ChunkNum, ChunkRanges};
 use bloom::{BloomFilter, ASMS};
 use ipc_api::subnet_id::SubnetID;
 use ipc_observability::emit;
+use iroh::blobs::get::fsm::{BlobContentNext, ConnectedNext, EndBlobNext};
+use iroh::blobs::protocol::{GetRequest, RangeSpecSeq};
 use iroh::blobs::Hash;
 use iroh::client::blobs::ReadAtLen;
 use iroh::client::Iroh;
 use iroh::net::NodeAddr;
+use iroh_manager::tags::{stored_tag, TagDeletionQueue};
 use iroh_manager::IrohManager;
 use libipld::store::StoreParams;
 use libipld::Cid;
@@ -49,12 +54,31 @@ pub type ResolveResult = anyhow::Result<()>;
 /// Result of attempting to resolve a read request.
 pub type ResolveReadRequestResult = anyhow::Result<bytes::Bytes>;
 
+/// Result of attempting to resolve a chunk proof request.
+pub type ResolveChunkProofResult = anyhow::Result<ChunkRangeProof>;
+
 /// Channel to complete the results with.
 type ResponseChannel = Sender<ResolveResult>;
 
 /// Channel to complete the read request with.
 type ReadRequestResponseChannel = Sender<ResolveReadRequestResult>;
 
+/// Channel to complete the chunk proof request with.
+type ChunkProofResponseChannel = Sender<ResolveChunkProofResult>;
+
+/// A BLAKE3 range proof for a single chunk of a blob, as served by the local iroh node
+/// from the blob's bao outboard.
+#[derive(Debug, Clone)]
+pub struct ChunkRangeProof {
+    /// The chaining value pairs of the parent nodes on the path to the leaf that contains
+    /// the chunk, ordered from the root down.
+    pub parents: Vec<([u8; 32], [u8; 32])>,
+    /// The offset of the leaf in the blob.
+    pub offset: u64,
+    /// The leaf data, i.e., the chunk group that contains the chunk.
+    pub data: bytes::Bytes,
+}
+
 /// State of a query. The fallback peers can be used
 /// if the current attempt fails.
 struct Query {
@@ -111,7 +135,8 @@ pub(crate) enum Request<V> {
     UnpinSubnet(SubnetID),
     Resolve(Cid, SubnetID, ResponseChannel),
     ResolveIroh(Hash, NodeAddr, ResponseChannel),
-    ResolveIrohRead(Hash, u32, u32, ReadRequestResponseChannel),
+    ResolveIrohRead(Hash, u64, u32, ReadRequestResponseChannel),
+    ResolveIrohChunkProof(Hash, u64, ChunkProofResponseChannel),
     RateLimitUsed(PeerId, usize),
     UpdateRateLimit(u32),
 }
@@ -474,6 +499,9 @@ where
             Request::ResolveIrohRead(hash, offset, len, response_channel) => {
                 self.start_iroh_read_query(hash, offset, len, response_channel)
             }
+            Request::ResolveIrohChunkProof(hash, chunk, response_channel) => {
+                self.start_iroh_chunk_proof_query(hash, chunk, response_channel)
+            }
             Request::RateLimitUsed(peer_id, bytes) => {
                 self.content_mut().rate_limit_used(peer_id, bytes)
             }
@@ -546,7 +574,7 @@ where
     fn start_iroh_read_query(
         &mut self,
         hash: Hash,
-        offset: u32,
+        offset: u64,
         len: u32,
         response_channel: ReadRequestResponseChannel,
     ) {
@@ -568,6 +596,28 @@ where
         });
     }
 
+    /// Start a chunk proof request resolution using iroh.
+    fn start_iroh_chunk_proof_query(
+        &mut self,
+        hash: Hash,
+        chunk: u64,
+        response_channel: ChunkProofResponseChannel,
+    ) {
+        let mut iroh = self.iroh.clone();
+        tokio::spawn(async move {
+            match iroh.client().await {
+                Ok(client) => {
+                    let res = read_chunk_proof(client, hash, chunk).await;
+                    send_chunk_proof_result(response_channel, res);
+                }
+                Err(e) => warn!(
+                    "cannot resolve chunk proof request {}; failed to create iroh client ({})",
+                    hash, e
+                ),
+            }
+        });
+    }
+
     /// Handle the results from a resolve attempt. If it succeeded, notify the
     /// listener. Otherwise if we have fallback peers to try, start another
     /// query and send the result to them. By default these are the peers
@@ -642,6 +692,12 @@ fn send_read_request_result(
     }
 }
 
+fn send_chunk_proof_result(tx: ChunkProofResponseChannel, res: ResolveChunkProofResult) {
+    if tx.send(res).is_err() {
+        error!("error sending chunk proof result; listener closed")
+    }
+}
+
 /// Builds the transport stack that libp2p will communicate over.
 ///
 /// Based on the equivalent in Forest.
@@ -670,9 +726,15 @@ pub fn build_transport(local_key: Keypair) -> Boxed<(PeerId, StreamMuxerBox)> {
 }
 
 async fn download_blob(iroh: Iroh, hash: Hash, node_addr: NodeAddr) -> anyhow::Result<()> {
-    // Use an explicit tag so we can keep track of it
-    // TODO: this needs to be tagged with a "user id"
-    let tag = iroh::blobs::Tag(format!("stored-{hash}").into());
+    // A deletion of the tag may still be queued from an earlier subscription to the same blob,
+    // which must not remove the tag we are about to set.
+    if let Some(queue) = TagDeletionQueue::from_env() {
+        queue?.cancel(&hash).await?;
+    }
+
+    // Use an explicit tag so we can keep track of it.
+    // The tag is reference counted by the blobs actor, and removed with the last subscription.
+    let tag = stored_tag(&hash);
     let res = iroh
         .blobs()
         .download_with_opts(
@@ -690,19 +752,69 @@ async fn download_blob(iroh: Iroh, hash: Hash, node_addr: NodeAddr) -> anyhow::R
     debug!("downloaded blob {}: {:?}", hash, res);
 
     // Delete the temporary tag (this might fail as not all nodes will have one).
-    // TODO: this needs to be tagged with a "user id"
     let tag = iroh::blobs::Tag(format!("temp-{hash}").into());
     iroh.tags().delete(tag).await.ok();
 
     Ok(())
 }
 
-async fn read_blob(iroh: Iroh, hash: Hash, offset: u32, len: u32) -> anyhow::Result<bytes::Bytes> {
+async fn read_blob(iroh: Iroh, hash: Hash, offset: u64, len: u32) -> anyhow::Result<bytes::Bytes> {
     let len = ReadAtLen::AtMost(len as u64);
-    let res = iroh
-        .blobs()
-        .read_at_to_bytes(hash, offset as u64, len)
-        .await?;
+    let res = iroh.blobs().read_at_to_bytes(hash, offset, len).await?;
     debug!("read blob {}: {:?}", hash, res);
     Ok(res)
 }
+
+/// Requests a single chunk of a blob from the local iroh node over the blobs protocol.
+///
+/// The node answers with a verified range, i.e., the parent nodes on the path to the chunk,
+/// which it takes from the blob's outboard, and the data of the leaf that contains the chunk.
+/// This proves the chunk without reading the rest of the blob.
+async fn read_chunk_proof(iroh: Iroh, hash: Hash, chunk: u64) -> anyhow::Result<ChunkRangeProof> {
+    let node_addr = iroh.net().node_addr().await?;
+    let endpoint = iroh::net::Endpoint::builder().bind().await?;
+    let connection = endpoint
+        .connect(node_addr, iroh::blobs::protocol::ALPN)
+        .await?;
+
+    let ranges = ChunkRanges::from(ChunkNum(chunk)..ChunkNum(chunk + 1));
+    let request = GetRequest::new(hash, RangeSpecSeq::from_ranges([ranges]));
+    let connected = iroh::blobs::get::fsm::start(connection, request)
+        .next()
+        .await?;
+    let ConnectedNext::StartRoot(start) = connected.next().await? else {
+        bail!("unexpected response to chunk request for blob {}", hash);
+    };
+    let (mut content, _) = start.next().next().await?;
+
+    let mut parents = Vec::new();
+    let mut leaf = None;
+    let end = loop {
+        match content.next().await {
+            BlobContentNext::More((next, item)) => {
+                match item? {
+                    BaoContentItem::Parent(parent) => {
+                        parents.push((*parent.pair.0.as_bytes(), *parent.pair.1.as_bytes()))
+                    }
+                    BaoContentItem::Leaf(data) => leaf = Some((data.offset, data.data)),
+                }
+                content = next;
+            }
+            BlobContentNext::Done(end) => break end,
+        }
+    };
+    if let EndBlobNext::Closing(closing) = end.next() {
+        closing.next().await?;
+    }
+    endpoint.close(0u32.into(), b"").await.ok();
+
+    let Some((offset, data)) = leaf else {
+        bail!("no data received for chunk {} of blob {}", chunk, hash);
+    };
+    debug!("read chunk proof {} of blob {}", chunk, hash);
+    Ok(ChunkRangeProof {
+        parents,
+        offset,
+        data,
+    })
+}
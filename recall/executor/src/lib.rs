@@ -29,7 +29,7 @@ use fvm_shared::{
     event::StampedEvent,
     message::Message,
     receipt::Receipt,
-    ActorID, IPLD_RAW, METHOD_SEND,
+    ActorID, MethodNum, IPLD_RAW, METHOD_SEND,
 };
 use num_traits::Zero;
 use tracing::debug;
@@ -457,7 +457,8 @@ where
         sender_state.sequence += 1;
 
         // Get sender's gas allowance for gas fees.
-        let gas_allowance = self.get_gas_allowance(msg.from)?;
+        // The sponsored amount is already limited by the sponsor's policy for this message.
+        let gas_allowance = self.get_gas_allowance(msg.from, msg.to, msg.method_num)?;
 
         // Pre-resolve the message sponsor's address, if known.
         let sponsor_id = if let Some(sponsor) = gas_allowance.sponsor {
@@ -701,9 +702,18 @@ where
         )
     }
 
-    /// Returns the gas allowance for the sender.
-    fn get_gas_allowance(&mut self, from: Address) -> Result<GasAllowance> {
-        let params = RawBytes::serialize(GetGasAllowanceParams(from))?;
+    /// Returns the gas allowance for the sender of a message to `to` calling `method_num`.
+    fn get_gas_allowance(
+        &mut self,
+        from: Address,
+        to: Address,
+        method_num: MethodNum,
+    ) -> Result<GasAllowance> {
+        let params = RawBytes::serialize(GetGasAllowanceParams {
+            from,
+            to,
+            method_num,
+        })?;
 
         let msg = Message {
             from: SYSTEM_ACTOR_ADDR,
@@ -7,6 +7,8 @@ use std::net::ToSocketAddrs;
 use anyhow::anyhow;
 use iroh::client::Iroh;
 
+pub mod tags;
+
 #[derive(Clone, Debug)]
 pub struct IrohManager {
     addr: Option<String>,
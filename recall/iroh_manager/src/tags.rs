@@ -0,0 +1,245 @@
+// Copyright 2025 Recall Contributors
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Tags that keep subscribed blobs alive in the local iroh store.
+//!
+//! A blob downloaded for the blobs actor is kept under a single tag per hash. The tag is
+//! reference counted by the actor: it's only removed once the last subscription to the blob
+//! is gone, so blobs shared by several subscribers survive the deletion of any one of them.
+//!
+//! Blobs rebuilt from their parities by the objects service are kept under a separate tag,
+//! which is removed along with the stored tag.
+//!
+//! Removing a tag is not part of consensus, so failed removals are kept in a durable queue
+//! and retried until they succeed, even across restarts. A blob may be subscribed again while
+//! its removal is queued, so queued removals are serialized with the downloads that cancel them.
+//!
+//! The objects service also keeps blobs under temporary tags while they are uploaded in parts
+//! or rebuilt, which it removes once it's done with them.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use iroh::blobs::{Hash, Tag};
+use iroh::client::Iroh;
+use tokio::sync::Mutex;
+
+/// Prefix of the tags under which subscribed blobs are stored.
+pub const STORED_TAG_PREFIX: &str = "stored-";
+
+/// Prefix of the tags under which repaired blobs are stored.
+pub const REPAIRED_TAG_PREFIX: &str = "repaired-";
+
+/// Prefix of the tags under which the parts of multipart uploads are kept until the upload ends.
+pub const UPLOAD_PART_TAG_PREFIX: &str = "temp-upload-";
+
+/// Prefix of the tags under which blobs are rebuilt until they have been verified.
+pub const REPAIRING_TAG_PREFIX: &str = "temp-repair-";
+
+/// Environment variable with the directory of the tag deletion queue.
+pub const ENV_TAG_QUEUE_DIR: &str = "IROH_TAG_QUEUE_DIR";
+
+/// Serializes the deletion of queued tags with the cancellation of queued deletions.
+static TAG_DELETION_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Returns the tag under which a subscribed blob is stored.
+pub fn stored_tag(hash: &Hash) -> Tag {
+    Tag(format!("{STORED_TAG_PREFIX}{hash}").into())
+}
+
+/// Returns the tag under which a repaired blob is stored.
+///
+/// The stored tag may have been lost along with the blob, so repaired blobs get their own.
+pub fn repaired_tag(hash: &Hash) -> Tag {
+    Tag(format!("{REPAIRED_TAG_PREFIX}{hash}").into())
+}
+
+/// Deletes all the tags which keep a blob alive.
+///
+/// Deleting the tags will trigger deletion of the blob if they were the last references.
+pub async fn delete_blob_tags(iroh: &Iroh, hash: &Hash) -> anyhow::Result<()> {
+    for tag in [stored_tag(hash), repaired_tag(hash)] {
+        iroh.tags().delete(tag).await?;
+    }
+    Ok(())
+}
+
+/// Returns the hash of a blob stored under `tag`, if it's a stored blob tag.
+pub fn parse_stored_tag(tag: &Tag) -> Option<Hash> {
+    parse_tag(tag, STORED_TAG_PREFIX)
+}
+
+/// Returns the hash of a blob stored under `tag`, if it's a repaired blob tag.
+pub fn parse_repaired_tag(tag: &Tag) -> Option<Hash> {
+    parse_tag(tag, REPAIRED_TAG_PREFIX)
+}
+
+fn parse_tag(tag: &Tag, prefix: &str) -> Option<Hash> {
+    std::str::from_utf8(&tag.0)
+        .ok()?
+        .strip_prefix(prefix)
+        .and_then(|hash| Hash::from_str(hash).ok())
+}
+
+/// A durable queue of stored blob tags to delete from iroh.
+///
+/// Each pending deletion is an empty file named after the blob hash,
+/// so pushing the same hash twice is a no-op.
+#[derive(Clone, Debug)]
+pub struct TagDeletionQueue {
+    dir: PathBuf,
+}
+
+impl TagDeletionQueue {
+    /// Opens the queue in `dir`, creating the directory if needed.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Opens the queue in the directory set in [`ENV_TAG_QUEUE_DIR`], if any.
+    pub fn from_env() -> Option<io::Result<Self>> {
+        std::env::var(ENV_TAG_QUEUE_DIR).ok().map(Self::open)
+    }
+
+    /// Adds a blob whose tag should be deleted.
+    pub fn push(&self, hash: &Hash) -> io::Result<()> {
+        fs::write(self.path(hash), [])
+    }
+
+    /// Cancels the pending deletion of a blob's tags, before the blob is stored again.
+    ///
+    /// Waits for a deletion of the tags that is already underway, so that it can't remove
+    /// the tag set when storing the blob.
+    pub async fn cancel(&self, hash: &Hash) -> io::Result<()> {
+        let _guard = TAG_DELETION_LOCK.lock().await;
+        self.remove(hash)
+    }
+
+    /// Removes a blob from the queue, e.g., once its tag is deleted.
+    pub fn remove(&self, hash: &Hash) -> io::Result<()> {
+        match fs::remove_file(self.path(hash)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the blobs whose tags are pending deletion.
+    pub fn pending(&self) -> io::Result<Vec<Hash>> {
+        let mut hashes = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let name = entry?.file_name();
+            match name.to_str().map(Hash::from_str) {
+                Some(Ok(hash)) => hashes.push(hash),
+                _ => tracing::warn!(?name, "ignoring unexpected file in tag deletion queue"),
+            }
+        }
+        Ok(hashes)
+    }
+
+    /// Tries to delete the tags of all pending blobs from iroh.
+    ///
+    /// Blobs whose tags could not be deleted stay in the queue.
+    /// Returns the number of blobs still pending.
+    pub async fn process(&self, iroh: &Iroh) -> io::Result<usize> {
+        let mut remaining = 0;
+        for hash in self.pending()? {
+            let _guard = TAG_DELETION_LOCK.lock().await;
+            // The deletion may have been cancelled since the queue was read
+            if !self.path(&hash).exists() {
+                continue;
+            }
+            match delete_blob_tags(iroh, &hash).await {
+                Ok(_) => {
+                    self.remove(&hash)?;
+                    tracing::debug!(hash = ?hash, "removed content from Iroh");
+                }
+                Err(e) => {
+                    remaining += 1;
+                    tracing::warn!(hash = ?hash, error = e.to_string(), "deleting tags from Iroh failed; will retry");
+                }
+            }
+        }
+        Ok(remaining)
+    }
+
+    fn path(&self, hash: &Hash) -> PathBuf {
+        self.dir.join(hash.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+
+    #[test]
+    fn test_stored_tag() {
+        let hash = Hash::new(b"blob");
+        assert_eq!(parse_stored_tag(&stored_tag(&hash)), Some(hash));
+        assert_eq!(parse_stored_tag(&Tag(format!("temp-{hash}").into())), None);
+        assert_eq!(parse_stored_tag(&Tag("stored-invalid".into())), None);
+        assert_eq!(parse_stored_tag(&repaired_tag(&hash)), None);
+        assert_eq!(parse_repaired_tag(&repaired_tag(&hash)), Some(hash));
+        assert_eq!(parse_repaired_tag(&stored_tag(&hash)), None);
+    }
+
+    #[test]
+    fn test_tag_deletion_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = TagDeletionQueue::open(dir.path()).unwrap();
+        let (a, b) = (Hash::new(b"a"), Hash::new(b"b"));
+        queue.push(&a).unwrap();
+        queue.push(&b).unwrap();
+        queue.push(&a).unwrap();
+        let mut pending = queue.pending().unwrap();
+        pending.sort();
+        let mut expected = vec![a, b];
+        expected.sort();
+        assert_eq!(pending, expected);
+
+        // The queue survives reopening
+        let queue = TagDeletionQueue::open(dir.path()).unwrap();
+        queue.remove(&a).unwrap();
+        queue.remove(&a).unwrap();
+        assert_eq!(queue.pending().unwrap(), vec![b]);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_tag_deletion() {
+        let iroh = iroh::node::Node::memory().spawn().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let queue = TagDeletionQueue::open(dir.path()).unwrap();
+        let mut hashes = Vec::new();
+        for data in [&b"a"[..], &b"b"[..]] {
+            let hash = Hash::new(data);
+            iroh.blobs()
+                .add_bytes_named(data, stored_tag(&hash))
+                .await
+                .unwrap();
+            queue.push(&hash).unwrap();
+            hashes.push(hash);
+        }
+        let a = hashes[0];
+
+        // The blob is stored again before the deletion goes through
+        queue.cancel(&a).await.unwrap();
+        assert_eq!(queue.process(iroh.client()).await.unwrap(), 0);
+        assert!(queue.pending().unwrap().is_empty());
+
+        let tags: Vec<_> = iroh
+            .tags()
+            .list()
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        let tags: Vec<_> = tags.into_iter().map(|tag| tag.name).collect();
+        assert_eq!(tags, vec![stored_tag(&a)]);
+    }
+}
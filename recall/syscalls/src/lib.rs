@@ -2,12 +2,14 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::sync::Arc;
+use std::sync::{Arc, Once};
+use std::time::Duration;
 
 use fvm::kernel::{ExecutionError, Result, SyscallError};
 use fvm::syscalls::Context;
 use fvm_shared::error::ErrorNumber;
 use iroh::blobs::Hash;
+use iroh_manager::tags::{delete_blob_tags, TagDeletionQueue};
 use iroh_manager::IrohManager;
 use once_cell::sync::Lazy;
 use recall_kernel_ops::RecallOps;
@@ -22,35 +24,96 @@ static IROH_INSTANCE: Lazy<Arc<Mutex<IrohManager>>> = Lazy::new(|| {
     Arc::new(Mutex::new(IrohManager::from_addr(iroh_addr)))
 });
 
+/// Interval between retries of failed tag deletions.
+const TAG_DELETION_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+static TAG_DELETION_QUEUE: Lazy<Option<TagDeletionQueue>> =
+    Lazy::new(|| match TagDeletionQueue::from_env()? {
+        Ok(queue) => Some(queue),
+        Err(e) => {
+            tracing::error!(error = e.to_string(), "failed to open tag deletion queue");
+            None
+        }
+    });
+static TAG_DELETION_RETRIES: Once = Once::new();
+
 fn hash_source(bytes: &[u8]) -> Result<[u8; 32]> {
     bytes
         .try_into()
         .map_err(|e| ExecutionError::Syscall(SyscallError::new(ErrorNumber::IllegalArgument, e)))
 }
 
+/// Removes the local iroh tags of a blob.
+///
+/// The blobs actor only calls this once the last subscription to the blob is gone.
+/// If a deletion queue is configured, the deletion is retried until it succeeds.
 pub fn hash_rm(context: Context<'_, impl RecallOps>, hash_offset: u32) -> Result<()> {
     let hash_bytes = context.memory.try_slice(hash_offset, 32)?;
     let hash = Hash::from_bytes(hash_source(hash_bytes)?);
     let iroh = IROH_INSTANCE.clone();
 
+    let Some(queue) = TAG_DELETION_QUEUE.as_ref() else {
+        // Don't block the chain with this.
+        spawn(delete_tag(iroh, hash));
+        return Ok(());
+    };
+    if let Err(e) = queue.push(&hash) {
+        tracing::error!(hash = ?hash, error = e.to_string(), "failed to queue tag deletion");
+        spawn(delete_tag(iroh, hash));
+        return Ok(());
+    }
+    TAG_DELETION_RETRIES.call_once(|| {
+        spawn(retry_tag_deletions(iroh.clone(), queue.clone()));
+    });
+
     // Don't block the chain with this.
+    let queue = queue.clone();
     spawn(async move {
-        let iroh_client = match iroh.lock().await.client().await {
-            Ok(client) => client,
-            Err(e) => {
-                tracing::error!(hash = ?hash, error = e.to_string(), "failed to initialize Iroh client");
-                return;
-            }
-        };
-        // Deleting the tag will trigger deletion of the blob if it was the last reference.
-        // TODO: this needs to be tagged with a "user id"
-        let tag = iroh::blobs::Tag(format!("stored-{hash}").into());
-        match iroh_client.tags().delete(tag.clone()).await {
-            Ok(_) => tracing::debug!(tag = ?tag, hash = ?hash, "removed content from Iroh"),
-            Err(e) => {
-                tracing::warn!(tag = ?tag, hash = ?hash, error = e.to_string(), "deleting tag from Iroh failed");
-            }
-        }
+        process_tag_deletions(&iroh, &queue).await;
     });
     Ok(())
 }
+
+/// Deletes the tags of a blob once, without retries.
+async fn delete_tag(iroh: Arc<Mutex<IrohManager>>, hash: Hash) {
+    let iroh_client = match iroh.lock().await.client().await {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!(hash = ?hash, error = e.to_string(), "failed to initialize Iroh client");
+            return;
+        }
+    };
+    match delete_blob_tags(&iroh_client, &hash).await {
+        Ok(_) => tracing::debug!(hash = ?hash, "removed content from Iroh"),
+        Err(e) => {
+            tracing::warn!(hash = ?hash, error = e.to_string(), "deleting tags from Iroh failed");
+        }
+    }
+}
+
+/// Periodically retries the tag deletions that are still queued, including any left over
+/// from a previous run.
+async fn retry_tag_deletions(iroh: Arc<Mutex<IrohManager>>, queue: TagDeletionQueue) {
+    let mut interval = tokio::time::interval(TAG_DELETION_RETRY_INTERVAL);
+    loop {
+        interval.tick().await;
+        process_tag_deletions(&iroh, &queue).await;
+    }
+}
+
+async fn process_tag_deletions(iroh: &Arc<Mutex<IrohManager>>, queue: &TagDeletionQueue) {
+    let iroh_client = match iroh.lock().await.client().await {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!(error = e.to_string(), "failed to initialize Iroh client");
+            return;
+        }
+    };
+    match queue.process(&iroh_client).await {
+        Ok(0) => {}
+        Ok(remaining) => tracing::debug!(remaining, "tag deletions pending retry"),
+        Err(e) => tracing::error!(
+            error = e.to_string(),
+            "failed to process tag deletion queue"
+        ),
+    }
+}